@@ -0,0 +1,354 @@
+//! 命令行模式（`cc-switch <子命令>`）
+//!
+//! 与 GUI 共用同一套供应商 / 配置 / 代理逻辑与数据目录，便于脚本或高级用户在不打开应用的情况下切换供应商：
+//!
+//! ```text
+//! cc-switch list    [--app <claude|codex|gemini>]          列出供应商（* 为当前）
+//! cc-switch current [--app <...>]                          显示当前供应商
+//! cc-switch use <名称或 ID> [--app <...>]                  切换供应商
+//! cc-switch test [<名称或 ID>] [--app <...>]               流式健康检查（默认当前供应商）
+//! cc-switch proxy start                                    前台运行代理（同 `--daemon`）
+//! cc-switch proxy stop                                     停止 `proxy start` / `--daemon` 启动的代理
+//! ```
+//!
+//! `--app` 默认为 claude。代理运行时（GUI 或守护进程），`use` 通过本机控制接口交由代理进程切换：
+//! 接管模式下热切换而不改写 Live 配置，GUI 的托盘与界面同步刷新。`proxy stop` 同样通过控制接口
+//! 让守护进程正常退出（恢复 Live 配置），不会停止 GUI 运行的代理。
+
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::provider::Provider;
+use crate::proxy::{control, discovery};
+use crate::services::stream_check::StreamCheckService;
+use crate::services::ProviderService;
+use crate::store::AppState;
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 支持的子命令（第一个参数命中时进入命令行模式）
+const SUBCOMMANDS: &[&str] = &["list", "current", "use", "test", "proxy", "help"];
+
+const USAGE: &str = "\
+用法: cc-switch <子命令> [参数]
+
+  list    [--app <claude|codex|gemini>]     列出供应商（* 为当前）
+  current [--app <...>]                     显示当前供应商
+  use <名称或 ID> [--app <...>]             切换供应商
+  test [<名称或 ID>] [--app <...>]          流式健康检查（默认当前供应商）
+  proxy start                               前台运行代理（同 --daemon）
+  proxy stop                                停止 proxy start / --daemon 启动的代理";
+
+/// 解析后的命令行
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    List,
+    Current,
+    Use(String),
+    Test(Option<String>),
+    ProxyStart,
+    ProxyStop,
+    Help,
+}
+
+/// 命令行参数是否为子命令调用（第一个参数为程序路径，忽略）
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.get(1)
+        .is_some_and(|arg| SUBCOMMANDS.contains(&arg.as_str()))
+}
+
+/// 解析子命令与 `--app` 选项
+fn parse(args: &[String]) -> Result<(Command, AppType), String> {
+    let mut app_type = AppType::Claude;
+    let mut positional = Vec::new();
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--app" => {
+                let value = iter.next().ok_or("--app 需要指定应用")?;
+                app_type = AppType::from_str(value).map_err(|e| e.to_string())?;
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let command = match positional.as_slice() {
+        ["list"] => Command::List,
+        ["current"] => Command::Current,
+        ["use", target] => Command::Use(target.to_string()),
+        ["use"] => return Err("use 需要指定供应商名称或 ID".into()),
+        ["test"] => Command::Test(None),
+        ["test", target] => Command::Test(Some(target.to_string())),
+        ["proxy", "start"] => Command::ProxyStart,
+        ["proxy", "stop"] => Command::ProxyStop,
+        ["help"] => Command::Help,
+        _ => return Err(format!("无法识别的参数: {}", positional.join(" "))),
+    };
+    Ok((command, app_type))
+}
+
+/// 按 ID 或名称查找供应商（ID 精确匹配优先，名称忽略大小写且须唯一）
+pub(crate) fn find_provider<'a>(
+    providers: &'a IndexMap<String, Provider>,
+    target: &str,
+) -> Result<&'a Provider, String> {
+    if let Some(provider) = providers.get(target) {
+        return Ok(provider);
+    }
+    let mut matches = providers
+        .values()
+        .filter(|p| p.name.eq_ignore_ascii_case(target));
+    match (matches.next(), matches.next()) {
+        (Some(provider), None) => Ok(provider),
+        (Some(_), Some(_)) => Err(format!("存在多个名为 {target} 的供应商，请改用 ID")),
+        _ => Err(format!("供应商 {target} 不存在")),
+    }
+}
+
+fn open_state() -> Result<AppState, String> {
+    let db = Database::init().map_err(|e| format!("初始化数据库失败: {e}"))?;
+    Ok(AppState::new(Arc::new(db)))
+}
+
+fn list(state: &AppState, app_type: AppType) -> Result<(), String> {
+    let providers = ProviderService::list(state, app_type.clone()).map_err(|e| e.to_string())?;
+    let current = ProviderService::current(state, app_type).map_err(|e| e.to_string())?;
+    for provider in providers.values() {
+        let marker = if provider.id == current { "*" } else { " " };
+        println!("{marker} {} ({})", provider.name, provider.id);
+    }
+    Ok(())
+}
+
+fn current(state: &AppState, app_type: AppType) -> Result<(), String> {
+    let providers = ProviderService::list(state, app_type.clone()).map_err(|e| e.to_string())?;
+    let current = ProviderService::current(state, app_type).map_err(|e| e.to_string())?;
+    match providers.get(&current) {
+        Some(provider) => println!("{} ({})", provider.name, provider.id),
+        None => return Err("当前没有选中的供应商".into()),
+    }
+    Ok(())
+}
+
+/// 控制接口请求结果
+enum ControlResponse {
+    /// 没有正在运行的代理（无发现文件或无法连接）
+    NotRunning,
+    /// 代理已处理请求
+    Done,
+}
+
+/// 向发现文件中记录的代理发送控制请求
+fn control_request(action: &str, body: Value) -> Result<ControlResponse, String> {
+    let Some(endpoint) = discovery::read() else {
+        return Ok(ControlResponse::NotRunning);
+    };
+    let Some(token) = endpoint.control_token else {
+        return Ok(ControlResponse::NotRunning);
+    };
+    // 仅访问本机代理：不走系统代理，接受自签名证书
+    let client = reqwest::Client::builder()
+        .no_proxy()
+        .danger_accept_invalid_certs(true)
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let url = format!("{}/ccswitch/control/{action}", endpoint.base_url);
+
+    tauri::async_runtime::block_on(async move {
+        let response = match client
+            .post(&url)
+            .header(control::TOKEN_HEADER, token)
+            .json(&body)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) if e.is_connect() => return Ok(ControlResponse::NotRunning),
+            Err(e) => return Err(format!("请求代理控制接口失败: {e}")),
+        };
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            return Err(format!("代理拒绝请求（{status}）: {text}"));
+        }
+        Ok(ControlResponse::Done)
+    })
+}
+
+fn switch(state: &AppState, app_type: AppType, target: &str) -> Result<(), String> {
+    let providers = ProviderService::list(state, app_type.clone()).map_err(|e| e.to_string())?;
+    let provider = find_provider(&providers, target)?;
+
+    // 代理运行中：由代理进程切换，接管模式下热切换，避免覆盖接管后的 Live 配置
+    let body = json!({ "app": app_type.as_str(), "provider": provider.id });
+    if let ControlResponse::Done = control_request("switch", body)? {
+        println!(
+            "已通过代理将 {} 切换到 {}",
+            app_type.as_str(),
+            provider.name
+        );
+        return Ok(());
+    }
+
+    ProviderService::switch(state, app_type.clone(), &provider.id).map_err(|e| e.to_string())?;
+    println!("已将 {} 切换到 {}", app_type.as_str(), provider.name);
+    Ok(())
+}
+
+fn test(state: &AppState, app_type: AppType, target: Option<&str>) -> Result<bool, String> {
+    let providers = ProviderService::list(state, app_type.clone()).map_err(|e| e.to_string())?;
+    let provider = match target {
+        Some(target) => find_provider(&providers, target)?,
+        None => {
+            let current =
+                ProviderService::current(state, app_type.clone()).map_err(|e| e.to_string())?;
+            providers
+                .get(&current)
+                .ok_or("当前没有选中的供应商，请指定要检查的供应商")?
+        }
+    };
+    let config = state
+        .db
+        .get_stream_check_config()
+        .map_err(|e| e.to_string())?;
+
+    crate::init_global_http_client(&state.db);
+    let result = tauri::async_runtime::block_on(StreamCheckService::check_with_retry(
+        &app_type, provider, &config,
+    ))
+    .map_err(|e| e.to_string())?;
+    let _ =
+        state
+            .db
+            .save_stream_check_log(&provider.id, &provider.name, app_type.as_str(), &result);
+
+    let elapsed = result
+        .response_time_ms
+        .map(|ms| format!(" {ms}ms"))
+        .unwrap_or_default();
+    println!(
+        "{} [{:?}]{elapsed} {} - {}",
+        provider.name, result.status, result.model_used, result.message
+    );
+    Ok(result.success)
+}
+
+/// 通过控制接口让守护进程正常退出，并等待其完成清理
+fn proxy_stop() -> Result<(), String> {
+    let endpoint = discovery::read().ok_or("代理未运行")?;
+    if let ControlResponse::NotRunning = control_request("shutdown", Value::Null)? {
+        // 进程已不存在：清理残留的发现文件
+        discovery::remove();
+        return Err("代理未运行".into());
+    }
+
+    // 守护进程恢复 Live 配置后删除发现文件
+    for _ in 0..50 {
+        if !matches!(discovery::read(), Some(current) if current.pid == endpoint.pid) {
+            println!("代理已停止（{}）", endpoint.base_url);
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+    println!("已通知代理退出（{}），清理仍在进行", endpoint.base_url);
+    Ok(())
+}
+
+/// 执行子命令，返回进程退出码
+pub fn run(args: Vec<String>) -> i32 {
+    let (command, app_type) = match parse(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    match command {
+        Command::Help => {
+            println!("{USAGE}");
+            return 0;
+        }
+        Command::ProxyStart => {
+            crate::daemon::run();
+            return 0;
+        }
+        _ => {}
+    }
+
+    crate::daemon::prepare_headless();
+    let result = match command {
+        Command::ProxyStop => proxy_stop().map(|_| true),
+        command => open_state().and_then(|state| match command {
+            Command::List => list(&state, app_type).map(|_| true),
+            Command::Current => current(&state, app_type).map(|_| true),
+            Command::Use(target) => switch(&state, app_type, &target).map(|_| true),
+            Command::Test(target) => test(&state, app_type, target.as_deref()),
+            Command::Help | Command::ProxyStart | Command::ProxyStop => unreachable!(),
+        }),
+    };
+
+    match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("cc-switch")
+            .chain(list.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn parses_subcommands_and_app_option() {
+        assert!(is_cli_invocation(&args(&["list"])));
+        assert!(!is_cli_invocation(&args(&["--daemon"])));
+        assert!(!is_cli_invocation(&args(&[])));
+
+        assert_eq!(
+            parse(&args(&["use", "My Relay", "--app", "codex"])).unwrap(),
+            (Command::Use("My Relay".into()), AppType::Codex)
+        );
+        assert_eq!(
+            parse(&args(&["test"])).unwrap(),
+            (Command::Test(None), AppType::Claude)
+        );
+        assert_eq!(
+            parse(&args(&["proxy", "stop"])).unwrap().0,
+            Command::ProxyStop
+        );
+        assert!(parse(&args(&["use"])).is_err());
+        assert!(parse(&args(&["list", "--app", "vim"])).is_err());
+        assert!(parse(&args(&["proxy", "restart"])).is_err());
+    }
+
+    #[test]
+    fn finds_provider_by_id_or_unique_name() {
+        let mut providers = IndexMap::new();
+        for (id, name) in [("a", "Relay"), ("b", "Official"), ("c", "official")] {
+            providers.insert(
+                id.to_string(),
+                Provider::with_id(id.into(), name.into(), json!({}), None::<String>),
+            );
+        }
+
+        assert_eq!(find_provider(&providers, "a").unwrap().id, "a");
+        assert_eq!(find_provider(&providers, "relay").unwrap().id, "a");
+        assert!(find_provider(&providers, "Official").is_err());
+        assert!(find_provider(&providers, "missing").is_err());
+    }
+}
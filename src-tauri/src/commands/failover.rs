@@ -5,6 +5,7 @@
 use crate::database::FailoverQueueItem;
 use crate::provider::Provider;
 use crate::store::AppState;
+use std::str::FromStr;
 
 /// 获取故障转移队列
 #[tauri::command]
@@ -100,3 +101,15 @@ pub async fn set_auto_failover_enabled(
         .await
         .map_err(|e| e.to_string())
 }
+
+/// 故障转移演练：模拟当前供应商故障并探测故障转移队列
+#[tauri::command]
+pub async fn run_failover_drill(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+) -> Result<crate::services::FailoverDrillReport, String> {
+    let app = crate::app_config::AppType::from_str(&app_type).map_err(|e| e.to_string())?;
+    crate::services::FailoverDrillService::run(state.inner(), app)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -47,6 +47,21 @@ pub async fn check_for_updates(handle: AppHandle) -> Result<bool, String> {
     Ok(true)
 }
 
+/// 导出诊断包（日志、脱敏后的供应商配置、版本与系统信息、代理状态），返回打包的文件数
+#[tauri::command]
+pub async fn export_diagnostic_bundle(
+    state: State<'_, crate::AppState>,
+    file_path: String,
+) -> Result<usize, String> {
+    let proxy_status = state.proxy_service.get_status().await?;
+    crate::services::diagnostics::export_diagnostic_bundle(
+        state.inner(),
+        &proxy_status,
+        Path::new(&file_path),
+    )
+    .map_err(|e| e.to_string())
+}
+
 /// 判断是否为便携版（绿色版）运行
 #[tauri::command]
 pub async fn is_portable_mode() -> Result<bool, String> {
@@ -8,13 +8,18 @@ mod global_proxy;
 mod import_export;
 mod mcp;
 mod misc;
+mod onboarding;
 mod plugin;
+mod profile;
 mod prompt;
 mod provider;
 mod proxy;
+mod response_compare;
 mod settings;
+mod settings_history;
 pub mod skill;
 mod stream_check;
+mod team_gateway;
 mod usage;
 
 pub use config::*;
@@ -25,11 +30,16 @@ pub use global_proxy::*;
 pub use import_export::*;
 pub use mcp::*;
 pub use misc::*;
+pub use onboarding::*;
 pub use plugin::*;
+pub use profile::*;
 pub use prompt::*;
 pub use provider::*;
 pub use proxy::*;
+pub use response_compare::*;
 pub use settings::*;
+pub use settings_history::*;
 pub use skill::*;
 pub use stream_check::*;
+pub use team_gateway::*;
 pub use usage::*;
@@ -0,0 +1,79 @@
+//! 供应商接入向导命令
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::services::onboarding::{
+    AuthProbe, CapabilityProbe, EndToEndTest, ModelDiscovery, ProviderOnboardingService,
+    SuggestedModelMap, UrlCheck,
+};
+use crate::store::AppState;
+use std::collections::BTreeMap;
+use tauri::State;
+
+/// 接入向导：规范化供应商地址
+#[tauri::command]
+pub fn onboarding_normalize_url(app_type: AppType, base_url: String) -> UrlCheck {
+    ProviderOnboardingService::normalize_url(&app_type, &base_url)
+}
+
+/// 接入向导：探测密钥是否有效
+#[tauri::command]
+pub async fn onboarding_probe_auth(
+    app_type: AppType,
+    base_url: String,
+    api_key: String,
+) -> Result<AuthProbe, AppError> {
+    Ok(ProviderOnboardingService::probe_auth(&app_type, &base_url, &api_key).await)
+}
+
+/// 接入向导：探测供应商能力
+#[tauri::command]
+pub async fn onboarding_probe_capabilities(
+    state: State<'_, AppState>,
+    app_type: AppType,
+    base_url: String,
+    api_key: String,
+    model: Option<String>,
+) -> Result<CapabilityProbe, AppError> {
+    let config = state.db.get_stream_check_config()?;
+    Ok(ProviderOnboardingService::probe_capabilities(
+        &app_type,
+        &base_url,
+        &api_key,
+        model.as_deref(),
+        &config,
+    )
+    .await)
+}
+
+/// 接入向导：拉取模型列表
+#[tauri::command]
+pub async fn onboarding_discover_models(
+    app_type: AppType,
+    base_url: String,
+    api_key: String,
+) -> Result<ModelDiscovery, AppError> {
+    Ok(ProviderOnboardingService::discover_models(&app_type, &base_url, &api_key).await)
+}
+
+/// 接入向导：推荐模型映射
+#[tauri::command]
+pub fn onboarding_suggest_model_map(app_type: AppType, models: Vec<String>) -> SuggestedModelMap {
+    ProviderOnboardingService::suggest_model_map(&app_type, &models)
+}
+
+/// 接入向导：使用最终配置进行端到端测试
+#[tauri::command]
+pub async fn onboarding_end_to_end_test(
+    state: State<'_, AppState>,
+    app_type: AppType,
+    base_url: String,
+    api_key: String,
+    mapping: BTreeMap<String, String>,
+) -> Result<EndToEndTest, AppError> {
+    let config = state.db.get_stream_check_config()?;
+    Ok(ProviderOnboardingService::end_to_end_test(
+        &app_type, &base_url, &api_key, &mapping, &config,
+    )
+    .await)
+}
@@ -0,0 +1,98 @@
+//! 配置档案命令
+
+use crate::error::AppError;
+use crate::services::profile::{Profile, ProfileService};
+use crate::store::AppState;
+use tauri::{Emitter, State};
+
+/// 获取全部配置档案
+#[tauri::command]
+pub fn list_profiles(state: State<'_, AppState>) -> Result<Vec<Profile>, AppError> {
+    state.db.get_profiles()
+}
+
+/// 获取最近应用的配置档案 ID
+#[tauri::command]
+pub fn get_active_profile_id(state: State<'_, AppState>) -> Result<Option<String>, AppError> {
+    state.db.get_active_profile_id()
+}
+
+/// 新增或更新配置档案（ID 为空时自动生成）
+#[tauri::command]
+pub fn save_profile(state: State<'_, AppState>, mut profile: Profile) -> Result<Profile, AppError> {
+    if profile.name.trim().is_empty() {
+        return Err(AppError::Message("档案名称不能为空".to_string()));
+    }
+    ProfileService::validate(&state, &profile)?;
+    if profile.id.trim().is_empty() {
+        profile.id = uuid::Uuid::new_v4().to_string();
+    }
+    if profile.created_at == 0 {
+        profile.created_at = chrono::Utc::now().timestamp();
+    }
+    let mut profiles = state.db.get_profiles()?;
+    match profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile.clone(),
+        None => profiles.push(profile.clone()),
+    }
+    state.db.set_profiles(&profiles)?;
+    Ok(profile)
+}
+
+/// 以当前各应用的设置创建并保存档案
+#[tauri::command]
+pub async fn capture_profile(
+    state: State<'_, AppState>,
+    name: String,
+) -> Result<Profile, AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::Message("档案名称不能为空".to_string()));
+    }
+    let profile = ProfileService::capture(&state, name.trim()).await?;
+    let mut profiles = state.db.get_profiles()?;
+    profiles.push(profile.clone());
+    state.db.set_profiles(&profiles)?;
+    Ok(profile)
+}
+
+/// 删除配置档案
+#[tauri::command]
+pub fn delete_profile(state: State<'_, AppState>, id: String) -> Result<bool, AppError> {
+    let mut profiles = state.db.get_profiles()?;
+    let before = profiles.len();
+    profiles.retain(|p| p.id != id);
+    if profiles.len() == before {
+        return Ok(false);
+    }
+    state.db.set_profiles(&profiles)?;
+    if state.db.get_active_profile_id()?.as_deref() == Some(id.as_str()) {
+        state.db.set_active_profile_id(None)?;
+    }
+    Ok(true)
+}
+
+/// 应用配置档案，完成后刷新托盘并通知前端
+#[tauri::command]
+pub async fn apply_profile(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Profile, AppError> {
+    let profile = ProfileService::apply(&state, &id).await?;
+
+    if let Ok(new_menu) = crate::tray::create_tray_menu(&app, state.inner()) {
+        if let Some(tray) = app.tray_by_id("main") {
+            if let Err(e) = tray.set_menu(Some(new_menu)) {
+                log::error!("更新托盘菜单失败: {e}");
+            }
+        }
+    }
+    let payload = serde_json::json!({
+        "profileId": profile.id,
+        "profileName": profile.name,
+    });
+    if let Err(e) = app.emit("profile-applied", payload) {
+        log::error!("发射档案切换事件失败: {e}");
+    }
+    Ok(profile)
+}
@@ -36,6 +36,15 @@ pub fn add_provider(
     ProviderService::add(state.inner(), app_type, provider).map_err(|e| e.to_string())
 }
 
+/// 添加内置的 Mock 供应商（不请求上游，用于离线测试），返回新供应商
+#[tauri::command]
+pub fn add_mock_provider(state: State<'_, AppState>, app: String) -> Result<Provider, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let provider = crate::proxy::mock_upstream::mock_provider(&app_type);
+    ProviderService::add(state.inner(), app_type, provider.clone()).map_err(|e| e.to_string())?;
+    Ok(provider)
+}
+
 /// 更新供应商
 #[tauri::command]
 pub fn update_provider(
@@ -119,6 +128,37 @@ pub async fn queryProviderUsage(
         .map_err(|e| e.to_string())
 }
 
+/// 查询中转站余额，低于阈值时向前端发送 `provider-low-balance` 事件
+#[allow(non_snake_case)]
+#[tauri::command]
+pub async fn queryProviderBalance(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    #[allow(non_snake_case)] providerId: String,
+    app: String,
+) -> Result<crate::provider::BalanceResult, String> {
+    use tauri::Emitter;
+
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    let result = ProviderService::query_balance(state.inner(), app_type, &providerId)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if result.low_balance {
+        let payload = serde_json::json!({
+            "appType": app,
+            "providerId": providerId,
+            "balance": result.balance,
+            "unit": result.unit,
+        });
+        if let Err(e) = app_handle.emit("provider-low-balance", payload) {
+            log::error!("发射低余额事件失败: {e}");
+        }
+    }
+
+    Ok(result)
+}
+
 /// 测试用量脚本（使用当前编辑器中的脚本，不保存）
 #[allow(non_snake_case)]
 #[allow(clippy::too_many_arguments)]
@@ -152,6 +192,111 @@ pub async fn testUsageScript(
     .map_err(|e| e.to_string())
 }
 
+/// 导出供应商配置摘要（不含密钥），用于团队文档或问题反馈
+///
+/// `app` 为空时导出全部应用，`format` 支持 `markdown` / `json`
+#[tauri::command]
+pub fn export_provider_summary(
+    state: State<'_, AppState>,
+    app: Option<String>,
+    format: crate::services::provider::SummaryFormat,
+) -> Result<String, String> {
+    let app_type = app
+        .map(|a| AppType::from_str(&a))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    ProviderService::export_summary(state.inner(), app_type, format).map_err(|e| e.to_string())
+}
+
+/// 将全部应用的供应商（含密钥）导出为密码加密的文件，返回导出的供应商数量
+#[tauri::command]
+pub fn export_providers_encrypted(
+    state: State<'_, AppState>,
+    file_path: String,
+    passphrase: String,
+) -> Result<usize, String> {
+    ProviderService::export_encrypted(state.inner(), &passphrase, std::path::Path::new(&file_path))
+        .map_err(|e| e.to_string())
+}
+
+/// 从密码加密的导出文件导入供应商
+///
+/// 已存在的同 ID 供应商在 `overwrite` 为 true 时覆盖，否则跳过
+#[tauri::command]
+pub fn import_providers_encrypted(
+    state: State<'_, AppState>,
+    file_path: String,
+    passphrase: String,
+    overwrite: Option<bool>,
+) -> Result<crate::services::provider::BundleImportResult, String> {
+    ProviderService::import_encrypted(
+        state.inner(),
+        &passphrase,
+        std::path::Path::new(&file_path),
+        overwrite.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 将供应商导出为密码加密的配置种子（供前端渲染为二维码）
+///
+/// 指定 `app` 时只导出该应用的供应商；内容超出单个二维码容量时返回错误
+#[tauri::command]
+pub fn export_provider_seed(
+    state: State<'_, AppState>,
+    passphrase: String,
+    app: Option<String>,
+) -> Result<String, String> {
+    let app_type = app
+        .map(|a| AppType::from_str(&a))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    ProviderService::export_seed(state.inner(), &passphrase, app_type).map_err(|e| e.to_string())
+}
+
+/// 从配置种子文本导入供应商
+///
+/// 只接受 `ccswitch-seed:` 开头的文本，不支持直接读取二维码图片文件
+///
+/// 已存在的同 ID 供应商在 `overwrite` 为 true 时覆盖，否则跳过
+#[tauri::command]
+pub fn import_provider_seed(
+    state: State<'_, AppState>,
+    seed: String,
+    passphrase: String,
+    overwrite: Option<bool>,
+) -> Result<crate::services::provider::BundleImportResult, String> {
+    ProviderService::import_seed(
+        state.inner(),
+        &passphrase,
+        &seed,
+        overwrite.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// 从剪贴板文本导入供应商
+///
+/// 自动识别 API Key、Base URL、供应商 JSON、环境变量片段或深链接；
+/// `create` 为 true 且信息完整时直接保存，否则仅返回预填草稿
+#[tauri::command]
+pub fn import_provider_from_clipboard(
+    state: State<'_, AppState>,
+    app: String,
+    text: String,
+    create: Option<bool>,
+) -> Result<crate::services::provider::ClipboardImportResult, String> {
+    let app_type = AppType::from_str(&app).map_err(|e| e.to_string())?;
+    ProviderService::import_from_clipboard(state.inner(), app_type, &text, create.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// 获取状态页监控发现的进行中故障
+#[tauri::command]
+pub fn get_provider_status_incidents() -> Vec<crate::services::StatusIncident> {
+    crate::services::StatusWatcherService::list_incidents()
+}
+
 /// 读取当前生效的配置内容
 #[tauri::command]
 pub fn read_live_provider_settings(app: String) -> Result<serde_json::Value, String> {
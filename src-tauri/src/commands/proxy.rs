@@ -2,6 +2,16 @@
 //!
 //! 提供前端调用的 API 接口
 
+use crate::database::JournalEntry;
+use crate::proxy::discovery::ActiveProxyEndpoint;
+use crate::proxy::fault_injection::{self, FaultInjectionConfig};
+use crate::proxy::lan_access;
+use crate::proxy::metrics;
+use crate::proxy::mock_upstream;
+use crate::proxy::provider_score::{self, ProviderScore};
+use crate::proxy::rate_limit_sim::{self, RateLimitSimulation};
+use crate::proxy::response_cache;
+use crate::proxy::tls;
 use crate::proxy::types::*;
 use crate::proxy::{CircuitBreakerConfig, CircuitBreakerStats};
 use crate::store::AppState;
@@ -20,6 +30,20 @@ pub async fn stop_proxy_with_restore(state: tauri::State<'_, AppState>) -> Resul
     state.proxy_service.stop_with_restore().await
 }
 
+/// 获取上次异常退出时丢失的请求
+#[tauri::command]
+pub fn get_lost_proxy_requests(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<JournalEntry>, String> {
+    state.db.get_lost_requests().map_err(|e| e.to_string())
+}
+
+/// 清除丢失请求记录
+#[tauri::command]
+pub fn clear_lost_proxy_requests(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.db.clear_lost_requests().map_err(|e| e.to_string())
+}
+
 /// 获取各应用接管状态
 #[tauri::command]
 pub async fn get_proxy_takeover_status(
@@ -62,6 +86,173 @@ pub async fn update_proxy_config(
     state.proxy_service.update_config(&config).await
 }
 
+/// 获取是否允许代理监听局域网地址
+#[tauri::command]
+pub async fn get_proxy_lan_access(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    state
+        .db
+        .get_proxy_lan_access()
+        .map(|enabled| enabled.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// 设置是否允许代理监听局域网地址
+///
+/// 关闭前需先把监听地址改回本机地址，避免代理下次启动失败
+#[tauri::command]
+pub async fn set_proxy_lan_access(
+    state: tauri::State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    if !enabled {
+        let config = state
+            .db
+            .get_global_proxy_config()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !lan_access::is_loopback(&config.listen_address) {
+            return Err(format!(
+                "当前监听地址为 {}，请先改回 127.0.0.1 再关闭局域网访问",
+                config.listen_address
+            ));
+        }
+    }
+    state
+        .db
+        .set_proxy_lan_access(enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取本地代理访问令牌（未设置时为 None）
+#[tauri::command]
+pub async fn get_proxy_access_token(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    state.db.get_proxy_access_token().map_err(|e| e.to_string())
+}
+
+/// 设置本地代理访问令牌（传入空值时关闭校验）
+#[tauri::command]
+pub async fn set_proxy_access_token(
+    state: tauri::State<'_, AppState>,
+    token: Option<String>,
+) -> Result<(), String> {
+    state
+        .db
+        .set_proxy_access_token(token.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 生成并保存新的本地代理访问令牌
+#[tauri::command]
+pub async fn generate_proxy_access_token(
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let token = format!("ccsw-{}", uuid::Uuid::new_v4().simple());
+    state
+        .db
+        .set_proxy_access_token(Some(&token))
+        .map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// 获取管理 API 令牌（未设置时为 None，管理 API 关闭）
+#[tauri::command]
+pub async fn get_proxy_admin_token(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    state.db.get_proxy_admin_token().map_err(|e| e.to_string())
+}
+
+/// 设置管理 API 令牌（传入空值时关闭管理 API）
+#[tauri::command]
+pub async fn set_proxy_admin_token(
+    state: tauri::State<'_, AppState>,
+    token: Option<String>,
+) -> Result<(), String> {
+    state
+        .db
+        .set_proxy_admin_token(token.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// 生成并保存新的管理 API 令牌
+#[tauri::command]
+pub async fn generate_proxy_admin_token(
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let token = format!("ccsw-admin-{}", uuid::Uuid::new_v4().simple());
+    state
+        .db
+        .set_proxy_admin_token(Some(&token))
+        .map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// 获取本地代理 HTTPS 配置
+#[tauri::command]
+pub async fn get_proxy_tls_config(
+    state: tauri::State<'_, AppState>,
+) -> Result<ProxyTlsConfig, String> {
+    state.db.get_proxy_tls_config().map_err(|e| e.to_string())
+}
+
+/// 设置本地代理 HTTPS 配置（修改后需重启代理）
+#[tauri::command]
+pub async fn set_proxy_tls_config(
+    state: tauri::State<'_, AppState>,
+    config: ProxyTlsConfig,
+) -> Result<(), String> {
+    let is_set = |p: &Option<String>| p.as_deref().is_some_and(|p| !p.trim().is_empty());
+    if is_set(&config.cert_path) != is_set(&config.key_path) {
+        return Err("自定义证书需要同时指定证书与私钥路径".to_string());
+    }
+    if is_set(&config.cert_path) {
+        let (cert, key) = tls::effective_paths(&config);
+        for path in [cert, key] {
+            if !path.exists() {
+                return Err(format!("文件不存在: {}", path.display()));
+            }
+        }
+    }
+    state
+        .db
+        .set_proxy_tls_config(&config)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取当前生效的 HTTPS 证书路径（供客户端配置信任）
+#[tauri::command]
+pub async fn get_proxy_certificate_path(
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let config = state.db.get_proxy_tls_config().map_err(|e| e.to_string())?;
+    Ok(tls::effective_paths(&config)
+        .0
+        .to_string_lossy()
+        .to_string())
+}
+
+/// 重新生成自签名证书（下次启动代理时生效）
+#[tauri::command]
+pub async fn regenerate_proxy_certificate() -> Result<String, String> {
+    let (cert, key) = tls::self_signed_paths();
+    for path in [&cert, &key] {
+        if path.exists() {
+            std::fs::remove_file(path).map_err(|e| format!("删除旧证书失败: {e}"))?;
+        }
+    }
+    Ok(cert.to_string_lossy().to_string())
+}
+
+/// 获取代理实际监听地址（配置端口被占用时可能顺延；未运行时返回 None）
+#[tauri::command]
+pub async fn get_active_proxy_endpoint(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<ActiveProxyEndpoint>, String> {
+    Ok(state.proxy_service.get_active_endpoint().await)
+}
+
 // ==================== Global & Per-App Config ====================
 
 /// 获取全局代理配置
@@ -86,6 +277,11 @@ pub async fn update_global_proxy_config(
     config: GlobalProxyConfig,
 ) -> Result<(), String> {
     let db = &state.db;
+    let previous = db
+        .get_global_proxy_config()
+        .await
+        .map_err(|e| e.to_string())?;
+    lan_access::check_listen_address(db, &config.listen_address, Some(&previous.listen_address))?;
     db.update_global_proxy_config(config)
         .await
         .map_err(|e| e.to_string())
@@ -292,3 +488,96 @@ pub async fn get_circuit_breaker_stats(
     let _ = (state, provider_id, app_type);
     Ok(None)
 }
+
+/// 获取当前 rate limit 模拟状态
+#[tauri::command]
+pub async fn get_rate_limit_simulation() -> Result<Option<RateLimitSimulation>, String> {
+    Ok(rate_limit_sim::current())
+}
+
+/// 开启 rate limit 模拟（代理直接返回 429 或流式限流错误，不请求上游）
+#[tauri::command]
+pub async fn start_rate_limit_simulation(simulation: RateLimitSimulation) -> Result<(), String> {
+    rate_limit_sim::start(simulation);
+    Ok(())
+}
+
+/// 关闭 rate limit 模拟
+#[tauri::command]
+pub async fn stop_rate_limit_simulation() -> Result<(), String> {
+    rate_limit_sim::stop();
+    Ok(())
+}
+
+/// 获取当前故障注入配置
+#[tauri::command]
+pub async fn get_fault_injection() -> Result<Option<FaultInjectionConfig>, String> {
+    Ok(fault_injection::current())
+}
+
+/// 开启故障注入（按概率注入 429、500、流式截断与额外延迟）
+#[tauri::command]
+pub async fn start_fault_injection(config: FaultInjectionConfig) -> Result<(), String> {
+    fault_injection::start(config);
+    Ok(())
+}
+
+/// 关闭故障注入
+#[tauri::command]
+pub async fn stop_fault_injection() -> Result<(), String> {
+    fault_injection::stop();
+    Ok(())
+}
+
+/// 是否处于离线演练模式（所有请求由模拟上游响应）
+#[tauri::command]
+pub async fn get_mock_dry_run() -> Result<bool, String> {
+    Ok(mock_upstream::is_dry_run())
+}
+
+/// 开启或关闭离线演练模式
+#[tauri::command]
+pub async fn set_mock_dry_run(enabled: bool) -> Result<(), String> {
+    mock_upstream::set_dry_run(enabled);
+    Ok(())
+}
+
+/// 获取响应缓存的命中统计
+#[tauri::command]
+pub async fn get_response_cache_stats() -> Result<response_cache::ResponseCacheStats, String> {
+    Ok(response_cache::stats())
+}
+
+/// 清空响应缓存，返回清除的条目数
+#[tauri::command]
+pub async fn clear_response_cache() -> Result<usize, String> {
+    Ok(response_cache::clear())
+}
+
+/// 获取指定应用各供应商的滚动评分（按评分从高到低）
+#[tauri::command]
+pub async fn get_provider_scores(
+    state: tauri::State<'_, AppState>,
+    app_type: String,
+) -> Result<Vec<ProviderScore>, String> {
+    let config = state
+        .db
+        .get_provider_scoring_config()
+        .map_err(|e| e.to_string())?;
+    provider_score::list_scores(&state.db, &app_type, &config).map_err(|e| e.to_string())
+}
+
+/// 根据应用内阈值生成 Prometheus 告警规则（YAML），指定路径时同时写入文件
+#[tauri::command]
+pub async fn export_alert_rules(
+    state: tauri::State<'_, AppState>,
+    file_path: Option<String>,
+) -> Result<String, String> {
+    let rules = metrics::generate_alert_rules(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Some(path) = file_path {
+        std::fs::write(&path, &rules).map_err(|e| format!("写入告警规则失败: {e}"))?;
+    }
+    Ok(rules)
+}
@@ -0,0 +1,15 @@
+//! 供应商响应对比命令
+
+use crate::error::AppError;
+use crate::services::response_compare::{CompareRequest, ComparisonReport, ResponseCompareService};
+use crate::store::AppState;
+use tauri::State;
+
+/// 将同一提示发送给两个供应商并返回对比结果
+#[tauri::command]
+pub async fn compare_provider_responses(
+    state: State<'_, AppState>,
+    request: CompareRequest,
+) -> Result<ComparisonReport, AppError> {
+    ResponseCompareService::compare(&state.db, request).await
+}
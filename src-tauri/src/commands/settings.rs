@@ -33,6 +33,21 @@ pub async fn get_app_config_dir_override(app: AppHandle) -> Result<Option<String
         .map(|p| p.to_string_lossy().to_string()))
 }
 
+/// 获取当前生效的数据目录（主目录不可写时为回退目录）
+#[tauri::command]
+pub async fn get_data_paths() -> Result<crate::data_dir::DataPaths, String> {
+    Ok(crate::data_dir::current_paths())
+}
+
+/// 迁移旧版 ~/tmp/log 调试日志并按保留策略清理，返回迁移与释放的空间
+#[tauri::command]
+pub async fn cleanup_debug_logs(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::log_retention::DebugLogCleanup, String> {
+    let config = state.db.get_debug_log_config().map_err(|e| e.to_string())?;
+    Ok(crate::proxy::log_retention::migrate_debug_logs(&config))
+}
+
 /// 设置 app_config_dir 覆盖配置 (到 Store)
 #[tauri::command]
 pub async fn set_app_config_dir_override(
@@ -80,3 +95,674 @@ pub async fn set_rectifier_config(
         .map_err(|e| e.to_string())?;
     Ok(true)
 }
+
+/// 获取本地客户端限流配置
+#[tauri::command]
+pub async fn get_client_rate_limit_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::ClientRateLimitConfig, String> {
+    state
+        .db
+        .get_client_rate_limit_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置本地客户端限流配置
+#[tauri::command]
+pub async fn set_client_rate_limit_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::ClientRateLimitConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_client_rate_limit_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取团队网关配置
+#[tauri::command]
+pub async fn get_team_gateway_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::TeamGatewayConfig, String> {
+    state
+        .db
+        .get_team_gateway_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置团队网关配置
+#[tauri::command]
+pub async fn set_team_gateway_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::TeamGatewayConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_team_gateway_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取自动回切配置
+#[tauri::command]
+pub async fn get_failback_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::FailbackConfig, String> {
+    state.db.get_failback_config().map_err(|e| e.to_string())
+}
+
+/// 设置自动回切配置
+#[tauri::command]
+pub async fn set_failback_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::FailbackConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_failback_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取故障通知配置
+#[tauri::command]
+pub async fn get_failure_notification_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::FailureNotificationConfig, String> {
+    state
+        .db
+        .get_failure_notification_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置故障通知配置
+#[tauri::command]
+pub async fn set_failure_notification_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::FailureNotificationConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_failure_notification_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取消费提醒配置
+#[tauri::command]
+pub async fn get_cost_alert_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::CostAlertConfig, String> {
+    state.db.get_cost_alert_config().map_err(|e| e.to_string())
+}
+
+/// 设置消费提醒配置
+#[tauri::command]
+pub async fn set_cost_alert_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::CostAlertConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_cost_alert_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取告警规则列表
+#[tauri::command]
+pub async fn get_alert_rules(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Vec<crate::proxy::types::AlertRule>, String> {
+    state.db.get_alert_rules().map_err(|e| e.to_string())
+}
+
+/// 设置告警规则列表（ID 为空时自动生成）
+#[tauri::command]
+pub async fn set_alert_rules(
+    state: tauri::State<'_, crate::AppState>,
+    mut rules: Vec<crate::proxy::types::AlertRule>,
+) -> Result<bool, String> {
+    for rule in &mut rules {
+        if rule.id.trim().is_empty() {
+            rule.id = uuid::Uuid::new_v4().simple().to_string();
+        }
+        if let crate::proxy::types::AlertAction::SwitchProvider { provider_id } = &rule.action {
+            if rule.app_type.is_none() || provider_id.trim().is_empty() {
+                return Err(format!(
+                    "告警规则 {} 切换供应商时必须指定应用与目标供应商",
+                    rule.name
+                ));
+            }
+        }
+    }
+    state
+        .db
+        .set_alert_rules(&rules)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取匿名使用统计配置
+#[tauri::command]
+pub async fn get_telemetry_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::services::telemetry::TelemetryConfig, String> {
+    state.db.get_telemetry_config().map_err(|e| e.to_string())
+}
+
+/// 设置匿名使用统计配置（首次开启时生成随机安装 ID）
+#[tauri::command]
+pub async fn set_telemetry_config(
+    state: tauri::State<'_, crate::AppState>,
+    mut config: crate::services::telemetry::TelemetryConfig,
+) -> Result<bool, String> {
+    let current = state.db.get_telemetry_config().map_err(|e| e.to_string())?;
+    if config.install_id.trim().is_empty() {
+        config.install_id = current.install_id;
+    }
+    if config.enabled && config.install_id.trim().is_empty() {
+        config.install_id = uuid::Uuid::new_v4().simple().to_string();
+    }
+    config.last_sent_at = current.last_sent_at;
+    state
+        .db
+        .set_telemetry_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 预览匿名使用统计将要发送的完整内容
+#[tauri::command]
+pub async fn preview_telemetry_report(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::services::telemetry::TelemetryReport, String> {
+    let config = state.db.get_telemetry_config().map_err(|e| e.to_string())?;
+    crate::services::telemetry::build_report(&state.db, &config.install_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 获取供应商评分配置
+#[tauri::command]
+pub async fn get_provider_scoring_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::ProviderScoringConfig, String> {
+    state
+        .db
+        .get_provider_scoring_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置供应商评分配置
+#[tauri::command]
+pub async fn set_provider_scoring_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::ProviderScoringConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_provider_scoring_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取入站 IP 白名单配置
+#[tauri::command]
+pub async fn get_ip_allowlist_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::IpAllowlistConfig, String> {
+    state
+        .db
+        .get_ip_allowlist_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置入站 IP 白名单配置（保存前校验地址段格式）
+#[tauri::command]
+pub async fn set_ip_allowlist_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::IpAllowlistConfig,
+) -> Result<bool, String> {
+    crate::proxy::ip_allowlist::validate(&config)?;
+    state
+        .db
+        .set_ip_allowlist_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取请求体大小限制配置
+#[tauri::command]
+pub async fn get_request_size_limit_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::RequestSizeLimitConfig, String> {
+    state
+        .db
+        .get_request_size_limit_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置请求体大小限制配置
+#[tauri::command]
+pub async fn set_request_size_limit_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::RequestSizeLimitConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_request_size_limit_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取 Webhook 配置
+#[tauri::command]
+pub async fn get_webhook_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::WebhookConfig, String> {
+    state.db.get_webhook_config().map_err(|e| e.to_string())
+}
+
+/// 设置 Webhook 配置（ID 为空时自动生成，保存前校验地址）
+#[tauri::command]
+pub async fn set_webhook_config(
+    state: tauri::State<'_, crate::AppState>,
+    mut config: crate::proxy::types::WebhookConfig,
+) -> Result<bool, String> {
+    for endpoint in &mut config.endpoints {
+        let url = url::Url::parse(endpoint.url.trim())
+            .map_err(|e| format!("Webhook 地址无效: {} ({e})", endpoint.url))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "Webhook 地址必须以 http(s):// 开头: {}",
+                endpoint.url
+            ));
+        }
+        if endpoint.id.trim().is_empty() {
+            endpoint.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+    state
+        .db
+        .set_webhook_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 向指定 Webhook 发送一条测试事件
+#[tauri::command]
+pub async fn test_webhook(endpoint: crate::proxy::types::WebhookEndpoint) -> Result<bool, String> {
+    crate::proxy::webhook::deliver(
+        &endpoint,
+        crate::proxy::types::WebhookEvent::ProviderFailover,
+        &serde_json::json!({
+            "appType": "claude",
+            "providerId": "test",
+            "source": "test",
+        }),
+    )
+    .await?;
+    Ok(true)
+}
+
+/// 获取 A/B 实验列表
+#[tauri::command]
+pub async fn get_experiments(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Vec<crate::proxy::types::Experiment>, String> {
+    state.db.get_experiments().map_err(|e| e.to_string())
+}
+
+/// 设置 A/B 实验列表（ID 为空时自动生成）
+#[tauri::command]
+pub async fn set_experiments(
+    state: tauri::State<'_, crate::AppState>,
+    mut experiments: Vec<crate::proxy::types::Experiment>,
+) -> Result<bool, String> {
+    for experiment in &mut experiments {
+        if experiment.percentage > 100 {
+            return Err(format!(
+                "实验 {} 的流量占比必须在 0-100 之间",
+                experiment.name
+            ));
+        }
+        if experiment.id.trim().is_empty() {
+            experiment.id = uuid::Uuid::new_v4().simple().to_string();
+        } else if experiment.id.contains(':') {
+            return Err(format!("实验 ID 不能包含冒号: {}", experiment.id));
+        }
+        if let crate::proxy::types::ExperimentTreatment::TrafficSplit { provider_id } =
+            &experiment.treatment
+        {
+            if provider_id.trim().is_empty()
+                || experiment.provider_id.as_deref() == Some(provider_id.as_str())
+            {
+                return Err(format!(
+                    "实验 {} 的流量切分目标必须是另一个供应商",
+                    experiment.name
+                ));
+            }
+        }
+    }
+    state
+        .db
+        .set_experiments(&experiments)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取实验各分组的消费、延迟与错误率
+#[tauri::command]
+pub async fn get_experiment_results(
+    state: tauri::State<'_, crate::AppState>,
+    experiment_id: String,
+) -> Result<Vec<crate::database::ExperimentArmStats>, String> {
+    state
+        .db
+        .get_experiment_results(&experiment_id)
+        .map_err(|e| e.to_string())
+}
+
+/// 获取请求日志脱敏配置
+#[tauri::command]
+pub async fn get_log_redaction_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::LogRedactionConfig, String> {
+    state
+        .db
+        .get_log_redaction_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置请求日志脱敏配置
+#[tauri::command]
+pub async fn set_log_redaction_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::LogRedactionConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_log_redaction_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取调试日志配置
+#[tauri::command]
+pub async fn get_debug_log_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::DebugLogConfig, String> {
+    state.db.get_debug_log_config().map_err(|e| e.to_string())
+}
+
+/// 设置调试日志配置
+#[tauri::command]
+pub async fn set_debug_log_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::DebugLogConfig,
+) -> Result<bool, String> {
+    if let Some(dir) = config
+        .log_dir
+        .as_deref()
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+    {
+        if !crate::data_dir::is_dir_writable(std::path::Path::new(dir)) {
+            return Err(format!("调试日志目录不可写: {dir}"));
+        }
+    }
+    state
+        .db
+        .set_debug_log_config(&config)
+        .map_err(|e| e.to_string())?;
+    crate::proxy::debug_log::apply_config(&config);
+    Ok(true)
+}
+
+/// 获取分类日志级别配置
+#[tauri::command]
+pub async fn get_log_levels_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::log_levels::LogLevelsConfig, String> {
+    state.db.get_log_levels_config().map_err(|e| e.to_string())
+}
+
+/// 设置分类日志级别配置（立即生效）
+#[tauri::command]
+pub async fn set_log_levels_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::log_levels::LogLevelsConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_log_levels_config(&config)
+        .map_err(|e| e.to_string())?;
+    crate::log_levels::apply(&config);
+    Ok(true)
+}
+
+/// 运行时开关请求体与响应内容记录（仅修改该项，保留其他调试日志配置）
+#[tauri::command]
+pub async fn set_debug_log_bodies(
+    state: tauri::State<'_, crate::AppState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let mut config = state.db.get_debug_log_config().map_err(|e| e.to_string())?;
+    config.log_bodies = enabled;
+    state
+        .db
+        .set_debug_log_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 运行时开关调试日志（仅修改开关，保留其他调试日志配置）
+#[tauri::command]
+pub async fn set_debug_logging_enabled(
+    state: tauri::State<'_, crate::AppState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let mut config = state.db.get_debug_log_config().map_err(|e| e.to_string())?;
+    config.enabled = enabled;
+    state
+        .db
+        .set_debug_log_config(&config)
+        .map_err(|e| e.to_string())?;
+    crate::proxy::debug_log::apply_config(&config);
+    log::info!("调试日志已{}", if enabled { "开启" } else { "关闭" });
+    Ok(true)
+}
+
+/// 获取链路追踪配置
+#[tauri::command]
+pub async fn get_otel_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::OtelConfig, String> {
+    state.db.get_otel_config().map_err(|e| e.to_string())
+}
+
+/// 设置链路追踪配置
+#[tauri::command]
+pub async fn set_otel_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::OtelConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_otel_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取成本标注配置
+#[tauri::command]
+pub async fn get_cost_annotation_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::CostAnnotationConfig, String> {
+    state
+        .db
+        .get_cost_annotation_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置成本标注配置
+#[tauri::command]
+pub async fn set_cost_annotation_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::CostAnnotationConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_cost_annotation_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取对话记录采集配置
+#[tauri::command]
+pub async fn get_transcript_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::TranscriptConfig, String> {
+    state.db.get_transcript_config().map_err(|e| e.to_string())
+}
+
+/// 设置对话记录采集配置
+#[tauri::command]
+pub async fn set_transcript_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::TranscriptConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_transcript_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取完整对话采集配置
+#[tauri::command]
+pub async fn get_conversation_capture_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::ConversationCaptureConfig, String> {
+    state
+        .db
+        .get_conversation_capture_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置完整对话采集配置
+#[tauri::command]
+pub async fn set_conversation_capture_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::ConversationCaptureConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_conversation_capture_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取请求重放采集配置
+#[tauri::command]
+pub async fn get_replay_capture_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::ReplayCaptureConfig, String> {
+    state
+        .db
+        .get_replay_capture_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置请求重放采集配置
+#[tauri::command]
+pub async fn set_replay_capture_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::ReplayCaptureConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_replay_capture_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取影子流量配置
+#[tauri::command]
+pub async fn get_shadow_traffic_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::ShadowTrafficConfig, String> {
+    state
+        .db
+        .get_shadow_traffic_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置影子流量配置
+#[tauri::command]
+pub async fn set_shadow_traffic_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::ShadowTrafficConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_shadow_traffic_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// 获取响应缓存配置
+#[tauri::command]
+pub async fn get_response_cache_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::ResponseCacheConfig, String> {
+    state
+        .db
+        .get_response_cache_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置响应缓存配置（关闭时清空已缓存的响应）
+#[tauri::command]
+pub async fn set_response_cache_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::ResponseCacheConfig,
+) -> Result<bool, String> {
+    state
+        .db
+        .set_response_cache_config(&config)
+        .map_err(|e| e.to_string())?;
+    if !config.enabled {
+        crate::proxy::response_cache::clear();
+    }
+    Ok(true)
+}
+
+/// 获取 SSE 保活配置
+#[tauri::command]
+pub async fn get_sse_keepalive_config(
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<crate::proxy::types::SseKeepaliveConfig, String> {
+    state
+        .db
+        .get_sse_keepalive_config()
+        .map_err(|e| e.to_string())
+}
+
+/// 设置 SSE 保活配置
+#[tauri::command]
+pub async fn set_sse_keepalive_config(
+    state: tauri::State<'_, crate::AppState>,
+    config: crate::proxy::types::SseKeepaliveConfig,
+) -> Result<bool, String> {
+    if config.enabled && config.interval_secs == 0 {
+        return Err("SSE 保活间隔必须大于 0 秒".to_string());
+    }
+    state
+        .db
+        .set_sse_keepalive_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
@@ -0,0 +1,32 @@
+//! Claude 配置变更历史命令
+
+use crate::config::get_claude_settings_path;
+use crate::error::AppError;
+use crate::services::settings_history::{DiffLine, SettingsHistory, SettingsVersion};
+
+/// 获取 Claude settings.json 的历史快照（从新到旧）
+#[tauri::command]
+pub fn list_claude_settings_history() -> Result<Vec<SettingsVersion>, AppError> {
+    SettingsHistory::claude().list()
+}
+
+/// 读取指定快照的原始内容
+#[tauri::command]
+pub fn get_claude_settings_version(id: String) -> Result<String, AppError> {
+    SettingsHistory::claude().read(&id)
+}
+
+/// 对比两个快照；未指定 `to` 时与当前 settings.json 对比
+#[tauri::command]
+pub fn diff_claude_settings_versions(
+    from: String,
+    to: Option<String>,
+) -> Result<Vec<DiffLine>, AppError> {
+    SettingsHistory::claude().diff(&from, to.as_deref(), &get_claude_settings_path())
+}
+
+/// 将 settings.json 恢复为指定快照
+#[tauri::command]
+pub fn restore_claude_settings_version(id: String) -> Result<(), AppError> {
+    SettingsHistory::claude().restore(&id, &get_claude_settings_path())
+}
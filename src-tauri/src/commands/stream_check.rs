@@ -5,6 +5,7 @@ use crate::error::AppError;
 use crate::services::stream_check::{
     HealthStatus, StreamCheckConfig, StreamCheckResult, StreamCheckService,
 };
+use crate::services::{HealthProbeConfig, HealthProbeResult, HealthProbeService};
 use crate::store::AppState;
 use std::collections::HashSet;
 use tauri::State;
@@ -104,3 +105,24 @@ pub fn save_stream_check_config(
 ) -> Result<(), AppError> {
     state.db.save_stream_check_config(&config)
 }
+
+/// 获取后台健康探测配置
+#[tauri::command]
+pub fn get_health_probe_config(state: State<'_, AppState>) -> Result<HealthProbeConfig, AppError> {
+    state.db.get_health_probe_config()
+}
+
+/// 保存后台健康探测配置
+#[tauri::command]
+pub fn save_health_probe_config(
+    state: State<'_, AppState>,
+    config: HealthProbeConfig,
+) -> Result<(), AppError> {
+    state.db.save_health_probe_config(&config)
+}
+
+/// 获取各供应商最近一次后台探测结果
+#[tauri::command]
+pub fn get_health_probe_results() -> Result<Vec<HealthProbeResult>, AppError> {
+    Ok(HealthProbeService::list_results())
+}
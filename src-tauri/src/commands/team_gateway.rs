@@ -0,0 +1,71 @@
+//! 团队网关成员管理命令
+
+use crate::database::{TeamMember, TeamMemberUsage};
+use crate::error::AppError;
+use crate::store::AppState;
+use tauri::State;
+
+/// 生成成员访问令牌
+fn generate_token() -> String {
+    format!("ccsw-{}", uuid::Uuid::new_v4().simple())
+}
+
+/// 获取全部团队成员
+#[tauri::command]
+pub fn list_team_members(state: State<'_, AppState>) -> Result<Vec<TeamMember>, AppError> {
+    state.db.list_team_members()
+}
+
+/// 新增或更新团队成员（ID / 令牌为空时自动生成）
+#[tauri::command]
+pub fn save_team_member(
+    state: State<'_, AppState>,
+    mut member: TeamMember,
+) -> Result<TeamMember, AppError> {
+    if member.name.trim().is_empty() {
+        return Err(AppError::Message("成员名称不能为空".to_string()));
+    }
+    if member.id.trim().is_empty() {
+        member.id = uuid::Uuid::new_v4().to_string();
+    }
+    if member.token.trim().is_empty() {
+        member.token = generate_token();
+    }
+    if member.created_at == 0 {
+        member.created_at = chrono::Utc::now().timestamp();
+    }
+    state.db.save_team_member(&member)?;
+    Ok(member)
+}
+
+/// 重新生成成员访问令牌（旧令牌立即失效）
+#[tauri::command]
+pub fn regenerate_team_member_token(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<TeamMember, AppError> {
+    let mut member = state
+        .db
+        .get_team_member(&id)?
+        .ok_or_else(|| AppError::Message(format!("团队成员 {id} 不存在")))?;
+    member.token = generate_token();
+    state.db.save_team_member(&member)?;
+    Ok(member)
+}
+
+/// 删除团队成员
+#[tauri::command]
+pub fn delete_team_member(state: State<'_, AppState>, id: String) -> Result<(), AppError> {
+    state.db.delete_team_member(&id)
+}
+
+/// 获取各团队成员的当日 / 当月用量
+#[tauri::command]
+pub fn get_team_member_usage(state: State<'_, AppState>) -> Result<Vec<TeamMemberUsage>, AppError> {
+    state
+        .db
+        .list_team_members()?
+        .iter()
+        .map(|member| state.db.get_team_member_usage(&member.id))
+        .collect()
+}
@@ -1,6 +1,7 @@
 //! 使用统计相关命令
 
 use crate::error::AppError;
+use crate::services::usage_report::UsageReportFormat;
 use crate::services::usage_stats::*;
 use crate::store::AppState;
 use tauri::State;
@@ -25,12 +26,86 @@ pub fn get_usage_trends(
     state.db.get_daily_trends(start_date, end_date)
 }
 
+/// 获取用量序列（按小时/天聚合，可按供应商或模型分组）
+#[tauri::command]
+pub fn get_usage_series(
+    state: State<'_, AppState>,
+    query: UsageSeriesQuery,
+) -> Result<Vec<UsageSeriesPoint>, AppError> {
+    state.db.get_usage_series(&query)
+}
+
+/// 导出指定时间范围内的使用记录（CSV / JSON）
+#[tauri::command]
+pub async fn export_usage_report(
+    state: State<'_, AppState>,
+    file_path: String,
+    format: Option<UsageReportFormat>,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+    app_type: Option<String>,
+) -> Result<usize, AppError> {
+    let db = state.db.clone();
+    let filters = LogFilters {
+        app_type,
+        start_date,
+        end_date,
+        ..Default::default()
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::services::usage_report::export_usage_report(
+            &db,
+            &filters,
+            format.unwrap_or_default(),
+            std::path::Path::new(&file_path),
+        )
+    })
+    .await
+    .map_err(|e| AppError::Message(format!("导出使用记录失败: {e}")))?
+}
+
+/// 导出指定时间范围内采集的请求为 HAR 文件（已脱敏）
+#[tauri::command]
+pub async fn export_har(
+    state: State<'_, AppState>,
+    file_path: String,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+) -> Result<usize, AppError> {
+    let db = state.db.clone();
+    // 请求 URL 使用客户端实际访问的本地代理地址
+    let base_url = crate::proxy::discovery::read()
+        .map(|endpoint| endpoint.base_url)
+        .unwrap_or_else(|| "http://127.0.0.1".to_string());
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::services::har_export::export_har(
+            &db,
+            start_date,
+            end_date,
+            &base_url,
+            std::path::Path::new(&file_path),
+        )
+    })
+    .await
+    .map_err(|e| AppError::Message(format!("导出 HAR 失败: {e}")))?
+}
+
 /// 获取 Provider 统计
 #[tauri::command]
 pub fn get_provider_stats(state: State<'_, AppState>) -> Result<Vec<ProviderStats>, AppError> {
     state.db.get_provider_stats()
 }
 
+/// 获取 Provider 延迟分位数（默认统计最近 15 分钟与 1 小时）
+#[tauri::command]
+pub fn get_provider_latency_percentiles(
+    state: State<'_, AppState>,
+    windows_minutes: Option<Vec<u32>>,
+) -> Result<Vec<ProviderLatencyPercentiles>, AppError> {
+    let windows = windows_minutes.unwrap_or_else(|| vec![15, 60]);
+    state.db.get_provider_latency_percentiles(&windows)
+}
+
 /// 获取模型统计
 #[tauri::command]
 pub fn get_model_stats(state: State<'_, AppState>) -> Result<Vec<ModelStats>, AppError> {
@@ -57,6 +132,101 @@ pub fn get_request_detail(
     state.db.get_request_detail(&request_id)
 }
 
+/// 获取单个请求的完整记录（请求与响应、重试过程、用量与耗时）
+#[tauri::command]
+pub fn get_request_inspection(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<Option<crate::proxy::request_inspection::RequestInspection>, AppError> {
+    crate::proxy::request_inspection::inspect(&state.db, &request_id)
+}
+
+/// 全文搜索对话记录
+#[tauri::command]
+pub fn search_transcripts(
+    state: State<'_, AppState>,
+    query: crate::database::TranscriptSearchQuery,
+    page: u32,
+    page_size: u32,
+) -> Result<crate::database::PaginatedTranscripts, AppError> {
+    state.db.search_transcripts(&query, page, page_size)
+}
+
+/// 获取单条对话记录全文
+#[tauri::command]
+pub fn get_transcript(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<Option<crate::database::TranscriptRecord>, AppError> {
+    state.db.get_transcript(&request_id)
+}
+
+/// 清空全部对话记录
+#[tauri::command]
+pub fn clear_transcripts(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.db.clear_transcripts()
+}
+
+/// 列出可重放的请求（按时间倒序）
+#[tauri::command]
+pub fn get_request_captures(
+    state: State<'_, AppState>,
+    app_type: Option<String>,
+) -> Result<Vec<crate::database::RequestCaptureSummary>, AppError> {
+    state.db.list_request_captures(app_type.as_deref())
+}
+
+/// 获取单条采集的请求（含发往上游的完整请求体与响应）
+#[tauri::command]
+pub fn get_request_capture(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<Option<crate::database::RequestCapture>, AppError> {
+    state.db.get_request_capture(&request_id)
+}
+
+/// 清空全部可重放的请求
+#[tauri::command]
+pub fn clear_request_captures(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.db.clear_request_captures()
+}
+
+/// 重放历史请求（未指定供应商时发往当前供应商）
+#[tauri::command]
+pub async fn replay_request(
+    state: State<'_, AppState>,
+    request_id: String,
+    provider_id: Option<String>,
+) -> Result<crate::proxy::replay::ReplayResult, AppError> {
+    crate::proxy::replay::replay(&state.db, &request_id, provider_id.as_deref()).await
+}
+
+/// 获取最近的影子请求结果
+#[tauri::command]
+pub fn get_shadow_results(
+    state: State<'_, AppState>,
+    app_type: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<crate::database::ShadowResult>, AppError> {
+    state
+        .db
+        .get_shadow_results(app_type.as_deref(), limit.unwrap_or(100))
+}
+
+/// 按影子供应商汇总影子流量的成功率、延迟与用量
+#[tauri::command]
+pub fn get_shadow_summary(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::database::ShadowProviderSummary>, AppError> {
+    state.db.get_shadow_summary()
+}
+
+/// 清空影子请求结果
+#[tauri::command]
+pub fn clear_shadow_results(state: State<'_, AppState>) -> Result<(), AppError> {
+    state.db.clear_shadow_results()
+}
+
 /// 获取模型定价列表
 #[tauri::command]
 pub fn get_model_pricing(state: State<'_, AppState>) -> Result<Vec<ModelPricingInfo>, AppError> {
@@ -150,6 +320,29 @@ pub fn check_provider_limits(
     state.db.check_provider_limits(&provider_id, &app_type)
 }
 
+/// 获取 Provider 各轮换密钥的累计消费
+#[tauri::command]
+pub fn get_provider_key_spend(
+    state: State<'_, AppState>,
+    provider_id: String,
+    app_type: String,
+) -> Result<Vec<crate::database::ProviderKeySpend>, AppError> {
+    state.db.get_provider_key_spend(&app_type, &provider_id)
+}
+
+/// 重置 Provider 轮换密钥的累计消费（不指定 key_id 时重置全部密钥）
+#[tauri::command]
+pub fn reset_provider_key_spend(
+    state: State<'_, AppState>,
+    provider_id: String,
+    app_type: String,
+    key_id: Option<String>,
+) -> Result<(), AppError> {
+    state
+        .db
+        .reset_provider_key_spend(&app_type, &provider_id, key_id.as_deref())
+}
+
 /// 删除模型定价
 #[tauri::command]
 pub fn delete_model_pricing(state: State<'_, AppState>, model_id: String) -> Result<(), AppError> {
@@ -166,6 +359,99 @@ pub fn delete_model_pricing(state: State<'_, AppState>, model_id: String) -> Res
     Ok(())
 }
 
+/// 导出模型定价表为 JSON（便于手动编辑后再导入）
+#[tauri::command]
+pub fn export_model_pricing_json(state: State<'_, AppState>) -> Result<String, AppError> {
+    let pricing = get_model_pricing(state)?;
+    pricing_to_json(&pricing)
+}
+
+/// 将定价列表序列化为导出格式（与 `parse_pricing_json` 互逆）
+fn pricing_to_json(pricing: &[ModelPricingInfo]) -> Result<String, AppError> {
+    serde_json::to_string_pretty(pricing).map_err(|source| AppError::JsonSerialize { source })
+}
+
+/// 从 JSON 导入模型定价表
+///
+/// JSON 为 `ModelPricingInfo` 数组；`replace` 为 true 时先清空现有定价，否则按 model_id 覆盖合并。
+/// 返回导入的条目数
+#[tauri::command]
+pub fn import_model_pricing_json(
+    state: State<'_, AppState>,
+    json: String,
+    replace: Option<bool>,
+) -> Result<usize, AppError> {
+    let entries = parse_pricing_json(&json)?;
+
+    let db = state.db.clone();
+    let mut conn = crate::database::lock_conn!(db.conn);
+    let tx = conn
+        .transaction()
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    if replace.unwrap_or(false) {
+        tx.execute("DELETE FROM model_pricing", [])
+            .map_err(|e| AppError::Database(format!("清空模型定价失败: {e}")))?;
+    }
+
+    for entry in &entries {
+        tx.execute(
+            "INSERT OR REPLACE INTO model_pricing (
+                model_id, display_name, input_cost_per_million, output_cost_per_million,
+                cache_read_cost_per_million, cache_creation_cost_per_million
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                entry.model_id,
+                entry.display_name,
+                entry.input_cost_per_million,
+                entry.output_cost_per_million,
+                entry.cache_read_cost_per_million,
+                entry.cache_creation_cost_per_million
+            ],
+        )
+        .map_err(|e| AppError::Database(format!("导入模型定价失败: {e}")))?;
+    }
+
+    tx.commit()
+        .map_err(|e| AppError::Database(format!("提交模型定价失败: {e}")))?;
+
+    log::info!("已导入 {} 条模型定价", entries.len());
+    Ok(entries.len())
+}
+
+/// 解析并校验定价 JSON：model_id 非空且价格为非负数
+fn parse_pricing_json(json: &str) -> Result<Vec<ModelPricingInfo>, AppError> {
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    let entries: Vec<ModelPricingInfo> = serde_json::from_str(json)
+        .map_err(|e| AppError::InvalidInput(format!("定价 JSON 格式错误: {e}")))?;
+
+    for entry in &entries {
+        if entry.model_id.trim().is_empty() {
+            return Err(AppError::InvalidInput("modelId 不能为空".to_string()));
+        }
+        for price in [
+            &entry.input_cost_per_million,
+            &entry.output_cost_per_million,
+            &entry.cache_read_cost_per_million,
+            &entry.cache_creation_cost_per_million,
+        ] {
+            match Decimal::from_str(price) {
+                Ok(v) if !v.is_sign_negative() => {}
+                _ => {
+                    return Err(AppError::InvalidInput(format!(
+                        "模型 {} 的价格无效: {price}",
+                        entry.model_id
+                    )))
+                }
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
 /// 模型定价信息
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -177,3 +463,70 @@ pub struct ModelPricingInfo {
     pub cache_read_cost_per_million: String,
     pub cache_creation_cost_per_million: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(model_id: &str, input_cost: &str) -> ModelPricingInfo {
+        ModelPricingInfo {
+            model_id: model_id.to_string(),
+            display_name: "Test Model".to_string(),
+            input_cost_per_million: input_cost.to_string(),
+            output_cost_per_million: "15".to_string(),
+            cache_read_cost_per_million: "0.3".to_string(),
+            cache_creation_cost_per_million: "3.75".to_string(),
+        }
+    }
+
+    fn parse(entries: &[ModelPricingInfo]) -> Result<Vec<ModelPricingInfo>, AppError> {
+        parse_pricing_json(&serde_json::to_string(entries).unwrap())
+    }
+
+    #[test]
+    fn rejects_negative_price() {
+        let err = parse(&[entry("claude-test", "-1")]).unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(msg) if msg.contains("claude-test")));
+    }
+
+    #[test]
+    fn rejects_non_numeric_price() {
+        assert!(matches!(
+            parse(&[entry("claude-test", "free")]),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            parse(&[entry("claude-test", "")]),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_model_id() {
+        assert!(matches!(
+            parse(&[entry("  ", "3")]),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_json() {
+        assert!(matches!(
+            parse_pricing_json(r#"{"modelId":"claude-test"}"#),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn export_format_round_trips() {
+        let pricing = vec![entry("claude-test", "3"), entry("gpt-test", "0")];
+        let json = pricing_to_json(&pricing).unwrap();
+        assert!(json.contains("\"inputCostPerMillion\""));
+
+        let parsed = parse_pricing_json(&json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::to_value(&pricing).unwrap()
+        );
+    }
+}
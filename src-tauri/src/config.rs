@@ -71,6 +71,9 @@ pub fn get_app_config_dir() -> PathBuf {
     if let Some(custom) = crate::app_store::get_app_config_dir_override() {
         return custom;
     }
+    if let Some(fallback) = crate::data_dir::get_fallback_dir() {
+        return fallback;
+    }
 
     dirs::home_dir()
         .expect("无法获取用户主目录")
@@ -125,7 +128,9 @@ pub fn write_json_file<T: Serialize>(path: &Path, data: &T) -> Result<(), AppErr
     let json =
         serde_json::to_string_pretty(data).map_err(|e| AppError::JsonSerialize { source: e })?;
 
-    atomic_write(path, json.as_bytes())
+    atomic_write(path, json.as_bytes())?;
+    crate::services::settings_history::record_claude_settings_write(path, json.as_bytes());
+    Ok(())
 }
 
 /// 原子写入文本文件（用于 TOML/纯文本）
@@ -0,0 +1,183 @@
+//! 无界面守护进程模式（`cc-switch --daemon`）
+//!
+//! 仅启动数据库、出站 HTTP 客户端与本地代理（含 Live 配置接管），不创建窗口与托盘，
+//! 适用于服务器、WSL、devcontainer 等没有桌面环境的场景。
+//!
+//! - 数据目录与 GUI 相同（默认 `~/.cc-switch`）；GUI 中通过 Store 设置的目录覆盖不会生效
+//! - 启动时恢复上次异常退出残留的接管状态，按 `proxy_config.enabled` 恢复各应用接管，
+//!   没有需要恢复的应用时也会启动代理
+//! - 日志输出到 stderr 与 `<app_config_dir>/logs/cc-switch-daemon.log`
+//! - 收到 Ctrl+C / SIGTERM 或 `cc-switch proxy stop`（本机控制接口）后恢复 Live 配置并退出
+//!   （保留代理状态，下次启动自动恢复）
+
+use crate::database::Database;
+use crate::store::AppState;
+use once_cell::sync::Lazy;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// 启用守护进程模式的命令行参数
+pub const DAEMON_FLAG: &str = "--daemon";
+
+/// 命令行参数中是否包含 [`DAEMON_FLAG`]（第一个参数为程序路径，忽略）
+pub fn is_daemon_requested(args: impl IntoIterator<Item = String>) -> bool {
+    args.into_iter().skip(1).any(|arg| arg == DAEMON_FLAG)
+}
+
+/// 守护进程日志：同时写入 stderr 与日志文件
+struct DaemonLogger {
+    file: Option<Mutex<std::fs::File>>,
+}
+
+impl log::Log for DaemonLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "[{}][{}][{}] {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let _ = std::io::stderr().write_all(line.as_bytes());
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.write_all(line.as_bytes());
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = file.lock() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+fn init_logger() {
+    let log_dir = crate::panic_hook::get_log_dir();
+    let file = std::fs::create_dir_all(&log_dir)
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(log_dir.join("cc-switch-daemon.log"))
+        })
+        .ok()
+        .map(Mutex::new);
+
+    if log::set_boxed_logger(Box::new(DaemonLogger { file })).is_ok() {
+        log::set_max_level(log::LevelFilter::Info);
+    }
+}
+
+/// 通过本机控制接口发起的退出请求
+static SHUTDOWN_REQUEST: Lazy<Notify> = Lazy::new(Notify::new);
+
+/// 请求守护进程正常退出；非守护进程模式（GUI）时返回 false
+pub(crate) fn request_shutdown() -> bool {
+    if crate::store::headless_state().is_none() {
+        return false;
+    }
+    SHUTDOWN_REQUEST.notify_one();
+    true
+}
+
+/// 等待 Ctrl+C（Unix 下同时监听 SIGTERM）
+async fn wait_for_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        log::error!("监听退出信号失败: {e}");
+        std::future::pending::<()>().await;
+    }
+}
+
+/// 等待退出信号或控制接口的退出请求
+async fn wait_for_shutdown() {
+    tokio::select! {
+        _ = wait_for_signal() => {}
+        _ = SHUTDOWN_REQUEST.notified() => {}
+    }
+}
+
+/// 无界面运行（守护进程 / 命令行）前的初始化：panic 日志与数据目录
+pub(crate) fn prepare_headless() {
+    crate::panic_hook::setup_panic_hook();
+    // 主目录只读时改用可写的回退目录
+    crate::data_dir::detect_writable_app_config_dir();
+    crate::panic_hook::init_app_config_dir(crate::config::get_app_config_dir());
+}
+
+/// 以守护进程模式运行，直到收到退出信号
+pub fn run() {
+    prepare_headless();
+    init_logger();
+
+    log::info!(
+        "以守护进程模式启动，数据目录: {}",
+        crate::config::get_app_config_dir().display()
+    );
+
+    let db = match Database::init() {
+        Ok(db) => Arc::new(db),
+        Err(e) => {
+            log::error!("初始化数据库失败: {e}");
+            std::process::exit(1);
+        }
+    };
+    let state = Arc::new(AppState::new(db));
+    crate::store::set_headless_state(state.clone());
+    crate::init_global_http_client(&state.db);
+
+    tauri::async_runtime::block_on(async move {
+        crate::recover_live_configs_after_crash(&state).await;
+        crate::restore_proxy_state_on_startup(&state).await;
+
+        if !state.proxy_service.is_running().await {
+            if let Err(e) = state.proxy_service.start().await {
+                log::error!("启动代理服务器失败: {e}");
+                std::process::exit(1);
+            }
+        }
+        if let Ok(status) = state.proxy_service.get_status().await {
+            log::info!("代理服务器运行于 {}:{}", status.address, status.port);
+        }
+
+        wait_for_shutdown().await;
+        log::info!("收到退出信号，开始清理...");
+        crate::cleanup_proxy_before_exit(&state).await;
+        log::info!("清理完成，退出守护进程");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_daemon_flag_after_program_path() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(is_daemon_requested(args(&["cc-switch", "--daemon"])));
+        assert!(!is_daemon_requested(args(&["cc-switch"])));
+        assert!(!is_daemon_requested(args(&["--daemon"])));
+    }
+}
@@ -0,0 +1,194 @@
+//! 数据目录可写性检测
+//!
+//! 部分企业电脑 / 沙箱环境中用户主目录是只读的，`~/.cc-switch` 无法创建。
+//! 启动时检测默认数据目录是否可写，不可写时依次回退到系统本地数据目录、临时目录，
+//! 数据库、配置与日志都写入回退目录。用户通过 Store 显式覆盖的目录不参与回退。
+//! 临时目录可能被其他用户共享，回退目录必须归当前用户所有（不能是符号链接），Unix 下权限收紧为 0700。
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+/// 当前生效的回退目录（None 表示默认目录可写）
+static FALLBACK_DIR: OnceLock<RwLock<Option<PathBuf>>> = OnceLock::new();
+
+fn fallback_cache() -> &'static RwLock<Option<PathBuf>> {
+    FALLBACK_DIR.get_or_init(|| RwLock::new(None))
+}
+
+/// 获取当前生效的回退目录
+pub fn get_fallback_dir() -> Option<PathBuf> {
+    fallback_cache().read().ok()?.clone()
+}
+
+/// 默认应用数据目录（~/.cc-switch）
+pub fn default_app_config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join(".cc-switch"))
+}
+
+/// 目录是否可写（不存在时尝试创建，并写入探测文件）
+pub fn is_dir_writable(dir: &Path) -> bool {
+    if std::fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(format!(".write-test-{}", std::process::id()));
+    match std::fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// 回退目录候选（按优先级）
+fn fallback_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = dirs::data_local_dir() {
+        candidates.push(dir.join("cc-switch"));
+    }
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "user".to_string());
+    candidates.push(std::env::temp_dir().join(format!("cc-switch-{user}")));
+    candidates
+}
+
+/// 回退目录是否归当前用户所有，是则把权限收紧为 0700
+///
+/// 以在目录中新建的探测文件的属主作为当前用户，目录属主不同（如他人预先创建的
+/// `/tmp/cc-switch-<用户名>`）或为符号链接时拒绝使用
+#[cfg(unix)]
+fn ensure_private_dir(dir: &Path) -> bool {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let Ok(meta) = std::fs::symlink_metadata(dir) else {
+        return false;
+    };
+    if !meta.is_dir() {
+        return false;
+    }
+    let probe = dir.join(format!(".owner-test-{}", std::process::id()));
+    let owner = std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::metadata(&probe))
+        .map(|probe_meta| probe_meta.uid());
+    let _ = std::fs::remove_file(&probe);
+    if owner.ok() != Some(meta.uid()) {
+        log::warn!("回退目录 {} 不属于当前用户，跳过", dir.display());
+        return false;
+    }
+    meta.mode() & 0o077 == 0
+        || std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)).is_ok()
+}
+
+#[cfg(not(unix))]
+fn ensure_private_dir(_dir: &Path) -> bool {
+    true
+}
+
+/// 在默认目录不可写时选出第一个可写且归当前用户所有的候选目录
+fn choose_dir(default_dir: Option<&Path>, candidates: &[PathBuf]) -> Option<PathBuf> {
+    if default_dir.is_some_and(is_dir_writable) {
+        return None;
+    }
+    candidates
+        .iter()
+        .find(|dir| is_dir_writable(dir) && ensure_private_dir(dir))
+        .cloned()
+}
+
+/// 检测默认数据目录并在不可写时启用回退目录
+///
+/// 应在刷新 Store 覆盖配置之后、初始化日志与数据库之前调用
+pub fn detect_writable_app_config_dir() -> Option<PathBuf> {
+    if crate::app_store::get_app_config_dir_override().is_some() {
+        return None;
+    }
+
+    let default_dir = default_app_config_dir();
+    let chosen = choose_dir(default_dir.as_deref(), &fallback_candidates());
+    match (&chosen, &default_dir) {
+        (Some(dir), _) => log::warn!(
+            "默认数据目录 {default_dir:?} 不可写，改用 {}",
+            dir.display()
+        ),
+        (None, Some(dir)) if !is_dir_writable(dir) => {
+            log::error!(
+                "默认数据目录 {} 不可写，且没有可用的回退目录",
+                dir.display()
+            )
+        }
+        _ => {}
+    }
+    if let Ok(mut guard) = fallback_cache().write() {
+        *guard = chosen.clone();
+    }
+    chosen
+}
+
+/// 当前生效的数据路径
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataPaths {
+    pub app_config_dir: String,
+    pub database_path: String,
+    pub log_dir: String,
+    pub debug_log_dir: String,
+    /// 默认目录不可写时为 true
+    pub using_fallback: bool,
+    pub default_app_config_dir: Option<String>,
+}
+
+/// 汇总当前生效的数据路径
+pub fn current_paths() -> DataPaths {
+    let app_config_dir = crate::config::get_app_config_dir();
+    let display = |p: &Path| p.to_string_lossy().to_string();
+    DataPaths {
+        database_path: display(&app_config_dir.join("cc-switch.db")),
+        log_dir: display(&crate::panic_hook::get_log_dir()),
+        debug_log_dir: display(&crate::proxy::log_retention::debug_log_dir()),
+        using_fallback: get_fallback_dir().is_some(),
+        default_app_config_dir: default_app_config_dir().map(|p| display(&p)),
+        app_config_dir: display(&app_config_dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_first_writable_candidate() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        // 以普通文件作为父目录，保证即使以 root 运行也无法创建
+        let blocker = tmp.path().join("home");
+        std::fs::write(&blocker, b"").unwrap();
+        let unwritable = blocker.join(".cc-switch");
+        let candidates = vec![blocker.join("other"), tmp.path().join("fallback")];
+
+        assert_eq!(choose_dir(Some(tmp.path()), &candidates), None);
+        assert_eq!(
+            choose_dir(Some(&unwritable), &candidates),
+            Some(tmp.path().join("fallback"))
+        );
+        assert_eq!(choose_dir(None, &candidates[..1]), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restricts_fallback_dir_permissions_and_rejects_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let shared = tmp.path().join("cc-switch-user");
+        std::fs::create_dir(&shared).unwrap();
+        std::fs::set_permissions(&shared, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(ensure_private_dir(&shared));
+        let mode = std::fs::metadata(&shared).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&shared, &link).unwrap();
+        assert!(!ensure_private_dir(&link));
+    }
+}
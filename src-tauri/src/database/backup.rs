@@ -2,9 +2,10 @@
 //!
 //! 提供 SQL 导出/导入和二进制快照备份功能。
 
-use super::{lock_conn, Database, DB_BACKUP_RETAIN};
+use super::{lock_conn, secrets, Database, DB_BACKUP_RETAIN};
 use crate::config::get_app_config_dir;
 use crate::error::AppError;
+use crate::provider::ProviderMeta;
 use chrono::Utc;
 use rusqlite::backup::Backup;
 use rusqlite::types::ValueRef;
@@ -19,6 +20,7 @@ impl Database {
     /// 导出为 SQLite 兼容的 SQL 文本
     pub fn export_sql(&self, target_path: &Path) -> Result<(), AppError> {
         let snapshot = self.snapshot_to_memory()?;
+        Self::resolve_snapshot_secrets(&snapshot)?;
         let dump = Self::dump_sql(&snapshot)?;
 
         if let Some(parent) = target_path.parent() {
@@ -72,6 +74,11 @@ impl Database {
                 .map_err(|e| AppError::Database(e.to_string()))?;
         }
 
+        // 导出时密钥已解析为明文，导入后迁移到本机钥匙串
+        if let Err(e) = self.migrate_secrets_to_keychain() {
+            log::warn!("迁移导入的供应商密钥到系统钥匙串失败: {e}");
+        }
+
         let backup_id = backup_path
             .and_then(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
             .unwrap_or_default();
@@ -96,6 +103,57 @@ impl Database {
         Ok(snapshot)
     }
 
+    /// 把快照中的钥匙串引用解析为明文，使导出的 SQL 可在其他设备导入
+    ///
+    /// 钥匙串无法读取时拒绝导出，避免生成缺少密钥的备份
+    fn resolve_snapshot_secrets(snapshot: &Connection) -> Result<(), AppError> {
+        let rows: Vec<(String, String, String, String)> = {
+            let mut stmt = snapshot
+                .prepare("SELECT id, app_type, settings_config, meta FROM providers")
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })
+                .map_err(|e| AppError::Database(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            rows
+        };
+
+        for (id, app_type, settings, meta) in rows {
+            let (Ok(mut settings), Ok(mut meta)) = (
+                serde_json::from_str::<serde_json::Value>(&settings),
+                serde_json::from_str::<ProviderMeta>(&meta),
+            ) else {
+                continue;
+            };
+            if !secrets::has_references(&settings, &meta) {
+                continue;
+            }
+            secrets::resolve(&mut settings, &mut meta);
+            if secrets::has_references(&settings, &meta) {
+                return Err(AppError::localized(
+                    "backup.sql.keychain_unavailable",
+                    format!("无法从系统钥匙串读取供应商 {id} 的密钥，已取消导出"),
+                    format!("Failed to read keys of provider {id} from the OS keychain; export cancelled"),
+                ));
+            }
+            snapshot
+                .execute(
+                    "UPDATE providers SET settings_config = ?1, meta = ?2 WHERE id = ?3 AND app_type = ?4",
+                    rusqlite::params![
+                        super::to_json_string(&settings)?,
+                        super::to_json_string(&meta)?,
+                        id,
+                        app_type
+                    ],
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     fn validate_cc_switch_sql_export(sql: &str) -> Result<(), AppError> {
         let trimmed = sql.trim_start();
         if trimmed.starts_with(CC_SWITCH_SQL_EXPORT_HEADER) {
@@ -0,0 +1,117 @@
+//! A/B 实验结果 DAO
+//!
+//! 请求日志的 `experiment` 列记录 `<实验 ID>:<组>`，这里按组汇总实验结果。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use serde::Serialize;
+
+/// 单个实验分组的结果
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentArmStats {
+    /// `treatment` / `control`
+    pub arm: String,
+    pub requests: u64,
+    pub errors: u64,
+    /// 错误率（0-1）
+    pub error_rate: f64,
+    pub avg_latency_ms: f64,
+    pub total_cost_usd: f64,
+    pub avg_cost_usd: f64,
+    pub cache_read_tokens: u64,
+}
+
+impl Database {
+    /// 按分组统计实验结果
+    pub fn get_experiment_results(
+        &self,
+        experiment_id: &str,
+    ) -> Result<Vec<ExperimentArmStats>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let prefix = format!("{experiment_id}:");
+        let mut stmt = conn
+            .prepare(
+                "SELECT substr(experiment, length(?1) + 1) AS arm,
+                        COUNT(*),
+                        SUM(CASE WHEN status_code >= 400 THEN 1 ELSE 0 END),
+                        COALESCE(AVG(latency_ms), 0),
+                        COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0),
+                        COALESCE(SUM(cache_read_tokens), 0)
+                 FROM proxy_request_logs
+                 WHERE substr(experiment, 1, length(?1)) = ?1
+                 GROUP BY arm
+                 ORDER BY arm DESC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map([&prefix], |row| {
+                let requests = row.get::<_, i64>(1)?.max(0) as u64;
+                let errors = row.get::<_, i64>(2)?.max(0) as u64;
+                let total_cost_usd: f64 = row.get(4)?;
+                let per_request = |value: f64| {
+                    if requests == 0 {
+                        0.0
+                    } else {
+                        value / requests as f64
+                    }
+                };
+                Ok(ExperimentArmStats {
+                    arm: row.get(0)?,
+                    requests,
+                    errors,
+                    error_rate: per_request(errors as f64),
+                    avg_latency_ms: row.get(3)?,
+                    total_cost_usd,
+                    avg_cost_usd: per_request(total_cost_usd),
+                    cache_read_tokens: row.get::<_, i64>(5)?.max(0) as u64,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregates_results_per_arm() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, experiment, status, latency, cost) in [
+                ("r1", "cache:treatment", 200, 100, "0.2"),
+                ("r2", "cache:treatment", 500, 300, "0"),
+                ("r3", "cache:control", 200, 50, "0.5"),
+                ("r4", "cache2:control", 200, 10, "9"),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model,
+                     input_tokens, output_tokens, total_cost_usd, latency_ms, status_code,
+                     created_at, experiment)
+                     VALUES (?1, 'p1', 'claude', 'm', 100, 50, ?2, ?3, ?4, 0, ?5)",
+                    rusqlite::params![id, cost, latency, status, experiment],
+                )
+                .unwrap();
+            }
+        }
+
+        let results = db.get_experiment_results("cache")?;
+        assert_eq!(results.len(), 2);
+        let treatment = &results[0];
+        assert_eq!(treatment.arm, "treatment");
+        assert_eq!((treatment.requests, treatment.errors), (2, 1));
+        assert!((treatment.error_rate - 0.5).abs() < 1e-9);
+        assert!((treatment.avg_latency_ms - 200.0).abs() < 1e-9);
+        assert!((treatment.avg_cost_usd - 0.1).abs() < 1e-9);
+        let control = &results[1];
+        assert_eq!((control.arm.as_str(), control.requests), ("control", 1));
+        assert!((control.total_cost_usd - 0.5).abs() < 1e-9);
+        Ok(())
+    }
+}
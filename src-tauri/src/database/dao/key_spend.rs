@@ -0,0 +1,151 @@
+//! 按密钥消费统计 DAO
+//!
+//! 记录多密钥供应商中每个密钥的累计消费，用于额度判断与轮换
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// 单个密钥的累计消费
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderKeySpend {
+    pub key_id: String,
+    /// 累计消费（USD）
+    pub spent_usd: String,
+    pub request_count: u64,
+    pub updated_at: i64,
+}
+
+impl Database {
+    /// 获取供应商各密钥的累计消费
+    pub fn get_provider_key_spend(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<Vec<ProviderKeySpend>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT key_id, spent_usd, request_count, updated_at
+                 FROM provider_key_spend
+                 WHERE app_type = ?1 AND provider_id = ?2
+                 ORDER BY key_id",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map([app_type, provider_id], |row| {
+                Ok(ProviderKeySpend {
+                    key_id: row.get(0)?,
+                    spent_usd: row.get(1)?,
+                    request_count: row.get::<_, i64>(2)? as u64,
+                    updated_at: row.get(3)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 累加密钥消费
+    pub fn add_provider_key_spend(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        key_id: &str,
+        cost: Decimal,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        // 金额以 TEXT 存储，在 Rust 侧用 Decimal 累加以避免浮点误差
+        let current: Option<String> = conn
+            .query_row(
+                "SELECT spent_usd FROM provider_key_spend
+                 WHERE app_type = ?1 AND provider_id = ?2 AND key_id = ?3",
+                [app_type, provider_id, key_id],
+                |row| row.get(0),
+            )
+            .ok();
+        let spent = current
+            .and_then(|s| Decimal::from_str(&s).ok())
+            .unwrap_or(Decimal::ZERO)
+            + cost;
+
+        conn.execute(
+            "INSERT INTO provider_key_spend
+             (app_type, provider_id, key_id, spent_usd, request_count, updated_at)
+             VALUES (?1, ?2, ?3, ?4, 1, ?5)
+             ON CONFLICT(app_type, provider_id, key_id) DO UPDATE SET
+                spent_usd = excluded.spent_usd,
+                request_count = request_count + 1,
+                updated_at = excluded.updated_at",
+            rusqlite::params![
+                app_type,
+                provider_id,
+                key_id,
+                spent.to_string(),
+                chrono::Utc::now().timestamp(),
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// 重置密钥消费（`key_id` 为空时重置该供应商的全部密钥）
+    pub fn reset_provider_key_spend(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+        key_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+
+        match key_id {
+            Some(key_id) => conn.execute(
+                "DELETE FROM provider_key_spend
+                 WHERE app_type = ?1 AND provider_id = ?2 AND key_id = ?3",
+                [app_type, provider_id, key_id],
+            ),
+            None => conn.execute(
+                "DELETE FROM provider_key_spend WHERE app_type = ?1 AND provider_id = ?2",
+                [app_type, provider_id],
+            ),
+        }
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates_and_resets_spend() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        db.add_provider_key_spend("claude", "p1", "k1", Decimal::from_str("0.25").unwrap())?;
+        db.add_provider_key_spend("claude", "p1", "k1", Decimal::from_str("0.5").unwrap())?;
+        db.add_provider_key_spend("claude", "p1", "k2", Decimal::ONE)?;
+
+        let spend = db.get_provider_key_spend("claude", "p1")?;
+        assert_eq!(spend.len(), 2);
+        assert_eq!(spend[0].key_id, "k1");
+        assert_eq!(spend[0].spent_usd, "0.75");
+        assert_eq!(spend[0].request_count, 2);
+
+        db.reset_provider_key_spend("claude", "p1", Some("k1"))?;
+        assert_eq!(db.get_provider_key_spend("claude", "p1")?.len(), 1);
+
+        db.reset_provider_key_spend("claude", "p1", None)?;
+        assert!(db.get_provider_key_spend("claude", "p1")?.is_empty());
+        Ok(())
+    }
+}
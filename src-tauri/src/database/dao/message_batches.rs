@@ -0,0 +1,51 @@
+//! Message Batches DAO
+//!
+//! 批处理 ID 只在创建它的供应商处有效，`proxy_message_batches` 记录每个批处理所属的供应商，
+//! 切换供应商后轮询状态、下载结果仍会发往原供应商。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use rusqlite::OptionalExtension;
+
+impl Database {
+    /// 记录批处理所属的供应商
+    pub fn record_message_batch(&self, batch_id: &str, provider_id: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO proxy_message_batches (batch_id, provider_id, created_at)
+             VALUES (?1, ?2, ?3)",
+            rusqlite::params![batch_id, provider_id, chrono::Utc::now().timestamp()],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 查询批处理所属的供应商（非经代理创建的批处理返回 None）
+    pub fn get_message_batch_provider(&self, batch_id: &str) -> Result<Option<String>, AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.query_row(
+            "SELECT provider_id FROM proxy_message_batches WHERE batch_id = ?1",
+            [batch_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_batch_provider() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        assert_eq!(db.get_message_batch_provider("msgbatch_1")?, None);
+        db.record_message_batch("msgbatch_1", "provider-a")?;
+        assert_eq!(
+            db.get_message_batch_provider("msgbatch_1")?.as_deref(),
+            Some("provider-a")
+        );
+        Ok(())
+    }
+}
@@ -2,16 +2,38 @@
 //!
 //! Database access operations for each domain
 
+pub mod experiments;
 pub mod failover;
+pub mod key_spend;
 pub mod mcp;
+pub mod message_batches;
 pub mod prompts;
 pub mod providers;
 pub mod proxy;
+pub mod request_captures;
+pub mod request_journal;
 pub mod settings;
+pub mod shadow_results;
 pub mod skills;
 pub mod stream_check;
+pub mod team_members;
+pub mod transcripts;
 pub mod universal_providers;
 
 // 所有 DAO 方法都通过 Database impl 提供，无需单独导出
 // 导出 FailoverQueueItem 供外部使用
 pub use failover::FailoverQueueItem;
+// 导出实验结果类型供命令层使用
+pub use experiments::ExperimentArmStats;
+// 导出 ProviderKeySpend 供命令层使用
+pub use key_spend::ProviderKeySpend;
+// 导出请求采集类型供代理与命令层使用
+pub use request_captures::{RequestCapture, RequestCaptureSummary};
+// 导出影子流量结果类型供代理与命令层使用
+pub use shadow_results::{ShadowProviderSummary, ShadowResult};
+// 导出 JournalEntry 供代理与命令层使用
+pub use request_journal::JournalEntry;
+// 导出对话记录类型供代理与命令层使用
+pub use transcripts::{PaginatedTranscripts, TranscriptRecord, TranscriptSearchQuery};
+// 导出团队网关成员类型供代理与命令层使用
+pub use team_members::{TeamMember, TeamMemberUsage};
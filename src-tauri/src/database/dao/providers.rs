@@ -2,7 +2,7 @@
 //!
 //! 提供供应商（Provider）的 CRUD 操作。
 
-use crate::database::{lock_conn, Database};
+use crate::database::{lock_conn, secrets, Database};
 use crate::error::AppError;
 use crate::provider::{Provider, ProviderMeta};
 use indexmap::IndexMap;
@@ -99,7 +99,13 @@ impl Database {
 
             providers.insert(id, provider);
         }
+        drop(stmt);
+        drop(conn);
 
+        // 钥匙串访问可能弹出授权窗口，释放数据库锁后再解析密钥
+        for provider in providers.values_mut() {
+            resolve_secrets(provider);
+        }
         Ok(providers)
     }
 
@@ -166,9 +172,13 @@ impl Database {
                 })
             },
         );
+        drop(conn);
 
         match result {
-            Ok(provider) => Ok(Some(provider)),
+            Ok(mut provider) => {
+                resolve_secrets(&mut provider);
+                Ok(Some(provider))
+            }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(AppError::Database(e.to_string())),
         }
@@ -179,15 +189,25 @@ impl Database {
     /// 注意：更新模式下不同步 endpoints，因为编辑模式下端点通过单独的 API 管理
     /// （add_custom_endpoint / remove_custom_endpoint），避免覆盖用户的修改。
     pub fn save_provider(&self, app_type: &str, provider: &Provider) -> Result<(), AppError> {
+        // 处理 meta：取出 endpoints 以便单独处理
+        let mut settings_config = provider.settings_config.clone();
+        let mut meta_clone = provider.meta.clone().unwrap_or_default();
+        let endpoints = std::mem::take(&mut meta_clone.custom_endpoints);
+        // 写入前把密钥移入系统钥匙串（在获取数据库锁之前完成）
+        if self.secrets_in_keychain {
+            secrets::store(
+                app_type,
+                &provider.id,
+                &mut settings_config,
+                &mut meta_clone,
+            );
+        }
+
         let mut conn = lock_conn!(self.conn);
         let tx = conn
             .transaction()
             .map_err(|e| AppError::Database(e.to_string()))?;
 
-        // 处理 meta：取出 endpoints 以便单独处理
-        let mut meta_clone = provider.meta.clone().unwrap_or_default();
-        let endpoints = std::mem::take(&mut meta_clone.custom_endpoints);
-
         // 检查是否存在（用于判断新增/更新，以及保留 is_current 和 in_failover_queue）
         let existing: Option<(bool, bool)> = tx
             .query_row(
@@ -220,7 +240,7 @@ impl Database {
                 WHERE id = ?13 AND app_type = ?14",
                 params![
                     provider.name,
-                    serde_json::to_string(&provider.settings_config).map_err(|e| {
+                    serde_json::to_string(&settings_config).map_err(|e| {
                         AppError::Database(format!("Failed to serialize settings_config: {e}"))
                     })?,
                     provider.website_url,
@@ -251,7 +271,7 @@ impl Database {
                     provider.id,
                     app_type,
                     provider.name,
-                    serde_json::to_string(&provider.settings_config)
+                    serde_json::to_string(&settings_config)
                         .map_err(|e| AppError::Database(format!("Failed to serialize settings_config: {e}")))?,
                     provider.website_url,
                     provider.category,
@@ -286,11 +306,25 @@ impl Database {
     /// 删除供应商
     pub fn delete_provider(&self, app_type: &str, id: &str) -> Result<(), AppError> {
         let conn = lock_conn!(self.conn);
+        let stored: Option<(String, String)> = conn
+            .query_row(
+                "SELECT settings_config, meta FROM providers WHERE id = ?1 AND app_type = ?2",
+                params![id, app_type],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
         conn.execute(
             "DELETE FROM providers WHERE id = ?1 AND app_type = ?2",
             params![id, app_type],
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
+        drop(conn);
+
+        if let Some((settings, meta)) = stored {
+            let settings = serde_json::from_str(&settings).unwrap_or_default();
+            let meta = serde_json::from_str(&meta).unwrap_or_default();
+            secrets::delete(&settings, &meta);
+        }
         Ok(())
     }
 
@@ -326,13 +360,18 @@ impl Database {
         provider_id: &str,
         settings_config: &serde_json::Value,
     ) -> Result<(), AppError> {
+        let mut settings_config = settings_config.clone();
+        if self.secrets_in_keychain {
+            let mut meta = ProviderMeta::default();
+            secrets::store(app_type, provider_id, &mut settings_config, &mut meta);
+        }
         let conn = lock_conn!(self.conn);
         conn.execute(
             "UPDATE providers SET settings_config = ?1 WHERE id = ?2 AND app_type = ?3",
             params![
-                serde_json::to_string(settings_config).map_err(|e| AppError::Database(format!(
-                    "Failed to serialize settings_config: {e}"
-                )))?,
+                serde_json::to_string(&settings_config).map_err(|e| AppError::Database(
+                    format!("Failed to serialize settings_config: {e}")
+                ))?,
                 provider_id,
                 app_type
             ],
@@ -341,6 +380,62 @@ impl Database {
         Ok(())
     }
 
+    /// 把数据库中仍为明文的供应商密钥迁移到系统钥匙串，返回迁移的供应商数量
+    pub fn migrate_secrets_to_keychain(&self) -> Result<usize, AppError> {
+        if !self.secrets_in_keychain {
+            return Ok(0);
+        }
+        let rows: Vec<(String, String, String, String)> = {
+            let conn = lock_conn!(self.conn);
+            let mut stmt = conn
+                .prepare("SELECT id, app_type, settings_config, meta FROM providers")
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+                })
+                .map_err(|e| AppError::Database(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| AppError::Database(e.to_string()))?;
+            rows
+        };
+
+        let mut migrated = 0;
+        for (id, app_type, settings, meta) in rows {
+            let (Ok(mut settings), Ok(mut meta)) = (
+                serde_json::from_str::<serde_json::Value>(&settings),
+                serde_json::from_str::<ProviderMeta>(&meta),
+            ) else {
+                continue;
+            };
+            if !secrets::has_plaintext(&settings, &meta) {
+                continue;
+            }
+            secrets::store(&app_type, &id, &mut settings, &mut meta);
+            if secrets::has_plaintext(&settings, &meta) {
+                // 钥匙串不可用，后续供应商同样会失败
+                break;
+            }
+            let conn = lock_conn!(self.conn);
+            conn.execute(
+                "UPDATE providers SET settings_config = ?1, meta = ?2 WHERE id = ?3 AND app_type = ?4",
+                params![
+                    serde_json::to_string(&settings).map_err(|e| AppError::Database(format!(
+                        "Failed to serialize settings_config: {e}"
+                    )))?,
+                    serde_json::to_string(&meta).map_err(|e| AppError::Database(format!(
+                        "Failed to serialize meta: {e}"
+                    )))?,
+                    id,
+                    app_type
+                ],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+            migrated += 1;
+        }
+        Ok(migrated)
+    }
+
     /// 添加自定义端点
     pub fn add_custom_endpoint(
         &self,
@@ -373,3 +468,9 @@ impl Database {
         Ok(())
     }
 }
+
+/// 解析供应商中的钥匙串引用（需在释放数据库锁后调用）
+fn resolve_secrets(provider: &mut Provider) {
+    let meta = provider.meta.get_or_insert_with(ProviderMeta::default);
+    secrets::resolve(&mut provider.settings_config, meta);
+}
@@ -0,0 +1,260 @@
+//! 请求采集 DAO（请求重放）
+//!
+//! `proxy_request_captures` 保存启用采集后发往代理的请求体与上游响应，按时间只保留最近 N 条。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use serde::Serialize;
+
+const CAPTURE_COLUMNS: &str = "request_id, app_type, endpoint, provider_id, model, created_at, \
+     body, status_code, duration_ms, response_body";
+
+fn capture_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<RequestCapture> {
+    Ok(RequestCapture {
+        request_id: row.get(0)?,
+        app_type: row.get(1)?,
+        endpoint: row.get(2)?,
+        provider_id: row.get(3)?,
+        model: row.get(4)?,
+        created_at: row.get(5)?,
+        body: row.get(6)?,
+        status_code: row.get(7)?,
+        duration_ms: row.get::<_, Option<i64>>(8)?.map(|ms| ms as u64),
+        response_body: row.get(9)?,
+    })
+}
+
+/// 一条采集的请求
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestCapture {
+    pub request_id: String,
+    pub app_type: String,
+    /// 代理端点（如 `/v1/messages`）
+    pub endpoint: String,
+    /// 原始请求选中的供应商
+    pub provider_id: String,
+    pub model: String,
+    pub created_at: i64,
+    /// 请求体 JSON
+    pub body: String,
+    /// 上游响应状态码（请求未完成时为空）
+    pub status_code: Option<u16>,
+    pub duration_ms: Option<u64>,
+    /// 响应体原文（流式响应为重组后的 SSE 文本）
+    pub response_body: Option<String>,
+}
+
+/// 采集列表项（不含请求体）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestCaptureSummary {
+    pub request_id: String,
+    pub app_type: String,
+    pub endpoint: String,
+    pub provider_id: String,
+    pub model: String,
+    pub created_at: i64,
+    pub status_code: Option<u16>,
+}
+
+impl Database {
+    /// 保存采集的请求，并删除超出 `max_entries` 的最早记录
+    pub fn insert_request_capture(
+        &self,
+        capture: &RequestCapture,
+        max_entries: u32,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO proxy_request_captures
+             (request_id, app_type, endpoint, provider_id, model, body, created_at,
+              status_code, duration_ms, response_body)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                capture.request_id,
+                capture.app_type,
+                capture.endpoint,
+                capture.provider_id,
+                capture.model,
+                capture.body,
+                capture.created_at,
+                capture.status_code,
+                capture.duration_ms.map(|ms| ms as i64),
+                capture.response_body,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM proxy_request_captures WHERE request_id NOT IN (
+                SELECT request_id FROM proxy_request_captures
+                ORDER BY created_at DESC, rowid DESC LIMIT ?1
+            )",
+            [max_entries],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 记录采集请求的上游响应（`provider_id` 为实际响应的供应商，故障转移后可能与原始供应商不同）
+    pub fn record_request_capture_response(
+        &self,
+        request_id: &str,
+        provider_id: &str,
+        status_code: u16,
+        duration_ms: u64,
+        response_body: &str,
+    ) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "UPDATE proxy_request_captures
+             SET provider_id = ?2, status_code = ?3, duration_ms = ?4, response_body = ?5
+             WHERE request_id = ?1",
+            rusqlite::params![
+                request_id,
+                provider_id,
+                status_code,
+                duration_ms as i64,
+                response_body
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 按时间倒序列出采集的请求
+    pub fn list_request_captures(
+        &self,
+        app_type: Option<&str>,
+    ) -> Result<Vec<RequestCaptureSummary>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT request_id, app_type, endpoint, provider_id, model, created_at, status_code
+                 FROM proxy_request_captures
+                 WHERE ?1 IS NULL OR app_type = ?1
+                 ORDER BY created_at DESC, rowid DESC",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([app_type], |row| {
+                Ok(RequestCaptureSummary {
+                    request_id: row.get(0)?,
+                    app_type: row.get(1)?,
+                    endpoint: row.get(2)?,
+                    provider_id: row.get(3)?,
+                    model: row.get(4)?,
+                    created_at: row.get(5)?,
+                    status_code: row.get(6)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 获取单条采集的请求
+    pub fn get_request_capture(
+        &self,
+        request_id: &str,
+    ) -> Result<Option<RequestCapture>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            &format!("SELECT {CAPTURE_COLUMNS} FROM proxy_request_captures WHERE request_id = ?1"),
+            [request_id],
+            capture_from_row,
+        );
+        match result {
+            Ok(capture) => Ok(Some(capture)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    /// 按时间正序获取时间范围内（Unix 秒，闭区间）的完整采集记录
+    pub fn get_request_captures_between(
+        &self,
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<Vec<RequestCapture>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {CAPTURE_COLUMNS} FROM proxy_request_captures
+                 WHERE (?1 IS NULL OR created_at >= ?1) AND (?2 IS NULL OR created_at <= ?2)
+                 ORDER BY created_at ASC, rowid ASC"
+            ))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([start, end], capture_from_row)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 清空全部采集的请求
+    pub fn clear_request_captures(&self) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM proxy_request_captures", [])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(id: &str, created_at: i64) -> RequestCapture {
+        RequestCapture {
+            request_id: id.to_string(),
+            app_type: "claude".to_string(),
+            endpoint: "/v1/messages".to_string(),
+            provider_id: "p1".to_string(),
+            model: "claude-sonnet".to_string(),
+            created_at,
+            body: r#"{"model":"claude-sonnet"}"#.to_string(),
+            status_code: None,
+            duration_ms: None,
+            response_body: None,
+        }
+    }
+
+    #[test]
+    fn keeps_most_recent_captures() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        for (i, id) in ["a", "b", "c"].into_iter().enumerate() {
+            db.insert_request_capture(&capture(id, i as i64), 2)?;
+        }
+
+        let ids: Vec<_> = db
+            .list_request_captures(None)?
+            .into_iter()
+            .map(|c| c.request_id)
+            .collect();
+        assert_eq!(ids, vec!["c", "b"]);
+        assert!(db.get_request_capture("a")?.is_none());
+        assert_eq!(db.get_request_capture("c")?, Some(capture("c", 2)));
+        assert!(db.list_request_captures(Some("codex"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn records_response_and_filters_by_time() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        for (i, id) in ["a", "b", "c"].into_iter().enumerate() {
+            db.insert_request_capture(&capture(id, i as i64 * 100), 10)?;
+        }
+        db.record_request_capture_response("b", "p2", 200, 1234, "{}")?;
+
+        let captures = db.get_request_captures_between(Some(50), Some(200))?;
+        let ids: Vec<_> = captures.iter().map(|c| c.request_id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+        assert_eq!(captures[0].provider_id, "p2");
+        assert_eq!(captures[0].status_code, Some(200));
+        assert_eq!(captures[0].duration_ms, Some(1234));
+        assert_eq!(captures[0].response_body.as_deref(), Some("{}"));
+        assert_eq!(captures[1].status_code, None);
+        Ok(())
+    }
+}
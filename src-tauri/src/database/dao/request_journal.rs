@@ -0,0 +1,139 @@
+//! 代理请求日志（journal）DAO
+//!
+//! 记录已接收但尚未转发完成的请求；进程正常处理完请求后删除对应记录，
+//! 启动时仍残留的记录即为上次崩溃时丢失的请求。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+/// 未完成转发的请求
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub request_id: String,
+    pub app_type: String,
+    pub model: String,
+    pub session_id: String,
+    /// 接收时间（Unix 毫秒）
+    pub accepted_at: i64,
+}
+
+impl Database {
+    /// 写入请求日志
+    pub fn insert_journal_entry(&self, entry: &JournalEntry) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT OR REPLACE INTO proxy_request_journal
+             (request_id, app_type, model, session_id, accepted_at, lost)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+            rusqlite::params![
+                entry.request_id,
+                entry.app_type,
+                entry.model,
+                entry.session_id,
+                entry.accepted_at,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 请求处理完成后删除日志
+    pub fn remove_journal_entry(&self, request_id: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "DELETE FROM proxy_request_journal WHERE request_id = ?1 AND lost = 0",
+            [request_id],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 将残留的日志标记为丢失，返回本次新标记的记录
+    ///
+    /// 仅应在启动时、代理开始接收请求之前调用
+    pub fn mark_journal_entries_lost(&self) -> Result<Vec<JournalEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let entries = query_entries(&conn, 0)?;
+        conn.execute(
+            "UPDATE proxy_request_journal SET lost = 1 WHERE lost = 0",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(entries)
+    }
+
+    /// 获取已确认丢失的请求
+    pub fn get_lost_requests(&self) -> Result<Vec<JournalEntry>, AppError> {
+        let conn = lock_conn!(self.conn);
+        query_entries(&conn, 1)
+    }
+
+    /// 清除已确认丢失的请求
+    pub fn clear_lost_requests(&self) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM proxy_request_journal WHERE lost = 1", [])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn query_entries(conn: &rusqlite::Connection, lost: i64) -> Result<Vec<JournalEntry>, AppError> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT request_id, app_type, model, session_id, accepted_at
+             FROM proxy_request_journal
+             WHERE lost = ?1
+             ORDER BY accepted_at",
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    let rows = stmt
+        .query_map([lost], |row| {
+            Ok(JournalEntry {
+                request_id: row.get(0)?,
+                app_type: row.get(1)?,
+                model: row.get(2)?,
+                session_id: row.get(3)?,
+                accepted_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AppError::Database(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> JournalEntry {
+        JournalEntry {
+            request_id: id.into(),
+            app_type: "claude".into(),
+            model: "m".into(),
+            session_id: "s".into(),
+            accepted_at: 1,
+        }
+    }
+
+    #[test]
+    fn leftover_entries_become_lost() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.insert_journal_entry(&entry("done"))?;
+        db.insert_journal_entry(&entry("crashed"))?;
+        db.remove_journal_entry("done")?;
+
+        let lost = db.mark_journal_entries_lost()?;
+        assert_eq!(lost, vec![entry("crashed")]);
+        // 再次启动不会重复上报
+        assert!(db.mark_journal_entries_lost()?.is_empty());
+        assert_eq!(db.get_lost_requests()?.len(), 1);
+
+        db.clear_lost_requests()?;
+        assert!(db.get_lost_requests()?.is_empty());
+        Ok(())
+    }
+}
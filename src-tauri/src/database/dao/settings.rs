@@ -98,6 +98,76 @@ impl Database {
         }
     }
 
+    const PROXY_ACCESS_TOKEN_KEY: &'static str = "proxy_access_token";
+    const PROXY_ADMIN_TOKEN_KEY: &'static str = "proxy_admin_token";
+
+    /// 获取本地代理访问令牌
+    ///
+    /// 返回 None 表示未设置（不校验访问令牌）
+    pub fn get_proxy_access_token(&self) -> Result<Option<String>, AppError> {
+        Ok(self
+            .get_setting(Self::PROXY_ACCESS_TOKEN_KEY)?
+            .filter(|token| !token.trim().is_empty()))
+    }
+
+    /// 设置本地代理访问令牌（传入空字符串或 None 时清除）
+    pub fn set_proxy_access_token(&self, token: Option<&str>) -> Result<(), AppError> {
+        match token.map(str::trim) {
+            Some(t) if !t.is_empty() => self.set_setting(Self::PROXY_ACCESS_TOKEN_KEY, t),
+            _ => {
+                let conn = lock_conn!(self.conn);
+                conn.execute(
+                    "DELETE FROM settings WHERE key = ?1",
+                    params![Self::PROXY_ACCESS_TOKEN_KEY],
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// 获取管理 API 令牌
+    ///
+    /// 返回 None 表示未设置（管理 API 关闭）
+    pub fn get_proxy_admin_token(&self) -> Result<Option<String>, AppError> {
+        Ok(self
+            .get_setting(Self::PROXY_ADMIN_TOKEN_KEY)?
+            .filter(|token| !token.trim().is_empty()))
+    }
+
+    /// 设置管理 API 令牌（传入空字符串或 None 时清除并关闭管理 API）
+    pub fn set_proxy_admin_token(&self, token: Option<&str>) -> Result<(), AppError> {
+        match token.map(str::trim) {
+            Some(t) if !t.is_empty() => self.set_setting(Self::PROXY_ADMIN_TOKEN_KEY, t),
+            _ => {
+                let conn = lock_conn!(self.conn);
+                conn.execute(
+                    "DELETE FROM settings WHERE key = ?1",
+                    params![Self::PROXY_ADMIN_TOKEN_KEY],
+                )
+                .map_err(|e| AppError::Database(e.to_string()))?;
+                Ok(())
+            }
+        }
+    }
+
+    const PROXY_LAN_ACCESS_KEY: &'static str = "proxy_lan_access";
+
+    /// 获取是否允许代理监听局域网地址
+    ///
+    /// 返回 None 表示用户尚未做出选择
+    pub fn get_proxy_lan_access(&self) -> Result<Option<bool>, AppError> {
+        Ok(self
+            .get_setting(Self::PROXY_LAN_ACCESS_KEY)?
+            .map(|value| value == "true"))
+    }
+
+    /// 设置是否允许代理监听局域网地址
+    pub fn set_proxy_lan_access(&self, enabled: bool) -> Result<(), AppError> {
+        let value = if enabled { "true" } else { "false" };
+        self.set_setting(Self::PROXY_LAN_ACCESS_KEY, value)
+    }
+
     // --- 代理接管状态管理（已废弃，使用 proxy_config.enabled 替代）---
 
     /// 获取指定应用的代理接管状态
@@ -186,4 +256,519 @@ impl Database {
             .map_err(|e| AppError::Database(format!("序列化整流器配置失败: {e}")))?;
         self.set_setting("rectifier_config", &json)
     }
+
+    /// 获取本地客户端限流配置
+    pub fn get_client_rate_limit_config(
+        &self,
+    ) -> Result<crate::proxy::types::ClientRateLimitConfig, AppError> {
+        match self.get_setting("client_rate_limit_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析本地限流配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ClientRateLimitConfig::default()),
+        }
+    }
+
+    /// 更新本地客户端限流配置
+    pub fn set_client_rate_limit_config(
+        &self,
+        config: &crate::proxy::types::ClientRateLimitConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化本地限流配置失败: {e}")))?;
+        self.set_setting("client_rate_limit_config", &json)
+    }
+
+    /// 获取团队网关配置
+    pub fn get_team_gateway_config(
+        &self,
+    ) -> Result<crate::proxy::types::TeamGatewayConfig, AppError> {
+        match self.get_setting("team_gateway_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析团队网关配置失败: {e}"))),
+            None => Ok(crate::proxy::types::TeamGatewayConfig::default()),
+        }
+    }
+
+    /// 更新团队网关配置
+    pub fn set_team_gateway_config(
+        &self,
+        config: &crate::proxy::types::TeamGatewayConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化团队网关配置失败: {e}")))?;
+        self.set_setting("team_gateway_config", &json)
+    }
+
+    /// 获取入站 IP 白名单配置
+    pub fn get_ip_allowlist_config(
+        &self,
+    ) -> Result<crate::proxy::types::IpAllowlistConfig, AppError> {
+        match self.get_setting("ip_allowlist_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析 IP 白名单配置失败: {e}"))),
+            None => Ok(crate::proxy::types::IpAllowlistConfig::default()),
+        }
+    }
+
+    /// 更新入站 IP 白名单配置
+    pub fn set_ip_allowlist_config(
+        &self,
+        config: &crate::proxy::types::IpAllowlistConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化 IP 白名单配置失败: {e}")))?;
+        self.set_setting("ip_allowlist_config", &json)
+    }
+
+    /// 获取请求体大小限制配置
+    pub fn get_request_size_limit_config(
+        &self,
+    ) -> Result<crate::proxy::types::RequestSizeLimitConfig, AppError> {
+        match self.get_setting("request_size_limit_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析请求体大小限制配置失败: {e}"))),
+            None => Ok(crate::proxy::types::RequestSizeLimitConfig::default()),
+        }
+    }
+
+    /// 更新请求体大小限制配置
+    pub fn set_request_size_limit_config(
+        &self,
+        config: &crate::proxy::types::RequestSizeLimitConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化请求体大小限制配置失败: {e}")))?;
+        self.set_setting("request_size_limit_config", &json)
+    }
+
+    /// 获取本地代理 HTTPS 配置
+    pub fn get_proxy_tls_config(&self) -> Result<crate::proxy::types::ProxyTlsConfig, AppError> {
+        match self.get_setting("proxy_tls_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析 HTTPS 配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ProxyTlsConfig::default()),
+        }
+    }
+
+    /// 更新本地代理 HTTPS 配置
+    pub fn set_proxy_tls_config(
+        &self,
+        config: &crate::proxy::types::ProxyTlsConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化 HTTPS 配置失败: {e}")))?;
+        self.set_setting("proxy_tls_config", &json)
+    }
+
+    /// 获取 Webhook 配置
+    pub fn get_webhook_config(&self) -> Result<crate::proxy::types::WebhookConfig, AppError> {
+        match self.get_setting("webhook_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析 Webhook 配置失败: {e}"))),
+            None => Ok(crate::proxy::types::WebhookConfig::default()),
+        }
+    }
+
+    /// 更新 Webhook 配置
+    pub fn set_webhook_config(
+        &self,
+        config: &crate::proxy::types::WebhookConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化 Webhook 配置失败: {e}")))?;
+        self.set_setting("webhook_config", &json)
+    }
+
+    /// 获取 A/B 实验列表
+    pub fn get_experiments(&self) -> Result<Vec<crate::proxy::types::Experiment>, AppError> {
+        match self.get_setting("experiments")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析实验配置失败: {e}"))),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// 更新 A/B 实验列表
+    pub fn set_experiments(
+        &self,
+        experiments: &[crate::proxy::types::Experiment],
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(experiments)
+            .map_err(|e| AppError::Database(format!("序列化实验配置失败: {e}")))?;
+        self.set_setting("experiments", &json)
+    }
+
+    /// 获取自动回切配置
+    pub fn get_failback_config(&self) -> Result<crate::proxy::types::FailbackConfig, AppError> {
+        match self.get_setting("failback_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析自动回切配置失败: {e}"))),
+            None => Ok(crate::proxy::types::FailbackConfig::default()),
+        }
+    }
+
+    /// 更新自动回切配置
+    pub fn set_failback_config(
+        &self,
+        config: &crate::proxy::types::FailbackConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化自动回切配置失败: {e}")))?;
+        self.set_setting("failback_config", &json)
+    }
+
+    /// 获取故障通知配置
+    pub fn get_failure_notification_config(
+        &self,
+    ) -> Result<crate::proxy::types::FailureNotificationConfig, AppError> {
+        match self.get_setting("failure_notification_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析故障通知配置失败: {e}"))),
+            None => Ok(crate::proxy::types::FailureNotificationConfig::default()),
+        }
+    }
+
+    /// 更新故障通知配置
+    pub fn set_failure_notification_config(
+        &self,
+        config: &crate::proxy::types::FailureNotificationConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化故障通知配置失败: {e}")))?;
+        self.set_setting("failure_notification_config", &json)
+    }
+
+    /// 获取配置档案列表
+    pub fn get_profiles(&self) -> Result<Vec<crate::services::profile::Profile>, AppError> {
+        match self.get_setting("profiles")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析配置档案失败: {e}"))),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// 更新配置档案列表
+    pub fn set_profiles(
+        &self,
+        profiles: &[crate::services::profile::Profile],
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(profiles)
+            .map_err(|e| AppError::Database(format!("序列化配置档案失败: {e}")))?;
+        self.set_setting("profiles", &json)
+    }
+
+    /// 获取最近应用的配置档案 ID
+    pub fn get_active_profile_id(&self) -> Result<Option<String>, AppError> {
+        Ok(self
+            .get_setting("active_profile_id")?
+            .filter(|id| !id.is_empty()))
+    }
+
+    /// 记录最近应用的配置档案 ID（None 表示清除）
+    pub fn set_active_profile_id(&self, id: Option<&str>) -> Result<(), AppError> {
+        self.set_setting("active_profile_id", id.unwrap_or_default())
+    }
+
+    /// 获取匿名使用统计配置
+    pub fn get_telemetry_config(
+        &self,
+    ) -> Result<crate::services::telemetry::TelemetryConfig, AppError> {
+        match self.get_setting("telemetry_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析使用统计配置失败: {e}"))),
+            None => Ok(crate::services::telemetry::TelemetryConfig::default()),
+        }
+    }
+
+    /// 更新匿名使用统计配置
+    pub fn set_telemetry_config(
+        &self,
+        config: &crate::services::telemetry::TelemetryConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化使用统计配置失败: {e}")))?;
+        self.set_setting("telemetry_config", &json)
+    }
+
+    /// 获取告警规则列表
+    pub fn get_alert_rules(&self) -> Result<Vec<crate::proxy::types::AlertRule>, AppError> {
+        match self.get_setting("alert_rules")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析告警规则失败: {e}"))),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// 更新告警规则列表
+    pub fn set_alert_rules(
+        &self,
+        rules: &[crate::proxy::types::AlertRule],
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(rules)
+            .map_err(|e| AppError::Database(format!("序列化告警规则失败: {e}")))?;
+        self.set_setting("alert_rules", &json)
+    }
+
+    /// 获取消费提醒配置
+    pub fn get_cost_alert_config(&self) -> Result<crate::proxy::types::CostAlertConfig, AppError> {
+        match self.get_setting("cost_alert_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析消费提醒配置失败: {e}"))),
+            None => Ok(crate::proxy::types::CostAlertConfig::default()),
+        }
+    }
+
+    /// 更新消费提醒配置
+    pub fn set_cost_alert_config(
+        &self,
+        config: &crate::proxy::types::CostAlertConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化消费提醒配置失败: {e}")))?;
+        self.set_setting("cost_alert_config", &json)
+    }
+
+    /// 获取供应商评分配置
+    pub fn get_provider_scoring_config(
+        &self,
+    ) -> Result<crate::proxy::types::ProviderScoringConfig, AppError> {
+        match self.get_setting("provider_scoring_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析供应商评分配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ProviderScoringConfig::default()),
+        }
+    }
+
+    /// 更新供应商评分配置
+    pub fn set_provider_scoring_config(
+        &self,
+        config: &crate::proxy::types::ProviderScoringConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化供应商评分配置失败: {e}")))?;
+        self.set_setting("provider_scoring_config", &json)
+    }
+
+    /// 获取请求日志脱敏配置
+    pub fn get_log_redaction_config(
+        &self,
+    ) -> Result<crate::proxy::types::LogRedactionConfig, AppError> {
+        match self.get_setting("log_redaction_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析日志脱敏配置失败: {e}"))),
+            None => Ok(crate::proxy::types::LogRedactionConfig::default()),
+        }
+    }
+
+    /// 更新请求日志脱敏配置
+    pub fn set_log_redaction_config(
+        &self,
+        config: &crate::proxy::types::LogRedactionConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化日志脱敏配置失败: {e}")))?;
+        self.set_setting("log_redaction_config", &json)
+    }
+
+    /// 获取调试日志配置
+    pub fn get_debug_log_config(&self) -> Result<crate::proxy::types::DebugLogConfig, AppError> {
+        match self.get_setting("debug_log_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析调试日志配置失败: {e}"))),
+            None => Ok(crate::proxy::types::DebugLogConfig::default()),
+        }
+    }
+
+    /// 更新调试日志配置
+    pub fn set_debug_log_config(
+        &self,
+        config: &crate::proxy::types::DebugLogConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化调试日志配置失败: {e}")))?;
+        self.set_setting("debug_log_config", &json)
+    }
+
+    /// 获取链路追踪配置
+    pub fn get_otel_config(&self) -> Result<crate::proxy::types::OtelConfig, AppError> {
+        match self.get_setting("otel_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析链路追踪配置失败: {e}"))),
+            None => Ok(crate::proxy::types::OtelConfig::default()),
+        }
+    }
+
+    /// 更新链路追踪配置
+    pub fn set_otel_config(
+        &self,
+        config: &crate::proxy::types::OtelConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化链路追踪配置失败: {e}")))?;
+        self.set_setting("otel_config", &json)
+    }
+
+    /// 获取成本标注配置
+    pub fn get_cost_annotation_config(
+        &self,
+    ) -> Result<crate::proxy::types::CostAnnotationConfig, AppError> {
+        match self.get_setting("cost_annotation_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析成本标注配置失败: {e}"))),
+            None => Ok(crate::proxy::types::CostAnnotationConfig::default()),
+        }
+    }
+
+    /// 更新成本标注配置
+    pub fn set_cost_annotation_config(
+        &self,
+        config: &crate::proxy::types::CostAnnotationConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化成本标注配置失败: {e}")))?;
+        self.set_setting("cost_annotation_config", &json)
+    }
+
+    /// 获取对话记录采集配置
+    pub fn get_transcript_config(&self) -> Result<crate::proxy::types::TranscriptConfig, AppError> {
+        match self.get_setting("transcript_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析对话记录配置失败: {e}"))),
+            None => Ok(crate::proxy::types::TranscriptConfig::default()),
+        }
+    }
+
+    /// 更新对话记录采集配置
+    pub fn set_transcript_config(
+        &self,
+        config: &crate::proxy::types::TranscriptConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化对话记录配置失败: {e}")))?;
+        self.set_setting("transcript_config", &json)
+    }
+
+    /// 获取分类日志级别配置
+    pub fn get_log_levels_config(&self) -> Result<crate::log_levels::LogLevelsConfig, AppError> {
+        match self.get_setting("log_levels_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析日志级别配置失败: {e}"))),
+            None => Ok(crate::log_levels::LogLevelsConfig::default()),
+        }
+    }
+
+    /// 更新分类日志级别配置
+    pub fn set_log_levels_config(
+        &self,
+        config: &crate::log_levels::LogLevelsConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化日志级别配置失败: {e}")))?;
+        self.set_setting("log_levels_config", &json)
+    }
+
+    /// 获取完整对话采集配置
+    pub fn get_conversation_capture_config(
+        &self,
+    ) -> Result<crate::proxy::types::ConversationCaptureConfig, AppError> {
+        match self.get_setting("conversation_capture_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析完整对话采集配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ConversationCaptureConfig::default()),
+        }
+    }
+
+    /// 更新完整对话采集配置
+    pub fn set_conversation_capture_config(
+        &self,
+        config: &crate::proxy::types::ConversationCaptureConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化完整对话采集配置失败: {e}")))?;
+        self.set_setting("conversation_capture_config", &json)
+    }
+
+    /// 获取请求重放采集配置
+    pub fn get_replay_capture_config(
+        &self,
+    ) -> Result<crate::proxy::types::ReplayCaptureConfig, AppError> {
+        match self.get_setting("replay_capture_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析请求重放配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ReplayCaptureConfig::default()),
+        }
+    }
+
+    /// 更新请求重放采集配置
+    pub fn set_replay_capture_config(
+        &self,
+        config: &crate::proxy::types::ReplayCaptureConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化请求重放配置失败: {e}")))?;
+        self.set_setting("replay_capture_config", &json)
+    }
+
+    /// 获取影子流量配置
+    pub fn get_shadow_traffic_config(
+        &self,
+    ) -> Result<crate::proxy::types::ShadowTrafficConfig, AppError> {
+        match self.get_setting("shadow_traffic_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析影子流量配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ShadowTrafficConfig::default()),
+        }
+    }
+
+    /// 更新影子流量配置
+    pub fn set_shadow_traffic_config(
+        &self,
+        config: &crate::proxy::types::ShadowTrafficConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化影子流量配置失败: {e}")))?;
+        self.set_setting("shadow_traffic_config", &json)
+    }
+
+    /// 获取响应缓存配置
+    pub fn get_response_cache_config(
+        &self,
+    ) -> Result<crate::proxy::types::ResponseCacheConfig, AppError> {
+        match self.get_setting("response_cache_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析响应缓存配置失败: {e}"))),
+            None => Ok(crate::proxy::types::ResponseCacheConfig::default()),
+        }
+    }
+
+    /// 更新响应缓存配置
+    pub fn set_response_cache_config(
+        &self,
+        config: &crate::proxy::types::ResponseCacheConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化响应缓存配置失败: {e}")))?;
+        self.set_setting("response_cache_config", &json)
+    }
+
+    /// 获取 SSE 保活配置
+    pub fn get_sse_keepalive_config(
+        &self,
+    ) -> Result<crate::proxy::types::SseKeepaliveConfig, AppError> {
+        match self.get_setting("sse_keepalive_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Database(format!("解析 SSE 保活配置失败: {e}"))),
+            None => Ok(crate::proxy::types::SseKeepaliveConfig::default()),
+        }
+    }
+
+    /// 更新 SSE 保活配置
+    pub fn set_sse_keepalive_config(
+        &self,
+        config: &crate::proxy::types::SseKeepaliveConfig,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Database(format!("序列化 SSE 保活配置失败: {e}")))?;
+        self.set_setting("sse_keepalive_config", &json)
+    }
 }
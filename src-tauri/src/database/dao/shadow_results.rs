@@ -0,0 +1,195 @@
+//! 影子流量结果 DAO
+//!
+//! `proxy_shadow_results` 记录每个复制到影子供应商的请求的状态、延迟与用量，按时间只保留最近 N 条。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use serde::Serialize;
+
+/// 最多保留的影子请求结果数
+const MAX_SHADOW_RESULTS: u32 = 2000;
+
+/// 单个影子请求的结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowResult {
+    pub app_type: String,
+    /// 实际处理原请求的供应商
+    pub primary_provider_id: String,
+    pub shadow_provider_id: String,
+    pub model: String,
+    /// 影子供应商的响应状态码（请求失败时为 0）
+    pub status_code: u16,
+    pub latency_ms: u64,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub error_message: Option<String>,
+    pub created_at: i64,
+}
+
+/// 影子供应商的汇总统计
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowProviderSummary {
+    pub app_type: String,
+    pub shadow_provider_id: String,
+    pub requests: u64,
+    /// 2xx 响应数
+    pub success_requests: u64,
+    /// 成功请求的平均延迟（毫秒）
+    pub avg_latency_ms: Option<f64>,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+}
+
+impl Database {
+    /// 记录影子请求结果，并删除超出保留上限的最早记录
+    pub fn insert_shadow_result(&self, result: &ShadowResult) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO proxy_shadow_results
+             (app_type, primary_provider_id, shadow_provider_id, model, status_code, latency_ms,
+              input_tokens, output_tokens, error_message, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                result.app_type,
+                result.primary_provider_id,
+                result.shadow_provider_id,
+                result.model,
+                result.status_code,
+                result.latency_ms as i64,
+                result.input_tokens,
+                result.output_tokens,
+                result.error_message,
+                result.created_at,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "DELETE FROM proxy_shadow_results WHERE id NOT IN (
+                SELECT id FROM proxy_shadow_results ORDER BY id DESC LIMIT ?1
+            )",
+            [MAX_SHADOW_RESULTS],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 按时间倒序列出最近的影子请求结果
+    pub fn get_shadow_results(
+        &self,
+        app_type: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<ShadowResult>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT app_type, primary_provider_id, shadow_provider_id, model, status_code,
+                        latency_ms, input_tokens, output_tokens, error_message, created_at
+                 FROM proxy_shadow_results
+                 WHERE ?1 IS NULL OR app_type = ?1
+                 ORDER BY id DESC LIMIT ?2",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map(rusqlite::params![app_type, limit], |row| {
+                Ok(ShadowResult {
+                    app_type: row.get(0)?,
+                    primary_provider_id: row.get(1)?,
+                    shadow_provider_id: row.get(2)?,
+                    model: row.get(3)?,
+                    status_code: row.get(4)?,
+                    latency_ms: row.get::<_, i64>(5)? as u64,
+                    input_tokens: row.get(6)?,
+                    output_tokens: row.get(7)?,
+                    error_message: row.get(8)?,
+                    created_at: row.get(9)?,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 按影子供应商汇总请求数、成功率、平均延迟与用量
+    pub fn get_shadow_summary(&self) -> Result<Vec<ShadowProviderSummary>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(
+                "SELECT app_type, shadow_provider_id, COUNT(*),
+                        SUM(CASE WHEN status_code >= 200 AND status_code < 300 THEN 1 ELSE 0 END),
+                        AVG(CASE WHEN status_code >= 200 AND status_code < 300 THEN latency_ms END),
+                        SUM(input_tokens), SUM(output_tokens)
+                 FROM proxy_shadow_results
+                 GROUP BY app_type, shadow_provider_id
+                 ORDER BY app_type, shadow_provider_id",
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(ShadowProviderSummary {
+                    app_type: row.get(0)?,
+                    shadow_provider_id: row.get(1)?,
+                    requests: row.get::<_, i64>(2)? as u64,
+                    success_requests: row.get::<_, i64>(3)? as u64,
+                    avg_latency_ms: row.get(4)?,
+                    total_input_tokens: row.get::<_, i64>(5)? as u64,
+                    total_output_tokens: row.get::<_, i64>(6)? as u64,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 清空影子请求结果
+    pub fn clear_shadow_results(&self) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM proxy_shadow_results", [])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(status_code: u16, latency_ms: u64) -> ShadowResult {
+        ShadowResult {
+            app_type: "claude".to_string(),
+            primary_provider_id: "primary".to_string(),
+            shadow_provider_id: "shadow".to_string(),
+            model: "claude-sonnet".to_string(),
+            status_code,
+            latency_ms,
+            input_tokens: 10,
+            output_tokens: 5,
+            error_message: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn summarizes_shadow_results() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.insert_shadow_result(&result(200, 100))?;
+        db.insert_shadow_result(&result(200, 300))?;
+        db.insert_shadow_result(&result(502, 50))?;
+
+        let summary = db.get_shadow_summary()?;
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].requests, 3);
+        assert_eq!(summary[0].success_requests, 2);
+        assert_eq!(summary[0].avg_latency_ms, Some(200.0));
+        assert_eq!(summary[0].total_output_tokens, 15);
+
+        assert_eq!(
+            db.get_shadow_results(Some("claude"), 2)?[0].status_code,
+            502
+        );
+        db.clear_shadow_results()?;
+        assert!(db.get_shadow_summary()?.is_empty());
+        Ok(())
+    }
+}
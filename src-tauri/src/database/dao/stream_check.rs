@@ -2,7 +2,8 @@
 
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
-use crate::services::stream_check::{StreamCheckConfig, StreamCheckResult};
+use crate::services::health_probe::HealthProbeConfig;
+use crate::services::stream_check::{HealthStatus, StreamCheckConfig, StreamCheckResult};
 
 impl Database {
     /// 保存流式检查日志
@@ -39,6 +40,47 @@ impl Database {
         Ok(conn.last_insert_rowid())
     }
 
+    /// 获取 Provider 最近一次流式检查结果
+    pub fn get_latest_stream_check(
+        &self,
+        provider_id: &str,
+        app_type: &str,
+    ) -> Result<Option<StreamCheckResult>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            "SELECT status, success, message, response_time_ms, http_status, model_used,
+                    retry_count, tested_at
+             FROM stream_check_logs
+             WHERE app_type = ?1 AND provider_id = ?2
+             ORDER BY tested_at DESC, id DESC
+             LIMIT 1",
+            rusqlite::params![app_type, provider_id],
+            |row| {
+                let status: String = row.get(0)?;
+                Ok(StreamCheckResult {
+                    status: match status.as_str() {
+                        "operational" => HealthStatus::Operational,
+                        "degraded" => HealthStatus::Degraded,
+                        _ => HealthStatus::Failed,
+                    },
+                    success: row.get(1)?,
+                    message: row.get(2)?,
+                    response_time_ms: row.get::<_, Option<i64>>(3)?.map(|v| v as u64),
+                    http_status: row.get::<_, Option<i64>>(4)?.map(|v| v as u16),
+                    model_used: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                    retry_count: row.get::<_, Option<i64>>(6)?.unwrap_or(0) as u32,
+                    tested_at: row.get(7)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(check) => Ok(Some(check)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
     /// 获取流式检查配置
     pub fn get_stream_check_config(&self) -> Result<StreamCheckConfig, AppError> {
         match self.get_setting("stream_check_config")? {
@@ -54,4 +96,20 @@ impl Database {
             .map_err(|e| AppError::Message(format!("序列化配置失败: {e}")))?;
         self.set_setting("stream_check_config", &json)
     }
+
+    /// 获取后台健康探测配置
+    pub fn get_health_probe_config(&self) -> Result<HealthProbeConfig, AppError> {
+        match self.get_setting("health_probe_config")? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::Message(format!("解析配置失败: {e}"))),
+            None => Ok(HealthProbeConfig::default()),
+        }
+    }
+
+    /// 保存后台健康探测配置
+    pub fn save_health_probe_config(&self, config: &HealthProbeConfig) -> Result<(), AppError> {
+        let json = serde_json::to_string(config)
+            .map_err(|e| AppError::Message(format!("序列化配置失败: {e}")))?;
+        self.set_setting("health_probe_config", &json)
+    }
 }
@@ -0,0 +1,241 @@
+//! 团队网关成员 DAO
+//!
+//! 每个成员持有独立的访问令牌，代理按令牌识别请求来源，
+//! 并据此统计用量、执行配额与供应商限制。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use crate::provider::QuotaCalendar;
+use crate::services::quota_calendar;
+use serde::{Deserialize, Serialize};
+
+/// 团队网关成员
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMember {
+    pub id: String,
+    pub name: String,
+    /// 访问令牌（成员将其作为 API Key 配置到客户端）
+    pub token: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 允许使用的供应商 ID，为空时不限制
+    #[serde(default)]
+    pub allowed_providers: Vec<String>,
+    /// 每日请求数上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_request_limit: Option<u32>,
+    /// 每月消费上限（USD）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monthly_limit_usd: Option<String>,
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 成员当日 / 当月用量
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamMemberUsage {
+    pub member_id: String,
+    pub requests_today: u64,
+    pub cost_today_usd: f64,
+    pub requests_this_month: u64,
+    pub cost_this_month_usd: f64,
+    pub tokens_this_month: u64,
+}
+
+impl TeamMember {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        let allowed: String = row.get(4)?;
+        Ok(Self {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            token: row.get(2)?,
+            enabled: row.get(3)?,
+            allowed_providers: serde_json::from_str(&allowed).unwrap_or_default(),
+            daily_request_limit: row.get::<_, Option<i64>>(5)?.map(|v| v as u32),
+            monthly_limit_usd: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, name, token, enabled, allowed_providers,
+     daily_request_limit, monthly_limit_usd, created_at FROM team_members";
+
+impl Database {
+    /// 获取全部团队网关成员
+    pub fn list_team_members(&self) -> Result<Vec<TeamMember>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn
+            .prepare(&format!(
+                "{SELECT_COLUMNS} ORDER BY created_at ASC, name ASC"
+            ))
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = stmt
+            .query_map([], TeamMember::from_row)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    /// 按 ID 获取成员
+    pub fn get_team_member(&self, id: &str) -> Result<Option<TeamMember>, AppError> {
+        self.query_team_member("id", id)
+    }
+
+    /// 按访问令牌获取成员
+    pub fn get_team_member_by_token(&self, token: &str) -> Result<Option<TeamMember>, AppError> {
+        self.query_team_member("token", token)
+    }
+
+    fn query_team_member(&self, column: &str, value: &str) -> Result<Option<TeamMember>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            &format!("{SELECT_COLUMNS} WHERE {column} = ?1"),
+            [value],
+            TeamMember::from_row,
+        );
+        match result {
+            Ok(member) => Ok(Some(member)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    /// 新增或更新成员
+    pub fn save_team_member(&self, member: &TeamMember) -> Result<(), AppError> {
+        let allowed = serde_json::to_string(&member.allowed_providers)
+            .map_err(|e| AppError::Database(format!("序列化供应商限制失败: {e}")))?;
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO team_members
+             (id, name, token, enabled, allowed_providers, daily_request_limit, monthly_limit_usd, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(id) DO UPDATE SET
+               name = excluded.name, token = excluded.token, enabled = excluded.enabled,
+               allowed_providers = excluded.allowed_providers,
+               daily_request_limit = excluded.daily_request_limit,
+               monthly_limit_usd = excluded.monthly_limit_usd",
+            rusqlite::params![
+                member.id,
+                member.name,
+                member.token,
+                member.enabled,
+                allowed,
+                member.daily_request_limit.map(|v| v as i64),
+                member.monthly_limit_usd,
+                member.created_at,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 删除成员（历史请求日志保留）
+    pub fn delete_team_member(&self, id: &str) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM team_members WHERE id = ?1", [id])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 统计成员当日 / 当月用量（按本地时区自然日与自然月）
+    pub fn get_team_member_usage(&self, member_id: &str) -> Result<TeamMemberUsage, AppError> {
+        let windows =
+            quota_calendar::current_windows(&QuotaCalendar::default(), chrono::Utc::now());
+        let conn = lock_conn!(self.conn);
+        let window_usage = |start: i64| -> Result<(i64, f64, i64), AppError> {
+            conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0),
+                        COALESCE(SUM(input_tokens + output_tokens), 0)
+                 FROM proxy_request_logs
+                 WHERE member_id = ?1 AND created_at >= ?2",
+                rusqlite::params![member_id, start],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| AppError::Database(e.to_string()))
+        };
+
+        let (requests_today, cost_today_usd, _) = window_usage(windows.daily.start)?;
+        let (requests_this_month, cost_this_month_usd, tokens_this_month) =
+            window_usage(windows.period.start)?;
+        Ok(TeamMemberUsage {
+            member_id: member_id.to_string(),
+            requests_today: requests_today.max(0) as u64,
+            cost_today_usd,
+            requests_this_month: requests_this_month.max(0) as u64,
+            cost_this_month_usd,
+            tokens_this_month: tokens_this_month.max(0) as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: &str, token: &str) -> TeamMember {
+        TeamMember {
+            id: id.into(),
+            name: format!("member {id}"),
+            token: token.into(),
+            enabled: true,
+            allowed_providers: vec!["p1".into()],
+            daily_request_limit: Some(10),
+            monthly_limit_usd: None,
+            created_at: 1,
+        }
+    }
+
+    #[test]
+    fn saves_and_looks_up_by_token() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.save_team_member(&member("alice", "tok-a"))?;
+        db.save_team_member(&member("bob", "tok-b"))?;
+
+        let found = db.get_team_member_by_token("tok-b")?.unwrap();
+        assert_eq!(found.id, "bob");
+        assert_eq!(found.allowed_providers, vec!["p1".to_string()]);
+
+        let mut updated = member("alice", "tok-a2");
+        updated.enabled = false;
+        db.save_team_member(&updated)?;
+        assert!(db.get_team_member_by_token("tok-a")?.is_none());
+        assert!(!db.get_team_member("alice")?.unwrap().enabled);
+
+        db.delete_team_member("bob")?;
+        assert_eq!(db.list_team_members()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn counts_usage_per_member() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let now = chrono::Utc::now().timestamp();
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, member) in [("r1", "alice"), ("r2", "alice"), ("r3", "bob")] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model,
+                     input_tokens, output_tokens, total_cost_usd, latency_ms, status_code,
+                     created_at, member_id)
+                     VALUES (?1, 'p1', 'claude', 'm', 100, 50, '0.5', 10, 200, ?2, ?3)",
+                    rusqlite::params![id, now, member],
+                )
+                .unwrap();
+            }
+        }
+
+        let usage = db.get_team_member_usage("alice")?;
+        assert_eq!(usage.requests_today, 2);
+        assert_eq!(usage.requests_this_month, 2);
+        assert!((usage.cost_this_month_usd - 1.0).abs() < 1e-9);
+        assert_eq!(usage.tokens_this_month, 300);
+        Ok(())
+    }
+}
@@ -0,0 +1,353 @@
+//! 对话记录全文索引 DAO
+//!
+//! `proxy_transcripts` 为 FTS5 虚拟表，仅 `request_text` / `response_text` 参与全文索引，
+//! 其余列为 UNINDEXED 的过滤字段。FTS5 的 MATCH 不支持表别名，查询中统一使用完整表名。
+//! 搜索命中会关联请求日志（状态码）与请求采集（是否保存了发往上游的完整请求体），
+//! 便于从命中直接查看当时发给哪个供应商、发送了什么。
+
+use crate::database::{lock_conn, Database};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+
+/// 一轮对话记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptRecord {
+    pub request_id: String,
+    pub provider_id: String,
+    pub app_type: String,
+    pub model: String,
+    pub created_at: i64,
+    pub request_text: String,
+    pub response_text: String,
+}
+
+/// 对话记录搜索条件
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSearchQuery {
+    /// 搜索关键词（空格分隔，需全部命中）
+    pub query: String,
+    pub app_type: Option<String>,
+    pub provider_id: Option<String>,
+    /// 模型名（模糊匹配）
+    pub model: Option<String>,
+    pub start_date: Option<i64>,
+    pub end_date: Option<i64>,
+}
+
+/// 搜索命中
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptHit {
+    pub request_id: String,
+    pub provider_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+    pub app_type: String,
+    pub model: String,
+    pub created_at: i64,
+    /// 用户输入摘要（命中词以 `**` 包裹）
+    pub request_snippet: String,
+    /// 模型回复摘要（命中词以 `**` 包裹）
+    pub response_snippet: String,
+    /// 上游响应状态码（无对应请求日志时为空）
+    pub status_code: Option<u16>,
+    /// 是否保存了完整请求体（可通过 `get_request_capture` 查看）
+    pub has_capture: bool,
+}
+
+/// 分页搜索结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedTranscripts {
+    pub data: Vec<TranscriptHit>,
+    pub total: u32,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Database {
+    /// 保存对话记录
+    pub fn insert_transcript(&self, record: &TranscriptRecord) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute(
+            "INSERT INTO proxy_transcripts
+             (request_id, provider_id, app_type, model, created_at, request_text, response_text)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                record.request_id,
+                record.provider_id,
+                record.app_type,
+                record.model,
+                record.created_at,
+                record.request_text,
+                record.response_text,
+            ],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    /// 全文搜索对话记录（按时间倒序分页）
+    pub fn search_transcripts(
+        &self,
+        query: &TranscriptSearchQuery,
+        page: u32,
+        page_size: u32,
+    ) -> Result<PaginatedTranscripts, AppError> {
+        let empty = PaginatedTranscripts {
+            data: Vec::new(),
+            total: 0,
+            page,
+            page_size,
+        };
+        let Some(match_expr) = build_match_expr(&query.query) else {
+            return Ok(empty);
+        };
+
+        let conn = lock_conn!(self.conn);
+
+        let mut conditions = vec!["proxy_transcripts MATCH ?"];
+        let mut params: Vec<Box<dyn rusqlite::ToSql>> = vec![Box::new(match_expr)];
+
+        if let Some(ref app_type) = query.app_type {
+            conditions.push("proxy_transcripts.app_type = ?");
+            params.push(Box::new(app_type.clone()));
+        }
+        if let Some(ref provider_id) = query.provider_id {
+            conditions.push("proxy_transcripts.provider_id = ?");
+            params.push(Box::new(provider_id.clone()));
+        }
+        if let Some(ref model) = query.model {
+            conditions.push("proxy_transcripts.model LIKE ?");
+            params.push(Box::new(format!("%{model}%")));
+        }
+        if let Some(start) = query.start_date {
+            conditions.push("proxy_transcripts.created_at >= ?");
+            params.push(Box::new(start));
+        }
+        if let Some(end) = query.end_date {
+            conditions.push("proxy_transcripts.created_at <= ?");
+            params.push(Box::new(end));
+        }
+        let where_clause = conditions.join(" AND ");
+
+        let count_sql = format!("SELECT COUNT(*) FROM proxy_transcripts WHERE {where_clause}");
+        let count_params: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let total: u32 = conn
+            .query_row(&count_sql, count_params.as_slice(), |row| {
+                row.get::<_, i64>(0).map(|v| v as u32)
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        params.push(Box::new(page_size as i64));
+        params.push(Box::new((page * page_size) as i64));
+
+        let sql = format!(
+            "SELECT proxy_transcripts.request_id, proxy_transcripts.provider_id, p.name,
+                    proxy_transcripts.app_type, proxy_transcripts.model, proxy_transcripts.created_at,
+                    snippet(proxy_transcripts, 5, '**', '**', '…', 24),
+                    snippet(proxy_transcripts, 6, '**', '**', '…', 24),
+                    l.status_code,
+                    EXISTS(SELECT 1 FROM proxy_request_captures c
+                           WHERE c.request_id = proxy_transcripts.request_id)
+             FROM proxy_transcripts
+             LEFT JOIN providers p
+               ON proxy_transcripts.provider_id = p.id AND proxy_transcripts.app_type = p.app_type
+             LEFT JOIN proxy_request_logs l ON proxy_transcripts.request_id = l.request_id
+             WHERE {where_clause}
+             ORDER BY proxy_transcripts.created_at DESC
+             LIMIT ? OFFSET ?"
+        );
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+        let rows = stmt
+            .query_map(params_refs.as_slice(), |row| {
+                Ok(TranscriptHit {
+                    request_id: row.get(0)?,
+                    provider_id: row.get(1)?,
+                    provider_name: row.get(2)?,
+                    app_type: row.get(3)?,
+                    model: row.get(4)?,
+                    created_at: row.get(5)?,
+                    request_snippet: row.get(6)?,
+                    response_snippet: row.get(7)?,
+                    status_code: row.get::<_, Option<i64>>(8)?.map(|v| v as u16),
+                    has_capture: row.get::<_, i64>(9)? != 0,
+                })
+            })
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let data = rows
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(PaginatedTranscripts {
+            data,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    /// 获取单条对话记录全文
+    pub fn get_transcript(&self, request_id: &str) -> Result<Option<TranscriptRecord>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            "SELECT request_id, provider_id, app_type, model, created_at, request_text, response_text
+             FROM proxy_transcripts WHERE request_id = ?1",
+            [request_id],
+            |row| {
+                Ok(TranscriptRecord {
+                    request_id: row.get(0)?,
+                    provider_id: row.get(1)?,
+                    app_type: row.get(2)?,
+                    model: row.get(3)?,
+                    created_at: row.get(4)?,
+                    request_text: row.get(5)?,
+                    response_text: row.get(6)?,
+                })
+            },
+        );
+        match result {
+            Ok(record) => Ok(Some(record)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    /// 清空全部对话记录
+    pub fn clear_transcripts(&self) -> Result<(), AppError> {
+        let conn = lock_conn!(self.conn);
+        conn.execute("DELETE FROM proxy_transcripts", [])
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// 把用户输入转换为 FTS5 MATCH 表达式
+///
+/// 每个词作为带引号的短语，避免用户输入中的 `"`、`-`、`:` 等被当作 FTS 语法
+fn build_match_expr(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: &str, provider: &str, created_at: i64, response: &str) -> TranscriptRecord {
+        TranscriptRecord {
+            request_id: id.into(),
+            provider_id: provider.into(),
+            app_type: "claude".into(),
+            model: "claude-sonnet".into(),
+            created_at,
+            request_text: "how does the borrow checker work".into(),
+            response_text: response.into(),
+        }
+    }
+
+    #[test]
+    fn searches_with_filters_and_pagination() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.insert_transcript(&record("r1", "p1", 100, "lifetimes explained"))?;
+        db.insert_transcript(&record("r2", "p2", 200, "ownership and lifetimes"))?;
+        db.insert_transcript(&record("r3", "p1", 300, "unrelated answer"))?;
+
+        let query = TranscriptSearchQuery {
+            query: "lifetimes".into(),
+            ..Default::default()
+        };
+        let result = db.search_transcripts(&query, 0, 10)?;
+        assert_eq!(result.total, 2);
+        assert_eq!(result.data[0].request_id, "r2");
+        assert!(result.data[0].response_snippet.contains("**lifetimes**"));
+        assert_eq!(result.data[0].status_code, None);
+        assert!(!result.data[0].has_capture);
+
+        let filtered = db.search_transcripts(
+            &TranscriptSearchQuery {
+                provider_id: Some("p1".into()),
+                ..query.clone()
+            },
+            0,
+            10,
+        )?;
+        assert_eq!(filtered.total, 1);
+        assert_eq!(filtered.data[0].request_id, "r1");
+
+        let page = db.search_transcripts(&query, 1, 1)?;
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.data[0].request_id, "r1");
+        Ok(())
+    }
+
+    #[test]
+    fn hits_link_request_log_and_capture() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.insert_transcript(&record("r1", "p1", 100, "lifetimes explained"))?;
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (
+                    request_id, provider_id, app_type, model, latency_ms, status_code, created_at
+                ) VALUES ('r1', 'p1', 'claude', 'claude-sonnet', 100, 429, 100)",
+                [],
+            )
+            .map_err(|e| AppError::Database(e.to_string()))?;
+        }
+        db.insert_request_capture(
+            &crate::database::RequestCapture {
+                request_id: "r1".into(),
+                app_type: "claude".into(),
+                endpoint: "/v1/messages".into(),
+                provider_id: "p1".into(),
+                model: "claude-sonnet".into(),
+                created_at: 100,
+                body: "{}".into(),
+                status_code: None,
+                duration_ms: None,
+                response_body: None,
+            },
+            10,
+        )?;
+
+        let result = db.search_transcripts(
+            &TranscriptSearchQuery {
+                query: "lifetimes".into(),
+                ..Default::default()
+            },
+            0,
+            10,
+        )?;
+        assert_eq!(result.data[0].status_code, Some(429));
+        assert!(result.data[0].has_capture);
+        Ok(())
+    }
+
+    #[test]
+    fn fts_syntax_in_query_is_escaped() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.insert_transcript(&record("r1", "p1", 100, "use std::sync"))?;
+
+        let query = TranscriptSearchQuery {
+            query: "std::sync \"".into(),
+            ..Default::default()
+        };
+        assert!(db.search_transcripts(&query, 0, 10).is_ok());
+        assert_eq!(
+            db.search_transcripts(&TranscriptSearchQuery::default(), 0, 10)?
+                .total,
+            0
+        );
+        Ok(())
+    }
+}
@@ -27,12 +27,17 @@ mod backup;
 mod dao;
 mod migration;
 mod schema;
+pub(crate) mod secrets;
 
 #[cfg(test)]
 mod tests;
 
 // DAO 类型导出供外部使用
-pub use dao::FailoverQueueItem;
+pub use dao::{
+    ExperimentArmStats, FailoverQueueItem, JournalEntry, PaginatedTranscripts, ProviderKeySpend,
+    RequestCapture, RequestCaptureSummary, ShadowProviderSummary, ShadowResult, TeamMember,
+    TeamMemberUsage, TranscriptRecord, TranscriptSearchQuery,
+};
 
 use crate::config::get_app_config_dir;
 use crate::error::AppError;
@@ -73,6 +78,8 @@ pub(crate) use lock_conn;
 /// rusqlite::Connection 本身不是 Sync 的，因此需要这层包装。
 pub struct Database {
     pub(crate) conn: Mutex<Connection>,
+    /// 是否把供应商密钥存入系统钥匙串（内存数据库不使用，避免测试写入真实钥匙串）
+    pub(crate) secrets_in_keychain: bool,
 }
 
 impl Database {
@@ -95,6 +102,7 @@ impl Database {
 
         let db = Self {
             conn: Mutex::new(conn),
+            secrets_in_keychain: true,
         };
         db.create_tables()?;
         db.apply_schema_migrations()?;
@@ -113,6 +121,7 @@ impl Database {
 
         let db = Self {
             conn: Mutex::new(conn),
+            secrets_in_keychain: false,
         };
         db.create_tables()?;
         db.ensure_model_pricing_seeded()?;
@@ -237,6 +237,105 @@ impl Database {
         )
         .map_err(|e| AppError::Database(e.to_string()))?;
 
+        // 17. Provider Key Spend 表（多密钥轮换的按密钥消费统计）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS provider_key_spend (
+            app_type TEXT NOT NULL, provider_id TEXT NOT NULL, key_id TEXT NOT NULL,
+            spent_usd TEXT NOT NULL DEFAULT '0', request_count INTEGER NOT NULL DEFAULT 0,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (app_type, provider_id, key_id)
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 18. Proxy Request Journal 表（崩溃时未完成转发的请求）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS proxy_request_journal (
+            request_id TEXT PRIMARY KEY, app_type TEXT NOT NULL, model TEXT NOT NULL,
+            session_id TEXT NOT NULL, accepted_at INTEGER NOT NULL,
+            lost INTEGER NOT NULL DEFAULT 0
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 19. Proxy Transcripts 全文索引表（可选的对话记录采集）
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS proxy_transcripts USING fts5(
+            request_id UNINDEXED, provider_id UNINDEXED, app_type UNINDEXED,
+            model UNINDEXED, created_at UNINDEXED, request_text, response_text
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 20. Team Members 表（团队网关模式下的成员令牌、配额与供应商限制）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS team_members (
+            id TEXT PRIMARY KEY, name TEXT NOT NULL, token TEXT NOT NULL UNIQUE,
+            enabled INTEGER NOT NULL DEFAULT 1, allowed_providers TEXT NOT NULL DEFAULT '[]',
+            daily_request_limit INTEGER, monthly_limit_usd TEXT, created_at INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 21. Proxy Request Captures 表（可选的请求体采集，用于重放）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS proxy_request_captures (
+            request_id TEXT PRIMARY KEY, app_type TEXT NOT NULL, endpoint TEXT NOT NULL,
+            provider_id TEXT NOT NULL, model TEXT NOT NULL, body TEXT NOT NULL,
+            created_at INTEGER NOT NULL, status_code INTEGER, duration_ms INTEGER,
+            response_body TEXT
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_request_captures_created_at ON proxy_request_captures(created_at)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        // 旧版采集表缺少响应列
+        for column in [
+            "status_code INTEGER",
+            "duration_ms INTEGER",
+            "response_body TEXT",
+        ] {
+            let _ = conn.execute(
+                &format!("ALTER TABLE proxy_request_captures ADD COLUMN {column}"),
+                [],
+            );
+        }
+
+        // 22. Proxy Shadow Results 表（影子流量的延迟与用量）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS proxy_shadow_results (
+            id INTEGER PRIMARY KEY AUTOINCREMENT, app_type TEXT NOT NULL,
+            primary_provider_id TEXT NOT NULL, shadow_provider_id TEXT NOT NULL,
+            model TEXT NOT NULL, status_code INTEGER NOT NULL, latency_ms INTEGER NOT NULL,
+            input_tokens INTEGER NOT NULL DEFAULT 0, output_tokens INTEGER NOT NULL DEFAULT 0,
+            error_message TEXT, created_at INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_shadow_results_provider ON proxy_shadow_results(shadow_provider_id, app_type)",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        // 23. Proxy Message Batches 表（批处理 ID 所属的供应商，轮询与下载结果时路由回原供应商）
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS proxy_message_batches (
+            batch_id TEXT PRIMARY KEY, provider_id TEXT NOT NULL, created_at INTEGER NOT NULL
+        )",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
         // 尝试添加 live_takeover_active 列到 proxy_config 表
         let _ = conn.execute(
             "ALTER TABLE proxy_config ADD COLUMN live_takeover_active INTEGER NOT NULL DEFAULT 0",
@@ -291,6 +390,25 @@ impl Database {
             "BOOLEAN NOT NULL DEFAULT 0",
         )?;
 
+        // 确保请求日志记录团队网关成员（对于已存在的数据库）
+        Self::add_column_if_missing(conn, "proxy_request_logs", "member_id", "TEXT")?;
+        let _ = conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_request_logs_member
+             ON proxy_request_logs(member_id, created_at)",
+            [],
+        );
+
+        // 确保请求日志记录 A/B 实验分组（对于已存在的数据库）
+        Self::add_column_if_missing(conn, "proxy_request_logs", "experiment", "TEXT")?;
+
+        // 确保请求日志记录流式输出速度（对于已存在的数据库）
+        Self::add_column_if_missing(
+            conn,
+            "proxy_request_logs",
+            "output_tokens_per_second",
+            "REAL",
+        )?;
+
         // 删除旧的 failover_queue 表（如果存在）
         let _ = conn.execute("DROP INDEX IF EXISTS idx_failover_queue_order", []);
         let _ = conn.execute("DROP TABLE IF EXISTS failover_queue", []);
@@ -0,0 +1,270 @@
+//! 供应商密钥的系统钥匙串存储
+//!
+//! 写入数据库前，把供应商中的密钥存入系统钥匙串（macOS Keychain / Windows 凭据管理器 /
+//! Linux Secret Service），数据库中只保留 `keychain:<app>/<provider_id>/<字段>` 形式的引用：
+//! - `settingsConfig` 中 `env` / `auth` 下的密钥字段（API Key、Token 等）
+//! - `meta.apiKeys` 中的轮换密钥与 `meta.balance.token` 余额查询令牌
+//!
+//! 读取时再解析回原值，因此上层代码看到的始终是完整配置。解析需在释放数据库锁后进行，
+//! 避免钥匙串授权弹窗阻塞其他数据库访问。
+//!
+//! 钥匙串不可用时（如无 Secret Service 的 Linux 环境）保留明文并记录警告。
+//! 写入各 CLI 的 Live 配置仍包含明文密钥（由对应 CLI 读取），不受此影响；
+//! SQL 导出时会解析为明文，导入后再迁移到本机钥匙串。
+
+use crate::provider::ProviderMeta;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// 钥匙串中的服务名
+const SERVICE: &str = "cc-switch";
+/// 引用前缀
+const REF_PREFIX: &str = "keychain:";
+/// 存放密钥字段的配置段
+const SECTIONS: [&str; 2] = ["env", "auth"];
+
+/// 已解析的密钥缓存（引用账户 → 密钥），避免每次读取供应商都访问钥匙串
+static CACHE: Lazy<Mutex<HashMap<String, String>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 判断字段名是否为密钥
+fn is_secret_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    upper.contains("API_KEY") || upper.ends_with("TOKEN") || upper.contains("SECRET")
+}
+
+/// 可能存放密钥的字段（字段名, 值）
+fn slots<'a>(settings: &'a mut Value, meta: &'a mut ProviderMeta) -> Vec<(String, &'a mut String)> {
+    let mut slots = Vec::new();
+    if let Some(obj) = settings.as_object_mut() {
+        for (section, value) in obj.iter_mut() {
+            if !SECTIONS.contains(&section.as_str()) {
+                continue;
+            }
+            let Some(fields) = value.as_object_mut() else {
+                continue;
+            };
+            for (key, value) in fields.iter_mut() {
+                if let Value::String(value) = value {
+                    if is_secret_key(key) {
+                        slots.push((format!("{section}.{key}"), value));
+                    }
+                }
+            }
+        }
+    }
+    for key in meta.api_keys.iter_mut() {
+        slots.push((format!("apiKeys.{}", key.id), &mut key.key));
+    }
+    if let Some(token) = meta.balance.as_mut().and_then(|b| b.token.as_mut()) {
+        slots.push(("balance.token".to_string(), token));
+    }
+    slots
+}
+
+/// 把明文密钥替换为引用，返回（账户, 密钥）列表
+fn extract(
+    app_type: &str,
+    provider_id: &str,
+    settings: &mut Value,
+    meta: &mut ProviderMeta,
+) -> Vec<(String, String)> {
+    let mut extracted = Vec::new();
+    for (field, value) in slots(settings, meta) {
+        if value.trim().is_empty() || value.starts_with(REF_PREFIX) {
+            continue;
+        }
+        let account = format!("{app_type}/{provider_id}/{field}");
+        let secret = std::mem::replace(value, format!("{REF_PREFIX}{account}"));
+        extracted.push((account, secret));
+    }
+    extracted
+}
+
+/// 列出引用账户
+fn references(settings: &Value, meta: &ProviderMeta) -> Vec<String> {
+    let (mut settings, mut meta) = (settings.clone(), meta.clone());
+    slots(&mut settings, &mut meta)
+        .into_iter()
+        .filter_map(|(_, value)| value.strip_prefix(REF_PREFIX).map(str::to_string))
+        .collect()
+}
+
+/// 把引用替换为 `lookup` 返回的密钥（找不到时保留引用）
+fn replace_references(
+    settings: &mut Value,
+    meta: &mut ProviderMeta,
+    lookup: impl Fn(&str) -> Option<String>,
+) {
+    for (_, value) in slots(settings, meta) {
+        let Some(account) = value.strip_prefix(REF_PREFIX) else {
+            continue;
+        };
+        if let Some(secret) = lookup(account) {
+            *value = secret;
+        }
+    }
+}
+
+fn entry(account: &str) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(SERVICE, account)
+}
+
+/// 把密钥移入钥匙串并替换为引用（任一密钥写入失败时保持原始明文）
+pub(crate) fn store(
+    app_type: &str,
+    provider_id: &str,
+    settings: &mut Value,
+    meta: &mut ProviderMeta,
+) {
+    let (mut stored_settings, mut stored_meta) = (settings.clone(), meta.clone());
+    let extracted = extract(
+        app_type,
+        provider_id,
+        &mut stored_settings,
+        &mut stored_meta,
+    );
+    if extracted.is_empty() {
+        return;
+    }
+    for (account, secret) in &extracted {
+        if let Err(e) = entry(account).and_then(|entry| entry.set_password(secret)) {
+            log::warn!("写入系统钥匙串失败，密钥将以明文保存: {e}");
+            return;
+        }
+    }
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.extend(extracted);
+    }
+    *settings = stored_settings;
+    *meta = stored_meta;
+}
+
+/// 把钥匙串引用解析为密钥（不要在持有数据库锁时调用）
+pub(crate) fn resolve(settings: &mut Value, meta: &mut ProviderMeta) {
+    if !has_references(settings, meta) {
+        return;
+    }
+    replace_references(settings, meta, |account| {
+        if let Some(secret) = CACHE.lock().ok()?.get(account) {
+            return Some(secret.clone());
+        }
+        match entry(account).and_then(|entry| entry.get_password()) {
+            Ok(secret) => {
+                if let Ok(mut cache) = CACHE.lock() {
+                    cache.insert(account.to_string(), secret.clone());
+                }
+                Some(secret)
+            }
+            Err(e) => {
+                log::warn!("读取系统钥匙串失败（{account}）: {e}");
+                None
+            }
+        }
+    });
+}
+
+/// 删除引用的钥匙串条目
+pub(crate) fn delete(settings: &Value, meta: &ProviderMeta) {
+    for account in references(settings, meta) {
+        if let Ok(mut cache) = CACHE.lock() {
+            cache.remove(&account);
+        }
+        match entry(&account).and_then(|entry| entry.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) => log::warn!("删除系统钥匙串条目失败（{account}）: {e}"),
+        }
+    }
+}
+
+/// 是否包含钥匙串引用
+pub(crate) fn has_references(settings: &Value, meta: &ProviderMeta) -> bool {
+    !references(settings, meta).is_empty()
+}
+
+/// 是否仍有明文密钥
+pub(crate) fn has_plaintext(settings: &Value, meta: &ProviderMeta) -> bool {
+    let (mut settings, mut meta) = (settings.clone(), meta.clone());
+    !extract("", "", &mut settings, &mut meta).is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{BalanceConfig, ProviderApiKey};
+    use serde_json::json;
+
+    fn meta_with_keys() -> ProviderMeta {
+        ProviderMeta {
+            api_keys: vec![ProviderApiKey {
+                id: "k2".to_string(),
+                key: "sk-pool".to_string(),
+                label: None,
+                allowance_usd: None,
+            }],
+            balance: Some(BalanceConfig {
+                token: Some("balance-token".to_string()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn extracts_secrets_into_references() {
+        let mut settings = json!({
+            "env": {
+                "ANTHROPIC_AUTH_TOKEN": "sk-secret",
+                "ANTHROPIC_BASE_URL": "https://api.example.com",
+                "ANTHROPIC_API_KEY": "",
+            },
+            "auth": { "OPENAI_API_KEY": "sk-openai" },
+        });
+        let mut meta = meta_with_keys();
+        let extracted = extract("claude", "p1", &mut settings, &mut meta);
+        let mut accounts: Vec<&str> = extracted.iter().map(|(a, _)| a.as_str()).collect();
+        accounts.sort_unstable();
+        assert_eq!(
+            accounts,
+            vec![
+                "claude/p1/apiKeys.k2",
+                "claude/p1/auth.OPENAI_API_KEY",
+                "claude/p1/balance.token",
+                "claude/p1/env.ANTHROPIC_AUTH_TOKEN",
+            ]
+        );
+        assert_eq!(
+            settings["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "keychain:claude/p1/env.ANTHROPIC_AUTH_TOKEN"
+        );
+        assert_eq!(
+            settings["env"]["ANTHROPIC_BASE_URL"],
+            "https://api.example.com"
+        );
+        assert_eq!(meta.api_keys[0].key, "keychain:claude/p1/apiKeys.k2");
+        assert!(!has_plaintext(&settings, &meta));
+        // 已是引用的字段不会重复提取
+        assert!(extract("claude", "p1", &mut settings, &mut meta).is_empty());
+    }
+
+    #[test]
+    fn replaces_known_references_only() {
+        let mut settings = json!({
+            "env": {
+                "GEMINI_API_KEY": "keychain:gemini/p1/env.GEMINI_API_KEY",
+                "GOOGLE_API_KEY": "keychain:gemini/p1/env.GOOGLE_API_KEY",
+            },
+        });
+        let mut meta = ProviderMeta::default();
+        assert_eq!(references(&settings, &meta).len(), 2);
+        replace_references(&mut settings, &mut meta, |account| {
+            (account == "gemini/p1/env.GEMINI_API_KEY").then(|| "AIza-secret".to_string())
+        });
+        assert_eq!(settings["env"]["GEMINI_API_KEY"], "AIza-secret");
+        assert_eq!(
+            settings["env"]["GOOGLE_API_KEY"],
+            "keychain:gemini/p1/env.GOOGLE_API_KEY"
+        );
+    }
+}
@@ -3,15 +3,19 @@ mod app_store;
 mod auto_launch;
 mod claude_mcp;
 mod claude_plugin;
+mod cli;
 mod codex_config;
 mod commands;
 mod config;
+mod daemon;
+mod data_dir;
 mod database;
 mod deeplink;
 mod error;
 mod gemini_config;
 mod gemini_mcp;
 mod init_status;
+mod log_levels;
 mod mcp;
 mod panic_hook;
 mod prompt;
@@ -26,10 +30,12 @@ mod tray;
 mod usage_script;
 
 pub use app_config::{AppType, McpApps, McpServer, MultiAppConfig};
+pub use cli::{is_cli_invocation, run as run_cli};
 pub use codex_config::{get_codex_auth_path, get_codex_config_path, write_codex_live_atomic};
 pub use commands::open_provider_terminal;
 pub use commands::*;
 pub use config::{get_claude_mcp_path, get_claude_settings_path, read_json_file};
+pub use daemon::{is_daemon_requested, run as run_daemon};
 pub use database::Database;
 pub use deeplink::{import_provider_from_deeplink, parse_deeplink_url, DeepLinkImportRequest};
 pub use error::AppError;
@@ -121,13 +127,8 @@ fn handle_deeplink_url(
                 log::info!("✓ Emitted deeplink-import event to frontend");
             }
 
-            if focus_main_window {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.unminimize();
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                    log::info!("✓ Window shown and focused");
-                }
+            if focus_main_window && tray::show_main_window(app) {
+                log::info!("✓ Window shown and focused");
             }
         }
         Err(e) => {
@@ -212,12 +213,21 @@ pub fn run() {
                 log::info!("ℹ No deep link URL found in args (this is expected on macOS when launched via system)");
             }
 
-            // Show and focus window regardless
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.unminimize();
-                let _ = window.show();
-                let _ = window.set_focus();
+            // 命令行子命令与 --daemon 在 main 中先于单实例检查执行（子命令经控制接口操作本实例），
+            // 能转发到这里的只有深链接（已在上方交给后端处理）与无法识别的参数；
+            // 第二个实例会在回调返回后退出，不会再启动自己的代理服务
+            let ignored_args: Vec<String> = args
+                .iter()
+                .skip(1)
+                .filter(|arg| !arg.starts_with("ccswitch://"))
+                .map(|arg| redact_url_for_log(arg))
+                .collect();
+            if !ignored_args.is_empty() {
+                log::warn!("忽略第二个实例的无法识别的启动参数: {ignored_args:?}");
             }
+
+            // Show and focus window regardless
+            tray::show_main_window(app);
         }));
     }
 
@@ -247,11 +257,14 @@ pub fn run() {
         })
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_store::Builder::new().build())
         .setup(|app| {
             // 预先刷新 Store 覆盖配置，确保后续路径读取正确（日志/数据库等）
             app_store::refresh_app_config_dir_override(app.handle());
+            // 主目录只读（企业电脑 / 沙箱）时改用可写的回退目录
+            data_dir::detect_writable_app_config_dir();
             panic_hook::init_app_config_dir(crate::config::get_app_config_dir());
 
             // 注册 Updater 插件（桌面端）
@@ -265,7 +278,7 @@ pub fn run() {
                     log::warn!("初始化 Updater 插件失败，已跳过：{e}");
                 }
             }
-            // 初始化日志（Debug 和 Release 模式都启用 Info 级别）
+            // 初始化日志（默认 Info 级别，数据库就绪后按分类日志级别配置调整）
             // 日志同时输出到控制台和文件（<app_config_dir>/logs/；若设置了覆盖则使用覆盖目录）
             {
                 use tauri_plugin_log::{RotationStrategy, Target, TargetKind, TimezoneStrategy};
@@ -274,7 +287,8 @@ pub fn run() {
 
                 app.handle().plugin(
                     tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
+                        .level(log::LevelFilter::Trace)
+                        .filter(log_levels::enabled)
                         .targets([
                             // 输出到控制台
                             Target::new(TargetKind::Stdout),
@@ -378,6 +392,15 @@ pub fn run() {
                 }
             }
 
+            // 把仍以明文保存的供应商密钥迁移到系统钥匙串
+            match db.migrate_secrets_to_keychain() {
+                Ok(count) if count > 0 => {
+                    log::info!("✓ 已将 {count} 个供应商的密钥迁移到系统钥匙串");
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("✗ 迁移供应商密钥到系统钥匙串失败: {e}"),
+            }
+
             let app_state = AppState::new(db);
 
             // 设置 AppHandle 用于代理故障转移时的 UI 更新
@@ -645,60 +668,48 @@ pub fn run() {
             app.manage(commands::skill::SkillServiceState(Arc::new(skill_service)));
 
             // 初始化全局出站代理 HTTP 客户端
-            {
-                let db = &app.state::<AppState>().db;
-                let proxy_url = db.get_global_proxy_url().ok().flatten();
-
-                if let Err(e) = crate::proxy::http_client::init(proxy_url.as_deref()) {
-                    log::error!(
-                        "[GlobalProxy] [GP-005] Failed to initialize with saved config: {e}"
-                    );
-
-                    // 清除无效的代理配置
-                    if proxy_url.is_some() {
-                        log::warn!(
-                            "[GlobalProxy] [GP-006] Clearing invalid proxy config from database"
-                        );
-                        if let Err(clear_err) = db.set_global_proxy_url(None) {
-                            log::error!(
-                                "[GlobalProxy] [GP-007] Failed to clear invalid config: {clear_err}"
-                            );
-                        }
-                    }
+            init_global_http_client(&app.state::<AppState>().db);
 
-                    // 使用直连模式重新初始化
-                    if let Err(fallback_err) = crate::proxy::http_client::init(None) {
-                        log::error!(
-                            "[GlobalProxy] [GP-008] Failed to initialize direct connection: {fallback_err}"
-                        );
-                    }
-                }
-            }
+            // 启动上游状态页监控
+            crate::services::StatusWatcherService::start(
+                app.state::<AppState>().db.clone(),
+                app.handle().clone(),
+            );
+
+            // 启动后台上游健康探测（未启用时仅定时检查配置）
+            crate::services::HealthProbeService::start(
+                app.state::<AppState>().db.clone(),
+                app.handle().clone(),
+            );
+
+            // 启动睡眠唤醒 / 网络切换监控
+            crate::services::WakeWatcherService::start(
+                app.state::<AppState>().db.clone(),
+                app.handle().clone(),
+            );
+
+            // 启动匿名使用统计上报（用户未开启时不发送）
+            crate::services::TelemetryService::start(app.state::<AppState>().db.clone());
+
+            // 迁移旧版 ~/tmp/log 调试日志，之后每小时按保留策略（天数、总大小、压缩）清理一次
+            let db = app.state::<AppState>().db.clone();
+            log_levels::apply(&db.get_log_levels_config().unwrap_or_default());
+            crate::proxy::debug_log::apply_config(&db.get_debug_log_config().unwrap_or_default());
+            std::thread::spawn(move || loop {
+                let config = db.get_debug_log_config().unwrap_or_default();
+                crate::proxy::log_retention::migrate_debug_logs(&config);
+                std::thread::sleep(std::time::Duration::from_secs(60 * 60));
+            });
 
             // 异常退出恢复 + 代理状态自动恢复
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 let state = app_handle.state::<AppState>();
 
-                // 检查是否有 Live 备份（表示上次异常退出时可能处于接管状态）
-                let has_backups = match state.db.has_any_live_backup().await {
-                    Ok(v) => v,
-                    Err(e) => {
-                        log::error!("检查 Live 备份失败: {e}");
-                        false
-                    }
-                };
-                // 检查 Live 配置是否仍处于被接管状态（包含占位符）
-                let live_taken_over = state.proxy_service.detect_takeover_in_live_configs();
-
-                if has_backups || live_taken_over {
-                    log::warn!("检测到上次异常退出（存在接管残留），正在恢复 Live 配置...");
-                    if let Err(e) = state.proxy_service.recover_from_crash().await {
-                        log::error!("恢复 Live 配置失败: {e}");
-                    } else {
-                        log::info!("Live 配置已恢复");
-                    }
-                }
+                recover_live_configs_after_crash(&state).await;
+
+                // 上报上次异常退出时未完成转发的请求（需在代理恢复前执行）
+                crate::proxy::journal::recover_lost_requests(&state.db, &app_handle);
 
                 // 检查 settings 表中的代理状态，自动恢复代理服务
                 restore_proxy_state_on_startup(&state).await;
@@ -710,6 +721,7 @@ pub fn run() {
             commands::get_providers,
             commands::get_current_provider,
             commands::add_provider,
+            commands::add_mock_provider,
             commands::update_provider,
             commands::delete_provider,
             commands::switch_provider,
@@ -736,9 +748,67 @@ pub fn run() {
             commands::save_settings,
             commands::get_rectifier_config,
             commands::set_rectifier_config,
+            commands::get_client_rate_limit_config,
+            commands::set_client_rate_limit_config,
+            commands::list_profiles,
+            commands::get_active_profile_id,
+            commands::save_profile,
+            commands::capture_profile,
+            commands::delete_profile,
+            commands::apply_profile,
+            commands::get_team_gateway_config,
+            commands::set_team_gateway_config,
+            commands::get_failback_config,
+            commands::set_failback_config,
+            commands::get_failure_notification_config,
+            commands::set_failure_notification_config,
+            commands::get_cost_alert_config,
+            commands::set_cost_alert_config,
+            commands::get_alert_rules,
+            commands::set_alert_rules,
+            commands::get_telemetry_config,
+            commands::set_telemetry_config,
+            commands::preview_telemetry_report,
+            commands::get_provider_scoring_config,
+            commands::set_provider_scoring_config,
+            commands::get_ip_allowlist_config,
+            commands::set_ip_allowlist_config,
+            commands::get_request_size_limit_config,
+            commands::set_request_size_limit_config,
+            commands::get_webhook_config,
+            commands::set_webhook_config,
+            commands::test_webhook,
+            commands::get_experiments,
+            commands::set_experiments,
+            commands::get_experiment_results,
+            commands::get_log_redaction_config,
+            commands::set_log_redaction_config,
+            commands::get_debug_log_config,
+            commands::set_debug_log_config,
+            commands::set_debug_logging_enabled,
+            commands::set_debug_log_bodies,
+            commands::get_log_levels_config,
+            commands::set_log_levels_config,
+            commands::get_otel_config,
+            commands::set_otel_config,
+            commands::get_cost_annotation_config,
+            commands::set_cost_annotation_config,
+            commands::get_transcript_config,
+            commands::set_transcript_config,
+            commands::get_conversation_capture_config,
+            commands::set_conversation_capture_config,
+            commands::get_replay_capture_config,
+            commands::set_replay_capture_config,
+            commands::get_shadow_traffic_config,
+            commands::set_shadow_traffic_config,
+            commands::get_response_cache_config,
+            commands::set_response_cache_config,
+            commands::get_sse_keepalive_config,
+            commands::set_sse_keepalive_config,
             commands::restart_app,
             commands::check_for_updates,
             commands::is_portable_mode,
+            commands::export_diagnostic_bundle,
             commands::get_claude_plugin_status,
             commands::read_claude_plugin_config,
             commands::apply_claude_plugin_config,
@@ -753,6 +823,14 @@ pub fn run() {
             commands::validate_mcp_command,
             // usage query
             commands::queryProviderUsage,
+            commands::queryProviderBalance,
+            commands::export_provider_summary,
+            commands::export_providers_encrypted,
+            commands::import_providers_encrypted,
+            commands::export_provider_seed,
+            commands::import_provider_seed,
+            commands::import_provider_from_clipboard,
+            commands::get_provider_status_incidents,
             commands::testUsageScript,
             // New MCP via config.json (SSOT)
             commands::get_mcp_config,
@@ -780,6 +858,8 @@ pub fn run() {
             commands::update_endpoint_last_used,
             // app_config_dir override via Store
             commands::get_app_config_dir_override,
+            commands::get_data_paths,
+            commands::cleanup_debug_logs,
             commands::set_app_config_dir_override,
             // provider sort order management
             commands::update_providers_sort_order,
@@ -823,6 +903,8 @@ pub fn run() {
             // Proxy server management
             commands::start_proxy_server,
             commands::stop_proxy_with_restore,
+            commands::get_lost_proxy_requests,
+            commands::clear_lost_proxy_requests,
             commands::get_proxy_takeover_status,
             commands::set_proxy_takeover_for_app,
             commands::get_proxy_status,
@@ -831,6 +913,19 @@ pub fn run() {
             // Global & Per-App Config
             commands::get_global_proxy_config,
             commands::update_global_proxy_config,
+            commands::get_proxy_lan_access,
+            commands::set_proxy_lan_access,
+            commands::get_proxy_access_token,
+            commands::set_proxy_access_token,
+            commands::generate_proxy_access_token,
+            commands::get_proxy_admin_token,
+            commands::set_proxy_admin_token,
+            commands::generate_proxy_admin_token,
+            commands::get_proxy_tls_config,
+            commands::set_proxy_tls_config,
+            commands::get_proxy_certificate_path,
+            commands::regenerate_proxy_certificate,
+            commands::get_active_proxy_endpoint,
             commands::get_proxy_config_for_app,
             commands::update_proxy_config_for_app,
             commands::is_proxy_running,
@@ -842,8 +937,28 @@ pub fn run() {
             commands::get_circuit_breaker_config,
             commands::update_circuit_breaker_config,
             commands::get_circuit_breaker_stats,
+            // Team gateway members
+            commands::list_team_members,
+            commands::save_team_member,
+            commands::regenerate_team_member_token,
+            commands::delete_team_member,
+            commands::get_team_member_usage,
+            // Rate limit simulation
+            commands::get_rate_limit_simulation,
+            commands::start_rate_limit_simulation,
+            commands::stop_rate_limit_simulation,
+            commands::get_fault_injection,
+            commands::start_fault_injection,
+            commands::stop_fault_injection,
+            commands::get_mock_dry_run,
+            commands::set_mock_dry_run,
+            commands::get_response_cache_stats,
+            commands::clear_response_cache,
+            commands::get_provider_scores,
+            commands::export_alert_rules,
             // Failover queue management
             commands::get_failover_queue,
+            commands::run_failover_drill,
             commands::get_available_providers_for_failover,
             commands::add_to_failover_queue,
             commands::remove_from_failover_queue,
@@ -852,20 +967,56 @@ pub fn run() {
             // Usage statistics
             commands::get_usage_summary,
             commands::get_usage_trends,
+            commands::get_usage_series,
+            commands::export_usage_report,
+            commands::export_har,
             commands::get_provider_stats,
+            commands::get_provider_latency_percentiles,
             commands::get_model_stats,
             commands::get_request_logs,
             commands::get_request_detail,
+            commands::get_request_inspection,
+            commands::search_transcripts,
+            commands::get_transcript,
+            commands::clear_transcripts,
+            commands::get_request_captures,
+            commands::get_request_capture,
+            commands::clear_request_captures,
+            commands::replay_request,
+            commands::get_shadow_results,
+            commands::get_shadow_summary,
+            commands::clear_shadow_results,
             commands::get_model_pricing,
             commands::update_model_pricing,
             commands::delete_model_pricing,
+            commands::export_model_pricing_json,
+            commands::import_model_pricing_json,
             commands::check_provider_limits,
+            commands::get_provider_key_spend,
+            commands::reset_provider_key_spend,
             // Stream health check
             commands::stream_check_provider,
             commands::stream_check_all_providers,
             commands::get_stream_check_config,
             commands::save_stream_check_config,
+            commands::get_health_probe_config,
+            commands::save_health_probe_config,
+            commands::get_health_probe_results,
+            // Provider onboarding wizard
+            commands::onboarding_normalize_url,
+            commands::onboarding_probe_auth,
+            commands::onboarding_probe_capabilities,
+            commands::onboarding_discover_models,
+            commands::onboarding_suggest_model_map,
+            commands::onboarding_end_to_end_test,
             commands::get_tool_versions,
+            // Provider response comparison
+            commands::compare_provider_responses,
+            // Claude settings history
+            commands::list_claude_settings_history,
+            commands::get_claude_settings_version,
+            commands::diff_claude_settings_versions,
+            commands::restore_claude_settings_version,
             // Provider terminal
             commands::open_provider_terminal,
             // Universal Provider management
@@ -912,16 +1063,7 @@ pub fn run() {
             match event {
                 // macOS 在 Dock 图标被点击并重新激活应用时会触发 Reopen 事件，这里手动恢复主窗口
                 RunEvent::Reopen { .. } => {
-                    if let Some(window) = app_handle.get_webview_window("main") {
-                        #[cfg(target_os = "windows")]
-                        {
-                            let _ = window.set_skip_taskbar(false);
-                        }
-                        let _ = window.unminimize();
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                        tray::apply_tray_policy(app_handle, true);
-                    }
+                    tray::show_main_window(app_handle);
                 }
                 // 处理通过自定义 URL 协议触发的打开事件（例如 ccswitch://...）
                 RunEvent::Opened { urls } => {
@@ -967,11 +1109,7 @@ pub fn run() {
                             }
 
                             // 确保主窗口可见
-                            if let Some(window) = app_handle.get_webview_window("main") {
-                                let _ = window.unminimize();
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                            }
+                            tray::show_main_window(app_handle);
                         }
                     }
                 }
@@ -997,37 +1135,89 @@ pub fn run() {
 /// 使用 stop_with_restore_keep_state 保留 settings 表中的代理状态，下次启动时自动恢复。
 pub async fn cleanup_before_exit(app_handle: &tauri::AppHandle) {
     if let Some(state) = app_handle.try_state::<store::AppState>() {
-        let proxy_service = &state.proxy_service;
+        cleanup_proxy_before_exit(&state).await;
+    }
+}
 
-        // 退出时也需要兜底：代理可能已崩溃/未运行，但 Live 接管残留仍在（占位符/备份）。
-        let has_backups = match state.db.has_any_live_backup().await {
-            Ok(v) => v,
-            Err(e) => {
-                log::error!("退出时检查 Live 备份失败: {e}");
-                false
-            }
-        };
-        let live_taken_over = proxy_service.detect_takeover_in_live_configs();
-        let needs_restore = has_backups || live_taken_over;
-
-        if needs_restore {
-            log::info!("检测到接管残留，开始恢复 Live 配置（保留代理状态）...");
-            // 使用 keep_state 版本，保留 settings 表中的代理状态
-            if let Err(e) = proxy_service.stop_with_restore_keep_state().await {
-                log::error!("退出时恢复 Live 配置失败: {e}");
-            } else {
-                log::info!("已恢复 Live 配置（代理状态已保留，下次启动将自动恢复）");
-            }
-            return;
+/// 退出前停止代理并恢复 Live 配置（GUI 与守护进程模式共用）
+async fn cleanup_proxy_before_exit(state: &store::AppState) {
+    let proxy_service = &state.proxy_service;
+
+    // 退出时也需要兜底：代理可能已崩溃/未运行，但 Live 接管残留仍在（占位符/备份）。
+    let has_backups = match state.db.has_any_live_backup().await {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("退出时检查 Live 备份失败: {e}");
+            false
+        }
+    };
+    let live_taken_over = proxy_service.detect_takeover_in_live_configs();
+    let needs_restore = has_backups || live_taken_over;
+
+    if needs_restore {
+        log::info!("检测到接管残留，开始恢复 Live 配置（保留代理状态）...");
+        // 使用 keep_state 版本，保留 settings 表中的代理状态
+        if let Err(e) = proxy_service.stop_with_restore_keep_state().await {
+            log::error!("退出时恢复 Live 配置失败: {e}");
+        } else {
+            log::info!("已恢复 Live 配置（代理状态已保留，下次启动将自动恢复）");
         }
+        return;
+    }
 
-        // 非接管模式：代理在运行则仅停止代理
-        if proxy_service.is_running().await {
-            log::info!("检测到代理服务器正在运行，开始停止...");
-            if let Err(e) = proxy_service.stop().await {
-                log::error!("退出时停止代理失败: {e}");
+    // 非接管模式：代理在运行则仅停止代理
+    if proxy_service.is_running().await {
+        log::info!("检测到代理服务器正在运行，开始停止...");
+        if let Err(e) = proxy_service.stop().await {
+            log::error!("退出时停止代理失败: {e}");
+        }
+        log::info!("代理服务器清理完成");
+    }
+}
+
+/// 初始化全局出站代理 HTTP 客户端（保存的代理无效时清除并回退为直连）
+fn init_global_http_client(db: &crate::database::Database) {
+    let proxy_url = db.get_global_proxy_url().ok().flatten();
+
+    if let Err(e) = crate::proxy::http_client::init(proxy_url.as_deref()) {
+        log::error!("[GlobalProxy] [GP-005] Failed to initialize with saved config: {e}");
+
+        // 清除无效的代理配置
+        if proxy_url.is_some() {
+            log::warn!("[GlobalProxy] [GP-006] Clearing invalid proxy config from database");
+            if let Err(clear_err) = db.set_global_proxy_url(None) {
+                log::error!("[GlobalProxy] [GP-007] Failed to clear invalid config: {clear_err}");
             }
-            log::info!("代理服务器清理完成");
+        }
+
+        // 使用直连模式重新初始化
+        if let Err(fallback_err) = crate::proxy::http_client::init(None) {
+            log::error!(
+                "[GlobalProxy] [GP-008] Failed to initialize direct connection: {fallback_err}"
+            );
+        }
+    }
+}
+
+/// 上次异常退出时残留接管状态（Live 备份 / 占位符）则恢复 Live 配置
+async fn recover_live_configs_after_crash(state: &store::AppState) {
+    // 检查是否有 Live 备份（表示上次异常退出时可能处于接管状态）
+    let has_backups = match state.db.has_any_live_backup().await {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("检查 Live 备份失败: {e}");
+            false
+        }
+    };
+    // 检查 Live 配置是否仍处于被接管状态（包含占位符）
+    let live_taken_over = state.proxy_service.detect_takeover_in_live_configs();
+
+    if has_backups || live_taken_over {
+        log::warn!("检测到上次异常退出（存在接管残留），正在恢复 Live 配置...");
+        if let Err(e) = state.proxy_service.recover_from_crash().await {
+            log::error!("恢复 Live 配置失败: {e}");
+        } else {
+            log::info!("Live 配置已恢复");
         }
     }
 }
@@ -0,0 +1,189 @@
+//! 分类日志级别
+//!
+//! 应用日志按模块归入四个分类，每个分类的级别可在运行时独立调整（settings 表 `log_levels_config`）：
+//! - `retry`：限流重试、熔断、故障转移与自动回切
+//! - `debugCapture`：流式响应的逐块/逐事件日志、调试日志与采集（重放、对话记录）
+//! - `proxy`：其余代理模块
+//! - `config`：配置文件读写与设置
+//!
+//! 例如单独把 `retry` 调到 debug 查看重试细节，而不会被 `debugCapture` 的逐块日志淹没。
+//! 未归类的日志（其他模块与第三方库）固定为 info。
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 日志级别
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for log::LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => Self::Off,
+            LogLevel::Error => Self::Error,
+            LogLevel::Warn => Self::Warn,
+            LogLevel::Info => Self::Info,
+            LogLevel::Debug => Self::Debug,
+            LogLevel::Trace => Self::Trace,
+        }
+    }
+}
+
+/// 分类日志级别配置
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLevelsConfig {
+    #[serde(default)]
+    pub proxy: LogLevel,
+    #[serde(default)]
+    pub retry: LogLevel,
+    #[serde(default)]
+    pub config: LogLevel,
+    #[serde(default)]
+    pub debug_capture: LogLevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Proxy = 0,
+    Retry = 1,
+    Config = 2,
+    DebugCapture = 3,
+}
+
+/// 各分类的模块前缀（按顺序匹配，更具体的前缀在前）
+const CATEGORY_TARGETS: &[(&str, Category)] = &[
+    ("cc_switch_lib::proxy::rate_limit_retry", Category::Retry),
+    ("cc_switch_lib::proxy::circuit_breaker", Category::Retry),
+    ("cc_switch_lib::proxy::failover_switch", Category::Retry),
+    ("cc_switch_lib::proxy::failback", Category::Retry),
+    ("cc_switch_lib::proxy::debug_log", Category::DebugCapture),
+    (
+        "cc_switch_lib::proxy::response_processor",
+        Category::DebugCapture,
+    ),
+    (
+        "cc_switch_lib::proxy::providers::streaming",
+        Category::DebugCapture,
+    ),
+    (
+        "cc_switch_lib::proxy::stream_buffer",
+        Category::DebugCapture,
+    ),
+    (
+        "cc_switch_lib::proxy::sse_keepalive",
+        Category::DebugCapture,
+    ),
+    ("cc_switch_lib::proxy::replay", Category::DebugCapture),
+    ("cc_switch_lib::proxy::transcript", Category::DebugCapture),
+    (
+        "cc_switch_lib::proxy::conversation_capture",
+        Category::DebugCapture,
+    ),
+    ("cc_switch_lib::proxy", Category::Proxy),
+    ("cc_switch_lib::config", Category::Config),
+    ("cc_switch_lib::app_config", Category::Config),
+    ("cc_switch_lib::codex_config", Category::Config),
+    ("cc_switch_lib::gemini_config", Category::Config),
+    ("cc_switch_lib::settings", Category::Config),
+    ("cc_switch_lib::store", Category::Config),
+    ("cc_switch_lib::app_store", Category::Config),
+    ("cc_switch_lib::services::config", Category::Config),
+];
+
+/// 未归类日志的级别
+const DEFAULT_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+
+/// 各分类当前级别（`LevelFilter as usize`）
+static LEVELS: [AtomicUsize; 4] = [
+    AtomicUsize::new(DEFAULT_LEVEL as usize),
+    AtomicUsize::new(DEFAULT_LEVEL as usize),
+    AtomicUsize::new(DEFAULT_LEVEL as usize),
+    AtomicUsize::new(DEFAULT_LEVEL as usize),
+];
+
+fn category(target: &str) -> Option<Category> {
+    CATEGORY_TARGETS
+        .iter()
+        .find(|(prefix, _)| {
+            target
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+        .map(|(_, category)| *category)
+}
+
+fn level_filter(value: usize) -> log::LevelFilter {
+    match value {
+        0 => log::LevelFilter::Off,
+        1 => log::LevelFilter::Error,
+        2 => log::LevelFilter::Warn,
+        3 => log::LevelFilter::Info,
+        4 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    }
+}
+
+fn level_for(target: &str) -> log::LevelFilter {
+    match category(target) {
+        Some(category) => level_filter(LEVELS[category as usize].load(Ordering::Relaxed)),
+        None => DEFAULT_LEVEL,
+    }
+}
+
+/// 日志过滤器（注册到日志插件）
+pub fn enabled(metadata: &log::Metadata) -> bool {
+    metadata.level() <= level_for(metadata.target())
+}
+
+/// 应用分类级别（启动时及配置更新后调用）
+pub fn apply(config: &LogLevelsConfig) {
+    let levels = [
+        (Category::Proxy, config.proxy),
+        (Category::Retry, config.retry),
+        (Category::Config, config.config),
+        (Category::DebugCapture, config.debug_capture),
+    ];
+    let mut max = DEFAULT_LEVEL;
+    for (category, level) in levels {
+        let filter = log::LevelFilter::from(level);
+        LEVELS[category as usize].store(filter as usize, Ordering::Relaxed);
+        max = max.max(filter);
+    }
+    // 全局上限取各分类最高级别，低于上限的日志宏直接跳过
+    log::set_max_level(max);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_targets_to_most_specific_category() {
+        assert_eq!(
+            category("cc_switch_lib::proxy::rate_limit_retry"),
+            Some(Category::Retry)
+        );
+        assert_eq!(
+            category("cc_switch_lib::proxy::response_processor"),
+            Some(Category::DebugCapture)
+        );
+        assert_eq!(
+            category("cc_switch_lib::proxy::forwarder"),
+            Some(Category::Proxy)
+        );
+        assert_eq!(category("cc_switch_lib::settings"), Some(Category::Config));
+        // 前缀必须在模块边界处匹配
+        assert_eq!(category("cc_switch_lib::settings_history"), None);
+        assert_eq!(category("hyper::client"), None);
+    }
+}
@@ -1,7 +1,42 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+/// Windows 发布版使用 GUI 子系统，没有控制台：命令行 / 守护进程模式下附加到父进程（终端）的控制台，
+/// 没有父控制台（如从资源管理器启动）时新建一个，否则输出不可见
+#[cfg(all(windows, not(debug_assertions)))]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+        fn AllocConsole() -> i32;
+    }
+    // SAFETY: 两个 Win32 API 均无指针参数，失败时仅返回 0
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS) == 0 {
+            AllocConsole();
+        }
+    }
+}
+
+#[cfg(not(all(windows, not(debug_assertions))))]
+fn attach_console() {}
+
 fn main() {
+    // 命令行模式：`cc-switch list` / `use <名称>` 等子命令，执行完即退出
+    let args: Vec<String> = std::env::args().collect();
+    if cc_switch_lib::is_cli_invocation(&args) {
+        attach_console();
+        std::process::exit(cc_switch_lib::run_cli(args));
+    }
+
+    // 无界面守护进程模式：仅启动代理与配置服务，不创建窗口
+    if cc_switch_lib::is_daemon_requested(args) {
+        attach_console();
+        cc_switch_lib::run_daemon();
+        return;
+    }
+
     // 在 Linux 上设置 WebKit 环境变量以解决 DMA-BUF 渲染问题
     // 某些 Linux 系统（如 Debian 13.2、Nvidia GPU）上 WebKitGTK 的 DMA-BUF 渲染器可能导致白屏/黑屏
     // 参考: https://github.com/tauri-apps/tauri/issues/9394
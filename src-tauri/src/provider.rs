@@ -65,6 +65,19 @@ impl Provider {
             in_failover_queue: false,
         }
     }
+
+    /// 测试用：创建仅携带指定元数据的供应商（名称为 ID 的大写形式）
+    #[cfg(test)]
+    pub(crate) fn with_meta(id: &str, meta: ProviderMeta) -> Self {
+        let mut provider = Self::with_id(
+            id.into(),
+            id.to_uppercase(),
+            Value::Object(Default::default()),
+            None,
+        );
+        provider.meta = Some(meta);
+        provider
+    }
 }
 
 /// 供应商管理器
@@ -142,6 +155,63 @@ pub struct UsageResult {
     pub error: Option<String>,
 }
 
+/// 中转站余额接口类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BalanceKind {
+    /// OpenAI 兼容账单接口（one-api / new-api 等中转站通用）
+    #[default]
+    OpenaiBilling,
+    /// OpenRouter `/api/v1/credits`
+    Openrouter,
+    /// DeepSeek `/user/balance`
+    Deepseek,
+    /// SiliconFlow `/v1/user/info`
+    Siliconflow,
+    /// 自定义 URL + JSON Pointer
+    Custom,
+}
+
+/// 余额查询配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub kind: BalanceKind,
+    /// 接口地址（预设类型时为站点根地址，自定义类型时为完整 URL；为空时使用供应商 Base URL）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// 查询令牌（为空时使用供应商 API Key）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// 自定义类型的余额字段路径（JSON Pointer，如 `/data/balance`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_path: Option<String>,
+    /// 余额单位（为空时使用接口返回或预设单位）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// 低余额告警阈值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_threshold: Option<f64>,
+}
+
+/// 余额查询结果
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// 余额低于告警阈值
+    pub low_balance: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 /// 供应商元数据
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProviderMeta {
@@ -172,6 +242,452 @@ pub struct ProviderMeta {
     /// 每月消费限额（USD）
     #[serde(rename = "limitMonthlyUsd", skip_serializing_if = "Option::is_none")]
     pub limit_monthly_usd: Option<String>,
+    /// 每日 token 限额（输入 + 输出）
+    #[serde(rename = "limitDailyTokens", skip_serializing_if = "Option::is_none")]
+    pub limit_daily_tokens: Option<u64>,
+    /// 每月 token 限额（输入 + 输出）
+    #[serde(rename = "limitMonthlyTokens", skip_serializing_if = "Option::is_none")]
+    pub limit_monthly_tokens: Option<u64>,
+    /// 超出限额时代理的处理方式（为空时限额仅用于展示）
+    #[serde(rename = "budgetAction", skip_serializing_if = "Option::is_none")]
+    pub budget_action: Option<BudgetAction>,
+    /// 限额重置日历（为空时按本地时区自然日 / 自然月重置）
+    #[serde(rename = "quotaCalendar", skip_serializing_if = "Option::is_none")]
+    pub quota_calendar: Option<QuotaCalendar>,
+    /// 请求头改写规则（代理转发时应用）
+    #[serde(rename = "headerRules", skip_serializing_if = "Option::is_none")]
+    pub header_rules: Option<HeaderRules>,
+    /// 客户端请求头透传策略（白名单/黑名单）
+    #[serde(rename = "headerPassthrough", skip_serializing_if = "Option::is_none")]
+    pub header_passthrough: Option<HeaderPassthrough>,
+    /// 系统提示词注入（代理转发时添加到请求的系统提示词前后）
+    #[serde(rename = "systemPrompt", skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<SystemPromptInjection>,
+    /// 固定发送给上游的 anthropic-version（为空时沿用客户端版本）
+    #[serde(rename = "anthropicVersion", skip_serializing_if = "Option::is_none")]
+    pub anthropic_version: Option<String>,
+    /// 自动提示词缓存（为大段 system/tools 注入 cache_control 断点）
+    #[serde(rename = "promptCaching", skip_serializing_if = "Option::is_none")]
+    pub prompt_caching: Option<PromptCachingConfig>,
+    /// 中转站余额查询配置
+    #[serde(rename = "balance", skip_serializing_if = "Option::is_none")]
+    pub balance: Option<BalanceConfig>,
+    /// 出站请求 max_tokens 上限
+    #[serde(rename = "maxTokensLimit", skip_serializing_if = "Option::is_none")]
+    pub max_tokens_limit: Option<MaxTokensLimit>,
+    /// 采样参数覆盖/默认值
+    #[serde(rename = "sampling", skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingOverrides>,
+    /// 转发前移除的请求字段（点号分隔路径，如 `metadata.user_id`；遇到数组时作用于每个元素）
+    #[serde(
+        rename = "fieldDenylist",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub field_denylist: Vec<String>,
+    /// 流式响应中 extended thinking 内容块的处理方式
+    #[serde(rename = "thinkingBlocks", skip_serializing_if = "Option::is_none")]
+    pub thinking_blocks: Option<ThinkingBlockMode>,
+    /// 上游状态页监控
+    #[serde(rename = "statusPage", skip_serializing_if = "Option::is_none")]
+    pub status_page: Option<StatusPageConfig>,
+    /// 上下文窗口限制
+    #[serde(rename = "contextWindow", skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<ContextWindowConfig>,
+    /// 上游是否支持 `/v1/messages/count_tokens`（为空时自动探测，失败回退到本地估算）
+    #[serde(rename = "countTokens", skip_serializing_if = "Option::is_none")]
+    pub count_tokens: Option<bool>,
+    /// 多密钥轮换：按顺序使用，当前密钥额度用尽后自动切换到下一个
+    #[serde(rename = "apiKeys", default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<ProviderApiKey>,
+    /// 自定义认证方式（为空时使用适配器默认的认证头）
+    #[serde(rename = "authScheme", skip_serializing_if = "Option::is_none")]
+    pub auth_scheme: Option<AuthScheme>,
+    /// 为该供应商单独开启详细调试日志（记录完整请求/响应体）
+    #[serde(rename = "debugLogging", skip_serializing_if = "Option::is_none")]
+    pub debug_logging: Option<bool>,
+    /// 该供应商的详细日志是否记录请求体与响应内容（为空时使用全局调试日志配置）
+    #[serde(rename = "logBodies", skip_serializing_if = "Option::is_none")]
+    pub log_bodies: Option<bool>,
+    /// 该供应商专用的出站代理（如 `http://proxy.corp:8080`、`socks5h://127.0.0.1:1080`），
+    /// 为空时使用全局代理设置
+    #[serde(rename = "upstreamProxy", skip_serializing_if = "Option::is_none")]
+    pub upstream_proxy: Option<String>,
+    /// 上游 TLS 选项（自定义根证书 / 跳过证书校验）
+    #[serde(rename = "tls", skip_serializing_if = "Option::is_none")]
+    pub tls: Option<ProviderTlsOptions>,
+    /// 上游连接调优（HTTP/2、连接池、TCP 参数）
+    #[serde(rename = "connection", skip_serializing_if = "Option::is_none")]
+    pub connection: Option<ProviderConnectionOptions>,
+    /// 分阶段超时（覆盖应用级代理配置）
+    #[serde(rename = "timeouts", skip_serializing_if = "Option::is_none")]
+    pub timeouts: Option<ProviderTimeouts>,
+    /// 模拟上游：设置后代理不请求上游，直接合成响应（用于离线测试接入与路由规则）
+    #[serde(rename = "mock", skip_serializing_if = "Option::is_none")]
+    pub mock: Option<MockUpstreamConfig>,
+}
+
+/// 模拟上游配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MockUpstreamConfig {
+    /// 固定回复文本（为空时回显最后一条用户消息）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<String>,
+    /// 响应前的模拟延迟（毫秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+/// 供应商的轮换密钥
+///
+/// 消费按 `id` 统计，修改密钥值不会清空已有记录。
+/// 代理仅替换认证头中的密钥值，认证方式仍由供应商配置决定。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderApiKey {
+    /// 密钥标识
+    pub id: String,
+    /// 密钥值
+    pub key: String,
+    /// 显示名称
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// 消费额度（USD，为空表示不限）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowance_usd: Option<String>,
+}
+
+/// 超出消费/token 限额时的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// 仅记录警告并通知前端，继续使用该供应商
+    Warn,
+    /// 跳过该供应商，切换到故障转移队列中的下一个
+    Fallback,
+    /// 直接返回本地错误
+    Reject,
+}
+
+/// 限额重置日历
+///
+/// 用于对齐上游的额度重置时间（例如按 UTC-8 每日重置、每周一重置的周额度）。
+/// 时区使用固定 UTC 偏移，不处理夏令时切换。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaCalendar {
+    /// 相对 UTC 的偏移（分钟，如 UTC-8 为 -480；为空时使用本地时区）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset_minutes: Option<i32>,
+    /// 每日重置时刻（0-23 点）
+    #[serde(default)]
+    pub reset_hour: u32,
+    /// 长周期类型（对应 `limitMonthly*` 限额）
+    #[serde(default)]
+    pub period: QuotaPeriod,
+    /// 月度周期的重置日（1-28，默认 1）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_day: Option<u32>,
+    /// 周度周期的重置星期（0 = 周一 … 6 = 周日，默认 0）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_weekday: Option<u32>,
+}
+
+/// 长周期限额的重置周期
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPeriod {
+    #[default]
+    Monthly,
+    Weekly,
+}
+
+/// 上下文窗口超限时的处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContextOverflowAction {
+    /// 直接返回本地错误
+    #[default]
+    Reject,
+    /// 从最早的对话轮次开始截断
+    Truncate,
+}
+
+/// 上下文窗口配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextWindowConfig {
+    /// 上下文窗口大小（token）
+    pub max_tokens: u64,
+    #[serde(default)]
+    pub on_overflow: ContextOverflowAction,
+    /// 是否为请求声明的最大输出长度预留空间
+    #[serde(default = "default_true")]
+    pub reserve_output: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// 状态页类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusPageKind {
+    /// Atlassian Statuspage（`/api/v2/status.json`）
+    #[default]
+    Statuspage,
+    /// RSS / Atom 订阅
+    Rss,
+}
+
+/// 状态页监控配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusPageConfig {
+    /// 状态页地址（Statuspage 填站点根地址，RSS 填订阅地址）
+    pub url: String,
+    #[serde(default)]
+    pub kind: StatusPageKind,
+    /// 轮询间隔（分钟），为空时使用默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_minutes: Option<u64>,
+    /// 故障期间将该供应商移到故障转移队列末尾
+    #[serde(default)]
+    pub deprioritize_on_outage: bool,
+}
+
+/// extended thinking 内容块处理方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ThinkingBlockMode {
+    /// 原样透传
+    #[default]
+    Passthrough,
+    /// 移除 thinking 块（后续内容块重新编号）
+    Strip,
+    /// 改写为 `<thinking>` 标签包裹的 text 块
+    Rewrap,
+}
+
+/// 采样参数
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u64>,
+}
+
+/// 采样参数覆盖配置
+///
+/// 处理顺序：strip → defaults（仅在请求未携带时填充）→ overrides（强制覆盖）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SamplingOverrides {
+    /// 请求未携带时填充的默认值
+    #[serde(default)]
+    pub defaults: SamplingParams,
+    /// 强制覆盖的值
+    #[serde(default)]
+    pub overrides: SamplingParams,
+    /// 需要移除的参数（temperature / topP / topK）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strip: Vec<String>,
+}
+
+/// max_tokens 上限配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MaxTokensLimit {
+    /// 允许的最大输出 token 数，超过时截断为该值
+    pub cap: u64,
+    /// 将输出长度字段改写为指定字段名（如 `max_completion_tokens`），为空时保留原字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rename_to: Option<String>,
+}
+
+/// 供应商上游 TLS 选项
+///
+/// 用于企业 TLS 拦截、要求双向认证的企业网关或使用私有证书的自建中转站
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderTlsOptions {
+    /// 额外信任的根证书文件（PEM，可包含多个证书）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle_path: Option<String>,
+    /// mTLS 客户端证书文件（PEM，可包含中间证书链）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert_path: Option<String>,
+    /// mTLS 客户端私钥文件（PEM，PKCS#8 / PKCS#1 / SEC1）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_path: Option<String>,
+    /// 危险：跳过证书校验（任何人都可以冒充上游，仅用于排查问题）
+    #[serde(default)]
+    pub skip_verify: bool,
+}
+
+/// 供应商上游连接调优
+///
+/// 未设置的项沿用全局客户端的默认值。连续的 Agent 请求可借助更多空闲连接与保活减少首字延迟
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConnectionOptions {
+    /// 是否允许 HTTP/2（false 时强制 HTTP/1.1；true 时额外开启 HTTP/2 保活与自适应窗口）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http2: Option<bool>,
+    /// 每个主机保留的最大空闲连接数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// 空闲连接保留时间（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// 保活间隔（秒），同时用于 TCP keepalive 与 HTTP/2 PING
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive_interval_secs: Option<u64>,
+    /// 是否禁用 Nagle 算法
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_nodelay: Option<bool>,
+}
+
+/// 供应商分阶段超时（秒）
+///
+/// 未设置的项沿用应用级代理配置；供应商设置的值在故障转移关闭时同样生效，0 表示不限制
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderTimeouts {
+    /// 建立连接（含 TLS 握手）超时（0 表示不限制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_secs: Option<u64>,
+    /// 流式响应首字节超时（0 表示不限制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_byte_secs: Option<u64>,
+    /// 流式响应两次数据之间的静默超时（0 表示不限制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_secs: Option<u64>,
+    /// 整个请求的最长时间（含读取响应体；0 表示不限制）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_secs: Option<u64>,
+}
+
+impl ProviderTimeouts {
+    /// 把超时秒数转换为时长，0 转换为 `Duration::MAX`（tokio 计时器将其视为永不超时）
+    pub fn duration(secs: u64) -> std::time::Duration {
+        if secs == 0 {
+            std::time::Duration::MAX
+        } else {
+            std::time::Duration::from_secs(secs)
+        }
+    }
+}
+
+/// 自动提示词缓存配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptCachingConfig {
+    /// 是否启用自动注入
+    #[serde(default)]
+    pub enabled: bool,
+    /// 触发注入的最小字符数（system 或 tools 序列化后的长度），为空时使用默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_chars: Option<usize>,
+}
+
+/// 系统提示词注入配置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SystemPromptInjection {
+    /// 添加到系统提示词开头的内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// 添加到系统提示词末尾的内容
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+}
+
+/// 认证方式类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthSchemeType {
+    /// 通过查询参数传递密钥
+    Query,
+    /// 通过自定义 header 传递密钥
+    Header,
+    /// HMAC-SHA256 请求签名（密钥作为签名密钥，不直接发送）
+    Hmac,
+}
+
+/// 自定义认证方式
+///
+/// 启用后代理不再添加适配器默认的认证头，改为按此配置传递密钥。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthScheme {
+    #[serde(rename = "type")]
+    pub kind: AuthSchemeType,
+    /// 查询参数名（默认 `key`）/ header 名（必填）/ 签名 header 名（默认 `x-signature`）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// header 值前缀（如 `Token `，仅 header 方式）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// 访问密钥 ID（仅 HMAC 方式，通过 `x-access-key-id` 发送）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+}
+
+/// 请求头透传模式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderPassthroughMode {
+    /// 黑名单模式（默认）：除内置黑名单和 `deny` 外全部透传
+    #[default]
+    Denylist,
+    /// 白名单模式：仅透传内置白名单和 `allow` 中的 header
+    Allowlist,
+}
+
+/// 客户端请求头透传策略
+///
+/// header 名称不区分大小写，支持 `x-stainless-*` 形式的前缀通配。
+/// 认证、连接类等由代理管理的 header 不受此策略影响。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HeaderPassthrough {
+    #[serde(default)]
+    pub mode: HeaderPassthroughMode,
+    /// 额外允许透传的 header（黑名单模式下可放行内置黑名单项）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// 额外禁止透传的 header（两种模式下均生效，优先级最高）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+/// 请求头改写规则
+///
+/// 代理转发时按 strip → set → inject 的顺序应用，header 名称不区分大小写。
+/// 值中可使用 `{{apiKey}}` 占位符，转发时替换为该供应商的 API Key。
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct HeaderRules {
+    /// 注入：仅当请求中不存在该 header 时添加
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub inject: IndexMap<String, String>,
+    /// 覆盖：无论请求中是否存在都设置为指定值
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub set: IndexMap<String, String>,
+    /// 移除：从请求中删除这些 header（包括代理自动添加的认证头）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strip: Vec<String>,
+}
+
+impl HeaderRules {
+    /// 是否未配置任何规则
+    pub fn is_empty(&self) -> bool {
+        self.inject.is_empty() && self.set.is_empty() && self.strip.is_empty()
+    }
 }
 
 impl ProviderManager {
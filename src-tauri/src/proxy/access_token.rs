@@ -0,0 +1,171 @@
+//! 本地代理访问令牌
+//!
+//! 设置访问令牌后，发往 API 路由的请求必须在 `x-api-key` 或 `Authorization: Bearer` 中携带该令牌，
+//! 否则返回 401。客户端把令牌配置为 API Key 即可，真实的供应商密钥仍由代理注入。
+//!
+//! 校验通过后会移除携带令牌的 `x-goog-api-key` 请求头与 `?key=` 查询参数，令牌不会转发到上游。
+//!
+//! 团队网关启用时由成员令牌负责鉴权，此处不再重复校验。令牌存储在 settings 表
+//! （`proxy_access_token`）中，每次请求实时读取，修改后无需重启代理。
+//!
+//! 监控端点（`/status`、`/healthz`、`/metrics`）会暴露供应商与用量信息：须携带访问令牌或
+//! 管理令牌，两者均未设置时仅允许本机访问。未通过校验时 `/healthz` 只返回 ok / degraded 摘要。
+
+use super::{server::ProxyState, team_gateway, ProxyError};
+use crate::database::Database;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::SocketAddr;
+
+/// Axum 中间件：校验访问令牌
+pub async fn enforce_access_token(
+    State(state): State<ProxyState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    match check(&state.db, request.headers(), request.uri()) {
+        Ok(verified) => {
+            if verified {
+                team_gateway::strip_token(&mut request);
+            }
+            next.run(request).await
+        }
+        Err(e) => {
+            log::warn!("[AccessToken] 拒绝请求: {e}");
+            e.into_response()
+        }
+    }
+}
+
+/// 校验请求携带的访问令牌，返回是否校验了令牌（未设置令牌或团队网关已启用时直接放行）
+fn check(db: &Database, headers: &HeaderMap, uri: &Uri) -> Result<bool, ProxyError> {
+    let Some(expected) = db
+        .get_proxy_access_token()
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?
+    else {
+        return Ok(false);
+    };
+    if db
+        .get_team_gateway_config()
+        .map(|c| c.enabled)
+        .unwrap_or(false)
+    {
+        return Ok(false);
+    }
+
+    match team_gateway::extract_token(headers, uri) {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(true),
+        Some(_) => Err(ProxyError::AuthError("代理访问令牌无效".to_string())),
+        None => Err(ProxyError::AuthError("缺少代理访问令牌".to_string())),
+    }
+}
+
+/// 监控端点是否可以返回完整信息
+pub fn is_monitoring_authorized(
+    db: &Database,
+    headers: &HeaderMap,
+    uri: &Uri,
+    addr: SocketAddr,
+) -> Result<bool, ProxyError> {
+    let db_err = |e: crate::error::AppError| ProxyError::DatabaseError(e.to_string());
+    let expected: Vec<String> = [
+        db.get_proxy_access_token().map_err(db_err)?,
+        db.get_proxy_admin_token().map_err(db_err)?,
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if expected.is_empty() {
+        return Ok(addr.ip().is_loopback());
+    }
+    Ok(
+        team_gateway::extract_token(headers, uri).is_some_and(|token| {
+            expected
+                .iter()
+                .any(|expected| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+        }),
+    )
+}
+
+/// Axum 中间件：`/status`、`/metrics` 须通过监控端点校验
+pub async fn enforce_monitoring_token(
+    State(state): State<ProxyState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match is_monitoring_authorized(&state.db, request.headers(), request.uri(), addr) {
+        Ok(true) => next.run(request).await,
+        Ok(false) => {
+            ProxyError::AuthError("监控端点需要访问令牌或管理令牌".to_string()).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+/// 定长比较，避免通过响应耗时猜测令牌（管理 API 等令牌校验共用）
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use crate::proxy::types::TeamGatewayConfig;
+    use axum::http::HeaderValue;
+
+    fn headers(name: &'static str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn requires_configured_token() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let uri = Uri::from_static("/v1/messages");
+        assert!(check(&db, &HeaderMap::new(), &uri).is_ok());
+
+        db.set_proxy_access_token(Some("secret-token"))?;
+        assert!(check(&db, &HeaderMap::new(), &uri).is_err());
+        assert!(check(&db, &headers("x-api-key", "wrong"), &uri).is_err());
+        assert!(check(&db, &headers("x-api-key", "secret-token"), &uri).is_ok());
+        assert!(check(&db, &headers("authorization", "Bearer secret-token"), &uri).is_ok());
+
+        // 团队网关启用时交由成员令牌鉴权
+        db.set_team_gateway_config(&TeamGatewayConfig { enabled: true })?;
+        assert!(check(&db, &HeaderMap::new(), &uri).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn guards_monitoring_endpoints() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let uri = Uri::from_static("/metrics");
+        let local: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let remote: SocketAddr = "192.168.1.2:50000".parse().unwrap();
+        let none = HeaderMap::new();
+        let authorized =
+            |headers: &HeaderMap, addr| is_monitoring_authorized(&db, headers, &uri, addr).unwrap();
+
+        // 未设置令牌：仅限本机
+        assert!(authorized(&none, local));
+        assert!(!authorized(&none, remote));
+
+        // 设置令牌后即使本机也须携带访问令牌或管理令牌（团队网关启用时同样如此）
+        db.set_proxy_access_token(Some("access"))?;
+        db.set_proxy_admin_token(Some("admin"))?;
+        db.set_team_gateway_config(&TeamGatewayConfig { enabled: true })?;
+        assert!(!authorized(&none, local));
+        let bearer = |token| headers("authorization", &format!("Bearer {token}"));
+        assert!(authorized(&bearer("access"), remote));
+        assert!(authorized(&bearer("admin"), remote));
+        assert!(!authorized(&bearer("wrong"), local));
+        Ok(())
+    }
+}
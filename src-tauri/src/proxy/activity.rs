@@ -0,0 +1,213 @@
+//! 实时活动事件
+//!
+//! 代理在请求生命周期的关键节点通过 Tauri 事件 `proxy-activity` 推送状态，
+//! 前端据此展示实时活动列表，无需轮询日志文件或数据库：
+//! - `started`：开始转发（首个供应商与故障转移链长度）
+//! - `retry`：限流重试或切换到下一个供应商
+//! - `progress`：流式响应进度（累计块数与字节数，每个请求至多每秒一次）
+//! - `completed` / `failed`：请求结束
+//!
+//! 同一请求的事件共享 `requestId`，与请求日志、请求采集及对话记录的 ID 一致。
+
+use super::request_inspection;
+use bytes::Bytes;
+use futures::stream::{Stream, StreamExt};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// 前端订阅的事件名
+pub const ACTIVITY_EVENT: &str = "proxy-activity";
+
+/// 流式进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 活动事件类型
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ActivityKind {
+    Started {
+        provider_id: String,
+        provider_name: String,
+        /// 故障转移链中的供应商数量
+        chain_length: usize,
+    },
+    Retry {
+        provider_id: String,
+        provider_name: String,
+        /// 第几次重试（限流重试）或第几个失败的供应商（故障转移）
+        attempt: u32,
+        /// true 表示切换到下一个供应商，false 表示在同一供应商上限流重试
+        failover: bool,
+        reason: String,
+    },
+    Progress {
+        chunks: u64,
+        bytes: u64,
+    },
+    Completed {
+        status_code: u16,
+        latency_ms: u64,
+        /// 流式响应的块数（非流式响应为空）
+        chunks: Option<u64>,
+    },
+    Failed {
+        status_code: u16,
+        latency_ms: u64,
+        error: String,
+    },
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivityEvent<'a> {
+    request_id: &'a str,
+    app_type: &'a str,
+    model: &'a str,
+    timestamp: i64,
+    #[serde(flatten)]
+    kind: ActivityKind,
+}
+
+/// 单个请求的活动事件发射器
+#[derive(Clone)]
+pub struct ActivityFeed {
+    app_handle: Option<tauri::AppHandle>,
+    request_id: Arc<str>,
+    app_type: &'static str,
+    model: Arc<str>,
+    start_time: Instant,
+}
+
+impl ActivityFeed {
+    pub fn new(
+        app_handle: Option<tauri::AppHandle>,
+        request_id: &str,
+        app_type: &'static str,
+        model: &str,
+        start_time: Instant,
+    ) -> Self {
+        Self {
+            app_handle,
+            request_id: request_id.into(),
+            app_type,
+            model: model.into(),
+            start_time,
+        }
+    }
+
+    /// 更新模型名（Gemini 的模型名在创建上下文后才从 URI 解析出来）
+    pub fn set_model(&mut self, model: &str) {
+        self.model = model.into();
+    }
+
+    /// 请求开始至今的毫秒数
+    pub fn elapsed_ms(&self) -> u64 {
+        self.start_time.elapsed().as_millis() as u64
+    }
+
+    /// 发射事件并记入请求过程（无 AppHandle 时只记录）
+    pub fn emit(&self, kind: ActivityKind) {
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        request_inspection::record_event(&self.request_id, timestamp, &kind);
+        let Some(app) = &self.app_handle else {
+            return;
+        };
+        let event = ActivityEvent {
+            request_id: &self.request_id,
+            app_type: self.app_type,
+            model: &self.model,
+            timestamp,
+            kind,
+        };
+        if let Err(e) = app.emit(ACTIVITY_EVENT, event) {
+            log::debug!("发射 {ACTIVITY_EVENT} 事件失败: {e}");
+        }
+    }
+
+    /// 请求成功结束
+    pub fn completed(&self, status_code: u16, chunks: Option<u64>) {
+        self.emit(ActivityKind::Completed {
+            status_code,
+            latency_ms: self.elapsed_ms(),
+            chunks,
+        });
+    }
+
+    /// 请求失败
+    pub fn failed(&self, status_code: u16, error: impl Into<String>) {
+        self.emit(ActivityKind::Failed {
+            status_code,
+            latency_ms: self.elapsed_ms(),
+            error: error.into(),
+        });
+    }
+}
+
+/// 包装流式响应：累计块数并定期发射进度，流结束时发射完成或失败事件
+pub fn instrument_stream<S>(
+    stream: S,
+    feed: ActivityFeed,
+    status_code: u16,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+{
+    async_stream::stream! {
+        let mut chunks: u64 = 0;
+        let mut bytes: u64 = 0;
+        let mut last_progress = Instant::now();
+        tokio::pin!(stream);
+        while let Some(chunk) = stream.next().await {
+            match &chunk {
+                Ok(data) => {
+                    chunks += 1;
+                    bytes += data.len() as u64;
+                    if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                        last_progress = Instant::now();
+                        feed.emit(ActivityKind::Progress { chunks, bytes });
+                    }
+                }
+                Err(e) => {
+                    feed.failed(status_code, e.to_string());
+                    yield chunk;
+                    return;
+                }
+            }
+            yield chunk;
+        }
+        feed.completed(status_code, Some(chunks));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_kind_with_flattened_fields() {
+        let event = ActivityEvent {
+            request_id: "r1",
+            app_type: "claude",
+            model: "claude-sonnet",
+            timestamp: 1,
+            kind: ActivityKind::Retry {
+                provider_id: "p1".into(),
+                provider_name: "Primary".into(),
+                attempt: 1,
+                failover: true,
+                reason: "HTTP 503".into(),
+            },
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["kind"], "retry");
+        assert_eq!(value["requestId"], "r1");
+        assert_eq!(value["providerName"], "Primary");
+        assert_eq!(value["failover"], true);
+    }
+}
@@ -0,0 +1,256 @@
+//! 本地管理 API（`/admin/*`）
+//!
+//! 供外部工具、脚本与编辑器插件自动切换供应商、读取实时统计：
+//!
+//! - `GET  /admin/providers[?app=claude]`：各应用的供应商列表与当前供应商
+//! - `POST /admin/switch`：`{"app": "claude", "provider": "<名称或 ID>"}` 切换供应商
+//! - `GET  /admin/stats`：代理运行状态、最近 24 小时用量汇总与各供应商统计
+//!
+//! 设置管理令牌（settings 表 `proxy_admin_token`）后启用，请求须在 `Authorization: Bearer`
+//! 或 `x-api-key` 中携带该令牌；未设置时所有 `/admin` 路由返回 404。
+//! 与代理访问令牌相互独立，持有访问令牌的客户端无法调用管理 API。
+
+use super::{access_token::constant_time_eq, server::ProxyState, team_gateway, ProxyError};
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::services::usage_stats::{ProviderStats, UsageSummary};
+use crate::services::ProviderService;
+use axum::{
+    extract::{Query, Request, State},
+    http::{HeaderMap, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// 管理 API 路由（挂载在 `/admin` 下）
+pub fn router(state: ProxyState) -> Router<ProxyState> {
+    Router::new()
+        .route("/providers", get(list_providers))
+        .route("/switch", post(switch_provider))
+        .route("/stats", get(stats))
+        .route_layer(middleware::from_fn_with_state(state, enforce_admin_token))
+}
+
+/// 令牌校验结果
+#[derive(Debug, PartialEq, Eq)]
+enum AdminAuth {
+    /// 未设置管理令牌
+    Disabled,
+    Allowed,
+    Denied(&'static str),
+}
+
+fn check(db: &Database, headers: &HeaderMap, uri: &Uri) -> Result<AdminAuth, ProxyError> {
+    let Some(expected) = db
+        .get_proxy_admin_token()
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?
+    else {
+        return Ok(AdminAuth::Disabled);
+    };
+
+    Ok(match team_gateway::extract_token(headers, uri) {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => {
+            AdminAuth::Allowed
+        }
+        Some(_) => AdminAuth::Denied("管理令牌无效"),
+        None => AdminAuth::Denied("缺少管理令牌"),
+    })
+}
+
+/// Axum 中间件：校验管理令牌
+async fn enforce_admin_token(
+    State(state): State<ProxyState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match check(&state.db, request.headers(), request.uri()) {
+        Ok(AdminAuth::Allowed) => next.run(request).await,
+        Ok(AdminAuth::Disabled) => StatusCode::NOT_FOUND.into_response(),
+        Ok(AdminAuth::Denied(reason)) => {
+            log::warn!("[AdminApi] 拒绝请求: {reason}");
+            ProxyError::AuthError(reason.to_string()).into_response()
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ProvidersQuery {
+    app: Option<String>,
+}
+
+/// 单个供应商
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct AdminProvider {
+    id: String,
+    name: String,
+    current: bool,
+}
+
+/// 单个应用的供应商列表
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AdminAppProviders {
+    app_type: String,
+    current_provider_id: Option<String>,
+    providers: Vec<AdminProvider>,
+}
+
+fn parse_app(app: &str) -> Result<AppType, ProxyError> {
+    AppType::from_str(app).map_err(|e| ProxyError::InvalidRequest(e.to_string()))
+}
+
+async fn list_providers(
+    State(state): State<ProxyState>,
+    Query(query): Query<ProvidersQuery>,
+) -> Result<Json<Vec<AdminAppProviders>>, ProxyError> {
+    let apps = match query.app.as_deref() {
+        Some(app) => vec![parse_app(app)?],
+        None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+    };
+
+    let mut result = Vec::with_capacity(apps.len());
+    for app_type in apps {
+        let providers = state
+            .db
+            .get_all_providers(app_type.as_str())
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+        let current = crate::settings::get_effective_current_provider(&state.db, &app_type)
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+        result.push(AdminAppProviders {
+            app_type: app_type.as_str().to_string(),
+            providers: providers
+                .values()
+                .map(|p| AdminProvider {
+                    id: p.id.clone(),
+                    name: p.name.clone(),
+                    current: current.as_deref() == Some(p.id.as_str()),
+                })
+                .collect(),
+            current_provider_id: current,
+        });
+    }
+    Ok(Json(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct SwitchRequest {
+    app: String,
+    /// 供应商名称或 ID
+    provider: String,
+}
+
+/// 切换供应商（本机控制接口 `/ccswitch/control/switch` 共用）
+pub(super) async fn switch_provider(
+    State(state): State<ProxyState>,
+    Json(request): Json<SwitchRequest>,
+) -> Result<Json<AdminProvider>, ProxyError> {
+    let app_type = parse_app(&request.app)?;
+    let providers = state
+        .db
+        .get_all_providers(app_type.as_str())
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+    let provider = crate::cli::find_provider(&providers, &request.provider)
+        .map_err(ProxyError::InvalidRequest)?;
+    let (id, name) = (provider.id.clone(), provider.name.clone());
+
+    // 切换流程内部使用阻塞等待，放到阻塞线程池执行
+    let app_handle = state.app_handle.clone();
+    let switch_id = id.clone();
+    tokio::task::spawn_blocking(move || match app_handle {
+        // GUI 模式：与托盘切换一致（同时刷新托盘并通知前端）
+        Some(app) => crate::tray::switch_provider_internal(&app, app_type, switch_id)
+            .map_err(|e| e.to_string()),
+        None => match crate::store::headless_state() {
+            Some(app_state) => {
+                ProviderService::switch(&app_state, app_type, &switch_id).map_err(|e| e.to_string())
+            }
+            None => Err("当前运行模式不支持切换供应商".to_string()),
+        },
+    })
+    .await
+    .map_err(|e| ProxyError::Internal(e.to_string()))?
+    .map_err(ProxyError::Internal)?;
+
+    log::info!("[AdminApi] 已将 {} 切换到 {name}", request.app);
+    Ok(Json(AdminProvider {
+        id,
+        name,
+        current: true,
+    }))
+}
+
+/// 统计响应
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AdminStats {
+    status: super::types::ProxyStatus,
+    /// 最近 24 小时用量汇总
+    last_24h: UsageSummary,
+    providers: Vec<ProviderStats>,
+}
+
+async fn stats(State(state): State<ProxyState>) -> Result<Json<AdminStats>, ProxyError> {
+    let mut status = state.status.read().await.clone();
+    if let Some(start) = *state.start_time.read().await {
+        status.uptime_seconds = start.elapsed().as_secs();
+    }
+
+    let since = chrono::Utc::now().timestamp() - 24 * 60 * 60;
+    let db_err = |e: crate::error::AppError| ProxyError::DatabaseError(e.to_string());
+    Ok(Json(AdminStats {
+        status,
+        last_24h: state
+            .db
+            .get_usage_summary(Some(since), None)
+            .map_err(db_err)?,
+        providers: state.db.get_provider_stats().map_err(db_err)?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn requires_admin_token_when_configured() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let uri = Uri::from_static("/admin/providers");
+        let bearer = |token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                "authorization",
+                HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+            );
+            headers
+        };
+
+        assert_eq!(
+            check(&db, &HeaderMap::new(), &uri).unwrap(),
+            AdminAuth::Disabled
+        );
+
+        db.set_proxy_admin_token(Some("admin-secret"))?;
+        db.set_proxy_access_token(Some("client-secret"))?;
+        assert!(matches!(
+            check(&db, &HeaderMap::new(), &uri).unwrap(),
+            AdminAuth::Denied(_)
+        ));
+        assert!(matches!(
+            check(&db, &bearer("client-secret"), &uri).unwrap(),
+            AdminAuth::Denied(_)
+        ));
+        assert_eq!(
+            check(&db, &bearer("admin-secret"), &uri).unwrap(),
+            AdminAuth::Allowed
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+//! 告警规则
+//!
+//! 用户配置的「条件 → 动作」规则，随代理服务器启动后台任务每分钟评估一次：
+//! - 条件：错误率、p95 延迟、重试次数（按供应商统计），以及消费（按规则范围统计）
+//! - 动作：发送通知、切换当前供应商、停止代理
+//!
+//! 规则命中时总会发射前端事件 `alert-rule-triggered` 并发送桌面通知；
+//! 同一规则对同一对象在 `cooldown_minutes` 内只触发一次。
+//! 重试次数来自内存中的请求过程记录（见 `request_inspection`），代理重启后重新计数。
+//! 规则列表存储在 settings 表（`alert_rules`）中。
+
+use super::failure_alert::show_notification;
+use super::request_inspection;
+use super::server::ProxyState;
+use super::types::{AlertAction, AlertCondition, AlertRule};
+use crate::app_config::AppType;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// 规则评估间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 未限定应用时统计的应用
+const APP_TYPES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 评估对象在时间窗口内的指标
+///
+/// `provider_id` 为空时表示规则范围内的汇总（仅用于消费条件）
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Metrics {
+    app_type: Option<String>,
+    provider_id: Option<String>,
+    requests: u64,
+    errors: u64,
+    p95_ms: Option<u64>,
+    retries: u64,
+    cost_usd: f64,
+}
+
+/// 条件满足时返回（观测值, 阈值）
+fn evaluate(condition: &AlertCondition, metrics: &Metrics) -> Option<(f64, f64)> {
+    let is_provider = metrics.provider_id.is_some();
+    match condition {
+        AlertCondition::ErrorRate {
+            min_percent,
+            min_requests,
+        } => {
+            if !is_provider || metrics.requests == 0 || metrics.requests < *min_requests {
+                return None;
+            }
+            let rate = metrics.errors as f64 * 100.0 / metrics.requests as f64;
+            (rate >= *min_percent).then_some((rate, *min_percent))
+        }
+        AlertCondition::Latency { p95_ms } => {
+            let p95 = metrics.p95_ms.filter(|_| is_provider)?;
+            (p95 >= *p95_ms).then_some((p95 as f64, *p95_ms as f64))
+        }
+        AlertCondition::Retries { min_count } => {
+            (is_provider && *min_count > 0 && metrics.retries >= *min_count)
+                .then_some((metrics.retries as f64, *min_count as f64))
+        }
+        AlertCondition::Spend { min_usd } => {
+            (!is_provider && *min_usd > 0.0 && metrics.cost_usd >= *min_usd)
+                .then_some((metrics.cost_usd, *min_usd))
+        }
+    }
+}
+
+fn describe(condition: &AlertCondition, observed: f64, threshold: f64) -> String {
+    match condition {
+        AlertCondition::ErrorRate { .. } => {
+            format!("错误率 {observed:.1}%（阈值 {threshold}%）")
+        }
+        AlertCondition::Latency { .. } => {
+            format!("p95 延迟 {observed:.0}ms（阈值 {threshold:.0}ms）")
+        }
+        AlertCondition::Retries { .. } => {
+            format!("重试 {observed:.0} 次（阈值 {threshold:.0} 次）")
+        }
+        AlertCondition::Spend { .. } => format!("消费 ${observed:.2}（阈值 ${threshold:.2}）"),
+    }
+}
+
+/// 统计规则范围内各供应商及汇总的窗口指标
+fn collect_metrics(state: &ProxyState, rule: &AlertRule) -> Vec<Metrics> {
+    let window = rule.window_minutes.max(1);
+    let since = chrono::Utc::now().timestamp() - i64::from(window) * 60;
+    let apps: Vec<&str> = match rule.app_type.as_deref() {
+        Some(app) => vec![app],
+        None => APP_TYPES.to_vec(),
+    };
+
+    let p95: HashMap<(String, String), u64> = state
+        .db
+        .get_provider_latency_percentiles(&[window])
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| ((p.app_type, p.provider_id), p.latency.p95))
+        .collect();
+    let retries = request_inspection::retry_counts(since * 1000);
+
+    let mut scope = Metrics {
+        app_type: rule.app_type.clone(),
+        ..Default::default()
+    };
+    let mut metrics = Vec::new();
+    for app in apps {
+        let stats = match state.db.get_recent_provider_stats(app, since) {
+            Ok(stats) => stats,
+            Err(e) => {
+                log::debug!("[AlertRules] 统计 {app} 请求失败: {e}");
+                continue;
+            }
+        };
+        for s in stats {
+            scope.requests += s.requests;
+            scope.errors += s.errors;
+            scope.cost_usd += s.cost_usd;
+            metrics.push(Metrics {
+                app_type: Some(app.to_string()),
+                p95_ms: p95.get(&(app.to_string(), s.provider_id.clone())).copied(),
+                retries: retries.get(&s.provider_id).copied().unwrap_or(0),
+                provider_id: Some(s.provider_id),
+                requests: s.requests,
+                errors: s.errors,
+                cost_usd: s.cost_usd,
+            });
+        }
+    }
+    metrics.push(scope);
+    metrics
+}
+
+/// 后台规则评估任务（规则在每次评估时重新读取）
+pub async fn run(state: ProxyState) {
+    // 最近一次触发时间（key = "rule_id:app_type:provider_id"）
+    let mut last_fired: HashMap<String, Instant> = HashMap::new();
+    loop {
+        tokio::time::sleep(TICK_INTERVAL).await;
+        let rules = match state.db.get_alert_rules() {
+            Ok(rules) => rules,
+            Err(e) => {
+                log::warn!("[AlertRules] 读取告警规则失败: {e}");
+                continue;
+            }
+        };
+        for rule in rules.iter().filter(|r| r.enabled) {
+            let cooldown = Duration::from_secs(u64::from(rule.cooldown_minutes) * 60);
+            for metrics in collect_metrics(&state, rule) {
+                let Some((observed, threshold)) = evaluate(&rule.condition, &metrics) else {
+                    continue;
+                };
+                let key = format!(
+                    "{}:{}:{}",
+                    rule.id,
+                    metrics.app_type.as_deref().unwrap_or("*"),
+                    metrics.provider_id.as_deref().unwrap_or("*")
+                );
+                if last_fired.get(&key).is_some_and(|t| t.elapsed() < cooldown) {
+                    continue;
+                }
+                last_fired.insert(key, Instant::now());
+                fire(&state, rule, &metrics, observed, threshold);
+            }
+        }
+    }
+}
+
+fn fire(state: &ProxyState, rule: &AlertRule, metrics: &Metrics, observed: f64, threshold: f64) {
+    let provider_name = match (&metrics.app_type, &metrics.provider_id) {
+        (Some(app), Some(id)) => state
+            .db
+            .get_provider_by_id(id, app)
+            .ok()
+            .flatten()
+            .map(|p| p.name)
+            .or_else(|| Some(id.clone())),
+        _ => None,
+    };
+    let detail = describe(&rule.condition, observed, threshold);
+    let subject = match (&metrics.app_type, &provider_name) {
+        (Some(app), Some(name)) => format!("[{app}] {name}"),
+        (Some(app), None) => format!("[{app}]"),
+        _ => "所有应用".to_string(),
+    };
+    log::warn!("[AlertRules] 规则 {} 命中：{subject} {detail}", rule.name);
+
+    let Some(app) = &state.app_handle else {
+        return;
+    };
+    let payload = serde_json::json!({
+        "ruleId": rule.id,
+        "ruleName": rule.name,
+        "appType": metrics.app_type,
+        "providerId": metrics.provider_id,
+        "providerName": provider_name,
+        "observed": observed,
+        "threshold": threshold,
+        "action": rule.action,
+    });
+    if let Err(e) = app.emit("alert-rule-triggered", payload) {
+        log::error!("[AlertRules] 发射事件失败: {e}");
+    }
+    show_notification(app, &rule.name, &format!("{subject} {detail}"));
+
+    match &rule.action {
+        AlertAction::Notify => {}
+        AlertAction::SwitchProvider { provider_id } => {
+            let Some(Ok(app_type)) = rule.app_type.as_deref().map(AppType::from_str) else {
+                log::warn!("[AlertRules] 规则 {} 未指定应用，无法切换供应商", rule.name);
+                return;
+            };
+            let current = state
+                .db
+                .get_current_provider(app_type.as_str())
+                .ok()
+                .flatten();
+            if current.as_deref() == Some(provider_id.as_str()) {
+                return;
+            }
+            let app = app.clone();
+            let provider_id = provider_id.clone();
+            // 切换供应商会同步写入配置文件，放到阻塞线程执行
+            tauri::async_runtime::spawn_blocking(move || {
+                if let Err(e) = crate::tray::switch_provider_internal(&app, app_type, provider_id) {
+                    log::warn!("[AlertRules] 切换供应商失败: {e}");
+                }
+            });
+        }
+        AlertAction::PauseProxy => {
+            let app = app.clone();
+            // 停止代理会中止本任务，放到独立任务执行
+            tauri::async_runtime::spawn(async move {
+                let Some(app_state) = app.try_state::<crate::store::AppState>() else {
+                    return;
+                };
+                if let Err(e) = app_state.proxy_service.stop_with_restore().await {
+                    log::warn!("[AlertRules] 停止代理失败: {e}");
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(requests: u64, errors: u64, retries: u64) -> Metrics {
+        Metrics {
+            app_type: Some("claude".into()),
+            provider_id: Some("p1".into()),
+            requests,
+            errors,
+            p95_ms: Some(4_000),
+            retries,
+            cost_usd: 3.0,
+        }
+    }
+
+    #[test]
+    fn evaluates_provider_conditions() {
+        let error_rate = AlertCondition::ErrorRate {
+            min_percent: 50.0,
+            min_requests: 4,
+        };
+        assert_eq!(
+            evaluate(&error_rate, &provider(4, 2, 0)),
+            Some((50.0, 50.0))
+        );
+        // 请求数不足时不判断
+        assert_eq!(evaluate(&error_rate, &provider(2, 2, 0)), None);
+
+        let latency = AlertCondition::Latency { p95_ms: 3_000 };
+        assert_eq!(
+            evaluate(&latency, &provider(4, 0, 0)),
+            Some((4_000.0, 3_000.0))
+        );
+
+        let retries = AlertCondition::Retries { min_count: 3 };
+        assert_eq!(evaluate(&retries, &provider(4, 0, 2)), None);
+        assert_eq!(evaluate(&retries, &provider(4, 0, 3)), Some((3.0, 3.0)));
+    }
+
+    #[test]
+    fn spend_applies_to_scope_only() {
+        let spend = AlertCondition::Spend { min_usd: 2.0 };
+        assert_eq!(evaluate(&spend, &provider(4, 0, 0)), None);
+
+        let scope = Metrics {
+            provider_id: None,
+            ..provider(4, 0, 0)
+        };
+        assert_eq!(evaluate(&spend, &scope), Some((3.0, 2.0)));
+        assert_eq!(
+            evaluate(&AlertCondition::Latency { p95_ms: 1 }, &scope),
+            None
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! anthropic-version 版本固定模块
+//!
+//! 决定转发到 Claude 上游时使用的 `anthropic-version`：
+//! - Provider 配置了 `meta.anthropicVersion` 时固定使用该版本
+//! - 否则优先使用客户端发送的版本
+//! - 客户端未发送或格式无效时使用默认版本
+//!
+//! Anthropic 在版本之间的差异通过请求体转换抹平；目前 Messages API 仅有
+//! `2023-06-01` 一个稳定版本，旧版 `2023-01-01` 只影响 Text Completions 接口，
+//! 因此 Messages 请求在版本不一致时无需改写请求体，仅需统一 header。
+
+use crate::provider::Provider;
+
+/// 默认 API 版本
+pub const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// 版本解析结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedVersion {
+    /// 发送到上游的版本
+    pub upstream: String,
+    /// 是否与客户端请求的版本不同（固定版本或修正了无效版本）
+    pub overridden: bool,
+}
+
+/// 校验版本号格式（YYYY-MM-DD）
+pub fn is_valid_version(version: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(version, "%Y-%m-%d").is_ok()
+}
+
+/// 获取 Provider 固定的 anthropic-version（格式无效时忽略）
+pub fn get_pinned_version(provider: &Provider) -> Option<&str> {
+    let pinned = provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.anthropic_version.as_deref())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())?;
+
+    if is_valid_version(pinned) {
+        Some(pinned)
+    } else {
+        log::warn!(
+            "[AnthropicVersion] 供应商 {} 配置的版本 '{pinned}' 格式无效，已忽略",
+            provider.name
+        );
+        None
+    }
+}
+
+/// 解析最终发送到上游的 anthropic-version
+pub fn resolve_version(client: Option<&str>, pinned: Option<&str>) -> ResolvedVersion {
+    let client = client.map(str::trim).filter(|v| !v.is_empty());
+
+    let upstream = match (pinned, client) {
+        (Some(p), _) => p,
+        (None, Some(c)) if is_valid_version(c) => c,
+        (None, Some(c)) => {
+            log::warn!(
+                "[AnthropicVersion] 客户端版本 '{c}' 格式无效，使用默认版本 {DEFAULT_ANTHROPIC_VERSION}"
+            );
+            DEFAULT_ANTHROPIC_VERSION
+        }
+        (None, None) => DEFAULT_ANTHROPIC_VERSION,
+    };
+
+    let overridden = client.is_some_and(|c| c != upstream);
+    if overridden {
+        log::debug!(
+            "[AnthropicVersion] 版本已改写: {} → {upstream}",
+            client.unwrap_or_default()
+        );
+    }
+
+    ResolvedVersion {
+        upstream: upstream.to_string(),
+        overridden,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    #[test]
+    fn test_client_version_used_by_default() {
+        let resolved = resolve_version(Some("2023-01-01"), None);
+        assert_eq!(resolved.upstream, "2023-01-01");
+        assert!(!resolved.overridden);
+    }
+
+    #[test]
+    fn test_missing_client_version_uses_default() {
+        let resolved = resolve_version(None, None);
+        assert_eq!(resolved.upstream, DEFAULT_ANTHROPIC_VERSION);
+        assert!(!resolved.overridden);
+    }
+
+    #[test]
+    fn test_pinned_version_overrides_client() {
+        let resolved = resolve_version(Some("2023-01-01"), Some("2023-06-01"));
+        assert_eq!(resolved.upstream, "2023-06-01");
+        assert!(resolved.overridden);
+    }
+
+    #[test]
+    fn test_invalid_client_version_is_replaced() {
+        let resolved = resolve_version(Some("latest"), None);
+        assert_eq!(resolved.upstream, DEFAULT_ANTHROPIC_VERSION);
+        assert!(resolved.overridden);
+    }
+
+    #[test]
+    fn test_invalid_pinned_version_is_ignored() {
+        let mut provider = Provider::with_id("p".into(), "P".into(), json!({}), None);
+        provider.meta = Some(ProviderMeta {
+            anthropic_version: Some("v2".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(get_pinned_version(&provider), None);
+
+        provider.meta.as_mut().unwrap().anthropic_version = Some(" 2023-06-01 ".to_string());
+        assert_eq!(get_pinned_version(&provider), Some("2023-06-01"));
+    }
+}
@@ -0,0 +1,162 @@
+//! 供应商自定义认证方式
+//!
+//! 部分自建网关不接受标准的 `Authorization` / `x-api-key`，需要：
+//! - `query`：密钥作为查询参数（如 `?key=sk-xxx`）
+//! - `header`：自定义 header 名及前缀（如 `X-Gateway-Token: Token sk-xxx`）
+//! - `hmac`：对请求做 HMAC-SHA256 签名，密钥本身不发送
+//!
+//! HMAC 签名串为 `{timestamp}\n{METHOD}\n{path?query}\n{sha256_hex(body)}`，
+//! 签名以十六进制写入签名 header（默认 `x-signature`），时间戳（Unix 秒）写入 `x-timestamp`。
+
+use super::ProxyError;
+use crate::provider::{AuthScheme, AuthSchemeType, Provider};
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue};
+use sha2::{Digest, Sha256};
+
+const DEFAULT_QUERY_PARAM: &str = "key";
+const DEFAULT_SIGNATURE_HEADER: &str = "x-signature";
+const TIMESTAMP_HEADER: &str = "x-timestamp";
+const ACCESS_KEY_ID_HEADER: &str = "x-access-key-id";
+
+/// 获取供应商配置的认证方式
+pub fn get_auth_scheme(provider: &Provider) -> Option<&AuthScheme> {
+    provider.meta.as_ref()?.auth_scheme.as_ref()
+}
+
+/// 按认证方式为已构建的请求添加凭据
+pub fn apply_auth_scheme(
+    request: &mut reqwest::Request,
+    scheme: &AuthScheme,
+    api_key: &str,
+) -> Result<(), ProxyError> {
+    match scheme.kind {
+        AuthSchemeType::Query => {
+            let name = scheme.name.as_deref().unwrap_or(DEFAULT_QUERY_PARAM);
+            request
+                .url_mut()
+                .query_pairs_mut()
+                .append_pair(name, api_key);
+        }
+        AuthSchemeType::Header => {
+            let name = scheme
+                .name
+                .as_deref()
+                .filter(|n| !n.is_empty())
+                .ok_or_else(|| ProxyError::ConfigError("header 认证方式缺少 header 名".into()))?;
+            let value = format!("{}{api_key}", scheme.prefix.as_deref().unwrap_or_default());
+            insert_header(request, name, &value)?;
+        }
+        AuthSchemeType::Hmac => {
+            let timestamp = chrono::Utc::now().timestamp();
+            let path = match request.url().query() {
+                Some(query) => format!("{}?{query}", request.url().path()),
+                None => request.url().path().to_string(),
+            };
+            let body = request
+                .body()
+                .and_then(|b| b.as_bytes())
+                .unwrap_or_default();
+            let signature = sign(api_key, timestamp, request.method().as_str(), &path, body);
+
+            let name = scheme.name.as_deref().unwrap_or(DEFAULT_SIGNATURE_HEADER);
+            insert_header(request, name, &signature)?;
+            insert_header(request, TIMESTAMP_HEADER, &timestamp.to_string())?;
+            if let Some(id) = scheme.access_key_id.as_deref() {
+                insert_header(request, ACCESS_KEY_ID_HEADER, id)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 计算 HMAC-SHA256 签名（十六进制）
+fn sign(secret: &str, timestamp: i64, method: &str, path: &str, body: &[u8]) -> String {
+    let body_hash = to_hex(&Sha256::digest(body));
+    let message = format!("{timestamp}\n{method}\n{path}\n{body_hash}");
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC 接受任意长度的密钥");
+    mac.update(message.as_bytes());
+    to_hex(&mac.finalize().into_bytes())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn insert_header(
+    request: &mut reqwest::Request,
+    name: &str,
+    value: &str,
+) -> Result<(), ProxyError> {
+    let name = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|e| ProxyError::ConfigError(format!("无效的认证 header 名 {name}: {e}")))?;
+    let value = HeaderValue::from_str(value)
+        .map_err(|e| ProxyError::ConfigError(format!("无效的认证 header 值: {e}")))?;
+    request.headers_mut().insert(name, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(url: &str) -> reqwest::Request {
+        reqwest::Client::new()
+            .post(url)
+            .body("{\"a\":1}")
+            .build()
+            .unwrap()
+    }
+
+    fn scheme(kind: AuthSchemeType, name: Option<&str>) -> AuthScheme {
+        AuthScheme {
+            kind,
+            name: name.map(Into::into),
+            prefix: None,
+            access_key_id: None,
+        }
+    }
+
+    #[test]
+    fn query_scheme_appends_param() {
+        let mut req = request("https://gw.example.com/v1/chat?x=1");
+        apply_auth_scheme(&mut req, &scheme(AuthSchemeType::Query, None), "sk-1").unwrap();
+        assert_eq!(req.url().query(), Some("x=1&key=sk-1"));
+    }
+
+    #[test]
+    fn header_scheme_uses_prefix_and_requires_name() {
+        let mut req = request("https://gw.example.com/v1/chat");
+        let mut s = scheme(AuthSchemeType::Header, Some("X-Gateway-Token"));
+        s.prefix = Some("Token ".into());
+        apply_auth_scheme(&mut req, &s, "sk-1").unwrap();
+        assert_eq!(req.headers()["x-gateway-token"], "Token sk-1");
+
+        let err = apply_auth_scheme(&mut req, &scheme(AuthSchemeType::Header, None), "sk-1");
+        assert!(matches!(err, Err(ProxyError::ConfigError(_))));
+    }
+
+    #[test]
+    fn hmac_signature_is_stable() {
+        assert_eq!(
+            sign(
+                "secret",
+                1_700_000_000,
+                "POST",
+                "/v1/chat?x=1",
+                b"{\"a\":1}"
+            ),
+            "d99785a0bd8a32691932e1a1f6cb3c574474cc4de176186964dabacbe6535ca5"
+        );
+
+        let mut req = request("https://gw.example.com/v1/chat");
+        let mut s = scheme(AuthSchemeType::Hmac, None);
+        s.access_key_id = Some("ak-1".into());
+        apply_auth_scheme(&mut req, &s, "secret").unwrap();
+        assert_eq!(req.headers()[DEFAULT_SIGNATURE_HEADER].len(), 64);
+        assert!(req.headers().contains_key(TIMESTAMP_HEADER));
+        assert_eq!(req.headers()[ACCESS_KEY_ID_HEADER], "ak-1");
+    }
+}
@@ -0,0 +1,102 @@
+//! Message Batches API 透传
+//!
+//! `/v1/messages/batches`（创建、列表、轮询、取消、下载结果）经 [`passthrough`] 原样转发到 Claude 供应商。
+//! 批处理 ID 只在创建它的供应商处有效，创建成功时记录 ID 与供应商的对应关系，
+//! 后续针对该批处理的请求始终发往原供应商，不受当前供应商切换影响。
+
+use super::{passthrough, server::ProxyState, ProxyError};
+use crate::provider::Provider;
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    response::Response,
+};
+use serde_json::Value;
+
+/// 批处理接口路径前缀
+const BATCHES_PATH: &str = "/v1/messages/batches";
+
+/// 从路径中提取批处理 ID（`/v1/messages/batches/{id}[/results|/cancel]`）
+fn batch_id(endpoint: &str) -> Option<&str> {
+    let rest = endpoint.split('?').next()?.strip_prefix(BATCHES_PATH)?;
+    rest.strip_prefix('/')?
+        .split('/')
+        .next()
+        .filter(|id| !id.is_empty())
+}
+
+/// 选择处理批处理请求的供应商：已记录的批处理发往原供应商，否则使用当前首选供应商
+async fn select_provider(
+    state: &ProxyState,
+    batch_id: Option<&str>,
+) -> Result<Provider, ProxyError> {
+    if let Some(id) = batch_id {
+        let recorded = state
+            .db
+            .get_message_batch_provider(id)
+            .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+        if let Some(provider_id) = recorded {
+            match state.db.get_provider_by_id(&provider_id, "claude") {
+                Ok(Some(provider)) => return Ok(provider),
+                _ => log::warn!(
+                    "[Batches] 批处理 {id} 的供应商 {provider_id} 已不存在，改用当前供应商"
+                ),
+            }
+        }
+    }
+    passthrough::current_provider(state).await
+}
+
+/// 处理 /v1/messages/batches 及其子路径的请求
+pub async fn handle_message_batches(
+    State(state): State<ProxyState>,
+    request: Request,
+) -> Result<Response, ProxyError> {
+    let (parts, body) = request.into_parts();
+    let endpoint = passthrough::upstream_endpoint(&parts.uri);
+    let provider = select_provider(&state, batch_id(&endpoint)).await?;
+    let is_create = parts.method == Method::POST && batch_id(&endpoint).is_none();
+
+    let response = passthrough::send("Batches", &provider, &endpoint, &parts, body).await?;
+    if !is_create || !response.status().is_success() {
+        return passthrough::stream_response(response);
+    }
+
+    // 创建成功：记录批处理所属的供应商
+    let status = response.status();
+    let response_headers = response.headers().clone();
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| ProxyError::ForwardFailed(format!("读取响应失败: {e}")))?;
+    let created = serde_json::from_slice::<Value>(&bytes).ok();
+    if let Some(id) = created
+        .as_ref()
+        .and_then(|v| v.get("id"))
+        .and_then(|v| v.as_str())
+    {
+        if let Err(e) = state.db.record_message_batch(id, &provider.id) {
+            log::warn!("[Batches] 记录批处理 {id} 失败: {e}");
+        }
+    }
+    passthrough::build_response(status, &response_headers, axum::body::Body::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_batch_id_from_subpaths() {
+        assert_eq!(batch_id("/v1/messages/batches"), None);
+        assert_eq!(batch_id("/v1/messages/batches?limit=20"), None);
+        assert_eq!(
+            batch_id("/v1/messages/batches/msgbatch_1"),
+            Some("msgbatch_1")
+        );
+        assert_eq!(
+            batch_id("/v1/messages/batches/msgbatch_1/results?x=1"),
+            Some("msgbatch_1")
+        );
+    }
+}
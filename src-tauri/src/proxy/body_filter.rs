@@ -109,11 +109,98 @@ fn filter_recursive_with_whitelist(
     }
 }
 
+/// 按字段路径移除请求体中上游不支持的字段
+///
+/// 路径使用点号分隔（如 `metadata.user_id`），路径途经数组时作用于数组中的每个元素
+/// （如 `messages.content.cache_control`）。
+///
+/// # Returns
+/// 实际移除了字段的路径列表
+pub fn strip_denied_fields(body: &mut Value, denylist: &[String]) -> Vec<String> {
+    let mut removed = Vec::new();
+    for path in denylist {
+        let segments: Vec<&str> = path
+            .split('.')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect();
+        if !segments.is_empty() && remove_path(body, &segments) {
+            removed.push(path.clone());
+        }
+    }
+
+    if !removed.is_empty() {
+        log::debug!("[BodyFilter] 移除上游不支持的字段: {removed:?}");
+    }
+    removed
+}
+
+/// 递归移除路径对应的字段，返回是否有字段被移除
+fn remove_path(value: &mut Value, segments: &[&str]) -> bool {
+    match value {
+        Value::Array(arr) => {
+            let mut removed = false;
+            for item in arr.iter_mut() {
+                removed |= remove_path(item, segments);
+            }
+            removed
+        }
+        Value::Object(map) => match segments {
+            [] => false,
+            [last] => map.remove(*last).is_some(),
+            [first, rest @ ..] => map
+                .get_mut(*first)
+                .is_some_and(|child| remove_path(child, rest)),
+        },
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_strip_denied_fields() {
+        let mut body = json!({
+            "model": "claude-3",
+            "thinking": {"type": "enabled", "budget_tokens": 1024},
+            "metadata": {"user_id": "u1", "keep": true},
+            "messages": [
+                {"role": "user", "content": [
+                    {"type": "text", "text": "a", "cache_control": {"type": "ephemeral"}},
+                    {"type": "text", "text": "b"}
+                ]}
+            ]
+        });
+
+        let removed = strip_denied_fields(
+            &mut body,
+            &[
+                "thinking".to_string(),
+                "metadata.user_id".to_string(),
+                "messages.content.cache_control".to_string(),
+                "context_management".to_string(),
+            ],
+        );
+
+        assert_eq!(
+            removed,
+            vec![
+                "thinking",
+                "metadata.user_id",
+                "messages.content.cache_control"
+            ]
+        );
+        assert!(body.get("thinking").is_none());
+        assert_eq!(body["metadata"], json!({"keep": true}));
+        assert!(body["messages"][0]["content"][0]
+            .get("cache_control")
+            .is_none());
+        assert_eq!(body["model"], "claude-3");
+    }
+
     #[test]
     fn test_filter_top_level_private_params() {
         let input = json!({
@@ -0,0 +1,240 @@
+//! 供应商预算（消费/token 限额）执行
+//!
+//! 限额沿用 `limitDailyUsd` / `limitMonthlyUsd`，并新增 `limitDailyTokens` / `limitMonthlyTokens`；
+//! 仅当供应商配置了 `budgetAction` 时代理才会执行限额，否则限额只用于界面展示。
+//! 统计周期由供应商的 `quotaCalendar` 决定（见 `services::quota_calendar`）。
+//!
+//! 处理方式：
+//! - `warn`：记录警告并继续使用
+//! - `fallback`：从本次请求的故障转移链中移除该供应商
+//! - `reject`：故障转移链在该供应商处截断，若其为首选供应商则直接返回本地错误
+//!
+//! 无论哪种处理方式，超限时都会通知前端并推送 Webhook（每个供应商每个周期仅通知一次）。
+
+use super::{types::WebhookEvent, webhook, ProxyError};
+use crate::database::Database;
+use crate::provider::{BudgetAction, Provider, QuotaPeriod};
+use crate::services::quota_calendar;
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+use tauri::Emitter;
+
+/// 已通知过的超限记录（`app_type:provider_id:周期`）
+static WARNED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+/// 单个供应商的限额检查结果
+#[derive(Debug, Clone, PartialEq)]
+enum BudgetStatus {
+    Within,
+    /// 超出限额（`daily` / `monthly`）
+    Exceeded(&'static str),
+}
+
+/// 按预算配置过滤故障转移链
+///
+/// 过滤后为空时返回 `ProxyError::BudgetExceeded`
+pub fn apply_budgets(
+    db: &Database,
+    app_handle: Option<&tauri::AppHandle>,
+    app_type: &str,
+    providers: Vec<Provider>,
+) -> Result<Vec<Provider>, ProxyError> {
+    let mut result = Vec::with_capacity(providers.len());
+    let mut last_error = None;
+
+    for provider in providers {
+        let Some(action) = provider.meta.as_ref().and_then(|m| m.budget_action) else {
+            result.push(provider);
+            continue;
+        };
+
+        let BudgetStatus::Exceeded(period) = check_budget(db, app_type, &provider) else {
+            result.push(provider);
+            continue;
+        };
+
+        let error = ProxyError::BudgetExceeded {
+            provider: provider.name.clone(),
+            period: period_label(&provider, period).to_string(),
+        };
+
+        match action {
+            BudgetAction::Warn => {
+                notify_once(db, app_handle, app_type, &provider, period);
+                result.push(provider);
+            }
+            BudgetAction::Fallback => {
+                notify_once(db, app_handle, app_type, &provider, period);
+                log::info!(
+                    "[{app_type}] 供应商 {} 已超出{}限额，本次请求跳过",
+                    provider.name,
+                    period_label(&provider, period)
+                );
+                last_error = Some(error);
+            }
+            BudgetAction::Reject => {
+                notify_once(db, app_handle, app_type, &provider, period);
+                log::warn!(
+                    "[{app_type}] 供应商 {} 已超出{}限额，拒绝继续转发",
+                    provider.name,
+                    period_label(&provider, period)
+                );
+                last_error = Some(error);
+                break;
+            }
+        }
+    }
+
+    match (result.is_empty(), last_error) {
+        (true, Some(error)) => Err(error),
+        _ => Ok(result),
+    }
+}
+
+fn check_budget(db: &Database, app_type: &str, provider: &Provider) -> BudgetStatus {
+    match db.check_provider_limits(&provider.id, app_type) {
+        Ok(status) if status.daily_exceeded => BudgetStatus::Exceeded("daily"),
+        Ok(status) if status.monthly_exceeded => BudgetStatus::Exceeded("monthly"),
+        Ok(_) => BudgetStatus::Within,
+        Err(e) => {
+            log::warn!("[{app_type}] 检查供应商 {} 限额失败: {e}", provider.id);
+            BudgetStatus::Within
+        }
+    }
+}
+
+fn period_label(provider: &Provider, period: &str) -> &'static str {
+    match (period, quota_calendar::calendar_of(provider).period) {
+        ("daily", _) => "每日",
+        (_, QuotaPeriod::Weekly) => "每周",
+        _ => "每月",
+    }
+}
+
+/// 当前周期标识（周期起始时间，按重置日历计算）
+fn period_key(provider: &Provider, period: &str) -> i64 {
+    let windows =
+        quota_calendar::current_windows(&quota_calendar::calendar_of(provider), chrono::Utc::now());
+    match period {
+        "daily" => windows.daily.start,
+        _ => windows.period.start,
+    }
+}
+
+fn notify_once(
+    db: &Database,
+    app_handle: Option<&tauri::AppHandle>,
+    app_type: &str,
+    provider: &Provider,
+    period: &'static str,
+) {
+    let key = format!(
+        "{app_type}:{}:{}",
+        provider.id,
+        period_key(provider, period)
+    );
+    let first = WARNED
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .map(|mut set| set.insert(key))
+        .unwrap_or(false);
+    if !first {
+        return;
+    }
+
+    let label = period_label(provider, period);
+    let action = provider.meta.as_ref().and_then(|m| m.budget_action);
+    if action == Some(BudgetAction::Warn) {
+        log::warn!(
+            "[{app_type}] 供应商 {} 已超出{label}限额（仅警告）",
+            provider.name
+        );
+    }
+    let payload = serde_json::json!({
+        "appType": app_type,
+        "providerId": provider.id,
+        "providerName": provider.name,
+        "period": period,
+        "periodLabel": label,
+        "action": action,
+    });
+    if let Some(app) = app_handle {
+        let _ = app.emit("provider-budget-exceeded", payload.clone());
+    }
+    webhook::dispatch(db, WebhookEvent::BudgetExceeded, payload);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use crate::provider::ProviderMeta;
+
+    fn provider(id: &str, action: Option<BudgetAction>, daily_tokens: Option<u64>) -> Provider {
+        Provider::with_meta(
+            id,
+            ProviderMeta {
+                budget_action: action,
+                limit_daily_tokens: daily_tokens,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn seed(db: &Database, providers: &[&Provider]) -> Result<(), AppError> {
+        for p in providers {
+            db.save_provider("claude", p)?;
+        }
+        let conn = crate::database::lock_conn!(db.conn);
+        conn.execute(
+            "INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model,
+                input_tokens, output_tokens, total_cost_usd, latency_ms, status_code, created_at)
+             VALUES ('r1', 'a', 'claude', 'm', 600, 500, '0', 10, 200, strftime('%s','now'))",
+            [],
+        )
+        .map_err(|e| AppError::Database(e.to_string()))?;
+        Ok(())
+    }
+
+    #[test]
+    fn fallback_skips_exceeded_provider() {
+        let db = Database::memory().unwrap();
+        let a = provider("a", Some(BudgetAction::Fallback), Some(1000));
+        let b = provider("b", None, None);
+        seed(&db, &[&a, &b]).unwrap();
+
+        let result = apply_budgets(&db, None, "claude", vec![a, b]).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "b");
+    }
+
+    #[test]
+    fn reject_returns_local_error() {
+        let db = Database::memory().unwrap();
+        let a = provider("a", Some(BudgetAction::Reject), Some(1000));
+        let b = provider("b", None, None);
+        seed(&db, &[&a, &b]).unwrap();
+
+        let err = apply_budgets(&db, None, "claude", vec![a, b]).unwrap_err();
+        assert!(matches!(err, ProxyError::BudgetExceeded { .. }));
+    }
+
+    #[test]
+    fn warn_and_unenforced_limits_keep_provider() {
+        let db = Database::memory().unwrap();
+        let a = provider("a", Some(BudgetAction::Warn), Some(1000));
+        seed(&db, &[&a]).unwrap();
+        assert_eq!(
+            apply_budgets(&db, None, "claude", vec![a]).unwrap().len(),
+            1
+        );
+
+        let db = Database::memory().unwrap();
+        let a = provider("a", None, Some(1000));
+        seed(&db, &[&a]).unwrap();
+        assert_eq!(
+            apply_budgets(&db, None, "claude", vec![a]).unwrap().len(),
+            1
+        );
+    }
+}
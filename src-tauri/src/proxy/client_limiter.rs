@@ -0,0 +1,205 @@
+//! 本地客户端限流模块
+//!
+//! 多个工具或团队成员共用一个 cc-switch 实例时，单个客户端的突发请求可能耗尽共享的上游配额。
+//! 启用后按客户端身份（IP / API Key / User-Agent）统计最近 60 秒的请求数，
+//! 超过每分钟上限时直接返回 429，并通过 `Retry-After` 告知客户端等待时间。
+//!
+//! 采用滑动窗口计数，配置存储在 settings 表（`client_rate_limit_config`）中，每次请求实时读取。
+
+use super::{
+    server::ProxyState,
+    types::{ClientIdentity, ClientRateLimitConfig},
+    ProxyError,
+};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 滑动窗口长度
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// 客户端数量超过该值时清理过期记录
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// 按客户端身份统计请求频率的滑动窗口限流器
+#[derive(Default)]
+pub struct ClientRateLimiter {
+    windows: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl ClientRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次请求并检查是否超限
+    ///
+    /// 超限时返回需要等待的秒数（至少 1 秒），此次请求不计入窗口
+    pub fn check(&self, client: &str, limit: u32, now: Instant) -> Result<(), u64> {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+
+        if windows.len() > PRUNE_THRESHOLD {
+            windows.retain(|_, hits| {
+                hits.back()
+                    .is_some_and(|last| now.duration_since(*last) < WINDOW)
+            });
+        }
+
+        let hits = windows.entry(client.to_string()).or_default();
+        while hits
+            .front()
+            .is_some_and(|first| now.duration_since(*first) >= WINDOW)
+        {
+            hits.pop_front();
+        }
+
+        if hits.len() >= limit as usize {
+            let oldest = hits.front().copied().unwrap_or(now);
+            let wait = WINDOW.saturating_sub(now.duration_since(oldest));
+            return Err(wait.as_secs_f64().ceil().max(1.0) as u64);
+        }
+
+        hits.push_back(now);
+        Ok(())
+    }
+}
+
+/// 根据配置的识别方式提取客户端身份
+fn client_key(identity: ClientIdentity, headers: &HeaderMap, addr: Option<SocketAddr>) -> String {
+    let header_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    let key = match identity {
+        ClientIdentity::Ip => None,
+        // 团队网关会在鉴权后移除 x-goog-api-key，此时按已识别的成员区分
+        ClientIdentity::ApiKey => super::team_gateway::member_id(headers)
+            .map(|id| format!("member:{id}"))
+            .or_else(|| {
+                header_value("x-api-key")
+                    .or_else(|| {
+                        header_value("authorization")
+                            .map(|v| v.strip_prefix("Bearer ").map(str::to_string).unwrap_or(v))
+                    })
+                    .or_else(|| header_value("x-goog-api-key"))
+                    .map(|k| format!("key:{}", mask_key(&k)))
+            }),
+        ClientIdentity::UserAgent => header_value("user-agent").map(|ua| format!("ua:{ua}")),
+    };
+
+    key.unwrap_or_else(|| match addr {
+        Some(addr) => format!("ip:{}", addr.ip()),
+        None => "ip:unknown".to_string(),
+    })
+}
+
+/// 仅保留 Key 的首尾字符，避免在内存和日志中保存完整密钥
+fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 12 {
+        return key.to_string();
+    }
+    let head: String = chars[..6].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{head}…{tail}({})", chars.len())
+}
+
+/// Axum 中间件：在转发前执行本地限流
+pub async fn enforce_client_rate_limit(
+    State(state): State<ProxyState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config: ClientRateLimitConfig = state.db.get_client_rate_limit_config().unwrap_or_default();
+    if !config.enabled || config.requests_per_minute == 0 {
+        return next.run(request).await;
+    }
+
+    let addr = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let client = client_key(config.identity, request.headers(), addr);
+
+    match state
+        .client_limiter
+        .check(&client, config.requests_per_minute, Instant::now())
+    {
+        Ok(()) => next.run(request).await,
+        Err(retry_after_secs) => {
+            log::warn!(
+                "[ClientLimit] 客户端 {client} 超过每分钟 {} 次请求上限，{retry_after_secs} 秒后可重试",
+                config.requests_per_minute
+            );
+            ProxyError::ClientRateLimited { retry_after_secs }.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_up_to_limit_then_rejects() {
+        let limiter = ClientRateLimiter::new();
+        let now = Instant::now();
+        assert!(limiter.check("a", 2, now).is_ok());
+        assert!(limiter.check("a", 2, now + Duration::from_secs(10)).is_ok());
+        let wait = limiter
+            .check("a", 2, now + Duration::from_secs(20))
+            .unwrap_err();
+        assert_eq!(wait, 40);
+        // 其他客户端不受影响
+        assert!(limiter.check("b", 2, now).is_ok());
+    }
+
+    #[test]
+    fn test_window_slides() {
+        let limiter = ClientRateLimiter::new();
+        let now = Instant::now();
+        assert!(limiter.check("a", 1, now).is_ok());
+        assert!(limiter
+            .check("a", 1, now + Duration::from_secs(59))
+            .is_err());
+        assert!(limiter.check("a", 1, now + Duration::from_secs(60)).is_ok());
+    }
+
+    #[test]
+    fn test_client_key_fallbacks() {
+        let addr: SocketAddr = "192.168.1.5:50000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            client_key(ClientIdentity::ApiKey, &headers, Some(addr)),
+            "ip:192.168.1.5"
+        );
+
+        headers.insert(
+            "authorization",
+            "Bearer sk-abcdefghijklmnop".parse().unwrap(),
+        );
+        assert_eq!(
+            client_key(ClientIdentity::ApiKey, &headers, Some(addr)),
+            "key:sk-abc…mnop(19)"
+        );
+
+        headers.insert("user-agent", "claude-cli/2.0".parse().unwrap());
+        assert_eq!(
+            client_key(ClientIdentity::UserAgent, &headers, None),
+            "ua:claude-cli/2.0"
+        );
+        assert_eq!(client_key(ClientIdentity::Ip, &headers, None), "ip:unknown");
+    }
+}
@@ -0,0 +1,180 @@
+//! 上游压缩响应解码
+//!
+//! 代理向上游固定发送 `accept-encoding: identity`，但部分中转仍会返回 gzip / deflate / brotli 压缩的响应体，
+//! 直接处理会让日志、用量解析与错误信息读到乱码。响应到达时按 `content-encoding` 流式解压，
+//! 并移除 `content-encoding` / `content-length`，之后的日志、用量统计、错误处理与客户端都只看到明文响应。
+//!
+//! 多重编码或无法识别的编码原样透传（保留原响应头，由客户端自行解码）。
+
+use bytes::Bytes;
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, CONTENT_ENCODING, CONTENT_LENGTH};
+use std::io::Write;
+
+/// brotli 解码器内部缓冲大小
+const BROTLI_BUFFER_SIZE: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Gzip,
+    Deflate,
+    Brotli,
+}
+
+/// 识别响应的压缩编码（未压缩、多重编码或不支持的编码返回 None）
+fn detect(headers: &HeaderMap) -> Option<Encoding> {
+    let value = headers.get(CONTENT_ENCODING)?.to_str().ok()?;
+    match value.trim().to_ascii_lowercase().as_str() {
+        "gzip" | "x-gzip" => Some(Encoding::Gzip),
+        "deflate" => Some(Encoding::Deflate),
+        "br" => Some(Encoding::Brotli),
+        _ => None,
+    }
+}
+
+/// 增量解码器：每次写入一个块并取出已解压的数据
+enum Decoder {
+    Gzip(flate2::write::GzDecoder<Vec<u8>>),
+    Deflate(flate2::write::ZlibDecoder<Vec<u8>>),
+    Brotli(Box<brotli_decompressor::DecompressorWriter<Vec<u8>>>),
+}
+
+impl Decoder {
+    fn new(encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Gzip => Self::Gzip(flate2::write::GzDecoder::new(Vec::new())),
+            Encoding::Deflate => Self::Deflate(flate2::write::ZlibDecoder::new(Vec::new())),
+            Encoding::Brotli => Self::Brotli(Box::new(
+                brotli_decompressor::DecompressorWriter::new(Vec::new(), BROTLI_BUFFER_SIZE),
+            )),
+        }
+    }
+
+    fn write(&mut self, chunk: &[u8]) -> std::io::Result<Bytes> {
+        let output = match self {
+            Self::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                decoder.get_mut()
+            }
+            Self::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                decoder.get_mut()
+            }
+            Self::Brotli(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                decoder.get_mut()
+            }
+        };
+        Ok(Bytes::from(std::mem::take(output)))
+    }
+
+    fn finish(self) -> std::io::Result<Bytes> {
+        let output = match self {
+            Self::Gzip(decoder) => decoder.finish()?,
+            Self::Deflate(decoder) => decoder.finish()?,
+            Self::Brotli(decoder) => decoder.into_inner().map_err(|_| {
+                std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "brotli 响应体不完整")
+            })?,
+        };
+        Ok(Bytes::from(output))
+    }
+}
+
+/// 解压流式数据
+fn decode_stream(
+    stream: impl futures::Stream<Item = reqwest::Result<Bytes>> + Send + 'static,
+    encoding: Encoding,
+) -> impl futures::Stream<Item = std::io::Result<Bytes>> + Send + 'static {
+    async_stream::stream! {
+        let mut decoder = Decoder::new(encoding);
+        tokio::pin!(stream);
+        while let Some(chunk) = stream.next().await {
+            let decoded = match chunk {
+                Ok(bytes) => decoder.write(&bytes),
+                Err(e) => Err(std::io::Error::other(e.to_string())),
+            };
+            match decoded {
+                Ok(bytes) if bytes.is_empty() => {}
+                Ok(bytes) => yield Ok(bytes),
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+        match decoder.finish() {
+            Ok(bytes) if bytes.is_empty() => {}
+            result => yield result,
+        }
+    }
+}
+
+/// 解压上游响应（未压缩或不支持的编码原样返回）
+pub fn decode_response(response: reqwest::Response) -> reqwest::Response {
+    let Some(encoding) = detect(response.headers()) else {
+        return response;
+    };
+    log::debug!("上游返回 {encoding:?} 压缩响应，解压后转发");
+
+    let status = response.status();
+    let mut headers = response.headers().clone();
+    headers.remove(CONTENT_ENCODING);
+    headers.remove(CONTENT_LENGTH);
+    let extensions = response.extensions().clone();
+    let stream = decode_stream(response.bytes_stream(), encoding);
+
+    let mut builder = axum::http::Response::builder().status(status);
+    if let Some(target) = builder.headers_mut() {
+        *target = headers;
+    }
+    match builder.body(reqwest::Body::wrap_stream(stream)) {
+        Ok(decoded) => {
+            let mut decoded = reqwest::Response::from(decoded);
+            *decoded.extensions_mut() = extensions;
+            decoded
+        }
+        Err(e) => {
+            log::error!("构造解压响应失败: {e}");
+            reqwest::Response::from(axum::http::Response::new(reqwest::Body::from("")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+
+    #[tokio::test]
+    async fn decodes_gzip_body_split_across_chunks() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(br#"{"error":{"message":"rate limit"}}"#)
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+        let (a, b) = compressed.split_at(compressed.len() / 2);
+        let chunks = vec![Ok(Bytes::copy_from_slice(a)), Ok(Bytes::copy_from_slice(b))];
+
+        let decoded: Vec<_> = decode_stream(futures::stream::iter(chunks), Encoding::Gzip)
+            .collect()
+            .await;
+        let text: Vec<u8> = decoded
+            .into_iter()
+            .flat_map(|chunk| chunk.unwrap().to_vec())
+            .collect();
+        assert_eq!(text, br#"{"error":{"message":"rate limit"}}"#);
+    }
+
+    #[test]
+    fn detects_supported_encodings_only() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(detect(&headers), None);
+        headers.insert(CONTENT_ENCODING, "BR".parse().unwrap());
+        assert_eq!(detect(&headers), Some(Encoding::Brotli));
+        headers.insert(CONTENT_ENCODING, "gzip, br".parse().unwrap());
+        assert_eq!(detect(&headers), None);
+    }
+}
@@ -0,0 +1,319 @@
+//! 上下文窗口检查模块
+//!
+//! 根据 Provider 配置的 `contextWindow` 估算请求 token 数，超过目标供应商的上下文窗口时：
+//! - `reject`：跳过该供应商，全部超限时返回清晰的本地错误（而不是上游的含糊 400）
+//! - `truncate`：从最早的对话轮次开始移除，直到请求可以放入窗口
+//!
+//! 截断时保证剩余对话以完整的用户轮次开头，不会留下孤立的 tool_result / 函数响应；
+//! 找不到这样的截断点（如最后一条消息就是 tool_result）时按超限处理。
+
+use super::{
+    system_prompt::PromptFormat,
+    token_estimate::{estimate_value_tokens, requested_output_tokens},
+    ProxyError,
+};
+use crate::provider::{ContextOverflowAction, ContextWindowConfig, Provider};
+use serde_json::Value;
+
+/// 上下文窗口检查结果
+#[derive(Debug)]
+pub enum ContextCheck {
+    /// 无需处理
+    Fits,
+    /// 已截断，返回截断后的请求体
+    Truncated(Value),
+    /// 超限且不截断（或截断后仍超限）
+    Exceeded(ProxyError),
+}
+
+fn get_config(provider: &Provider) -> Option<&ContextWindowConfig> {
+    provider
+        .meta
+        .as_ref()?
+        .context_window
+        .as_ref()
+        .filter(|c| c.max_tokens > 0)
+}
+
+/// 对话数组字段名
+fn turns_key(format: PromptFormat) -> &'static str {
+    match format {
+        PromptFormat::Anthropic | PromptFormat::OpenAIChat => "messages",
+        PromptFormat::OpenAIResponses => "input",
+        PromptFormat::Gemini => "contents",
+    }
+}
+
+/// 是否为必须保留在开头的系统消息（OpenAI 格式）
+fn is_pinned(format: PromptFormat, item: &Value) -> bool {
+    matches!(
+        format,
+        PromptFormat::OpenAIChat | PromptFormat::OpenAIResponses
+    ) && matches!(
+        item.get("role").and_then(|r| r.as_str()),
+        Some("system" | "developer")
+    )
+}
+
+/// 是否可以作为截断后的第一条消息（完整的用户轮次）
+fn is_clean_user_turn(format: PromptFormat, item: &Value) -> bool {
+    if item.get("role").and_then(|r| r.as_str()) != Some("user") {
+        return false;
+    }
+    match format {
+        PromptFormat::Anthropic => {
+            !item
+                .get("content")
+                .and_then(|c| c.as_array())
+                .is_some_and(|blocks| {
+                    blocks
+                        .iter()
+                        .any(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+                })
+        }
+        PromptFormat::OpenAIChat => true,
+        PromptFormat::OpenAIResponses => item
+            .get("type")
+            .and_then(|t| t.as_str())
+            .is_none_or(|t| t == "message"),
+        PromptFormat::Gemini => !item
+            .get("parts")
+            .and_then(|p| p.as_array())
+            .is_some_and(|parts| parts.iter().any(|p| p.get("functionResponse").is_some())),
+    }
+}
+
+/// 估算请求所需的 token 数（包含预留输出）
+fn required_tokens(body: &Value, config: &ContextWindowConfig) -> u64 {
+    let output = if config.reserve_output {
+        requested_output_tokens(body)
+    } else {
+        0
+    };
+    estimate_value_tokens(body) + output
+}
+
+/// 从最早的轮次开始截断，返回是否截断成功（可放入窗口）
+///
+/// 只在完整的用户轮次处截断，tool_use 与对应的 tool_result 总是一起移除。
+/// 数组的估算值等于各元素之和，因此每条消息只估算一次。
+fn truncate_oldest(body: &mut Value, format: PromptFormat, config: &ContextWindowConfig) -> bool {
+    let key = turns_key(format);
+    let Some(turns) = body.get_mut(key).and_then(|t| t.as_array_mut()) else {
+        return false;
+    };
+    let mut items = std::mem::take(turns);
+    // 对话数组为空时的请求体开销
+    let base = required_tokens(body, config);
+    let pinned = items.iter().take_while(|t| is_pinned(format, t)).count();
+    let costs: Vec<u64> = items.iter().map(estimate_value_tokens).collect();
+    let pinned_cost: u64 = costs[..pinned].iter().sum();
+    let mut kept: u64 = costs[pinned..].iter().sum();
+
+    // 依次尝试从更晚的完整用户轮次开始保留，取第一个能放入窗口的截断点
+    let mut cut = None;
+    for start in pinned + 1..items.len() {
+        kept -= costs[start - 1];
+        if is_clean_user_turn(format, &items[start])
+            && base + pinned_cost + kept <= config.max_tokens
+        {
+            cut = Some(start);
+            break;
+        }
+    }
+
+    if let Some(start) = cut {
+        items.drain(pinned..start);
+    }
+    body[key] = Value::Array(items);
+    cut.is_some()
+}
+
+/// 检查请求是否超出 Provider 的上下文窗口
+pub fn check_context_window(body: &Value, provider: &Provider, endpoint: &str) -> ContextCheck {
+    let Some(config) = get_config(provider) else {
+        return ContextCheck::Fits;
+    };
+
+    let estimated = required_tokens(body, config);
+    if estimated <= config.max_tokens {
+        return ContextCheck::Fits;
+    }
+
+    let exceeded = || {
+        ContextCheck::Exceeded(ProxyError::ContextWindowExceeded {
+            estimated,
+            limit: config.max_tokens,
+        })
+    };
+
+    if config.on_overflow != ContextOverflowAction::Truncate {
+        log::warn!(
+            "[ContextWindow] 请求约 {estimated} tokens，超过供应商 {} 的上下文窗口 {}",
+            provider.name,
+            config.max_tokens
+        );
+        return exceeded();
+    }
+
+    let Some(format) = PromptFormat::from_endpoint(endpoint) else {
+        return exceeded();
+    };
+
+    let mut truncated = body.clone();
+    let before = truncated
+        .get(turns_key(format))
+        .and_then(|t| t.as_array())
+        .map_or(0, |t| t.len());
+    if !truncate_oldest(&mut truncated, format, config) {
+        log::warn!(
+            "[ContextWindow] 请求约 {estimated} tokens，截断后仍超过供应商 {} 的上下文窗口 {}",
+            provider.name,
+            config.max_tokens
+        );
+        return exceeded();
+    }
+
+    let after = truncated
+        .get(turns_key(format))
+        .and_then(|t| t.as_array())
+        .map_or(0, |t| t.len());
+    log::info!(
+        "[ContextWindow] 供应商 {} 上下文超限（约 {estimated} / {} tokens），已移除最早的 {} 条消息",
+        provider.name,
+        config.max_tokens,
+        before - after
+    );
+    ContextCheck::Truncated(truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider(max_tokens: u64, on_overflow: ContextOverflowAction) -> Provider {
+        Provider::with_meta(
+            "p",
+            ProviderMeta {
+                context_window: Some(ContextWindowConfig {
+                    max_tokens,
+                    on_overflow,
+                    reserve_output: false,
+                }),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn long_text(tokens: usize) -> String {
+        "abcd".repeat(tokens)
+    }
+
+    #[test]
+    fn test_fits() {
+        let body = json!({"messages": [{"role": "user", "content": "hi"}]});
+        assert!(matches!(
+            check_context_window(
+                &body,
+                &provider(1000, ContextOverflowAction::Reject),
+                "/v1/messages"
+            ),
+            ContextCheck::Fits
+        ));
+    }
+
+    #[test]
+    fn test_reject() {
+        let body = json!({"messages": [{"role": "user", "content": long_text(2000)}]});
+        match check_context_window(
+            &body,
+            &provider(1000, ContextOverflowAction::Reject),
+            "/v1/messages",
+        ) {
+            ContextCheck::Exceeded(ProxyError::ContextWindowExceeded { limit, .. }) => {
+                assert_eq!(limit, 1000)
+            }
+            other => panic!("unexpected: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_truncate_anthropic_skips_orphan_tool_results() {
+        let body = json!({
+            "messages": [
+                {"role": "user", "content": long_text(600)},
+                {"role": "assistant", "content": [{"type": "tool_use", "id": "t1", "name": "x", "input": {}}]},
+                {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]},
+                {"role": "assistant", "content": "done"},
+                {"role": "user", "content": "next question"}
+            ]
+        });
+        let result = check_context_window(
+            &body,
+            &provider(500, ContextOverflowAction::Truncate),
+            "/v1/messages",
+        );
+        let ContextCheck::Truncated(body) = result else {
+            panic!("expected truncation");
+        };
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["content"], "next question");
+    }
+
+    #[test]
+    fn test_truncate_openai_keeps_system() {
+        let body = json!({
+            "messages": [
+                {"role": "system", "content": "be nice"},
+                {"role": "user", "content": long_text(600)},
+                {"role": "assistant", "content": "ok"},
+                {"role": "user", "content": "short"}
+            ]
+        });
+        let ContextCheck::Truncated(body) = check_context_window(
+            &body,
+            &provider(500, ContextOverflowAction::Truncate),
+            "/v1/chat/completions",
+        ) else {
+            panic!("expected truncation");
+        };
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(messages.last().unwrap()["content"], "short");
+    }
+
+    #[test]
+    fn test_truncate_refuses_to_orphan_trailing_tool_result() {
+        let body = json!({
+            "messages": [
+                {"role": "user", "content": long_text(600)},
+                {"role": "assistant", "content": [{"type": "tool_use", "id": "t1", "name": "x", "input": {}}]},
+                {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]}
+            ]
+        });
+        assert!(matches!(
+            check_context_window(
+                &body,
+                &provider(500, ContextOverflowAction::Truncate),
+                "/v1/messages"
+            ),
+            ContextCheck::Exceeded(_)
+        ));
+    }
+
+    #[test]
+    fn test_truncate_fails_when_last_message_too_large() {
+        let body = json!({"messages": [{"role": "user", "content": long_text(2000)}]});
+        assert!(matches!(
+            check_context_window(
+                &body,
+                &provider(500, ContextOverflowAction::Truncate),
+                "/v1/messages"
+            ),
+            ContextCheck::Exceeded(_)
+        ));
+    }
+}
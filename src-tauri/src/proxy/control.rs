@@ -0,0 +1,94 @@
+//! 本机控制接口（`/ccswitch/control/*`）
+//!
+//! 供同一台机器上的 `cc-switch` 命令行操作正在运行的代理进程（GUI 或 `--daemon`）：
+//!
+//! - `POST /ccswitch/control/switch`：`{"app": "claude", "provider": "<名称或 ID>"}` 由代理进程切换供应商
+//!   （接管模式下热切换，不改写 Live 配置）
+//! - `POST /ccswitch/control/shutdown`：守护进程按正常流程退出（恢复 Live 配置、删除发现文件）；
+//!   GUI 运行的代理返回 409，需在应用中停止
+//!
+//! 仅接受回环地址的请求，且须在 [`TOKEN_HEADER`] 中携带本进程启动时生成的控制令牌。
+//! 令牌随端口写入发现文件（仅当前用户可读），不会出现在日志或设置中。
+
+use super::{access_token::constant_time_eq, admin_api, server::ProxyState, ProxyError};
+use axum::{
+    extract::{ConnectInfo, Request},
+    http::StatusCode,
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use once_cell::sync::Lazy;
+use serde_json::json;
+use std::net::SocketAddr;
+
+/// 携带控制令牌的请求头
+pub const TOKEN_HEADER: &str = "x-ccswitch-control";
+
+/// 本进程的控制令牌（每次启动随机生成）
+static TOKEN: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().simple().to_string());
+
+/// 当前进程的控制令牌
+pub fn token() -> &'static str {
+    &TOKEN
+}
+
+/// 控制接口路由（挂载在 `/ccswitch/control` 下）
+pub fn router() -> Router<ProxyState> {
+    Router::new()
+        .route("/switch", post(admin_api::switch_provider))
+        .route("/shutdown", post(shutdown))
+        .route_layer(middleware::from_fn(enforce_control_token))
+}
+
+/// 请求是否来自回环地址且携带了正确的控制令牌
+fn is_authorized(addr: SocketAddr, token: Option<&str>) -> bool {
+    addr.ip().is_loopback()
+        && token.is_some_and(|token| constant_time_eq(token.as_bytes(), TOKEN.as_bytes()))
+}
+
+/// Axum 中间件：校验回环地址与控制令牌
+async fn enforce_control_token(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let token = request
+        .headers()
+        .get(TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok());
+    if !is_authorized(addr, token) {
+        log::warn!("[Control] 拒绝来自 {addr} 的控制请求");
+        return ProxyError::AuthError("控制令牌无效".to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+async fn shutdown() -> Response {
+    if crate::daemon::request_shutdown() {
+        log::info!("[Control] 收到命令行停止请求");
+        return StatusCode::ACCEPTED.into_response();
+    }
+    (
+        StatusCode::CONFLICT,
+        Json(json!({ "error": "代理由桌面应用运行，请在应用中停止" })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_loopback_and_token() {
+        let local: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+        let remote: SocketAddr = "192.168.1.2:50000".parse().unwrap();
+
+        assert!(is_authorized(local, Some(token())));
+        assert!(!is_authorized(remote, Some(token())));
+        assert!(!is_authorized(local, Some("wrong")));
+        assert!(!is_authorized(local, None));
+    }
+}
@@ -0,0 +1,622 @@
+//! 完整对话采集
+//!
+//! 与只保存单轮输入/回复的 [`super::transcript`] 不同，启用后从每个请求重建完整对话：
+//! 系统提示词、历史各轮消息、工具调用与工具结果，以及本次上游返回的最终回复。
+//! 客户端每次请求都会携带完整历史，因此同一会话的文件在每轮结束时整体覆盖，始终对应最新状态。
+//!
+//! 文件按 `<输出目录>/<应用>/<会话 ID>.md|.json` 写入，支持 Anthropic Messages、OpenAI Chat Completions、
+//! OpenAI Responses 与 Gemini 格式。配置存储在 settings 表（`conversation_capture_config`）中。
+
+use super::transcript;
+use super::types::{ConversationCaptureConfig, ConversationFormat};
+use crate::database::Database;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// 一次工具调用
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: Value,
+}
+
+/// 一次工具调用的结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolResult {
+    pub tool_use_id: String,
+    pub content: String,
+}
+
+/// 对话中的一条消息
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Turn {
+    pub role: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_results: Vec<ToolResult>,
+}
+
+impl Turn {
+    fn new(role: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.tool_calls.is_empty() && self.tool_results.is_empty()
+    }
+
+    fn push_text(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(text);
+    }
+}
+
+/// 重建后的完整对话
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub session_id: String,
+    pub app_type: String,
+    pub model: String,
+    pub updated_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    pub turns: Vec<Turn>,
+}
+
+/// 进行中的采集：请求阶段解析历史，响应结束时补充回复并写入文件
+#[derive(Debug, Clone)]
+pub struct ConversationCapture {
+    conversation: Conversation,
+    config: ConversationCaptureConfig,
+}
+
+/// 启用时从请求体解析对话历史
+pub fn begin(
+    db: &Database,
+    app_type: &str,
+    session_id: &str,
+    model: &str,
+    body: &Value,
+) -> Option<ConversationCapture> {
+    let config = db
+        .get_conversation_capture_config()
+        .ok()
+        .filter(|c| c.enabled)?;
+    let (system, turns) = parse_request(body);
+    Some(ConversationCapture {
+        conversation: Conversation {
+            session_id: session_id.to_string(),
+            app_type: app_type.to_string(),
+            model: model.to_string(),
+            updated_at: 0,
+            system,
+            turns,
+        },
+        config,
+    })
+}
+
+impl ConversationCapture {
+    /// 以非流式响应体补充回复并写入
+    pub fn finish_response(&self, body: &Value) {
+        self.write(parse_response(body));
+    }
+
+    /// 以流式响应的 SSE 事件补充回复并写入
+    pub fn finish_stream(&self, events: &[Value]) {
+        self.write(parse_stream(events));
+    }
+
+    fn write(&self, reply: Turn) {
+        let mut conversation = self.conversation.clone();
+        conversation.updated_at = chrono::Utc::now().timestamp();
+        if !reply.is_empty() {
+            conversation.turns.push(reply);
+        }
+
+        let dir = output_dir(&self.config).join(&conversation.app_type);
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            log::warn!("创建对话导出目录 {} 失败: {e}", dir.display());
+            return;
+        }
+        let stem = file_stem(&conversation.session_id);
+        let format = self.config.format;
+        if matches!(
+            format,
+            ConversationFormat::Markdown | ConversationFormat::Both
+        ) {
+            let path = dir.join(format!("{stem}.md"));
+            if let Err(e) = std::fs::write(&path, to_markdown(&conversation)) {
+                log::warn!("写入对话记录 {} 失败: {e}", path.display());
+            }
+        }
+        if matches!(format, ConversationFormat::Json | ConversationFormat::Both) {
+            let path = dir.join(format!("{stem}.json"));
+            let json = serde_json::to_string_pretty(&conversation).unwrap_or_default();
+            if let Err(e) = std::fs::write(&path, json) {
+                log::warn!("写入对话记录 {} 失败: {e}", path.display());
+            }
+        }
+    }
+}
+
+fn output_dir(config: &ConversationCaptureConfig) -> PathBuf {
+    match config.output_dir.as_deref().map(str::trim) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => crate::config::get_app_config_dir().join("conversations"),
+    }
+}
+
+/// 会话 ID 转为安全的文件名
+fn file_stem(session_id: &str) -> String {
+    let stem: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "unknown".to_string()
+    } else {
+        stem
+    }
+}
+
+/// 字符串或内容块数组中的文本
+fn text_of(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|b| match b {
+                Value::String(text) => Some(text.as_str()),
+                _ => b.get("text").and_then(|t| t.as_str()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// OpenAI 工具参数是 JSON 字符串，无法解析时保留原文
+fn parse_arguments(arguments: &str) -> Value {
+    serde_json::from_str(arguments).unwrap_or_else(|_| Value::String(arguments.to_string()))
+}
+
+/// 将一个内容块（Anthropic / Responses content、Gemini part）合入消息
+fn push_block(turn: &mut Turn, block: &Value) {
+    let str_field = |key: &str| {
+        block
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    match block.get("type").and_then(|t| t.as_str()) {
+        Some("text" | "input_text" | "output_text") => turn.push_text(&str_field("text")),
+        Some("tool_use") => turn.tool_calls.push(ToolCall {
+            id: str_field("id"),
+            name: str_field("name"),
+            input: block.get("input").cloned().unwrap_or(Value::Null),
+        }),
+        Some("tool_result") => turn.tool_results.push(ToolResult {
+            tool_use_id: str_field("tool_use_id"),
+            content: text_of(block.get("content")),
+        }),
+        Some(_) => {}
+        // Gemini part
+        None => {
+            if let Some(text) = block.get("text").and_then(|t| t.as_str()) {
+                turn.push_text(text);
+            } else if let Some(call) = block.get("functionCall") {
+                turn.tool_calls.push(ToolCall {
+                    id: String::new(),
+                    name: text_of(call.get("name")),
+                    input: call.get("args").cloned().unwrap_or(Value::Null),
+                });
+            } else if let Some(response) = block.get("functionResponse") {
+                turn.tool_results.push(ToolResult {
+                    tool_use_id: text_of(response.get("name")),
+                    content: response
+                        .get("response")
+                        .map(|r| r.to_string())
+                        .unwrap_or_default(),
+                });
+            }
+        }
+    }
+}
+
+/// 解析 Anthropic / OpenAI Chat 格式的一条消息
+fn parse_message(message: &Value) -> Turn {
+    let role = message
+        .get("role")
+        .and_then(|r| r.as_str())
+        .unwrap_or("user");
+    let mut turn = Turn::new(role);
+    match message.get("content") {
+        Some(Value::Array(blocks)) => blocks.iter().for_each(|b| push_block(&mut turn, b)),
+        content => turn.push_text(&text_of(content)),
+    }
+    // OpenAI Chat：assistant 的 tool_calls 与 tool 角色的结果
+    if let Some(calls) = message.get("tool_calls").and_then(|c| c.as_array()) {
+        for call in calls {
+            turn.tool_calls.push(ToolCall {
+                id: text_of(call.get("id")),
+                name: text_of(call.pointer("/function/name")),
+                input: parse_arguments(&text_of(call.pointer("/function/arguments"))),
+            });
+        }
+    }
+    if role == "tool" {
+        turn.tool_results.push(ToolResult {
+            tool_use_id: text_of(message.get("tool_call_id")),
+            content: std::mem::take(&mut turn.text),
+        });
+    }
+    turn
+}
+
+/// 末尾为同角色消息时复用，否则追加新消息
+fn last_turn<'a>(turns: &'a mut Vec<Turn>, role: &str) -> &'a mut Turn {
+    if turns.last().map(|t| t.role.as_str()) != Some(role) {
+        turns.push(Turn::new(role));
+    }
+    let last = turns.len() - 1;
+    &mut turns[last]
+}
+
+/// 解析 OpenAI Responses 的输出/输入项，相邻的同角色项合并为一条消息
+fn push_response_item(turns: &mut Vec<Turn>, system: &mut Vec<String>, item: &Value) {
+    match item.get("type").and_then(|t| t.as_str()) {
+        Some("function_call") => last_turn(turns, "assistant").tool_calls.push(ToolCall {
+            id: text_of(item.get("call_id")),
+            name: text_of(item.get("name")),
+            input: parse_arguments(&text_of(item.get("arguments"))),
+        }),
+        Some("function_call_output") => last_turn(turns, "tool").tool_results.push(ToolResult {
+            tool_use_id: text_of(item.get("call_id")),
+            content: text_of(item.get("output")),
+        }),
+        Some("message") | None => {
+            let role = item.get("role").and_then(|r| r.as_str()).unwrap_or("user");
+            if role == "system" || role == "developer" {
+                system.push(text_of(item.get("content")));
+                return;
+            }
+            let turn = last_turn(turns, role);
+            match item.get("content") {
+                Some(Value::Array(blocks)) => blocks.iter().for_each(|b| push_block(turn, b)),
+                content => turn.push_text(&text_of(content)),
+            }
+        }
+        // reasoning 等其他项不进入记录
+        Some(_) => {}
+    }
+}
+
+/// 从请求体解析系统提示词与历史消息
+fn parse_request(body: &Value) -> (Option<String>, Vec<Turn>) {
+    let mut system = Vec::new();
+    let mut turns = Vec::new();
+
+    if let Some(contents) = body.get("contents").and_then(|c| c.as_array()) {
+        // Gemini
+        let instruction = body
+            .get("systemInstruction")
+            .or_else(|| body.get("system_instruction"));
+        if let Some(parts) = instruction.and_then(|i| i.get("parts")) {
+            system.push(text_of(Some(parts)));
+        }
+        for content in contents {
+            let role = match content.get("role").and_then(|r| r.as_str()) {
+                Some("model") => "assistant",
+                _ => "user",
+            };
+            let mut turn = Turn::new(role);
+            if let Some(parts) = content.get("parts").and_then(|p| p.as_array()) {
+                parts.iter().for_each(|p| push_block(&mut turn, p));
+            }
+            turns.push(turn);
+        }
+    } else if let Some(messages) = body.get("messages").and_then(|m| m.as_array()) {
+        // Anthropic / OpenAI Chat
+        if body.get("system").is_some() {
+            system.push(text_of(body.get("system")));
+        }
+        for message in messages {
+            let turn = parse_message(message);
+            if turn.role == "system" || turn.role == "developer" {
+                system.push(turn.text);
+            } else {
+                turns.push(turn);
+            }
+        }
+    } else {
+        // OpenAI Responses
+        if let Some(instructions) = body.get("instructions").and_then(|i| i.as_str()) {
+            system.push(instructions.to_string());
+        }
+        match body.get("input") {
+            Some(Value::Array(items)) => {
+                for item in items {
+                    push_response_item(&mut turns, &mut system, item);
+                }
+            }
+            input => {
+                let mut turn = Turn::new("user");
+                turn.push_text(&text_of(input));
+                turns.push(turn);
+            }
+        }
+    }
+
+    system.retain(|s| !s.is_empty());
+    let system = (!system.is_empty()).then(|| system.join("\n\n"));
+    turns.retain(|t| !t.is_empty());
+    (system, turns)
+}
+
+/// 从非流式响应体解析回复
+fn parse_response(body: &Value) -> Turn {
+    // Anthropic
+    if let Some(blocks) = body.get("content").and_then(|c| c.as_array()) {
+        let mut turn = Turn::new("assistant");
+        blocks.iter().for_each(|b| push_block(&mut turn, b));
+        return turn;
+    }
+    // OpenAI Chat
+    if let Some(message) = body.pointer("/choices/0/message") {
+        let mut turn = parse_message(message);
+        turn.role = "assistant".to_string();
+        return turn;
+    }
+    // OpenAI Responses
+    if let Some(items) = body.get("output").and_then(|o| o.as_array()) {
+        let mut turns = Vec::new();
+        for item in items {
+            push_response_item(&mut turns, &mut Vec::new(), item);
+        }
+        let mut turn = Turn::new("assistant");
+        for t in turns {
+            turn.push_text(&t.text);
+            turn.tool_calls.extend(t.tool_calls);
+        }
+        return turn;
+    }
+    // Gemini
+    let mut turn = Turn::new("assistant");
+    if let Some(parts) = body
+        .pointer("/candidates/0/content/parts")
+        .and_then(|p| p.as_array())
+    {
+        parts.iter().for_each(|p| push_block(&mut turn, p));
+    }
+    turn
+}
+
+/// 流式工具调用的增量累积
+#[derive(Default)]
+struct PartialCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// 从流式 SSE 事件解析回复
+fn parse_stream(events: &[Value]) -> Turn {
+    // OpenAI Responses：完成事件携带完整响应
+    if let Some(response) = events
+        .iter()
+        .find(|e| e.get("type").and_then(|t| t.as_str()) == Some("response.completed"))
+        .and_then(|e| e.get("response"))
+    {
+        return parse_response(response);
+    }
+
+    let mut turn = Turn::new("assistant");
+    turn.push_text(&transcript::extract_stream_text(events));
+
+    let mut partial: BTreeMap<u64, PartialCall> = BTreeMap::new();
+    for event in events {
+        // Anthropic：content_block_start(tool_use) + input_json_delta
+        let index = event.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+        if let Some(block) = event
+            .get("content_block")
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+        {
+            let call = partial.entry(index).or_default();
+            call.id = text_of(block.get("id"));
+            call.name = text_of(block.get("name"));
+            continue;
+        }
+        if let Some(json) = event
+            .get("delta")
+            .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("input_json_delta"))
+            .and_then(|d| d.get("partial_json"))
+            .and_then(|p| p.as_str())
+        {
+            partial.entry(index).or_default().arguments.push_str(json);
+            continue;
+        }
+        // OpenAI Chat：delta.tool_calls 按 index 累积
+        if let Some(calls) = event
+            .pointer("/choices/0/delta/tool_calls")
+            .and_then(|c| c.as_array())
+        {
+            for call in calls {
+                let index = call.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+                let entry = partial.entry(index).or_default();
+                if let Some(id) = call.get("id").and_then(|v| v.as_str()) {
+                    entry.id = id.to_string();
+                }
+                if let Some(name) = call.pointer("/function/name").and_then(|v| v.as_str()) {
+                    entry.name.push_str(name);
+                }
+                if let Some(args) = call.pointer("/function/arguments").and_then(|v| v.as_str()) {
+                    entry.arguments.push_str(args);
+                }
+            }
+            continue;
+        }
+        // Gemini：functionCall 在单个事件中完整给出
+        if let Some(parts) = event
+            .pointer("/candidates/0/content/parts")
+            .and_then(|p| p.as_array())
+        {
+            for part in parts.iter().filter(|p| p.get("functionCall").is_some()) {
+                push_block(&mut turn, part);
+            }
+        }
+    }
+    turn.tool_calls
+        .extend(partial.into_values().map(|call| ToolCall {
+            id: call.id,
+            name: call.name,
+            input: if call.arguments.is_empty() {
+                Value::Object(Default::default())
+            } else {
+                parse_arguments(&call.arguments)
+            },
+        }));
+    turn
+}
+
+/// 渲染为 Markdown
+fn to_markdown(conversation: &Conversation) -> String {
+    let mut out = format!("# {}\n\n", conversation.session_id);
+    out.push_str(&format!("- app: {}\n", conversation.app_type));
+    out.push_str(&format!("- model: {}\n", conversation.model));
+    if let Some(time) = chrono::DateTime::from_timestamp(conversation.updated_at, 0) {
+        out.push_str(&format!("- updated: {}\n", time.to_rfc3339()));
+    }
+    if let Some(system) = &conversation.system {
+        out.push_str(&format!("\n## System\n\n{system}\n"));
+    }
+    for turn in &conversation.turns {
+        let mut role = turn.role.clone();
+        if let Some(first) = role.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        out.push_str(&format!("\n## {role}\n"));
+        if !turn.text.is_empty() {
+            out.push_str(&format!("\n{}\n", turn.text));
+        }
+        for call in &turn.tool_calls {
+            let input = serde_json::to_string_pretty(&call.input).unwrap_or_default();
+            out.push_str(&format!(
+                "\n**Tool call** `{}` ({})\n\n```json\n{input}\n```\n",
+                call.name, call.id
+            ));
+        }
+        for result in &turn.tool_results {
+            out.push_str(&format!(
+                "\n**Tool result** ({})\n\n```\n{}\n```\n",
+                result.tool_use_id, result.content
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_anthropic_history_with_tools() {
+        let body = json!({
+            "system": [{"type": "text", "text": "You are helpful."}],
+            "messages": [
+                {"role": "user", "content": "list files"},
+                {"role": "assistant", "content": [
+                    {"type": "text", "text": "Listing."},
+                    {"type": "tool_use", "id": "toolu_1", "name": "ls", "input": {"path": "."}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "a.rs"}
+                ]}
+            ]
+        });
+        let (system, turns) = parse_request(&body);
+        assert_eq!(system.as_deref(), Some("You are helpful."));
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1].tool_calls[0].name, "ls");
+        assert_eq!(turns[2].tool_results[0].content, "a.rs");
+    }
+
+    #[test]
+    fn accumulates_streamed_tool_calls() {
+        let events = vec![
+            json!({"choices": [{"delta": {"content": "Checking"}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [
+                {"index": 0, "id": "call_1", "function": {"name": "read", "arguments": "{\"pa"}}
+            ]}}]}),
+            json!({"choices": [{"delta": {"tool_calls": [
+                {"index": 0, "function": {"arguments": "th\":\"x\"}"}}
+            ]}}]}),
+        ];
+        let turn = parse_stream(&events);
+        assert_eq!(turn.text, "Checking");
+        assert_eq!(
+            turn.tool_calls,
+            [ToolCall {
+                id: "call_1".to_string(),
+                name: "read".to_string(),
+                input: json!({"path": "x"}),
+            }]
+        );
+    }
+
+    #[test]
+    fn renders_markdown_sections() {
+        let conversation = Conversation {
+            session_id: "s1".to_string(),
+            app_type: "claude".to_string(),
+            model: "claude-sonnet".to_string(),
+            updated_at: 0,
+            system: Some("sys".to_string()),
+            turns: vec![
+                Turn {
+                    role: "user".to_string(),
+                    text: "hi".to_string(),
+                    ..Default::default()
+                },
+                Turn {
+                    role: "assistant".to_string(),
+                    text: "hello".to_string(),
+                    ..Default::default()
+                },
+            ],
+        };
+        let markdown = to_markdown(&conversation);
+        assert!(markdown.contains("## System\n\nsys\n"));
+        assert!(markdown.contains("## User\n\nhi\n"));
+        assert!(markdown.contains("## Assistant\n\nhello\n"));
+        assert_eq!(file_stem("a/b:c"), "a_b_c");
+    }
+}
@@ -0,0 +1,172 @@
+//! 消费阈值提醒
+//!
+//! 每次记录请求消费后，统计所有应用在本日 / 本周的估算消费，
+//! 超出用户设置的阈值时（每个周期仅提醒一次）：
+//! - 发射前端事件 `cost-threshold-exceeded` 并发送桌面通知
+//! - 若为该应用配置了 `switchTo`，自动切换到指定的（通常更便宜的）供应商
+//!
+//! 配置存储在 settings 表（`cost_alert_config`）中。
+
+use super::failure_alert::show_notification;
+use super::types::CostAlertConfig;
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::provider::{QuotaCalendar, QuotaPeriod};
+use crate::services::quota_calendar;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use tauri::Emitter;
+
+/// 已提醒过的周期（`daily:周期起点` / `weekly:周期起点`）
+static ALERTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Period {
+    Daily,
+    Weekly,
+}
+
+impl Period {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Daily => "今日",
+            Self::Weekly => "本周",
+        }
+    }
+
+    /// 当前周期起点（Unix 秒），按限额重置日历计算
+    fn start(self, calendar: &QuotaCalendar, now: DateTime<Utc>) -> i64 {
+        let windows = quota_calendar::current_windows(calendar, now);
+        match self {
+            Self::Daily => windows.daily.start,
+            Self::Weekly => windows.period.start,
+        }
+    }
+}
+
+/// 消费提醒使用的日历：本地时区零点为一天的起点，每周一重置
+fn calendar() -> QuotaCalendar {
+    QuotaCalendar {
+        period: QuotaPeriod::Weekly,
+        ..Default::default()
+    }
+}
+
+/// 检查消费是否超出阈值（请求消费记录后调用）
+pub fn check_thresholds(db: &Database, app_handle: Option<&tauri::AppHandle>, app_type: &str) {
+    let config = match db.get_cost_alert_config() {
+        Ok(config) if config.enabled => config,
+        Ok(_) => return,
+        Err(e) => {
+            log::debug!("读取消费提醒配置失败: {e}");
+            return;
+        }
+    };
+
+    let (calendar, now) = (calendar(), Utc::now());
+    for (period, limit) in [
+        (Period::Daily, config.daily_limit_usd),
+        (Period::Weekly, config.weekly_limit_usd),
+    ] {
+        let Some(limit) = limit.filter(|l| *l > 0.0) else {
+            continue;
+        };
+        let start = period.start(&calendar, now);
+        let spent = match db.get_usage_summary(Some(start), None) {
+            Ok(summary) => summary.total_cost.parse::<f64>().unwrap_or(0.0),
+            Err(e) => {
+                log::warn!("统计{}消费失败: {e}", period.label());
+                continue;
+            }
+        };
+        if spent < limit {
+            continue;
+        }
+        let first = ALERTED
+            .get_or_init(|| Mutex::new(HashSet::new()))
+            .lock()
+            .map(|mut set| set.insert(format!("{}:{start}", period.as_str())))
+            .unwrap_or(false);
+        if first {
+            alert(&config, app_handle, app_type, period, limit, spent);
+        }
+    }
+}
+
+fn alert(
+    config: &CostAlertConfig,
+    app_handle: Option<&tauri::AppHandle>,
+    app_type: &str,
+    period: Period,
+    limit: f64,
+    spent: f64,
+) {
+    log::warn!(
+        "{}消费 ${spent:.2} 已超出提醒阈值 ${limit:.2}",
+        period.label()
+    );
+    let Some(app) = app_handle else {
+        return;
+    };
+
+    let switch_to = config.switch_to.get(app_type).cloned();
+    let payload = serde_json::json!({
+        "appType": app_type,
+        "period": period.as_str(),
+        "limitUsd": limit,
+        "spentUsd": spent,
+        "switchTo": switch_to,
+    });
+    if let Err(e) = app.emit("cost-threshold-exceeded", payload) {
+        log::error!("发射消费提醒事件失败: {e}");
+    }
+
+    let mut body = format!("{}消费 ${spent:.2}，已超出阈值 ${limit:.2}", period.label());
+    if let Some(provider_id) = &switch_to {
+        body.push_str(&format!("，[{app_type}] 将切换到 {provider_id}"));
+    }
+    show_notification(app, "消费超出阈值", &body);
+
+    let (Some(provider_id), Ok(app_type)) = (switch_to, AppType::from_str(app_type)) else {
+        return;
+    };
+    let app = app.clone();
+    // 切换供应商会同步写入配置文件，放到阻塞线程执行
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = crate::tray::switch_provider_internal(&app, app_type, provider_id) {
+            log::warn!("消费超出阈值后切换供应商失败: {e}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    #[test]
+    fn period_starts_at_local_midnight_and_monday() {
+        let tz = FixedOffset::east_opt(8 * 3600).unwrap();
+        let calendar = QuotaCalendar {
+            utc_offset_minutes: Some(8 * 60),
+            ..calendar()
+        };
+        // 2024-05-16（周四）10:30 +08:00
+        let now = tz.with_ymd_and_hms(2024, 5, 16, 10, 30, 0).unwrap();
+
+        let daily = tz.with_ymd_and_hms(2024, 5, 16, 0, 0, 0).unwrap();
+        let weekly = tz.with_ymd_and_hms(2024, 5, 13, 0, 0, 0).unwrap();
+        let now = now.with_timezone(&Utc);
+        assert_eq!(Period::Daily.start(&calendar, now), daily.timestamp());
+        assert_eq!(Period::Weekly.start(&calendar, now), weekly.timestamp());
+    }
+}
@@ -0,0 +1,258 @@
+//! 单次请求成本标注
+//!
+//! 启用后在响应中注入 `x-ccswitch-cost` / `x-ccswitch-tokens` 响应头；
+//! 流式响应的响应头在首字节前就已发出，因此改为在流末尾追加一条 SSE 注释。
+//! SSE 注释行以 `:` 开头，符合规范的客户端会直接忽略。
+//!
+//! 配置存储在 settings 表（`cost_annotation_config`）中，每次请求实时读取。
+
+use super::{
+    handler_config::UsageParserConfig, stream_buffer::SseEventBuffer, usage::parser::TokenUsage,
+};
+use crate::database::Database;
+use crate::provider::Provider;
+use axum::http::{HeaderMap, HeaderValue};
+use bytes::Bytes;
+use futures::stream::{Stream, StreamExt};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// 成本响应头（美元，未知定价时为 `unknown`）
+pub const COST_HEADER: &str = "x-ccswitch-cost";
+/// Token 用量响应头
+pub const TOKENS_HEADER: &str = "x-ccswitch-tokens";
+
+/// 格式化 Token 用量
+pub fn format_tokens(usage: &TokenUsage) -> String {
+    format!(
+        "input={}; output={}; cache_read={}; cache_creation={}",
+        usage.input_tokens,
+        usage.output_tokens,
+        usage.cache_read_tokens,
+        usage.cache_creation_tokens
+    )
+}
+
+/// 格式化成本
+pub fn format_cost(cost: Option<Decimal>) -> String {
+    match cost {
+        Some(c) => c.round_dp(6).normalize().to_string(),
+        None => "unknown".to_string(),
+    }
+}
+
+/// 按模型定价与供应商成本倍数计算本次请求成本
+///
+/// 模型定价未找到时返回 None
+pub fn calculate_cost(
+    db: &Database,
+    provider: &Provider,
+    model: &str,
+    usage: &TokenUsage,
+) -> Option<Decimal> {
+    use super::usage::{calculator::CostCalculator, logger::UsageLogger};
+
+    let pricing = UsageLogger::new(db).get_model_pricing(model).ok()??;
+    let multiplier = provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.cost_multiplier.as_deref())
+        .and_then(|cm| Decimal::from_str(cm).ok())
+        .unwrap_or(Decimal::ONE);
+
+    Some(CostCalculator::calculate(usage, &pricing, multiplier).total_cost)
+}
+
+/// 是否启用成本标注
+pub fn is_enabled(db: &Database) -> bool {
+    db.get_cost_annotation_config()
+        .map(|c| c.enabled)
+        .unwrap_or(false)
+}
+
+/// 写入成本与用量响应头
+pub fn insert_headers(headers: &mut HeaderMap, usage: &TokenUsage, cost: Option<Decimal>) {
+    if let Ok(v) = HeaderValue::from_str(&format_cost(cost)) {
+        headers.insert(COST_HEADER, v);
+    }
+    if let Ok(v) = HeaderValue::from_str(&format_tokens(usage)) {
+        headers.insert(TOKENS_HEADER, v);
+    }
+}
+
+/// 构造流末尾的 SSE 注释
+pub fn sse_comment(usage: &TokenUsage, cost: Option<Decimal>) -> String {
+    format!(
+        ": {COST_HEADER}={}; {TOKENS_HEADER}={}\n\n",
+        format_cost(cost),
+        format_tokens(usage)
+    )
+}
+
+/// 流式事件中解析用量与模型所需的部分
+///
+/// 只保留 Anthropic 的 `message_start` / `message_delta`、Responses 的 `response.completed`、
+/// 最近一个带 `usage`（OpenAI Chat）或 `usageMetadata`（Gemini）的事件以及第一个带模型名的事件，
+/// 内存占用不随响应长度增长
+#[derive(Default)]
+struct UsageEvents {
+    events: Vec<Value>,
+    latest_usage: Option<Value>,
+    has_model: bool,
+}
+
+impl UsageEvents {
+    fn push(&mut self, event: Value) {
+        let event_type = event.get("type").and_then(|t| t.as_str());
+        if matches!(
+            event_type,
+            Some("message_start" | "message_delta" | "response.completed")
+        ) {
+            self.events.push(event);
+        } else if event.get("usage").is_some_and(|u| !u.is_null())
+            || event.get("usageMetadata").is_some()
+        {
+            self.latest_usage = Some(event);
+        } else if !self.has_model
+            && (event.get("model").is_some() || event.get("modelVersion").is_some())
+        {
+            self.has_model = true;
+            self.events.push(event);
+        }
+    }
+
+    fn into_events(mut self) -> Vec<Value> {
+        self.events.extend(self.latest_usage);
+        self.events
+    }
+}
+
+/// 流式响应末尾追加成本注释
+///
+/// 透传所有数据块，同时保留与用量相关的 SSE data 事件；流正常结束后解析用量并追加一条注释。
+/// 上游流出错时不追加。
+pub fn annotate_stream(
+    stream: impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+    db: Arc<Database>,
+    provider: Provider,
+    request_model: String,
+    parser_config: UsageParserConfig,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send {
+    async_stream::stream! {
+        let mut buffer = SseEventBuffer::default();
+        let mut events = UsageEvents::default();
+        let mut failed = false;
+
+        tokio::pin!(stream);
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(bytes) => {
+                    buffer.split(&bytes, |event_text| {
+                        event_text
+                            .lines()
+                            .filter_map(|line| line.strip_prefix("data: "))
+                            .filter_map(|data| serde_json::from_str::<Value>(data).ok())
+                            .for_each(|event| events.push(event));
+                    });
+                    yield Ok(bytes);
+                }
+                Err(e) => {
+                    failed = true;
+                    yield Err(e);
+                    break;
+                }
+            }
+        }
+
+        if !failed {
+            let events = events.into_events();
+            if let Some(usage) = (parser_config.stream_parser)(&events) {
+                let model = (parser_config.model_extractor)(&events, &request_model);
+                let cost = calculate_cost(&db, &provider, &model, &usage);
+                yield Ok(Bytes::from(sse_comment(&usage, cost)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage() -> TokenUsage {
+        TokenUsage {
+            input_tokens: 1200,
+            output_tokens: 300,
+            cache_read_tokens: 100,
+            cache_creation_tokens: 0,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn formats_cost_and_tokens() {
+        assert_eq!(format_cost(None), "unknown");
+        assert_eq!(
+            format_cost(Some(Decimal::from_str("0.0123456789").unwrap())),
+            "0.012346"
+        );
+        assert_eq!(
+            format_tokens(&usage()),
+            "input=1200; output=300; cache_read=100; cache_creation=0"
+        );
+    }
+
+    #[test]
+    fn sse_comment_is_a_terminated_comment_line() {
+        let comment = sse_comment(&usage(), None);
+        assert!(comment.starts_with(": x-ccswitch-cost=unknown; x-ccswitch-tokens=input=1200"));
+        assert!(comment.ends_with("\n\n"));
+    }
+
+    #[test]
+    fn inserts_both_headers() {
+        let mut headers = HeaderMap::new();
+        insert_headers(&mut headers, &usage(), Some(Decimal::from(2)));
+        assert_eq!(headers.get(COST_HEADER).unwrap(), "2");
+        assert!(headers.get(TOKENS_HEADER).is_some());
+    }
+
+    #[test]
+    fn keeps_only_usage_events() {
+        use crate::proxy::handler_config::{CLAUDE_PARSER_CONFIG, OPENAI_PARSER_CONFIG};
+        use serde_json::json;
+
+        let mut claude = UsageEvents::default();
+        claude.push(json!({"type": "message_start", "message": {"model": "claude-x", "usage": {"input_tokens": 10}}}));
+        for _ in 0..1000 {
+            claude.push(json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "a"}}));
+        }
+        claude.push(json!({"type": "message_delta", "usage": {"output_tokens": 20}}));
+        let events = claude.into_events();
+        assert_eq!(events.len(), 2);
+        let usage = (CLAUDE_PARSER_CONFIG.stream_parser)(&events).unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (10, 20));
+        assert_eq!(
+            (CLAUDE_PARSER_CONFIG.model_extractor)(&events, "req"),
+            "claude-x"
+        );
+
+        let mut chat = UsageEvents::default();
+        for _ in 0..1000 {
+            chat.push(
+                json!({"model": "gpt-x", "choices": [{"delta": {"content": "a"}}], "usage": null}),
+            );
+        }
+        chat.push(json!({"model": "gpt-x", "choices": [], "usage": {"prompt_tokens": 5, "completion_tokens": 7}}));
+        let events = chat.into_events();
+        assert_eq!(events.len(), 2);
+        let usage = (OPENAI_PARSER_CONFIG.stream_parser)(&events).unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (5, 7));
+        assert_eq!(
+            (OPENAI_PARSER_CONFIG.model_extractor)(&events, "req"),
+            "gpt-x"
+        );
+    }
+}
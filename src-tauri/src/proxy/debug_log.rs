@@ -1,55 +1,147 @@
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
 use serde_json::Value;
+use crate::provider::Provider;
+use crate::proxy::secret_redaction::{format_headers, redact_log_text, redact_value};
+use crate::proxy::types::{DebugLogConfig, DebugLogLevel};
+
+/// 请求级调试开关：携带该请求头（值不为 `0` / `false`）的请求记录完整日志
+pub const DEBUG_HEADER: &str = "x-ccswitch-debug";
+
+/// 调试日志运行时开关
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 应用调试日志配置中的开关与目录（启动时及配置更新后调用）
+pub fn apply_config(config: &DebugLogConfig) {
+    ENABLED.store(config.enabled, Ordering::Relaxed);
+    crate::proxy::log_retention::set_debug_log_dir_override(config.log_dir.as_deref());
+}
+
+/// 是否写入调试日志
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 判断本次请求是否记录完整日志
+///
+/// 全局为详细级别时始终记录；摘要级别下仅对开启了调试日志的供应商或携带调试请求头的请求记录完整内容
+pub fn is_verbose(
+    config: &DebugLogConfig,
+    provider: &Provider,
+    headers: &axum::http::HeaderMap,
+) -> bool {
+    if config.level == DebugLogLevel::Verbose {
+        return true;
+    }
+    if provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.debug_logging)
+        .unwrap_or(false)
+    {
+        return true;
+    }
+    headers
+        .get(DEBUG_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "" | "0" | "false"))
+}
+
+/// 判断详细日志是否记录请求体与响应内容（供应商配置优先于全局配置）
+pub fn logs_bodies(config: &DebugLogConfig, provider: &Provider) -> bool {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.log_bodies)
+        .unwrap_or(config.log_bodies)
+}
 
 /// 写入日志文件
+///
+/// 写入前强制脱敏：请求头、请求体、响应块与错误信息中的密钥均不会落盘
 pub fn write_log_entry(entry: String) {
-    if let Some(home) = dirs::home_dir() {
-        let log_dir = home.join("tmp").join("log");
-        if let Err(e) = std::fs::create_dir_all(&log_dir) {
-            log::error!("Failed to create log dir: {}", e);
-            return;
-        }
+    if !is_enabled() {
+        return;
+    }
+    let entry = redact_log_text(&entry);
 
-        let now = chrono::Local::now();
-        let filename = format!("cc-{}.log", now.format("%Y%m%d%H"));
-        let log_path = log_dir.join(filename);
-
-        let mut file = match OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)
-        {
-            Ok(f) => f,
-            Err(e) => {
-                log::error!("Failed to open log file: {}", e);
-                return;
-            }
-        };
+    let log_dir = crate::proxy::log_retention::debug_log_dir();
+    if let Err(e) = std::fs::create_dir_all(&log_dir) {
+        log::error!("Failed to create log dir: {}", e);
+        return;
+    }
+
+    let filename = crate::proxy::log_retention::debug_log_file_name(chrono::Local::now());
+    let log_path = log_dir.join(filename);
 
-        if let Err(e) = file.write_all(entry.as_bytes()) {
-            log::error!("Failed to write to log file: {}", e);
+    let mut file = match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+    {
+        Ok(f) => f,
+        Err(e) => {
+            log::error!("Failed to open log file: {}", e);
+            return;
         }
+    };
+
+    if let Err(e) = file.write_all(entry.as_bytes()) {
+        log::error!("Failed to write to log file: {}", e);
     }
 }
 
-/// 记录请求日志
+/// 记录请求日志（`body` 为 None 时仅记录请求头）
 pub fn log_request(
     request_id: &str,
     provider_name: &str,
     url: &str,
-    body: &Value,
+    body: Option<&Value>,
     headers: &axum::http::HeaderMap,
 ) {
     let now = chrono::Local::now();
+    let body = match body {
+        Some(body) => {
+            let mut body = body.clone();
+            redact_value(&mut body);
+            serde_json::to_string_pretty(&body).unwrap_or_else(|_| "Invalid JSON".to_string())
+        }
+        None => "(omitted)".to_string(),
+    };
     let entry = format!(
-        "[{}] [REQ:{}] Provider: {}\nURL: {}\nHeaders: {:?}\nBody: {}\n\n--------------------------------------------------\n\n",
+        "[{}] [REQ:{}] Provider: {}\nURL: {}\nHeaders: {}\nBody: {}\n\n--------------------------------------------------\n\n",
         now.format("%Y-%m-%d %H:%M:%S%.3f"),
         request_id,
         provider_name,
         url,
-        headers,
-        serde_json::to_string_pretty(body).unwrap_or_else(|_| "Invalid JSON".to_string())
+        format_headers(headers),
+        body
+    );
+    write_log_entry(entry);
+}
+
+/// 记录请求摘要日志（摘要级别，不含请求头与请求体）
+pub fn log_request_summary(request_id: &str, provider_name: &str, url: &str) {
+    let now = chrono::Local::now();
+    let entry = format!(
+        "[{}] [REQ:{}] Provider: {} URL: {}\n",
+        now.format("%Y-%m-%d %H:%M:%S%.3f"),
+        request_id,
+        provider_name,
+        url
+    );
+    write_log_entry(entry);
+}
+
+/// 记录响应状态摘要日志（摘要级别，不含响应头与响应体）
+pub fn log_response_summary(request_id: &str, status: u16) {
+    let now = chrono::Local::now();
+    let entry = format!(
+        "[{}] [RES:{}] Status: {}\n",
+        now.format("%Y-%m-%d %H:%M:%S%.3f"),
+        request_id,
+        status
     );
     write_log_entry(entry);
 }
@@ -62,11 +154,11 @@ pub fn log_response_headers(
 ) {
     let now = chrono::Local::now();
     let entry = format!(
-        "[{}] [RES:{}] Status: {}\nHeaders: {:?}\n\n--------------------------------------------------\n\n",
+        "[{}] [RES:{}] Status: {}\nHeaders: {}\n\n--------------------------------------------------\n\n",
         now.format("%Y-%m-%d %H:%M:%S%.3f"),
         request_id,
         status,
-        headers
+        format_headers(headers)
     );
     write_log_entry(entry);
 }
@@ -83,15 +175,15 @@ pub fn log_response_chunk(request_id: &str, chunk: &str) {
     write_log_entry(entry);
 }
 
-/// 记录响应错误日志
-pub fn log_response_error(request_id: &str, status: u16, body: &Option<String>) {
+/// 记录响应错误日志（`body` 为 None 时不记录响应体）
+pub fn log_response_error(request_id: &str, status: u16, body: Option<&str>) {
     let now = chrono::Local::now();
     let entry = format!(
         "[{}] [ERR:{}] Upstream Error Status: {}\nBody: {}\n\n--------------------------------------------------\n\n",
         now.format("%Y-%m-%d %H:%M:%S%.3f"),
         request_id,
         status,
-        body.as_deref().unwrap_or("(empty)")
+        body.unwrap_or("(omitted)")
     );
     write_log_entry(entry);
 }
@@ -110,4 +202,58 @@ pub fn log_network_error(request_id: &str, error: &str) {
 
 /// 用于在 Response Extensions 中存储 Request ID
 #[derive(Clone)]
-pub struct LogRequestId(pub String);
\ No newline at end of file
+pub struct LogRequestId(pub String);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+
+    fn provider(debug_logging: Option<bool>) -> Provider {
+        Provider::with_meta(
+            "p1",
+            ProviderMeta {
+                debug_logging,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn summary_level_is_verbose_only_for_flagged_provider_or_request() {
+        let summary = DebugLogConfig {
+            level: DebugLogLevel::Summary,
+            ..Default::default()
+        };
+        let mut headers = axum::http::HeaderMap::new();
+
+        assert!(is_verbose(
+            &DebugLogConfig::default(),
+            &provider(None),
+            &headers
+        ));
+        assert!(!is_verbose(&summary, &provider(None), &headers));
+        assert!(is_verbose(&summary, &provider(Some(true)), &headers));
+
+        headers.insert(DEBUG_HEADER, "1".parse().unwrap());
+        assert!(is_verbose(&summary, &provider(None), &headers));
+        headers.insert(DEBUG_HEADER, "false".parse().unwrap());
+        assert!(!is_verbose(&summary, &provider(None), &headers));
+    }
+
+    #[test]
+    fn provider_overrides_body_logging() {
+        let config = DebugLogConfig::default();
+        let mut p = provider(None);
+        assert!(!logs_bodies(&config, &p));
+        p.meta.as_mut().unwrap().log_bodies = Some(true);
+        assert!(logs_bodies(&config, &p));
+
+        let config = DebugLogConfig {
+            log_bodies: true,
+            ..Default::default()
+        };
+        p.meta.as_mut().unwrap().log_bodies = Some(false);
+        assert!(!logs_bodies(&config, &p));
+    }
+}
@@ -0,0 +1,123 @@
+//! 代理端口发现
+//!
+//! 配置的端口被占用时代理会顺延到后续空闲端口，实际监听地址写入
+//! `<app_config_dir>/proxy-endpoint.json`，供包装脚本读取，停止代理时删除。
+//! 文件同时记录本机控制接口的令牌（见 [`super::control`]），Unix 下权限为 0600。
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 端口被占用时最多向后尝试的端口数
+pub const PORT_FALLBACK_RANGE: u16 = 20;
+
+/// 当前生效的代理监听地址
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveProxyEndpoint {
+    pub address: String,
+    pub port: u16,
+    /// 配置的端口（与 `port` 不同时表示发生了端口顺延）
+    pub configured_port: u16,
+    /// 客户端应使用的地址（如 `http://127.0.0.1:15722`）
+    pub base_url: String,
+    pub pid: u32,
+    pub started_at: String,
+    /// 本机控制接口令牌（旧版本写入的文件没有此字段）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub control_token: Option<String>,
+}
+
+/// 发现文件路径
+pub fn endpoint_file_path() -> PathBuf {
+    crate::config::get_app_config_dir().join("proxy-endpoint.json")
+}
+
+/// 客户端连接用的地址（0.0.0.0 / :: 改为回环地址，IPv6 加方括号）
+pub fn base_url(listen_address: &str, port: u16, https: bool) -> String {
+    let host = match listen_address {
+        "0.0.0.0" => "127.0.0.1",
+        "::" => "::1",
+        other => other,
+    };
+    let host = if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]")
+    } else {
+        host.to_string()
+    };
+    let scheme = if https { "https" } else { "http" };
+    format!("{scheme}://{host}:{port}")
+}
+
+/// 待尝试的端口（配置端口及其后 [`PORT_FALLBACK_RANGE`] 个端口）
+pub fn candidate_ports(configured: u16, fallback: bool) -> Vec<u16> {
+    if configured == 0 || !fallback {
+        return vec![configured];
+    }
+    (0..=PORT_FALLBACK_RANGE)
+        .filter_map(|offset| configured.checked_add(offset))
+        .collect()
+}
+
+/// 写入发现文件（失败仅记录日志）
+pub fn write(endpoint: &ActiveProxyEndpoint) {
+    let path = endpoint_file_path();
+    let result = serde_json::to_vec_pretty(endpoint)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| crate::config::atomic_write(&path, &bytes).map_err(|e| e.to_string()))
+        .and_then(|_| restrict_permissions(&path).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        log::warn!("写入代理端口发现文件失败 {}: {e}", path.display());
+    }
+}
+
+/// 发现文件包含控制令牌，仅允许当前用户读写
+#[cfg(unix)]
+fn restrict_permissions(path: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &std::path::Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// 读取发现文件
+pub fn read() -> Option<ActiveProxyEndpoint> {
+    let content = std::fs::read_to_string(endpoint_file_path()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 删除发现文件
+pub fn remove() {
+    let _ = std::fs::remove_file(endpoint_file_path());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_candidates_and_base_urls() {
+        assert_eq!(candidate_ports(15721, false), vec![15721]);
+        let ports = candidate_ports(15721, true);
+        assert_eq!(ports.first(), Some(&15721));
+        assert_eq!(ports.len(), usize::from(PORT_FALLBACK_RANGE) + 1);
+        assert_eq!(candidate_ports(65530, true).last(), Some(&65535));
+        assert_eq!(candidate_ports(0, true), vec![0]);
+
+        assert_eq!(base_url("0.0.0.0", 15722, false), "http://127.0.0.1:15722");
+        assert_eq!(base_url("::", 15721, true), "https://[::1]:15721");
+        assert_eq!(base_url("127.0.0.1", 1, false), "http://127.0.0.1:1");
+    }
+
+    #[test]
+    fn reads_endpoint_files_without_control_token() {
+        let endpoint: ActiveProxyEndpoint = serde_json::from_str(
+            r#"{"address":"127.0.0.1","port":15721,"configuredPort":15721,
+                "baseUrl":"http://127.0.0.1:15721","pid":1,"startedAt":""}"#,
+        )
+        .unwrap();
+        assert_eq!(endpoint.control_token, None);
+    }
+}
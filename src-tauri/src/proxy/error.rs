@@ -75,6 +75,34 @@ pub enum ProxyError {
     #[allow(dead_code)]
     #[error("内部错误: {0}")]
     Internal(String),
+
+    /// 请求超出供应商上下文窗口
+    #[error("请求约 {estimated} tokens，超出供应商上下文窗口（{limit} tokens）")]
+    ContextWindowExceeded { estimated: u64, limit: u64 },
+
+    /// 供应商超出消费/token 限额
+    #[error("供应商 {provider} 已超出{period}限额")]
+    BudgetExceeded { provider: String, period: String },
+
+    /// 供应商的全部轮换密钥额度已用尽
+    #[error("供应商 {provider} 的全部密钥额度已用尽")]
+    KeyAllowanceExhausted { provider: String },
+
+    /// 本地客户端请求频率超限
+    #[error("本地请求频率超限，请在 {retry_after_secs} 秒后重试")]
+    ClientRateLimited { retry_after_secs: u64 },
+
+    /// 团队成员超出配额
+    #[error("团队成员 {member} 已超出{quota}上限")]
+    MemberQuotaExceeded { member: String, quota: String },
+
+    /// 来源地址不在 IP 白名单中
+    #[error("来源地址 {ip} 不在代理 IP 白名单中")]
+    ClientNotAllowed { ip: String },
+
+    /// 请求体超过本地大小限制
+    #[error("请求体超过代理限制的 {limit_mb} MB，未发送到上游，请减少粘贴的文件或图片后重试")]
+    RequestTooLarge { limit_mb: u64 },
 }
 
 impl IntoResponse for ProxyError {
@@ -154,16 +182,43 @@ impl IntoResponse for ProxyError {
                         (StatusCode::GATEWAY_TIMEOUT, self.to_string())
                     }
                     ProxyError::AuthError(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+                    ProxyError::ClientNotAllowed { .. } => {
+                        (StatusCode::FORBIDDEN, self.to_string())
+                    }
+                    ProxyError::RequestTooLarge { .. } => {
+                        (StatusCode::PAYLOAD_TOO_LARGE, self.to_string())
+                    }
                     ProxyError::Internal(_) => {
                         (StatusCode::INTERNAL_SERVER_ERROR, self.to_string())
                     }
+                    ProxyError::ClientRateLimited { .. } => {
+                        (StatusCode::TOO_MANY_REQUESTS, self.to_string())
+                    }
+                    ProxyError::ContextWindowExceeded { .. } => {
+                        (StatusCode::BAD_REQUEST, self.to_string())
+                    }
+                    ProxyError::BudgetExceeded { .. }
+                    | ProxyError::KeyAllowanceExhausted { .. }
+                    | ProxyError::MemberQuotaExceeded { .. } => {
+                        (StatusCode::PAYMENT_REQUIRED, self.to_string())
+                    }
                     ProxyError::UpstreamError { .. } => unreachable!(),
                 };
 
+                let error_type = match &self {
+                    ProxyError::ClientRateLimited { .. } => "rate_limit_error",
+                    ProxyError::ContextWindowExceeded { .. } => "invalid_request_error",
+                    ProxyError::BudgetExceeded { .. }
+                    | ProxyError::KeyAllowanceExhausted { .. }
+                    | ProxyError::MemberQuotaExceeded { .. } => "budget_exceeded_error",
+                    ProxyError::ClientNotAllowed { .. } => "permission_error",
+                    ProxyError::RequestTooLarge { .. } => "request_too_large",
+                    _ => "proxy_error",
+                };
                 let error_body = json!({
                     "error": {
                         "message": message,
-                        "type": "proxy_error",
+                        "type": error_type,
                     }
                 });
 
@@ -171,7 +226,15 @@ impl IntoResponse for ProxyError {
             }
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let ProxyError::ClientRateLimited { retry_after_secs } = &self {
+            if let Ok(value) = axum::http::HeaderValue::from_str(&retry_after_secs.to_string()) {
+                response
+                    .headers_mut()
+                    .insert(axum::http::header::RETRY_AFTER, value);
+            }
+        }
+        response
     }
 }
 
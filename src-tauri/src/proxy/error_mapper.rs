@@ -45,6 +45,23 @@ pub fn map_proxy_error_to_status(error: &ProxyError) -> u16 {
         // 转换错误：500 Internal Server Error
         ProxyError::TransformError(_) => 500,
 
+        // 本地限流：429 Too Many Requests
+        ProxyError::ClientRateLimited { .. } => 429,
+
+        // 上下文窗口超限：400 Bad Request
+        ProxyError::ContextWindowExceeded { .. } => 400,
+
+        // 供应商限额 / 团队成员配额超出：402 Payment Required
+        ProxyError::BudgetExceeded { .. }
+        | ProxyError::KeyAllowanceExhausted { .. }
+        | ProxyError::MemberQuotaExceeded { .. } => 402,
+
+        // 来源地址不在白名单：403 Forbidden
+        ProxyError::ClientNotAllowed { .. } => 403,
+
+        // 请求体超过本地大小限制：413 Payload Too Large
+        ProxyError::RequestTooLarge { .. } => 413,
+
         // 其他未知错误：500 Internal Server Error
         _ => 500,
     }
@@ -0,0 +1,233 @@
+//! A/B 实验
+//!
+//! 对指定应用（可限定供应商）的请求按会话分流：`percentage`% 进入实验组并应用
+//! 实验的转换规则（cache_control 注入、系统提示词调整），其余进入对照组并移除同类规则。
+//! 流量切分实验（`trafficSplit`）不改写规则，而是把实验组的请求路由到另一个供应商
+//! （置于故障转移链首位），用于在真实流量下对比两个供应商。
+//! 同一会话始终落在同一组，分组结果以 `<实验 ID>:<组>` 写入请求日志的 `experiment` 列，
+//! 用于按组统计消费、延迟与错误率。
+//!
+//! 每个请求最多参与一个实验（取第一个匹配的启用实验）。实验存储在 settings 表（`experiments`）中。
+
+use super::types::{Experiment, ExperimentTreatment};
+use crate::database::Database;
+use crate::provider::{PromptCachingConfig, Provider, SystemPromptInjection};
+use sha2::{Digest, Sha256};
+
+/// 实验组
+pub const ARM_TREATMENT: &str = "treatment";
+/// 对照组
+pub const ARM_CONTROL: &str = "control";
+
+/// 会话是否分入实验组（按实验 ID + 会话 ID 哈希，结果稳定）
+fn in_treatment(experiment_id: &str, session_id: &str, percentage: u8) -> bool {
+    let digest = Sha256::digest(format!("{experiment_id}:{session_id}").as_bytes());
+    let mut bucket = [0u8; 8];
+    bucket.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(bucket) % 100) < u64::from(percentage.min(100))
+}
+
+/// 实验是否作用于该请求的故障转移链
+fn applies_to(experiment: &Experiment, app_type: &str, providers: &[Provider]) -> bool {
+    experiment.enabled
+        && experiment.app_type == app_type
+        && experiment
+            .provider_id
+            .as_ref()
+            .is_none_or(|id| providers.iter().any(|p| &p.id == id))
+}
+
+/// 按分组改写单个供应商的转换规则
+fn apply_arm(provider: &mut Provider, treatment: &ExperimentTreatment, treated: bool) {
+    if matches!(treatment, ExperimentTreatment::TrafficSplit { .. }) {
+        return;
+    }
+    let meta = provider.meta.get_or_insert_with(Default::default);
+    match treatment {
+        ExperimentTreatment::PromptCaching { min_chars } => {
+            meta.prompt_caching = treated.then_some(PromptCachingConfig {
+                enabled: true,
+                min_chars: *min_chars,
+            });
+        }
+        ExperimentTreatment::SystemPrompt { prefix, suffix } => {
+            meta.system_prompt = treated.then(|| SystemPromptInjection {
+                prefix: prefix.clone(),
+                suffix: suffix.clone(),
+            });
+        }
+        ExperimentTreatment::TrafficSplit { .. } => {}
+    }
+}
+
+/// 流量切分：把目标供应商移到链首（不在链中时通过 `lookup` 加载，找不到时保持原链）
+fn route_to(
+    providers: &mut Vec<Provider>,
+    target_id: &str,
+    lookup: impl FnOnce(&str) -> Option<Provider>,
+) -> bool {
+    let target = match providers.iter().position(|p| p.id == target_id) {
+        Some(index) => providers.remove(index),
+        None => match lookup(target_id) {
+            Some(provider) => provider,
+            None => return false,
+        },
+    };
+    providers.insert(0, target);
+    true
+}
+
+/// 为本次请求分组并改写故障转移链，返回 (改写后的链, 分组标签)
+///
+/// `lookup` 用于加载流量切分目标供应商（目标不在故障转移链中时）
+pub fn assign(
+    experiments: &[Experiment],
+    app_type: &str,
+    session_id: &str,
+    mut providers: Vec<Provider>,
+    lookup: impl FnOnce(&str) -> Option<Provider>,
+) -> (Vec<Provider>, Option<String>) {
+    let Some(experiment) = experiments
+        .iter()
+        .find(|e| applies_to(e, app_type, &providers))
+    else {
+        return (providers, None);
+    };
+
+    let treated = in_treatment(&experiment.id, session_id, experiment.percentage);
+    if let (true, ExperimentTreatment::TrafficSplit { provider_id }) =
+        (treated, &experiment.treatment)
+    {
+        if !route_to(&mut providers, provider_id, lookup) {
+            log::warn!(
+                "[Experiment] 流量切分目标供应商 {provider_id} 不存在，实验 {} 本次不分组",
+                experiment.id
+            );
+            return (providers, None);
+        }
+    }
+    for provider in providers
+        .iter_mut()
+        .filter(|p| experiment.provider_id.as_ref().is_none_or(|id| &p.id == id))
+    {
+        apply_arm(provider, &experiment.treatment, treated);
+    }
+
+    let arm = if treated { ARM_TREATMENT } else { ARM_CONTROL };
+    (providers, Some(format!("{}:{arm}", experiment.id)))
+}
+
+/// 读取实验配置并分组（读取失败时不参与实验）
+pub fn apply_experiments(
+    db: &Database,
+    app_type: &str,
+    session_id: &str,
+    providers: Vec<Provider>,
+) -> (Vec<Provider>, Option<String>) {
+    let lookup = |id: &str| db.get_provider_by_id(id, app_type).ok().flatten();
+    match db.get_experiments() {
+        Ok(experiments) => assign(&experiments, app_type, session_id, providers, lookup),
+        Err(e) => {
+            log::warn!("读取实验配置失败: {e}");
+            (providers, None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn experiment(percentage: u8) -> Experiment {
+        Experiment {
+            id: "cache".into(),
+            name: "cache".into(),
+            enabled: true,
+            app_type: "claude".into(),
+            provider_id: Some("p1".into()),
+            percentage,
+            treatment: ExperimentTreatment::PromptCaching {
+                min_chars: Some(100),
+            },
+        }
+    }
+
+    fn providers() -> Vec<Provider> {
+        ["p1", "p2"]
+            .into_iter()
+            .map(|id| {
+                let mut provider =
+                    Provider::with_id(id.into(), id.into(), json!({}), None::<String>);
+                provider.meta = Some(Default::default());
+                provider.meta.as_mut().unwrap().prompt_caching = Some(PromptCachingConfig {
+                    enabled: true,
+                    min_chars: None,
+                });
+                provider
+            })
+            .collect()
+    }
+
+    #[test]
+    fn assigns_sticky_arms_and_rewrites_target_provider() {
+        let treated = (0..200)
+            .filter(|i| in_treatment("cache", &format!("s{i}"), 30))
+            .count();
+        assert!((30..90).contains(&treated), "treated = {treated}");
+        assert_eq!(
+            in_treatment("cache", "s1", 30),
+            in_treatment("cache", "s1", 30)
+        );
+
+        let (chain, tag) = assign(&[experiment(100)], "claude", "s1", providers(), |_| None);
+        assert_eq!(tag.as_deref(), Some("cache:treatment"));
+        let caching = |p: &Provider| p.meta.as_ref().unwrap().prompt_caching.clone();
+        assert_eq!(caching(&chain[0]).unwrap().min_chars, Some(100));
+        assert_eq!(caching(&chain[1]).unwrap().min_chars, None);
+
+        let (chain, tag) = assign(&[experiment(0)], "claude", "s1", providers(), |_| None);
+        assert_eq!(tag.as_deref(), Some("cache:control"));
+        assert!(caching(&chain[0]).is_none());
+        assert!(caching(&chain[1]).is_some());
+
+        let (_, tag) = assign(&[experiment(100)], "codex", "s1", providers(), |_| None);
+        assert!(tag.is_none());
+    }
+
+    #[test]
+    fn traffic_split_routes_treatment_to_target_provider() {
+        let split = |percentage, target: &str| Experiment {
+            treatment: ExperimentTreatment::TrafficSplit {
+                provider_id: target.into(),
+            },
+            ..experiment(percentage)
+        };
+        let ids = |chain: &[Provider]| chain.iter().map(|p| p.id.clone()).collect::<Vec<_>>();
+
+        let (chain, tag) = assign(&[split(100, "p2")], "claude", "s1", providers(), |_| None);
+        assert_eq!(tag.as_deref(), Some("cache:treatment"));
+        assert_eq!(ids(&chain), vec!["p2", "p1"]);
+        // 流量切分不改写转换规则
+        assert!(chain[1].meta.as_ref().unwrap().prompt_caching.is_some());
+
+        let (chain, tag) = assign(&[split(0, "p2")], "claude", "s1", providers(), |_| None);
+        assert_eq!(tag.as_deref(), Some("cache:control"));
+        assert_eq!(ids(&chain), vec!["p1", "p2"]);
+
+        let loaded = |id: &str| {
+            Some(Provider::with_id(
+                id.into(),
+                id.into(),
+                json!({}),
+                None::<String>,
+            ))
+        };
+        let (chain, _) = assign(&[split(100, "p3")], "claude", "s1", providers(), loaded);
+        assert_eq!(ids(&chain), vec!["p3", "p1", "p2"]);
+
+        let (chain, tag) = assign(&[split(100, "p3")], "claude", "s1", providers(), |_| None);
+        assert!(tag.is_none());
+        assert_eq!(ids(&chain), vec!["p1", "p2"]);
+    }
+}
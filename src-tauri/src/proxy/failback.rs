@@ -0,0 +1,204 @@
+//! 自动回切
+//!
+//! 故障转移把某个应用的流量切离首选供应商后，记录首选供应商并随代理服务器启动后台任务：
+//! 按配置间隔对首选供应商发起轻量探测（复用流式检查，不重试），连续成功达到阈值后
+//! 重置其熔断器并切回，同时向前端发送 `provider-failback` 事件。
+//!
+//! 应用处于供应商评分的 auto 模式时不回切；用户在回切前手动切换了供应商时放弃回切；故障转移链继续切换时保留最初的首选供应商。
+//! 回切状态仅保存在内存中，代理重启后失效。
+
+use super::server::ProxyState;
+use crate::app_config::AppType;
+use crate::proxy::types::FailbackConfig;
+use crate::services::stream_check::{StreamCheckConfig, StreamCheckService};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+/// 调度器检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 等待回切的首选供应商
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailbackTarget {
+    pub app_type: String,
+    pub preferred_id: String,
+    pub preferred_name: String,
+    /// 故障转移后当前使用的供应商
+    pub fallback_id: String,
+    pub consecutive_successes: u32,
+}
+
+/// 记录一次故障转移切换
+///
+/// 首次切离时把切换前的供应商记为首选；已在等待回切时只更新当前供应商，
+/// 若转移回了首选供应商则不再需要回切。
+pub(crate) fn note_failover(
+    targets: &mut HashMap<String, FailbackTarget>,
+    app_type: &str,
+    previous: Option<(String, String)>,
+    new_provider_id: &str,
+) {
+    if let Some(target) = targets.get_mut(app_type) {
+        if target.preferred_id == new_provider_id {
+            targets.remove(app_type);
+        } else {
+            target.fallback_id = new_provider_id.to_string();
+            target.consecutive_successes = 0;
+        }
+        return;
+    }
+
+    if let Some((preferred_id, preferred_name)) = previous {
+        if preferred_id != new_provider_id {
+            targets.insert(
+                app_type.to_string(),
+                FailbackTarget {
+                    app_type: app_type.to_string(),
+                    preferred_id,
+                    preferred_name,
+                    fallback_id: new_provider_id.to_string(),
+                    consecutive_successes: 0,
+                },
+            );
+        }
+    }
+}
+
+/// 后台回切任务（配置在每次检查时重新读取）
+pub async fn run(state: ProxyState) {
+    let mut last_round: Option<Instant> = None;
+    loop {
+        let config = state.db.get_failback_config().unwrap_or_default();
+        let interval = Duration::from_secs(config.check_interval_seconds.max(10));
+        if config.enabled && last_round.is_none_or(|t| t.elapsed() >= interval) {
+            last_round = Some(Instant::now());
+            check_targets(&state, &config).await;
+        }
+        tokio::time::sleep(TICK_INTERVAL).await;
+    }
+}
+
+async fn check_targets(state: &ProxyState, config: &FailbackConfig) {
+    let targets = state.failover_manager.failback_targets().await;
+    if targets.is_empty() {
+        return;
+    }
+
+    // 探测只需确认首选供应商已恢复，不做重试
+    let check_config = StreamCheckConfig {
+        max_retries: 0,
+        ..state.db.get_stream_check_config().unwrap_or_default()
+    };
+    let manager = &state.failover_manager;
+    let scoring = state.db.get_provider_scoring_config().unwrap_or_default();
+
+    for target in targets {
+        let app = target.app_type.as_str();
+
+        // auto 模式下由评分决定路由，不回切
+        if scoring.is_auto(app) {
+            manager.clear_failback_target(app).await;
+            continue;
+        }
+
+        // 用户已手动切换供应商，放弃回切
+        let current = state.db.get_current_provider(app).ok().flatten();
+        if current.as_deref() != Some(target.fallback_id.as_str()) {
+            log::info!(
+                "[Failback] {app} 当前供应商已变更，放弃回切到 {}",
+                target.preferred_name
+            );
+            manager.clear_failback_target(app).await;
+            continue;
+        }
+
+        let (Ok(app_type), Ok(Some(provider))) = (
+            AppType::from_str(app),
+            state.db.get_provider_by_id(&target.preferred_id, app),
+        ) else {
+            manager.clear_failback_target(app).await;
+            continue;
+        };
+
+        let healthy =
+            match StreamCheckService::check_with_retry(&app_type, &provider, &check_config).await {
+                Ok(result) => result.success,
+                Err(e) => {
+                    log::debug!("[Failback] 探测 {} 失败: {e}", provider.name);
+                    false
+                }
+            };
+        let successes = manager.record_failback_probe(app, healthy).await;
+        log::debug!(
+            "[Failback] {app} 首选供应商 {} 探测{}（连续成功 {successes}/{}）",
+            provider.name,
+            if healthy { "成功" } else { "失败" },
+            config.required_successes
+        );
+        if successes < config.required_successes.max(1) {
+            continue;
+        }
+
+        // 重置熔断器，避免切回后首个请求因熔断打开而再次转移
+        state
+            .provider_router
+            .reset_provider_breaker(&provider.id, app)
+            .await;
+        match manager
+            .try_failback(state.app_handle.as_ref(), app, &provider.id, &provider.name)
+            .await
+        {
+            Ok(true) => {
+                log::info!("[Failback] {app} 已切回首选供应商 {}", provider.name);
+                if let Some(app_handle) = &state.app_handle {
+                    let event = serde_json::json!({
+                        "appType": app,
+                        "providerId": provider.id,
+                        "providerName": provider.name,
+                        "fromProviderId": target.fallback_id,
+                        "consecutiveSuccesses": successes,
+                    });
+                    if let Err(e) = app_handle.emit("provider-failback", event) {
+                        log::error!("[Failback] 发射事件失败: {e}");
+                    }
+                }
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("[Failback] 切回 {} 失败: {e}", provider.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prev(id: &str) -> Option<(String, String)> {
+        Some((id.to_string(), id.to_uppercase()))
+    }
+
+    #[test]
+    fn tracks_original_preferred_provider_across_failovers() {
+        let mut targets = HashMap::new();
+        note_failover(&mut targets, "claude", prev("p1"), "p2");
+        assert_eq!(targets["claude"].preferred_id, "p1");
+        assert_eq!(targets["claude"].fallback_id, "p2");
+
+        targets.get_mut("claude").unwrap().consecutive_successes = 2;
+        note_failover(&mut targets, "claude", prev("p2"), "p3");
+        assert_eq!(targets["claude"].preferred_id, "p1");
+        assert_eq!(targets["claude"].fallback_id, "p3");
+        assert_eq!(targets["claude"].consecutive_successes, 0);
+
+        note_failover(&mut targets, "claude", prev("p3"), "p1");
+        assert!(targets.is_empty());
+
+        note_failover(&mut targets, "codex", None, "p1");
+        note_failover(&mut targets, "codex", prev("p1"), "p1");
+        assert!(targets.is_empty());
+    }
+}
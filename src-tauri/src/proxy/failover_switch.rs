@@ -4,12 +4,16 @@
 //! - 去重控制（避免多个请求同时触发）
 //! - 数据库更新
 //! - 托盘菜单更新
-//! - 前端事件发射
+//! - 前端事件发射与 Webhook 推送
 //! - Live 备份更新
+//! - 记录切换前的首选供应商，供自动回切使用
 
+use super::failback::{self, FailbackTarget};
+use super::failure_alert;
+use super::{types::WebhookEvent, webhook};
 use crate::database::Database;
 use crate::error::AppError;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 use tauri::{Emitter, Manager};
@@ -22,6 +26,8 @@ use tokio::sync::RwLock;
 pub struct FailoverSwitchManager {
     /// 正在处理中的切换（key = "app_type:provider_id"）
     pending_switches: Arc<RwLock<HashSet<String>>>,
+    /// 等待自动回切的首选供应商（key = app_type）
+    failback_targets: Arc<RwLock<HashMap<String, FailbackTarget>>>,
     db: Arc<Database>,
 }
 
@@ -29,6 +35,7 @@ impl FailoverSwitchManager {
     pub fn new(db: Arc<Database>) -> Self {
         Self {
             pending_switches: Arc::new(RwLock::new(HashSet::new())),
+            failback_targets: Arc::new(RwLock::new(HashMap::new())),
             db,
         }
     }
@@ -47,6 +54,61 @@ impl FailoverSwitchManager {
         app_type: &str,
         provider_id: &str,
         provider_name: &str,
+    ) -> Result<bool, AppError> {
+        self.switch_with_source(app_handle, app_type, provider_id, provider_name, "failover")
+            .await
+    }
+
+    /// 尝试切回首选供应商（自动回切）
+    pub async fn try_failback(
+        &self,
+        app_handle: Option<&tauri::AppHandle>,
+        app_type: &str,
+        provider_id: &str,
+        provider_name: &str,
+    ) -> Result<bool, AppError> {
+        self.switch_with_source(app_handle, app_type, provider_id, provider_name, "failback")
+            .await
+    }
+
+    /// 当前等待回切的首选供应商
+    pub async fn failback_targets(&self) -> Vec<FailbackTarget> {
+        self.failback_targets
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// 放弃某个应用的回切
+    pub async fn clear_failback_target(&self, app_type: &str) {
+        self.failback_targets.write().await.remove(app_type);
+    }
+
+    /// 记录一次首选供应商探测结果，返回连续成功次数
+    pub async fn record_failback_probe(&self, app_type: &str, healthy: bool) -> u32 {
+        let mut targets = self.failback_targets.write().await;
+        match targets.get_mut(app_type) {
+            Some(target) if healthy => {
+                target.consecutive_successes += 1;
+                target.consecutive_successes
+            }
+            Some(target) => {
+                target.consecutive_successes = 0;
+                0
+            }
+            None => 0,
+        }
+    }
+
+    async fn switch_with_source(
+        &self,
+        app_handle: Option<&tauri::AppHandle>,
+        app_type: &str,
+        provider_id: &str,
+        provider_name: &str,
+        source: &'static str,
     ) -> Result<bool, AppError> {
         let switch_key = format!("{app_type}:{provider_id}");
 
@@ -62,7 +124,7 @@ impl FailoverSwitchManager {
 
         // 执行切换（确保最后清理 pending 标记）
         let result = self
-            .do_switch(app_handle, app_type, provider_id, provider_name)
+            .do_switch(app_handle, app_type, provider_id, provider_name, source)
             .await;
 
         // 清理 pending 标记
@@ -80,6 +142,7 @@ impl FailoverSwitchManager {
         app_type: &str,
         provider_id: &str,
         provider_name: &str,
+        source: &'static str,
     ) -> Result<bool, AppError> {
         // 检查该应用是否已被代理接管（enabled=true）
         // 只有被接管的应用才允许执行故障转移切换
@@ -98,16 +161,64 @@ impl FailoverSwitchManager {
 
         log::info!("[FO-001] 切换: {app_type} → {provider_name}");
 
+        let previous = self
+            .db
+            .get_current_provider(app_type)
+            .ok()
+            .flatten()
+            .map(|id| {
+                let name = self
+                    .db
+                    .get_provider_by_id(&id, app_type)
+                    .ok()
+                    .flatten()
+                    .map(|p| p.name)
+                    .unwrap_or_else(|| id.clone());
+                (id, name)
+            });
+
+        let previous_name = previous.as_ref().map(|(_, name)| name.clone());
+
         // 1. 更新数据库 is_current
         self.db.set_current_provider(app_type, provider_id)?;
 
+        // 记录首选供应商（回切完成后清除）
+        {
+            let mut targets = self.failback_targets.write().await;
+            if source == "failback" {
+                targets.remove(app_type);
+            } else {
+                failback::note_failover(&mut targets, app_type, previous, provider_id);
+            }
+        }
+
         // 2. 更新本地 settings（设备级）
         let app_type_enum = crate::app_config::AppType::from_str(app_type)
             .map_err(|_| AppError::Message(format!("无效的应用类型: {app_type}")))?;
         crate::settings::set_current_provider(&app_type_enum, Some(provider_id))?;
 
+        webhook::dispatch(
+            &self.db,
+            WebhookEvent::ProviderFailover,
+            serde_json::json!({
+                "appType": app_type,
+                "providerId": provider_id,
+                "source": source,
+            }),
+        );
+
         // 3. 更新托盘菜单和发射事件
         if let Some(app) = app_handle {
+            if source != "failback" {
+                failure_alert::notify_failover(
+                    app,
+                    &self.db,
+                    app_type,
+                    previous_name.as_deref(),
+                    provider_name,
+                );
+            }
+
             // 更新托盘菜单
             if let Some(app_state) = app.try_state::<crate::store::AppState>() {
                 // 更新 Live 备份（确保代理停止时恢复正确配置）
@@ -135,7 +246,7 @@ impl FailoverSwitchManager {
             let event_data = serde_json::json!({
                 "appType": app_type,
                 "providerId": provider_id,
-                "source": "failover"  // 标识来源是故障转移
+                "source": source  // 标识来源：failover（故障转移）/ failback（自动回切）
             });
             if let Err(e) = app.emit("provider-switched", event_data) {
                 log::error!("[Failover] 发射事件失败: {e}");
@@ -0,0 +1,151 @@
+//! 供应商故障桌面通知
+//!
+//! 长时间运行的 Agent 任务中，供应商反复失败或发生故障转移时往往无人察觉。
+//! 满足以下条件时发送系统桌面通知（macOS / Windows 上点击通知即可打开应用）：
+//! - 同一供应商在 `window_minutes` 分钟内失败达到 `failure_threshold` 次
+//!   （通知后重新计数，避免持续故障时刷屏）
+//! - 故障转移切换了当前供应商（`notify_on_failover`）
+//!
+//! 配置存储在 settings 表（`failure_notification_config`）中。
+
+use super::types::FailureNotificationConfig;
+use crate::database::Database;
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+use tauri_plugin_notification::NotificationExt;
+
+/// 各供应商最近的失败时间（key = "app_type:provider_id"）
+static FAILURES: Lazy<Mutex<HashMap<String, VecDeque<Instant>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 记录一次失败，达到阈值时返回窗口内的失败次数并重新计数
+fn note_failure(
+    failures: &mut HashMap<String, VecDeque<Instant>>,
+    key: String,
+    now: Instant,
+    config: &FailureNotificationConfig,
+) -> Option<usize> {
+    let window = Duration::from_secs(u64::from(config.window_minutes.max(1)) * 60);
+    let times = failures.entry(key).or_default();
+    while times
+        .front()
+        .is_some_and(|t| now.duration_since(*t) > window)
+    {
+        times.pop_front();
+    }
+    times.push_back(now);
+    let count = times.len();
+    if count < config.failure_threshold.max(1) as usize {
+        return None;
+    }
+    times.clear();
+    Some(count)
+}
+
+fn load_config(db: &Database) -> Option<FailureNotificationConfig> {
+    match db.get_failure_notification_config() {
+        Ok(config) if config.enabled => Some(config),
+        Ok(_) => None,
+        Err(e) => {
+            log::debug!("读取故障通知配置失败: {e}");
+            None
+        }
+    }
+}
+
+/// 发送桌面通知（失败只记录日志）
+pub(crate) fn show_notification(app: &tauri::AppHandle, title: &str, body: &str) {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("发送桌面通知失败: {e}");
+    }
+}
+
+/// 记录供应商请求失败，短时间内失败过多时发送通知
+pub fn record_failure(
+    app: Option<&tauri::AppHandle>,
+    app_type: &str,
+    provider_id: &str,
+    provider_name: &str,
+) {
+    let Some(app) = app else {
+        return;
+    };
+    let Some(state) = app.try_state::<crate::store::AppState>() else {
+        return;
+    };
+    let Some(config) = load_config(&state.db) else {
+        return;
+    };
+    let count = match FAILURES.lock() {
+        Ok(mut failures) => note_failure(
+            &mut failures,
+            format!("{app_type}:{provider_id}"),
+            Instant::now(),
+            &config,
+        ),
+        Err(_) => None,
+    };
+    if let Some(count) = count {
+        show_notification(
+            app,
+            &format!("{provider_name} 连续失败"),
+            &format!(
+                "[{app_type}] {provider_name} 在 {} 分钟内失败 {count} 次",
+                config.window_minutes.max(1)
+            ),
+        );
+    }
+}
+
+/// 故障转移切换了当前供应商时发送通知
+pub fn notify_failover(
+    app: &tauri::AppHandle,
+    db: &Database,
+    app_type: &str,
+    from: Option<&str>,
+    to: &str,
+) {
+    let Some(config) = load_config(db) else {
+        return;
+    };
+    if !config.notify_on_failover {
+        return;
+    }
+    let body = match from {
+        Some(from) => format!("[{app_type}] {from} 不可用，已切换到 {to}"),
+        None => format!("[{app_type}] 已切换到 {to}"),
+    };
+    show_notification(app, "已故障转移", &body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifies_once_per_threshold_within_window() {
+        let config = FailureNotificationConfig {
+            failure_threshold: 3,
+            window_minutes: 5,
+            ..Default::default()
+        };
+        let mut failures = HashMap::new();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+        let key = || "claude:p1".to_string();
+
+        assert_eq!(note_failure(&mut failures, key(), at(0), &config), None);
+        // 超出窗口的失败不计入
+        assert_eq!(note_failure(&mut failures, key(), at(400), &config), None);
+        assert_eq!(note_failure(&mut failures, key(), at(410), &config), None);
+        assert_eq!(
+            note_failure(&mut failures, key(), at(420), &config),
+            Some(3)
+        );
+        // 通知后重新计数
+        assert_eq!(note_failure(&mut failures, key(), at(430), &config), None);
+    }
+}
@@ -0,0 +1,266 @@
+//! 故障注入（混沌测试）
+//!
+//! 开启后代理按配置的概率在转发时注入 429、500、流式响应截断与额外延迟，
+//! 用于验证客户端以及 cc-switch 自身的重试、熔断与故障转移在真实故障模式下的表现。
+//!
+//! 与 rate limit 模拟一样只保存在内存中，重启应用后失效。注入在每次上游转发尝试时独立判定：
+//! 429 / 500 不请求上游，直接以 `UpstreamError` 返回；截断仅作用于成功的流式响应，
+//! 在转发指定字节数后直接结束响应流（模拟上游中途断开）。
+
+use super::ProxyError;
+use futures::StreamExt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 注入错误的提示信息
+const INJECTED_MESSAGE: &str = "Injected by cc-switch fault injection";
+
+static FAULT_INJECTION: Mutex<Option<FaultInjectionConfig>> = Mutex::new(None);
+
+/// 故障注入配置（概率取值 0-1）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FaultInjectionConfig {
+    /// 仅对指定应用生效（claude / codex / gemini），为空时对全部应用生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_type: Option<String>,
+    /// 仅对指定供应商生效（便于验证故障转移），为空时对全部供应商生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// 返回 429 的概率
+    #[serde(default)]
+    pub rate_limit_rate: f64,
+    /// 返回 500 的概率
+    #[serde(default)]
+    pub server_error_rate: f64,
+    /// 截断流式响应的概率
+    #[serde(default)]
+    pub truncate_rate: f64,
+    /// 截断前转发的字节数
+    #[serde(default = "default_truncate_after_bytes")]
+    pub truncate_after_bytes: usize,
+    /// 每次转发前的额外延迟（毫秒）
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// 额外延迟的随机抖动上限（毫秒）
+    #[serde(default)]
+    pub latency_jitter_ms: u64,
+}
+
+fn default_truncate_after_bytes() -> usize {
+    512
+}
+
+impl Default for FaultInjectionConfig {
+    fn default() -> Self {
+        Self {
+            app_type: None,
+            provider_id: None,
+            rate_limit_rate: 0.0,
+            server_error_rate: 0.0,
+            truncate_rate: 0.0,
+            truncate_after_bytes: default_truncate_after_bytes(),
+            latency_ms: 0,
+            latency_jitter_ms: 0,
+        }
+    }
+}
+
+/// 注入的故障
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// 直接返回指定状态码
+    Status(u16),
+    /// 转发指定字节数后截断流式响应
+    Truncate(usize),
+}
+
+/// 单次转发尝试的注入计划
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultPlan {
+    pub delay: Option<Duration>,
+    pub fault: Option<Fault>,
+}
+
+/// 开启故障注入（覆盖已有配置）
+pub fn start(config: FaultInjectionConfig) {
+    if let Ok(mut current) = FAULT_INJECTION.lock() {
+        log::warn!("[FAULT-INJECTION] 开启故障注入: {config:?}");
+        *current = Some(config);
+    }
+}
+
+/// 关闭故障注入
+pub fn stop() {
+    if let Ok(mut current) = FAULT_INJECTION.lock() {
+        if current.take().is_some() {
+            log::info!("[FAULT-INJECTION] 已关闭故障注入");
+        }
+    }
+}
+
+/// 当前故障注入配置
+pub fn current() -> Option<FaultInjectionConfig> {
+    FAULT_INJECTION
+        .lock()
+        .ok()
+        .and_then(|current| current.clone())
+}
+
+/// 按配置与随机数 `roll`（0-1）决定注入计划
+fn plan_with(config: &FaultInjectionConfig, roll: f64, jitter: u64) -> FaultPlan {
+    let delay_ms = config.latency_ms + jitter;
+    let rate_limit = config.rate_limit_rate.clamp(0.0, 1.0);
+    let server_error = rate_limit + config.server_error_rate.clamp(0.0, 1.0);
+    let truncate = server_error + config.truncate_rate.clamp(0.0, 1.0);
+
+    let fault = if roll < rate_limit {
+        Some(Fault::Status(429))
+    } else if roll < server_error {
+        Some(Fault::Status(500))
+    } else if roll < truncate {
+        Some(Fault::Truncate(config.truncate_after_bytes))
+    } else {
+        None
+    };
+    FaultPlan {
+        delay: (delay_ms > 0).then(|| Duration::from_millis(delay_ms)),
+        fault,
+    }
+}
+
+/// 为一次转发尝试生成注入计划（未开启或不匹配时返回 None）
+pub fn plan(app_type: &str, provider_id: &str) -> Option<FaultPlan> {
+    let config = current()?;
+    if config
+        .app_type
+        .as_deref()
+        .is_some_and(|app| !app.eq_ignore_ascii_case(app_type))
+        || config
+            .provider_id
+            .as_deref()
+            .is_some_and(|id| id != provider_id)
+    {
+        return None;
+    }
+
+    let mut rng = rand::thread_rng();
+    let jitter = if config.latency_jitter_ms > 0 {
+        rng.gen_range(0..=config.latency_jitter_ms)
+    } else {
+        0
+    };
+    let plan = plan_with(&config, rng.gen::<f64>(), jitter);
+    (plan != FaultPlan::default()).then_some(plan)
+}
+
+/// 构造注入的上游错误（与真实上游错误走相同的重试、熔断与故障转移路径）
+pub fn error(status: u16, app_type: &str) -> ProxyError {
+    let body = match (app_type.to_ascii_lowercase().as_str(), status) {
+        ("claude", 429) => json!({
+            "type": "error",
+            "error": { "type": "rate_limit_error", "message": format!("Rate limit exceeded ({INJECTED_MESSAGE})") }
+        }),
+        ("claude", _) => json!({
+            "type": "error",
+            "error": { "type": "api_error", "message": INJECTED_MESSAGE }
+        }),
+        ("gemini", _) => json!({
+            "error": { "code": status, "message": INJECTED_MESSAGE }
+        }),
+        (_, 429) => json!({
+            "error": {
+                "message": format!("Rate limit exceeded ({INJECTED_MESSAGE})"),
+                "type": "rate_limit_error",
+                "code": "rate_limit_exceeded"
+            }
+        }),
+        _ => json!({
+            "error": { "message": INJECTED_MESSAGE, "type": "server_error" }
+        }),
+    };
+    ProxyError::UpstreamError {
+        status,
+        body: Some(body.to_string()),
+    }
+}
+
+/// 截断流式响应：转发 `limit` 字节后结束响应流（未指定或非流式响应原样返回）
+pub fn truncate(response: reqwest::Response, limit: Option<usize>) -> reqwest::Response {
+    let Some(limit) = limit else {
+        return response;
+    };
+    if !super::response_processor::is_sse_response(&response) {
+        return response;
+    }
+    log::warn!("[FAULT-INJECTION] 流式响应将在 {limit} 字节后截断");
+
+    let status = response.status();
+    let mut headers = response.headers().clone();
+    headers.remove(reqwest::header::CONTENT_LENGTH);
+    let extensions = response.extensions().clone();
+    let stream = response.bytes_stream().scan(limit, |remaining, chunk| {
+        let item = match chunk {
+            Ok(_) if *remaining == 0 => None,
+            Ok(bytes) => {
+                let len = bytes.len().min(*remaining);
+                *remaining -= len;
+                Some(Ok(bytes.slice(..len)))
+            }
+            Err(e) => Some(Err(e)),
+        };
+        futures::future::ready(item)
+    });
+
+    let mut builder = axum::http::Response::builder().status(status);
+    if let Some(target) = builder.headers_mut() {
+        *target = headers;
+    }
+    match builder.body(reqwest::Body::wrap_stream(stream)) {
+        Ok(truncated) => {
+            let mut truncated = reqwest::Response::from(truncated);
+            *truncated.extensions_mut() = extensions;
+            truncated
+        }
+        Err(e) => {
+            log::error!("[FAULT-INJECTION] 构造截断响应失败: {e}");
+            reqwest::Response::from(axum::http::Response::new(reqwest::Body::from("")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_faults_by_cumulative_rate() {
+        let config = FaultInjectionConfig {
+            rate_limit_rate: 0.2,
+            server_error_rate: 0.2,
+            truncate_rate: 0.2,
+            truncate_after_bytes: 64,
+            latency_ms: 100,
+            ..Default::default()
+        };
+        assert_eq!(plan_with(&config, 0.1, 0).fault, Some(Fault::Status(429)));
+        assert_eq!(plan_with(&config, 0.3, 0).fault, Some(Fault::Status(500)));
+        assert_eq!(plan_with(&config, 0.5, 0).fault, Some(Fault::Truncate(64)));
+        let plan = plan_with(&config, 0.9, 20);
+        assert_eq!(plan.fault, None);
+        assert_eq!(plan.delay, Some(Duration::from_millis(120)));
+
+        match error(429, "Claude") {
+            ProxyError::UpstreamError { status, body } => {
+                assert_eq!(status, 429);
+                assert!(super::super::rate_limit_retry::is_rate_limit_error(
+                    &body.unwrap()
+                ));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
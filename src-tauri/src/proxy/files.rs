@@ -0,0 +1,23 @@
+//! Files API 透传
+//!
+//! `/v1/files`（上传、列表、查询元数据、下载内容、删除）经 [`passthrough`] 原样转发到当前 Claude 供应商。
+//! 上传使用 multipart 表单，请求体与 `content-type`（含 boundary）按原始字节流式转发，不做解析、
+//! 不在本地缓冲；不受本地请求体大小限制约束，单个文件大小由上游校验。
+
+use super::{passthrough, server::ProxyState, ProxyError};
+use axum::{
+    extract::{Request, State},
+    response::Response,
+};
+
+/// 处理 /v1/files 及其子路径的请求
+pub async fn handle_files(
+    State(state): State<ProxyState>,
+    request: Request,
+) -> Result<Response, ProxyError> {
+    let (parts, body) = request.into_parts();
+    let endpoint = passthrough::upstream_endpoint(&parts.uri);
+    let provider = passthrough::current_provider(&state).await?;
+    let response = passthrough::send("Files", &provider, &endpoint, &parts, body).await?;
+    passthrough::stream_response(response)
+}
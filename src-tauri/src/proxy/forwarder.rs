@@ -3,18 +3,35 @@
 //! 负责将请求转发到上游Provider，支持故障转移
 
 use super::{
-    body_filter::filter_private_params_with_whitelist,
+    activity::{ActivityFeed, ActivityKind},
+    anthropic_version, auth_scheme,
+    body_filter::{filter_private_params_with_whitelist, strip_denied_fields},
+    content_encoding,
+    context_window::{check_context_window, ContextCheck},
     debug_log::{self, LogRequestId},
     error::*,
     failover_switch::FailoverSwitchManager,
+    failure_alert,
+    fault_injection::{self, Fault},
+    header_filter::HeaderFilter,
+    header_rules, key_pool,
+    log_redaction::redact_for_log,
+    max_tokens, mock_upstream,
+    otel::{RequestTrace, SpanKind},
+    prompt_cache,
     provider_router::ProviderRouter,
     providers::{get_adapter, ProviderAdapter, ProviderType},
     rate_limit_retry::{detect_rate_limit_in_sse, RetryConfig, RetryState},
+    rate_limit_sim, sampling, system_prompt,
     thinking_rectifier::{rectify_anthropic_request, should_rectify_thinking_signature},
-    types::{ProxyStatus, RectifierConfig},
+    types::{DebugLogConfig, LogRedactionConfig, ProxyStatus, RectifierConfig},
     ProxyError,
 };
-use crate::{app_config::AppType, provider::Provider};
+use crate::{
+    app_config::AppType,
+    provider::{Provider, ProviderTimeouts},
+};
+use bytes::Bytes;
 use reqwest::Response;
 use serde_json::Value;
 use std::fs::OpenOptions;
@@ -23,58 +40,6 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-/// Headers 黑名单 - 不透传到上游的 Headers
-///
-/// 精简版黑名单，只过滤必须覆盖或可能导致问题的 header
-/// 参考成功透传的请求，保留更多原始 header
-///
-/// 注意：客户端 IP 类（x-forwarded-for, x-real-ip）默认透传
-const HEADER_BLACKLIST: &[&str] = &[
-    // 认证类（会被覆盖）
-    "authorization",
-    "x-api-key",
-    // 连接类（由 HTTP 客户端管理）
-    "host",
-    "content-length",
-    "transfer-encoding",
-    // 编码类（会被覆盖为 identity）
-    "accept-encoding",
-    // 代理转发类（保留 x-forwarded-for 和 x-real-ip）
-    "x-forwarded-host",
-    "x-forwarded-port",
-    "x-forwarded-proto",
-    "forwarded",
-    // CDN/云服务商特定头
-    "cf-connecting-ip",
-    "cf-ipcountry",
-    "cf-ray",
-    "cf-visitor",
-    "true-client-ip",
-    "fastly-client-ip",
-    "x-azure-clientip",
-    "x-azure-fdid",
-    "x-azure-ref",
-    "akamai-origin-hop",
-    "x-akamai-config-log-detail",
-    // 请求追踪类
-    "x-request-id",
-    "x-correlation-id",
-    "x-trace-id",
-    "x-amzn-trace-id",
-    "x-b3-traceid",
-    "x-b3-spanid",
-    "x-b3-parentspanid",
-    "x-b3-sampled",
-    "traceparent",
-    "tracestate",
-    // anthropic 特定头单独处理，避免重复
-    "anthropic-beta",
-    "anthropic-version",
-    // 客户端 IP 单独处理（默认透传）
-    "x-forwarded-for",
-    "x-real-ip",
-];
-
 pub struct ForwardResult {
     pub response: Response,
     pub provider: Provider,
@@ -98,10 +63,20 @@ pub struct RequestForwarder {
     current_provider_id_at_start: String,
     /// 整流器配置
     rectifier_config: RectifierConfig,
+    /// 请求日志脱敏配置
+    log_redaction: LogRedactionConfig,
+    /// 调试日志配置
+    debug_log: DebugLogConfig,
+    /// 链路追踪
+    trace: Option<Arc<RequestTrace>>,
     /// Rate limit 重试配置
     retry_config: RetryConfig,
     /// 非流式请求超时（秒）
     non_streaming_timeout: std::time::Duration,
+    /// 客户端原始请求体（请求体未被改写时直接转发，避免重新序列化）
+    raw_body: Option<Bytes>,
+    /// 实时活动事件发射器
+    activity: Option<ActivityFeed>,
 }
 
 impl RequestForwarder {
@@ -117,6 +92,9 @@ impl RequestForwarder {
         _streaming_first_byte_timeout: u64,
         _streaming_idle_timeout: u64,
         rectifier_config: RectifierConfig,
+        log_redaction: LogRedactionConfig,
+        debug_log: DebugLogConfig,
+        trace: Option<Arc<RequestTrace>>,
         retry_config: Option<RetryConfig>,
     ) -> Self {
         Self {
@@ -127,8 +105,31 @@ impl RequestForwarder {
             app_handle,
             current_provider_id_at_start,
             rectifier_config,
+            log_redaction,
+            debug_log,
+            trace,
             retry_config: retry_config.unwrap_or_default(),
             non_streaming_timeout: std::time::Duration::from_secs(non_streaming_timeout),
+            raw_body: None,
+            activity: None,
+        }
+    }
+
+    /// 设置客户端原始请求体（须与传入 `forward_with_retry` 的 `body` 对应）
+    pub fn with_raw_body(mut self, raw_body: Bytes) -> Self {
+        self.raw_body = Some(raw_body);
+        self
+    }
+
+    /// 设置实时活动事件发射器
+    pub fn with_activity(mut self, activity: ActivityFeed) -> Self {
+        self.activity = Some(activity);
+        self
+    }
+
+    fn emit_activity(&self, kind: ActivityKind) {
+        if let Some(activity) = &self.activity {
+            activity.emit(kind);
         }
     }
 
@@ -159,6 +160,12 @@ impl RequestForwarder {
             });
         }
 
+        self.emit_activity(ActivityKind::Started {
+            provider_id: providers[0].id.clone(),
+            provider_name: providers[0].name.clone(),
+            chain_length: providers.len(),
+        });
+
         let mut last_error = None;
         let mut last_provider = None;
         let mut attempted_providers = 0usize;
@@ -171,6 +178,17 @@ impl RequestForwarder {
 
         // 依次尝试每个供应商
         for provider in providers.iter() {
+            // 上下文窗口检查：超限时按供应商配置截断，或跳过该供应商
+            let mut truncated_body = match check_context_window(&body, provider, endpoint) {
+                ContextCheck::Fits => None,
+                ContextCheck::Truncated(truncated) => Some(truncated),
+                ContextCheck::Exceeded(err) => {
+                    last_error = Some(err);
+                    last_provider = Some(provider.clone());
+                    continue;
+                }
+            };
+
             // 发起请求前先获取熔断器放行许可（HalfOpen 会占用探测名额）
             // 单 Provider 场景下跳过此检查，避免熔断器阻塞所有请求
             let (allowed, used_half_open_permit) = if bypass_circuit_breaker {
@@ -200,7 +218,14 @@ impl RequestForwarder {
 
             // 转发请求（每个 Provider 只尝试一次，重试由客户端控制）
             match self
-                .forward_with_rate_limit_retry(provider, endpoint, &body, &headers, adapter.as_ref())
+                .forward_with_rate_limit_retry(
+                    provider,
+                    endpoint,
+                    truncated_body.as_ref().unwrap_or(&body),
+                    self.raw_body.as_ref().filter(|_| truncated_body.is_none()),
+                    &headers,
+                    adapter.as_ref(),
+                )
                 .await
             {
                 Ok(response) => {
@@ -300,6 +325,9 @@ impl RequestForwarder {
 
                             // 首次触发：整流请求体
                             let rectified = rectify_anthropic_request(&mut body);
+                            if let Some(truncated) = truncated_body.as_mut() {
+                                rectify_anthropic_request(truncated);
+                            }
 
                             // 整流未生效：直接返回错误（不可重试客户端错误）
                             if !rectified.applied {
@@ -341,7 +369,15 @@ impl RequestForwarder {
 
                             // 使用同一供应商重试（不计入熔断器）
                             match self
-                                .forward_with_rate_limit_retry(provider, endpoint, &body, &headers, adapter.as_ref())
+                                .forward_with_rate_limit_retry(
+                                    provider,
+                                    endpoint,
+                                    truncated_body.as_ref().unwrap_or(&body),
+                                    // 整流后的请求体与原始字节不再一致
+                                    None,
+                                    &headers,
+                                    adapter.as_ref(),
+                                )
                                 .await
                             {
                                 Ok(response) => {
@@ -431,6 +467,12 @@ impl RequestForwarder {
                                                 Some(retry_err.to_string()),
                                             )
                                             .await;
+                                        failure_alert::record_failure(
+                                            self.app_handle.as_ref(),
+                                            app_type_str,
+                                            &provider.id,
+                                            &provider.name,
+                                        );
                                     } else {
                                         // 客户端问题：仅释放 permit，不记录熔断器
                                         self.router
@@ -470,6 +512,12 @@ impl RequestForwarder {
                             Some(e.to_string()),
                         )
                         .await;
+                    failure_alert::record_failure(
+                        self.app_handle.as_ref(),
+                        app_type_str,
+                        &provider.id,
+                        &provider.name,
+                    );
 
                     // 分类错误
                     let category = self.categorize_proxy_error(&e);
@@ -490,6 +538,13 @@ impl RequestForwarder {
                                 attempted_providers,
                                 providers.len()
                             );
+                            self.emit_activity(ActivityKind::Retry {
+                                provider_id: provider.id.clone(),
+                                provider_name: provider.name.clone(),
+                                attempt: attempted_providers as u32,
+                                failover: true,
+                                reason: e.to_string(),
+                            });
 
                             last_error = Some(e);
                             last_provider = Some(provider.clone());
@@ -529,6 +584,13 @@ impl RequestForwarder {
                         (status.success_requests as f32 / status.total_requests as f32) * 100.0;
                 }
             }
+            // 全部因上下文窗口超限被跳过时，返回超限错误
+            if let Some(err @ ProxyError::ContextWindowExceeded { .. }) = last_error {
+                return Err(ForwardError {
+                    error: err,
+                    provider: last_provider,
+                });
+            }
             return Err(ForwardError {
                 error: ProxyError::NoAvailableProvider,
                 provider: None,
@@ -560,9 +622,40 @@ impl RequestForwarder {
         provider: &Provider,
         endpoint: &str,
         body: &Value,
+        raw_body: Option<&Bytes>,
         headers: &axum::http::HeaderMap,
         adapter: &dyn ProviderAdapter,
     ) -> Result<Response, ProxyError> {
+        // 本地 rate limit 模拟：不请求上游，直接返回模拟的限流结果
+        if let Some(mode) = rate_limit_sim::take(adapter.name()) {
+            log::warn!(
+                "[RATE-LIMIT-SIM] 模拟 {} 的 rate limit 响应 ({mode:?})",
+                provider.name
+            );
+            let streaming = body.get("stream").and_then(|s| s.as_bool()) == Some(true)
+                || endpoint.contains("streamGenerateContent");
+            return rate_limit_sim::simulate(mode, adapter.name(), streaming);
+        }
+
+        // 故障注入：额外延迟、直接返回 429/500，或截断本次流式响应
+        let fault_plan = fault_injection::plan(adapter.name(), &provider.id).unwrap_or_default();
+        if let Some(delay) = fault_plan.delay {
+            tokio::time::sleep(delay).await;
+        }
+        let truncate_after = match fault_plan.fault {
+            Some(Fault::Status(status)) => {
+                log::warn!("[FAULT-INJECTION] 向 {} 注入 HTTP {status}", provider.name);
+                return Err(fault_injection::error(status, adapter.name()));
+            }
+            Some(Fault::Truncate(limit)) => Some(limit),
+            None => None,
+        };
+
+        let transform_span = self
+            .trace
+            .as_ref()
+            .map(|t| t.span("proxy.transform", SpanKind::Internal));
+
         // 使用适配器提取 base_url
         let base_url = adapter.extract_base_url(provider)?;
 
@@ -576,6 +669,12 @@ impl RequestForwarder {
                 endpoint
             };
 
+        // 模拟上游（Mock 供应商或离线演练模式）：不请求上游，按请求格式合成响应
+        if mock_upstream::should_mock(provider) {
+            let response = mock_upstream::respond(provider, effective_endpoint, body).await?;
+            return Ok(fault_injection::truncate(response, truncate_after));
+        }
+
         // 使用适配器构建 URL
         let url = adapter.build_url(&base_url, effective_endpoint);
 
@@ -584,43 +683,80 @@ impl RequestForwarder {
             super::model_mapper::apply_model_mapping(body.clone(), provider);
 
         // 转换请求体（如果需要）
-        let request_body = if needs_transform {
+        let mut request_body = if needs_transform {
             adapter.transform_request(mapped_body, provider)?
         } else {
             mapped_body
         };
 
+        // 注入供应商配置的系统提示词前缀/后缀（在格式转换之后，按最终格式处理）
+        system_prompt::apply_system_prompt_injection(
+            &mut request_body,
+            provider,
+            effective_endpoint,
+        );
+
+        // 按供应商上限截断输出长度字段，避免上游直接 400
+        max_tokens::apply_max_tokens_limit(&mut request_body, provider, effective_endpoint);
+
+        // 应用供应商采样参数覆盖（temperature / top_p / top_k）
+        sampling::apply_sampling_overrides(&mut request_body, provider, effective_endpoint);
+
+        // 自动注入 Prompt Caching 断点（仅 Anthropic Messages 格式）
+        let prompt_cache_injected = adapter.name() == "Claude"
+            && !needs_transform
+            && prompt_cache::apply_prompt_caching(&mut request_body, provider);
+
+        // 移除供应商配置的不支持字段，避免上游直接 400
+        if let Some(meta) = provider.meta.as_ref() {
+            strip_denied_fields(&mut request_body, &meta.field_denylist);
+        }
+
         // 过滤私有参数（以 `_` 开头的字段），防止内部信息泄露到上游
         // 默认使用空白名单，过滤所有 _ 前缀字段
         let filtered_body = filter_private_params_with_whitelist(request_body, &[]);
+        drop(transform_span);
 
-        // 生成请求 ID 并记录日志
+        // 生成请求 ID 并记录日志（摘要级别下仅对指定供应商或携带调试请求头的请求记录完整内容）
         let request_id = Uuid::new_v4().to_string();
-        debug_log::log_request(
-            &request_id,
-            &provider.name,
-            &url,
-            &filtered_body,
-            headers,
-        );
+        let verbose_log = debug_log::is_verbose(&self.debug_log, provider, headers);
+        let log_bodies = verbose_log && debug_log::logs_bodies(&self.debug_log, provider);
+        let mut upstream_span = self.trace.as_ref().map(|t| {
+            let mut span = t.span("proxy.upstream", SpanKind::Client);
+            span.set_attribute("ccswitch.request_id", request_id.clone());
+            span.set_attribute("ccswitch.provider_id", provider.id.clone());
+            span.set_attribute("ccswitch.provider_name", provider.name.clone());
+            span.set_attribute("url.full", url.clone());
+            span
+        });
+        if verbose_log {
+            debug_log::log_request(
+                &request_id,
+                &provider.name,
+                &url,
+                log_bodies
+                    .then(|| redact_for_log(&filtered_body, &self.log_redaction))
+                    .as_deref(),
+                headers,
+            );
+        } else {
+            debug_log::log_request_summary(&request_id, &provider.name, &url);
+        }
 
-        // 每次请求时获取最新的全局 HTTP 客户端（支持热更新代理配置）
-        let client = super::http_client::get();
+        // 每次请求时获取最新的 HTTP 客户端（支持热更新代理配置，供应商可指定专用代理）
+        let client = super::http_client::get_for_provider(provider)
+            .map_err(ProxyError::ConfigError)?;
         let mut request = client.post(&url);
 
-        // 只有当 timeout > 0 时才设置请求超时
-        // Duration::ZERO 在 reqwest 中表示"立刻超时"而不是"禁用超时"
-        // 故障转移关闭时会传入 0，此时应该使用 client 的默认超时（600秒）
-        if !self.non_streaming_timeout.is_zero() {
-            request = request.timeout(self.non_streaming_timeout);
+        if let Some(timeout) = total_timeout(provider, self.non_streaming_timeout) {
+            request = request.timeout(timeout);
         }
 
-        // 过滤黑名单 Headers，保护隐私并避免冲突
+        // 按供应商的透传策略过滤客户端 Headers，保护隐私并避免冲突
+        // 客户端 IP 类（x-forwarded-for, x-real-ip）在默认黑名单模式下同样透传
+        let header_filter = HeaderFilter::from_provider(provider);
         for (key, value) in headers {
-            if HEADER_BLACKLIST
-                .iter()
-                .any(|h| key.as_str().eq_ignore_ascii_case(h))
-            {
+            if !header_filter.should_forward(key.as_str()) {
                 continue;
             }
             request = request.header(key, value);
@@ -647,74 +783,118 @@ impl RequestForwarder {
                 // 如果客户端没有发送，使用默认值
                 CLAUDE_CODE_BETA.to_string()
             };
+            let beta_value = if prompt_cache_injected {
+                prompt_cache::append_beta_flag(&beta_value)
+            } else {
+                beta_value
+            };
             request = request.header("anthropic-beta", &beta_value);
         }
 
-        // 客户端 IP 透传（默认开启）
-        if let Some(xff) = headers.get("x-forwarded-for") {
-            if let Ok(xff_str) = xff.to_str() {
-                request = request.header("x-forwarded-for", xff_str);
-            }
-        }
-        if let Some(real_ip) = headers.get("x-real-ip") {
-            if let Ok(real_ip_str) = real_ip.to_str() {
-                request = request.header("x-real-ip", real_ip_str);
-            }
-        }
-
         // 禁用压缩，避免 gzip 流式响应解析错误
         // 参考 CCH: undici 在连接提前关闭时会对不完整的 gzip 流抛出错误
         request = request.header("accept-encoding", "identity");
 
-        // 使用适配器添加认证头
-        if let Some(auth) = adapter.extract_auth(provider) {
-            request = adapter.add_auth_headers(request, &auth);
+        // 使用适配器添加认证头（配置了自定义认证方式时改为在构建请求后处理）
+        let mut auth = adapter.extract_auth(provider);
+        key_pool::override_auth(provider, &mut auth);
+        let auth_scheme = auth_scheme::get_auth_scheme(provider);
+        if let (Some(auth), None) = (&auth, auth_scheme) {
+            request = adapter.add_auth_headers(request, auth);
         }
 
-        // anthropic-version 统一处理（仅 Claude）：优先使用客户端的版本号，否则使用默认值
+        // anthropic-version 统一处理（仅 Claude）：供应商固定版本 > 客户端版本 > 默认值
         // 注意：只设置一次，避免重复
         if adapter.name() == "Claude" {
-            let version_str = headers
-                .get("anthropic-version")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("2023-06-01");
-            request = request.header("anthropic-version", version_str);
+            let version = anthropic_version::resolve_version(
+                headers
+                    .get("anthropic-version")
+                    .and_then(|v| v.to_str().ok()),
+                anthropic_version::get_pinned_version(provider),
+            );
+            request = request.header("anthropic-version", version.upstream);
+        }
+
+        // 构建请求，应用供应商配置的请求头改写规则（需在认证头之后，以便移除/替换认证头）
+        // 请求体未被任何规则改写时直接发送客户端原始字节，避免重新序列化大请求体
+        let request = match raw_body.filter(|_| filtered_body == *body) {
+            Some(raw_body) => request.body(raw_body.clone()),
+            None => request.json(&filtered_body),
+        };
+        let mut request = request.build().map_err(|e| {
+            debug_log::log_network_error(&request_id, &e.to_string());
+            ProxyError::ForwardFailed(format!("构建请求失败: {e}"))
+        })?;
+        request
+            .headers_mut()
+            .entry(reqwest::header::CONTENT_TYPE)
+            .or_insert(reqwest::header::HeaderValue::from_static("application/json"));
+        if let (Some(auth), Some(scheme)) = (&auth, auth_scheme) {
+            auth_scheme::apply_auth_scheme(&mut request, scheme, &auth.api_key)?;
+        }
+        if let Some(rules) = header_rules::get_header_rules(provider) {
+            header_rules::apply_header_rules(
+                request.headers_mut(),
+                rules,
+                auth.as_ref().map(|a| a.api_key.as_str()),
+            );
         }
 
         // 发送请求
-        let mut response = request.json(&filtered_body).send().await.map_err(|e| {
-            let error_msg = if e.is_timeout() {
-                format!("请求超时: {e}")
-            } else if e.is_connect() {
-                format!("连接失败: {e}")
-            } else {
-                e.to_string()
-            };
+        let response = client.execute(request).await.map_err(|e| {
+            let is_timeout = e.is_timeout();
+            let error_msg = send_error_message(e);
             debug_log::log_network_error(&request_id, &error_msg);
-            
-            if e.is_timeout() {
+            if let Some(span) = upstream_span.as_mut() {
+                span.set_error(error_msg.clone());
+            }
+
+            if is_timeout {
                 ProxyError::Timeout(error_msg)
-            } else if e.is_connect() {
-                ProxyError::ForwardFailed(error_msg)
             } else {
                 ProxyError::ForwardFailed(error_msg)
             }
         })?;
 
-        // 将 Request ID 注入 Response，以便 response_processor 使用
-        response.extensions_mut().insert(LogRequestId(request_id.clone()));
+        // 上游忽略 accept-encoding 返回压缩响应时先解压，后续日志、用量解析与错误处理均基于明文
+        let mut response = content_encoding::decode_response(response);
+
+        // 将 Request ID 注入 Response，以便 response_processor 记录响应内容
+        if log_bodies {
+            response
+                .extensions_mut()
+                .insert(LogRequestId(request_id.clone()));
+        }
 
         // 检查响应状态
         let status = response.status();
+        if let Some(span) = upstream_span.as_mut() {
+            span.set_attribute("http.response.status_code", status.as_u16());
+            if !status.is_success() {
+                span.set_error(format!("upstream returned {status}"));
+            }
+        }
 
         if status.is_success() {
-            debug_log::log_response_headers(&request_id, status, response.headers());
-            Ok(response)
+            if verbose_log {
+                debug_log::log_response_headers(&request_id, status, response.headers());
+            } else {
+                debug_log::log_response_summary(&request_id, status.as_u16());
+            }
+            Ok(fault_injection::truncate(response, truncate_after))
         } else {
             let status_code = status.as_u16();
             let body_text = response.text().await.ok();
             
-            debug_log::log_response_error(&request_id, status_code, &body_text);
+            if verbose_log {
+                debug_log::log_response_error(
+                    &request_id,
+                    status_code,
+                    body_text.as_deref().filter(|_| log_bodies),
+                );
+            } else {
+                debug_log::log_response_summary(&request_id, status_code);
+            }
 
             Err(ProxyError::UpstreamError {
                 status: status_code,
@@ -723,12 +903,29 @@ impl RequestForwarder {
         }
     }
 
+    /// 仅向指定 Provider 转发一次（不经过故障转移与熔断器）
+    ///
+    /// 用于 count_tokens 等辅助端点：上游不支持时由调用方回退，不应计入供应商健康状态
+    pub async fn forward_once(
+        &self,
+        provider: &Provider,
+        app_type: &AppType,
+        endpoint: &str,
+        body: &Value,
+        headers: &axum::http::HeaderMap,
+    ) -> Result<Response, ProxyError> {
+        let adapter = get_adapter(app_type);
+        self.forward(provider, endpoint, body, None, headers, adapter.as_ref())
+            .await
+    }
+
     /// 转发单个请求（带 Rate limit 重试）
     async fn forward_with_rate_limit_retry(
         &self,
         provider: &Provider,
         endpoint: &str,
         body: &Value,
+        raw_body: Option<&Bytes>,
         headers: &axum::http::HeaderMap,
         adapter: &dyn ProviderAdapter,
     ) -> Result<Response, ProxyError> {
@@ -736,7 +933,7 @@ impl RequestForwarder {
 
         loop {
             // 尝试发送请求
-            match self.forward(provider, endpoint, body, headers, adapter).await {
+            match self.forward(provider, endpoint, body, raw_body, headers, adapter).await {
                 Ok(response) => {
                     let status = response.status();
 
@@ -748,6 +945,13 @@ impl RequestForwarder {
                                 retry_state.attempt + 1,
                                 self.retry_config.max_retries
                             );
+                            self.emit_activity(ActivityKind::Retry {
+                                provider_id: provider.id.clone(),
+                                provider_name: provider.name.clone(),
+                                attempt: (retry_state.attempt + 1) as u32,
+                                failover: false,
+                                reason: "HTTP 429".to_string(),
+                            });
 
                             retry_state.wait_and_increment().await;
                             continue; // 重试
@@ -771,6 +975,13 @@ impl RequestForwarder {
                                     "[RATE-LIMIT] 请求失败包含 Rate limit 错误，准备重试: {}",
                                     error_msg.chars().take(100).collect::<String>()
                                 );
+                                self.emit_activity(ActivityKind::Retry {
+                                    provider_id: provider.id.clone(),
+                                    provider_name: provider.name.clone(),
+                                    attempt: (retry_state.attempt + 1) as u32,
+                                    failover: false,
+                                    reason: error_msg.chars().take(100).collect(),
+                                });
 
                                 retry_state.wait_and_increment().await;
                                 continue; // 重试
@@ -820,3 +1031,116 @@ fn extract_error_message(error: &ProxyError) -> Option<String> {
         _ => Some(error.to_string()),
     }
 }
+
+/// 发送请求失败的错误信息
+///
+/// 不包含请求 URL：查询参数认证方式会把 API Key 拼在 URL 中（`?key=...`），
+/// 而该信息会写入调试日志、追踪 span 并返回给客户端。改为附带底层错误链说明失败原因
+pub(super) fn send_error_message(e: reqwest::Error) -> String {
+    let e = e.without_url();
+    let mut detail = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(cause) = source {
+        detail.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    if e.is_timeout() {
+        format!("请求超时: {detail}")
+    } else if e.is_connect() {
+        format!("连接失败: {detail}")
+    } else {
+        detail
+    }
+}
+
+/// 请求总超时（None 表示不设置请求级超时）
+///
+/// 供应商配置了总超时时优先使用（0 表示不限制），故障转移关闭时同样生效。
+/// 否则只有应用级超时 > 0 时才设置：Duration::ZERO 在 reqwest 中表示"立刻超时"而不是"禁用超时"，
+/// 故障转移关闭时会传入 0，此时应该使用 client 的默认超时（600秒）
+fn total_timeout(
+    provider: &Provider,
+    non_streaming_timeout: std::time::Duration,
+) -> Option<std::time::Duration> {
+    let provider_secs = provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.timeouts.as_ref())
+        .and_then(|t| t.total_secs);
+    match provider_secs {
+        Some(secs) => Some(ProviderTimeouts::duration(secs)),
+        None => (!non_streaming_timeout.is_zero()).then_some(non_streaming_timeout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use std::time::Duration;
+
+    fn provider_with_total(total_secs: Option<u64>) -> Provider {
+        let mut provider = Provider::with_id("p".into(), "p".into(), serde_json::json!({}), None);
+        provider.meta = Some(ProviderMeta {
+            timeouts: Some(ProviderTimeouts {
+                total_secs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn total_timeout_prefers_provider_override() {
+        let app_timeout = Duration::from_secs(300);
+
+        // 未配置时使用应用级超时；故障转移关闭（应用级为 0）时不设置请求级超时
+        let provider = provider_with_total(None);
+        assert_eq!(total_timeout(&provider, app_timeout), Some(app_timeout));
+        assert_eq!(total_timeout(&provider, Duration::ZERO), None);
+
+        // 供应商的值优先，故障转移关闭时同样生效
+        let provider = provider_with_total(Some(30));
+        assert_eq!(
+            total_timeout(&provider, app_timeout),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            total_timeout(&provider, Duration::ZERO),
+            Some(Duration::from_secs(30))
+        );
+
+        // 0 表示不限制，覆盖应用级超时与客户端默认的 600 秒
+        let provider = provider_with_total(Some(0));
+        assert_eq!(total_timeout(&provider, app_timeout), Some(Duration::MAX));
+        assert_eq!(
+            total_timeout(&provider, Duration::ZERO),
+            Some(Duration::MAX)
+        );
+    }
+
+    #[tokio::test]
+    async fn send_error_does_not_leak_query_auth_key() {
+        use crate::provider::{AuthScheme, AuthSchemeType};
+
+        let client = reqwest::Client::new();
+        // 端口 1 上没有服务，连接会被拒绝
+        let mut request = client.post("http://127.0.0.1:1/v1/chat").build().unwrap();
+        let scheme = AuthScheme {
+            kind: AuthSchemeType::Query,
+            name: None,
+            prefix: None,
+            access_key_id: None,
+        };
+        auth_scheme::apply_auth_scheme(&mut request, &scheme, "sk-secret").unwrap();
+        assert!(request.url().as_str().contains("sk-secret"));
+
+        let error = client.execute(request).await.unwrap_err();
+        assert!(error.is_connect());
+        assert!(error.to_string().contains("sk-secret"));
+        let message = send_error_message(error);
+        assert!(message.starts_with("连接失败"));
+        assert!(!message.contains("sk-secret"));
+    }
+}
@@ -3,15 +3,21 @@
 //! 提供请求生命周期的上下文管理，封装通用初始化逻辑
 
 use crate::app_config::AppType;
-use crate::provider::Provider;
+use crate::provider::{Provider, ProviderTimeouts};
 use crate::proxy::{
+    activity::ActivityFeed,
+    conversation_capture::{self, ConversationCapture},
     extract_session_id,
     forwarder::RequestForwarder,
+    journal::JournalGuard,
+    otel::RequestTrace,
     server::ProxyState,
-    types::{AppProxyConfig, RectifierConfig},
+    team_gateway, transcript,
+    types::{AppProxyConfig, DebugLogConfig, LogRedactionConfig, RectifierConfig},
     ProxyError,
 };
 use axum::http::HeaderMap;
+use std::sync::Arc;
 use std::time::Instant;
 
 /// 流式超时配置
@@ -33,6 +39,8 @@ pub struct StreamingTimeoutConfig {
 /// - 日志标签
 /// - Session ID（用于日志关联）
 pub struct RequestContext {
+    /// 请求 ID（请求日志、请求采集、对话记录与活动事件共用）
+    pub request_id: String,
     /// 请求开始时间
     pub start_time: Instant,
     /// 应用级代理配置（per-app，包含重试次数和超时配置）
@@ -59,6 +67,29 @@ pub struct RequestContext {
     pub session_id: String,
     /// 整流器配置
     pub rectifier_config: RectifierConfig,
+    /// 请求日志脱敏配置
+    pub log_redaction_config: LogRedactionConfig,
+    /// 调试日志配置
+    pub debug_log_config: DebugLogConfig,
+    /// 链路追踪（仅在启用时创建）
+    pub trace: Option<Arc<RequestTrace>>,
+    /// 本轮用户输入（仅在启用对话记录时提取）
+    pub transcript_prompt: Option<String>,
+    /// 完整对话采集（仅在启用时解析请求历史）
+    pub conversation: Option<ConversationCapture>,
+    /// 团队网关成员 ID（仅在启用团队网关时存在）
+    pub member_id: Option<String>,
+    /// A/B 实验分组（`<实验 ID>:<组>`，未参与实验时为空）
+    pub experiment: Option<String>,
+    /// 请求重放采集 ID（仅在启用采集后设置，用于回填上游响应）
+    pub capture_id: Option<String>,
+    /// 响应缓存键（仅在查询缓存未命中时设置，用于写入成功的响应）
+    pub cache_key: Option<String>,
+    /// 实时活动事件发射器
+    pub activity: ActivityFeed,
+    /// 请求日志守卫（最后一个引用销毁时删除记录，崩溃时保留以便启动后上报；
+    /// 流式响应体持有一个引用，直到流结束）
+    journal: Option<Arc<JournalGuard>>,
 }
 
 impl RequestContext {
@@ -93,6 +124,8 @@ impl RequestContext {
 
         // 从数据库读取整流器配置
         let rectifier_config = state.db.get_rectifier_config().unwrap_or_default();
+        let log_redaction_config = state.db.get_log_redaction_config().unwrap_or_default();
+        let debug_log_config = state.db.get_debug_log_config().unwrap_or_default();
 
         let current_provider_id =
             crate::settings::get_current_provider(&app_type).unwrap_or_default();
@@ -116,6 +149,25 @@ impl RequestContext {
             session_result.client_provided
         );
 
+        let transcript_prompt =
+            transcript::is_enabled(&state.db).then(|| transcript::extract_prompt(body));
+        let conversation =
+            conversation_capture::begin(&state.db, app_type_str, &session_id, &request_model, body);
+        let trace = RequestTrace::start(&state.db, app_type_str, &request_model, &session_id);
+        if let Some(trace) = &trace {
+            log::debug!("[{tag}] Trace ID: {}", trace.trace_id());
+        }
+
+        // 记录已接收的请求，转发完成前崩溃时可在下次启动时上报（与请求日志使用同一 Request ID）
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let journal = JournalGuard::begin(
+            &state.db,
+            &request_id,
+            app_type_str,
+            &request_model,
+            &session_id,
+        );
+
         // 使用共享的 ProviderRouter 选择 Provider（熔断器状态跨请求保持）
         // 注意：只在这里调用一次，结果传递给 forwarder，避免重复消耗 HalfOpen 名额
         let providers = state
@@ -130,6 +182,26 @@ impl RequestContext {
                 _ => ProxyError::DatabaseError(e.to_string()),
             })?;
 
+        // 团队网关：按成员的供应商限制过滤故障转移链
+        let member_id = team_gateway::member_id(headers);
+        let providers =
+            team_gateway::restrict_providers(&state.db, member_id.as_deref(), providers)?;
+
+        // 按供应商预算配置过滤故障转移链（超限时警告、跳过或直接拒绝）
+        let providers = super::budget::apply_budgets(
+            &state.db,
+            state.app_handle.as_ref(),
+            app_type_str,
+            providers,
+        )?;
+
+        // 多密钥供应商：选定本次使用的密钥，全部额度用尽的供应商从链中移除
+        let providers = super::key_pool::apply_key_pools(&state.db, app_type_str, providers)?;
+
+        // A/B 实验：按会话分组，实验组应用转换规则、对照组移除同类规则
+        let (providers, experiment) =
+            super::experiment::apply_experiments(&state.db, app_type_str, &session_id, providers);
+
         let provider = providers
             .first()
             .cloned()
@@ -144,7 +216,17 @@ impl RequestContext {
             session_id
         );
 
+        super::request_inspection::begin(&request_id, headers);
+        let activity = ActivityFeed::new(
+            state.app_handle.clone(),
+            &request_id,
+            app_type_str,
+            &request_model,
+            start_time,
+        );
+
         Ok(Self {
+            request_id,
             start_time,
             app_config,
             provider,
@@ -156,6 +238,17 @@ impl RequestContext {
             app_type,
             session_id,
             rectifier_config,
+            log_redaction_config,
+            debug_log_config,
+            trace,
+            transcript_prompt,
+            conversation,
+            member_id,
+            experiment,
+            capture_id: None,
+            cache_key: None,
+            activity,
+            journal: journal.map(Arc::new),
         })
     }
 
@@ -176,6 +269,7 @@ impl RequestContext {
             .map(|s| s.split(':').next().unwrap_or(s))
             .unwrap_or("unknown")
             .to_string();
+        self.activity.set_model(&self.request_model);
 
         self
     }
@@ -216,8 +310,12 @@ impl RequestContext {
             first_byte_timeout,
             idle_timeout,
             self.rectifier_config.clone(),
+            self.log_redaction_config.clone(),
+            self.debug_log_config.clone(),
+            self.trace.clone(),
             None, // 使用默认的 RetryConfig
         )
+        .with_activity(self.activity.clone())
     }
 
     /// 获取 Provider 列表（用于故障转移）
@@ -227,6 +325,11 @@ impl RequestContext {
         self.providers.clone()
     }
 
+    /// 请求日志守卫的引用（流式响应体持有，直到流结束）
+    pub fn journal(&self) -> Option<Arc<JournalGuard>> {
+        self.journal.clone()
+    }
+
     /// 计算请求延迟（毫秒）
     #[inline]
     pub fn latency_ms(&self) -> u64 {
@@ -238,20 +341,97 @@ impl RequestContext {
     /// 配置生效规则：
     /// - 故障转移开启：返回配置的值（0 表示禁用超时检查）
     /// - 故障转移关闭：返回 0（禁用超时检查）
+    /// - 当前供应商配置了分阶段超时时，以供应商的值为准
     #[inline]
     pub fn streaming_timeout_config(&self) -> StreamingTimeoutConfig {
-        if self.app_config.auto_failover_enabled {
-            // 故障转移开启：使用配置的值（0 = 禁用超时）
-            StreamingTimeoutConfig {
-                first_byte_timeout: self.app_config.streaming_first_byte_timeout as u64,
-                idle_timeout: self.app_config.streaming_idle_timeout as u64,
-            }
-        } else {
-            // 故障转移关闭：禁用流式超时检查
-            StreamingTimeoutConfig {
-                first_byte_timeout: 0,
-                idle_timeout: 0,
-            }
+        streaming_timeouts(
+            &self.app_config,
+            self.provider
+                .meta
+                .as_ref()
+                .and_then(|m| m.timeouts.as_ref()),
+        )
+    }
+}
+
+/// 合并应用级配置与供应商的分阶段超时（规则见 [`RequestContext::streaming_timeout_config`]）
+fn streaming_timeouts(
+    app_config: &AppProxyConfig,
+    timeouts: Option<&ProviderTimeouts>,
+) -> StreamingTimeoutConfig {
+    let mut config = if app_config.auto_failover_enabled {
+        // 故障转移开启：使用配置的值（0 = 禁用超时）
+        StreamingTimeoutConfig {
+            first_byte_timeout: app_config.streaming_first_byte_timeout as u64,
+            idle_timeout: app_config.streaming_idle_timeout as u64,
+        }
+    } else {
+        // 故障转移关闭：禁用流式超时检查
+        StreamingTimeoutConfig {
+            first_byte_timeout: 0,
+            idle_timeout: 0,
+        }
+    };
+
+    if let Some(timeouts) = timeouts {
+        if let Some(secs) = timeouts.first_byte_secs {
+            config.first_byte_timeout = secs;
+        }
+        if let Some(secs) = timeouts.idle_secs {
+            config.idle_timeout = secs;
+        }
+    }
+    config
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_config(auto_failover_enabled: bool) -> AppProxyConfig {
+        AppProxyConfig {
+            app_type: "claude".to_string(),
+            enabled: true,
+            auto_failover_enabled,
+            max_retries: 3,
+            streaming_first_byte_timeout: 60,
+            streaming_idle_timeout: 120,
+            non_streaming_timeout: 600,
+            circuit_failure_threshold: 5,
+            circuit_success_threshold: 2,
+            circuit_timeout_seconds: 60,
+            circuit_error_rate_threshold: 0.5,
+            circuit_min_requests: 10,
         }
     }
+
+    #[test]
+    fn provider_streaming_timeouts_override_app_config() {
+        let timeouts = |first_byte_secs, idle_secs| ProviderTimeouts {
+            first_byte_secs,
+            idle_secs,
+            ..Default::default()
+        };
+
+        // 未配置时沿用应用级配置，故障转移关闭时禁用
+        let config = streaming_timeouts(&app_config(true), None);
+        assert_eq!((config.first_byte_timeout, config.idle_timeout), (60, 120));
+        let config = streaming_timeouts(&app_config(false), None);
+        assert_eq!((config.first_byte_timeout, config.idle_timeout), (0, 0));
+
+        // 供应商的值优先，未设置的项沿用应用级配置
+        let provider = timeouts(Some(10), None);
+        let config = streaming_timeouts(&app_config(true), Some(&provider));
+        assert_eq!((config.first_byte_timeout, config.idle_timeout), (10, 120));
+
+        // 0 表示不限制
+        let provider = timeouts(Some(0), Some(0));
+        let config = streaming_timeouts(&app_config(true), Some(&provider));
+        assert_eq!((config.first_byte_timeout, config.idle_timeout), (0, 0));
+
+        // 故障转移关闭时供应商的值同样生效
+        let provider = timeouts(Some(10), Some(30));
+        let config = streaming_timeouts(&app_config(false), Some(&provider));
+        assert_eq!((config.first_byte_timeout, config.idle_timeout), (10, 30));
+    }
 }
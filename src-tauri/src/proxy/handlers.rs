@@ -8,20 +8,32 @@
 //! - Claude 的格式转换逻辑保留在此文件（用于 OpenRouter 旧接口回退）
 
 use super::{
+    activity, cost_alert,
     error_mapper::{get_error_message, map_proxy_error_to_status},
     handler_config::{
         CLAUDE_PARSER_CONFIG, CODEX_PARSER_CONFIG, GEMINI_PARSER_CONFIG, OPENAI_PARSER_CONFIG,
     },
     handler_context::RequestContext,
+    journal, key_pool,
     providers::{get_adapter, streaming::create_anthropic_sse_stream, transform},
+    replay,
+    request_body::JsonBody,
+    request_inspection, response_cache,
     response_processor::{create_logged_passthrough_stream, process_response, SseUsageCollector},
     server::ProxyState,
+    shadow, sse_keepalive,
+    stream_buffer::{self, STREAM_CHANNEL_CAPACITY},
+    system_prompt::PromptFormat,
+    thinking_filter, token_estimate, transcript,
     types::*,
     usage::parser::TokenUsage,
-    ProxyError,
+    webhook, ProxyError,
 };
 use crate::app_config::AppType;
+use crate::database::TranscriptRecord;
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use bytes::Bytes;
+use futures::StreamExt;
 use rust_decimal::Decimal;
 use serde_json::{json, Value};
 use std::str::FromStr;
@@ -59,7 +71,26 @@ pub async fn get_status(State(state): State<ProxyState>) -> Result<Json<ProxySta
 pub async fn handle_messages(
     State(state): State<ProxyState>,
     headers: axum::http::HeaderMap,
-    Json(body): Json<Value>,
+    JsonBody { value: body, raw }: JsonBody,
+) -> Result<axum::response::Response, ProxyError> {
+    if let Some(interval) = sse_keepalive::interval(&state.db, &body) {
+        return Ok(sse_keepalive::wrap(
+            "Claude",
+            interval,
+            PromptFormat::Anthropic,
+            forward_messages(state, headers, body, raw),
+        )
+        .await);
+    }
+    forward_messages(state, headers, body, raw).await
+}
+
+/// 转发 /v1/messages 请求
+async fn forward_messages(
+    state: ProxyState,
+    headers: axum::http::HeaderMap,
+    body: Value,
+    raw: Bytes,
 ) -> Result<axum::response::Response, ProxyError> {
     let mut ctx =
         RequestContext::new(&state, &body, &headers, AppType::Claude, "Claude", "claude").await?;
@@ -70,7 +101,14 @@ pub async fn handle_messages(
         .unwrap_or(false);
 
     // 转发请求
-    let forwarder = ctx.create_forwarder(&state);
+    if let Some(cached) =
+        response_cache::lookup(&state.db, &mut ctx, "/v1/messages", &body, is_stream)
+    {
+        return Ok(cached);
+    }
+    ctx.capture_id = replay::capture(&state.db, &ctx, "/v1/messages", &body);
+    shadow::mirror(&state.db, &ctx, "/v1/messages", &body);
+    let forwarder = ctx.create_forwarder(&state).with_raw_body(raw);
     let result = match forwarder
         .forward_with_retry(
             &AppType::Claude,
@@ -107,6 +145,68 @@ pub async fn handle_messages(
     process_response(response, &ctx, &state, &CLAUDE_PARSER_CONFIG).await
 }
 
+/// 处理 /v1/messages/count_tokens 请求（Claude API）
+///
+/// 上游支持时直接转发；上游不支持（格式转换供应商、配置关闭或请求失败）时
+/// 回退到本地估算，保证预先计算 token 的客户端在任意上游下都能工作。
+pub async fn handle_count_tokens(
+    State(state): State<ProxyState>,
+    headers: axum::http::HeaderMap,
+    JsonBody { value: body, .. }: JsonBody,
+) -> Result<axum::response::Response, ProxyError> {
+    let ctx =
+        RequestContext::new(&state, &body, &headers, AppType::Claude, "Claude", "claude").await?;
+
+    let adapter = get_adapter(&AppType::Claude);
+    let upstream_supported = ctx
+        .provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.count_tokens)
+        .unwrap_or(true)
+        && !adapter.needs_transform(&ctx.provider);
+
+    if upstream_supported {
+        let forwarder = ctx.create_forwarder(&state);
+        match forwarder
+            .forward_once(
+                &ctx.provider,
+                &AppType::Claude,
+                "/v1/messages/count_tokens",
+                &body,
+                &headers,
+            )
+            .await
+        {
+            Ok(response) => {
+                let status = response.status();
+                let body_bytes = response.bytes().await.map_err(|e| {
+                    ProxyError::ForwardFailed(format!("Failed to read response body: {e}"))
+                })?;
+                return axum::response::Response::builder()
+                    .status(status)
+                    .header("content-type", "application/json")
+                    .body(axum::body::Body::from(body_bytes))
+                    .map_err(|e| ProxyError::Internal(format!("Failed to build response: {e}")));
+            }
+            Err(e) => {
+                log::debug!(
+                    "[Claude] 供应商 {} count_tokens 不可用，回退到本地估算: {e}",
+                    ctx.provider.name
+                );
+            }
+        }
+    }
+
+    let input_tokens = token_estimate::estimate_value_tokens(&body);
+    log::debug!("[Claude] count_tokens 本地估算: {input_tokens} tokens");
+    Ok((
+        [("x-ccswitch-token-estimate", "local")],
+        Json(json!({ "input_tokens": input_tokens })),
+    )
+        .into_response())
+}
+
 /// Claude 格式转换处理（独有逻辑）
 ///
 /// 处理 OpenRouter 旧 OpenAI 兼容接口的回退方案（当前默认不启用）
@@ -118,30 +218,61 @@ async fn handle_claude_transform(
     is_stream: bool,
 ) -> Result<axum::response::Response, ProxyError> {
     let status = response.status();
+    request_inspection::record_response(&ctx.request_id, status, response.headers());
 
     if is_stream {
         // 流式响应转换 (OpenAI SSE → Anthropic SSE)
-        let stream = response.bytes_stream();
+        let stream = stream_buffer::bounded(response.bytes_stream(), STREAM_CHANNEL_CAPACITY);
         let sse_stream = create_anthropic_sse_stream(stream);
+        let sse_stream = match thinking_filter::get_thinking_mode(&ctx.provider) {
+            Some(mode) => thinking_filter::filter_thinking_stream(sse_stream, mode).boxed(),
+            None => sse_stream.boxed(),
+        };
 
         // 创建使用量收集器
         let usage_collector = {
             let state = state.clone();
+            let request_id = ctx.request_id.clone();
+            let provider = ctx.provider.clone();
             let provider_id = ctx.provider.id.clone();
             let model = ctx.request_model.clone();
             let status_code = status.as_u16();
             let start_time = ctx.start_time;
+            let member_id = ctx.member_id.clone();
+            let experiment = ctx.experiment.clone();
+            let transcript_prompt = ctx.transcript_prompt.clone();
 
             SseUsageCollector::new(start_time, move |events, first_token_ms| {
+                // 收集到的是转换后的 Anthropic 事件，与直连供应商的流式响应一样保存对话记录
+                if let Some(prompt) = transcript_prompt.clone() {
+                    transcript::record_with(
+                        &state.db,
+                        TranscriptRecord {
+                            request_id: request_id.clone(),
+                            provider_id: provider_id.clone(),
+                            app_type: "claude".to_string(),
+                            model: model.clone(),
+                            created_at: chrono::Utc::now().timestamp(),
+                            request_text: prompt,
+                            response_text: transcript::extract_stream_text(&events),
+                        },
+                    );
+                }
                 if let Some(usage) = TokenUsage::from_claude_stream_events(&events) {
                     let latency_ms = start_time.elapsed().as_millis() as u64;
                     let state = state.clone();
+                    let request_id = request_id.clone();
+                    let provider = provider.clone();
                     let provider_id = provider_id.clone();
                     let model = model.clone();
+                    let member_id = member_id.clone();
+                    let experiment = experiment.clone();
 
                     tokio::spawn(async move {
+                        key_pool::record_spend(&state.db, "claude", &provider, &model, &usage);
                         log_usage(
                             &state,
+                            request_id,
                             &provider_id,
                             "claude",
                             &model,
@@ -150,6 +281,8 @@ async fn handle_claude_transform(
                             first_token_ms,
                             true,
                             status_code,
+                            member_id,
+                            experiment,
                         )
                         .await;
                     });
@@ -169,6 +302,9 @@ async fn handle_claude_transform(
             timeout_config,
             None,
         );
+        let logged_stream =
+            activity::instrument_stream(logged_stream, ctx.activity.clone(), status.as_u16());
+        let logged_stream = journal::hold_until_end(logged_stream, ctx.journal());
 
         let mut headers = axum::http::HeaderMap::new();
         headers.insert(
@@ -208,6 +344,12 @@ async fn handle_claude_transform(
         e
     })?;
 
+    transcript::record(
+        &state.db,
+        ctx,
+        transcript::extract_response_text(&anthropic_response),
+    );
+
     // 记录使用量
     if let Some(usage) = TokenUsage::from_claude_response(&anthropic_response) {
         let model = anthropic_response
@@ -218,11 +360,17 @@ async fn handle_claude_transform(
 
         tokio::spawn({
             let state = state.clone();
+            let request_id = ctx.request_id.clone();
+            let provider = ctx.provider.clone();
             let provider_id = ctx.provider.id.clone();
             let model = model.to_string();
+            let member_id = ctx.member_id.clone();
+            let experiment = ctx.experiment.clone();
             async move {
+                key_pool::record_spend(&state.db, "claude", &provider, &model, &usage);
                 log_usage(
                     &state,
+                    request_id,
                     &provider_id,
                     "claude",
                     &model,
@@ -231,12 +379,16 @@ async fn handle_claude_transform(
                     None,
                     false,
                     status.as_u16(),
+                    member_id,
+                    experiment,
                 )
                 .await;
             }
         });
     }
 
+    ctx.activity.completed(status.as_u16(), None);
+
     // 构建响应
     let mut builder = axum::response::Response::builder().status(status);
 
@@ -270,7 +422,26 @@ async fn handle_claude_transform(
 pub async fn handle_chat_completions(
     State(state): State<ProxyState>,
     headers: axum::http::HeaderMap,
-    Json(body): Json<Value>,
+    JsonBody { value: body, raw }: JsonBody,
+) -> Result<axum::response::Response, ProxyError> {
+    if let Some(interval) = sse_keepalive::interval(&state.db, &body) {
+        return Ok(sse_keepalive::wrap(
+            "Codex",
+            interval,
+            PromptFormat::OpenAIChat,
+            forward_chat_completions(state, headers, body, raw),
+        )
+        .await);
+    }
+    forward_chat_completions(state, headers, body, raw).await
+}
+
+/// 转发 /v1/chat/completions 请求
+async fn forward_chat_completions(
+    state: ProxyState,
+    headers: axum::http::HeaderMap,
+    body: Value,
+    raw: Bytes,
 ) -> Result<axum::response::Response, ProxyError> {
     let mut ctx =
         RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex").await?;
@@ -280,7 +451,18 @@ pub async fn handle_chat_completions(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
-    let forwarder = ctx.create_forwarder(&state);
+    if let Some(cached) = response_cache::lookup(
+        &state.db,
+        &mut ctx,
+        "/v1/chat/completions",
+        &body,
+        is_stream,
+    ) {
+        return Ok(cached);
+    }
+    ctx.capture_id = replay::capture(&state.db, &ctx, "/v1/chat/completions", &body);
+    shadow::mirror(&state.db, &ctx, "/v1/chat/completions", &body);
+    let forwarder = ctx.create_forwarder(&state).with_raw_body(raw);
     let result = match forwarder
         .forward_with_retry(
             &AppType::Codex,
@@ -311,7 +493,26 @@ pub async fn handle_chat_completions(
 pub async fn handle_responses(
     State(state): State<ProxyState>,
     headers: axum::http::HeaderMap,
-    Json(body): Json<Value>,
+    JsonBody { value: body, raw }: JsonBody,
+) -> Result<axum::response::Response, ProxyError> {
+    if let Some(interval) = sse_keepalive::interval(&state.db, &body) {
+        return Ok(sse_keepalive::wrap(
+            "Codex",
+            interval,
+            PromptFormat::OpenAIResponses,
+            forward_responses(state, headers, body, raw),
+        )
+        .await);
+    }
+    forward_responses(state, headers, body, raw).await
+}
+
+/// 转发 /v1/responses 请求
+async fn forward_responses(
+    state: ProxyState,
+    headers: axum::http::HeaderMap,
+    body: Value,
+    raw: Bytes,
 ) -> Result<axum::response::Response, ProxyError> {
     let mut ctx =
         RequestContext::new(&state, &body, &headers, AppType::Codex, "Codex", "codex").await?;
@@ -321,7 +522,14 @@ pub async fn handle_responses(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
-    let forwarder = ctx.create_forwarder(&state);
+    if let Some(cached) =
+        response_cache::lookup(&state.db, &mut ctx, "/v1/responses", &body, is_stream)
+    {
+        return Ok(cached);
+    }
+    ctx.capture_id = replay::capture(&state.db, &ctx, "/v1/responses", &body);
+    shadow::mirror(&state.db, &ctx, "/v1/responses", &body);
+    let forwarder = ctx.create_forwarder(&state).with_raw_body(raw);
     let result = match forwarder
         .forward_with_retry(
             &AppType::Codex,
@@ -357,7 +565,7 @@ pub async fn handle_gemini(
     State(state): State<ProxyState>,
     uri: axum::http::Uri,
     headers: axum::http::HeaderMap,
-    Json(body): Json<Value>,
+    JsonBody { value: body, raw }: JsonBody,
 ) -> Result<axum::response::Response, ProxyError> {
     // Gemini 的模型名称在 URI 中
     let mut ctx = RequestContext::new(&state, &body, &headers, AppType::Gemini, "Gemini", "gemini")
@@ -375,7 +583,12 @@ pub async fn handle_gemini(
         .and_then(|v| v.as_bool())
         .unwrap_or(false);
 
-    let forwarder = ctx.create_forwarder(&state);
+    if let Some(cached) = response_cache::lookup(&state.db, &mut ctx, endpoint, &body, is_stream) {
+        return Ok(cached);
+    }
+    ctx.capture_id = replay::capture(&state.db, &ctx, endpoint, &body);
+    shadow::mirror(&state.db, &ctx, endpoint, &body);
+    let forwarder = ctx.create_forwarder(&state).with_raw_body(raw);
     let result = match forwarder
         .forward_with_retry(
             &AppType::Gemini,
@@ -414,10 +627,19 @@ fn log_forward_error(
 ) {
     use super::usage::logger::UsageLogger;
 
-    let logger = UsageLogger::new(&state.db);
+    let logger = UsageLogger::new(&state.db)
+        .with_member_id(ctx.member_id.clone())
+        .with_experiment(ctx.experiment.clone());
     let status_code = map_proxy_error_to_status(error);
     let error_message = get_error_message(error);
-    let request_id = uuid::Uuid::new_v4().to_string();
+    replay::record_response(&state.db, ctx, status_code, &error_message);
+    ctx.activity.failed(status_code, error_message.clone());
+    let request_id = ctx.request_id.clone();
+    if let Some(trace) = &ctx.trace {
+        trace.set_attribute("ccswitch.request_id", request_id.clone());
+        trace.set_attribute("http.response.status_code", status_code);
+        trace.set_error(error_message.clone());
+    }
 
     if let Err(e) = logger.log_error_with_context(
         request_id,
@@ -439,6 +661,7 @@ fn log_forward_error(
 #[allow(clippy::too_many_arguments)]
 async fn log_usage(
     state: &ProxyState,
+    request_id: String,
     provider_id: &str,
     app_type: &str,
     model: &str,
@@ -447,10 +670,14 @@ async fn log_usage(
     first_token_ms: Option<u64>,
     is_streaming: bool,
     status_code: u16,
+    member_id: Option<String>,
+    experiment: Option<String>,
 ) {
     use super::usage::logger::UsageLogger;
 
-    let logger = UsageLogger::new(&state.db);
+    let logger = UsageLogger::new(&state.db)
+        .with_member_id(member_id)
+        .with_experiment(experiment);
 
     // 获取 provider 的 cost_multiplier
     let multiplier = match state.db.get_provider_by_id(provider_id, app_type) {
@@ -473,14 +700,12 @@ async fn log_usage(
         _ => Decimal::from(1),
     };
 
-    let request_id = uuid::Uuid::new_v4().to_string();
-
-    if let Err(e) = logger.log_with_calculation(
+    match logger.log_with_calculation(
         request_id,
         provider_id.to_string(),
         app_type.to_string(),
         model.to_string(),
-        usage,
+        usage.clone(),
         multiplier,
         latency_ms,
         first_token_ms,
@@ -489,6 +714,71 @@ async fn log_usage(
         None, // provider_type
         is_streaming,
     ) {
-        log::warn!("[USG-001] 记录使用量失败: {e}");
+        Ok(cost) => {
+            webhook::notify_request_completed(
+                &state.db,
+                app_type,
+                provider_id,
+                model,
+                &usage,
+                status_code,
+                latency_ms,
+                cost,
+            );
+            cost_alert::check_thresholds(&state.db, state.app_handle.as_ref(), app_type);
+        }
+        Err(e) => log::warn!("[USG-001] 记录使用量失败: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transformed_response_yields_transcript_text() {
+        let openai = json!({
+            "id": "chatcmpl-1",
+            "model": "gpt-4o",
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": "hello from openai"},
+                "finish_reason": "stop"
+            }],
+            "usage": {"prompt_tokens": 3, "completion_tokens": 4}
+        });
+        let anthropic = transform::openai_to_anthropic(openai).unwrap();
+        assert_eq!(
+            transcript::extract_response_text(&anthropic),
+            "hello from openai"
+        );
+    }
+
+    #[tokio::test]
+    async fn transformed_stream_yields_transcript_text() {
+        let chunks = [
+            r#"data: {"id":"c1","model":"gpt-4o","choices":[{"index":0,"delta":{"role":"assistant","content":"Hel"}}]}"#,
+            r#"data: {"id":"c1","model":"gpt-4o","choices":[{"index":0,"delta":{"content":"lo"}}]}"#,
+            r#"data: {"id":"c1","model":"gpt-4o","choices":[{"index":0,"delta":{},"finish_reason":"stop"}]}"#,
+            "data: [DONE]",
+        ];
+        let upstream = futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|c| Ok::<Bytes, reqwest::Error>(Bytes::from(format!("{c}\n\n")))),
+        );
+        let output: Vec<u8> = create_anthropic_sse_stream(upstream)
+            .map(|chunk| chunk.unwrap().to_vec())
+            .concat()
+            .await;
+
+        // 与 SseUsageCollector 一样逐条解析 data 行，得到转换后的 Anthropic 事件
+        let events: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .filter_map(|data| serde_json::from_str(data).ok())
+            .collect();
+        assert_eq!(transcript::extract_stream_text(&events), "Hello");
     }
 }
@@ -0,0 +1,224 @@
+//! 请求头透传策略模块
+//!
+//! 决定客户端请求中的哪些 header 会被透传到上游。
+//!
+//! ## 分类
+//! - 代理管理类（认证、连接、编码、anthropic-*）：始终由代理自行设置，不走透传逻辑
+//! - 黑名单模式（默认）：除内置黑名单和 Provider 配置的 `deny` 外全部透传
+//! - 白名单模式：仅透传内置白名单和 Provider 配置的 `allow`
+//!
+//! Provider 通过 `meta.headerPassthrough` 配置，未配置时使用黑名单模式。
+
+use crate::provider::{HeaderPassthrough, HeaderPassthroughMode, Provider};
+
+/// 由代理管理的 Headers - 无论何种模式都不直接透传
+///
+/// 这些 header 会被覆盖或由 HTTP 客户端重新生成
+const MANAGED_HEADERS: &[&str] = &[
+    // 认证类（会被覆盖）
+    "authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    // 连接类（由 HTTP 客户端管理）
+    "host",
+    "content-length",
+    "transfer-encoding",
+    // 编码类（会被覆盖为 identity）
+    "accept-encoding",
+    // anthropic 特定头单独处理，避免重复
+    "anthropic-beta",
+    "anthropic-version",
+    // 代理自身的调试请求头，仅用于本地日志
+    "x-ccswitch-debug",
+    // 团队网关内部请求头（已识别的成员 ID）
+    "x-ccswitch-member-id",
+];
+
+/// 默认黑名单 - 黑名单模式下不透传到上游的 Headers
+///
+/// 精简版黑名单，只过滤可能导致问题或泄露链路信息的 header
+///
+/// 注意：客户端 IP 类（x-forwarded-for, x-real-ip）默认透传
+const DEFAULT_DENYLIST: &[&str] = &[
+    // 代理转发类（保留 x-forwarded-for 和 x-real-ip）
+    "x-forwarded-host",
+    "x-forwarded-port",
+    "x-forwarded-proto",
+    "forwarded",
+    // CDN/云服务商特定头
+    "cf-connecting-ip",
+    "cf-ipcountry",
+    "cf-ray",
+    "cf-visitor",
+    "true-client-ip",
+    "fastly-client-ip",
+    "x-azure-clientip",
+    "x-azure-fdid",
+    "x-azure-ref",
+    "akamai-origin-hop",
+    "x-akamai-config-log-detail",
+    // 请求追踪类
+    "x-request-id",
+    "x-correlation-id",
+    "x-trace-id",
+    "x-amzn-trace-id",
+    "x-b3-traceid",
+    "x-b3-spanid",
+    "x-b3-parentspanid",
+    "x-b3-sampled",
+    "traceparent",
+    "tracestate",
+];
+
+/// 默认白名单 - 白名单模式下允许透传的 Headers
+///
+/// anthropic-version / anthropic-beta 由代理单独处理，始终会发送到 Claude 上游
+const DEFAULT_ALLOWLIST: &[&str] = &[
+    "accept",
+    "content-type",
+    "user-agent",
+    "anthropic-dangerous-direct-browser-access",
+    "openai-beta",
+    "openai-organization",
+    "openai-project",
+    "x-goog-api-client",
+    "x-stainless-*",
+];
+
+/// 请求头透传过滤器
+pub struct HeaderFilter<'a> {
+    mode: HeaderPassthroughMode,
+    allow: &'a [String],
+    deny: &'a [String],
+}
+
+impl<'a> HeaderFilter<'a> {
+    /// 从 Provider 配置构建过滤器（未配置时使用默认黑名单模式）
+    pub fn from_provider(provider: &'a Provider) -> Self {
+        match provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.header_passthrough.as_ref())
+        {
+            Some(config) => Self::new(config),
+            None => Self {
+                mode: HeaderPassthroughMode::Denylist,
+                allow: &[],
+                deny: &[],
+            },
+        }
+    }
+
+    pub fn new(config: &'a HeaderPassthrough) -> Self {
+        Self {
+            mode: config.mode,
+            allow: &config.allow,
+            deny: &config.deny,
+        }
+    }
+
+    /// 判断客户端 header 是否应透传到上游
+    pub fn should_forward(&self, name: &str) -> bool {
+        if MANAGED_HEADERS.iter().any(|h| name.eq_ignore_ascii_case(h)) {
+            return false;
+        }
+        if self.deny.iter().any(|p| matches_pattern(p, name)) {
+            return false;
+        }
+
+        let explicitly_allowed = self.allow.iter().any(|p| matches_pattern(p, name));
+        match self.mode {
+            HeaderPassthroughMode::Denylist => {
+                explicitly_allowed || !DEFAULT_DENYLIST.iter().any(|p| matches_pattern(p, name))
+            }
+            HeaderPassthroughMode::Allowlist => {
+                explicitly_allowed || DEFAULT_ALLOWLIST.iter().any(|p| matches_pattern(p, name))
+            }
+        }
+    }
+}
+
+/// 匹配 header 名称（不区分大小写，支持尾部 `*` 前缀通配）
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.trim();
+    match pattern.strip_suffix('*') {
+        Some(prefix) => {
+            name.len() >= prefix.len() && name[..prefix.len()].eq_ignore_ascii_case(prefix)
+        }
+        None => name.eq_ignore_ascii_case(pattern),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn provider_with(passthrough: Option<HeaderPassthrough>) -> Provider {
+        let mut provider = Provider::with_id("p1".to_string(), "Test".to_string(), json!({}), None);
+        provider.meta = Some(crate::provider::ProviderMeta {
+            header_passthrough: passthrough,
+            ..Default::default()
+        });
+        provider
+    }
+
+    #[test]
+    fn test_default_denylist_mode() {
+        let provider = provider_with(None);
+        let filter = HeaderFilter::from_provider(&provider);
+
+        assert!(filter.should_forward("user-agent"));
+        assert!(filter.should_forward("x-custom-client"));
+        assert!(filter.should_forward("x-forwarded-for"));
+        assert!(!filter.should_forward("cf-ray"));
+        assert!(!filter.should_forward("Traceparent"));
+        // 代理管理的 header 不透传
+        assert!(!filter.should_forward("authorization"));
+        assert!(!filter.should_forward("anthropic-version"));
+    }
+
+    #[test]
+    fn test_denylist_mode_with_custom_rules() {
+        let provider = provider_with(Some(HeaderPassthrough {
+            mode: HeaderPassthroughMode::Denylist,
+            allow: vec!["x-request-id".to_string()],
+            deny: vec!["x-client-*".to_string(), "x-forwarded-for".to_string()],
+        }));
+        let filter = HeaderFilter::from_provider(&provider);
+
+        assert!(filter.should_forward("x-request-id"));
+        assert!(!filter.should_forward("X-Client-Fingerprint"));
+        assert!(!filter.should_forward("x-forwarded-for"));
+        assert!(filter.should_forward("user-agent"));
+    }
+
+    #[test]
+    fn test_allowlist_mode() {
+        let provider = provider_with(Some(HeaderPassthrough {
+            mode: HeaderPassthroughMode::Allowlist,
+            allow: vec!["x-app".to_string()],
+            deny: vec!["user-agent".to_string()],
+        }));
+        let filter = HeaderFilter::from_provider(&provider);
+
+        assert!(filter.should_forward("content-type"));
+        assert!(filter.should_forward("x-stainless-lang"));
+        assert!(filter.should_forward("x-app"));
+        assert!(!filter.should_forward("user-agent"));
+        assert!(!filter.should_forward("x-forwarded-for"));
+        assert!(!filter.should_forward("x-unknown"));
+        assert!(!filter.should_forward("x-api-key"));
+    }
+
+    #[test]
+    fn test_mode_deserialization() {
+        let config: HeaderPassthrough =
+            serde_json::from_value(json!({"mode": "allowlist", "allow": ["x-a"]})).unwrap();
+        assert_eq!(config.mode, HeaderPassthroughMode::Allowlist);
+        assert!(config.deny.is_empty());
+
+        let config: HeaderPassthrough = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(config.mode, HeaderPassthroughMode::Denylist);
+    }
+}
@@ -0,0 +1,186 @@
+//! 请求头改写模块
+//!
+//! 根据 Provider 配置的 `headerRules`，在请求发往上游前对请求头执行移除、覆盖和注入。
+//!
+//! ## 应用顺序
+//! 1. `strip`：移除指定 header（如 `x-api-key`、客户端指纹类 header）
+//! 2. `set`：覆盖为指定值（不存在则添加）
+//! 3. `inject`：仅在 header 不存在时添加
+//!
+//! 规则在认证头添加之后应用，因此可以移除或替换代理自动生成的认证头。
+
+use crate::provider::{HeaderRules, Provider};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+/// API Key 占位符
+const API_KEY_PLACEHOLDER: &str = "{{apiKey}}";
+
+/// 获取 Provider 配置的请求头改写规则（未配置或为空时返回 None）
+pub fn get_header_rules(provider: &Provider) -> Option<&HeaderRules> {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.header_rules.as_ref())
+        .filter(|rules| !rules.is_empty())
+}
+
+/// 对请求头应用改写规则
+///
+/// # Arguments
+/// * `headers` - 即将发送到上游的请求头
+/// * `rules` - 改写规则
+/// * `api_key` - 用于替换 `{{apiKey}}` 占位符的 API Key
+pub fn apply_header_rules(headers: &mut HeaderMap, rules: &HeaderRules, api_key: Option<&str>) {
+    for name in &rules.strip {
+        match HeaderName::from_bytes(name.trim().as_bytes()) {
+            Ok(header_name) => {
+                if headers.remove(&header_name).is_some() {
+                    log::debug!("[HeaderRules] 移除 header: {header_name}");
+                }
+            }
+            Err(e) => log::warn!("[HeaderRules] 无效的 header 名称 '{name}': {e}"),
+        }
+    }
+
+    for (name, value) in &rules.set {
+        if let Some((header_name, header_value)) = parse_header(name, value, api_key) {
+            log::debug!("[HeaderRules] 覆盖 header: {header_name}");
+            headers.insert(header_name, header_value);
+        }
+    }
+
+    for (name, value) in &rules.inject {
+        if let Some((header_name, header_value)) = parse_header(name, value, api_key) {
+            if !headers.contains_key(&header_name) {
+                log::debug!("[HeaderRules] 注入 header: {header_name}");
+                headers.insert(header_name, header_value);
+            }
+        }
+    }
+}
+
+/// 解析 header 名称和值（替换占位符），无效时记录警告并跳过
+fn parse_header(
+    name: &str,
+    value: &str,
+    api_key: Option<&str>,
+) -> Option<(HeaderName, HeaderValue)> {
+    let header_name = match HeaderName::from_bytes(name.trim().as_bytes()) {
+        Ok(n) => n,
+        Err(e) => {
+            log::warn!("[HeaderRules] 无效的 header 名称 '{name}': {e}");
+            return None;
+        }
+    };
+
+    let resolved = if value.contains(API_KEY_PLACEHOLDER) {
+        match api_key {
+            Some(key) => value.replace(API_KEY_PLACEHOLDER, key),
+            None => {
+                log::warn!("[HeaderRules] header '{name}' 引用了 {API_KEY_PLACEHOLDER}，但供应商未配置 API Key，已跳过");
+                return None;
+            }
+        }
+    } else {
+        value.to_string()
+    };
+
+    match HeaderValue::from_str(&resolved) {
+        Ok(v) => Some((header_name, v)),
+        Err(e) => {
+            log::warn!("[HeaderRules] header '{name}' 的值无效: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (k, v) in pairs {
+            map.insert(*k, HeaderValue::from_static(v));
+        }
+        map
+    }
+
+    #[test]
+    fn test_strip_removes_headers_case_insensitive() {
+        let mut map = headers(&[("x-api-key", "sk-1"), ("user-agent", "claude-cli")]);
+        let rules = HeaderRules {
+            strip: vec!["X-Api-Key".to_string()],
+            ..Default::default()
+        };
+
+        apply_header_rules(&mut map, &rules, None);
+
+        assert!(!map.contains_key("x-api-key"));
+        assert!(map.contains_key("user-agent"));
+    }
+
+    #[test]
+    fn test_set_overrides_and_replaces_api_key_placeholder() {
+        let mut map = headers(&[("authorization", "Bearer old")]);
+        let mut set = IndexMap::new();
+        set.insert("Authorization".to_string(), "Bearer {{apiKey}}".to_string());
+        let rules = HeaderRules {
+            set,
+            ..Default::default()
+        };
+
+        apply_header_rules(&mut map, &rules, Some("sk-new"));
+
+        assert_eq!(map.get("authorization").unwrap(), "Bearer sk-new");
+    }
+
+    #[test]
+    fn test_inject_only_when_absent() {
+        let mut map = headers(&[("x-existing", "keep")]);
+        let mut inject = IndexMap::new();
+        inject.insert("x-existing".to_string(), "replaced".to_string());
+        inject.insert("x-added".to_string(), "new".to_string());
+        let rules = HeaderRules {
+            inject,
+            ..Default::default()
+        };
+
+        apply_header_rules(&mut map, &rules, None);
+
+        assert_eq!(map.get("x-existing").unwrap(), "keep");
+        assert_eq!(map.get("x-added").unwrap(), "new");
+    }
+
+    #[test]
+    fn test_placeholder_without_api_key_is_skipped() {
+        let mut map = HeaderMap::new();
+        let mut set = IndexMap::new();
+        set.insert("authorization".to_string(), "Bearer {{apiKey}}".to_string());
+        let rules = HeaderRules {
+            set,
+            ..Default::default()
+        };
+
+        apply_header_rules(&mut map, &rules, None);
+
+        assert!(!map.contains_key("authorization"));
+    }
+
+    #[test]
+    fn test_invalid_header_name_is_ignored() {
+        let mut map = HeaderMap::new();
+        let mut set = IndexMap::new();
+        set.insert("bad header".to_string(), "v".to_string());
+        let rules = HeaderRules {
+            set,
+            strip: vec!["also bad".to_string()],
+            ..Default::default()
+        };
+
+        apply_header_rules(&mut map, &rules, None);
+
+        assert!(map.is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+//! `/healthz` 健康检查端点
+//!
+//! 返回代理运行时间、各应用当前使用的供应商，以及每个已配置供应商最近一次的
+//! 健康状态（熔断器、连续失败）与延迟（最近一次代理请求、最近一次流式检查），
+//! 供脚本和监控工具在不打开 GUI 的情况下检查 cc-switch。
+//!
+//! 任一应用当前供应商不健康（熔断打开或被标记为不健康）时整体状态为 `degraded`。
+//! 未携带访问令牌 / 管理令牌的请求只返回整体状态与时间戳（见 [`super::access_token`]）。
+
+use super::{access_token, circuit_breaker::CircuitState, server::ProxyState};
+use crate::app_config::AppType;
+use crate::services::stream_check::StreamCheckResult;
+use crate::services::usage_stats::LastRequestInfo;
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::net::SocketAddr;
+
+/// 健康检查响应
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthzResponse {
+    /// `ok` 或 `degraded`
+    pub status: &'static str,
+    pub timestamp: String,
+    pub uptime_seconds: u64,
+    pub apps: Vec<AppHealth>,
+}
+
+/// 单个应用的健康状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppHealth {
+    pub app_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_provider_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_provider_name: Option<String>,
+    pub providers: Vec<ProviderHealthEntry>,
+}
+
+/// 单个供应商的最近状态
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderHealthEntry {
+    pub id: String,
+    pub name: String,
+    pub active: bool,
+    pub in_failover_queue: bool,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    /// 熔断器状态（尚未经过代理转发时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_state: Option<CircuitState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_request: Option<LastRequestInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_check: Option<StreamCheckResult>,
+}
+
+/// 处理 `/healthz`
+pub async fn healthz(
+    State(state): State<ProxyState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Response {
+    let detailed =
+        access_token::is_monitoring_authorized(&state.db, &headers, &uri, addr).unwrap_or(false);
+    let report = report(&state).await;
+    if detailed {
+        return Json(report).into_response();
+    }
+    Json(json!({ "status": report.status, "timestamp": report.timestamp })).into_response()
+}
+
+/// 生成完整的健康报告
+async fn report(state: &ProxyState) -> HealthzResponse {
+    let uptime_seconds = state
+        .start_time
+        .read()
+        .await
+        .map(|start| start.elapsed().as_secs())
+        .unwrap_or(0);
+    let current_providers = state.current_providers.read().await.clone();
+
+    let mut degraded = false;
+    let mut apps = Vec::new();
+    for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let app = app_type.as_str();
+        let providers = match state.db.get_all_providers(app) {
+            Ok(providers) => providers,
+            Err(e) => {
+                log::warn!("[healthz] 读取 {app} 供应商失败: {e}");
+                continue;
+            }
+        };
+
+        // 优先使用代理实际转发的供应商，尚无请求时回退到配置中的当前供应商
+        let active_id = current_providers
+            .get(app)
+            .map(|(id, _)| id.clone())
+            .or_else(|| state.db.get_current_provider(app).ok().flatten());
+
+        let mut entries = Vec::with_capacity(providers.len());
+        for (id, provider) in providers {
+            let health = state.db.get_provider_health(&id, app).await.ok();
+            let circuit_state = state
+                .provider_router
+                .get_circuit_breaker_stats(&id, app)
+                .await
+                .map(|stats| stats.state);
+            let healthy = health.as_ref().is_none_or(|h| h.is_healthy)
+                && circuit_state != Some(CircuitState::Open);
+            let active = active_id.as_deref() == Some(id.as_str());
+            if active && !healthy {
+                degraded = true;
+            }
+
+            entries.push(ProviderHealthEntry {
+                last_request: state.db.get_last_request(app, &id).ok().flatten(),
+                last_check: state.db.get_latest_stream_check(&id, app).ok().flatten(),
+                name: provider.name,
+                active,
+                in_failover_queue: provider.in_failover_queue,
+                healthy,
+                consecutive_failures: health.as_ref().map_or(0, |h| h.consecutive_failures),
+                circuit_state,
+                last_error: health.and_then(|h| h.last_error),
+                id,
+            });
+        }
+
+        let active_provider_name = entries.iter().find(|e| e.active).map(|e| e.name.clone());
+        apps.push(AppHealth {
+            app_type: app.to_string(),
+            active_provider_id: active_id,
+            active_provider_name,
+            providers: entries,
+        });
+    }
+
+    HealthzResponse {
+        status: if degraded { "degraded" } else { "ok" },
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        uptime_seconds,
+        apps,
+    }
+}
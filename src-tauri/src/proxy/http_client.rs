@@ -2,11 +2,16 @@
 //!
 //! 提供支持全局代理配置的 HTTP 客户端。
 //! 所有需要发送 HTTP 请求的模块都应使用此模块提供的客户端。
+//! 配置了专用出站代理（`upstreamProxy`）、TLS 选项或连接调优的供应商，
+//! 使用按连接选项缓存的独立客户端。
 
+use crate::provider::{Provider, ProviderConnectionOptions, ProviderMeta, ProviderTimeouts};
 use once_cell::sync::OnceCell;
-use reqwest::Client;
+use reqwest::{Client, ClientBuilder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 /// 全局 HTTP 客户端实例
 static GLOBAL_CLIENT: OnceCell<RwLock<Client>> = OnceCell::new();
@@ -14,6 +19,30 @@ static GLOBAL_CLIENT: OnceCell<RwLock<Client>> = OnceCell::new();
 /// 当前代理 URL（用于日志和状态查询）
 static CURRENT_PROXY_URL: OnceCell<RwLock<Option<String>>> = OnceCell::new();
 
+/// 供应商专用的客户端（按连接选项缓存，复用连接池）
+static PROVIDER_CLIENTS: OnceCell<RwLock<HashMap<ProviderClientOptions, Client>>> = OnceCell::new();
+
+/// 文件路径及其修改时间（文件被替换后重新加载）
+type FileStamp = (PathBuf, Option<SystemTime>);
+
+/// 供应商专用客户端的连接选项
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ProviderClientOptions {
+    proxy_url: Option<String>,
+    ca_bundle: Option<FileStamp>,
+    /// mTLS 客户端证书与私钥
+    client_identity: Option<(FileStamp, FileStamp)>,
+    skip_verify: bool,
+    connection: Option<ProviderConnectionOptions>,
+    connect_timeout_secs: Option<u64>,
+}
+
+fn file_stamp(path: &str) -> FileStamp {
+    let path = PathBuf::from(path.trim());
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+    (path, modified)
+}
+
 /// 初始化全局 HTTP 客户端
 ///
 /// 应在应用启动时调用一次。
@@ -117,7 +146,6 @@ pub fn apply_proxy(proxy_url: Option<&str>) -> Result<(), String> {
 ///
 /// # Arguments
 /// * `proxy_url` - 新的代理 URL，None 或空字符串表示直连
-#[allow(dead_code)]
 pub fn update_proxy(proxy_url: Option<&str>) -> Result<(), String> {
     let effective_url = proxy_url.filter(|s| !s.trim().is_empty());
     let new_client = build_client(effective_url)?;
@@ -175,6 +203,183 @@ pub fn get() -> Client {
         })
 }
 
+/// 获取供应商使用的 HTTP 客户端
+///
+/// 供应商配置了 `upstreamProxy`、TLS 选项（根证书、mTLS 客户端证书、跳过校验）或连接调优时
+/// 返回对应的专用客户端
+/// （只配置 TLS 选项时沿用全局代理），否则返回全局客户端。
+pub fn get_for_provider(provider: &Provider) -> Result<Client, String> {
+    let meta = provider.meta.as_ref();
+    let non_empty = |s: &str| !s.trim().is_empty();
+    let proxy_url = meta
+        .and_then(|m| m.upstream_proxy.as_deref())
+        .filter(|s| non_empty(s))
+        .map(|s| s.trim().to_string());
+    let tls = meta.and_then(|m| m.tls.as_ref());
+    let ca_bundle = tls
+        .and_then(|t| t.ca_bundle_path.as_deref())
+        .filter(|s| non_empty(s))
+        .map(file_stamp);
+    let client_cert = tls
+        .and_then(|t| t.client_cert_path.as_deref())
+        .filter(|s| non_empty(s));
+    let client_key = tls
+        .and_then(|t| t.client_key_path.as_deref())
+        .filter(|s| non_empty(s));
+    let client_identity = match (client_cert, client_key) {
+        (Some(cert), Some(key)) => Some((file_stamp(cert), file_stamp(key))),
+        (None, None) => None,
+        _ => return Err("mTLS 客户端证书与私钥必须同时配置".to_string()),
+    };
+    let skip_verify = tls.is_some_and(|t| t.skip_verify);
+    let connection = meta
+        .and_then(|m| m.connection.clone())
+        .filter(|c| *c != ProviderConnectionOptions::default());
+    let connect_timeout_secs = connect_timeout_secs(meta);
+
+    if proxy_url.is_none()
+        && ca_bundle.is_none()
+        && client_identity.is_none()
+        && !skip_verify
+        && connection.is_none()
+        && connect_timeout_secs.is_none()
+    {
+        return Ok(get());
+    }
+    provider_client(ProviderClientOptions {
+        proxy_url: proxy_url.or_else(get_current_proxy_url),
+        ca_bundle,
+        client_identity,
+        skip_verify,
+        connection,
+        connect_timeout_secs,
+    })
+}
+
+/// 供应商配置的连接超时（秒，0 表示不限制；None 沿用全局客户端的 30 秒）
+fn connect_timeout_secs(meta: Option<&ProviderMeta>) -> Option<u64> {
+    meta.and_then(|m| m.timeouts.as_ref())
+        .and_then(|t| t.connect_secs)
+}
+
+/// 获取（或创建并缓存）指定连接选项的客户端
+fn provider_client(options: ProviderClientOptions) -> Result<Client, String> {
+    let cache = PROVIDER_CLIENTS.get_or_init(|| RwLock::new(HashMap::new()));
+    if let Some(client) = cache.read().ok().and_then(|m| m.get(&options).cloned()) {
+        return Ok(client);
+    }
+
+    let mut builder = client_builder(options.proxy_url.as_deref())?;
+    if let Some((path, _)) = &options.ca_bundle {
+        for cert in load_ca_bundle(path)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if let Some(((cert, _), (key, _))) = &options.client_identity {
+        builder = builder.identity(load_client_identity(cert, key)?);
+    }
+    if let Some(connection) = &options.connection {
+        builder = apply_connection_options(builder, connection);
+    }
+    if let Some(secs) = options.connect_timeout_secs {
+        builder = builder.connect_timeout(ProviderTimeouts::duration(secs));
+    }
+    if options.skip_verify {
+        log::warn!("[GlobalProxy] 供应商已关闭上游 TLS 证书校验，连接可能被中间人劫持");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    let client = builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))?;
+
+    if let Ok(mut clients) = cache.write() {
+        clients.insert(options, client.clone());
+    }
+    Ok(client)
+}
+
+/// 应用连接调优参数
+fn apply_connection_options(
+    mut builder: ClientBuilder,
+    options: &ProviderConnectionOptions,
+) -> ClientBuilder {
+    let keepalive = options
+        .keepalive_interval_secs
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs);
+    match options.http2 {
+        Some(false) => builder = builder.http1_only(),
+        Some(true) => {
+            builder = builder.http2_adaptive_window(true);
+            if let Some(interval) = keepalive {
+                builder = builder
+                    .http2_keep_alive_interval(interval)
+                    .http2_keep_alive_while_idle(true);
+            }
+        }
+        None => {}
+    }
+    if let Some(max_idle) = options.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max_idle);
+    }
+    if let Some(secs) = options.pool_idle_timeout_secs {
+        builder = builder.pool_idle_timeout((secs > 0).then(|| Duration::from_secs(secs)));
+    }
+    if let Some(interval) = keepalive {
+        builder = builder.tcp_keepalive(interval);
+    }
+    if let Some(nodelay) = options.tcp_nodelay {
+        builder = builder.tcp_nodelay(nodelay);
+    }
+    builder
+}
+
+/// 读取 PEM 根证书文件（可包含多个证书）
+fn load_ca_bundle(path: &Path) -> Result<Vec<reqwest::Certificate>, String> {
+    let pem =
+        std::fs::read(path).map_err(|e| format!("读取根证书文件失败 {}: {e}", path.display()))?;
+    let certs = reqwest::Certificate::from_pem_bundle(&pem)
+        .map_err(|e| format!("解析根证书文件失败 {}: {e}", path.display()))?;
+    if certs.is_empty() {
+        return Err(format!("根证书文件中没有证书: {}", path.display()));
+    }
+    Ok(certs)
+}
+
+/// 读取 mTLS 客户端证书与私钥（PEM）
+fn load_client_identity(cert: &Path, key: &Path) -> Result<reqwest::Identity, String> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|e| format!("读取客户端证书文件失败 {}: {e}", path.display()))
+    };
+    let mut pem = read(cert)?;
+    pem.push(b'\n');
+    pem.extend(read(key)?);
+    reqwest::Identity::from_pem(&pem).map_err(|e| {
+        format!(
+            "解析客户端证书失败 {} / {}: {e}",
+            cert.display(),
+            key.display()
+        )
+    })
+}
+
+/// 验证 mTLS 客户端证书与私钥（保存供应商配置前调用）
+pub fn validate_client_identity(cert: &str, key: &str) -> Result<(), String> {
+    load_client_identity(Path::new(cert.trim()), Path::new(key.trim())).map(|_| ())
+}
+
+/// 验证根证书文件（保存供应商配置前调用）
+pub fn validate_ca_bundle(path: &str) -> Result<(), String> {
+    load_ca_bundle(Path::new(path.trim())).map(|_| ())
+}
+
+/// 清空供应商专用的客户端缓存（系统唤醒后重建连接）
+pub fn clear_provider_clients() {
+    if let Some(mut clients) = PROVIDER_CLIENTS.get().and_then(|lock| lock.write().ok()) {
+        clients.clear();
+    }
+}
+
 /// 获取当前代理 URL
 ///
 /// 返回当前配置的代理 URL，None 表示直连。
@@ -193,6 +398,13 @@ pub fn is_proxy_enabled() -> bool {
 
 /// 构建 HTTP 客户端
 fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
+    client_builder(proxy_url)?
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+}
+
+/// 按代理配置创建客户端构建器
+fn client_builder(proxy_url: Option<&str>) -> Result<ClientBuilder, String> {
     let mut builder = Client::builder()
         .timeout(Duration::from_secs(600))
         .connect_timeout(Duration::from_secs(30))
@@ -223,9 +435,7 @@ fn build_client(proxy_url: Option<&str>) -> Result<Client, String> {
         log::debug!("[GlobalProxy] Direct connection (no proxy)");
     }
 
-    builder
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {e}"))
+    Ok(builder)
 }
 
 /// 隐藏 URL 中的敏感信息（用于日志）
@@ -298,4 +508,134 @@ mod tests {
         let result = build_client(Some("invalid-scheme://127.0.0.1:7890"));
         assert!(result.is_err(), "Should reject invalid proxy scheme");
     }
+
+    #[test]
+    fn test_get_for_provider_uses_upstream_proxy() {
+        let mut provider = Provider::with_id("p".into(), "p".into(), serde_json::json!({}), None);
+        assert!(get_for_provider(&provider).is_ok());
+
+        provider.meta = Some(crate::provider::ProviderMeta {
+            upstream_proxy: Some("socks5h://127.0.0.1:1080".into()),
+            ..Default::default()
+        });
+        assert!(get_for_provider(&provider).is_ok());
+        assert!(PROVIDER_CLIENTS
+            .get()
+            .and_then(|lock| lock.read().ok())
+            .is_some_and(|m| m
+                .keys()
+                .any(|k| k.proxy_url.as_deref() == Some("socks5h://127.0.0.1:1080"))));
+
+        provider.meta.as_mut().unwrap().upstream_proxy = Some("ftp://127.0.0.1:21".into());
+        assert!(get_for_provider(&provider).is_err());
+    }
+
+    #[test]
+    fn test_get_for_provider_with_tls_options() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let mut provider = Provider::with_id("p".into(), "p".into(), serde_json::json!({}), None);
+        provider.meta = Some(crate::provider::ProviderMeta {
+            tls: Some(crate::provider::ProviderTlsOptions {
+                skip_verify: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert!(get_for_provider(&provider).is_ok());
+
+        let missing = tmp.path().join("missing.pem");
+        provider.meta.as_mut().unwrap().tls = Some(crate::provider::ProviderTlsOptions {
+            ca_bundle_path: Some(missing.to_string_lossy().to_string()),
+            ..Default::default()
+        });
+        assert!(get_for_provider(&provider).is_err());
+
+        let empty = tmp.path().join("empty.pem");
+        std::fs::write(&empty, b"not a certificate").unwrap();
+        assert!(validate_ca_bundle(&empty.to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn test_get_for_provider_with_client_identity() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let (cert_pem, key_pem) = crate::proxy::tls::generate_self_signed(None).unwrap();
+        let cert = tmp.path().join("client.pem");
+        let key = tmp.path().join("client-key.pem");
+        std::fs::write(&cert, cert_pem).unwrap();
+        std::fs::write(&key, key_pem).unwrap();
+        let path = |p: &Path| Some(p.to_string_lossy().to_string());
+        assert!(validate_client_identity(&cert.to_string_lossy(), &key.to_string_lossy()).is_ok());
+
+        let mut provider = Provider::with_id("p".into(), "p".into(), serde_json::json!({}), None);
+        provider.meta = Some(crate::provider::ProviderMeta {
+            tls: Some(crate::provider::ProviderTlsOptions {
+                client_cert_path: path(&cert),
+                client_key_path: path(&key),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert!(get_for_provider(&provider).is_ok());
+
+        // 只配置证书、缺少私钥时拒绝
+        if let Some(tls) = provider.meta.as_mut().and_then(|m| m.tls.as_mut()) {
+            tls.client_key_path = None;
+        }
+        assert!(get_for_provider(&provider).is_err());
+    }
+
+    #[test]
+    fn test_get_for_provider_with_connection_options() {
+        let mut provider = Provider::with_id("p".into(), "p".into(), serde_json::json!({}), None);
+        let connection = ProviderConnectionOptions {
+            http2: Some(true),
+            pool_max_idle_per_host: Some(32),
+            pool_idle_timeout_secs: Some(300),
+            keepalive_interval_secs: Some(15),
+            tcp_nodelay: Some(true),
+        };
+        provider.meta = Some(crate::provider::ProviderMeta {
+            connection: Some(connection.clone()),
+            ..Default::default()
+        });
+        assert!(get_for_provider(&provider).is_ok());
+        assert!(PROVIDER_CLIENTS
+            .get()
+            .and_then(|lock| lock.read().ok())
+            .is_some_and(|m| m.keys().any(|k| k.connection.as_ref() == Some(&connection))));
+
+        let http1 = ProviderConnectionOptions {
+            http2: Some(false),
+            ..Default::default()
+        };
+        provider.meta.as_mut().unwrap().connection = Some(http1);
+        assert!(get_for_provider(&provider).is_ok());
+    }
+
+    #[test]
+    fn test_get_for_provider_with_connect_timeout() {
+        let mut provider = Provider::with_id("p".into(), "p".into(), serde_json::json!({}), None);
+        assert_eq!(connect_timeout_secs(provider.meta.as_ref()), None);
+
+        let timeouts = |connect_secs| crate::provider::ProviderMeta {
+            timeouts: Some(ProviderTimeouts {
+                connect_secs,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        provider.meta = Some(timeouts(Some(5)));
+        assert_eq!(connect_timeout_secs(provider.meta.as_ref()), Some(5));
+        assert!(get_for_provider(&provider).is_ok());
+
+        // 0 表示不限制，仍使用独立客户端而不是全局客户端的 30 秒
+        provider.meta = Some(timeouts(Some(0)));
+        assert_eq!(connect_timeout_secs(provider.meta.as_ref()), Some(0));
+        assert_eq!(ProviderTimeouts::duration(0), Duration::MAX);
+        assert!(get_for_provider(&provider).is_ok());
+        assert!(PROVIDER_CLIENTS
+            .get()
+            .and_then(|lock| lock.read().ok())
+            .is_some_and(|m| m.keys().any(|k| k.connect_timeout_secs == Some(0))));
+    }
 }
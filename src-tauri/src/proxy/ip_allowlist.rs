@@ -0,0 +1,152 @@
+//! 入站 IP 白名单
+//!
+//! 代理监听非回环地址时，启用白名单后只有来源地址命中 CIDR 列表的连接可以访问，
+//! 其余连接直接返回 403。本机回环地址始终允许，避免把本机客户端锁在外面。
+//! 配置存储在 settings 表（`ip_allowlist_config`）中，每次请求实时读取。
+
+use super::{server::ProxyState, types::IpAllowlistConfig, ProxyError};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::net::{IpAddr, SocketAddr};
+
+/// 解析后的地址段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// 解析 `a.b.c.d/n`、`::/n` 或单个地址
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let invalid = || format!("无效的地址段: {value}");
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (value, None),
+        };
+        let network: IpAddr = addr.trim().parse().map_err(|_| invalid())?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.trim().parse::<u8>().map_err(|_| invalid())?,
+            None => max_prefix,
+        };
+        if prefix > max_prefix {
+            return Err(invalid());
+        }
+        Ok(Self { network, prefix })
+    }
+
+    /// 地址是否落在该地址段内
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, normalize(ip)) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// IPv4 映射的 IPv6 地址（`::ffff:a.b.c.d`）按 IPv4 处理
+fn normalize(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// 校验白名单配置中的全部地址段
+pub fn validate(config: &IpAllowlistConfig) -> Result<Vec<Cidr>, String> {
+    config.cidrs.iter().map(|c| Cidr::parse(c)).collect()
+}
+
+/// 来源地址是否允许访问
+pub fn is_allowed(config: &IpAllowlistConfig, ip: IpAddr) -> bool {
+    if !config.enabled || normalize(ip).is_loopback() {
+        return true;
+    }
+    // 保存时已校验，这里忽略无法解析的条目
+    config
+        .cidrs
+        .iter()
+        .filter_map(|c| Cidr::parse(c).ok())
+        .any(|cidr| cidr.contains(ip))
+}
+
+/// Axum 中间件：拒绝不在白名单中的来源地址
+pub async fn enforce_ip_allowlist(
+    State(state): State<ProxyState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = state.db.get_ip_allowlist_config().unwrap_or_default();
+    if !config.enabled {
+        return next.run(request).await;
+    }
+
+    let Some(ip) = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0.ip())
+    else {
+        return next.run(request).await;
+    };
+    if is_allowed(&config, ip) {
+        next.run(request).await
+    } else {
+        log::warn!("[IpAllowlist] 拒绝来自 {ip} 的连接");
+        ProxyError::ClientNotAllowed { ip: ip.to_string() }.into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_and_matches_cidrs() {
+        let lan = Cidr::parse("192.168.1.0/24").unwrap();
+        assert!(lan.contains(ip("192.168.1.42")));
+        assert!(lan.contains(ip("::ffff:192.168.1.42")));
+        assert!(!lan.contains(ip("192.168.2.1")));
+
+        assert!(Cidr::parse("10.0.0.5").unwrap().contains(ip("10.0.0.5")));
+        assert!(Cidr::parse("0.0.0.0/0").unwrap().contains(ip("8.8.8.8")));
+        assert!(Cidr::parse("fd00::/8").unwrap().contains(ip("fd12::1")));
+
+        assert!(Cidr::parse("10.0.0.0/33").is_err());
+        assert!(Cidr::parse("example.com").is_err());
+    }
+
+    #[test]
+    fn only_enforced_when_enabled_and_always_allows_loopback() {
+        let mut config = IpAllowlistConfig {
+            enabled: false,
+            cidrs: vec!["10.0.0.0/8".into()],
+        };
+        assert!(is_allowed(&config, ip("192.168.1.2")));
+
+        config.enabled = true;
+        assert!(!is_allowed(&config, ip("192.168.1.2")));
+        assert!(is_allowed(&config, ip("10.1.2.3")));
+        assert!(is_allowed(&config, ip("127.0.0.1")));
+        assert!(is_allowed(&config, ip("::1")));
+    }
+}
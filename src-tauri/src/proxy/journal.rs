@@ -0,0 +1,100 @@
+//! 请求日志（journal）：崩溃后报告丢失的请求
+//!
+//! 每个请求在进入处理流程时写入 `proxy_request_journal`，请求处理结束时删除：非流式响应在
+//! 请求上下文销毁时，流式响应在响应体结束（或客户端断开）时。进程崩溃时来不及删除的记录
+//! 会在下次启动时被标记为丢失，并通过 `proxy-requests-lost` 事件通知前端，前端也可随时通过命令查询。
+//!
+//! 丢失的请求只上报、不自动重放：请求可能已被上游部分处理（已计费、已执行工具调用或已输出部分内容），
+//! 代理无法得知上游的处理进度，重放不能保证恰好一次，是否重试交由客户端决定。
+
+use crate::database::{Database, JournalEntry};
+use futures::stream::{Stream, StreamExt};
+use std::sync::Arc;
+use tauri::Emitter;
+
+/// 请求日志守卫，销毁时删除对应记录
+pub struct JournalGuard {
+    db: Arc<Database>,
+    request_id: String,
+}
+
+impl JournalGuard {
+    /// 记录一个新接收的请求
+    ///
+    /// `request_id` 为请求上下文的 Request ID，便于把崩溃后上报的丢失请求与请求日志、活动事件对应起来。
+    /// 写入失败时仅记录警告，不影响请求处理
+    pub fn begin(
+        db: &Arc<Database>,
+        request_id: &str,
+        app_type: &str,
+        model: &str,
+        session_id: &str,
+    ) -> Option<Self> {
+        let entry = JournalEntry {
+            request_id: request_id.to_string(),
+            app_type: app_type.to_string(),
+            model: model.to_string(),
+            session_id: session_id.to_string(),
+            accepted_at: chrono::Utc::now().timestamp_millis(),
+        };
+        if let Err(e) = db.insert_journal_entry(&entry) {
+            log::warn!("[{app_type}] 写入请求日志失败: {e}");
+            return None;
+        }
+        Some(Self {
+            db: db.clone(),
+            request_id: entry.request_id,
+        })
+    }
+}
+
+impl Drop for JournalGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.db.remove_journal_entry(&self.request_id) {
+            log::warn!("删除请求日志 {} 失败: {e}", self.request_id);
+        }
+    }
+}
+
+/// 让请求日志记录保持到流式响应体结束（流被消费完或客户端断开后删除）
+pub fn hold_until_end<S>(
+    stream: S,
+    guard: Option<Arc<JournalGuard>>,
+) -> impl Stream<Item = S::Item> + Send
+where
+    S: Stream + Send + 'static,
+{
+    stream.map(move |item| {
+        let _ = &guard;
+        item
+    })
+}
+
+/// 启动时检查上次运行遗留的请求日志
+///
+/// 必须在代理服务启动之前调用，否则会把正在处理的请求误判为丢失
+pub fn recover_lost_requests(db: &Database, app_handle: &tauri::AppHandle) {
+    let lost = match db.mark_journal_entries_lost() {
+        Ok(lost) => lost,
+        Err(e) => {
+            log::error!("检查请求日志失败: {e}");
+            return;
+        }
+    };
+    if lost.is_empty() {
+        return;
+    }
+
+    log::warn!("检测到上次异常退出时有 {} 个请求未完成转发", lost.len());
+    for entry in &lost {
+        log::warn!(
+            "  丢失请求: id={}, app={}, model={}, session={}, accepted_at={}",
+            entry.request_id,
+            entry.app_type,
+            entry.model,
+            entry.session_id,
+            entry.accepted_at
+        );
+    }
+    let _ = app_handle.emit("proxy-requests-lost", &lost);
+}
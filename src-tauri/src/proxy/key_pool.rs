@@ -0,0 +1,195 @@
+//! 多密钥轮换与按密钥消费额度
+//!
+//! 供应商配置了 `apiKeys` 时，每次请求按顺序选用第一个未用尽额度的密钥，
+//! 替换认证头中的密钥值；请求完成后把本次成本累加到该密钥名下。
+//! 全部密钥额度用尽的供应商会从本次请求的故障转移链中移除。
+//!
+//! 消费统计存储在 `provider_key_spend` 表中，可通过命令查看或重置。
+
+use super::{cost_annotation, providers::AuthInfo, usage::parser::TokenUsage, ProxyError};
+use crate::database::Database;
+use crate::provider::{Provider, ProviderApiKey};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// 为故障转移链中的多密钥供应商选定本次使用的密钥
+///
+/// 选定后供应商副本的 `apiKeys` 只保留仍有额度的密钥，首个即为本次使用的密钥。
+/// 过滤后为空时返回 `ProxyError::KeyAllowanceExhausted`
+pub fn apply_key_pools(
+    db: &Database,
+    app_type: &str,
+    providers: Vec<Provider>,
+) -> Result<Vec<Provider>, ProxyError> {
+    let mut result = Vec::with_capacity(providers.len());
+    let mut last_error = None;
+
+    for mut provider in providers {
+        let Some(meta) = provider.meta.as_mut().filter(|m| !m.api_keys.is_empty()) else {
+            result.push(provider);
+            continue;
+        };
+
+        let spend = match db.get_provider_key_spend(app_type, &provider.id) {
+            Ok(rows) => rows
+                .into_iter()
+                .filter_map(|r| Some((r.key_id, Decimal::from_str(&r.spent_usd).ok()?)))
+                .collect(),
+            Err(e) => {
+                log::warn!("[{app_type}] 读取供应商 {} 密钥消费失败: {e}", provider.id);
+                HashMap::new()
+            }
+        };
+
+        let total = meta.api_keys.len();
+        meta.api_keys.retain(|key| has_allowance(key, &spend));
+
+        match meta.api_keys.first() {
+            Some(key) => {
+                if meta.api_keys.len() < total {
+                    log::debug!(
+                        "[{app_type}] 供应商 {} 已跳过 {} 个额度用尽的密钥，使用 {}",
+                        provider.name,
+                        total - meta.api_keys.len(),
+                        key.id
+                    );
+                }
+                result.push(provider);
+            }
+            None => {
+                log::info!(
+                    "[{app_type}] 供应商 {} 的全部密钥额度已用尽，本次请求跳过",
+                    provider.name
+                );
+                last_error = Some(ProxyError::KeyAllowanceExhausted {
+                    provider: provider.name.clone(),
+                });
+            }
+        }
+    }
+
+    match (result.is_empty(), last_error) {
+        (true, Some(error)) => Err(error),
+        _ => Ok(result),
+    }
+}
+
+/// 密钥是否仍有额度（未配置额度视为不限）
+fn has_allowance(key: &ProviderApiKey, spend: &HashMap<String, Decimal>) -> bool {
+    let Some(allowance) = key
+        .allowance_usd
+        .as_deref()
+        .and_then(|a| Decimal::from_str(a).ok())
+    else {
+        return true;
+    };
+    spend.get(&key.id).copied().unwrap_or(Decimal::ZERO) < allowance
+}
+
+/// 本次请求使用的密钥
+pub fn active_key(provider: &Provider) -> Option<&ProviderApiKey> {
+    provider.meta.as_ref()?.api_keys.first()
+}
+
+/// 用选定的密钥替换认证信息中的密钥值
+pub fn override_auth(provider: &Provider, auth: &mut Option<AuthInfo>) {
+    if let (Some(key), Some(auth)) = (active_key(provider), auth.as_mut()) {
+        auth.api_key = key.key.clone();
+    }
+}
+
+/// 把本次请求成本累加到所用密钥名下
+///
+/// 非多密钥供应商或模型定价未知时不记录
+pub fn record_spend(
+    db: &Database,
+    app_type: &str,
+    provider: &Provider,
+    model: &str,
+    usage: &TokenUsage,
+) {
+    let Some(key) = active_key(provider) else {
+        return;
+    };
+    let Some(cost) = cost_annotation::calculate_cost(db, provider, model, usage) else {
+        return;
+    };
+    if let Err(e) = db.add_provider_key_spend(app_type, &provider.id, &key.id, cost) {
+        log::warn!(
+            "[{app_type}] 记录供应商 {} 密钥 {} 消费失败: {e}",
+            provider.id,
+            key.id
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+
+    fn key(id: &str, allowance: Option<&str>) -> ProviderApiKey {
+        ProviderApiKey {
+            id: id.into(),
+            key: format!("sk-{id}"),
+            label: None,
+            allowance_usd: allowance.map(Into::into),
+        }
+    }
+
+    fn provider(id: &str, keys: Vec<ProviderApiKey>) -> Provider {
+        Provider::with_meta(
+            id,
+            ProviderMeta {
+                api_keys: keys,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn rotates_past_exhausted_key() {
+        let db = Database::memory().unwrap();
+        db.add_provider_key_spend("claude", "a", "k1", Decimal::from(5))
+            .unwrap();
+
+        let a = provider("a", vec![key("k1", Some("5")), key("k2", Some("5"))]);
+        let result = apply_key_pools(&db, "claude", vec![a]).unwrap();
+        assert_eq!(active_key(&result[0]).unwrap().id, "k2");
+
+        let mut auth = Some(AuthInfo::new(
+            "sk-primary".into(),
+            super::super::providers::AuthStrategy::Bearer,
+        ));
+        override_auth(&result[0], &mut auth);
+        assert_eq!(auth.unwrap().api_key, "sk-k2");
+    }
+
+    #[test]
+    fn exhausted_provider_is_skipped() {
+        let db = Database::memory().unwrap();
+        db.add_provider_key_spend("claude", "a", "k1", Decimal::from(1))
+            .unwrap();
+
+        let a = provider("a", vec![key("k1", Some("1"))]);
+        let b = provider("b", vec![]);
+        let result = apply_key_pools(&db, "claude", vec![a.clone(), b]).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "b");
+
+        let err = apply_key_pools(&db, "claude", vec![a]).unwrap_err();
+        assert!(matches!(err, ProxyError::KeyAllowanceExhausted { .. }));
+    }
+
+    #[test]
+    fn key_without_allowance_is_unlimited() {
+        let db = Database::memory().unwrap();
+        db.add_provider_key_spend("claude", "a", "k1", Decimal::from(1000))
+            .unwrap();
+
+        let a = provider("a", vec![key("k1", None)]);
+        let result = apply_key_pools(&db, "claude", vec![a]).unwrap();
+        assert_eq!(active_key(&result[0]).unwrap().id, "k1");
+    }
+}
@@ -0,0 +1,95 @@
+//! 局域网访问控制
+//!
+//! 代理默认只监听本机回环地址。监听 `0.0.0.0` 或指定网卡地址会把代理（以及其中保存的
+//! 供应商密钥）暴露给局域网内的其他机器 / 容器，因此需要在设置中显式开启局域网访问：
+//! - 未开启时，不允许把监听地址改为非回环地址
+//! - 旧版本已配置的非回环地址在未做选择前保持可用（不在升级后突然失效）
+//! - 以非回环地址启动时记录警告，并向前端发送 `proxy-lan-exposed` 事件
+
+use crate::database::Database;
+use std::net::IpAddr;
+use tauri::Emitter;
+
+/// 解析监听地址（支持 `localhost`）
+pub fn parse_listen_address(address: &str) -> Result<IpAddr, String> {
+    let address = address.trim();
+    if address.eq_ignore_ascii_case("localhost") {
+        return Ok(IpAddr::from([127, 0, 0, 1]));
+    }
+    address.parse::<IpAddr>().map_err(|_| {
+        format!("无效的监听地址: {address}（请填写 IP 地址，如 127.0.0.1 或 0.0.0.0）")
+    })
+}
+
+/// 是否仅本机可访问
+pub fn is_loopback(address: &str) -> bool {
+    parse_listen_address(address).is_ok_and(|ip| ip.is_loopback())
+}
+
+/// 校验新的监听地址
+///
+/// `previous` 为修改前的监听地址：未开启局域网访问时，只允许保留旧版本已配置的地址
+pub fn check_listen_address(
+    db: &Database,
+    address: &str,
+    previous: Option<&str>,
+) -> Result<(), String> {
+    let ip = parse_listen_address(address)?;
+    if ip.is_loopback() {
+        return Ok(());
+    }
+
+    let allowed = match db.get_proxy_lan_access().map_err(|e| e.to_string())? {
+        Some(enabled) => enabled,
+        None => previous.is_some_and(|p| p.trim() == address.trim()),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!(
+            "监听 {address} 会把代理暴露给局域网，请先在设置中开启局域网访问"
+        ))
+    }
+}
+
+/// 以非回环地址启动时发出警告
+pub fn warn_if_exposed(app_handle: Option<&tauri::AppHandle>, address: &str, port: u16) {
+    if is_loopback(address) {
+        return;
+    }
+    log::warn!(
+        "代理监听于 {address}:{port}，局域网内的其他设备可以访问；建议启用团队网关为成员分配访问令牌"
+    );
+    if let Some(app) = app_handle {
+        let payload = serde_json::json!({ "address": address, "port": port });
+        if let Err(e) = app.emit("proxy-lan-exposed", payload) {
+            log::error!("发射 proxy-lan-exposed 事件失败: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    #[test]
+    fn requires_explicit_opt_in_for_lan_addresses() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        assert!(check_listen_address(&db, "127.0.0.1", None).is_ok());
+        assert!(check_listen_address(&db, "localhost", None).is_ok());
+        assert!(check_listen_address(&db, "::1", None).is_ok());
+        assert!(check_listen_address(&db, "not-an-ip", None).is_err());
+
+        // 未做选择：新设置被拒绝，旧版本已有的地址保持可用
+        assert!(check_listen_address(&db, "0.0.0.0", Some("127.0.0.1")).is_err());
+        assert!(check_listen_address(&db, "0.0.0.0", Some("0.0.0.0")).is_ok());
+
+        db.set_proxy_lan_access(true)?;
+        assert!(check_listen_address(&db, "192.168.1.10", Some("127.0.0.1")).is_ok());
+
+        db.set_proxy_lan_access(false)?;
+        assert!(check_listen_address(&db, "0.0.0.0", Some("0.0.0.0")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+//! 请求日志中的 tool_result 脱敏
+//!
+//! Claude Code 的 tool_result 常常包含整份源文件，原样写入调试日志既占空间又会把敏感内容落盘。
+//! 启用后仅在写日志时替换 tool_result 内容（截断或摘要为哈希），保留工具名、大小、错误标记等元数据；
+//! 实际转发给上游的请求体不受影响。
+//!
+//! 支持三种请求格式：
+//! - Anthropic Messages：`messages[].content[]` 中 `type = "tool_result"` 的块
+//! - OpenAI Chat Completions：`role = "tool"` 的消息
+//! - OpenAI Responses（Codex）：`input[]` 中 `type = "function_call_output"` 的条目
+//!
+//! 配置存储在 settings 表（`log_redaction_config`）中。
+
+use super::types::{LogRedactionConfig, LogRedactionMode};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// 按配置返回用于写日志的请求体
+///
+/// 未启用脱敏时直接借用原始请求体，避免额外拷贝
+pub fn redact_for_log<'a>(body: &'a Value, config: &LogRedactionConfig) -> Cow<'a, Value> {
+    if config.mode == LogRedactionMode::Off {
+        return Cow::Borrowed(body);
+    }
+
+    let tool_names = collect_tool_names(body);
+    let mut redacted = body.clone();
+
+    // Anthropic / OpenAI Chat
+    if let Some(messages) = redacted.get_mut("messages").and_then(|m| m.as_array_mut()) {
+        for message in messages {
+            if message.get("role").and_then(|r| r.as_str()) == Some("tool") {
+                let tool = lookup_name(&tool_names, message.get("tool_call_id"));
+                if let Some(content) = message.get_mut("content") {
+                    if let Some(text) = content.as_str() {
+                        *content = Value::String(redact_text(text, tool, config));
+                    }
+                }
+                continue;
+            }
+
+            let Some(blocks) = message.get_mut("content").and_then(|c| c.as_array_mut()) else {
+                continue;
+            };
+            for block in blocks {
+                if block.get("type").and_then(|t| t.as_str()) != Some("tool_result") {
+                    continue;
+                }
+                let tool = lookup_name(&tool_names, block.get("tool_use_id"));
+                if let Some(content) = block.get_mut("content") {
+                    redact_tool_result_content(content, tool, config);
+                }
+            }
+        }
+    }
+
+    // OpenAI Responses
+    if let Some(items) = redacted.get_mut("input").and_then(|i| i.as_array_mut()) {
+        for item in items {
+            if item.get("type").and_then(|t| t.as_str()) != Some("function_call_output") {
+                continue;
+            }
+            let tool = lookup_name(&tool_names, item.get("call_id"));
+            if let Some(output) = item.get_mut("output") {
+                if let Some(text) = output.as_str() {
+                    *output = Value::String(redact_function_output(text, tool, config));
+                }
+            }
+        }
+    }
+
+    Cow::Owned(redacted)
+}
+
+/// 收集 tool_use / tool_call 的 id → 工具名映射
+fn collect_tool_names(body: &Value) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let mut insert = |id: Option<&Value>, name: Option<&Value>| {
+        if let (Some(id), Some(name)) = (id.and_then(|v| v.as_str()), name.and_then(|v| v.as_str()))
+        {
+            names.insert(id.to_string(), name.to_string());
+        }
+    };
+
+    if let Some(messages) = body.get("messages").and_then(|m| m.as_array()) {
+        for message in messages {
+            if let Some(blocks) = message.get("content").and_then(|c| c.as_array()) {
+                for block in blocks {
+                    if block.get("type").and_then(|t| t.as_str()) == Some("tool_use") {
+                        insert(block.get("id"), block.get("name"));
+                    }
+                }
+            }
+            if let Some(calls) = message.get("tool_calls").and_then(|c| c.as_array()) {
+                for call in calls {
+                    insert(call.get("id"), call.pointer("/function/name"));
+                }
+            }
+        }
+    }
+
+    if let Some(items) = body.get("input").and_then(|i| i.as_array()) {
+        for item in items {
+            if item.get("type").and_then(|t| t.as_str()) == Some("function_call") {
+                insert(item.get("call_id"), item.get("name"));
+            }
+        }
+    }
+
+    names
+}
+
+fn lookup_name<'a>(names: &'a HashMap<String, String>, id: Option<&Value>) -> &'a str {
+    id.and_then(|v| v.as_str())
+        .and_then(|id| names.get(id))
+        .map(String::as_str)
+        .unwrap_or("unknown")
+}
+
+/// 脱敏 Anthropic tool_result 的 content（字符串或内容块数组）
+fn redact_tool_result_content(content: &mut Value, tool: &str, config: &LogRedactionConfig) {
+    match content {
+        Value::String(text) => *text = redact_text(text, tool, config),
+        Value::Array(blocks) => {
+            for block in blocks {
+                match block.get("type").and_then(|t| t.as_str()) {
+                    Some("text") => {
+                        if let Some(Value::String(text)) = block.get_mut("text") {
+                            *text = redact_text(text, tool, config);
+                        }
+                    }
+                    // 图片等二进制内容只保留类型与大小
+                    Some(kind) => {
+                        let size = block.to_string().len();
+                        *block = json!({ "type": kind, "redacted_bytes": size });
+                    }
+                    None => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 脱敏 Codex function_call_output
+///
+/// Codex 的 output 通常是 `{"output": "...", "metadata": {"exit_code": 0, ...}}` 形式的 JSON 字符串，
+/// 此时只替换 output 字段以保留退出码等元数据
+fn redact_function_output(text: &str, tool: &str, config: &LogRedactionConfig) -> String {
+    if let Ok(mut parsed) = serde_json::from_str::<Value>(text) {
+        if let Some(Value::String(inner)) = parsed.get_mut("output") {
+            *inner = redact_text(inner, tool, config);
+            return parsed.to_string();
+        }
+    }
+    redact_text(text, tool, config)
+}
+
+/// 按模式替换单段文本
+fn redact_text(text: &str, tool: &str, config: &LogRedactionConfig) -> String {
+    let bytes = text.len();
+    let lines = text.lines().count();
+
+    match config.mode {
+        LogRedactionMode::Off => text.to_string(),
+        LogRedactionMode::Truncate => {
+            if text.chars().count() <= config.truncate_chars {
+                return text.to_string();
+            }
+            let prefix: String = text.chars().take(config.truncate_chars).collect();
+            format!("{prefix}…[truncated tool={tool} bytes={bytes} lines={lines}]")
+        }
+        LogRedactionMode::Hash => {
+            let digest = Sha256::digest(text.as_bytes());
+            let hash: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+            format!("[redacted tool={tool} bytes={bytes} lines={lines} sha256={hash}]")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: LogRedactionMode) -> LogRedactionConfig {
+        LogRedactionConfig {
+            mode,
+            truncate_chars: 5,
+        }
+    }
+
+    fn anthropic_body() -> Value {
+        json!({
+            "messages": [
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "tu_1", "is_error": false,
+                     "content": "fn main() {}\nfn other() {}"}
+                ]}
+            ]
+        })
+    }
+
+    #[test]
+    fn off_mode_borrows_original() {
+        let body = anthropic_body();
+        assert!(matches!(
+            redact_for_log(&body, &config(LogRedactionMode::Off)),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn hash_mode_keeps_metadata() {
+        let body = anthropic_body();
+        let out = redact_for_log(&body, &config(LogRedactionMode::Hash));
+        let block = &out["messages"][1]["content"][0];
+        let content = block["content"].as_str().unwrap();
+        assert!(content.starts_with("[redacted tool=Read bytes=26 lines=2 sha256="));
+        assert_eq!(block["is_error"], json!(false));
+        // 原始请求体不受影响
+        assert_eq!(
+            body["messages"][1]["content"][0]["content"],
+            json!("fn main() {}\nfn other() {}")
+        );
+    }
+
+    #[test]
+    fn truncate_mode_keeps_prefix() {
+        let body = anthropic_body();
+        let out = redact_for_log(&body, &config(LogRedactionMode::Truncate));
+        assert_eq!(
+            out["messages"][1]["content"][0]["content"],
+            json!("fn ma…[truncated tool=Read bytes=26 lines=2]")
+        );
+    }
+
+    #[test]
+    fn redacts_openai_tool_messages() {
+        let body = json!({
+            "messages": [
+                {"role": "assistant", "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "shell", "arguments": "{}"}}
+                ]},
+                {"role": "tool", "tool_call_id": "call_1", "content": "long output"}
+            ]
+        });
+        let out = redact_for_log(&body, &config(LogRedactionMode::Truncate));
+        assert_eq!(
+            out["messages"][1]["content"],
+            json!("long …[truncated tool=shell bytes=11 lines=1]")
+        );
+    }
+
+    #[test]
+    fn codex_output_keeps_exit_code() {
+        let body = json!({
+            "input": [
+                {"type": "function_call", "call_id": "c1", "name": "shell", "arguments": "{}"},
+                {"type": "function_call_output", "call_id": "c1",
+                 "output": "{\"output\":\"secret file contents\",\"metadata\":{\"exit_code\":1}}"}
+            ]
+        });
+        let out = redact_for_log(&body, &config(LogRedactionMode::Hash));
+        let output: Value =
+            serde_json::from_str(out["input"][1]["output"].as_str().unwrap()).unwrap();
+        assert_eq!(output["metadata"]["exit_code"], json!(1));
+        assert!(output["output"]
+            .as_str()
+            .unwrap()
+            .starts_with("[redacted tool=shell bytes=20"));
+    }
+}
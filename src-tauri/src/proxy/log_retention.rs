@@ -0,0 +1,339 @@
+//! 调试日志目录与保留策略
+//!
+//! 调试日志默认写入 `<app_config_dir>/logs/debug`（可在调试日志配置中指定其他目录），旧版写在 `~/tmp/log` 的 `cc-*.log`
+//! 在启动时迁移过来。之后每小时按保留策略清理一次：删除超出保留天数的文件，
+//! 可选将已轮转的小时文件压缩为 `.log.gz`，总大小超出上限时从最早的文件开始删除。
+
+use crate::proxy::types::DebugLogConfig;
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// 用户配置的调试日志目录（None 表示使用默认目录）
+static DEBUG_LOG_DIR_OVERRIDE: OnceLock<RwLock<Option<PathBuf>>> = OnceLock::new();
+
+fn debug_log_dir_cache() -> &'static RwLock<Option<PathBuf>> {
+    DEBUG_LOG_DIR_OVERRIDE.get_or_init(|| RwLock::new(None))
+}
+
+/// 设置调试日志目录（空字符串或 None 恢复默认目录）
+pub fn set_debug_log_dir_override(dir: Option<&str>) {
+    let dir = dir
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from);
+    if let Ok(mut cache) = debug_log_dir_cache().write() {
+        *cache = dir;
+    }
+}
+
+/// 默认调试日志目录（`<app_config_dir>/logs/debug`）
+pub fn default_debug_log_dir() -> PathBuf {
+    crate::config::get_app_config_dir()
+        .join("logs")
+        .join("debug")
+}
+
+/// 当前生效的调试日志目录（用户配置优先）
+pub fn debug_log_dir() -> PathBuf {
+    debug_log_dir_cache()
+        .read()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .unwrap_or_else(default_debug_log_dir)
+}
+
+/// 旧版调试日志目录（~/tmp/log）
+pub fn legacy_debug_log_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|h| h.join("tmp").join("log"))
+}
+
+/// 调试日志迁移 / 清理结果
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugLogCleanup {
+    /// 从旧目录迁移过来的文件数
+    pub moved_files: usize,
+    /// 迁移出旧目录的字节数
+    pub moved_bytes: u64,
+    /// 超出保留天数或总大小上限被删除的文件数
+    pub removed_files: usize,
+    /// 删除文件释放的字节数
+    pub reclaimed_bytes: u64,
+    /// 旧目录是否已清空并删除
+    pub legacy_dir_removed: bool,
+    /// 被压缩的已轮转文件数
+    pub compressed_files: usize,
+}
+
+/// 指定时间对应的调试日志文件名（按小时轮转）
+pub fn debug_log_file_name(now: chrono::DateTime<chrono::Local>) -> String {
+    format!("cc-{}.log", now.format("%Y%m%d%H"))
+}
+
+fn is_debug_log(path: &Path) -> bool {
+    path.is_file()
+        && path.file_name().and_then(|n| n.to_str()).is_some_and(|n| {
+            n.starts_with("cc-") && (n.ends_with(".log") || n.ends_with(".log.gz"))
+        })
+}
+
+fn debug_log_files(dir: &Path) -> Vec<(PathBuf, std::fs::Metadata)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| is_debug_log(p))
+        .filter_map(|p| std::fs::metadata(&p).ok().map(|m| (p, m)))
+        .collect()
+}
+
+fn is_expired(meta: &std::fs::Metadata, cutoff: SystemTime) -> bool {
+    meta.modified().is_ok_and(|modified| modified < cutoff)
+}
+
+fn remove_expired(path: &Path, meta: &std::fs::Metadata, report: &mut DebugLogCleanup) {
+    match std::fs::remove_file(path) {
+        Ok(()) => {
+            report.removed_files += 1;
+            report.reclaimed_bytes += meta.len();
+        }
+        Err(e) => log::warn!("删除过期调试日志失败 {}: {e}", path.display()),
+    }
+}
+
+/// 压缩单个已轮转的日志文件，成功后删除原文件
+fn compress_log(path: &Path) -> std::io::Result<()> {
+    let mut gz_name = path.as_os_str().to_owned();
+    gz_name.push(".gz");
+    let gz_path = PathBuf::from(gz_name);
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&gz_path)?,
+        flate2::Compression::default(),
+    );
+    let result = std::io::copy(&mut std::fs::File::open(path)?, &mut encoder)
+        .and_then(|_| encoder.finish())
+        .and_then(|mut file| file.flush());
+    match result {
+        Ok(()) => std::fs::remove_file(path),
+        Err(e) => {
+            let _ = std::fs::remove_file(&gz_path);
+            Err(e)
+        }
+    }
+}
+
+/// 把 `legacy` 中的调试日志迁移到 `target`，并按保留策略清理日志
+///
+/// `retention_days` 为 0 时不按时间清理，`max_total_mb` 为 0 时不限制总大小；
+/// `current` 为正在写入的文件名，不会被压缩或按大小删除
+pub fn migrate_debug_logs_between(
+    legacy: Option<&Path>,
+    target: &Path,
+    config: &DebugLogConfig,
+    current: &str,
+) -> DebugLogCleanup {
+    let mut report = DebugLogCleanup::default();
+    let retention_days = config.retention_days;
+    let cutoff = (retention_days > 0)
+        .then(|| SystemTime::now() - Duration::from_secs(u64::from(retention_days) * 24 * 60 * 60));
+
+    if let Some(legacy) = legacy.filter(|dir| *dir != target && dir.is_dir()) {
+        for (path, meta) in debug_log_files(legacy) {
+            if cutoff.is_some_and(|cutoff| is_expired(&meta, cutoff)) {
+                remove_expired(&path, &meta, &mut report);
+                continue;
+            }
+            if let Err(e) = std::fs::create_dir_all(target) {
+                log::warn!("创建调试日志目录失败 {}: {e}", target.display());
+                break;
+            }
+            let Some(name) = path.file_name() else {
+                continue;
+            };
+            let dest = target.join(name);
+            // 跨文件系统时 rename 会失败，退回为复制后删除
+            let moved = std::fs::rename(&path, &dest).is_ok()
+                || (std::fs::copy(&path, &dest).is_ok() && std::fs::remove_file(&path).is_ok());
+            if moved {
+                report.moved_files += 1;
+                report.moved_bytes += meta.len();
+            } else {
+                log::warn!("迁移调试日志失败: {}", path.display());
+            }
+        }
+        // 仅在目录已空时删除（目录中可能还有用户自己的文件）
+        report.legacy_dir_removed = std::fs::remove_dir(legacy).is_ok();
+    }
+
+    if let Some(cutoff) = cutoff {
+        for (path, meta) in debug_log_files(target) {
+            if is_expired(&meta, cutoff) {
+                remove_expired(&path, &meta, &mut report);
+            }
+        }
+    }
+
+    let is_current = |path: &Path| path.file_name().is_some_and(|n| n == current);
+    if config.compress_rotated {
+        for (path, meta) in debug_log_files(target) {
+            if is_current(&path) || path.extension().is_some_and(|ext| ext == "gz") {
+                continue;
+            }
+            match compress_log(&path) {
+                Ok(()) => {
+                    report.compressed_files += 1;
+                    let compressed = std::fs::metadata(path.with_extension("log.gz"))
+                        .map(|m| m.len())
+                        .unwrap_or(0);
+                    report.reclaimed_bytes += meta.len().saturating_sub(compressed);
+                }
+                Err(e) => log::warn!("压缩调试日志失败 {}: {e}", path.display()),
+            }
+        }
+    }
+
+    if config.max_total_mb > 0 {
+        let limit = config.max_total_mb.saturating_mul(1024 * 1024);
+        let mut files = debug_log_files(target);
+        files.sort_by_key(|(_, meta)| meta.modified().unwrap_or(SystemTime::UNIX_EPOCH));
+        let mut total: u64 = files.iter().map(|(_, meta)| meta.len()).sum();
+        for (path, meta) in files {
+            if total <= limit {
+                break;
+            }
+            if is_current(&path) {
+                continue;
+            }
+            total = total.saturating_sub(meta.len());
+            remove_expired(&path, &meta, &mut report);
+        }
+    }
+    report
+}
+
+/// 迁移旧版 ~/tmp/log 中的调试日志并按保留策略清理
+pub fn migrate_debug_logs(config: &DebugLogConfig) -> DebugLogCleanup {
+    let report = migrate_debug_logs_between(
+        legacy_debug_log_dir().as_deref(),
+        &debug_log_dir(),
+        config,
+        &debug_log_file_name(chrono::Local::now()),
+    );
+    if report != DebugLogCleanup::default() {
+        log::info!(
+            "调试日志清理完成：迁移 {} 个文件（{} 字节），压缩 {} 个文件，删除 {} 个文件（共释放 {} 字节）",
+            report.moved_files,
+            report.moved_bytes,
+            report.compressed_files,
+            report.removed_files,
+            report.reclaimed_bytes
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_legacy_debug_logs_and_prunes_expired() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let legacy = tmp.path().join("tmp").join("log");
+        let target = tmp.path().join("logs").join("debug");
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("cc-2026010100.log"), b"recent").unwrap();
+        let expired = legacy.join("cc-2020010100.log");
+        std::fs::write(&expired, b"expired!").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(&expired)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let config = DebugLogConfig {
+            retention_days: 7,
+            ..Default::default()
+        };
+        let report = migrate_debug_logs_between(Some(&legacy), &target, &config, "cc-x.log");
+        assert_eq!(
+            report,
+            DebugLogCleanup {
+                moved_files: 1,
+                moved_bytes: 6,
+                removed_files: 1,
+                reclaimed_bytes: 8,
+                legacy_dir_removed: true,
+                compressed_files: 0,
+            }
+        );
+        assert!(target.join("cc-2026010100.log").exists());
+        assert!(!legacy.exists());
+
+        // 旧目录中有其他文件时保留目录
+        std::fs::create_dir_all(&legacy).unwrap();
+        std::fs::write(legacy.join("notes.txt"), b"keep").unwrap();
+        let config = DebugLogConfig {
+            retention_days: 0,
+            ..Default::default()
+        };
+        let report = migrate_debug_logs_between(Some(&legacy), &target, &config, "cc-x.log");
+        assert!(!report.legacy_dir_removed);
+        assert!(legacy.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn compresses_rotated_logs_and_enforces_size_cap() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let dir = tmp.path();
+        let mb = vec![b'a'; 1024 * 1024];
+        for (i, name) in [
+            "cc-2026010100.log",
+            "cc-2026010101.log",
+            "cc-2026010102.log",
+        ]
+        .iter()
+        .enumerate()
+        {
+            let path = dir.join(name);
+            std::fs::write(&path, &mb).unwrap();
+            let modified = SystemTime::now() - Duration::from_secs((3 - i as u64) * 3600);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        // 仅限制总大小：删除最早的文件直到不超过 2MB，当前文件保留
+        let config = DebugLogConfig {
+            retention_days: 0,
+            max_total_mb: 2,
+            compress_rotated: false,
+            ..Default::default()
+        };
+        let report = migrate_debug_logs_between(None, dir, &config, "cc-2026010102.log");
+        assert_eq!(report.removed_files, 1);
+        assert!(!dir.join("cc-2026010100.log").exists());
+        assert!(dir.join("cc-2026010102.log").exists());
+
+        // 压缩已轮转的文件，当前文件不压缩
+        let config = DebugLogConfig {
+            compress_rotated: true,
+            ..config
+        };
+        let report = migrate_debug_logs_between(None, dir, &config, "cc-2026010102.log");
+        assert_eq!(report.compressed_files, 1);
+        assert!(dir.join("cc-2026010101.log.gz").exists());
+        assert!(!dir.join("cc-2026010101.log").exists());
+        assert!(dir.join("cc-2026010102.log").exists());
+    }
+}
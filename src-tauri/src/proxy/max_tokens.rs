@@ -0,0 +1,175 @@
+//! max_tokens 上限模块
+//!
+//! 部分中转站会直接以 400 拒绝 `max_tokens` 超过其限制的请求。
+//! 根据 Provider 配置的 `maxTokensLimit`，在转发前将输出长度字段截断到上限，
+//! 并可按需改写字段名（如上游只接受 `max_completion_tokens`）。
+//!
+//! ## 支持的字段
+//! - Anthropic / OpenAI Chat：`max_tokens`、`max_completion_tokens`
+//! - OpenAI Responses：`max_output_tokens`
+//! - Gemini：`generationConfig.maxOutputTokens`
+
+use super::system_prompt::PromptFormat;
+use crate::provider::{MaxTokensLimit, Provider};
+use serde_json::{Map, Value};
+
+/// 各格式下可能出现的输出长度字段（按优先级）
+fn candidate_fields(format: PromptFormat) -> &'static [&'static str] {
+    match format {
+        PromptFormat::Anthropic | PromptFormat::OpenAIChat => {
+            &["max_tokens", "max_completion_tokens"]
+        }
+        PromptFormat::OpenAIResponses => &["max_output_tokens"],
+        PromptFormat::Gemini => &["maxOutputTokens"],
+    }
+}
+
+fn get_limit(provider: &Provider) -> Option<&MaxTokensLimit> {
+    provider
+        .meta
+        .as_ref()?
+        .max_tokens_limit
+        .as_ref()
+        .filter(|limit| limit.cap > 0)
+}
+
+/// 对请求体应用 max_tokens 上限
+///
+/// 返回是否做了修改
+pub fn apply_max_tokens_limit(body: &mut Value, provider: &Provider, endpoint: &str) -> bool {
+    let Some(limit) = get_limit(provider) else {
+        return false;
+    };
+    let Some(format) = PromptFormat::from_endpoint(endpoint) else {
+        return false;
+    };
+
+    let target = match format {
+        PromptFormat::Gemini => body
+            .get_mut("generationConfig")
+            .and_then(|v| v.as_object_mut()),
+        _ => body.as_object_mut(),
+    };
+    let Some(obj) = target else {
+        return false;
+    };
+
+    let changed = clamp_fields(obj, candidate_fields(format), limit);
+    if changed {
+        log::debug!(
+            "[MaxTokens] 供应商 {} 输出长度已限制为 {}",
+            provider.name,
+            limit.cap
+        );
+    }
+    changed
+}
+
+fn clamp_fields(obj: &mut Map<String, Value>, fields: &[&str], limit: &MaxTokensLimit) -> bool {
+    let mut changed = false;
+
+    for field in fields {
+        let Some(value) = obj.get(*field).and_then(|v| v.as_u64()) else {
+            continue;
+        };
+        let clamped = value.min(limit.cap);
+
+        let rename = limit
+            .rename_to
+            .as_deref()
+            .filter(|name| !name.is_empty() && name != field);
+        match rename {
+            Some(name) => {
+                obj.remove(*field);
+                // 目标字段已存在时取两者较小值
+                let existing = obj.get(name).and_then(|v| v.as_u64());
+                let merged = existing.map_or(clamped, |e| e.min(clamped));
+                obj.insert(name.to_string(), Value::from(merged));
+                changed = true;
+            }
+            None if clamped != value => {
+                obj.insert(field.to_string(), Value::from(clamped));
+                changed = true;
+            }
+            None => {}
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider(cap: u64, rename_to: Option<&str>) -> Provider {
+        Provider::with_meta(
+            "p",
+            ProviderMeta {
+                max_tokens_limit: Some(MaxTokensLimit {
+                    cap,
+                    rename_to: rename_to.map(String::from),
+                }),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_clamps_anthropic_max_tokens() {
+        let mut body = json!({"max_tokens": 64000});
+        assert!(apply_max_tokens_limit(
+            &mut body,
+            &provider(8192, None),
+            "/v1/messages"
+        ));
+        assert_eq!(body["max_tokens"], 8192);
+    }
+
+    #[test]
+    fn test_below_cap_untouched() {
+        let mut body = json!({"max_tokens": 1024});
+        assert!(!apply_max_tokens_limit(
+            &mut body,
+            &provider(8192, None),
+            "/v1/messages"
+        ));
+        assert_eq!(body["max_tokens"], 1024);
+    }
+
+    #[test]
+    fn test_rename_field() {
+        let mut body = json!({"max_tokens": 1024});
+        assert!(apply_max_tokens_limit(
+            &mut body,
+            &provider(8192, Some("max_completion_tokens")),
+            "/v1/chat/completions"
+        ));
+        assert!(body.get("max_tokens").is_none());
+        assert_eq!(body["max_completion_tokens"], 1024);
+    }
+
+    #[test]
+    fn test_gemini_generation_config() {
+        let mut body = json!({"generationConfig": {"maxOutputTokens": 65536}});
+        assert!(apply_max_tokens_limit(
+            &mut body,
+            &provider(8192, None),
+            "/v1beta/models/gemini-2.5-pro:generateContent"
+        ));
+        assert_eq!(body["generationConfig"]["maxOutputTokens"], 8192);
+    }
+
+    #[test]
+    fn test_responses_field() {
+        let mut body = json!({"max_output_tokens": 100000});
+        assert!(apply_max_tokens_limit(
+            &mut body,
+            &provider(32000, None),
+            "/v1/responses"
+        ));
+        assert_eq!(body["max_output_tokens"], 32000);
+    }
+}
@@ -0,0 +1,314 @@
+//! Prometheus `/metrics` 端点与告警规则导出
+//!
+//! `/metrics` 以 Prometheus 文本格式导出每个供应商的累计请求数、错误数、消费与首字延迟直方图
+//! （数据来自请求日志）。告警规则根据应用内已配置的阈值生成，使外部监控与应用内行为一致：
+//! - 供应商错误率：各应用熔断器的错误率阈值与最小请求数
+//! - 预算消耗速率：供应商的每日 / 每月消费限额（按最近消耗速率预测是否超限）
+//! - 首字延迟 SLA：流式检查的“降级”延迟阈值（p95）
+
+use super::server::ProxyState;
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::services::usage_stats::ProviderMetrics;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// 首字延迟直方图桶上界（毫秒）
+const TTFT_BUCKETS_MS: [u64; 10] = [250, 500, 1000, 2000, 3000, 5000, 10000, 20000, 30000, 60000];
+
+/// 处理 `/metrics`
+pub async fn metrics(State(state): State<ProxyState>) -> Response {
+    match render_metrics(&state.db) {
+        Ok(body) => (
+            [(
+                header::CONTENT_TYPE,
+                "text/plain; version=0.0.4; charset=utf-8",
+            )],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            log::warn!("[metrics] 生成指标失败: {e}");
+            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+/// 转义 Prometheus 标签值 / PromQL 字符串
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// 各应用的供应商名称（`app_type:provider_id` -> name）
+fn provider_names(db: &Database) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let app = app_type.as_str();
+        if let Ok(providers) = db.get_all_providers(app) {
+            for (id, provider) in providers {
+                names.insert(format!("{app}:{id}"), provider.name);
+            }
+        }
+    }
+    names
+}
+
+fn render_metrics(db: &Database) -> Result<String, AppError> {
+    let rows = db.get_provider_metrics(&TTFT_BUCKETS_MS)?;
+    let names = provider_names(db);
+    Ok(format_metrics(&rows, &names))
+}
+
+fn format_metrics(rows: &[ProviderMetrics], names: &HashMap<String, String>) -> String {
+    let labels = |row: &ProviderMetrics| {
+        let key = format!("{}:{}", row.app_type, row.provider_id);
+        let name = names
+            .get(&key)
+            .map(String::as_str)
+            .unwrap_or(&row.provider_id);
+        format!(
+            "app_type=\"{}\",provider_id=\"{}\",provider_name=\"{}\"",
+            escape_label(&row.app_type),
+            escape_label(&row.provider_id),
+            escape_label(name)
+        )
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "# HELP ccswitch_requests_total Proxied requests per provider."
+    );
+    let _ = writeln!(out, "# TYPE ccswitch_requests_total counter");
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "ccswitch_requests_total{{{}}} {}",
+            labels(row),
+            row.requests
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP ccswitch_request_errors_total Proxied requests that ended with status >= 400."
+    );
+    let _ = writeln!(out, "# TYPE ccswitch_request_errors_total counter");
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "ccswitch_request_errors_total{{{}}} {}",
+            labels(row),
+            row.errors
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP ccswitch_cost_usd_total Accumulated request cost in USD."
+    );
+    let _ = writeln!(out, "# TYPE ccswitch_cost_usd_total counter");
+    for row in rows {
+        let _ = writeln!(
+            out,
+            "ccswitch_cost_usd_total{{{}}} {}",
+            labels(row),
+            row.cost_usd
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "# HELP ccswitch_first_token_seconds Time to first token of streaming requests."
+    );
+    let _ = writeln!(out, "# TYPE ccswitch_first_token_seconds histogram");
+    for row in rows {
+        let labels = labels(row);
+        for (bound, count) in TTFT_BUCKETS_MS.iter().zip(&row.ttft_buckets) {
+            let _ = writeln!(
+                out,
+                "ccswitch_first_token_seconds_bucket{{{labels},le=\"{}\"}} {count}",
+                *bound as f64 / 1000.0
+            );
+        }
+        let _ = writeln!(
+            out,
+            "ccswitch_first_token_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+            row.ttft_count
+        );
+        let _ = writeln!(
+            out,
+            "ccswitch_first_token_seconds_sum{{{labels}}} {}",
+            row.ttft_sum_ms as f64 / 1000.0
+        );
+        let _ = writeln!(
+            out,
+            "ccswitch_first_token_seconds_count{{{labels}}} {}",
+            row.ttft_count
+        );
+    }
+    out
+}
+
+/// 根据应用内阈值生成 Prometheus 告警规则文件（YAML）
+pub async fn generate_alert_rules(db: &Database) -> Result<String, AppError> {
+    let ttft_sla_seconds = db.get_stream_check_config()?.degraded_threshold_ms as f64 / 1000.0;
+    let mut rules = Vec::new();
+
+    for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+        let app = app_type.as_str();
+        let config = db.get_proxy_config_for_app(app).await?;
+        let selector = format!("app_type=\"{app}\"");
+
+        rules.push(json!({
+            "alert": "CcSwitchProviderErrorRateHigh",
+            "expr": format!(
+                "(sum by (provider_id, provider_name) (rate(ccswitch_request_errors_total{{{selector}}}[5m])) \
+                 / sum by (provider_id, provider_name) (rate(ccswitch_requests_total{{{selector}}}[5m]))) >= {} \
+                 and sum by (provider_id, provider_name) (increase(ccswitch_requests_total{{{selector}}}[5m])) >= {}",
+                config.circuit_error_rate_threshold, config.circuit_min_requests
+            ),
+            "for": "5m",
+            "labels": { "severity": "warning", "app_type": app },
+            "annotations": {
+                "summary": format!("{app} provider {{{{ $labels.provider_name }}}} error rate is above {:.0}%", config.circuit_error_rate_threshold * 100.0),
+            },
+        }));
+
+        rules.push(json!({
+            "alert": "CcSwitchFirstTokenSlaBreached",
+            "expr": format!(
+                "histogram_quantile(0.95, sum by (le, provider_id, provider_name) \
+                 (rate(ccswitch_first_token_seconds_bucket{{{selector}}}[10m]))) > {ttft_sla_seconds}"
+            ),
+            "for": "10m",
+            "labels": { "severity": "warning", "app_type": app },
+            "annotations": {
+                "summary": format!("{app} provider {{{{ $labels.provider_name }}}} p95 time to first token is above {ttft_sla_seconds}s"),
+            },
+        }));
+
+        for (id, provider) in db.get_all_providers(app)? {
+            let Some(meta) = provider.meta.as_ref() else {
+                continue;
+            };
+            let selector = format!("app_type=\"{app}\",provider_id=\"{}\"", escape_label(&id));
+            let limits = [
+                ("daily", meta.limit_daily_usd.as_deref(), "1h", 24.0),
+                ("monthly", meta.limit_monthly_usd.as_deref(), "1d", 30.0),
+            ];
+            for (period, limit, window, multiplier) in limits {
+                let Some(limit) = limit.and_then(|v| v.trim().parse::<f64>().ok()) else {
+                    continue;
+                };
+                rules.push(json!({
+                    "alert": "CcSwitchBudgetBurnRateHigh",
+                    "expr": format!(
+                        "increase(ccswitch_cost_usd_total{{{selector}}}[{window}]) * {multiplier} > {limit}"
+                    ),
+                    "for": "15m",
+                    "labels": {
+                        "severity": "warning",
+                        "app_type": app,
+                        "provider_id": id,
+                        "period": period,
+                    },
+                    "annotations": {
+                        "summary": format!(
+                            "{} is spending fast enough to exceed its {period} budget of ${limit}",
+                            provider.name
+                        ),
+                    },
+                }));
+            }
+        }
+    }
+
+    let document: Value = json!({
+        "groups": [{ "name": "cc-switch", "rules": rules }],
+    });
+    serde_yaml::to_string(&document)
+        .map_err(|e| AppError::Message(format!("生成告警规则失败: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::lock_conn;
+    use crate::provider::{Provider, ProviderMeta};
+
+    #[test]
+    fn formats_counters_and_histogram() {
+        let rows = vec![ProviderMetrics {
+            app_type: "claude".into(),
+            provider_id: "p1".into(),
+            requests: 3,
+            errors: 1,
+            cost_usd: 0.5,
+            ttft_count: 2,
+            ttft_sum_ms: 1500,
+            ttft_buckets: vec![0, 1, 1, 2, 2, 2, 2, 2, 2, 2],
+        }];
+        let names = HashMap::from([("claude:p1".to_string(), "My \"P1\"".to_string())]);
+        let text = format_metrics(&rows, &names);
+
+        let labels = r#"app_type="claude",provider_id="p1",provider_name="My \"P1\"""#;
+        assert!(text.contains(&format!("ccswitch_requests_total{{{labels}}} 3")));
+        assert!(text.contains(&format!("ccswitch_request_errors_total{{{labels}}} 1")));
+        assert!(text.contains(&format!(
+            "ccswitch_first_token_seconds_bucket{{{labels},le=\"0.5\"}} 1"
+        )));
+        assert!(text.contains(&format!(
+            "ccswitch_first_token_seconds_bucket{{{labels},le=\"+Inf\"}} 2"
+        )));
+        assert!(text.contains(&format!("ccswitch_first_token_seconds_sum{{{labels}}} 1.5")));
+    }
+
+    #[tokio::test]
+    async fn generates_rules_from_configured_thresholds() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let mut provider = Provider::with_id("p1".into(), "P1".into(), json!({}), None);
+        provider.meta = Some(ProviderMeta {
+            limit_daily_usd: Some("10".into()),
+            ..Default::default()
+        });
+        db.save_provider("claude", &provider)?;
+        {
+            let conn = lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model,
+                 latency_ms, first_token_ms, status_code, created_at)
+                 VALUES ('r1', 'p1', 'claude', 'm', 900, 400, 200, 1)",
+                [],
+            )?;
+        }
+
+        let rules = generate_alert_rules(&db).await?;
+        let parsed: Value = serde_yaml::from_str(&rules).unwrap();
+        let rules = parsed["groups"][0]["rules"].as_array().unwrap();
+        let budget = rules
+            .iter()
+            .find(|r| r["alert"] == "CcSwitchBudgetBurnRateHigh")
+            .unwrap();
+        assert_eq!(budget["labels"]["period"], "daily");
+        assert!(budget["expr"].as_str().unwrap().ends_with("* 24 > 10"));
+        assert!(rules
+            .iter()
+            .any(|r| r["alert"] == "CcSwitchProviderErrorRateHigh"
+                && r["expr"].as_str().unwrap().contains(">= 0.6")));
+
+        let metrics = render_metrics(&db)?;
+        assert!(metrics.contains("provider_name=\"P1\"} 1"));
+        Ok(())
+    }
+}
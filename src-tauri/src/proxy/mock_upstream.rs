@@ -0,0 +1,488 @@
+//! 模拟上游（离线演练）
+//!
+//! 以下两种情况下代理不请求上游，而是按请求格式在本地合成响应（支持流式），
+//! 用于在离线环境与 CI 中验证 Claude Code 等客户端的接入、路由规则与代理处理流程：
+//!
+//! - 供应商配置了 `meta.mock`（内置的 Mock 供应商）：固定回复文本与模拟延迟可配置
+//! - 开启离线演练模式：所有供应商的请求都返回模拟响应，供应商选择、故障转移与用量记录照常执行。
+//!   可通过 Tauri 命令切换（仅保存在内存中，重启后失效），或设置环境变量 `CC_SWITCH_DRY_RUN=1`
+//!
+//! 支持 Anthropic Messages、OpenAI Chat Completions、OpenAI Responses 与 Gemini generateContent，
+//! 响应携带 `x-cc-switch-mock: 1` 头，用量按本地 token 估算填写。
+
+use super::token_estimate::{estimate_text_tokens, estimate_value_tokens};
+use super::ProxyError;
+use crate::app_config::AppType;
+use crate::provider::{MockUpstreamConfig, Provider, ProviderMeta};
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// 开启离线演练模式的环境变量
+pub const DRY_RUN_ENV: &str = "CC_SWITCH_DRY_RUN";
+
+/// 回显时保留的用户消息长度（字符）
+const ECHO_MAX_CHARS: usize = 200;
+
+/// 流式响应每个增量事件的文本长度（字符）
+const STREAM_CHUNK_CHARS: usize = 16;
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// 开启或关闭离线演练模式
+pub fn set_dry_run(enabled: bool) {
+    if DRY_RUN.swap(enabled, Ordering::SeqCst) != enabled {
+        log::warn!(
+            "[MOCK] 离线演练模式已{}",
+            if enabled { "开启" } else { "关闭" }
+        );
+    }
+}
+
+/// 是否处于离线演练模式（命令开启或设置了环境变量）
+pub fn is_dry_run() -> bool {
+    DRY_RUN.load(Ordering::SeqCst)
+        || std::env::var(DRY_RUN_ENV)
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+}
+
+/// 该供应商的请求是否应由模拟上游响应
+pub fn should_mock(provider: &Provider) -> bool {
+    mock_config(provider).is_some() || is_dry_run()
+}
+
+fn mock_config(provider: &Provider) -> Option<&MockUpstreamConfig> {
+    provider.meta.as_ref().and_then(|m| m.mock.as_ref())
+}
+
+/// 响应格式（按端点判断）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Anthropic,
+    ChatCompletions,
+    Responses,
+    Gemini,
+}
+
+fn detect_format(endpoint: &str) -> Format {
+    let path = endpoint.split('?').next().unwrap_or(endpoint);
+    if path.contains("generateContent") {
+        Format::Gemini
+    } else if path.ends_with("/responses") {
+        Format::Responses
+    } else if path.ends_with("chat/completions") {
+        Format::ChatCompletions
+    } else {
+        Format::Anthropic
+    }
+}
+
+/// 请求中的模型（Gemini 从 `/v1beta/models/<model>:generateContent` 中提取）
+fn request_model(endpoint: &str, body: &Value) -> String {
+    if let Some(model) = body.get("model").and_then(|m| m.as_str()) {
+        return model.to_string();
+    }
+    endpoint
+        .split("/models/")
+        .nth(1)
+        .and_then(|rest| rest.split([':', '?']).next())
+        .filter(|model| !model.is_empty())
+        .unwrap_or("mock")
+        .to_string()
+}
+
+/// 提取 content / parts 中的文本
+fn collect_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// 最后一条用户消息（Anthropic / Chat 的 `messages`、Responses 的 `input`、Gemini 的 `contents`）
+fn last_user_text(body: &Value) -> Option<String> {
+    if let Some(input) = body.get("input").and_then(|i| i.as_str()) {
+        return Some(input.to_string());
+    }
+    ["messages", "input", "contents"]
+        .iter()
+        .filter_map(|key| body.get(*key).and_then(|v| v.as_array()))
+        .flat_map(|items| items.iter().rev())
+        .find(|item| item.get("role").and_then(|r| r.as_str()) == Some("user"))
+        .map(|item| {
+            item.get("content")
+                .or_else(|| item.get("parts"))
+                .map(collect_text)
+                .unwrap_or_default()
+        })
+}
+
+/// 回复文本：配置的固定文本，否则回显最后一条用户消息
+fn reply_text(config: Option<&MockUpstreamConfig>, model: &str, body: &Value) -> String {
+    if let Some(text) = config.and_then(|c| c.response.clone()) {
+        return text;
+    }
+    let echo: String = last_user_text(body)
+        .unwrap_or_default()
+        .chars()
+        .take(ECHO_MAX_CHARS)
+        .collect();
+    format!("[cc-switch mock] 已收到发往 {model} 的请求（未连接上游）。最后一条用户消息：{echo}")
+}
+
+fn chunks(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(STREAM_CHUNK_CHARS)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+fn sse_event(name: Option<&str>, data: &Value) -> String {
+    match name {
+        Some(name) => format!("event: {name}\ndata: {data}\n\n"),
+        None => format!("data: {data}\n\n"),
+    }
+}
+
+/// 合成响应体，返回 (content-type, 响应体)
+fn build_body(
+    format: Format,
+    streaming: bool,
+    model: &str,
+    text: &str,
+    input_tokens: u64,
+) -> (&'static str, String) {
+    let output_tokens = estimate_text_tokens(text);
+    let id = format!("mock-{}", uuid::Uuid::new_v4().simple());
+
+    if !streaming {
+        let body = match format {
+            Format::Anthropic => json!({
+                "id": id,
+                "type": "message",
+                "role": "assistant",
+                "model": model,
+                "content": [{ "type": "text", "text": text }],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": { "input_tokens": input_tokens, "output_tokens": output_tokens }
+            }),
+            Format::ChatCompletions => json!({
+                "id": id,
+                "object": "chat.completion",
+                "created": chrono::Utc::now().timestamp(),
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": text },
+                    "finish_reason": "stop"
+                }],
+                "usage": {
+                    "prompt_tokens": input_tokens,
+                    "completion_tokens": output_tokens,
+                    "total_tokens": input_tokens + output_tokens
+                }
+            }),
+            Format::Responses => responses_object(&id, model, text, input_tokens, output_tokens),
+            Format::Gemini => gemini_chunk(model, text, input_tokens, output_tokens),
+        };
+        return ("application/json", body.to_string());
+    }
+
+    let pieces = chunks(text);
+    let mut events = String::new();
+    match format {
+        Format::Anthropic => {
+            events.push_str(&sse_event(
+                Some("message_start"),
+                &json!({
+                    "type": "message_start",
+                    "message": {
+                        "id": id, "type": "message", "role": "assistant", "model": model,
+                        "content": [], "stop_reason": null, "stop_sequence": null,
+                        "usage": { "input_tokens": input_tokens, "output_tokens": 0 }
+                    }
+                }),
+            ));
+            events.push_str(&sse_event(
+                Some("content_block_start"),
+                &json!({
+                    "type": "content_block_start",
+                    "index": 0,
+                    "content_block": { "type": "text", "text": "" }
+                }),
+            ));
+            for piece in &pieces {
+                events.push_str(&sse_event(
+                    Some("content_block_delta"),
+                    &json!({
+                        "type": "content_block_delta",
+                        "index": 0,
+                        "delta": { "type": "text_delta", "text": piece }
+                    }),
+                ));
+            }
+            events.push_str(&sse_event(
+                Some("content_block_stop"),
+                &json!({ "type": "content_block_stop", "index": 0 }),
+            ));
+            events.push_str(&sse_event(
+                Some("message_delta"),
+                &json!({
+                    "type": "message_delta",
+                    "delta": { "stop_reason": "end_turn", "stop_sequence": null },
+                    "usage": { "input_tokens": input_tokens, "output_tokens": output_tokens }
+                }),
+            ));
+            events.push_str(&sse_event(
+                Some("message_stop"),
+                &json!({ "type": "message_stop" }),
+            ));
+        }
+        Format::ChatCompletions => {
+            let created = chrono::Utc::now().timestamp();
+            let chunk = |delta: Value, finish: Option<&str>| {
+                json!({
+                    "id": id,
+                    "object": "chat.completion.chunk",
+                    "created": created,
+                    "model": model,
+                    "choices": [{ "index": 0, "delta": delta, "finish_reason": finish }]
+                })
+            };
+            events.push_str(&sse_event(
+                None,
+                &chunk(json!({ "role": "assistant", "content": "" }), None),
+            ));
+            for piece in &pieces {
+                events.push_str(&sse_event(None, &chunk(json!({ "content": piece }), None)));
+            }
+            let mut last = chunk(json!({}), Some("stop"));
+            last["usage"] = json!({
+                "prompt_tokens": input_tokens,
+                "completion_tokens": output_tokens,
+                "total_tokens": input_tokens + output_tokens
+            });
+            events.push_str(&sse_event(None, &last));
+            events.push_str("data: [DONE]\n\n");
+        }
+        Format::Responses => {
+            let completed = responses_object(&id, model, text, input_tokens, output_tokens);
+            let mut created = completed.clone();
+            created["status"] = json!("in_progress");
+            created["output"] = json!([]);
+            created["usage"] = Value::Null;
+            events.push_str(&sse_event(
+                Some("response.created"),
+                &json!({ "type": "response.created", "response": created }),
+            ));
+            for piece in &pieces {
+                events.push_str(&sse_event(
+                    Some("response.output_text.delta"),
+                    &json!({
+                        "type": "response.output_text.delta",
+                        "output_index": 0,
+                        "content_index": 0,
+                        "delta": piece
+                    }),
+                ));
+            }
+            events.push_str(&sse_event(
+                Some("response.completed"),
+                &json!({ "type": "response.completed", "response": completed }),
+            ));
+        }
+        Format::Gemini => {
+            let last = pieces.len().saturating_sub(1);
+            for (i, piece) in pieces.iter().enumerate() {
+                let mut chunk = gemini_chunk(model, piece, input_tokens, output_tokens);
+                if i != last {
+                    if let Some(candidate) = chunk["candidates"][0].as_object_mut() {
+                        candidate.remove("finishReason");
+                    }
+                }
+                events.push_str(&sse_event(None, &chunk));
+            }
+        }
+    }
+    ("text/event-stream", events)
+}
+
+fn responses_object(
+    id: &str,
+    model: &str,
+    text: &str,
+    input_tokens: u64,
+    output_tokens: u64,
+) -> Value {
+    json!({
+        "id": id,
+        "object": "response",
+        "created_at": chrono::Utc::now().timestamp(),
+        "status": "completed",
+        "model": model,
+        "output": [{
+            "type": "message",
+            "id": format!("msg_{id}"),
+            "status": "completed",
+            "role": "assistant",
+            "content": [{ "type": "output_text", "text": text, "annotations": [] }]
+        }],
+        "usage": {
+            "input_tokens": input_tokens,
+            "output_tokens": output_tokens,
+            "total_tokens": input_tokens + output_tokens
+        }
+    })
+}
+
+fn gemini_chunk(model: &str, text: &str, input_tokens: u64, output_tokens: u64) -> Value {
+    json!({
+        "candidates": [{
+            "content": { "role": "model", "parts": [{ "text": text }] },
+            "finishReason": "STOP",
+            "index": 0
+        }],
+        "usageMetadata": {
+            "promptTokenCount": input_tokens,
+            "candidatesTokenCount": output_tokens,
+            "totalTokenCount": input_tokens + output_tokens
+        },
+        "modelVersion": model
+    })
+}
+
+/// 构造模拟的上游响应
+pub async fn respond(
+    provider: &Provider,
+    endpoint: &str,
+    body: &Value,
+) -> Result<reqwest::Response, ProxyError> {
+    let config = mock_config(provider);
+    if let Some(ms) = config.and_then(|c| c.latency_ms).filter(|ms| *ms > 0) {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+    }
+
+    let format = detect_format(endpoint);
+    let streaming = body.get("stream").and_then(|s| s.as_bool()) == Some(true)
+        || endpoint.contains("streamGenerateContent");
+    let model = request_model(endpoint, body);
+    let text = reply_text(config, &model, body);
+    let (content_type, body) = build_body(
+        format,
+        streaming,
+        &model,
+        &text,
+        estimate_value_tokens(body),
+    );
+
+    log::info!(
+        "[MOCK] 模拟 {} 的响应（{format:?}{}）",
+        provider.name,
+        if streaming { "，流式" } else { "" }
+    );
+    let response = axum::http::Response::builder()
+        .status(StatusCode::OK)
+        .header("content-type", content_type)
+        .header("x-cc-switch-mock", "1")
+        .body(body)
+        .map_err(|e| ProxyError::Internal(format!("构造模拟响应失败: {e}")))?;
+    Ok(reqwest::Response::from(response))
+}
+
+/// 内置 Mock 供应商（指向占位地址并附带占位密钥，所有请求由模拟上游响应）
+pub fn mock_provider(app_type: &AppType) -> Provider {
+    let settings_config = match app_type {
+        AppType::Claude => json!({
+            "env": {
+                "ANTHROPIC_BASE_URL": "http://127.0.0.1/mock",
+                "ANTHROPIC_AUTH_TOKEN": "mock"
+            }
+        }),
+        AppType::Codex => json!({
+            "auth": { "OPENAI_API_KEY": "mock" },
+            "config": "model_provider = \"mock\"\nmodel = \"gpt-5\"\n\n[model_providers.mock]\nname = \"mock\"\nbase_url = \"http://127.0.0.1/mock/v1\"\nwire_api = \"responses\"\n"
+        }),
+        AppType::Gemini => json!({
+            "env": {
+                "GOOGLE_GEMINI_BASE_URL": "http://127.0.0.1/mock",
+                "GEMINI_API_KEY": "mock"
+            }
+        }),
+    };
+    let mut provider = Provider::with_id(
+        format!("mock-{}", uuid::Uuid::new_v4().simple()),
+        "Mock（离线）".to_string(),
+        settings_config,
+        None::<String>,
+    );
+    provider.meta = Some(ProviderMeta {
+        mock: Some(MockUpstreamConfig::default()),
+        ..Default::default()
+    });
+    provider
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_format_and_model() {
+        assert_eq!(detect_format("/v1/messages"), Format::Anthropic);
+        assert_eq!(
+            detect_format("/v1/chat/completions"),
+            Format::ChatCompletions
+        );
+        assert_eq!(detect_format("/v1/responses"), Format::Responses);
+        assert_eq!(
+            detect_format("/v1beta/models/gemini-pro:streamGenerateContent?alt=sse"),
+            Format::Gemini
+        );
+        assert_eq!(
+            request_model("/v1beta/models/gemini-pro:generateContent", &json!({})),
+            "gemini-pro"
+        );
+    }
+
+    #[test]
+    fn echoes_last_user_message() {
+        let body = json!({
+            "messages": [
+                { "role": "user", "content": "first" },
+                { "role": "assistant", "content": "ok" },
+                { "role": "user", "content": [{ "type": "text", "text": "ping" }] }
+            ]
+        });
+        assert!(reply_text(None, "claude", &body).ends_with("ping"));
+
+        let config = MockUpstreamConfig {
+            response: Some("pong".into()),
+            latency_ms: None,
+        };
+        assert_eq!(reply_text(Some(&config), "claude", &body), "pong");
+    }
+
+    #[test]
+    fn builds_streaming_anthropic_events() {
+        let (content_type, body) = build_body(Format::Anthropic, true, "claude", "hello", 3);
+        assert_eq!(content_type, "text/event-stream");
+        assert!(body.starts_with("event: message_start\n"));
+        assert!(body.contains("\"text\":\"hello\""));
+        assert!(body
+            .trim_end()
+            .ends_with(r#"data: {"type":"message_stop"}"#));
+
+        let (_, body) = build_body(Format::ChatCompletions, false, "gpt", "hello", 3);
+        let value: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(value["choices"][0]["message"]["content"], "hello");
+        assert_eq!(value["usage"]["prompt_tokens"], 3);
+    }
+}
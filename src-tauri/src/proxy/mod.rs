@@ -2,30 +2,86 @@
 //!
 //! 提供本地HTTP代理服务，支持多Provider故障转移和请求透传
 
+pub mod access_token;
+pub mod activity;
+mod admin_api;
+pub(crate) mod alert_rules;
+pub mod anthropic_version;
+pub mod auth_scheme;
+pub mod batches;
 pub mod body_filter;
+pub mod budget;
 pub mod circuit_breaker;
+pub mod client_limiter;
+pub mod content_encoding;
+pub mod context_window;
+pub mod control;
+pub mod conversation_capture;
+pub(crate) mod cost_alert;
+pub mod cost_annotation;
 pub mod debug_log;
+pub mod discovery;
 pub mod error;
 pub mod error_mapper;
+pub mod experiment;
+pub(crate) mod failback;
 pub(crate) mod failover_switch;
+pub(crate) mod failure_alert;
+pub mod fault_injection;
+pub mod files;
 mod forwarder;
 pub mod handler_config;
 pub mod handler_context;
 mod handlers;
+pub mod header_filter;
+pub mod header_rules;
 mod health;
+mod healthz;
 pub mod http_client;
+pub mod ip_allowlist;
+pub mod journal;
+pub mod key_pool;
+pub mod lan_access;
 pub mod log_codes;
+pub mod log_redaction;
+pub mod log_retention;
+pub mod max_tokens;
+pub mod metrics;
+pub mod mock_upstream;
 pub mod model_mapper;
+pub mod otel;
+pub mod passthrough;
+pub mod prompt_cache;
 pub mod provider_router;
+pub mod provider_score;
 pub mod providers;
 pub mod rate_limit_retry;
+pub mod rate_limit_sim;
+pub mod replay;
+pub mod request_body;
+pub mod request_inspection;
+pub mod request_limit;
+pub mod response_cache;
 pub mod response_handler;
 pub mod response_processor;
+pub mod sampling;
+pub mod secret_redaction;
 pub(crate) mod server;
 pub mod session;
+pub mod shadow;
+pub mod sse_keepalive;
+pub mod stream_buffer;
+pub mod system_prompt;
+pub mod team_gateway;
+pub mod thinking_filter;
 pub mod thinking_rectifier;
+pub mod tls;
+pub mod token_estimate;
+pub mod transcript;
 pub(crate) mod types;
 pub mod usage;
+pub mod webhook;
+pub mod websocket;
 
 // 公开导出给外部使用（commands, services等模块需要）
 #[allow(unused_imports)]
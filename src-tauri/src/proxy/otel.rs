@@ -0,0 +1,377 @@
+//! OpenTelemetry 链路追踪
+//!
+//! 为每个代理请求生成一条 trace，包含以下 span：
+//! - `proxy.receive`：根 span，从接收请求到响应（含流式响应）结束
+//! - `proxy.transform`：每次转发尝试中的请求体改写（模型映射、参数覆盖、字段过滤等）
+//! - `proxy.upstream`：每次转发尝试中等待上游响应头的过程
+//! - `proxy.stream`：流式响应体的透传过程
+//!
+//! 根 span 在请求上下文与流式响应都释放后结束，随后以 OTLP/HTTP JSON 格式
+//! 异步上报到配置的 collector（`{endpoint}/v1/traces`）。`proxy.upstream` span 的
+//! `ccswitch.request_id` 属性与调试日志中的请求 ID 一致，便于关联排查。
+//! 配置存储在 settings 表（`otel_config`）中。
+
+use super::types::OtelConfig;
+use crate::database::Database;
+use bytes::Bytes;
+use futures::stream::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCOPE_NAME: &str = "cc-switch.proxy";
+
+/// Span 类型（对应 OTLP SpanKind）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Internal = 1,
+    Server = 2,
+    Client = 3,
+}
+
+/// Span 属性值
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttrValue {
+    Str(String),
+    Int(i64),
+}
+
+impl From<&str> for AttrValue {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+impl From<String> for AttrValue {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<i64> for AttrValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<u16> for AttrValue {
+    fn from(value: u16) -> Self {
+        Self::Int(value as i64)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SpanData {
+    span_id: String,
+    parent_span_id: Option<String>,
+    name: &'static str,
+    kind: SpanKind,
+    start_ns: u64,
+    end_ns: u64,
+    attributes: Vec<(&'static str, AttrValue)>,
+    error: Option<String>,
+}
+
+/// 单个请求的 trace
+///
+/// 最后一个引用释放时结束根 span 并上报
+pub struct RequestTrace {
+    config: OtelConfig,
+    trace_id: String,
+    root: Mutex<SpanData>,
+    spans: Mutex<Vec<SpanData>>,
+}
+
+impl RequestTrace {
+    /// 开始一条新的 trace（未启用时返回 None）
+    pub fn start(
+        db: &Database,
+        app_type: &str,
+        model: &str,
+        session_id: &str,
+    ) -> Option<Arc<Self>> {
+        let config = db.get_otel_config().unwrap_or_default();
+        if !config.enabled || config.endpoint.trim().is_empty() {
+            return None;
+        }
+        Some(Arc::new(Self::new(config, app_type, model, session_id)))
+    }
+
+    fn new(config: OtelConfig, app_type: &str, model: &str, session_id: &str) -> Self {
+        let root = SpanData {
+            span_id: new_span_id(),
+            parent_span_id: None,
+            name: "proxy.receive",
+            kind: SpanKind::Server,
+            start_ns: now_ns(),
+            end_ns: 0,
+            attributes: vec![
+                ("ccswitch.app_type", app_type.into()),
+                ("ccswitch.session_id", session_id.into()),
+                ("gen_ai.request.model", model.into()),
+            ],
+            error: None,
+        };
+        Self {
+            config,
+            trace_id: uuid::Uuid::new_v4().simple().to_string(),
+            root: Mutex::new(root),
+            spans: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// trace ID（32 位十六进制）
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// 开始一个子 span，守卫释放时结束
+    pub fn span(self: &Arc<Self>, name: &'static str, kind: SpanKind) -> SpanGuard {
+        let parent = self.root.lock().map(|r| r.span_id.clone()).ok();
+        SpanGuard {
+            trace: self.clone(),
+            data: Some(SpanData {
+                span_id: new_span_id(),
+                parent_span_id: parent,
+                name,
+                kind,
+                start_ns: now_ns(),
+                end_ns: 0,
+                attributes: Vec::new(),
+                error: None,
+            }),
+        }
+    }
+
+    /// 设置根 span 属性
+    pub fn set_attribute(&self, key: &'static str, value: impl Into<AttrValue>) {
+        if let Ok(mut root) = self.root.lock() {
+            root.attributes.push((key, value.into()));
+        }
+    }
+
+    /// 标记请求失败
+    pub fn set_error(&self, message: impl Into<String>) {
+        if let Ok(mut root) = self.root.lock() {
+            root.error = Some(message.into());
+        }
+    }
+
+    /// 构造 OTLP/HTTP JSON 请求体
+    fn to_otlp(&self, root: &SpanData, spans: &[SpanData]) -> Value {
+        let spans: Vec<Value> = std::iter::once(root)
+            .chain(spans.iter())
+            .map(|span| span_json(&self.trace_id, span))
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [attr_json("service.name", &self.config.service_name.as_str().into())]
+                },
+                "scopeSpans": [{
+                    "scope": { "name": SCOPE_NAME },
+                    "spans": spans
+                }]
+            }]
+        })
+    }
+}
+
+impl Drop for RequestTrace {
+    fn drop(&mut self) {
+        let mut root = match self.root.lock() {
+            Ok(root) => root.clone(),
+            Err(_) => return,
+        };
+        root.end_ns = now_ns();
+        let spans = self.spans.lock().map(|s| s.clone()).unwrap_or_default();
+        let payload = self.to_otlp(&root, &spans);
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(export(self.config.clone(), payload));
+            }
+            Err(_) => log::debug!("[OTel] 无可用运行时，丢弃 trace {}", self.trace_id),
+        }
+    }
+}
+
+/// 子 span 守卫
+pub struct SpanGuard {
+    trace: Arc<RequestTrace>,
+    data: Option<SpanData>,
+}
+
+impl SpanGuard {
+    /// 设置 span 属性
+    pub fn set_attribute(&mut self, key: &'static str, value: impl Into<AttrValue>) {
+        if let Some(data) = self.data.as_mut() {
+            data.attributes.push((key, value.into()));
+        }
+    }
+
+    /// 标记 span 失败
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        if let Some(data) = self.data.as_mut() {
+            data.error = Some(message.into());
+        }
+    }
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        if let Some(mut data) = self.data.take() {
+            data.end_ns = now_ns();
+            if let Ok(mut spans) = self.trace.spans.lock() {
+                spans.push(data);
+            }
+        }
+    }
+}
+
+/// 为流式响应体包裹 `proxy.stream` span，流结束（或客户端断开）时结束
+pub fn instrument_stream<S>(
+    stream: S,
+    trace: Option<Arc<RequestTrace>>,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+{
+    async_stream::stream! {
+        let mut span = trace.as_ref().map(|t| t.span("proxy.stream", SpanKind::Internal));
+        let mut bytes: i64 = 0;
+        tokio::pin!(stream);
+        while let Some(chunk) = stream.next().await {
+            match &chunk {
+                Ok(data) => bytes += data.len() as i64,
+                Err(e) => {
+                    if let Some(span) = span.as_mut() {
+                        span.set_error(e.to_string());
+                    }
+                }
+            }
+            yield chunk;
+        }
+        if let Some(span) = span.as_mut() {
+            span.set_attribute("ccswitch.stream.bytes", bytes);
+        }
+    }
+}
+
+/// 上报 trace 到 collector
+async fn export(config: OtelConfig, payload: Value) {
+    let url = traces_url(&config.endpoint);
+    let mut request = super::http_client::get()
+        .post(&url)
+        .timeout(std::time::Duration::from_secs(10))
+        .json(&payload);
+    for (key, value) in &config.headers {
+        request = request.header(key, value);
+    }
+    match request.send().await {
+        Ok(resp) if resp.status().is_success() => {}
+        Ok(resp) => log::debug!("[OTel] 上报 trace 失败: HTTP {}", resp.status()),
+        Err(e) => log::debug!("[OTel] 上报 trace 失败: {e}"),
+    }
+}
+
+/// collector 地址：未包含 `/v1/traces` 路径时自动追加
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{endpoint}/v1/traces")
+    }
+}
+
+fn span_json(trace_id: &str, span: &SpanData) -> Value {
+    let status = match &span.error {
+        Some(message) => json!({ "code": 2, "message": message }),
+        None => json!({ "code": 1 }),
+    };
+    json!({
+        "traceId": trace_id,
+        "spanId": span.span_id,
+        "parentSpanId": span.parent_span_id.as_deref().unwrap_or_default(),
+        "name": span.name,
+        "kind": span.kind as i32,
+        "startTimeUnixNano": span.start_ns.to_string(),
+        "endTimeUnixNano": span.end_ns.to_string(),
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| attr_json(key, value))
+            .collect::<Vec<_>>(),
+        "status": status
+    })
+}
+
+fn attr_json(key: &str, value: &AttrValue) -> Value {
+    let value = match value {
+        AttrValue::Str(s) => json!({ "stringValue": s }),
+        // OTLP JSON 中 int64 以字符串表示
+        AttrValue::Int(i) => json!({ "intValue": i.to_string() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn new_span_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> OtelConfig {
+        OtelConfig {
+            enabled: true,
+            endpoint: "http://localhost:4318".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_otlp_payload_with_child_spans() {
+        let trace = Arc::new(RequestTrace::new(config(), "claude", "claude-sonnet", "s1"));
+        {
+            let mut upstream = trace.span("proxy.upstream", SpanKind::Client);
+            upstream.set_attribute("http.response.status_code", 502u16);
+            upstream.set_error("bad gateway");
+        }
+        trace.set_attribute("ccswitch.provider_id", "p1");
+
+        let root = trace.root.lock().unwrap().clone();
+        let spans = trace.spans.lock().unwrap().clone();
+        let payload = trace.to_otlp(&root, &spans);
+        let spans = &payload["resourceSpans"][0]["scopeSpans"][0]["spans"];
+
+        assert_eq!(spans.as_array().unwrap().len(), 2);
+        assert_eq!(spans[0]["name"], "proxy.receive");
+        assert_eq!(spans[0]["traceId"].as_str().unwrap().len(), 32);
+        assert_eq!(spans[1]["parentSpanId"], spans[0]["spanId"]);
+        assert_eq!(spans[1]["kind"], 3);
+        assert_eq!(spans[1]["status"]["code"], 2);
+        assert_eq!(spans[1]["attributes"][0]["value"]["intValue"], "502");
+    }
+
+    #[test]
+    fn appends_traces_path_to_endpoint() {
+        assert_eq!(
+            traces_url("http://localhost:4318/"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_url("https://otel.example.com/v1/traces"),
+            "https://otel.example.com/v1/traces"
+        );
+    }
+}
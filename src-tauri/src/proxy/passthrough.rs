@@ -0,0 +1,173 @@
+//! 原样透传的 Claude 辅助接口
+//!
+//! Message Batches、Files 等接口不涉及模型路由与格式转换，请求体（JSON 或 multipart 表单）
+//! 以原始字节边接收边转发到 Claude 供应商（HMAC 认证需对完整请求体签名时除外），
+//! 代理只负责注入供应商凭据、版本头与请求头改写规则，响应流式返回客户端。这类请求不经过故障转移（重放上传或创建请求会产生重复资源），也不记录用量。
+
+use super::{
+    anthropic_version, auth_scheme, content_encoding, forwarder,
+    header_filter::HeaderFilter,
+    header_rules, key_pool,
+    providers::get_adapter,
+    request_limit::{self, BodyLimit},
+    server::ProxyState,
+    ProxyError,
+};
+use crate::app_config::AppType;
+use crate::provider::{AuthSchemeType, Provider};
+use axum::{
+    body::{Body, HttpBody},
+    http::{header::CONTENT_LENGTH, request::Parts, HeaderMap, StatusCode, Uri},
+    response::Response,
+};
+
+/// 去掉 `/claude` 前缀，得到上游路径（含查询参数）
+pub fn upstream_endpoint(uri: &Uri) -> String {
+    let path = uri.path();
+    let path = path.strip_prefix("/claude").unwrap_or(path);
+    match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    }
+}
+
+/// 当前首选的 Claude 供应商
+pub async fn current_provider(state: &ProxyState) -> Result<Provider, ProxyError> {
+    state
+        .provider_router
+        .select_providers("claude")
+        .await
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .next()
+        .ok_or(ProxyError::NoAvailableProvider)
+}
+
+/// 将请求原样转发到供应商（`feature` 用于日志与错误提示）
+pub async fn send(
+    feature: &str,
+    provider: &Provider,
+    endpoint: &str,
+    parts: &Parts,
+    body: Body,
+) -> Result<reqwest::Response, ProxyError> {
+    let (method, headers) = (parts.method.clone(), &parts.headers);
+    let adapter = get_adapter(&AppType::Claude);
+    if adapter.needs_transform(provider) {
+        return Err(ProxyError::InvalidRequest(format!(
+            "供应商 {} 需要格式转换，不支持 {feature}",
+            provider.name
+        )));
+    }
+    let base_url = adapter.extract_base_url(provider)?;
+    let url = adapter.build_url(&base_url, endpoint);
+    log::info!("[{feature}] {method} {endpoint} -> {}", provider.name);
+
+    let client = super::http_client::get_for_provider(provider).map_err(ProxyError::ConfigError)?;
+    let mut request = client.request(method, &url);
+
+    // content-type 随其他请求头透传，multipart 表单的 boundary 保持不变
+    let header_filter = HeaderFilter::from_provider(provider);
+    for (key, value) in headers {
+        if header_filter.should_forward(key.as_str()) {
+            request = request.header(key, value);
+        }
+    }
+    // Files 等测试版接口依赖客户端声明的 beta 标记
+    if let Some(beta) = headers.get("anthropic-beta") {
+        request = request.header("anthropic-beta", beta);
+    }
+    request = request.header("accept-encoding", "identity");
+
+    let mut auth = adapter.extract_auth(provider);
+    key_pool::override_auth(provider, &mut auth);
+    let auth_scheme = auth_scheme::get_auth_scheme(provider);
+    if let (Some(auth), None) = (&auth, auth_scheme) {
+        request = adapter.add_auth_headers(request, auth);
+    }
+    let version = anthropic_version::resolve_version(
+        headers
+            .get("anthropic-version")
+            .and_then(|v| v.to_str().ok()),
+        anthropic_version::get_pinned_version(provider),
+    );
+    request = request.header("anthropic-version", version.upstream);
+
+    let limit = parts.extensions.get::<BodyLimit>().copied();
+    if auth_scheme.is_some_and(|s| s.kind == AuthSchemeType::Hmac) {
+        // HMAC 签名需要完整请求体
+        let bytes = request_limit::read_limited(body, limit).await?;
+        if !bytes.is_empty() {
+            request = request.body(bytes);
+        }
+    } else if !body.is_end_stream() {
+        // 已知长度时保留 content-length，避免上游拒绝分块上传
+        if let Some(length) = body.size_hint().exact() {
+            request = request.header(CONTENT_LENGTH, length);
+        }
+        request = request.body(request_limit::stream_limited(body, limit));
+    }
+    let mut request = request
+        .build()
+        .map_err(|e| ProxyError::ForwardFailed(format!("构建请求失败: {e}")))?;
+    if let (Some(auth), Some(scheme)) = (&auth, auth_scheme) {
+        auth_scheme::apply_auth_scheme(&mut request, scheme, &auth.api_key)?;
+    }
+    if let Some(rules) = header_rules::get_header_rules(provider) {
+        header_rules::apply_header_rules(
+            request.headers_mut(),
+            rules,
+            auth.as_ref().map(|a| a.api_key.as_str()),
+        );
+    }
+
+    let response = client.execute(request).await.map_err(|e| {
+        let is_timeout = e.is_timeout();
+        let message = forwarder::send_error_message(e);
+        if is_timeout {
+            ProxyError::Timeout(message)
+        } else {
+            ProxyError::ForwardFailed(message)
+        }
+    })?;
+    Ok(content_encoding::decode_response(response))
+}
+
+/// 构造返回客户端的响应
+pub fn build_response(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: axum::body::Body,
+) -> Result<Response, ProxyError> {
+    let mut builder = Response::builder().status(status);
+    for (key, value) in headers {
+        builder = builder.header(key, value);
+    }
+    builder
+        .body(body)
+        .map_err(|e| ProxyError::Internal(format!("构建响应失败: {e}")))
+}
+
+/// 流式透传上游响应（文件内容、批处理结果可能很大）
+pub fn stream_response(response: reqwest::Response) -> Result<Response, ProxyError> {
+    let status = response.status();
+    let headers = response.headers().clone();
+    build_response(
+        status,
+        &headers,
+        axum::body::Body::from_stream(response.bytes_stream()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_claude_prefix_and_keeps_query() {
+        let uri: Uri = "/claude/v1/files?limit=20".parse().unwrap();
+        assert_eq!(upstream_endpoint(&uri), "/v1/files?limit=20");
+        let uri: Uri = "/v1/files/file_1/content".parse().unwrap();
+        assert_eq!(upstream_endpoint(&uri), "/v1/files/file_1/content");
+    }
+}
@@ -0,0 +1,177 @@
+//! 自动提示词缓存模块
+//!
+//! 为支持 Prompt Caching 的 Claude 供应商自动注入 `cache_control` 断点，
+//! 客户端无需改动即可降低重复 system/tools 的计费。
+//!
+//! ## 注入规则
+//! - 仅处理 Anthropic Messages 格式请求
+//! - 请求中已有任意 `cache_control` 时视为客户端自行管理缓存，不做改动
+//! - tools 定义序列化长度达到阈值时，在最后一个 tool 上添加断点
+//! - system 序列化长度达到阈值时，在最后一个 system block 上添加断点（字符串会转换为 block 数组）
+//!
+//! 注入后需在 `anthropic-beta` 中携带 [`PROMPT_CACHING_BETA`]。
+
+use crate::provider::Provider;
+use serde_json::{json, Value};
+
+/// Prompt Caching beta 标记
+pub const PROMPT_CACHING_BETA: &str = "prompt-caching-2024-07-31";
+
+/// 默认触发阈值（约 1024 tokens，Anthropic 要求的最小可缓存长度）
+const DEFAULT_MIN_CHARS: usize = 4096;
+
+/// 获取 Provider 的缓存注入阈值（未启用时返回 None）
+fn get_min_chars(provider: &Provider) -> Option<usize> {
+    let config = provider.meta.as_ref()?.prompt_caching.as_ref()?;
+    if !config.enabled {
+        return None;
+    }
+    Some(config.min_chars.unwrap_or(DEFAULT_MIN_CHARS))
+}
+
+/// 请求体中是否已存在 cache_control
+fn has_cache_control(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => {
+            map.contains_key("cache_control") || map.values().any(has_cache_control)
+        }
+        Value::Array(arr) => arr.iter().any(has_cache_control),
+        _ => false,
+    }
+}
+
+fn serialized_len(value: &Value) -> usize {
+    serde_json::to_string(value).map(|s| s.len()).unwrap_or(0)
+}
+
+/// 为 Anthropic 请求体注入 cache_control 断点
+///
+/// 返回是否注入了断点（调用方据此添加 beta 标记）
+pub fn apply_prompt_caching(body: &mut Value, provider: &Provider) -> bool {
+    let Some(min_chars) = get_min_chars(provider) else {
+        return false;
+    };
+    if has_cache_control(body) {
+        log::debug!("[PromptCache] 请求已包含 cache_control，跳过自动注入");
+        return false;
+    }
+
+    let mut injected = 0;
+
+    if let Some(tools) = body.get_mut("tools").and_then(|t| t.as_array_mut()) {
+        if serialized_len(&Value::Array(tools.clone())) >= min_chars {
+            if let Some(Value::Object(last)) = tools.last_mut() {
+                last.insert("cache_control".to_string(), json!({"type": "ephemeral"}));
+                injected += 1;
+            }
+        }
+    }
+
+    if let Some(system) = body.get_mut("system") {
+        if serialized_len(system) >= min_chars {
+            if let Value::String(text) = system {
+                *system = json!([{"type": "text", "text": std::mem::take(text)}]);
+            }
+            if let Some(Value::Object(last)) = system.as_array_mut().and_then(|b| b.last_mut()) {
+                last.insert("cache_control".to_string(), json!({"type": "ephemeral"}));
+                injected += 1;
+            }
+        }
+    }
+
+    if injected > 0 {
+        log::debug!(
+            "[PromptCache] 已为供应商 {} 注入 {injected} 个 cache_control 断点",
+            provider.name
+        );
+    }
+    injected > 0
+}
+
+/// 向 anthropic-beta 值追加 Prompt Caching 标记（已存在时保持不变）
+pub fn append_beta_flag(beta_value: &str) -> String {
+    if beta_value
+        .split(',')
+        .any(|flag| flag.trim() == PROMPT_CACHING_BETA)
+    {
+        beta_value.to_string()
+    } else if beta_value.trim().is_empty() {
+        PROMPT_CACHING_BETA.to_string()
+    } else {
+        format!("{beta_value},{PROMPT_CACHING_BETA}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::{PromptCachingConfig, ProviderMeta};
+
+    fn provider(enabled: bool, min_chars: Option<usize>) -> Provider {
+        Provider::with_meta(
+            "p",
+            ProviderMeta {
+                prompt_caching: Some(PromptCachingConfig { enabled, min_chars }),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_injects_into_large_system_string() {
+        let mut body = json!({"system": "x".repeat(50), "messages": []});
+        assert!(apply_prompt_caching(&mut body, &provider(true, Some(10))));
+        let blocks = body["system"].as_array().unwrap();
+        assert_eq!(blocks[0]["text"], "x".repeat(50));
+        assert_eq!(blocks[0]["cache_control"]["type"], "ephemeral");
+    }
+
+    #[test]
+    fn test_injects_into_last_tool() {
+        let mut body = json!({
+            "tools": [
+                {"name": "a", "description": "x".repeat(30)},
+                {"name": "b", "description": "y".repeat(30)}
+            ]
+        });
+        assert!(apply_prompt_caching(&mut body, &provider(true, Some(20))));
+        assert!(body["tools"][0].get("cache_control").is_none());
+        assert_eq!(body["tools"][1]["cache_control"]["type"], "ephemeral");
+    }
+
+    #[test]
+    fn test_small_prompts_untouched() {
+        let mut body = json!({"system": "short", "tools": [{"name": "a"}]});
+        assert!(!apply_prompt_caching(&mut body, &provider(true, None)));
+        assert_eq!(body["system"], "short");
+    }
+
+    #[test]
+    fn test_respects_existing_cache_control() {
+        let mut body = json!({
+            "system": [{"type": "text", "text": "x".repeat(50)}],
+            "messages": [{"role": "user", "content": [
+                {"type": "text", "text": "hi", "cache_control": {"type": "ephemeral"}}
+            ]}]
+        });
+        assert!(!apply_prompt_caching(&mut body, &provider(true, Some(10))));
+        assert!(body["system"][0].get("cache_control").is_none());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut body = json!({"system": "x".repeat(50)});
+        assert!(!apply_prompt_caching(&mut body, &provider(false, Some(10))));
+    }
+
+    #[test]
+    fn test_append_beta_flag() {
+        assert_eq!(append_beta_flag(""), PROMPT_CACHING_BETA);
+        assert_eq!(
+            append_beta_flag("claude-code-20250219"),
+            format!("claude-code-20250219,{PROMPT_CACHING_BETA}")
+        );
+        let existing = format!("a, {PROMPT_CACHING_BETA}");
+        assert_eq!(append_beta_flag(&existing), existing);
+    }
+}
@@ -5,7 +5,13 @@
 use crate::database::Database;
 use crate::error::AppError;
 use crate::provider::Provider;
-use crate::proxy::circuit_breaker::{AllowResult, CircuitBreaker, CircuitBreakerConfig};
+use crate::proxy::circuit_breaker::{
+    AllowResult, CircuitBreaker, CircuitBreakerConfig, CircuitState,
+};
+use crate::proxy::provider_score;
+use crate::proxy::types::{ProviderScoringConfig, WebhookEvent};
+use crate::proxy::webhook;
+use crate::services::StatusWatcherService;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -32,6 +38,7 @@ impl ProviderRouter {
     /// 返回按优先级排序的可用供应商列表：
     /// - 故障转移关闭时：仅返回当前供应商
     /// - 故障转移开启时：完全按照故障转移队列顺序返回，忽略当前供应商设置
+    /// - auto 模式：按供应商评分从高到低返回（见 `select_auto_providers`）
     pub async fn select_providers(&self, app_type: &str) -> Result<Vec<Provider>, AppError> {
         let mut result = Vec::new();
         let mut total_providers = 0usize;
@@ -46,6 +53,13 @@ impl ProviderRouter {
             }
         };
 
+        let scoring = self.db.get_provider_scoring_config().unwrap_or_default();
+        if scoring.is_auto(app_type) {
+            return self
+                .select_auto_providers(app_type, auto_failover_enabled, &scoring)
+                .await;
+        }
+
         if auto_failover_enabled {
             // 故障转移开启：使用 in_failover_queue 标记的供应商，按 sort_index 排序
             let failover_providers = self.db.get_failover_providers(app_type)?;
@@ -61,6 +75,9 @@ impl ProviderRouter {
                     circuit_open_count += 1;
                 }
             }
+
+            // 状态页报告故障的供应商移到队列末尾（稳定排序，保持其余顺序不变）
+            result.sort_by_key(|p| StatusWatcherService::is_deprioritized(app_type, &p.id));
         } else {
             // 故障转移关闭：仅使用当前供应商，跳过熔断器检查
             if let Some(current_id) = self.db.get_current_provider(app_type)? {
@@ -84,6 +101,57 @@ impl ProviderRouter {
         Ok(result)
     }
 
+    /// auto 模式：按评分选择供应商
+    ///
+    /// 候选为故障转移队列中的供应商（队列为空时为该应用全部供应商），跳过熔断中的供应商后
+    /// 按评分从高到低排序；故障转移关闭时只返回评分最高的一个。
+    async fn select_auto_providers(
+        &self,
+        app_type: &str,
+        auto_failover_enabled: bool,
+        scoring: &ProviderScoringConfig,
+    ) -> Result<Vec<Provider>, AppError> {
+        let mut candidates = self.db.get_failover_providers(app_type)?;
+        if candidates.is_empty() {
+            candidates = self.db.get_all_providers(app_type)?.into_values().collect();
+        }
+        if candidates.is_empty() {
+            log::warn!("[{app_type}] [FO-005] 未配置供应商");
+            return Err(AppError::NoProvidersConfigured);
+        }
+
+        let mut result = Vec::with_capacity(candidates.len());
+        for provider in candidates {
+            let circuit_key = format!("{}:{}", app_type, provider.id);
+            let breaker = self.get_or_create_circuit_breaker(&circuit_key).await;
+            if breaker.is_available().await {
+                result.push(provider);
+            }
+        }
+        if result.is_empty() {
+            log::warn!("[{app_type}] [FO-004] 所有供应商均已熔断");
+            return Err(AppError::AllProvidersCircuitOpen);
+        }
+
+        let scores =
+            provider_score::compute_scores(&self.db, app_type, scoring).unwrap_or_else(|e| {
+                log::warn!("[{app_type}] 计算供应商评分失败: {e}");
+                HashMap::new()
+            });
+        provider_score::sort_by_score(&mut result, &scores, |p| p.id.as_str());
+        // 状态页报告故障的供应商移到末尾
+        result.sort_by_key(|p| StatusWatcherService::is_deprioritized(app_type, &p.id));
+        if !auto_failover_enabled {
+            result.truncate(1);
+        }
+        log::debug!(
+            "[{app_type}] auto 模式选择: {}（评分 {:.1}）",
+            result[0].name,
+            provider_score::score_of(&scores, &result[0].id)
+        );
+        Ok(result)
+    }
+
     /// 请求执行前获取熔断器“放行许可”
     ///
     /// - Closed：直接放行
@@ -120,7 +188,19 @@ impl ProviderRouter {
         if success {
             breaker.record_success(used_half_open_permit).await;
         } else {
+            let was_open = breaker.get_state().await == CircuitState::Open;
             breaker.record_failure(used_half_open_permit).await;
+            if !was_open && breaker.get_state().await == CircuitState::Open {
+                webhook::dispatch(
+                    &self.db,
+                    WebhookEvent::ProviderDown,
+                    serde_json::json!({
+                        "appType": app_type,
+                        "providerId": provider_id,
+                        "error": error_msg.as_deref().unwrap_or_default(),
+                    }),
+                );
+            }
         }
 
         // 3. 更新数据库健康状态（使用配置的阈值）
@@ -178,7 +258,6 @@ impl ProviderRouter {
     }
 
     /// 获取熔断器状态
-    #[allow(dead_code)]
     pub async fn get_circuit_breaker_stats(
         &self,
         provider_id: &str,
@@ -394,4 +473,43 @@ mod tests {
         assert!(third.allowed);
         assert!(third.used_half_open_permit);
     }
+
+    #[tokio::test]
+    async fn test_auto_mode_routes_to_best_scoring_provider() -> Result<(), AppError> {
+        let db = Arc::new(Database::memory().unwrap());
+        for id in ["a", "b"] {
+            let provider = Provider::with_id(id.to_string(), id.to_uppercase(), json!({}), None);
+            db.save_provider("claude", &provider).unwrap();
+        }
+        db.set_current_provider("claude", "a").unwrap();
+        db.set_provider_scoring_config(&ProviderScoringConfig {
+            auto_apps: vec!["claude".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+
+        // a 最近频繁限流，b 全部成功
+        {
+            let conn = crate::database::lock_conn!(db.conn);
+            let now = chrono::Utc::now().timestamp();
+            for (i, (provider, status)) in [("a", 429), ("a", 200), ("b", 200), ("b", 200)]
+                .into_iter()
+                .enumerate()
+            {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model,
+                     latency_ms, status_code, created_at)
+                     VALUES (?1, ?2, 'claude', 'm', 500, ?3, ?4)",
+                    rusqlite::params![format!("r{i}"), provider, status, now],
+                )
+                .unwrap();
+            }
+        }
+
+        let router = ProviderRouter::new(db.clone());
+        let providers = router.select_providers("claude").await.unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id, "b");
+        Ok(())
+    }
 }
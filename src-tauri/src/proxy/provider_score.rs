@@ -0,0 +1,172 @@
+//! 供应商评分
+//!
+//! 基于最近一段时间（默认 30 分钟）的请求日志，为每个供应商计算 0-100 的滚动评分：
+//!
+//! `score = 100 × (1 − (错误率 × w_error + 延迟占比 × w_latency + 限流率 × w_rate_limit) / Σw)`
+//!
+//! 其中延迟占比 = 成功请求平均延迟（流式取首字延迟）/ `latency_ceiling_ms`（上限为 1）。
+//! 窗口内没有请求的供应商记为中性分 50，使其仍有机会被选中并积累样本。
+//!
+//! 应用处于 auto 模式时，`ProviderRouter` 按评分从高到低排列候选供应商，始终优先路由到评分最高者。
+
+use crate::database::Database;
+use crate::error::AppError;
+use crate::proxy::types::ProviderScoringConfig;
+use crate::services::usage_stats::RecentProviderStats;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 没有样本时的中性评分
+const NEUTRAL_SCORE: f64 = 50.0;
+
+/// 单个供应商的评分
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderScore {
+    pub provider_id: String,
+    pub score: f64,
+    pub samples: u64,
+    pub error_rate: f64,
+    pub rate_limit_rate: f64,
+    pub avg_latency_ms: f64,
+}
+
+/// 根据窗口内统计计算评分
+pub fn score_from_stats(
+    stats: &RecentProviderStats,
+    config: &ProviderScoringConfig,
+) -> ProviderScore {
+    let requests = stats.requests.max(1) as f64;
+    let error_rate = stats.errors as f64 / requests;
+    let rate_limit_rate = stats.rate_limited as f64 / requests;
+    let latency_ratio = if stats.errors >= stats.requests {
+        // 全部失败时没有可参考的延迟，按最差处理
+        1.0
+    } else {
+        (stats.avg_latency_ms / config.latency_ceiling_ms.max(1) as f64).min(1.0)
+    };
+
+    let total_weight = config.error_weight + config.latency_weight + config.rate_limit_weight;
+    let penalty = if total_weight > 0.0 {
+        (error_rate * config.error_weight
+            + latency_ratio * config.latency_weight
+            + rate_limit_rate * config.rate_limit_weight)
+            / total_weight
+    } else {
+        0.0
+    };
+
+    ProviderScore {
+        provider_id: stats.provider_id.clone(),
+        score: (100.0 * (1.0 - penalty)).clamp(0.0, 100.0),
+        samples: stats.requests,
+        error_rate,
+        rate_limit_rate,
+        avg_latency_ms: stats.avg_latency_ms,
+    }
+}
+
+/// 计算指定应用所有有样本的供应商评分
+pub fn compute_scores(
+    db: &Database,
+    app_type: &str,
+    config: &ProviderScoringConfig,
+) -> Result<HashMap<String, ProviderScore>, AppError> {
+    let since = chrono::Utc::now().timestamp() - i64::from(config.window_minutes.max(1)) * 60;
+    Ok(db
+        .get_recent_provider_stats(app_type, since)?
+        .iter()
+        .map(|stats| (stats.provider_id.clone(), score_from_stats(stats, config)))
+        .collect())
+}
+
+/// 指定应用全部供应商的评分（按评分从高到低，无样本的供应商为中性分）
+pub fn list_scores(
+    db: &Database,
+    app_type: &str,
+    config: &ProviderScoringConfig,
+) -> Result<Vec<ProviderScore>, AppError> {
+    let scores = compute_scores(db, app_type, config)?;
+    let mut list: Vec<ProviderScore> = db
+        .get_all_providers(app_type)?
+        .into_keys()
+        .map(|id| {
+            scores.get(&id).cloned().unwrap_or(ProviderScore {
+                provider_id: id,
+                score: NEUTRAL_SCORE,
+                samples: 0,
+                error_rate: 0.0,
+                rate_limit_rate: 0.0,
+                avg_latency_ms: 0.0,
+            })
+        })
+        .collect();
+    sort_by_score(&mut list, &scores, |s| s.provider_id.as_str());
+    Ok(list)
+}
+
+/// 获取供应商评分（无样本时返回中性分）
+pub fn score_of(scores: &HashMap<String, ProviderScore>, provider_id: &str) -> f64 {
+    scores.get(provider_id).map_or(NEUTRAL_SCORE, |s| s.score)
+}
+
+/// 按评分从高到低排序（稳定排序，同分保持原有顺序）
+pub fn sort_by_score<T>(
+    items: &mut [T],
+    scores: &HashMap<String, ProviderScore>,
+    id: impl Fn(&T) -> &str,
+) {
+    items.sort_by(|a, b| score_of(scores, id(b)).total_cmp(&score_of(scores, id(a))));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(
+        id: &str,
+        requests: u64,
+        errors: u64,
+        rate_limited: u64,
+        latency: f64,
+    ) -> RecentProviderStats {
+        RecentProviderStats {
+            provider_id: id.into(),
+            requests,
+            errors,
+            rate_limited,
+            avg_latency_ms: latency,
+            cost_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn penalizes_errors_latency_and_rate_limits() {
+        let config = ProviderScoringConfig::default();
+        let healthy = score_from_stats(&stats("a", 10, 0, 0, 0.0), &config);
+        assert_eq!(healthy.score, 100.0);
+
+        let slow = score_from_stats(&stats("b", 10, 0, 0, 15_000.0), &config);
+        assert!((slow.score - 85.0).abs() < 1e-9);
+
+        let limited = score_from_stats(&stats("c", 10, 5, 5, 1_000.0), &config);
+        assert!(limited.score < slow.score);
+        assert_eq!(limited.rate_limit_rate, 0.5);
+
+        let dead = score_from_stats(&stats("d", 4, 4, 0, 0.0), &config);
+        assert!((dead.score - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sorts_by_score_with_neutral_default() {
+        let config = ProviderScoringConfig::default();
+        let scores: HashMap<_, _> = [stats("a", 10, 10, 0, 0.0), stats("b", 10, 0, 0, 100.0)]
+            .iter()
+            .map(|s| (s.provider_id.clone(), score_from_stats(s, &config)))
+            .collect();
+
+        let mut ids = vec!["a", "new", "b"];
+        sort_by_score(&mut ids, &scores, |id| *id);
+        assert_eq!(ids, vec!["b", "new", "a"]);
+    }
+}
@@ -100,11 +100,8 @@ pub fn detect_rate_limit_in_sse(sse_data: &str) -> bool {
 
             // 尝试解析为 JSON
             if let Ok(json_value) = serde_json::from_str::<serde_json::Value>(data) {
-                // 检查各种可能包含错误信息的字段
-                if let Some(error_text) = extract_error_from_json(&json_value) {
-                    if is_rate_limit_error(&error_text) {
-                        return true;
-                    }
+                if detect_rate_limit_in_json(&json_value) {
+                    return true;
                 }
             } else {
                 // 如果不是 JSON，直接检查文本内容
@@ -117,6 +114,12 @@ pub fn detect_rate_limit_in_sse(sse_data: &str) -> bool {
     false
 }
 
+/// 检测已解析的 SSE 事件 JSON 中的 Rate limit 错误（避免重复解析事件）
+pub fn detect_rate_limit_in_json(json: &serde_json::Value) -> bool {
+    // 检查各种可能包含错误信息的字段
+    extract_error_from_json(json).is_some_and(|error_text| is_rate_limit_error(&error_text))
+}
+
 /// 从 JSON 中提取错误信息
 fn extract_error_from_json(json: &serde_json::Value) -> Option<String> {
     // 检查常见的错误字段
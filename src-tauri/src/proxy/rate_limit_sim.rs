@@ -0,0 +1,227 @@
+//! Rate limit 模拟
+//!
+//! 按需让代理直接返回 429 或流式 rate limit 错误事件（不请求上游），用于在真实限流发生前
+//! 验证客户端（如 Claude Code）以及 cc-switch 自身 Rate limit 重试与故障转移配置的表现。
+//!
+//! 模拟状态仅保存在内存中，重启应用后失效。可通过 Tauri 命令或代理的本地端点
+//! `/ccswitch/simulate/rate-limit`（GET 查询 / POST 开启 / DELETE 关闭，仅接受回环地址请求）控制。
+//! 模拟在每次上游转发尝试时生效，重试与故障转移的每次尝试都会消耗一次计数。
+
+use super::ProxyError;
+use axum::{
+    body::Bytes,
+    extract::ConnectInfo,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+/// 模拟消息（包含 "rate limit"，以便触发 Rate limit 重试检测）
+const SIMULATED_MESSAGE: &str = "Rate limit exceeded (simulated by cc-switch)";
+
+static SIMULATION: Mutex<Option<RateLimitSimulation>> = Mutex::new(None);
+
+/// 模拟方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SimulationMode {
+    /// 返回 HTTP 429
+    #[default]
+    Http429,
+    /// 流式请求返回 200 + SSE error 事件（非流式请求仍返回 429）
+    SseError,
+}
+
+/// Rate limit 模拟配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitSimulation {
+    #[serde(default)]
+    pub mode: SimulationMode,
+    /// 仅对指定应用生效（claude / codex / gemini），为空时对全部应用生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_type: Option<String>,
+    /// 剩余模拟次数，为空时持续模拟直到手动关闭
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<u32>,
+}
+
+/// 开启模拟（覆盖已有模拟）
+pub fn start(simulation: RateLimitSimulation) {
+    let simulation = (simulation.remaining != Some(0)).then_some(simulation);
+    if let Ok(mut current) = SIMULATION.lock() {
+        log::warn!("[RATE-LIMIT-SIM] 开启 rate limit 模拟: {simulation:?}");
+        *current = simulation;
+    }
+}
+
+/// 关闭模拟
+pub fn stop() {
+    if let Ok(mut current) = SIMULATION.lock() {
+        if current.take().is_some() {
+            log::info!("[RATE-LIMIT-SIM] 已关闭 rate limit 模拟");
+        }
+    }
+}
+
+/// 当前模拟状态
+pub fn current() -> Option<RateLimitSimulation> {
+    SIMULATION.lock().ok().and_then(|current| current.clone())
+}
+
+/// 为一次转发尝试消耗一次模拟（未开启或应用不匹配时返回 None）
+pub fn take(app_type: &str) -> Option<SimulationMode> {
+    let mut current = SIMULATION.lock().ok()?;
+    let simulation = current.as_mut()?;
+    if simulation
+        .app_type
+        .as_deref()
+        .is_some_and(|app| !app.eq_ignore_ascii_case(app_type))
+    {
+        return None;
+    }
+
+    let mode = simulation.mode;
+    if let Some(remaining) = simulation.remaining.as_mut() {
+        *remaining = remaining.saturating_sub(1);
+        if *remaining == 0 {
+            *current = None;
+        }
+    }
+    Some(mode)
+}
+
+/// 构造模拟的上游结果
+///
+/// 429 与真实上游错误一样以 `UpstreamError` 返回，走相同的重试、熔断与故障转移路径
+pub fn simulate(
+    mode: SimulationMode,
+    app_type: &str,
+    streaming: bool,
+) -> Result<reqwest::Response, ProxyError> {
+    if mode == SimulationMode::SseError && streaming {
+        let event = if app_type.eq_ignore_ascii_case("claude") {
+            format!("event: error\ndata: {}\n\n", error_body(app_type))
+        } else {
+            format!("data: {}\n\n", error_body(app_type))
+        };
+        let response = axum::http::Response::builder()
+            .status(StatusCode::OK)
+            .header("content-type", "text/event-stream")
+            .body(event)
+            .map_err(|e| ProxyError::Internal(format!("构造模拟响应失败: {e}")))?;
+        return Ok(reqwest::Response::from(response));
+    }
+
+    Err(ProxyError::UpstreamError {
+        status: 429,
+        body: Some(error_body(app_type).to_string()),
+    })
+}
+
+/// 各应用格式的 rate limit 错误体
+fn error_body(app_type: &str) -> Value {
+    match app_type.to_ascii_lowercase().as_str() {
+        "claude" => json!({
+            "type": "error",
+            "error": { "type": "rate_limit_error", "message": SIMULATED_MESSAGE }
+        }),
+        "gemini" => json!({
+            "error": { "code": 429, "message": SIMULATED_MESSAGE, "status": "RESOURCE_EXHAUSTED" }
+        }),
+        _ => json!({
+            "error": {
+                "message": SIMULATED_MESSAGE,
+                "type": "rate_limit_error",
+                "code": "rate_limit_exceeded"
+            }
+        }),
+    }
+}
+
+fn reject_non_local(addr: SocketAddr) -> Option<Response> {
+    (!addr.ip().is_loopback()).then(|| {
+        (
+            StatusCode::FORBIDDEN,
+            Json(json!({ "error": "rate limit simulation is only available from localhost" })),
+        )
+            .into_response()
+    })
+}
+
+/// GET `/ccswitch/simulate/rate-limit`
+pub async fn get_simulation(ConnectInfo(addr): ConnectInfo<SocketAddr>) -> Response {
+    if let Some(rejected) = reject_non_local(addr) {
+        return rejected;
+    }
+    Json(json!({ "simulation": current() })).into_response()
+}
+
+/// POST `/ccswitch/simulate/rate-limit`（请求体为空时使用默认配置：429、持续模拟）
+pub async fn start_simulation(ConnectInfo(addr): ConnectInfo<SocketAddr>, body: Bytes) -> Response {
+    if let Some(rejected) = reject_non_local(addr) {
+        return rejected;
+    }
+    let simulation = if body.is_empty() {
+        RateLimitSimulation::default()
+    } else {
+        match serde_json::from_slice::<RateLimitSimulation>(&body) {
+            Ok(simulation) => simulation,
+            Err(e) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": format!("invalid simulation config: {e}") })),
+                )
+                    .into_response()
+            }
+        }
+    };
+    start(simulation);
+    Json(json!({ "simulation": current() })).into_response()
+}
+
+/// DELETE `/ccswitch/simulate/rate-limit`
+pub async fn stop_simulation(ConnectInfo(addr): ConnectInfo<SocketAddr>) -> Response {
+    if let Some(rejected) = reject_non_local(addr) {
+        return rejected;
+    }
+    stop();
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumes_limited_simulation_for_matching_app() {
+        start(RateLimitSimulation {
+            mode: SimulationMode::SseError,
+            app_type: Some("claude".into()),
+            remaining: Some(2),
+        });
+        assert_eq!(take("Codex"), None);
+        assert_eq!(take("Claude"), Some(SimulationMode::SseError));
+        assert_eq!(current().and_then(|s| s.remaining), Some(1));
+        assert_eq!(take("claude"), Some(SimulationMode::SseError));
+        assert_eq!(current(), None);
+        assert_eq!(take("claude"), None);
+
+        let err = simulate(SimulationMode::SseError, "claude", false).unwrap_err();
+        match err {
+            ProxyError::UpstreamError { status, body } => {
+                assert_eq!(status, 429);
+                assert!(super::super::rate_limit_retry::is_rate_limit_error(
+                    &body.unwrap()
+                ));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        let response = simulate(SimulationMode::SseError, "claude", true).unwrap();
+        assert_eq!(response.status(), 200);
+    }
+}
@@ -0,0 +1,276 @@
+//! 请求重放
+//!
+//! 启用采集后（settings 表 `replay_capture_config`）代理保存每个请求的端点、请求体与上游响应，
+//! 调试时可从历史中选取一条，按原端点重新发往当前或指定的供应商并返回完整响应，
+//! 便于对比不同供应商对同一请求的表现。
+//!
+//! 重放直接请求上游，不经过故障转移、熔断与用量统计；请求体按目标供应商重新执行
+//! 模型映射、格式转换与参数覆盖。客户端请求头不会被采集，重放时仅携带认证与必要的协议头。
+
+use super::body_filter::{filter_private_params_with_whitelist, strip_denied_fields};
+use super::handler_context::RequestContext;
+use super::providers::get_adapter;
+use super::{max_tokens, model_mapper, sampling, system_prompt};
+use crate::app_config::AppType;
+use crate::database::{Database, RequestCapture};
+use crate::error::AppError;
+use crate::provider::Provider;
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// 未配置供应商总超时时的重放超时
+const DEFAULT_REPLAY_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// 重放结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayResult {
+    pub request_id: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub url: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    /// 响应体原文（流式请求为完整的 SSE 文本）
+    pub body: String,
+}
+
+/// 采集本次请求（未启用时跳过，写入失败仅记录日志），返回采集 ID
+pub fn capture(
+    db: &Database,
+    ctx: &RequestContext,
+    endpoint: &str,
+    body: &Value,
+) -> Option<String> {
+    let config = match db.get_replay_capture_config() {
+        Ok(config) if config.enabled => config,
+        _ => return None,
+    };
+    let capture = RequestCapture {
+        request_id: ctx.request_id.clone(),
+        app_type: ctx.app_type_str.to_string(),
+        endpoint: endpoint.to_string(),
+        provider_id: ctx.provider.id.clone(),
+        model: ctx.request_model.clone(),
+        created_at: chrono::Utc::now().timestamp(),
+        body: body.to_string(),
+        status_code: None,
+        duration_ms: None,
+        response_body: None,
+    };
+    match db.insert_request_capture(&capture, config.max_entries) {
+        Ok(()) => Some(capture.request_id),
+        Err(e) => {
+            log::warn!("[{}] 保存重放请求失败: {e}", ctx.tag);
+            None
+        }
+    }
+}
+
+/// 回填采集请求的上游响应（未采集时跳过）
+pub fn record_response(db: &Database, ctx: &RequestContext, status: u16, body: &str) {
+    if let Some(capture_id) = ctx.capture_id.as_deref() {
+        record(
+            db,
+            capture_id,
+            &ctx.provider.id,
+            status,
+            ctx.latency_ms(),
+            body,
+        );
+    }
+}
+
+/// 回填上游响应（写入失败仅记录日志）
+pub fn record(
+    db: &Database,
+    capture_id: &str,
+    provider_id: &str,
+    status: u16,
+    duration_ms: u64,
+    body: &str,
+) {
+    if let Err(e) =
+        db.record_request_capture_response(capture_id, provider_id, status, duration_ms, body)
+    {
+        log::warn!("[Replay] 保存请求 {capture_id} 的响应失败: {e}");
+    }
+}
+
+/// 将收集到的 SSE 事件重组为 SSE 文本
+pub fn sse_text(events: &[Value]) -> String {
+    events
+        .iter()
+        .map(|event| format!("data: {event}\n\n"))
+        .collect()
+}
+
+/// 按目标供应商改写请求体，返回 (实际端点, 请求体)
+fn prepare_body(
+    app_type: &AppType,
+    provider: &Provider,
+    endpoint: &str,
+    body: Value,
+) -> Result<(String, Value), AppError> {
+    let adapter = get_adapter(app_type);
+    let needs_transform = adapter.needs_transform(provider);
+    let endpoint = if needs_transform && adapter.name() == "Claude" && endpoint == "/v1/messages" {
+        "/v1/chat/completions"
+    } else {
+        endpoint
+    };
+
+    let (body, _, _) = model_mapper::apply_model_mapping(body, provider);
+    let mut body = if needs_transform {
+        adapter
+            .transform_request(body, provider)
+            .map_err(|e| AppError::Message(e.to_string()))?
+    } else {
+        body
+    };
+    system_prompt::apply_system_prompt_injection(&mut body, provider, endpoint);
+    max_tokens::apply_max_tokens_limit(&mut body, provider, endpoint);
+    sampling::apply_sampling_overrides(&mut body, provider, endpoint);
+    if let Some(meta) = provider.meta.as_ref() {
+        strip_denied_fields(&mut body, &meta.field_denylist);
+    }
+    Ok((
+        endpoint.to_string(),
+        filter_private_params_with_whitelist(body, &[]),
+    ))
+}
+
+/// 重放一条采集的请求
+///
+/// `provider_id` 为空时发往该应用的当前供应商
+pub async fn replay(
+    db: &Database,
+    request_id: &str,
+    provider_id: Option<&str>,
+) -> Result<ReplayResult, AppError> {
+    let capture = db
+        .get_request_capture(request_id)?
+        .ok_or_else(|| AppError::Message(format!("请求 {request_id} 不存在或已被清理")))?;
+    let app_type = AppType::from_str(&capture.app_type)?;
+
+    let provider_id = match provider_id {
+        Some(id) => id.to_string(),
+        None => crate::settings::get_effective_current_provider(db, &app_type)?
+            .ok_or_else(|| AppError::Message("当前没有选中的供应商".to_string()))?,
+    };
+    let provider = db
+        .get_provider_by_id(&provider_id, app_type.as_str())?
+        .ok_or_else(|| AppError::Message(format!("供应商 {provider_id} 不存在")))?;
+
+    let body: Value = serde_json::from_str(&capture.body)
+        .map_err(|e| AppError::Message(format!("解析采集的请求体失败: {e}")))?;
+    log::info!("[Replay] 重放请求 {request_id} → {}", provider.name);
+    let reply = send(&app_type, &provider, &capture.endpoint, body).await?;
+
+    Ok(ReplayResult {
+        request_id: capture.request_id,
+        provider_id: provider.id,
+        provider_name: provider.name,
+        url: reply.url,
+        status: reply.status,
+        latency_ms: reply.latency_ms,
+        body: reply.body,
+    })
+}
+
+/// 直接发往上游的响应
+pub(crate) struct UpstreamReply {
+    pub url: String,
+    /// 实际请求的端点（格式转换后可能与原端点不同）
+    pub endpoint: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    pub body: String,
+}
+
+/// 按目标供应商改写请求体后直接发往上游并读取完整响应
+pub(crate) async fn send(
+    app_type: &AppType,
+    provider: &Provider,
+    endpoint: &str,
+    body: Value,
+) -> Result<UpstreamReply, AppError> {
+    let (endpoint, body) = prepare_body(app_type, provider, endpoint, body)?;
+
+    let adapter = get_adapter(app_type);
+    let base_url = adapter
+        .extract_base_url(provider)
+        .map_err(|e| AppError::Message(e.to_string()))?;
+    let url = adapter.build_url(&base_url, &endpoint);
+    let auth = adapter
+        .extract_auth(provider)
+        .ok_or_else(|| AppError::Message("API Key not found".to_string()))?;
+
+    let client = super::http_client::get_for_provider(provider).map_err(AppError::Message)?;
+    let timeout = provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.timeouts.as_ref())
+        .and_then(|t| t.total_secs)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_REPLAY_TIMEOUT);
+    let mut request = client
+        .post(&url)
+        .timeout(timeout)
+        .header("content-type", "application/json");
+    if adapter.name() == "Claude" {
+        request = request
+            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-beta", "claude-code-20250219");
+    }
+    let request = adapter.add_auth_headers(request, &auth).json(&body);
+
+    let start = Instant::now();
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Message(format!("请求上游失败: {e}")))?;
+    let status = response.status().as_u16();
+    let body = super::content_encoding::decode_response(response)
+        .text()
+        .await
+        .map_err(|e| AppError::Message(format!("读取上游响应失败: {e}")))?;
+
+    Ok(UpstreamReply {
+        url,
+        endpoint,
+        status,
+        latency_ms: start.elapsed().as_millis() as u64,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn prepares_body_for_target_provider() {
+        let mut provider = Provider::with_id(
+            "p1".into(),
+            "p1".into(),
+            json!({ "env": { "ANTHROPIC_BASE_URL": "https://example.com" } }),
+            None::<String>,
+        );
+        provider.meta = Some(Default::default());
+        provider.meta.as_mut().unwrap().field_denylist = vec!["metadata".into()];
+
+        let (endpoint, body) = prepare_body(
+            &AppType::Claude,
+            &provider,
+            "/v1/messages",
+            json!({ "model": "claude-sonnet", "metadata": {}, "_internal": true }),
+        )
+        .unwrap();
+        assert_eq!(endpoint, "/v1/messages");
+        assert_eq!(body, json!({ "model": "claude-sonnet" }));
+    }
+}
@@ -0,0 +1,38 @@
+//! 请求体提取
+//!
+//! 代理需要解析请求体以完成路由（模型、会话、统计），但转发时若没有任何规则改写请求体，
+//! 会直接发送客户端的原始字节，避免大请求（粘贴的大文件、图片）在每次转发尝试时重新序列化。
+//!
+//! 请求体仍需完整读取一次：故障转移需要向多个供应商重放同一请求体，
+//! 自定义认证方式（HMAC）也需要对完整请求体签名。读取时遵守本地请求体大小限制。
+
+use super::{
+    request_limit::{self, BodyLimit},
+    ProxyError,
+};
+use axum::async_trait;
+use axum::extract::{FromRequest, Request};
+use bytes::Bytes;
+use serde_json::Value;
+
+/// 解析后的 JSON 请求体及其原始字节（替代 `Json<Value>` 提取器）
+pub struct JsonBody {
+    pub value: Value,
+    pub raw: Bytes,
+}
+
+#[async_trait]
+impl<S> FromRequest<S> for JsonBody
+where
+    S: Send + Sync,
+{
+    type Rejection = ProxyError;
+
+    async fn from_request(req: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        let limit = req.extensions().get::<BodyLimit>().copied();
+        let raw = request_limit::read_limited(req.into_body(), limit).await?;
+        let value = serde_json::from_slice(&raw)
+            .map_err(|e| ProxyError::InvalidRequest(format!("请求体不是有效的 JSON: {e}")))?;
+        Ok(Self { value, raw })
+    }
+}
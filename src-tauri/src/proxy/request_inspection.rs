@@ -0,0 +1,206 @@
+//! 单请求排查
+//!
+//! 按请求 ID 汇总一次交互的全部记录：
+//! - 请求日志：实际使用的供应商、用量、成本与耗时
+//! - 请求采集（启用重放采集时）：完整请求体与上游原始响应
+//! - 对话记录（启用对话记录时）：重组后的回复文本
+//! - 请求过程：脱敏后的请求 / 响应头，以及每次限流重试与故障转移
+//!
+//! 请求过程只保存在内存中（最近 [`MAX_TIMELINES`] 个请求），应用重启后清空。
+
+use super::activity::ActivityKind;
+use super::secret_redaction::redact_headers;
+use crate::database::{Database, RequestCapture, TranscriptRecord};
+use crate::error::AppError;
+use crate::services::RequestLogDetail;
+use axum::http::{HeaderMap, StatusCode};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// 内存中保留的请求过程数量
+const MAX_TIMELINES: usize = 200;
+
+static TIMELINES: Lazy<Mutex<VecDeque<RequestTimeline>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(MAX_TIMELINES)));
+
+/// 请求过程中的一个事件
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimelineEvent {
+    pub timestamp: i64,
+    #[serde(flatten)]
+    pub kind: ActivityKind,
+}
+
+/// 请求过程（请求头、响应头与重试记录）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTimeline {
+    pub request_id: String,
+    /// 客户端请求头（凭据已脱敏）
+    pub request_headers: Vec<(String, String)>,
+    /// 最终采用的上游响应状态码
+    pub response_status: Option<u16>,
+    /// 最终采用的上游响应头（凭据已脱敏）
+    pub response_headers: Vec<(String, String)>,
+    /// 开始、重试、完成 / 失败事件（不含流式进度）
+    pub events: Vec<TimelineEvent>,
+}
+
+/// 单个请求的完整记录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestInspection {
+    pub request_id: String,
+    pub log: Option<RequestLogDetail>,
+    pub capture: Option<RequestCapture>,
+    pub transcript: Option<TranscriptRecord>,
+    pub timeline: Option<RequestTimeline>,
+}
+
+fn with_timeline(request_id: &str, f: impl FnOnce(&mut RequestTimeline)) {
+    if let Ok(mut timelines) = TIMELINES.lock() {
+        if let Some(timeline) = timelines
+            .iter_mut()
+            .rev()
+            .find(|t| t.request_id == request_id)
+        {
+            f(timeline);
+        }
+    }
+}
+
+/// 开始记录请求过程，超出容量时丢弃最早的记录
+pub fn begin(request_id: &str, headers: &HeaderMap) {
+    let timeline = RequestTimeline {
+        request_id: request_id.to_string(),
+        request_headers: redact_headers(headers),
+        response_status: None,
+        response_headers: Vec::new(),
+        events: Vec::new(),
+    };
+    if let Ok(mut timelines) = TIMELINES.lock() {
+        if timelines.len() >= MAX_TIMELINES {
+            timelines.pop_front();
+        }
+        timelines.push_back(timeline);
+    }
+}
+
+/// 记录请求事件（流式进度不记录）
+pub fn record_event(request_id: &str, timestamp: i64, kind: &ActivityKind) {
+    if matches!(kind, ActivityKind::Progress { .. }) {
+        return;
+    }
+    with_timeline(request_id, |timeline| {
+        timeline.events.push(TimelineEvent {
+            timestamp,
+            kind: kind.clone(),
+        })
+    });
+}
+
+/// 记录最终采用的上游响应
+pub fn record_response(request_id: &str, status: StatusCode, headers: &HeaderMap) {
+    with_timeline(request_id, |timeline| {
+        timeline.response_status = Some(status.as_u16());
+        timeline.response_headers = redact_headers(headers);
+    });
+}
+
+/// 统计指定时间（毫秒）之后各供应商的重试次数（限流重试与故障转移）
+pub fn retry_counts(since_ms: i64) -> HashMap<String, u64> {
+    let mut counts = HashMap::new();
+    let Ok(timelines) = TIMELINES.lock() else {
+        return counts;
+    };
+    for event in timelines.iter().flat_map(|t| &t.events) {
+        if let ActivityKind::Retry { provider_id, .. } = &event.kind {
+            if event.timestamp >= since_ms {
+                *counts.entry(provider_id.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+fn timeline(request_id: &str) -> Option<RequestTimeline> {
+    TIMELINES
+        .lock()
+        .ok()?
+        .iter()
+        .rev()
+        .find(|t| t.request_id == request_id)
+        .cloned()
+}
+
+/// 汇总请求的全部记录，找不到任何记录时返回 None
+pub fn inspect(db: &Database, request_id: &str) -> Result<Option<RequestInspection>, AppError> {
+    let inspection = RequestInspection {
+        request_id: request_id.to_string(),
+        log: db.get_request_detail(request_id)?,
+        capture: db.get_request_capture(request_id)?,
+        transcript: db.get_transcript(request_id)?,
+        timeline: timeline(request_id),
+    };
+    let found = inspection.log.is_some()
+        || inspection.capture.is_some()
+        || inspection.transcript.is_some()
+        || inspection.timeline.is_some();
+    Ok(found.then_some(inspection))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::secret_redaction::REDACTED;
+
+    #[test]
+    fn records_redacted_headers_and_attempts() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", "sk-secret".parse().unwrap());
+        headers.insert("user-agent", "claude-cli".parse().unwrap());
+        begin("inspect-r1", &headers);
+        record_event(
+            "inspect-r1",
+            1,
+            &ActivityKind::Retry {
+                provider_id: "p1".into(),
+                provider_name: "Primary".into(),
+                attempt: 1,
+                failover: true,
+                reason: "HTTP 503".into(),
+            },
+        );
+        record_event(
+            "inspect-r1",
+            2,
+            &ActivityKind::Progress {
+                chunks: 1,
+                bytes: 10,
+            },
+        );
+        record_response("inspect-r1", StatusCode::OK, &HeaderMap::new());
+
+        let timeline = timeline("inspect-r1").unwrap();
+        assert!(timeline
+            .request_headers
+            .contains(&("x-api-key".to_string(), REDACTED.to_string())));
+        assert!(timeline
+            .request_headers
+            .contains(&("user-agent".to_string(), "claude-cli".to_string())));
+        assert_eq!(timeline.events.len(), 1);
+        assert_eq!(timeline.response_status, Some(200));
+        assert!(retry_counts(0).get("p1").is_some_and(|n| *n >= 1));
+        assert_eq!(retry_counts(2).get("p1"), None);
+    }
+
+    #[test]
+    fn inspect_returns_none_for_unknown_request() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        assert!(inspect(&db, "inspect-missing")?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! 请求体大小限制
+//!
+//! 代理 API 路由在转发前检查请求体大小：`Content-Length` 超限时直接拒绝，
+//! 未声明长度（分块传输）时边读取边计数，超限立即停止读取。超限请求在本地返回 413 及说明，
+//! 不会发送到上游，避免误粘贴数百 MB 内容时长时间上传后才由上游报错。
+//! 配置存储在 settings 表（`request_size_limit_config`）中，每次请求实时读取。
+//!
+//! 中间件本身不缓冲请求体，只把限制写入请求扩展：需要解析的请求由 [`read_limited`] 读取，
+//! 透传接口（Message Batches）经 [`stream_limited`] 边转发边计数。
+//! Files API（`/v1/files`）上传的文件远大于对话请求，不受此限制，单个文件大小由上游校验。
+
+use super::{server::ProxyState, ProxyError};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::header::CONTENT_LENGTH,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures::StreamExt;
+
+/// 当前请求适用的大小限制（由中间件写入请求扩展）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit {
+    pub max_mb: u64,
+}
+
+impl BodyLimit {
+    fn max_bytes(self) -> u64 {
+        self.max_mb.saturating_mul(1024 * 1024)
+    }
+
+    fn exceeded(self) -> ProxyError {
+        log::warn!(
+            "[RequestLimit] 请求体超过 {} MB 限制，已在本地拒绝",
+            self.max_mb
+        );
+        ProxyError::RequestTooLarge {
+            limit_mb: self.max_mb,
+        }
+    }
+}
+
+/// 不受大小限制的路径（Files API 上传）
+fn is_exempt(path: &str) -> bool {
+    path.strip_prefix("/claude")
+        .unwrap_or(path)
+        .starts_with("/v1/files")
+}
+
+/// 在限制内读取完整请求体，超限时返回 413
+pub async fn read_limited(body: Body, limit: Option<BodyLimit>) -> Result<Bytes, ProxyError> {
+    let mut stream = body.into_data_stream();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk =
+            chunk.map_err(|e| ProxyError::InvalidRequest(format!("读取请求体失败: {e}")))?;
+        if let Some(limit) = limit {
+            if (buffer.len() + chunk.len()) as u64 > limit.max_bytes() {
+                return Err(limit.exceeded());
+            }
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(buffer))
+}
+
+/// 以流的形式转发请求体，超限时中止上传
+pub fn stream_limited(body: Body, limit: Option<BodyLimit>) -> reqwest::Body {
+    let mut received = 0u64;
+    let stream = body.into_data_stream().map(move |chunk| {
+        let chunk = chunk.map_err(std::io::Error::other)?;
+        received += chunk.len() as u64;
+        match limit {
+            Some(limit) if received > limit.max_bytes() => {
+                Err(std::io::Error::other(limit.exceeded().to_string()))
+            }
+            _ => Ok(chunk),
+        }
+    });
+    reqwest::Body::wrap_stream(stream)
+}
+
+/// Axum 中间件：拒绝声明长度超过限制的请求，并为读取请求体的一方记录限制
+pub async fn enforce_request_size_limit(
+    State(state): State<ProxyState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let config = state.db.get_request_size_limit_config().unwrap_or_default();
+    if config.max_body_mb == 0 || is_exempt(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let limit = BodyLimit {
+        max_mb: config.max_body_mb,
+    };
+
+    let declared = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|length| length > limit.max_bytes()) {
+        return limit.exceeded().into_response();
+    }
+
+    request.extensions_mut().insert(limit);
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stops_reading_once_limit_is_exceeded() {
+        let limit = Some(BodyLimit { max_mb: 1 });
+        let body = Body::from(vec![b'a'; 1024 * 1024 + 1]);
+        assert!(matches!(
+            read_limited(body, limit).await,
+            Err(ProxyError::RequestTooLarge { limit_mb: 1 })
+        ));
+
+        let body = Body::from(vec![b'a'; 8]);
+        assert_eq!(read_limited(body, limit).await.unwrap(), vec![b'a'; 8]);
+        let body = Body::from(vec![b'a'; 1024 * 1024 + 1]);
+        assert_eq!(
+            read_limited(body, None).await.unwrap().len(),
+            1024 * 1024 + 1
+        );
+    }
+
+    #[test]
+    fn exempts_file_uploads() {
+        assert!(is_exempt("/v1/files"));
+        assert!(is_exempt("/claude/v1/files/file_1/content"));
+        assert!(!is_exempt("/v1/messages"));
+        assert!(!is_exempt("/v1/messages/batches"));
+    }
+}
@@ -0,0 +1,299 @@
+//! 响应缓存
+//!
+//! 启用后（settings 表 `response_cache_config`）代理以「应用 + 团队成员 + 供应商 + 端点 + 规范化请求体」的哈希为键，
+//! 在有效期内直接返回相同非流式请求的缓存响应，减少标题生成等重复工具调用的费用。
+//!
+//! 只缓存成功（2xx）的非流式 JSON 响应，缓存保存在内存中，重启应用后失效。
+//! 命中缓存的请求不请求上游、不计入用量统计，响应带 `x-cc-switch-cache: hit` 头。
+//! 团队网关模式下缓存按成员隔离，成员不会命中他人请求写入的缓存而绕过各自的配额统计。
+
+use super::handler_context::RequestContext;
+use crate::database::Database;
+use axum::http::{header, HeaderMap, HeaderValue};
+use axum::response::Response;
+use bytes::Bytes;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 标记响应来自缓存的响应头
+const CACHE_HEADER: &str = "x-cc-switch-cache";
+
+/// 计算缓存键时忽略的请求字段（每次请求都可能不同，但不影响响应内容）
+const IGNORED_FIELDS: &[&str] = &["metadata", "user", "stream"];
+
+/// 缓存有效期上限（一年），避免过大的 `ttl_secs` 使过期时间溢出
+const MAX_TTL: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+static CACHE: Lazy<Mutex<HashMap<String, CachedResponse>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+
+struct CachedResponse {
+    status: u16,
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+    stored_at: Instant,
+    expires_at: Instant,
+}
+
+/// 响应缓存统计
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCacheStats {
+    /// 当前缓存的响应数（含已过期尚未清理的条目）
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// 规范化请求体：移除无关字段并按键名排序，使字段顺序不同的相同请求得到相同的键
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            let mut normalized = Map::new();
+            for key in keys {
+                normalized.insert(key.clone(), normalize(&map[key]));
+            }
+            Value::Object(normalized)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// 计算缓存键
+fn cache_key(
+    app_type: &str,
+    member_id: Option<&str>,
+    provider_id: &str,
+    endpoint: &str,
+    body: &Value,
+) -> String {
+    let mut body = body.clone();
+    if let Some(map) = body.as_object_mut() {
+        for field in IGNORED_FIELDS {
+            map.remove(*field);
+        }
+    }
+    let mut hasher = Sha256::new();
+    for part in [app_type, member_id.unwrap_or(""), provider_id, endpoint] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher.update(normalize(&body).to_string().as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// 查询缓存：命中时返回缓存的响应；未命中时在上下文中记录缓存键，供响应成功后写入
+///
+/// 未启用、流式请求或离线演练模式下直接返回 None。
+pub fn lookup(
+    db: &Database,
+    ctx: &mut RequestContext,
+    endpoint: &str,
+    body: &Value,
+    is_stream: bool,
+) -> Option<Response> {
+    if is_stream
+        || endpoint.contains("streamGenerateContent")
+        || super::mock_upstream::is_dry_run()
+        || !db
+            .get_response_cache_config()
+            .is_ok_and(|config| config.enabled)
+    {
+        return None;
+    }
+
+    let key = cache_key(
+        ctx.app_type_str,
+        ctx.member_id.as_deref(),
+        &ctx.provider.id,
+        endpoint,
+        body,
+    );
+    let cached = CACHE
+        .lock()
+        .ok()
+        .and_then(|mut cache| match cache.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                Some((entry.status, entry.content_type.clone(), entry.body.clone()))
+            }
+            Some(_) => {
+                cache.remove(&key);
+                None
+            }
+            None => None,
+        });
+
+    let Some((status, content_type, body)) = cached else {
+        MISSES.fetch_add(1, Ordering::Relaxed);
+        ctx.cache_key = Some(key);
+        return None;
+    };
+    HITS.fetch_add(1, Ordering::Relaxed);
+    log::info!("[{}] 命中响应缓存，跳过上游请求", ctx.tag);
+
+    let mut builder = Response::builder()
+        .status(status)
+        .header(CACHE_HEADER, "hit");
+    if let Some(content_type) = content_type {
+        builder = builder.header(header::CONTENT_TYPE, content_type);
+    }
+    builder.body(axum::body::Body::from(body)).ok()
+}
+
+/// 写入缓存（仅在本次请求查询过缓存且响应成功时生效）
+pub fn store(db: &Database, ctx: &RequestContext, status: u16, headers: &HeaderMap, body: &Bytes) {
+    let Some(key) = ctx.cache_key.clone() else {
+        return;
+    };
+    if !(200..300).contains(&status) {
+        return;
+    }
+    let config = match db.get_response_cache_config() {
+        Ok(config) if config.enabled && config.max_entries > 0 => config,
+        _ => return,
+    };
+
+    let now = Instant::now();
+    let entry = CachedResponse {
+        status,
+        content_type: headers.get(header::CONTENT_TYPE).cloned(),
+        body: body.clone(),
+        stored_at: now,
+        expires_at: now + Duration::from_secs(config.ttl_secs).min(MAX_TTL),
+    };
+    if let Ok(mut cache) = CACHE.lock() {
+        insert(&mut cache, key, entry, config.max_entries, now);
+    }
+}
+
+/// 插入缓存条目：超出上限时先清理过期条目，仍超出则淘汰最早写入的条目
+fn insert(
+    cache: &mut HashMap<String, CachedResponse>,
+    key: String,
+    entry: CachedResponse,
+    max_entries: usize,
+    now: Instant,
+) {
+    if !cache.contains_key(&key) && cache.len() >= max_entries {
+        cache.retain(|_, cached| cached.expires_at > now);
+        while cache.len() >= max_entries {
+            let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(key, entry);
+}
+
+/// 当前缓存统计
+pub fn stats() -> ResponseCacheStats {
+    ResponseCacheStats {
+        entries: CACHE.lock().map(|cache| cache.len()).unwrap_or(0),
+        hits: HITS.load(Ordering::Relaxed),
+        misses: MISSES.load(Ordering::Relaxed),
+    }
+}
+
+/// 清空缓存并重置统计，返回清除的条目数
+pub fn clear() -> usize {
+    HITS.store(0, Ordering::Relaxed);
+    MISSES.store(0, Ordering::Relaxed);
+    CACHE
+        .lock()
+        .map(|mut cache| {
+            let count = cache.len();
+            cache.clear();
+            count
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(stored_at: Instant, ttl: Duration) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            content_type: None,
+            body: Bytes::from_static(b"{}"),
+            stored_at,
+            expires_at: stored_at + ttl,
+        }
+    }
+
+    #[test]
+    fn cache_key_ignores_field_order_and_volatile_fields() {
+        let a = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}], "metadata": {"user_id": "1"}});
+        let b =
+            json!({"messages": [{"content": "hi", "role": "user"}], "model": "m", "stream": false});
+        assert_eq!(
+            cache_key("claude", None, "p", "/v1/messages", &a),
+            cache_key("claude", None, "p", "/v1/messages", &b)
+        );
+        assert_ne!(
+            cache_key("claude", None, "p", "/v1/messages", &a),
+            cache_key("claude", None, "other", "/v1/messages", &a)
+        );
+    }
+
+    #[test]
+    fn cache_key_is_scoped_to_team_member() {
+        let body = json!({"model": "m", "messages": []});
+        let key = |member| cache_key("claude", member, "p", "/v1/messages", &body);
+        assert_ne!(key(Some("alice")), key(Some("bob")));
+        assert_ne!(key(Some("alice")), key(None));
+        assert_eq!(key(Some("alice")), key(Some("alice")));
+    }
+
+    #[test]
+    fn huge_ttl_does_not_overflow() {
+        let now = Instant::now();
+        let expires_at = now + Duration::from_secs(u64::MAX).min(MAX_TTL);
+        assert!(expires_at > now);
+    }
+
+    #[test]
+    fn insert_evicts_expired_then_oldest_entries() {
+        let now = Instant::now();
+        let mut cache = HashMap::new();
+        cache.insert("expired".to_string(), entry(now, Duration::ZERO));
+        cache.insert("old".to_string(), entry(now, Duration::from_secs(60)));
+        cache.insert(
+            "recent".to_string(),
+            entry(now + Duration::from_millis(1), Duration::from_secs(60)),
+        );
+
+        insert(
+            &mut cache,
+            "new".to_string(),
+            entry(now, Duration::from_secs(60)),
+            2,
+            now,
+        );
+        let mut keys: Vec<_> = cache.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, ["new", "recent"]);
+    }
+}
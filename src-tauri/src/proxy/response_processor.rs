@@ -3,13 +3,18 @@
 //! 统一处理流式和非流式 API 响应
 
 use super::{
+    activity, cost_alert, cost_annotation,
     debug_log::{self, LogRequestId},
     handler_config::UsageParserConfig,
     handler_context::{RequestContext, StreamingTimeoutConfig},
+    journal, key_pool, otel, replay, request_inspection, response_cache,
     server::ProxyState,
+    stream_buffer::{self, SseEventBuffer, MAX_PENDING_EVENT_BYTES, STREAM_CHANNEL_CAPACITY},
+    thinking_filter, transcript,
     usage::parser::TokenUsage,
-    ProxyError,
+    webhook, ProxyError,
 };
+use crate::database::TranscriptRecord;
 use axum::response::{IntoResponse, Response};
 use bytes::Bytes;
 use futures::stream::{Stream, StreamExt};
@@ -53,16 +58,32 @@ pub async fn handle_streaming(
         .map(|id| id.0.clone());
     let status = response.status();
     let mut builder = axum::response::Response::builder().status(status);
+    let annotate = cost_annotation::is_enabled(&state.db);
 
-    // 复制响应头
+    // 复制响应头（追加成本注释会改变响应体长度，需去掉 content-length）
     for (key, value) in response.headers() {
+        if annotate && key == axum::http::header::CONTENT_LENGTH {
+            continue;
+        }
         builder = builder.header(key, value);
     }
 
-    // 创建字节流
+    // 创建字节流（经有界缓冲转发，客户端消费慢时暂停读取上游）
     let stream = response
         .bytes_stream()
         .map(|chunk| chunk.map_err(|e| std::io::Error::other(e.to_string())));
+    let stream = stream_buffer::bounded(stream, STREAM_CHANNEL_CAPACITY);
+
+    // 按供应商配置处理 thinking 块（仅 Anthropic SSE）
+    let thinking_mode = if ctx.app_type_str == "claude" {
+        thinking_filter::get_thinking_mode(&ctx.provider)
+    } else {
+        None
+    };
+    let stream = match thinking_mode {
+        Some(mode) => thinking_filter::filter_thinking_stream(stream, mode).boxed(),
+        None => stream.boxed(),
+    };
 
     // 创建使用量收集器
     let usage_collector = create_usage_collector(ctx, state, status.as_u16(), parser_config);
@@ -78,8 +99,23 @@ pub async fn handle_streaming(
         timeout_config,
         request_id,
     );
-
-    let body = axum::body::Body::from_stream(logged_stream);
+    let logged_stream = otel::instrument_stream(logged_stream, ctx.trace.clone());
+    let logged_stream =
+        activity::instrument_stream(logged_stream, ctx.activity.clone(), status.as_u16());
+    let logged_stream = journal::hold_until_end(logged_stream, ctx.journal());
+
+    // 按需在流末尾追加成本注释
+    let body = if annotate {
+        axum::body::Body::from_stream(cost_annotation::annotate_stream(
+            logged_stream,
+            state.db.clone(),
+            ctx.provider.clone(),
+            ctx.request_model.clone(),
+            *parser_config,
+        ))
+    } else {
+        axum::body::Body::from_stream(logged_stream)
+    };
     match builder.body(body) {
         Ok(resp) => resp,
         Err(e) => {
@@ -100,7 +136,7 @@ pub async fn handle_non_streaming(
         .extensions()
         .get::<LogRequestId>()
         .map(|id| id.0.clone());
-    let response_headers = response.headers().clone();
+    let mut response_headers = response.headers().clone();
     let status = response.status();
 
     // 读取响应体
@@ -115,9 +151,27 @@ pub async fn handle_non_streaming(
         debug_log::log_response_chunk(id, &body_text);
         debug_log::write_log_entry("\n--------------------------------------------------\n\n".to_string());
     }
+    replay::record_response(
+        &state.db,
+        ctx,
+        status.as_u16(),
+        &String::from_utf8_lossy(&body_bytes),
+    );
+    response_cache::store(&state.db, ctx, status.as_u16(), &response_headers, &body_bytes);
+
+    let annotate = cost_annotation::is_enabled(&state.db);
 
     // 解析并记录使用量
     if let Ok(json_value) = serde_json::from_slice::<Value>(&body_bytes) {
+        transcript::record(
+            &state.db,
+            ctx,
+            transcript::extract_response_text(&json_value),
+        );
+        if let Some(conversation) = &ctx.conversation {
+            conversation.finish_response(&json_value);
+        }
+
         // 解析使用量
         if let Some(usage) = (parser_config.response_parser)(&json_value) {
             // 优先使用 usage 中解析出的模型名称，其次使用响应中的 model 字段，最后回退到请求模型
@@ -129,6 +183,12 @@ pub async fn handle_non_streaming(
                 ctx.request_model.clone()
             };
 
+            if annotate {
+                let cost =
+                    cost_annotation::calculate_cost(&state.db, &ctx.provider, &model, &usage);
+                cost_annotation::insert_headers(&mut response_headers, &usage, cost);
+            }
+
             spawn_log_usage(state, ctx, usage, &model, status.as_u16(), false);
         } else {
             let model = json_value
@@ -165,6 +225,8 @@ pub async fn handle_non_streaming(
         );
     }
 
+    ctx.activity.completed(status.as_u16(), None);
+
     // 构建响应
     let mut builder = axum::response::Response::builder().status(status);
     for (key, value) in response_headers.iter() {
@@ -187,6 +249,12 @@ pub async fn process_response(
     state: &ProxyState,
     parser_config: &UsageParserConfig,
 ) -> Result<Response, ProxyError> {
+    if let Some(trace) = &ctx.trace {
+        trace.set_attribute("ccswitch.provider_id", ctx.provider.id.clone());
+        trace.set_attribute("http.response.status_code", response.status().as_u16());
+    }
+    request_inspection::record_response(&ctx.request_id, response.status(), response.headers());
+
     if is_sse_response(&response) {
         Ok(handle_streaming(response, ctx, state, parser_config).await)
     } else {
@@ -277,6 +345,8 @@ fn create_usage_collector(
     parser_config: &UsageParserConfig,
 ) -> SseUsageCollector {
     let state = state.clone();
+    let request_id = ctx.request_id.clone();
+    let provider = ctx.provider.clone();
     let provider_id = ctx.provider.id.clone();
     let request_model = ctx.request_model.clone();
     let app_type_str = parser_config.app_type_str;
@@ -285,19 +355,58 @@ fn create_usage_collector(
     let stream_parser = parser_config.stream_parser;
     let model_extractor = parser_config.model_extractor;
     let session_id = ctx.session_id.clone();
+    let transcript_prompt = ctx.transcript_prompt.clone();
+    let conversation = ctx.conversation.clone();
+    let member_id = ctx.member_id.clone();
+    let experiment = ctx.experiment.clone();
+    let capture_id = ctx.capture_id.clone();
 
     SseUsageCollector::new(start_time, move |events, first_token_ms| {
+        if let Some(capture_id) = capture_id.as_deref() {
+            replay::record(
+                &state.db,
+                capture_id,
+                &provider_id,
+                status_code,
+                start_time.elapsed().as_millis() as u64,
+                &replay::sse_text(&events),
+            );
+        }
+        if let Some(prompt) = transcript_prompt.clone() {
+            transcript::record_with(
+                &state.db,
+                TranscriptRecord {
+                    request_id: request_id.clone(),
+                    provider_id: provider_id.clone(),
+                    app_type: app_type_str.to_string(),
+                    model: model_extractor(&events, &request_model),
+                    created_at: chrono::Utc::now().timestamp(),
+                    request_text: prompt,
+                    response_text: transcript::extract_stream_text(&events),
+                },
+            );
+        }
+        if let Some(conversation) = &conversation {
+            conversation.finish_stream(&events);
+        }
+
         if let Some(usage) = stream_parser(&events) {
             let model = model_extractor(&events, &request_model);
             let latency_ms = start_time.elapsed().as_millis() as u64;
 
             let state = state.clone();
+            let request_id = request_id.clone();
+            let provider = provider.clone();
             let provider_id = provider_id.clone();
             let session_id = session_id.clone();
+            let member_id = member_id.clone();
+            let experiment = experiment.clone();
 
             tokio::spawn(async move {
+                key_pool::record_spend(&state.db, app_type_str, &provider, &model, &usage);
                 log_usage_internal(
                     &state,
+                    request_id,
                     &provider_id,
                     app_type_str,
                     &model,
@@ -307,6 +416,8 @@ fn create_usage_collector(
                     true, // is_streaming
                     status_code,
                     Some(session_id),
+                    member_id,
+                    experiment,
                 )
                 .await;
             });
@@ -314,12 +425,16 @@ fn create_usage_collector(
             let model = model_extractor(&events, &request_model);
             let latency_ms = start_time.elapsed().as_millis() as u64;
             let state = state.clone();
+            let request_id = request_id.clone();
             let provider_id = provider_id.clone();
             let session_id = session_id.clone();
+            let member_id = member_id.clone();
+            let experiment = experiment.clone();
 
             tokio::spawn(async move {
                 log_usage_internal(
                     &state,
+                    request_id,
                     &provider_id,
                     app_type_str,
                     &model,
@@ -329,6 +444,8 @@ fn create_usage_collector(
                     true, // is_streaming
                     status_code,
                     Some(session_id),
+                    member_id,
+                    experiment,
                 )
                 .await;
             });
@@ -347,15 +464,21 @@ fn spawn_log_usage(
     is_streaming: bool,
 ) {
     let state = state.clone();
+    let request_id = ctx.request_id.clone();
+    let provider = ctx.provider.clone();
     let provider_id = ctx.provider.id.clone();
     let app_type_str = ctx.app_type_str.to_string();
     let model = model.to_string();
     let latency_ms = ctx.latency_ms();
     let session_id = ctx.session_id.clone();
+    let member_id = ctx.member_id.clone();
+    let experiment = ctx.experiment.clone();
 
     tokio::spawn(async move {
+        key_pool::record_spend(&state.db, &app_type_str, &provider, &model, &usage);
         log_usage_internal(
             &state,
+            request_id,
             &provider_id,
             &app_type_str,
             &model,
@@ -365,6 +488,8 @@ fn spawn_log_usage(
             is_streaming,
             status_code,
             Some(session_id),
+            member_id,
+            experiment,
         )
         .await;
     });
@@ -374,6 +499,7 @@ fn spawn_log_usage(
 #[allow(clippy::too_many_arguments)]
 async fn log_usage_internal(
     state: &ProxyState,
+    request_id: String,
     provider_id: &str,
     app_type: &str,
     model: &str,
@@ -383,10 +509,14 @@ async fn log_usage_internal(
     is_streaming: bool,
     status_code: u16,
     session_id: Option<String>,
+    member_id: Option<String>,
+    experiment: Option<String>,
 ) {
     use super::usage::logger::UsageLogger;
 
-    let logger = UsageLogger::new(&state.db);
+    let logger = UsageLogger::new(&state.db)
+        .with_member_id(member_id)
+        .with_experiment(experiment);
 
     // 获取 provider 的 cost_multiplier
     let multiplier = match state.db.get_provider_by_id(provider_id, app_type) {
@@ -409,8 +539,6 @@ async fn log_usage_internal(
         _ => Decimal::from(1),
     };
 
-    let request_id = uuid::Uuid::new_v4().to_string();
-
     log::debug!(
         "[{app_type}] 记录请求日志: id={request_id}, provider={provider_id}, model={model}, streaming={is_streaming}, status={status_code}, latency_ms={latency_ms}, first_token_ms={first_token_ms:?}, session={}, input={}, output={}, cache_read={}, cache_creation={}",
         session_id.as_deref().unwrap_or("none"),
@@ -420,12 +548,12 @@ async fn log_usage_internal(
         usage.cache_creation_tokens
     );
 
-    if let Err(e) = logger.log_with_calculation(
+    match logger.log_with_calculation(
         request_id,
         provider_id.to_string(),
         app_type.to_string(),
         model.to_string(),
-        usage,
+        usage.clone(),
         multiplier,
         latency_ms,
         first_token_ms,
@@ -434,7 +562,63 @@ async fn log_usage_internal(
         None, // provider_type
         is_streaming,
     ) {
-        log::warn!("[USG-001] 记录使用量失败: {e}");
+        Ok(cost) => {
+            webhook::notify_request_completed(
+                &state.db,
+                app_type,
+                provider_id,
+                model,
+                &usage,
+                status_code,
+                latency_ms,
+                cost,
+            );
+            cost_alert::check_thresholds(&state.db, state.app_handle.as_ref(), app_type);
+        }
+        Err(e) => log::warn!("[USG-001] 记录使用量失败: {e}"),
+    }
+}
+
+/// 检查单个 SSE 事件：记录日志、检测 Rate limit 错误，并收集解析出的 JSON 数据
+fn inspect_event(tag: &str, event_text: &str, parsed: &mut Vec<Value>) {
+    for line in event_text.lines() {
+        let Some(data) = line.strip_prefix("data: ") else {
+            continue;
+        };
+        if data.trim() == "[DONE]" {
+            log::debug!("[{tag}] <<< SSE: [DONE]");
+            continue;
+        }
+
+        let json_value = serde_json::from_str::<Value>(data).ok();
+        // 检测 Rate limit 错误（复用已解析的 JSON，避免重复解析）
+        let rate_limited = match &json_value {
+            Some(value) => super::rate_limit_retry::detect_rate_limit_in_json(value),
+            None => super::rate_limit_retry::is_rate_limit_error(data),
+        };
+        if rate_limited {
+            log::warn!(
+                "[{}] 检测到流式响应中的 Rate limit 错误: {}",
+                tag,
+                data.chars().take(100).collect::<String>()
+            );
+            // 注意：在流式响应中，我们无法直接重试整个请求
+            // 这个错误会被传递给客户端，客户端可以选择重新发起请求
+        }
+
+        match json_value {
+            Some(value) => {
+                log::debug!(
+                    "[{}] <<< SSE 事件: {}",
+                    tag,
+                    data.chars().take(100).collect::<String>()
+                );
+                parsed.push(value);
+            }
+            None => {
+                log::debug!("[{tag}] <<< SSE 数据: {}", data.chars().take(100).collect::<String>());
+            }
+        }
     }
 }
 
@@ -447,7 +631,7 @@ pub fn create_logged_passthrough_stream(
     request_id: Option<String>,
 ) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send {
     async_stream::stream! {
-        let mut buffer = String::new();
+        let mut events = SseEventBuffer::default();
         let mut collector = usage_collector;
         let mut is_first_chunk = true;
         let req_id = request_id;
@@ -494,54 +678,27 @@ pub fn create_logged_passthrough_stream(
             match chunk_result {
                 Some(Ok(bytes)) => {
                     is_first_chunk = false;
-                    let text = String::from_utf8_lossy(&bytes);
-                    
-                    // 记录流式块到日志
+
+                    // 记录流式块到日志（仅在开启调试日志时转换为文本）
                     if let Some(id) = &req_id {
-                        debug_log::log_response_chunk(id, &text);
+                        debug_log::log_response_chunk(id, &String::from_utf8_lossy(&bytes));
                     }
-                    
-                    buffer.push_str(&text);
-
-                    // 尝试解析并记录完整的 SSE 事件
-                    while let Some(pos) = buffer.find("\n\n") {
-                        let event_text = buffer[..pos].to_string();
-                        buffer = buffer[pos + 2..].to_string();
-
-                        if !event_text.trim().is_empty() {
-                            // 提取 data 部分并尝试解析为 JSON
-                            for line in event_text.lines() {
-                                if let Some(data) = line.strip_prefix("data: ") {
-                                    if data.trim() != "[DONE]" {
-                                        // 检测 Rate limit 错误
-                                        if super::rate_limit_retry::detect_rate_limit_in_sse(&event_text) {
-                                            log::warn!(
-                                                "[{}] 检测到流式响应中的 Rate limit 错误: {}",
-                                                tag,
-                                                data.chars().take(100).collect::<String>()
-                                            );
-                                            // 注意：在流式响应中，我们无法直接重试整个请求
-                                            // 这个错误会被传递给客户端，客户端可以选择重新发起请求
-                                        }
-
-                                        if let Ok(json_value) = serde_json::from_str::<Value>(data) {
-                                            if let Some(c) = &collector {
-                                                c.push(json_value.clone()).await;
-                                            }
-                                            log::debug!(
-                                                "[{}] <<< SSE 事件: {}",
-                                                tag,
-                                                data.chars().take(100).collect::<String>()
-                                            );
-                                        } else {
-                                            log::debug!("[{tag}] <<< SSE 数据: {}", data.chars().take(100).collect::<String>());
-                                        }
-                                    } else {
-                                        log::debug!("[{tag}] <<< SSE: [DONE]");
-                                    }
-                                }
+
+                    // 仅在需要统计用量或输出调试日志时解析事件，原始块直接透传
+                    if collector.is_some() || log::log_enabled!(log::Level::Debug) {
+                        let mut parsed = Vec::new();
+                        events.split(&bytes, |event_text| inspect_event(tag, event_text, &mut parsed));
+                        if let Some(c) = &collector {
+                            for json_value in parsed {
+                                c.push(json_value).await;
                             }
                         }
+
+                        // 长时间没有事件分隔符时丢弃未完成的事件，避免缓冲无限增长（响应本身照常透传）
+                        if events.pending_len() > MAX_PENDING_EVENT_BYTES {
+                            log::warn!("[{tag}] 未完成的 SSE 事件超过 {MAX_PENDING_EVENT_BYTES} 字节，跳过解析");
+                            events.clear();
+                        }
                     }
 
                     yield Ok(bytes);
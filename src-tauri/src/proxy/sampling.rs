@@ -0,0 +1,206 @@
+//! 采样参数覆盖模块
+//!
+//! 部分 OpenAI 兼容后端在收到 Anthropic 客户端的默认采样参数时表现异常
+//! （如不支持 `top_k`、`temperature` 超出范围）。根据 Provider 配置的 `sampling`，
+//! 在转发前移除、补全或强制覆盖 `temperature` / `top_p` / `top_k`。
+//!
+//! 字段名按最终请求格式映射：Gemini 位于 `generationConfig` 下并使用 camelCase。
+
+use super::system_prompt::PromptFormat;
+use crate::provider::{Provider, SamplingOverrides, SamplingParams};
+use serde_json::{Map, Value};
+
+/// 采样参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Param {
+    Temperature,
+    TopP,
+    TopK,
+}
+
+impl Param {
+    const ALL: [Param; 3] = [Param::Temperature, Param::TopP, Param::TopK];
+
+    /// 配置中使用的名称
+    fn config_name(self) -> &'static str {
+        match self {
+            Param::Temperature => "temperature",
+            Param::TopP => "topP",
+            Param::TopK => "topK",
+        }
+    }
+
+    /// 请求体中的字段名
+    fn field_name(self, format: PromptFormat) -> &'static str {
+        match (self, format) {
+            (Param::Temperature, _) => "temperature",
+            (Param::TopP, PromptFormat::Gemini) => "topP",
+            (Param::TopK, PromptFormat::Gemini) => "topK",
+            (Param::TopP, _) => "top_p",
+            (Param::TopK, _) => "top_k",
+        }
+    }
+
+    fn value(self, params: &SamplingParams) -> Option<Value> {
+        match self {
+            Param::Temperature => params.temperature.map(Value::from),
+            Param::TopP => params.top_p.map(Value::from),
+            Param::TopK => params.top_k.map(Value::from),
+        }
+    }
+
+    /// strip 列表同时接受 camelCase 与 snake_case
+    fn matches(self, name: &str) -> bool {
+        let normalized = name.trim().replace('_', "").to_ascii_lowercase();
+        normalized == self.config_name().to_ascii_lowercase()
+    }
+}
+
+fn get_overrides(provider: &Provider) -> Option<&SamplingOverrides> {
+    provider.meta.as_ref()?.sampling.as_ref()
+}
+
+/// 对请求体应用采样参数覆盖
+///
+/// 返回是否做了修改
+pub fn apply_sampling_overrides(body: &mut Value, provider: &Provider, endpoint: &str) -> bool {
+    let Some(config) = get_overrides(provider) else {
+        return false;
+    };
+    let Some(format) = PromptFormat::from_endpoint(endpoint) else {
+        return false;
+    };
+
+    let target = match format {
+        PromptFormat::Gemini => {
+            let Some(obj) = body.as_object_mut() else {
+                return false;
+            };
+            // 仅在需要写入参数时才创建 generationConfig
+            let needs_insert = config.defaults != SamplingParams::default()
+                || config.overrides != SamplingParams::default();
+            if !needs_insert && !obj.contains_key("generationConfig") {
+                return false;
+            }
+            obj.entry("generationConfig")
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+        }
+        _ => body.as_object_mut(),
+    };
+    let Some(obj) = target else {
+        return false;
+    };
+
+    let mut changed = false;
+    for param in Param::ALL {
+        let field = param.field_name(format);
+
+        if config.strip.iter().any(|name| param.matches(name)) {
+            changed |= obj.remove(field).is_some();
+        }
+        if !obj.contains_key(field) {
+            if let Some(value) = param.value(&config.defaults) {
+                obj.insert(field.to_string(), value);
+                changed = true;
+            }
+        }
+        if let Some(value) = param.value(&config.overrides) {
+            if obj.get(field) != Some(&value) {
+                obj.insert(field.to_string(), value);
+                changed = true;
+            }
+        }
+    }
+
+    if changed {
+        log::debug!("[Sampling] 已对供应商 {} 应用采样参数覆盖", provider.name);
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+    use serde_json::json;
+
+    fn provider(config: SamplingOverrides) -> Provider {
+        Provider::with_meta(
+            "p",
+            ProviderMeta {
+                sampling: Some(config),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_override_and_default() {
+        let p = provider(SamplingOverrides {
+            defaults: SamplingParams {
+                top_p: Some(0.9),
+                temperature: Some(0.2),
+                ..Default::default()
+            },
+            overrides: SamplingParams {
+                temperature: Some(0.6),
+                ..Default::default()
+            },
+            strip: vec![],
+        });
+        let mut body = json!({"temperature": 1.0});
+        assert!(apply_sampling_overrides(
+            &mut body,
+            &p,
+            "/v1/chat/completions"
+        ));
+        assert_eq!(body["temperature"], 0.6);
+        assert_eq!(body["top_p"], 0.9);
+    }
+
+    #[test]
+    fn test_default_does_not_replace_existing() {
+        let p = provider(SamplingOverrides {
+            defaults: SamplingParams {
+                temperature: Some(0.2),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut body = json!({"temperature": 1.0});
+        assert!(!apply_sampling_overrides(&mut body, &p, "/v1/messages"));
+        assert_eq!(body["temperature"], 1.0);
+    }
+
+    #[test]
+    fn test_strip_accepts_both_cases() {
+        let p = provider(SamplingOverrides {
+            strip: vec!["top_k".into(), "topP".into()],
+            ..Default::default()
+        });
+        let mut body = json!({"top_k": 40, "top_p": 0.95, "temperature": 1.0});
+        assert!(apply_sampling_overrides(&mut body, &p, "/v1/messages"));
+        assert!(body.get("top_k").is_none());
+        assert!(body.get("top_p").is_none());
+        assert_eq!(body["temperature"], 1.0);
+    }
+
+    #[test]
+    fn test_gemini_generation_config() {
+        let p = provider(SamplingOverrides {
+            overrides: SamplingParams {
+                top_k: Some(20),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let mut body = json!({"contents": []});
+        assert!(apply_sampling_overrides(
+            &mut body,
+            &p,
+            "/v1beta/models/gemini-2.5-pro:generateContent"
+        ));
+        assert_eq!(body["generationConfig"]["topK"], 20);
+    }
+}
@@ -0,0 +1,174 @@
+//! 密钥脱敏
+//!
+//! 调试日志、HAR 导出等落盘内容在写入前统一经过这里：
+//! - 凭据请求头（`authorization`、`x-api-key`、`cookie` 等）的值替换为 `[REDACTED]`
+//! - JSON 字段：`api_key`、`authorization`、`token` 等字段的值
+//! - 文本：形如 `sk-...`、`Bearer ...`、`AIza...` 的密钥，以及 URL 查询参数 `key=` 等
+//!
+//! 脱敏始终生效，不受日志级别或 tool_result 脱敏配置影响。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// 替换密钥后的占位文本
+pub const REDACTED: &str = "[REDACTED]";
+
+/// 值需要整体替换的 JSON 字段 / 查询参数（小写，`-` 视为 `_`）
+const SECRET_KEYS: &[&str] = &[
+    "api_key",
+    "apikey",
+    "x_api_key",
+    "key",
+    "authorization",
+    "token",
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "secret",
+    "client_secret",
+    "password",
+];
+
+/// 值需要整体替换的请求头（小写）
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "x-goog-api-key",
+    "api-key",
+    "cookie",
+    "set-cookie",
+];
+
+static SECRET_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"sk-[A-Za-z0-9_\-]{16,}|(?i:bearer)\s+[A-Za-z0-9._~+/\-]{16,}=*|AIza[0-9A-Za-z_\-]{30,}",
+    )
+    .expect("Invalid secret regex")
+});
+
+/// 已序列化文本中的密钥字段（`"api_key": "..."`）与查询参数（`?key=...`）
+static SECRET_FIELD_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)("(?:api[_-]?key|x[_-]api[_-]key|authorization|access[_-]token|refresh[_-]token|id[_-]token|client[_-]secret|password)"\s*:\s*")[^"]*(")|([?&](?:key|api[_-]?key|access[_-]token|token)=)[^&\s"]+"#,
+    )
+    .expect("Invalid secret field regex")
+});
+
+pub fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase().replace('-', "_");
+    SECRET_KEYS.contains(&key.as_str())
+}
+
+pub fn is_secret_header(name: &str) -> bool {
+    SECRET_HEADERS.iter().any(|h| name.eq_ignore_ascii_case(h))
+}
+
+/// 替换文本中形似密钥的片段
+pub fn redact_text(text: &str) -> String {
+    SECRET_PATTERN.replace_all(text, REDACTED).into_owned()
+}
+
+/// 递归脱敏 JSON 中的密钥字段与字符串
+pub fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret_key(key) && !value.is_null() {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        Value::String(text) => *text = redact_text(text),
+        _ => {}
+    }
+}
+
+/// 脱敏请求头，凭据类请求头的值替换为占位文本
+pub fn redact_headers<K, V>(headers: impl IntoIterator<Item = (K, V)>) -> Vec<(String, String)>
+where
+    K: AsRef<str>,
+    V: AsRef<[u8]>,
+{
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            let name = name.as_ref();
+            let value = if is_secret_header(name) {
+                REDACTED.to_string()
+            } else {
+                redact_text(&String::from_utf8_lossy(value.as_ref()))
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// 格式化请求头，凭据类请求头的值替换为占位文本
+pub fn format_headers<K, V>(headers: impl IntoIterator<Item = (K, V)>) -> String
+where
+    K: AsRef<str>,
+    V: AsRef<[u8]>,
+{
+    let entries: Vec<String> = redact_headers(headers)
+        .into_iter()
+        .map(|(name, value)| format!("{name:?}: {value:?}"))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// 脱敏任意待落盘文本（已序列化的 JSON、SSE 块、错误信息、URL）
+pub fn redact_log_text(text: &str) -> Cow<'_, str> {
+    let fields = SECRET_FIELD_PATTERN.replace_all(text, |caps: &regex::Captures| {
+        match (caps.get(1), caps.get(2), caps.get(3)) {
+            (Some(prefix), Some(suffix), _) => {
+                format!("{}{REDACTED}{}", prefix.as_str(), suffix.as_str())
+            }
+            (_, _, Some(param)) => format!("{}{REDACTED}", param.as_str()),
+            _ => caps[0].to_string(),
+        }
+    });
+    if !SECRET_PATTERN.is_match(&fields) {
+        return fields;
+    }
+    Cow::Owned(SECRET_PATTERN.replace_all(&fields, REDACTED).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_credential_headers() {
+        let headers = [
+            ("x-api-key", "plain-key-without-prefix"),
+            ("Authorization", "Bearer abc"),
+            ("content-type", "application/json"),
+        ];
+        let formatted = format_headers(headers.iter().map(|(k, v)| (*k, v.as_bytes())));
+        assert!(!formatted.contains("plain-key-without-prefix"));
+        assert!(!formatted.contains("abc"));
+        assert!(formatted.contains(r#""content-type": "application/json""#));
+    }
+
+    #[test]
+    fn redacts_keys_in_serialized_text() {
+        let text = concat!(
+            r#"{"api_key": "custom-secret", "model": "m"} "#,
+            "https://host/v1beta/models/m:generate?key=raw-gemini-key&alt=sse ",
+            "token sk-ant-REDACTED"
+        );
+        let redacted = redact_log_text(text);
+        assert!(!redacted.contains("custom-secret"));
+        assert!(!redacted.contains("raw-gemini-key"));
+        assert!(!redacted.contains("abcdefghijklmnop"));
+        assert!(redacted.contains(r#""model": "m""#));
+        assert!(redacted.contains("&alt=sse"));
+        assert!(matches!(redact_log_text("no secrets"), Cow::Borrowed(_)));
+    }
+}
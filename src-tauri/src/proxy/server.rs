@@ -3,12 +3,31 @@
 //! 基于Axum的HTTP服务器，处理代理请求
 
 use super::{
-    failover_switch::FailoverSwitchManager, handlers, log_codes::srv as log_srv,
-    provider_router::ProviderRouter, types::*, ProxyError,
+    access_token::{enforce_access_token, enforce_monitoring_token},
+    admin_api, alert_rules, batches,
+    client_limiter::{enforce_client_rate_limit, ClientRateLimiter},
+    control,
+    discovery::{self, ActiveProxyEndpoint},
+    failback,
+    failover_switch::FailoverSwitchManager,
+    files, handlers, healthz,
+    ip_allowlist::enforce_ip_allowlist,
+    lan_access,
+    log_codes::srv as log_srv,
+    metrics,
+    provider_router::ProviderRouter,
+    rate_limit_sim,
+    request_limit::enforce_request_size_limit,
+    team_gateway::enforce_team_gateway,
+    tls,
+    types::*,
+    websocket, ProxyError,
 };
 use crate::database::Database;
 use axum::{
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{any, get, post},
     Router,
 };
 use std::net::SocketAddr;
@@ -32,6 +51,8 @@ pub struct ProxyState {
     pub app_handle: Option<tauri::AppHandle>,
     /// 故障转移切换管理器
     pub failover_manager: Arc<FailoverSwitchManager>,
+    /// 本地客户端限流器
+    pub client_limiter: Arc<ClientRateLimiter>,
 }
 
 /// 代理HTTP服务器
@@ -41,6 +62,10 @@ pub struct ProxyServer {
     shutdown_tx: Arc<RwLock<Option<oneshot::Sender<()>>>>,
     /// 服务器任务句柄，用于等待服务器实际关闭
     server_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// 自动回切后台任务句柄
+    failback_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
+    /// 告警规则后台任务句柄
+    alert_rules_handle: Arc<RwLock<Option<JoinHandle<()>>>>,
 }
 
 impl ProxyServer {
@@ -63,6 +88,7 @@ impl ProxyServer {
             provider_router,
             app_handle,
             failover_manager,
+            client_limiter: Arc::new(ClientRateLimiter::new()),
         };
 
         Self {
@@ -70,19 +96,58 @@ impl ProxyServer {
             state,
             shutdown_tx: Arc::new(RwLock::new(None)),
             server_handle: Arc::new(RwLock::new(None)),
+            failback_handle: Arc::new(RwLock::new(None)),
+            alert_rules_handle: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// 创建接替本服务器的新服务器（监听地址或端口变更时使用）
+    ///
+    /// 沿用本服务器的熔断器状态（ProviderRouter）、故障转移切换管理器与客户端限流计数，
+    /// 交接期间新旧服务器共享这些状态，变更监听地址不会重置熔断与限流
+    pub fn successor(&self, config: ProxyConfig, app_handle: Option<tauri::AppHandle>) -> Self {
+        let mut server = Self::new(config, self.state.db.clone(), app_handle);
+        server.state.provider_router = self.state.provider_router.clone();
+        server.state.failover_manager = self.state.failover_manager.clone();
+        server.state.client_limiter = self.state.client_limiter.clone();
+        server
+    }
+
+    /// 启动服务器（配置的端口被占用时顺延到后续空闲端口）
     pub async fn start(&self) -> Result<ProxyServerInfo, ProxyError> {
+        self.start_inner(true).await
+    }
+
+    /// 仅在配置的端口上启动（用于新旧服务器交接，端口冲突时直接失败）
+    pub async fn start_exact(&self) -> Result<ProxyServerInfo, ProxyError> {
+        self.start_inner(false).await
+    }
+
+    async fn start_inner(&self, port_fallback: bool) -> Result<ProxyServerInfo, ProxyError> {
         // 检查是否已在运行
         if self.shutdown_tx.read().await.is_some() {
             return Err(ProxyError::AlreadyRunning);
         }
 
-        let addr: SocketAddr =
-            format!("{}:{}", self.config.listen_address, self.config.listen_port)
-                .parse()
-                .map_err(|e| ProxyError::BindFailed(format!("无效的地址: {e}")))?;
+        let ip = lan_access::parse_listen_address(&self.config.listen_address)
+            .map_err(ProxyError::BindFailed)?;
+        if !ip.is_loopback() && self.state.db.get_proxy_lan_access().ok().flatten() == Some(false) {
+            return Err(ProxyError::BindFailed(format!(
+                "未开启局域网访问，无法监听 {}",
+                self.config.listen_address
+            )));
+        }
+
+        // HTTPS 监听（证书加载失败时直接拒绝启动，避免悄悄降级为明文）
+        let tls_config = self.state.db.get_proxy_tls_config().unwrap_or_default();
+        let tls_acceptor = if tls_config.enabled {
+            Some(tls::build_acceptor(
+                &tls_config,
+                &self.config.listen_address,
+            )?)
+        } else {
+            None
+        };
 
         // 创建关闭通道
         let (shutdown_tx, shutdown_rx) = oneshot::channel();
@@ -90,12 +155,48 @@ impl ProxyServer {
         // 构建路由
         let app = self.build_router();
 
-        // 绑定监听器
-        let listener = tokio::net::TcpListener::bind(&addr)
-            .await
+        // 绑定监听器（配置的端口被占用时顺延到后续空闲端口）
+        let mut listener = None;
+        let mut in_use_err = None;
+        for port in discovery::candidate_ports(self.config.listen_port, port_fallback) {
+            match tokio::net::TcpListener::bind(SocketAddr::new(ip, port)).await {
+                Ok(bound) => {
+                    listener = Some(bound);
+                    break;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => in_use_err = Some(e),
+                Err(e) => return Err(ProxyError::BindFailed(e.to_string())),
+            }
+        }
+        let listener = listener.ok_or_else(|| {
+            ProxyError::BindFailed(in_use_err.map(|e| e.to_string()).unwrap_or_default())
+        })?;
+        let addr = listener
+            .local_addr()
             .map_err(|e| ProxyError::BindFailed(e.to_string()))?;
+        if addr.port() != self.config.listen_port {
+            log::warn!(
+                "[{}] 端口 {} 已被占用，改用 {}",
+                log_srv::STARTED,
+                self.config.listen_port,
+                addr.port()
+            );
+        }
 
-        log::info!("[{}] 代理服务器启动于 {addr}", log_srv::STARTED);
+        log::info!(
+            "[{}] 代理服务器启动于 {}://{addr}",
+            log_srv::STARTED,
+            if tls_acceptor.is_some() {
+                "https"
+            } else {
+                "http"
+            }
+        );
+        lan_access::warn_if_exposed(
+            self.state.app_handle.as_ref(),
+            &self.config.listen_address,
+            addr.port(),
+        );
 
         // 保存关闭句柄
         *self.shutdown_tx.write().await = Some(shutdown_tx);
@@ -104,7 +205,7 @@ impl ProxyServer {
         let mut status = self.state.status.write().await;
         status.running = true;
         status.address = self.config.listen_address.clone();
-        status.port = self.config.listen_port;
+        status.port = addr.port();
         drop(status);
 
         // 记录启动时间
@@ -113,12 +214,19 @@ impl ProxyServer {
         // 启动服务器
         let state = self.state.clone();
         let handle = tokio::spawn(async move {
-            axum::serve(listener, app)
+            if let Some(acceptor) = tls_acceptor {
+                tls::serve(listener, acceptor, app, shutdown_rx).await;
+            } else {
+                axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
                 .with_graceful_shutdown(async {
                     shutdown_rx.await.ok();
                 })
                 .await
                 .ok();
+            }
 
             // 服务器停止后更新状态
             state.status.write().await.running = false;
@@ -128,10 +236,33 @@ impl ProxyServer {
         // 保存服务器任务句柄
         *self.server_handle.write().await = Some(handle);
 
+        // 启动自动回切任务
+        let failback_task = tokio::spawn(failback::run(self.state.clone()));
+        *self.failback_handle.write().await = Some(failback_task);
+
+        // 启动告警规则任务
+        let alert_rules_task = tokio::spawn(alert_rules::run(self.state.clone()));
+        *self.alert_rules_handle.write().await = Some(alert_rules_task);
+
+        let started_at = chrono::Utc::now().to_rfc3339();
+        discovery::write(&ActiveProxyEndpoint {
+            address: self.config.listen_address.clone(),
+            port: addr.port(),
+            configured_port: self.config.listen_port,
+            base_url: discovery::base_url(
+                &self.config.listen_address,
+                addr.port(),
+                tls_config.enabled,
+            ),
+            pid: std::process::id(),
+            started_at: started_at.clone(),
+            control_token: Some(control::token().to_string()),
+        });
+
         Ok(ProxyServerInfo {
             address: self.config.listen_address.clone(),
-            port: self.config.listen_port,
-            started_at: chrono::Utc::now().to_rfc3339(),
+            port: addr.port(),
+            started_at,
         })
     }
 
@@ -142,6 +273,13 @@ impl ProxyServer {
         } else {
             return Err(ProxyError::NotRunning);
         }
+        discovery::remove();
+        if let Some(task) = self.failback_handle.write().await.take() {
+            task.abort();
+        }
+        if let Some(task) = self.alert_rules_handle.write().await.take() {
+            task.abort();
+        }
 
         // 2. 等待服务器任务结束（带 5 秒超时保护）
         if let Some(handle) = self.server_handle.write().await.take() {
@@ -167,6 +305,39 @@ impl ProxyServer {
         }
     }
 
+    /// 平滑下线：停止接受新连接，已建立的请求（包括流式响应）在后台继续完成
+    ///
+    /// 用于监听地址变更时的新旧服务器交接，最多等待 `timeout` 后放弃等待
+    pub async fn drain(&self, timeout: std::time::Duration) {
+        if let Some(tx) = self.shutdown_tx.write().await.take() {
+            let _ = tx.send(());
+        }
+        if let Some(task) = self.failback_handle.write().await.take() {
+            task.abort();
+        }
+        if let Some(task) = self.alert_rules_handle.write().await.take() {
+            task.abort();
+        }
+        let Some(handle) = self.server_handle.write().await.take() else {
+            return;
+        };
+
+        let address = format!("{}:{}", self.config.listen_address, self.config.listen_port);
+        tokio::spawn(async move {
+            match tokio::time::timeout(timeout, handle).await {
+                Ok(_) => log::info!(
+                    "[{}] 旧代理服务器 {address} 已处理完剩余请求",
+                    log_srv::STOPPED
+                ),
+                Err(_) => log::warn!(
+                    "[{}] 旧代理服务器 {address} 在 {}s 内未处理完剩余请求，停止等待",
+                    log_srv::STOP_TIMEOUT,
+                    timeout.as_secs()
+                ),
+            }
+        });
+    }
+
     pub async fn get_status(&self) -> ProxyStatus {
         let mut status = self.state.status.read().await.clone();
 
@@ -195,13 +366,38 @@ impl ProxyServer {
             .allow_methods(Any)
             .allow_headers(Any);
 
-        Router::new()
-            // 健康检查
-            .route("/health", get(handlers::health_check))
-            .route("/status", get(handlers::get_status))
+        // API 路由（受本地客户端限流保护）
+        let api_routes = Router::new()
             // Claude API (支持带前缀和不带前缀两种格式)
             .route("/v1/messages", post(handlers::handle_messages))
             .route("/claude/v1/messages", post(handlers::handle_messages))
+            .route(
+                "/v1/messages/count_tokens",
+                post(handlers::handle_count_tokens),
+            )
+            .route(
+                "/claude/v1/messages/count_tokens",
+                post(handlers::handle_count_tokens),
+            )
+            // Claude Message Batches API（创建/轮询/取消/下载结果）
+            .route("/v1/messages/batches", any(batches::handle_message_batches))
+            .route(
+                "/v1/messages/batches/*path",
+                any(batches::handle_message_batches),
+            )
+            .route(
+                "/claude/v1/messages/batches",
+                any(batches::handle_message_batches),
+            )
+            .route(
+                "/claude/v1/messages/batches/*path",
+                any(batches::handle_message_batches),
+            )
+            // Claude Files API（multipart 上传/列表/下载/删除）
+            .route("/v1/files", any(files::handle_files))
+            .route("/v1/files/*path", any(files::handle_files))
+            .route("/claude/v1/files", any(files::handle_files))
+            .route("/claude/v1/files/*path", any(files::handle_files))
             // OpenAI Chat Completions API (Codex CLI，支持带前缀和不带前缀)
             .route("/chat/completions", post(handlers::handle_chat_completions))
             .route(
@@ -221,10 +417,64 @@ impl ProxyServer {
             .route("/v1/responses", post(handlers::handle_responses))
             .route("/v1/v1/responses", post(handlers::handle_responses))
             .route("/codex/v1/responses", post(handlers::handle_responses))
+            // OpenAI Realtime API（WebSocket 升级后双向透传）
+            .route("/v1/realtime", get(websocket::handle_realtime))
+            .route("/codex/v1/realtime", get(websocket::handle_realtime))
             // Gemini API (支持带前缀和不带前缀)
             .route("/v1beta/*path", post(handlers::handle_gemini))
             .route("/gemini/v1beta/*path", post(handlers::handle_gemini))
+            // 请求体大小由本地配置限制（取代 axum 默认的 2MB 上限）
+            .layer(DefaultBodyLimit::disable())
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                enforce_request_size_limit,
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                enforce_client_rate_limit,
+            ))
+            // 团队网关成员识别（后添加的 layer 先执行：先拒绝无效令牌，再执行本地限流）
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                enforce_team_gateway,
+            ))
+            // 本地访问令牌（最先执行）
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                enforce_access_token,
+            ));
+
+        // 运行状态与指标（须携带访问令牌或管理令牌，均未设置时仅限本机）
+        let monitoring_routes = Router::new()
+            .route("/status", get(handlers::get_status))
+            .route("/metrics", get(metrics::metrics))
+            .route_layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                enforce_monitoring_token,
+            ));
+
+        Router::new()
+            // 健康检查（/healthz 未通过校验时只返回整体状态）
+            .route("/health", get(handlers::health_check))
+            .route("/healthz", get(healthz::healthz))
+            .merge(monitoring_routes)
+            // 本地 rate limit 模拟（仅回环地址）
+            .route(
+                "/ccswitch/simulate/rate-limit",
+                get(rate_limit_sim::get_simulation)
+                    .post(rate_limit_sim::start_simulation)
+                    .delete(rate_limit_sim::stop_simulation),
+            )
+            .merge(api_routes)
+            .nest("/admin", admin_api::router(self.state.clone()))
+            // 本机控制接口（仅回环地址，供命令行切换与停止）
+            .nest("/ccswitch/control", control::router())
             .layer(cors)
+            // IP 白名单作用于全部路由，最先执行
+            .layer(middleware::from_fn_with_state(
+                self.state.clone(),
+                enforce_ip_allowlist,
+            ))
             .with_state(self.state.clone())
     }
 
@@ -0,0 +1,155 @@
+//! 影子流量
+//!
+//! 启用后（settings 表 `shadow_traffic_config`）代理将请求复制一份，异步发往该应用指定的影子供应商：
+//! 影子请求的响应直接丢弃，不影响原请求，仅在 `proxy_shadow_results` 中记录状态、延迟与用量，
+//! 用于在切换前评估新中转的质量与速度。
+//!
+//! 影子请求与请求重放共用发送逻辑：按影子供应商重新执行模型映射、格式转换与参数覆盖，
+//! 不经过故障转移、熔断与用量统计；原请求已由影子供应商处理或处于离线演练模式时不会复制。
+
+use super::handler_config::{
+    UsageParserConfig, CLAUDE_PARSER_CONFIG, CODEX_PARSER_CONFIG, GEMINI_PARSER_CONFIG,
+    OPENAI_PARSER_CONFIG,
+};
+use super::handler_context::RequestContext;
+use super::replay;
+use super::usage::parser::TokenUsage;
+use crate::app_config::AppType;
+use crate::database::{Database, ShadowResult};
+use crate::error::AppError;
+use rand::Rng;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// 记录的错误响应体最大长度（字符）
+const ERROR_BODY_MAX_CHARS: usize = 500;
+
+/// 按配置复制请求到影子供应商（未启用、未命中采样或原请求已发往影子供应商时跳过）
+pub fn mirror(db: &Arc<Database>, ctx: &RequestContext, endpoint: &str, body: &Value) {
+    let config = match db.get_shadow_traffic_config() {
+        Ok(config) if config.enabled => config,
+        _ => return,
+    };
+    let Some(shadow_id) = config.targets.get(ctx.app_type_str).cloned() else {
+        return;
+    };
+    // 离线演练时不向真实上游复制请求
+    if shadow_id == ctx.provider.id
+        || super::mock_upstream::is_dry_run()
+        || rand::thread_rng().gen_range(0..100) >= config.sample_percent.min(100)
+    {
+        return;
+    }
+
+    let db = db.clone();
+    let app_type = ctx.app_type.clone();
+    let primary_provider_id = ctx.provider.id.clone();
+    let model = ctx.request_model.clone();
+    let endpoint = endpoint.to_string();
+    let body = body.clone();
+    let tag = ctx.tag;
+    tokio::spawn(async move {
+        let mut result = ShadowResult {
+            app_type: app_type.as_str().to_string(),
+            primary_provider_id,
+            shadow_provider_id: shadow_id.clone(),
+            model,
+            status_code: 0,
+            latency_ms: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            error_message: None,
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        if let Err(e) = send(&db, &app_type, &shadow_id, &endpoint, body, &mut result).await {
+            log::debug!("[{tag}] 影子请求发往 {shadow_id} 失败: {e}");
+            result.error_message = Some(e.to_string());
+        }
+        if let Err(e) = db.insert_shadow_result(&result) {
+            log::warn!("[{tag}] 保存影子请求结果失败: {e}");
+        }
+    });
+}
+
+/// 发送影子请求并将状态、延迟与用量写入 `result`
+async fn send(
+    db: &Database,
+    app_type: &AppType,
+    shadow_id: &str,
+    endpoint: &str,
+    body: Value,
+    result: &mut ShadowResult,
+) -> Result<(), AppError> {
+    let provider = db
+        .get_provider_by_id(shadow_id, app_type.as_str())?
+        .ok_or_else(|| AppError::Message(format!("影子供应商 {shadow_id} 不存在")))?;
+    let reply = replay::send(app_type, &provider, endpoint, body).await?;
+    result.status_code = reply.status;
+    result.latency_ms = reply.latency_ms;
+
+    if !(200..300).contains(&reply.status) {
+        result.error_message = Some(reply.body.chars().take(ERROR_BODY_MAX_CHARS).collect());
+        return Ok(());
+    }
+    if let Some(usage) = parse_usage(parser_for(&reply.endpoint), &reply.body) {
+        result.input_tokens = usage.input_tokens;
+        result.output_tokens = usage.output_tokens;
+        if let Some(model) = usage.model {
+            result.model = model;
+        }
+    }
+    Ok(())
+}
+
+/// 按实际请求的端点选择用量解析器
+fn parser_for(endpoint: &str) -> &'static UsageParserConfig {
+    if endpoint.contains("generateContent") {
+        &GEMINI_PARSER_CONFIG
+    } else if endpoint.ends_with("/responses") {
+        &CODEX_PARSER_CONFIG
+    } else if endpoint.ends_with("chat/completions") {
+        &OPENAI_PARSER_CONFIG
+    } else {
+        &CLAUDE_PARSER_CONFIG
+    }
+}
+
+/// 解析完整响应体中的用量（SSE 文本按事件解析）
+fn parse_usage(parser: &UsageParserConfig, body: &str) -> Option<TokenUsage> {
+    let trimmed = body.trim_start();
+    if trimmed.starts_with("data:") || trimmed.starts_with("event:") {
+        let events: Vec<Value> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(str::trim)
+            .filter(|data| *data != "[DONE]")
+            .filter_map(|data| serde_json::from_str(data).ok())
+            .collect();
+        (parser.stream_parser)(&events)
+    } else {
+        serde_json::from_str(body)
+            .ok()
+            .and_then(|value| (parser.response_parser)(&value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_usage_from_streaming_and_json_bodies() {
+        let stream = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"model\":\"claude-sonnet\",\"usage\":{\"input_tokens\":12,\"output_tokens\":1}}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"usage\":{\"output_tokens\":7}}\n\n",
+        );
+        let usage = parse_usage(parser_for("/v1/messages"), stream).unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 7));
+
+        let json = r#"{"model":"gpt-4o","usage":{"prompt_tokens":3,"completion_tokens":4,"total_tokens":7}}"#;
+        let usage = parse_usage(parser_for("/v1/chat/completions"), json).unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (3, 4));
+    }
+}
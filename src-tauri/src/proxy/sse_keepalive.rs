@@ -0,0 +1,259 @@
+//! SSE 保活
+//!
+//! 部分上游在返回首个 token 前会停顿 60 秒以上，期间客户端收不到任何字节，可能因空闲超时断开连接。
+//! 启用后（settings 表 `sse_keepalive_config`），若首个保活间隔内上游仍未响应，
+//! 才以 `200 text/event-stream` 响应客户端，并在等待期间按间隔发送 SSE 注释行 `: keepalive`
+//! （客户端按规范忽略注释行），上游响应到达后继续透传其事件流；
+//! 转发过程中的静默期同样会补发保活行（仅在完整事件之间插入）。
+//!
+//! 首个间隔内完成的响应（包括快速失败）原样返回，保留真实状态码，客户端可照常重试。
+//! 响应头已提前发送后，转发失败或上游返回错误时按路由的协议以 SSE 错误事件告知客户端：
+//! Claude 路由使用 Anthropic 的 `event: error`，Codex 路由使用 OpenAI 的错误格式。
+
+use super::{system_prompt::PromptFormat, ProxyError};
+use crate::database::Database;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::time::Duration;
+
+/// 保活注释行
+const KEEPALIVE: &[u8] = b": keepalive\n\n";
+
+/// 读取错误响应体的最大字节数
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+/// 流式请求启用保活时返回保活间隔（非流式请求或未启用时返回 None）
+pub fn interval(db: &Database, body: &Value) -> Option<Duration> {
+    let is_stream = body
+        .get("stream")
+        .and_then(|s| s.as_bool())
+        .unwrap_or(false);
+    if !is_stream {
+        return None;
+    }
+    match db.get_sse_keepalive_config() {
+        Ok(config) if config.enabled && config.interval_secs > 0 => {
+            Some(Duration::from_secs(config.interval_secs))
+        }
+        _ => None,
+    }
+}
+
+/// 从错误响应体中提取错误对象（`{"error": {...}}`），无法解析时以状态码和原文构造
+fn error_object(status: StatusCode, body: &[u8]) -> Value {
+    serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|v| v.get("error").filter(|e| e.is_object()).cloned())
+        .unwrap_or_else(|| {
+            json!({
+                "type": "api_error",
+                "message": format!("{status}: {}", String::from_utf8_lossy(body)),
+            })
+        })
+}
+
+/// 将上游错误响应转换为对应协议的 SSE 错误事件
+fn error_event(format: PromptFormat, status: StatusCode, body: &[u8]) -> Bytes {
+    let error = error_object(status, body);
+    match format {
+        PromptFormat::Anthropic => {
+            let data = json!({ "type": "error", "error": error });
+            Bytes::from(format!("event: error\ndata: {data}\n\n"))
+        }
+        PromptFormat::OpenAIResponses => {
+            let code = error
+                .get("code")
+                .filter(|c| !c.is_null())
+                .or_else(|| error.get("type"))
+                .cloned()
+                .unwrap_or(Value::Null);
+            let data = json!({
+                "type": "error",
+                "code": code,
+                "message": error.get("message").cloned().unwrap_or(Value::Null),
+                "param": error.get("param").cloned().unwrap_or(Value::Null),
+            });
+            Bytes::from(format!("event: error\ndata: {data}\n\n"))
+        }
+        PromptFormat::OpenAIChat | PromptFormat::Gemini => {
+            let data = json!({ "error": error });
+            Bytes::from(format!("data: {data}\n\n"))
+        }
+    }
+}
+
+enum Next<T> {
+    Ready(T),
+    Tick,
+}
+
+/// 包装流式请求的处理过程：首个间隔内完成时原样返回，否则发送保活行并在完成后透传响应事件流
+///
+/// `format` 为路由的协议格式，决定提前提交响应头后错误事件的形状。
+pub async fn wrap<F>(
+    tag: &'static str,
+    interval: Duration,
+    format: PromptFormat,
+    response: F,
+) -> Response
+where
+    F: Future<Output = Result<Response, ProxyError>> + Send + 'static,
+{
+    let mut response = Box::pin(response);
+    if let Ok(result) = tokio::time::timeout(interval, &mut response).await {
+        return result.unwrap_or_else(IntoResponse::into_response);
+    }
+
+    log::debug!("[{tag}] 等待上游响应，提前返回 SSE 响应头并发送保活");
+    let stream = async_stream::stream! {
+        yield Ok::<Bytes, std::io::Error>(Bytes::from_static(KEEPALIVE));
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+
+        // 等待上游响应，期间定时发送保活行
+        let response = loop {
+            let next = tokio::select! {
+                result = &mut response => Next::Ready(result),
+                _ = ticker.tick() => Next::Tick,
+            };
+            match next {
+                Next::Ready(result) => break result.unwrap_or_else(IntoResponse::into_response),
+                Next::Tick => {
+                    log::debug!("[{tag}] 等待上游响应，发送 SSE 保活");
+                    yield Ok(Bytes::from_static(KEEPALIVE));
+                }
+            }
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = axum::body::to_bytes(response.into_body(), MAX_ERROR_BODY_BYTES)
+                .await
+                .unwrap_or_default();
+            log::warn!("[{tag}] 上游返回错误 {status}，已通过 SSE error 事件告知客户端");
+            yield Ok(error_event(format, status, &body));
+            return;
+        }
+
+        // 透传事件流，静默期在完整事件之间补发保活行
+        let mut body = response.into_body().into_data_stream();
+        let mut at_event_boundary = true;
+        ticker.reset();
+        loop {
+            let next = tokio::select! {
+                chunk = body.next() => Next::Ready(chunk),
+                _ = ticker.tick() => Next::Tick,
+            };
+            match next {
+                Next::Ready(Some(Ok(bytes))) => {
+                    at_event_boundary = bytes.ends_with(b"\n\n");
+                    ticker.reset();
+                    yield Ok(bytes);
+                }
+                Next::Ready(Some(Err(e))) => {
+                    yield Err(std::io::Error::other(e.to_string()));
+                    break;
+                }
+                Next::Ready(None) => break,
+                Next::Tick if at_event_boundary => {
+                    yield Ok(Bytes::from_static(KEEPALIVE));
+                }
+                Next::Tick => {}
+            }
+        }
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, "text/event-stream"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        axum::body::Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sends_keepalive_while_waiting_and_forwards_errors() {
+        let response = wrap(
+            "Test",
+            Duration::from_millis(20),
+            PromptFormat::Anthropic,
+            async {
+                tokio::time::sleep(Duration::from_millis(70)).await;
+                Err(ProxyError::UpstreamError {
+                    status: 529,
+                    body: Some(
+                        r#"{"type":"error","error":{"type":"overloaded_error"}}"#.to_string(),
+                    ),
+                })
+            },
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.starts_with(": keepalive\n\n"));
+        assert!(text.ends_with("\n\n"));
+        assert!(text.contains("event: error\ndata: "));
+        assert!(text.contains("overloaded_error"));
+    }
+
+    #[tokio::test]
+    async fn passes_fast_errors_through_with_real_status() {
+        let response = wrap(
+            "Test",
+            Duration::from_millis(200),
+            PromptFormat::Anthropic,
+            async {
+                Err(ProxyError::UpstreamError {
+                    status: 529,
+                    body: Some(
+                        r#"{"type":"error","error":{"type":"overloaded_error"}}"#.to_string(),
+                    ),
+                })
+            },
+        )
+        .await;
+        assert_eq!(response.status().as_u16(), 529);
+        assert_ne!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+    }
+
+    #[test]
+    fn formats_errors_per_protocol() {
+        let status = StatusCode::TOO_MANY_REQUESTS;
+        let body = br#"{"error":{"message":"slow down","type":"rate_limit_error","code":"rate_limit_exceeded"}}"#;
+
+        let chat = String::from_utf8(error_event(PromptFormat::OpenAIChat, status, body).to_vec())
+            .unwrap();
+        assert!(!chat.contains("event:"));
+        let data: Value = serde_json::from_str(chat["data: ".len()..].trim()).unwrap();
+        assert_eq!(data["error"]["code"], "rate_limit_exceeded");
+
+        let responses = error_event(PromptFormat::OpenAIResponses, status, body);
+        let responses = String::from_utf8(responses.to_vec()).unwrap();
+        let data: Value =
+            serde_json::from_str(responses.trim().split_once("data: ").unwrap().1).unwrap();
+        assert_eq!(data["type"], "error");
+        assert_eq!(data["code"], "rate_limit_exceeded");
+        assert_eq!(data["message"], "slow down");
+
+        let anthropic = error_event(PromptFormat::Anthropic, status, b"plain text");
+        let anthropic = String::from_utf8(anthropic.to_vec()).unwrap();
+        assert!(anthropic.starts_with("event: error\ndata: "));
+        assert!(anthropic.contains("plain text"));
+    }
+}
@@ -0,0 +1,148 @@
+//! 有界流式缓冲
+//!
+//! 上游响应流由独立任务读取，经容量固定的 channel 转交给客户端响应流：
+//! 客户端消费变慢（如终端暂停输出）时 channel 写满，读取任务随之暂停，
+//! 不再继续从上游读取，背压经 TCP 窗口传递给上游，内存占用保持在
+//! `STREAM_CHANNEL_CAPACITY` 个块以内。客户端断开后读取任务立即结束并释放上游连接。
+//!
+//! 需要检查事件内容（用量统计、日志）时使用 [`SseEventBuffer`] 按字节切分事件：
+//! 原始块照常以 `Bytes` 透传，切分时直接借用块内数据，仅跨块的未完成事件需要拷贝。
+
+use futures::stream::{Stream, StreamExt};
+use tokio::sync::mpsc;
+
+/// 缓冲的最大块数（上游 SSE 块通常不超过数 KB）
+pub const STREAM_CHANNEL_CAPACITY: usize = 16;
+
+/// 等待事件分隔符时最多缓冲的字节数
+pub const MAX_PENDING_EVENT_BYTES: usize = 1024 * 1024;
+
+/// 通过有界 channel 转发流，读取端与消费端解耦但缓冲不超过 `capacity` 个元素
+pub fn bounded<S, T>(stream: S, capacity: usize) -> impl Stream<Item = T> + Send + 'static
+where
+    S: Stream<Item = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(capacity.max(1));
+    tokio::spawn(async move {
+        tokio::pin!(stream);
+        while let Some(item) = stream.next().await {
+            // 接收端已销毁（客户端断开），停止读取上游
+            if tx.send(item).await.is_err() {
+                break;
+            }
+        }
+    });
+    futures::stream::unfold(rx, |mut rx| async move {
+        let item = rx.recv().await?;
+        Some((item, rx))
+    })
+}
+
+/// 按字节切分 SSE 事件（以空行 `\n\n` 或 `\r\n\r\n` 分隔）
+#[derive(Debug, Default)]
+pub struct SseEventBuffer {
+    /// 跨块的未完成事件
+    pending: Vec<u8>,
+}
+
+impl SseEventBuffer {
+    /// 追加一个块，并对其中每个完整事件（不含分隔符）调用 `on_event`
+    pub fn split(&mut self, chunk: &[u8], mut on_event: impl FnMut(&str)) {
+        if self.pending.is_empty() {
+            let consumed = emit_events(chunk, &mut on_event);
+            self.pending.extend_from_slice(&chunk[consumed..]);
+        } else {
+            self.pending.extend_from_slice(chunk);
+            let consumed = emit_events(&self.pending, &mut on_event);
+            self.pending.drain(..consumed);
+        }
+    }
+
+    /// 未完成事件的字节数
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// 丢弃未完成的事件
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// 取出未完成事件的原始字节（流结束或放弃切分时原样转发）
+    pub fn take_pending(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// 查找下一个事件分隔符，返回（位置, 长度）
+fn find_separator(data: &[u8]) -> Option<(usize, usize)> {
+    (0..data.len()).find_map(|i| {
+        if data[i..].starts_with(b"\n\n") {
+            Some((i, 2))
+        } else if data[i..].starts_with(b"\r\n\r\n") {
+            Some((i, 4))
+        } else {
+            None
+        }
+    })
+}
+
+/// 依次回调 `data` 中的完整事件，返回已消费的字节数
+fn emit_events(data: &[u8], on_event: &mut impl FnMut(&str)) -> usize {
+    let mut start = 0;
+    while let Some((pos, len)) = find_separator(&data[start..]) {
+        on_event(&String::from_utf8_lossy(&data[start..start + pos]));
+        start += pos + len;
+    }
+    start
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn stops_reading_when_consumer_is_slow() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let counter = produced.clone();
+        let source = futures::stream::iter(0..1000).inspect(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let mut stream = Box::pin(bounded(source, 4));
+        assert_eq!(stream.next().await, Some(0));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // 已读取量不超过：已消费 1 + channel 容量 4 + 正在等待发送的 1
+        assert!(produced.load(Ordering::SeqCst) <= 6);
+
+        let rest: Vec<_> = stream.collect().await;
+        assert_eq!(rest.len(), 999);
+    }
+
+    #[test]
+    fn splits_events_across_chunks_and_multibyte_boundaries() {
+        let data = "data: {\"text\":\"你好\"}\n\ndata: [DONE]\n\n".as_bytes();
+        let mut buffer = SseEventBuffer::default();
+        let mut events = Vec::new();
+        // 在多字节字符中间切分
+        buffer.split(&data[..16], |event| events.push(event.to_string()));
+        buffer.split(&data[16..], |event| events.push(event.to_string()));
+        assert_eq!(events, [r#"data: {"text":"你好"}"#, "data: [DONE]"]);
+        assert_eq!(buffer.pending_len(), 0);
+    }
+
+    #[test]
+    fn splits_crlf_delimited_events() {
+        let mut buffer = SseEventBuffer::default();
+        let mut events = Vec::new();
+        buffer.split(b"event: ping\r\ndata: {}\r\n\r\ndata: ", |event| {
+            events.push(event.to_string())
+        });
+        assert_eq!(events, ["event: ping\r\ndata: {}"]);
+        assert_eq!(buffer.take_pending(), b"data: ");
+    }
+}
@@ -0,0 +1,288 @@
+//! 系统提示词注入模块
+//!
+//! 根据 Provider 配置的 `systemPrompt`，在请求转发前向系统提示词添加前缀/后缀。
+//! 适用于需要在提示词中携带路由标记的中转服务，或透明地附加组织级指令。
+//!
+//! ## 支持的请求格式
+//! - Anthropic Messages：`system`（字符串或 text block 数组）
+//! - OpenAI Chat Completions：首条 `system`/`developer` 消息
+//! - OpenAI Responses：`instructions`
+//! - Gemini：`systemInstruction.parts`
+
+use crate::provider::{Provider, SystemPromptInjection};
+use serde_json::{json, Value};
+
+/// 前缀/后缀与原提示词之间的分隔符
+const SEPARATOR: &str = "\n\n";
+
+/// 请求体的提示词格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptFormat {
+    Anthropic,
+    OpenAIChat,
+    OpenAIResponses,
+    Gemini,
+}
+
+impl PromptFormat {
+    /// 根据上游端点推断请求体格式
+    pub fn from_endpoint(endpoint: &str) -> Option<Self> {
+        let path = endpoint.split('?').next().unwrap_or(endpoint);
+        if path.ends_with("/messages") {
+            Some(Self::Anthropic)
+        } else if path.ends_with("/chat/completions") {
+            Some(Self::OpenAIChat)
+        } else if path.ends_with("/responses") {
+            Some(Self::OpenAIResponses)
+        } else if path.contains(":generateContent") || path.contains(":streamGenerateContent") {
+            Some(Self::Gemini)
+        } else {
+            None
+        }
+    }
+}
+
+/// 获取 Provider 配置的系统提示词注入（前缀和后缀均为空时返回 None）
+fn get_injection(provider: &Provider) -> Option<(&str, &str)> {
+    let injection: &SystemPromptInjection = provider.meta.as_ref()?.system_prompt.as_ref()?;
+    let prefix = injection.prefix.as_deref().unwrap_or("");
+    let suffix = injection.suffix.as_deref().unwrap_or("");
+    if prefix.trim().is_empty() && suffix.trim().is_empty() {
+        None
+    } else {
+        Some((prefix, suffix))
+    }
+}
+
+/// 对请求体应用系统提示词注入
+///
+/// 返回是否做了修改
+pub fn apply_system_prompt_injection(
+    body: &mut Value,
+    provider: &Provider,
+    endpoint: &str,
+) -> bool {
+    let Some((prefix, suffix)) = get_injection(provider) else {
+        return false;
+    };
+    let Some(format) = PromptFormat::from_endpoint(endpoint) else {
+        return false;
+    };
+    if !body.is_object() {
+        return false;
+    }
+
+    match format {
+        PromptFormat::Anthropic => inject_anthropic(body, prefix, suffix),
+        PromptFormat::OpenAIChat => inject_openai_chat(body, prefix, suffix),
+        PromptFormat::OpenAIResponses => inject_string_field(body, "instructions", prefix, suffix),
+        PromptFormat::Gemini => inject_gemini(body, prefix, suffix),
+    }
+
+    log::debug!(
+        "[SystemPrompt] 已为供应商 {} 注入系统提示词 ({format:?})",
+        provider.name
+    );
+    true
+}
+
+/// 拼接前缀、原内容和后缀（忽略空白部分）
+fn wrap(prefix: &str, original: &str, suffix: &str) -> String {
+    [prefix, original, suffix]
+        .iter()
+        .filter(|s| !s.trim().is_empty())
+        .copied()
+        .collect::<Vec<_>>()
+        .join(SEPARATOR)
+}
+
+fn inject_string_field(body: &mut Value, field: &str, prefix: &str, suffix: &str) {
+    let original = body.get(field).and_then(|v| v.as_str()).unwrap_or("");
+    body[field] = json!(wrap(prefix, original, suffix));
+}
+
+fn inject_anthropic(body: &mut Value, prefix: &str, suffix: &str) {
+    match body.get_mut("system") {
+        Some(Value::Array(blocks)) => {
+            if !prefix.trim().is_empty() {
+                blocks.insert(0, json!({"type": "text", "text": prefix}));
+            }
+            if !suffix.trim().is_empty() {
+                blocks.push(json!({"type": "text", "text": suffix}));
+            }
+        }
+        _ => inject_string_field(body, "system", prefix, suffix),
+    }
+}
+
+fn inject_openai_chat(body: &mut Value, prefix: &str, suffix: &str) {
+    let Some(messages) = body.get_mut("messages").and_then(|m| m.as_array_mut()) else {
+        return;
+    };
+
+    let system_message = messages.iter_mut().find(|m| {
+        matches!(
+            m.get("role").and_then(|r| r.as_str()),
+            Some("system") | Some("developer")
+        )
+    });
+
+    match system_message {
+        Some(message) => match message.get_mut("content") {
+            Some(Value::Array(parts)) => {
+                if !prefix.trim().is_empty() {
+                    parts.insert(0, json!({"type": "text", "text": prefix}));
+                }
+                if !suffix.trim().is_empty() {
+                    parts.push(json!({"type": "text", "text": suffix}));
+                }
+            }
+            _ => inject_string_field(message, "content", prefix, suffix),
+        },
+        None => {
+            messages.insert(
+                0,
+                json!({"role": "system", "content": wrap(prefix, "", suffix)}),
+            );
+        }
+    }
+}
+
+fn inject_gemini(body: &mut Value, prefix: &str, suffix: &str) {
+    // 兼容 snake_case 写法
+    let key = if body.get("system_instruction").is_some() {
+        "system_instruction"
+    } else {
+        "systemInstruction"
+    };
+
+    if !body.get(key).is_some_and(|v| v.is_object()) {
+        body[key] = json!({"parts": []});
+    }
+    let instruction = &mut body[key];
+    if !instruction.get("parts").is_some_and(|p| p.is_array()) {
+        instruction["parts"] = json!([]);
+    }
+    if let Some(parts) = instruction["parts"].as_array_mut() {
+        if !prefix.trim().is_empty() {
+            parts.insert(0, json!({"text": prefix}));
+        }
+        if !suffix.trim().is_empty() {
+            parts.push(json!({"text": suffix}));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::ProviderMeta;
+
+    fn provider(prefix: Option<&str>, suffix: Option<&str>) -> Provider {
+        Provider::with_meta(
+            "p",
+            ProviderMeta {
+                system_prompt: Some(SystemPromptInjection {
+                    prefix: prefix.map(String::from),
+                    suffix: suffix.map(String::from),
+                }),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_anthropic_string_system() {
+        let mut body = json!({"system": "original", "messages": []});
+        let applied = apply_system_prompt_injection(
+            &mut body,
+            &provider(Some("PRE"), Some("POST")),
+            "/v1/messages",
+        );
+        assert!(applied);
+        assert_eq!(body["system"], "PRE\n\noriginal\n\nPOST");
+    }
+
+    #[test]
+    fn test_anthropic_block_system_keeps_cache_control() {
+        let mut body = json!({
+            "system": [{"type": "text", "text": "original", "cache_control": {"type": "ephemeral"}}]
+        });
+        apply_system_prompt_injection(&mut body, &provider(Some("PRE"), None), "/v1/messages");
+        let blocks = body["system"].as_array().unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0]["text"], "PRE");
+        assert_eq!(blocks[1]["cache_control"]["type"], "ephemeral");
+    }
+
+    #[test]
+    fn test_anthropic_missing_system() {
+        let mut body = json!({"messages": []});
+        apply_system_prompt_injection(&mut body, &provider(None, Some("POST")), "/v1/messages");
+        assert_eq!(body["system"], "POST");
+    }
+
+    #[test]
+    fn test_openai_chat_inserts_system_message() {
+        let mut body = json!({"messages": [{"role": "user", "content": "hi"}]});
+        apply_system_prompt_injection(
+            &mut body,
+            &provider(Some("PRE"), None),
+            "/v1/chat/completions",
+        );
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["role"], "system");
+        assert_eq!(messages[0]["content"], "PRE");
+    }
+
+    #[test]
+    fn test_openai_chat_existing_system_message() {
+        let mut body = json!({"messages": [
+            {"role": "system", "content": "sys"},
+            {"role": "user", "content": "hi"}
+        ]});
+        apply_system_prompt_injection(
+            &mut body,
+            &provider(Some("PRE"), Some("POST")),
+            "/chat/completions",
+        );
+        assert_eq!(body["messages"][0]["content"], "PRE\n\nsys\n\nPOST");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_responses_instructions() {
+        let mut body = json!({"instructions": "base"});
+        apply_system_prompt_injection(&mut body, &provider(None, Some("POST")), "/v1/responses");
+        assert_eq!(body["instructions"], "base\n\nPOST");
+    }
+
+    #[test]
+    fn test_gemini_system_instruction() {
+        let mut body = json!({"contents": []});
+        apply_system_prompt_injection(
+            &mut body,
+            &provider(Some("PRE"), None),
+            "/v1beta/models/gemini-pro:generateContent?alt=sse",
+        );
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "PRE");
+    }
+
+    #[test]
+    fn test_no_injection_configured() {
+        let mut body = json!({"system": "original"});
+        let p = Provider::with_id("p".to_string(), "P".to_string(), json!({}), None);
+        assert!(!apply_system_prompt_injection(
+            &mut body,
+            &p,
+            "/v1/messages"
+        ));
+        assert!(!apply_system_prompt_injection(
+            &mut body,
+            &provider(Some("  "), None),
+            "/v1/messages"
+        ));
+        assert_eq!(body["system"], "original");
+    }
+}
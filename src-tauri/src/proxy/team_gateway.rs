@@ -0,0 +1,304 @@
+//! 团队网关模式
+//!
+//! 在局域网内共享 cc-switch 时，为每位成员分配独立的访问令牌（成员将其配置为客户端的 API Key）。
+//! 启用后：
+//! - 未携带有效令牌或成员已停用的请求返回 401
+//! - 成员超出每日请求数 / 每月消费上限时返回本地错误
+//! - 成员配置了供应商限制时，故障转移链只保留允许的供应商
+//! - 请求日志记录成员 ID，用于按成员统计用量
+//!
+//! 鉴权通过后会移除携带令牌的 `x-goog-api-key` 请求头与 `?key=` 查询参数，成员令牌不会转发到上游。
+//! 识别结果通过内部请求头 `x-ccswitch-member-id` 传给请求上下文：客户端自带的同名请求头会先被清除，
+//! 且该请求头不会透传到上游。配置存储在 settings 表（`team_gateway_config`）中，每次请求实时读取。
+
+use super::{server::ProxyState, ProxyError};
+use crate::database::{Database, TeamMember};
+use crate::provider::Provider;
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// 内部请求头：已识别的团队成员 ID
+pub const MEMBER_HEADER: &str = "x-ccswitch-member-id";
+
+/// Axum 中间件：识别团队成员并执行配额检查
+pub async fn enforce_team_gateway(
+    State(state): State<ProxyState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    request.headers_mut().remove(MEMBER_HEADER);
+    if !state
+        .db
+        .get_team_gateway_config()
+        .map(|c| c.enabled)
+        .unwrap_or(false)
+    {
+        return next.run(request).await;
+    }
+
+    match authorize(&state.db, request.headers(), request.uri()) {
+        Ok(member) => {
+            log::debug!("[TeamGateway] 请求来自成员 {}", member.name);
+            strip_token(&mut request);
+            if let Ok(value) = HeaderValue::from_str(&member.id) {
+                request.headers_mut().insert(MEMBER_HEADER, value);
+            }
+            next.run(request).await
+        }
+        Err(e) => {
+            log::warn!("[TeamGateway] 拒绝请求: {e}");
+            e.into_response()
+        }
+    }
+}
+
+/// 从请求中提取访问令牌（x-api-key / Authorization Bearer / x-goog-api-key / `?key=`）
+pub(crate) fn extract_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    let header_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    header_value("x-api-key")
+        .or_else(|| {
+            header_value("authorization")
+                .map(|v| v.strip_prefix("Bearer ").map(str::to_string).unwrap_or(v))
+        })
+        .or_else(|| header_value("x-goog-api-key"))
+        .or_else(|| {
+            url::form_urlencoded::parse(uri.query()?.as_bytes())
+                .find(|(key, _)| key == "key")
+                .map(|(_, value)| value.into_owned())
+        })
+}
+
+/// 鉴权通过后移除携带本地令牌的 `x-goog-api-key` 请求头与 `key` 查询参数
+///
+/// `x-api-key` / `Authorization` 属于代理管理的请求头，转发时会被供应商密钥覆盖
+pub(crate) fn strip_token(request: &mut Request) {
+    request.headers_mut().remove("x-goog-api-key");
+    if let Some(uri) = without_query_token(request.uri()) {
+        *request.uri_mut() = uri;
+    }
+}
+
+/// 去掉 `key` 查询参数（不含该参数时返回 None）
+fn without_query_token(uri: &Uri) -> Option<Uri> {
+    let query = uri.query()?;
+    let pairs: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    if !pairs.iter().any(|(key, _)| key == "key") {
+        return None;
+    }
+    let rest = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs.iter().filter(|(key, _)| key != "key"))
+        .finish();
+    let path_and_query = if rest.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{rest}", uri.path())
+    };
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+/// 识别成员并检查配额
+fn authorize(db: &Database, headers: &HeaderMap, uri: &Uri) -> Result<TeamMember, ProxyError> {
+    let token = extract_token(headers, uri)
+        .ok_or_else(|| ProxyError::AuthError("缺少团队成员访问令牌".to_string()))?;
+    let member = db
+        .get_team_member_by_token(&token)
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?
+        .filter(|m| m.enabled)
+        .ok_or_else(|| ProxyError::AuthError("团队成员访问令牌无效或已停用".to_string()))?;
+    check_quota(db, &member)?;
+    Ok(member)
+}
+
+fn check_quota(db: &Database, member: &TeamMember) -> Result<(), ProxyError> {
+    let monthly_limit = member
+        .monthly_limit_usd
+        .as_deref()
+        .and_then(|s| s.trim().parse::<f64>().ok());
+    if member.daily_request_limit.is_none() && monthly_limit.is_none() {
+        return Ok(());
+    }
+
+    let usage = db
+        .get_team_member_usage(&member.id)
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?;
+    let exceeded = |quota: &str| ProxyError::MemberQuotaExceeded {
+        member: member.name.clone(),
+        quota: quota.to_string(),
+    };
+    if member
+        .daily_request_limit
+        .is_some_and(|limit| usage.requests_today >= limit as u64)
+    {
+        return Err(exceeded("每日请求数"));
+    }
+    if monthly_limit.is_some_and(|limit| usage.cost_this_month_usd >= limit) {
+        return Err(exceeded("每月消费"));
+    }
+    Ok(())
+}
+
+/// 从请求头读取已识别的成员 ID
+pub fn member_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(MEMBER_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// 按成员的供应商限制过滤故障转移链
+///
+/// 过滤后为空时返回 `ProxyError::NoAvailableProvider`
+pub fn restrict_providers(
+    db: &Database,
+    member_id: Option<&str>,
+    providers: Vec<Provider>,
+) -> Result<Vec<Provider>, ProxyError> {
+    let Some(member_id) = member_id else {
+        return Ok(providers);
+    };
+    let allowed = match db.get_team_member(member_id) {
+        Ok(Some(member)) if !member.allowed_providers.is_empty() => member.allowed_providers,
+        _ => return Ok(providers),
+    };
+
+    let providers: Vec<Provider> = providers
+        .into_iter()
+        .filter(|p| allowed.contains(&p.id))
+        .collect();
+    if providers.is_empty() {
+        log::warn!("[TeamGateway] 成员 {member_id} 允许的供应商均不可用");
+        return Err(ProxyError::NoAvailableProvider);
+    }
+    Ok(providers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use serde_json::json;
+
+    fn member(token: &str) -> TeamMember {
+        TeamMember {
+            id: "alice".into(),
+            name: "Alice".into(),
+            token: token.into(),
+            enabled: true,
+            allowed_providers: vec!["p2".into()],
+            daily_request_limit: Some(1),
+            monthly_limit_usd: None,
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn extracts_token_from_supported_locations() {
+        let mut headers = HeaderMap::new();
+        let uri: Uri = "/v1beta/models/g:generateContent?alt=sse&key=tok-q"
+            .parse()
+            .unwrap();
+        assert_eq!(extract_token(&headers, &uri).as_deref(), Some("tok-q"));
+
+        headers.insert("authorization", HeaderValue::from_static("Bearer tok-b"));
+        assert_eq!(extract_token(&headers, &uri).as_deref(), Some("tok-b"));
+
+        headers.insert("x-api-key", HeaderValue::from_static("tok-a"));
+        assert_eq!(extract_token(&headers, &uri).as_deref(), Some("tok-a"));
+    }
+
+    #[test]
+    fn strips_token_carriers_before_forwarding() {
+        let mut request = Request::builder()
+            .uri("/v1beta/models/g:streamGenerateContent?alt=sse&key=tok-q")
+            .header("x-goog-api-key", "tok-g")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        strip_token(&mut request);
+        assert!(request.headers().get("x-goog-api-key").is_none());
+        assert_eq!(
+            request.uri().to_string(),
+            "/v1beta/models/g:streamGenerateContent?alt=sse"
+        );
+
+        let uri: Uri = "/v1beta/models/g:generateContent?key=tok-q"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            without_query_token(&uri).unwrap().to_string(),
+            "/v1beta/models/g:generateContent"
+        );
+        let uri: Uri = "/v1/messages?beta=true".parse().unwrap();
+        assert!(without_query_token(&uri).is_none());
+    }
+
+    #[test]
+    fn authorizes_members_and_enforces_quota() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.save_team_member(&member("tok-a"))?;
+        let uri: Uri = "/v1/messages".parse().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static("wrong"));
+        assert!(matches!(
+            authorize(&db, &headers, &uri),
+            Err(ProxyError::AuthError(_))
+        ));
+
+        headers.insert("x-api-key", HeaderValue::from_static("tok-a"));
+        assert_eq!(authorize(&db, &headers, &uri).unwrap().id, "alice");
+
+        {
+            let conn = crate::database::lock_conn!(db.conn);
+            conn.execute(
+                "INSERT INTO proxy_request_logs (request_id, provider_id, app_type, model,
+                 latency_ms, status_code, created_at, member_id)
+                 VALUES ('r1', 'p2', 'claude', 'm', 10, 200, ?1, 'alice')",
+                [chrono::Utc::now().timestamp()],
+            )
+            .unwrap();
+        }
+        assert!(matches!(
+            authorize(&db, &headers, &uri),
+            Err(ProxyError::MemberQuotaExceeded { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn restricts_failover_chain_to_allowed_providers() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        db.save_team_member(&member("tok-a"))?;
+        let providers = vec![
+            Provider::with_id("p1".into(), "P1".into(), json!({}), None),
+            Provider::with_id("p2".into(), "P2".into(), json!({}), None),
+        ];
+
+        let restricted = restrict_providers(&db, Some("alice"), providers.clone()).unwrap();
+        assert_eq!(restricted.len(), 1);
+        assert_eq!(restricted[0].id, "p2");
+        assert_eq!(
+            restrict_providers(&db, None, providers.clone())
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(restrict_providers(&db, Some("alice"), providers[..1].to_vec()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+//! Thinking 内容块过滤模块
+//!
+//! 部分中转站返回的 extended thinking 增量格式不规范（缺少 `content_block_start`、
+//! 签名错乱等），会导致 Claude Code 解析失败。根据 Provider 配置的 `thinkingBlocks`，
+//! 对 Anthropic SSE 流中的 thinking 块进行处理：
+//!
+//! - `passthrough`：原样透传（默认）
+//! - `strip`：移除 thinking / redacted_thinking 块，后续内容块的 `index` 重新编号
+//! - `rewrap`：改写为 text 块，内容以 `<thinking>` 标签包裹，丢弃签名
+
+use super::stream_buffer::{SseEventBuffer, MAX_PENDING_EVENT_BYTES};
+use crate::provider::{Provider, ThinkingBlockMode};
+use bytes::Bytes;
+use futures::stream::{Stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const REWRAP_OPEN: &str = "<thinking>\n";
+const REWRAP_CLOSE: &str = "\n</thinking>\n\n";
+
+/// 获取 Provider 配置的 thinking 块处理方式（透传时返回 None）
+pub fn get_thinking_mode(provider: &Provider) -> Option<ThinkingBlockMode> {
+    provider
+        .meta
+        .as_ref()?
+        .thinking_blocks
+        .filter(|mode| *mode != ThinkingBlockMode::Passthrough)
+}
+
+/// 上游内容块在下游的映射
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockRoute {
+    /// 普通块，仅重新编号
+    Keep(u64),
+    /// 被移除的块
+    Drop,
+    /// 改写为 text 块的 thinking 块
+    Rewrap(u64),
+}
+
+/// 按 SSE 事件处理 thinking 块的状态机
+pub struct ThinkingStreamFilter {
+    mode: ThinkingBlockMode,
+    routes: HashMap<u64, BlockRoute>,
+    /// 已移除的块数量，用于计算后续块的下游 index
+    dropped: u64,
+}
+
+impl ThinkingStreamFilter {
+    pub fn new(mode: ThinkingBlockMode) -> Self {
+        Self {
+            mode,
+            routes: HashMap::new(),
+            dropped: 0,
+        }
+    }
+
+    fn is_thinking_type(block_type: &str) -> bool {
+        matches!(block_type, "thinking" | "redacted_thinking")
+    }
+
+    /// 为新块分配路由
+    fn open_block(&mut self, index: u64, block_type: &str) -> BlockRoute {
+        let new_index = index.saturating_sub(self.dropped);
+        let route = match (self.mode, block_type) {
+            (ThinkingBlockMode::Rewrap, "thinking") => BlockRoute::Rewrap(new_index),
+            (ThinkingBlockMode::Strip | ThinkingBlockMode::Rewrap, t)
+                if Self::is_thinking_type(t) =>
+            {
+                self.dropped += 1;
+                BlockRoute::Drop
+            }
+            _ => BlockRoute::Keep(new_index),
+        };
+        self.routes.insert(index, route);
+        route
+    }
+
+    /// 未见过 start 的块（上游格式不规范）：根据首个 delta 类型推断
+    fn route_for_delta(&mut self, index: u64, delta_type: &str) -> (BlockRoute, bool) {
+        if let Some(route) = self.routes.get(&index) {
+            return (*route, false);
+        }
+        let block_type = match delta_type {
+            "thinking_delta" | "signature_delta" => "thinking",
+            _ => "text",
+        };
+        (self.open_block(index, block_type), true)
+    }
+
+    /// 处理一个完整的 SSE 事件（不含结尾空行），返回需要输出的事件列表
+    pub fn process_event(&mut self, event_text: &str) -> Vec<String> {
+        if self.mode == ThinkingBlockMode::Passthrough {
+            return vec![event_text.to_string()];
+        }
+
+        let Some(data) = event_text
+            .lines()
+            .find_map(|line| line.strip_prefix("data:").map(str::trim))
+        else {
+            return vec![event_text.to_string()];
+        };
+        let Ok(mut value) = serde_json::from_str::<Value>(data) else {
+            return vec![event_text.to_string()];
+        };
+        let event_type = value
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let Some(index) = value.get("index").and_then(|i| i.as_u64()) else {
+            return vec![event_text.to_string()];
+        };
+
+        match event_type.as_str() {
+            "content_block_start" => {
+                let block_type = value
+                    .pointer("/content_block/type")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                match self.open_block(index, &block_type) {
+                    BlockRoute::Drop => vec![],
+                    BlockRoute::Keep(new_index) => {
+                        value["index"] = json!(new_index);
+                        vec![format_event(&value)]
+                    }
+                    BlockRoute::Rewrap(new_index) => rewrap_start(new_index),
+                }
+            }
+            "content_block_delta" => {
+                let delta_type = value
+                    .pointer("/delta/type")
+                    .and_then(|t| t.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                let (route, synthesized) = self.route_for_delta(index, &delta_type);
+                match route {
+                    BlockRoute::Drop => vec![],
+                    BlockRoute::Keep(new_index) => {
+                        value["index"] = json!(new_index);
+                        let mut out = Vec::new();
+                        if synthesized {
+                            out.push(format_event(&json!({
+                                "type": "content_block_start",
+                                "index": new_index,
+                                "content_block": {"type": "text", "text": ""}
+                            })));
+                        }
+                        out.push(format_event(&value));
+                        out
+                    }
+                    BlockRoute::Rewrap(new_index) => {
+                        let mut out = if synthesized {
+                            rewrap_start(new_index)
+                        } else {
+                            Vec::new()
+                        };
+                        match delta_type.as_str() {
+                            "thinking_delta" => {
+                                let text = value
+                                    .pointer("/delta/thinking")
+                                    .and_then(|t| t.as_str())
+                                    .unwrap_or_default();
+                                out.push(text_delta(new_index, text));
+                            }
+                            "text_delta" => {
+                                value["index"] = json!(new_index);
+                                out.push(format_event(&value));
+                            }
+                            // 签名等其他增量在改写后无意义，直接丢弃
+                            _ => {}
+                        }
+                        out
+                    }
+                }
+            }
+            "content_block_stop" => match self.routes.remove(&index) {
+                Some(BlockRoute::Drop) => vec![],
+                Some(BlockRoute::Rewrap(new_index)) => vec![
+                    text_delta(new_index, REWRAP_CLOSE),
+                    format_event(&json!({"type": "content_block_stop", "index": new_index})),
+                ],
+                Some(BlockRoute::Keep(new_index)) => {
+                    value["index"] = json!(new_index);
+                    vec![format_event(&value)]
+                }
+                None => {
+                    value["index"] = json!(index.saturating_sub(self.dropped));
+                    vec![format_event(&value)]
+                }
+            },
+            _ => vec![event_text.to_string()],
+        }
+    }
+}
+
+fn format_event(value: &Value) -> String {
+    let event_type = value
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("message");
+    format!("event: {event_type}\ndata: {value}")
+}
+
+fn text_delta(index: u64, text: &str) -> String {
+    format_event(&json!({
+        "type": "content_block_delta",
+        "index": index,
+        "delta": {"type": "text_delta", "text": text}
+    }))
+}
+
+fn rewrap_start(index: u64) -> Vec<String> {
+    vec![
+        format_event(&json!({
+            "type": "content_block_start",
+            "index": index,
+            "content_block": {"type": "text", "text": ""}
+        })),
+        text_delta(index, REWRAP_OPEN),
+    ]
+}
+
+/// 为 Anthropic SSE 字节流套上 thinking 块过滤
+///
+/// 单个事件超过 [`MAX_PENDING_EVENT_BYTES`] 仍未结束时停止过滤，剩余数据原样转发。
+pub fn filter_thinking_stream<S>(
+    stream: S,
+    mode: ThinkingBlockMode,
+) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send
+where
+    S: Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static,
+{
+    async_stream::stream! {
+        let mut filter = ThinkingStreamFilter::new(mode);
+        let mut events = SseEventBuffer::default();
+        let mut passthrough = false;
+        tokio::pin!(stream);
+
+        while let Some(chunk) = stream.next().await {
+            match chunk {
+                Ok(bytes) if passthrough => yield Ok(bytes),
+                Ok(bytes) => {
+                    let mut out = String::new();
+                    events.split(&bytes, |event_text| {
+                        for event in filter.process_event(event_text) {
+                            out.push_str(&event);
+                            out.push_str("\n\n");
+                        }
+                    });
+                    if !out.is_empty() {
+                        yield Ok(Bytes::from(out));
+                    }
+                    if events.pending_len() > MAX_PENDING_EVENT_BYTES {
+                        log::warn!(
+                            "[ThinkingFilter] 未完成的 SSE 事件超过 {MAX_PENDING_EVENT_BYTES} 字节，停止过滤"
+                        );
+                        passthrough = true;
+                        yield Ok(Bytes::from(events.take_pending()));
+                    }
+                }
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            }
+        }
+
+        let rest = events.take_pending();
+        if !rest.is_empty() {
+            yield Ok(Bytes::from(rest));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(event: &str) -> Value {
+        let line = event
+            .lines()
+            .find_map(|l| l.strip_prefix("data: "))
+            .unwrap();
+        serde_json::from_str(line).unwrap()
+    }
+
+    fn run(mode: ThinkingBlockMode, events: &[Value]) -> Vec<Value> {
+        let mut filter = ThinkingStreamFilter::new(mode);
+        events
+            .iter()
+            .flat_map(|e| filter.process_event(&format_event(e)))
+            .map(|e| data(&e))
+            .collect()
+    }
+
+    fn sample() -> Vec<Value> {
+        vec![
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "hmm"}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "hi"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+        ]
+    }
+
+    #[test]
+    fn test_strip_renumbers_following_blocks() {
+        let out = run(ThinkingBlockMode::Strip, &sample());
+        assert_eq!(out.len(), 3);
+        assert!(out.iter().all(|e| e["index"] == 0));
+        assert_eq!(out[0]["content_block"]["type"], "text");
+        assert_eq!(out[1]["delta"]["text"], "hi");
+    }
+
+    #[test]
+    fn test_rewrap_converts_to_text() {
+        let out = run(ThinkingBlockMode::Rewrap, &sample());
+        assert_eq!(out[0]["content_block"]["type"], "text");
+        assert_eq!(out[1]["delta"]["text"], REWRAP_OPEN);
+        assert_eq!(out[2]["delta"]["text"], "hmm");
+        // signature_delta 被丢弃
+        assert_eq!(out[3]["delta"]["text"], REWRAP_CLOSE);
+        assert_eq!(out[4]["type"], "content_block_stop");
+        assert_eq!(out[5]["index"], 1);
+    }
+
+    #[test]
+    fn test_rewrap_synthesizes_missing_start() {
+        let events = vec![
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "x"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+        ];
+        let out = run(ThinkingBlockMode::Rewrap, &events);
+        assert_eq!(out[0]["type"], "content_block_start");
+        assert_eq!(out[2]["delta"]["text"], "x");
+        assert_eq!(out.last().unwrap()["type"], "content_block_stop");
+    }
+
+    #[test]
+    fn test_non_block_events_untouched() {
+        let mut filter = ThinkingStreamFilter::new(ThinkingBlockMode::Strip);
+        let event = "event: message_start\ndata: {\"type\":\"message_start\"}";
+        assert_eq!(filter.process_event(event), vec![event.to_string()]);
+        assert_eq!(filter.process_event(": ping"), vec![": ping".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_split_across_chunks() {
+        let text = sample()
+            .iter()
+            .map(|e| format!("{}\n\n", format_event(e)))
+            .collect::<String>();
+        let (a, b) = text.split_at(text.len() / 2);
+        let chunks = vec![
+            Ok(Bytes::from(a.to_string())),
+            Ok(Bytes::from(b.to_string())),
+        ];
+        let output: Vec<_> =
+            filter_thinking_stream(futures::stream::iter(chunks), ThinkingBlockMode::Strip)
+                .collect()
+                .await;
+        let joined: String = output
+            .into_iter()
+            .map(|c| String::from_utf8(c.unwrap().to_vec()).unwrap())
+            .collect();
+        assert!(!joined.contains("thinking"));
+        assert!(joined.contains("\"text\":\"hi\""));
+    }
+
+    #[tokio::test]
+    async fn test_stream_handles_crlf_events() {
+        let text = sample()
+            .iter()
+            .map(|e| format!("{}\r\n\r\n", format_event(e).replace('\n', "\r\n")))
+            .collect::<String>();
+        let chunks = vec![Ok::<_, std::io::Error>(Bytes::from(text))];
+        let output: Vec<_> =
+            filter_thinking_stream(futures::stream::iter(chunks), ThinkingBlockMode::Strip)
+                .collect()
+                .await;
+        let joined: String = output
+            .into_iter()
+            .map(|c| String::from_utf8(c.unwrap().to_vec()).unwrap())
+            .collect();
+        assert!(!joined.contains("thinking"));
+        assert!(joined.contains("\"text\":\"hi\""));
+    }
+}
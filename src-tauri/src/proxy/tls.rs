@@ -0,0 +1,318 @@
+//! 本地代理 HTTPS 监听
+//!
+//! 部分客户端拒绝通过明文 HTTP 发送 API Key（即使目标是 localhost / 局域网），
+//! 启用 HTTPS 后代理改为 TLS 监听：
+//! - 配置了证书 / 私钥路径（PEM）时使用自定义证书
+//! - 否则自动生成自签名证书（ECDSA P-256，有效期 10 年），保存在 `<app_config_dir>/tls/`，
+//!   覆盖 `localhost`、`127.0.0.1`、`::1` 以及监听地址
+//!
+//! 自签名证书需要客户端信任，例如 Node.js 客户端可设置 `NODE_EXTRA_CA_CERTS` 指向证书文件。
+//! 配置存储在 settings 表（`proxy_tls_config`）中，修改后需重启代理生效。
+
+use super::types::ProxyTlsConfig;
+use super::ProxyError;
+use axum::{extract::ConnectInfo, Extension, Router};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto::Builder,
+    service::TowerToHyperService,
+};
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio_rustls::TlsAcceptor;
+
+/// 自签名证书有效期（天）
+const SELF_SIGNED_VALID_DAYS: i64 = 3650;
+
+/// 证书通用名
+const COMMON_NAME: &str = "cc-switch local proxy";
+
+/// 自动生成的证书文件路径 (证书, 私钥)
+pub fn self_signed_paths() -> (PathBuf, PathBuf) {
+    let dir = crate::config::get_app_config_dir().join("tls");
+    (dir.join("proxy-cert.pem"), dir.join("proxy-key.pem"))
+}
+
+/// 当前生效的证书与私钥路径（未配置自定义证书时为自动生成的证书）
+pub fn effective_paths(config: &ProxyTlsConfig) -> (PathBuf, PathBuf) {
+    match (
+        custom_path(&config.cert_path),
+        custom_path(&config.key_path),
+    ) {
+        (Some(cert), Some(key)) => (cert, key),
+        _ => self_signed_paths(),
+    }
+}
+
+fn custom_path(path: &Option<String>) -> Option<PathBuf> {
+    path.as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+}
+
+/// 构建 TLS 接收器（必要时生成自签名证书）
+pub fn build_acceptor(
+    config: &ProxyTlsConfig,
+    listen_address: &str,
+) -> Result<TlsAcceptor, ProxyError> {
+    let (cert_path, key_path) = effective_paths(config);
+    let is_custom =
+        custom_path(&config.cert_path).is_some() && custom_path(&config.key_path).is_some();
+    if !is_custom && (!cert_path.exists() || !key_path.exists()) {
+        write_self_signed(&cert_path, &key_path, listen_address)?;
+    }
+
+    let tls_error = |e: String| ProxyError::BindFailed(format!("加载 HTTPS 证书失败: {e}"));
+    let certs = CertificateDer::pem_file_iter(&cert_path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| tls_error(format!("{}: {e}", cert_path.display())))?;
+    let key = PrivateKeyDer::from_pem_file(&key_path)
+        .map_err(|e| tls_error(format!("{}: {e}", key_path.display())))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut server_config = rustls::ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| tls_error(e.to_string()))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| tls_error(e.to_string()))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// 生成自签名证书并写入 PEM 文件
+fn write_self_signed(
+    cert_path: &Path,
+    key_path: &Path,
+    listen_address: &str,
+) -> Result<(), ProxyError> {
+    let (cert_pem, key_pem) = generate_self_signed(listen_address.parse().ok())
+        .map_err(|e| ProxyError::BindFailed(format!("生成自签名证书失败: {e}")))?;
+    let to_error = |e: crate::error::AppError| ProxyError::BindFailed(e.to_string());
+    crate::config::write_text_file(key_path, &key_pem).map_err(to_error)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(key_path, std::fs::Permissions::from_mode(0o600));
+    }
+    crate::config::write_text_file(cert_path, &cert_pem).map_err(to_error)?;
+    log::info!("[TLS] 已生成自签名证书: {}", cert_path.display());
+    Ok(())
+}
+
+/// 以 HTTPS 提供服务，直到收到关闭信号
+pub async fn serve(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    app: Router,
+    mut shutdown_rx: oneshot::Receiver<()>,
+) {
+    loop {
+        let (stream, remote) = tokio::select! {
+            _ = &mut shutdown_rx => break,
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    log::warn!("[TLS] 接受连接失败: {e}");
+                    continue;
+                }
+            },
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::debug!("[TLS] 与 {remote} 握手失败: {e}");
+                    return;
+                }
+            };
+            // 与明文监听一致，向中间件提供客户端地址
+            let service = app.layer(Extension(ConnectInfo::<SocketAddr>(remote)));
+            if let Err(e) = Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(
+                    TokioIo::new(stream),
+                    TowerToHyperService::new(service),
+                )
+                .await
+            {
+                log::debug!("[TLS] 连接 {remote} 异常结束: {e}");
+            }
+        });
+    }
+}
+
+// ==================== 自签名证书（DER 编码） ====================
+
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend(bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn sequence(parts: &[&[u8]]) -> Vec<u8> {
+    der(0x30, &parts.concat())
+}
+
+/// ecdsa-with-SHA256 (1.2.840.10045.4.3.2)
+const OID_ECDSA_SHA256: &[u8] = &[0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02];
+/// id-ecPublicKey (1.2.840.10045.2.1)
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+/// prime256v1 (1.2.840.10045.3.1.7)
+const OID_P256: &[u8] = &[0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+/// commonName (2.5.4.3)
+const OID_COMMON_NAME: &[u8] = &[0x06, 0x03, 0x55, 0x04, 0x03];
+/// subjectAltName (2.5.29.17)
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x06, 0x03, 0x55, 0x1D, 0x11];
+
+fn utc_time(time: chrono::DateTime<chrono::Utc>) -> Vec<u8> {
+    der(0x17, time.format("%y%m%d%H%M%SZ").to_string().as_bytes())
+}
+
+/// 生成自签名证书，返回 (证书 PEM, PKCS#8 私钥 PEM)
+pub fn generate_self_signed(extra_ip: Option<IpAddr>) -> Result<(String, String), String> {
+    let rng = SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng)
+        .map_err(|e| e.to_string())?;
+    let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+        .map_err(|e| e.to_string())?;
+
+    let algorithm = sequence(&[OID_ECDSA_SHA256]);
+    let name = sequence(&[&der(
+        0x31,
+        &sequence(&[OID_COMMON_NAME, &der(0x0C, COMMON_NAME.as_bytes())]),
+    )]);
+
+    let now = chrono::Utc::now();
+    let validity = sequence(&[
+        &utc_time(now - chrono::Duration::days(1)),
+        &utc_time(now + chrono::Duration::days(SELF_SIGNED_VALID_DAYS)),
+    ]);
+
+    // 正整数序列号：清除最高位
+    let mut serial = uuid::Uuid::new_v4().into_bytes();
+    serial[0] &= 0x7F;
+
+    let mut ips: Vec<IpAddr> = vec![[127, 0, 0, 1].into(), std::net::Ipv6Addr::LOCALHOST.into()];
+    if let Some(ip) = extra_ip.filter(|ip| !ip.is_unspecified() && !ips.contains(ip)) {
+        ips.push(ip);
+    }
+    let mut alt_names = der(0x82, b"localhost");
+    for ip in ips {
+        let octets = match ip {
+            IpAddr::V4(v4) => v4.octets().to_vec(),
+            IpAddr::V6(v6) => v6.octets().to_vec(),
+        };
+        alt_names.extend(der(0x87, &octets));
+    }
+    let san = sequence(&[OID_SUBJECT_ALT_NAME, &der(0x04, &der(0x30, &alt_names))]);
+    let extensions = der(0xA3, &sequence(&[&san]));
+
+    let public_key = {
+        let mut bits = vec![0u8];
+        bits.extend_from_slice(key_pair.public_key().as_ref());
+        sequence(&[&sequence(&[OID_EC_PUBLIC_KEY, OID_P256]), &der(0x03, &bits)])
+    };
+
+    let tbs = sequence(&[
+        &der(0xA0, &der(0x02, &[2])),
+        &der(0x02, &serial),
+        &algorithm,
+        &name,
+        &validity,
+        &name,
+        &public_key,
+        &extensions,
+    ]);
+    let signature = key_pair.sign(&rng, &tbs).map_err(|e| e.to_string())?;
+    let mut signature_bits = vec![0u8];
+    signature_bits.extend_from_slice(signature.as_ref());
+    let cert = sequence(&[&tbs, &algorithm, &der(0x03, &signature_bits)]);
+
+    Ok((
+        pem("CERTIFICATE", &cert),
+        pem("PRIVATE KEY", pkcs8.as_ref()),
+    ))
+}
+
+fn pem(label: &str, der: &[u8]) -> String {
+    use base64::Engine;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut out = format!("-----BEGIN {label}-----\n");
+    for line in encoded.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {label}-----\n"));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::ServerName;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn self_signed_certificate_completes_handshake() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let config = ProxyTlsConfig {
+            enabled: true,
+            cert_path: Some(tmp.path().join("cert.pem").to_string_lossy().to_string()),
+            key_path: Some(tmp.path().join("key.pem").to_string_lossy().to_string()),
+        };
+        let (cert_path, key_path) = effective_paths(&config);
+        write_self_signed(&cert_path, &key_path, "192.168.1.10").expect("generate");
+        let acceptor = build_acceptor(&config, "192.168.1.10").expect("acceptor");
+
+        // 信任生成的证书后，客户端应能以 localhost 完成握手
+        let mut roots = rustls::RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter(&cert_path).unwrap() {
+            roots.add(cert.unwrap()).expect("valid certificate");
+        }
+        let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+        let connector = tokio_rustls::TlsConnector::from(Arc::new(client_config));
+
+        let (client_io, server_io) = tokio::io::duplex(16 * 1024);
+        let server = tokio::spawn(async move {
+            let mut stream = acceptor.accept(server_io).await.expect("server handshake");
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+        let mut client = connector
+            .connect(ServerName::try_from("localhost").unwrap(), client_io)
+            .await
+            .expect("client handshake");
+        client.write_all(b"ping").await.unwrap();
+        client.flush().await.unwrap();
+        assert_eq!(&server.await.unwrap(), b"ping");
+    }
+}
@@ -0,0 +1,134 @@
+//! Token 估算模块
+//!
+//! 在不依赖上游分词器的情况下粗略估算请求的 token 数，用于上下文窗口检查等本地判断。
+//!
+//! ## 估算规则
+//! - ASCII 字符约 4 个计 1 token
+//! - CJK 字符每个计 1 token，其他非 ASCII 字符约 2 个计 1 token
+//! - 图片 / 文档等二进制内容按固定值计算，不统计 base64 数据
+//! - 每个 JSON 对象额外计入少量结构开销
+
+use serde_json::Value;
+
+/// 每个 JSON 对象的结构开销
+const OBJECT_OVERHEAD: u64 = 3;
+/// 单张图片的估算 token 数
+const IMAGE_TOKENS: u64 = 1600;
+/// 单个文档（PDF 等）的估算 token 数
+const DOCUMENT_TOKENS: u64 = 3000;
+
+/// 不参与估算的字段（元信息，不进入模型上下文）
+const SKIPPED_KEYS: &[&str] = &[
+    "model",
+    "stream",
+    "max_tokens",
+    "max_completion_tokens",
+    "max_output_tokens",
+    "temperature",
+    "top_p",
+    "top_k",
+    "metadata",
+    "cache_control",
+    "signature",
+];
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF00..=0xFFEF | 0x20000..=0x2FA1F)
+}
+
+/// 估算一段文本的 token 数
+pub fn estimate_text_tokens(text: &str) -> u64 {
+    let (mut ascii, mut cjk, mut other) = (0u64, 0u64, 0u64);
+    for c in text.chars() {
+        if c.is_ascii() {
+            ascii += 1;
+        } else if is_cjk(c) {
+            cjk += 1;
+        } else {
+            other += 1;
+        }
+    }
+    ascii.div_ceil(4) + cjk + other.div_ceil(2)
+}
+
+/// 估算任意 JSON 值（请求体或其中一部分）的 token 数
+pub fn estimate_value_tokens(value: &Value) -> u64 {
+    match value {
+        Value::String(s) => estimate_text_tokens(s),
+        Value::Array(arr) => arr.iter().map(estimate_value_tokens).sum(),
+        Value::Object(map) => {
+            let block_type = map.get("type").and_then(|t| t.as_str());
+            match block_type {
+                Some("image" | "image_url" | "input_image") => return IMAGE_TOKENS,
+                Some("document" | "file" | "input_file") => return DOCUMENT_TOKENS,
+                _ => {}
+            }
+            // Gemini inline 二进制数据
+            if map.contains_key("inlineData") || map.contains_key("inline_data") {
+                return IMAGE_TOKENS;
+            }
+
+            OBJECT_OVERHEAD
+                + map
+                    .iter()
+                    .filter(|(k, _)| !SKIPPED_KEYS.contains(&k.as_str()))
+                    .map(|(_, v)| estimate_value_tokens(v))
+                    .sum::<u64>()
+        }
+        Value::Number(_) | Value::Bool(_) => 1,
+        Value::Null => 0,
+    }
+}
+
+/// 读取请求中声明的最大输出 token 数
+pub fn requested_output_tokens(body: &Value) -> u64 {
+    ["max_tokens", "max_completion_tokens", "max_output_tokens"]
+        .iter()
+        .find_map(|k| body.get(*k).and_then(|v| v.as_u64()))
+        .or_else(|| {
+            body.pointer("/generationConfig/maxOutputTokens")
+                .and_then(|v| v.as_u64())
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_text_estimate() {
+        assert_eq!(estimate_text_tokens(""), 0);
+        assert_eq!(estimate_text_tokens("abcd"), 1);
+        assert_eq!(estimate_text_tokens("hello world"), 3);
+        assert_eq!(estimate_text_tokens("你好世界"), 4);
+    }
+
+    #[test]
+    fn test_image_counts_fixed_tokens() {
+        let image = json!({
+            "type": "image",
+            "source": {"type": "base64", "media_type": "image/png", "data": "A".repeat(100_000)}
+        });
+        assert_eq!(estimate_value_tokens(&image), IMAGE_TOKENS);
+    }
+
+    #[test]
+    fn test_skips_metadata_fields() {
+        let a = json!({"model": "claude-sonnet-4-20250514", "messages": [{"role": "user", "content": "hi"}]});
+        let b = json!({"messages": [{"role": "user", "content": "hi"}]});
+        assert_eq!(estimate_value_tokens(&a), estimate_value_tokens(&b));
+    }
+
+    #[test]
+    fn test_requested_output_tokens() {
+        assert_eq!(requested_output_tokens(&json!({"max_tokens": 4096})), 4096);
+        assert_eq!(
+            requested_output_tokens(&json!({"generationConfig": {"maxOutputTokens": 100}})),
+            100
+        );
+        assert_eq!(requested_output_tokens(&json!({})), 0);
+    }
+}
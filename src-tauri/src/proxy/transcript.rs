@@ -0,0 +1,256 @@
+//! 对话记录（transcript）采集
+//!
+//! 启用后为每个请求保存「本轮用户输入」与「模型回复」的纯文本，写入 FTS5 全文索引表，
+//! 供历史搜索使用。只保存最后一条用户消息而非完整上下文，避免多轮对话重复入库。
+//!
+//! 支持 Anthropic Messages、OpenAI Chat Completions、OpenAI Responses（Codex）与 Gemini 格式，
+//! 流式响应从 SSE 事件中拼接文本增量。配置存储在 settings 表（`transcript_config`）中。
+
+use super::handler_context::RequestContext;
+use crate::database::{Database, TranscriptRecord};
+use serde_json::Value;
+
+/// 提取本轮用户输入文本
+pub fn extract_prompt(body: &Value) -> String {
+    // Anthropic / OpenAI Chat
+    if let Some(messages) = body.get("messages").and_then(|m| m.as_array()) {
+        if let Some(message) = messages
+            .iter()
+            .rev()
+            .find(|m| m.get("role").and_then(|r| r.as_str()) == Some("user"))
+        {
+            return content_text(message.get("content"));
+        }
+    }
+
+    // OpenAI Responses
+    match body.get("input") {
+        Some(Value::String(text)) => return text.clone(),
+        Some(Value::Array(items)) => {
+            if let Some(item) = items
+                .iter()
+                .rev()
+                .find(|i| i.get("role").and_then(|r| r.as_str()) == Some("user"))
+            {
+                return content_text(item.get("content"));
+            }
+        }
+        _ => {}
+    }
+
+    // Gemini
+    if let Some(contents) = body.get("contents").and_then(|c| c.as_array()) {
+        if let Some(content) = contents
+            .iter()
+            .rev()
+            .find(|c| c.get("role").and_then(|r| r.as_str()).unwrap_or("user") == "user")
+        {
+            return parts_text(content.get("parts"));
+        }
+    }
+
+    String::new()
+}
+
+/// 提取非流式响应中的回复文本
+pub fn extract_response_text(body: &Value) -> String {
+    // Anthropic
+    if body.get("content").is_some_and(|c| c.is_array()) {
+        return content_text(body.get("content"));
+    }
+
+    // OpenAI Chat
+    if let Some(choices) = body.get("choices").and_then(|c| c.as_array()) {
+        return choices
+            .iter()
+            .filter_map(|c| c.pointer("/message/content").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    // OpenAI Responses
+    if let Some(output) = body.get("output").and_then(|o| o.as_array()) {
+        return output
+            .iter()
+            .filter(|item| item.get("type").and_then(|t| t.as_str()) == Some("message"))
+            .map(|item| content_text(item.get("content")))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    // Gemini
+    if let Some(candidates) = body.get("candidates").and_then(|c| c.as_array()) {
+        return candidates
+            .iter()
+            .map(|c| parts_text(c.pointer("/content/parts")))
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    String::new()
+}
+
+/// 从流式 SSE 事件中拼接回复文本
+pub fn extract_stream_text(events: &[Value]) -> String {
+    let mut text = String::new();
+    for event in events {
+        // Anthropic：content_block_delta / text_delta
+        if let Some(delta) = event
+            .get("delta")
+            .filter(|d| d.get("type").and_then(|t| t.as_str()) == Some("text_delta"))
+            .and_then(|d| d.get("text"))
+            .and_then(|t| t.as_str())
+        {
+            text.push_str(delta);
+            continue;
+        }
+        // OpenAI Responses：response.output_text.delta
+        if event.get("type").and_then(|t| t.as_str()) == Some("response.output_text.delta") {
+            if let Some(delta) = event.get("delta").and_then(|d| d.as_str()) {
+                text.push_str(delta);
+            }
+            continue;
+        }
+        // OpenAI Chat
+        if let Some(choices) = event.get("choices").and_then(|c| c.as_array()) {
+            for choice in choices {
+                if let Some(delta) = choice.pointer("/delta/content").and_then(|t| t.as_str()) {
+                    text.push_str(delta);
+                }
+            }
+            continue;
+        }
+        // Gemini：每个事件是一个完整的 GenerateContentResponse 片段
+        if let Some(candidates) = event.get("candidates").and_then(|c| c.as_array()) {
+            for candidate in candidates {
+                text.push_str(&parts_text(candidate.pointer("/content/parts")));
+            }
+        }
+    }
+    text
+}
+
+/// 消息 content：字符串或内容块数组（text / input_text / output_text）
+fn content_text(content: Option<&Value>) -> String {
+    match content {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter(|b| {
+                matches!(
+                    b.get("type").and_then(|t| t.as_str()),
+                    Some("text" | "input_text" | "output_text")
+                )
+            })
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Gemini parts 中的文本
+fn parts_text(parts: Option<&Value>) -> String {
+    parts
+        .and_then(|p| p.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("")
+        })
+        .unwrap_or_default()
+}
+
+/// 是否启用对话记录
+pub fn is_enabled(db: &Database) -> bool {
+    db.get_transcript_config()
+        .map(|c| c.enabled)
+        .unwrap_or(false)
+}
+
+/// 保存一轮对话记录
+///
+/// 仅在请求上下文记录了用户输入（即已启用）时写入；输入与回复均为空时跳过
+pub fn record(db: &Database, ctx: &RequestContext, response_text: String) {
+    let Some(prompt) = ctx.transcript_prompt.clone() else {
+        return;
+    };
+    record_with(
+        db,
+        TranscriptRecord {
+            request_id: ctx.request_id.clone(),
+            provider_id: ctx.provider.id.clone(),
+            app_type: ctx.app_type_str.to_string(),
+            model: ctx.request_model.clone(),
+            created_at: chrono::Utc::now().timestamp(),
+            request_text: prompt,
+            response_text,
+        },
+    );
+}
+
+/// 保存已构造好的对话记录（供流式响应结束回调使用）
+pub fn record_with(db: &Database, record: TranscriptRecord) {
+    if record.request_text.is_empty() && record.response_text.is_empty() {
+        return;
+    }
+    if let Err(e) = db.insert_transcript(&record) {
+        log::warn!("[{}] 保存对话记录失败: {e}", record.app_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn extracts_last_user_prompt() {
+        let body = json!({
+            "messages": [
+                {"role": "user", "content": "first"},
+                {"role": "assistant", "content": "reply"},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "content": "ignored"},
+                    {"type": "text", "text": "second question"}
+                ]}
+            ]
+        });
+        assert_eq!(extract_prompt(&body), "second question");
+
+        let codex = json!({"input": [
+            {"role": "user", "content": [{"type": "input_text", "text": "codex prompt"}]}
+        ]});
+        assert_eq!(extract_prompt(&codex), "codex prompt");
+
+        let gemini = json!({"contents": [{"role": "user", "parts": [{"text": "gemini prompt"}]}]});
+        assert_eq!(extract_prompt(&gemini), "gemini prompt");
+    }
+
+    #[test]
+    fn extracts_response_text() {
+        let claude = json!({"content": [{"type": "text", "text": "hello"}]});
+        assert_eq!(extract_response_text(&claude), "hello");
+
+        let openai = json!({"choices": [{"message": {"role": "assistant", "content": "hi"}}]});
+        assert_eq!(extract_response_text(&openai), "hi");
+    }
+
+    #[test]
+    fn concatenates_stream_deltas() {
+        let events = vec![
+            json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "Hel"}}),
+            json!({"type": "content_block_delta", "delta": {"type": "input_json_delta", "partial_json": "{}"}}),
+            json!({"type": "content_block_delta", "delta": {"type": "text_delta", "text": "lo"}}),
+        ];
+        assert_eq!(extract_stream_text(&events), "Hello");
+
+        let chat = vec![
+            json!({"choices": [{"delta": {"content": "a"}}]}),
+            json!({"choices": [{"delta": {"content": "b"}}]}),
+        ];
+        assert_eq!(extract_stream_text(&chat), "ab");
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 代理服务器配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -221,6 +222,756 @@ fn default_true() -> bool {
     true
 }
 
+/// 本地客户端识别方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ClientIdentity {
+    /// 按客户端 IP 区分
+    #[default]
+    Ip,
+    /// 按客户端携带的 API Key 区分（缺失时回退到 IP）
+    ApiKey,
+    /// 按 User-Agent 区分（缺失时回退到 IP）
+    UserAgent,
+}
+
+/// 本地客户端限流配置
+///
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientRateLimitConfig {
+    /// 是否启用本地限流
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每个客户端每分钟允许的请求数
+    #[serde(default = "default_requests_per_minute")]
+    pub requests_per_minute: u32,
+    /// 客户端识别方式
+    #[serde(default)]
+    pub identity: ClientIdentity,
+}
+
+impl Default for ClientRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            requests_per_minute: default_requests_per_minute(),
+            identity: ClientIdentity::default(),
+        }
+    }
+}
+
+fn default_requests_per_minute() -> u32 {
+    60
+}
+
+/// 团队网关配置
+///
+/// 启用后每个请求都必须携带团队成员的访问令牌，按成员统计用量并执行配额与供应商限制。
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamGatewayConfig {
+    /// 是否启用团队网关模式
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 入站 IP 白名单配置
+///
+/// 启用后只有来源地址命中 CIDR 列表的连接可以访问代理（本机回环地址始终允许）。
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IpAllowlistConfig {
+    /// 是否启用 IP 白名单
+    #[serde(default)]
+    pub enabled: bool,
+    /// 允许的地址段，如 `192.168.1.0/24`、`10.0.0.5`、`fd00::/8`
+    #[serde(default)]
+    pub cidrs: Vec<String>,
+}
+
+/// 请求体大小限制配置
+///
+/// 超过上限的请求在本地直接返回 413，不会发送到上游。存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestSizeLimitConfig {
+    /// 请求体上限（MB），0 表示不限制
+    #[serde(default = "default_max_request_body_mb")]
+    pub max_body_mb: u64,
+}
+
+impl Default for RequestSizeLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_body_mb: default_max_request_body_mb(),
+        }
+    }
+}
+
+fn default_max_request_body_mb() -> u64 {
+    32
+}
+
+/// 本地代理 HTTPS 配置
+///
+/// 未指定证书 / 私钥路径时自动生成自签名证书。存储在 settings 表中，修改后需重启代理
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyTlsConfig {
+    /// 是否以 HTTPS 监听
+    #[serde(default)]
+    pub enabled: bool,
+    /// 自定义证书路径（PEM）
+    #[serde(default)]
+    pub cert_path: Option<String>,
+    /// 自定义私钥路径（PEM）
+    #[serde(default)]
+    pub key_path: Option<String>,
+}
+
+/// Webhook 事件类型
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookEvent {
+    /// 请求完成（含消费）
+    RequestCompleted,
+    /// 故障转移 / 自动回切切换了供应商
+    ProviderFailover,
+    /// 供应商超出预算限额
+    BudgetExceeded,
+    /// 供应商连续失败，熔断器打开
+    ProviderDown,
+}
+
+/// Webhook 负载格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookFormat {
+    /// 完整 JSON 事件
+    #[default]
+    Json,
+    /// Slack Incoming Webhook（`{"text": ...}`）
+    Slack,
+    /// Discord Webhook（`{"content": ...}`）
+    Discord,
+    /// 纯文本（ntfy 等）
+    Text,
+}
+
+/// 单个 Webhook 端点
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEndpoint {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 订阅的事件（为空时订阅全部事件）
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+/// Webhook 配置
+///
+/// 在关键事件发生时向外部地址推送 JSON / 文本通知。存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookConfig {
+    #[serde(default)]
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+/// 实验组应用的转换规则
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ExperimentTreatment {
+    /// 自动注入 cache_control 断点
+    #[serde(rename_all = "camelCase")]
+    PromptCaching {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_chars: Option<usize>,
+    },
+    /// 系统提示词注入
+    #[serde(rename_all = "camelCase")]
+    SystemPrompt {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        prefix: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suffix: Option<String>,
+    },
+    /// 流量切分：实验组改由指定供应商处理（对照组仍使用原供应商）
+    #[serde(rename_all = "camelCase")]
+    TrafficSplit { provider_id: String },
+}
+
+/// A/B 实验
+///
+/// 按会话把 `percentage`% 的请求分入实验组（应用 `treatment`），其余为对照组
+/// （不应用该类规则），两组的消费、延迟、错误率分别统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Experiment {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub enabled: bool,
+    /// 生效的应用（claude / codex / gemini）
+    pub app_type: String,
+    /// 仅对指定供应商生效（为空时对链上全部供应商生效）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// 实验组流量占比（0-100）
+    pub percentage: u8,
+    pub treatment: ExperimentTreatment,
+}
+
+/// 自动回切配置
+///
+/// 故障转移把流量切离首选供应商后，定期探测首选供应商，
+/// 连续探测成功达到阈值时自动切回。存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FailbackConfig {
+    /// 是否启用自动回切
+    #[serde(default)]
+    pub enabled: bool,
+    /// 探测间隔（秒）
+    #[serde(default = "default_failback_interval_seconds")]
+    pub check_interval_seconds: u64,
+    /// 切回前需要的连续成功探测次数
+    #[serde(default = "default_failback_required_successes")]
+    pub required_successes: u32,
+}
+
+fn default_failback_interval_seconds() -> u64 {
+    60
+}
+
+fn default_failback_required_successes() -> u32 {
+    3
+}
+
+impl Default for FailbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_seconds: default_failback_interval_seconds(),
+            required_successes: default_failback_required_successes(),
+        }
+    }
+}
+
+/// 供应商故障桌面通知配置（存储在 settings 表中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FailureNotificationConfig {
+    /// 是否启用桌面通知
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 触发通知的失败次数
+    #[serde(default = "default_failure_notification_threshold")]
+    pub failure_threshold: u32,
+    /// 统计失败次数的时间窗口（分钟）
+    #[serde(default = "default_failure_notification_window_minutes")]
+    pub window_minutes: u32,
+    /// 故障转移切换供应商时是否通知
+    #[serde(default = "default_true")]
+    pub notify_on_failover: bool,
+}
+
+fn default_failure_notification_threshold() -> u32 {
+    3
+}
+
+fn default_failure_notification_window_minutes() -> u32 {
+    5
+}
+
+impl Default for FailureNotificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: default_failure_notification_threshold(),
+            window_minutes: default_failure_notification_window_minutes(),
+            notify_on_failover: true,
+        }
+    }
+}
+
+/// 消费阈值提醒配置（存储在 settings 表中）
+///
+/// 统计所有应用的估算消费，每日按本地零点、每周按本地周一零点重新计算
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CostAlertConfig {
+    /// 是否启用消费提醒
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每日消费阈值（USD）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_limit_usd: Option<f64>,
+    /// 每周消费阈值（USD）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_limit_usd: Option<f64>,
+    /// 超出阈值后自动切换到的供应商（key = app_type，value = provider_id）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub switch_to: HashMap<String, String>,
+}
+
+/// 告警规则条件（在 `window_minutes` 时间窗口内统计）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AlertCondition {
+    /// 供应商错误率（%）达到阈值，请求数不足 `min_requests` 时不判断
+    ErrorRate {
+        min_percent: f64,
+        #[serde(default = "default_alert_min_requests")]
+        min_requests: u64,
+    },
+    /// 供应商成功请求的 p95 延迟（毫秒）达到阈值
+    Latency { p95_ms: u64 },
+    /// 供应商的限流重试与故障转移次数达到阈值
+    Retries { min_count: u64 },
+    /// 规则范围内的总消费（USD）达到阈值
+    Spend { min_usd: f64 },
+}
+
+fn default_alert_min_requests() -> u64 {
+    5
+}
+
+/// 告警规则触发后的动作
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum AlertAction {
+    /// 仅发送通知
+    Notify,
+    /// 把规则所属应用的当前供应商切换为指定供应商（需要设置 `appType`）
+    SwitchProvider { provider_id: String },
+    /// 停止代理并恢复 Live 配置
+    PauseProxy,
+}
+
+/// 告警规则（列表存储在 settings 表中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertRule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 限定应用（为空时统计所有应用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_type: Option<String>,
+    pub condition: AlertCondition,
+    pub action: AlertAction,
+    /// 统计窗口（分钟）
+    #[serde(default = "default_alert_window_minutes")]
+    pub window_minutes: u32,
+    /// 同一规则对同一对象再次触发的最小间隔（分钟）
+    #[serde(default = "default_alert_cooldown_minutes")]
+    pub cooldown_minutes: u32,
+}
+
+fn default_alert_window_minutes() -> u32 {
+    15
+}
+
+fn default_alert_cooldown_minutes() -> u32 {
+    30
+}
+
+/// 供应商评分与自动选择配置
+///
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderScoringConfig {
+    /// 处于 auto 模式的应用（claude / codex / gemini）：始终路由到评分最高的供应商
+    #[serde(default)]
+    pub auto_apps: Vec<String>,
+    /// 评分统计窗口（分钟）
+    #[serde(default = "default_scoring_window_minutes")]
+    pub window_minutes: u32,
+    /// 错误率权重
+    #[serde(default = "default_error_weight")]
+    pub error_weight: f64,
+    /// 延迟权重
+    #[serde(default = "default_latency_weight")]
+    pub latency_weight: f64,
+    /// 限流频率权重
+    #[serde(default = "default_rate_limit_weight")]
+    pub rate_limit_weight: f64,
+    /// 延迟达到该值（毫秒）时延迟得分为 0
+    #[serde(default = "default_latency_ceiling_ms")]
+    pub latency_ceiling_ms: u64,
+}
+
+fn default_scoring_window_minutes() -> u32 {
+    30
+}
+
+fn default_error_weight() -> f64 {
+    0.5
+}
+
+fn default_latency_weight() -> f64 {
+    0.3
+}
+
+fn default_rate_limit_weight() -> f64 {
+    0.2
+}
+
+fn default_latency_ceiling_ms() -> u64 {
+    30_000
+}
+
+impl Default for ProviderScoringConfig {
+    fn default() -> Self {
+        Self {
+            auto_apps: Vec::new(),
+            window_minutes: default_scoring_window_minutes(),
+            error_weight: default_error_weight(),
+            latency_weight: default_latency_weight(),
+            rate_limit_weight: default_rate_limit_weight(),
+            latency_ceiling_ms: default_latency_ceiling_ms(),
+        }
+    }
+}
+
+impl ProviderScoringConfig {
+    /// 指定应用是否处于 auto 模式
+    pub fn is_auto(&self, app_type: &str) -> bool {
+        self.auto_apps
+            .iter()
+            .any(|app| app.eq_ignore_ascii_case(app_type))
+    }
+}
+
+/// 请求日志中 tool_result 的脱敏方式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogRedactionMode {
+    /// 原样记录
+    #[default]
+    Off,
+    /// 仅保留前 N 个字符
+    Truncate,
+    /// 替换为 SHA-256 摘要
+    Hash,
+}
+
+/// 请求日志脱敏配置
+///
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRedactionConfig {
+    /// 脱敏方式
+    #[serde(default)]
+    pub mode: LogRedactionMode,
+    /// 截断模式下保留的字符数
+    #[serde(default = "default_truncate_chars")]
+    pub truncate_chars: usize,
+}
+
+impl Default for LogRedactionConfig {
+    fn default() -> Self {
+        Self {
+            mode: LogRedactionMode::default(),
+            truncate_chars: default_truncate_chars(),
+        }
+    }
+}
+
+fn default_truncate_chars() -> usize {
+    200
+}
+
+/// 调试日志详细程度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DebugLogLevel {
+    /// 记录完整请求头与响应头（开启请求体记录时同时记录请求体与响应内容）
+    #[default]
+    Verbose,
+    /// 仅记录请求摘要（供应商、URL、状态码），
+    /// 开启了调试日志的供应商或携带调试请求头的请求仍记录完整内容
+    Summary,
+}
+
+/// 调试日志配置
+///
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugLogConfig {
+    /// 是否写入调试日志（运行时开关，关闭后不再写入任何调试日志文件）
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 调试日志目录（为空时使用 `<app_config_dir>/logs/debug`）
+    #[serde(default)]
+    pub log_dir: Option<String>,
+    /// 全局日志级别
+    #[serde(default)]
+    pub level: DebugLogLevel,
+    /// 详细日志是否记录请求体与响应内容（默认关闭：请求体包含私有代码与提示词；供应商可单独覆盖）
+    #[serde(default)]
+    pub log_bodies: bool,
+    /// 调试日志保留天数（0 表示不按时间清理）
+    #[serde(default = "default_debug_log_retention_days")]
+    pub retention_days: u32,
+    /// 调试日志总大小上限（MB，超出时从最早的文件开始删除；0 表示不限）
+    #[serde(default = "default_debug_log_max_total_mb")]
+    pub max_total_mb: u64,
+    /// 是否将已轮转（非当前小时）的日志压缩为 `.log.gz`
+    #[serde(default)]
+    pub compress_rotated: bool,
+}
+
+fn default_debug_log_retention_days() -> u32 {
+    7
+}
+
+fn default_debug_log_max_total_mb() -> u64 {
+    500
+}
+
+impl Default for DebugLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            log_dir: None,
+            level: DebugLogLevel::default(),
+            log_bodies: false,
+            retention_days: default_debug_log_retention_days(),
+            max_total_mb: default_debug_log_max_total_mb(),
+            compress_rotated: false,
+        }
+    }
+}
+
+/// OpenTelemetry 链路追踪配置
+///
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtelConfig {
+    /// 是否上报 trace
+    #[serde(default)]
+    pub enabled: bool,
+    /// OTLP/HTTP collector 地址（如 `http://localhost:4318`）
+    #[serde(default)]
+    pub endpoint: String,
+    /// 上报的 `service.name` 资源属性
+    #[serde(default = "default_otel_service_name")]
+    pub service_name: String,
+    /// 上报时附加的请求头（如 collector 认证）
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: String::new(),
+            service_name: default_otel_service_name(),
+            headers: HashMap::new(),
+        }
+    }
+}
+
+fn default_otel_service_name() -> String {
+    "cc-switch".to_string()
+}
+
+/// 单次请求成本标注配置
+///
+/// 存储在 settings 表中
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CostAnnotationConfig {
+    /// 是否在响应中注入成本与用量信息
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 对话记录采集配置
+///
+/// 存储在 settings 表中，默认关闭
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptConfig {
+    /// 是否保存每轮对话的输入与回复文本，用于历史搜索
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// 完整对话导出格式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversationFormat {
+    #[default]
+    Markdown,
+    Json,
+    /// 同时写入 Markdown 与 JSON
+    Both,
+}
+
+/// 完整对话采集配置
+///
+/// 启用后按会话重建完整对话（系统提示词、各轮消息、工具调用与最终回复）并写入文件。
+/// 存储在 settings 表中，默认关闭
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationCaptureConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub format: ConversationFormat,
+    /// 输出目录（为空时使用配置目录下的 `conversations`）
+    #[serde(default)]
+    pub output_dir: Option<String>,
+}
+
+fn default_replay_max_entries() -> u32 {
+    50
+}
+
+/// 请求重放采集配置
+///
+/// 启用后保存发往上游的请求体，用于从历史中重放单个请求。存储在 settings 表中，默认关闭
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayCaptureConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 最多保留的请求数（超出时删除最早的记录）
+    #[serde(default = "default_replay_max_entries")]
+    pub max_entries: u32,
+}
+
+impl Default for ReplayCaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_entries: default_replay_max_entries(),
+        }
+    }
+}
+
+fn default_shadow_sample_percent() -> u8 {
+    100
+}
+
+/// 影子流量配置
+///
+/// 启用后将请求复制一份发往各应用指定的影子供应商，丢弃其响应、仅记录延迟与用量，
+/// 用于在切换前评估新中转的质量与速度。存储在 settings 表中，默认关闭
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowTrafficConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 各应用的影子供应商（应用类型 → 供应商 ID）
+    #[serde(default)]
+    pub targets: HashMap<String, String>,
+    /// 复制的请求比例（0-100）
+    #[serde(default = "default_shadow_sample_percent")]
+    pub sample_percent: u8,
+}
+
+impl Default for ShadowTrafficConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            targets: HashMap::new(),
+            sample_percent: default_shadow_sample_percent(),
+        }
+    }
+}
+
+fn default_response_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_response_cache_max_entries() -> usize {
+    200
+}
+
+/// 响应缓存配置
+///
+/// 启用后相同的非流式请求（如标题生成等重复的工具调用）在有效期内直接返回缓存的响应，
+/// 不再请求上游。存储在 settings 表中，默认关闭
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 缓存有效期（秒）
+    #[serde(default = "default_response_cache_ttl_secs")]
+    pub ttl_secs: u64,
+    /// 最多缓存的响应数
+    #[serde(default = "default_response_cache_max_entries")]
+    pub max_entries: usize,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_response_cache_ttl_secs(),
+            max_entries: default_response_cache_max_entries(),
+        }
+    }
+}
+
+fn default_sse_keepalive_interval_secs() -> u64 {
+    15
+}
+
+/// SSE 保活配置
+///
+/// 启用后流式请求在等待上游时，代理按间隔向客户端发送 SSE 注释行（`: keepalive`），
+/// 避免上游首 token 过慢时客户端因空闲超时断开连接。存储在 settings 表中，默认关闭
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SseKeepaliveConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 保活间隔（秒）
+    #[serde(default = "default_sse_keepalive_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl Default for SseKeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_sse_keepalive_interval_secs(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,16 +28,54 @@ pub struct RequestLog {
     pub is_streaming: bool,
     /// 成本倍数
     pub cost_multiplier: String,
+    /// 团队网关成员 ID
+    pub member_id: Option<String>,
+    /// A/B 实验分组
+    pub experiment: Option<String>,
+}
+
+/// 计算流式输出速度（tokens/s）
+///
+/// 按首个 token 到响应结束的时间计算，不含排队与首 token 前的处理时间；
+/// 非流式请求（无首 token 时间）或输出为空时返回 None
+pub fn output_tokens_per_second(
+    output_tokens: u32,
+    latency_ms: u64,
+    first_token_ms: Option<u64>,
+) -> Option<f64> {
+    let generation_ms = latency_ms.checked_sub(first_token_ms?)?;
+    if output_tokens == 0 || generation_ms == 0 {
+        return None;
+    }
+    Some(output_tokens as f64 * 1000.0 / generation_ms as f64)
 }
 
 /// 使用量记录器
 pub struct UsageLogger<'a> {
     db: &'a Database,
+    member_id: Option<String>,
+    experiment: Option<String>,
 }
 
 impl<'a> UsageLogger<'a> {
     pub fn new(db: &'a Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            member_id: None,
+            experiment: None,
+        }
+    }
+
+    /// 将记录归属到团队网关成员
+    pub fn with_member_id(mut self, member_id: Option<String>) -> Self {
+        self.member_id = member_id;
+        self
+    }
+
+    /// 标记记录所属的 A/B 实验分组
+    pub fn with_experiment(mut self, experiment: Option<String>) -> Self {
+        self.experiment = experiment;
+        self
     }
 
     /// 记录成功的请求
@@ -77,8 +115,9 @@ impl<'a> UsageLogger<'a> {
                 input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
                 input_cost_usd, output_cost_usd, cache_read_cost_usd, cache_creation_cost_usd, total_cost_usd,
                 latency_ms, first_token_ms, status_code, error_message, session_id,
-                provider_type, is_streaming, cost_multiplier, created_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22)",
+                provider_type, is_streaming, cost_multiplier, created_at, member_id, experiment,
+                output_tokens_per_second
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
             rusqlite::params![
                 log.request_id,
                 log.provider_id,
@@ -102,6 +141,13 @@ impl<'a> UsageLogger<'a> {
                 log.is_streaming as i64,
                 log.cost_multiplier,
                 created_at,
+                log.member_id,
+                log.experiment,
+                output_tokens_per_second(
+                    log.usage.output_tokens,
+                    log.latency_ms,
+                    log.first_token_ms
+                ),
             ],
         )
         .map_err(|e| AppError::Database(format!("记录请求日志失败: {e}")))?;
@@ -138,6 +184,8 @@ impl<'a> UsageLogger<'a> {
             provider_type: None,
             is_streaming: false,
             cost_multiplier: "1.0".to_string(),
+            member_id: self.member_id.clone(),
+            experiment: self.experiment.clone(),
         };
 
         self.log_request(&log)
@@ -175,6 +223,8 @@ impl<'a> UsageLogger<'a> {
             provider_type,
             is_streaming,
             cost_multiplier: "1.0".to_string(),
+            member_id: self.member_id.clone(),
+            experiment: self.experiment.clone(),
         };
 
         self.log_request(&log)
@@ -194,7 +244,7 @@ impl<'a> UsageLogger<'a> {
         }
     }
 
-    /// 计算并记录请求
+    /// 计算并记录请求（返回计算出的总消费，未找到模型定价时为 None）
     #[allow(clippy::too_many_arguments)]
     pub fn log_with_calculation(
         &self,
@@ -210,7 +260,7 @@ impl<'a> UsageLogger<'a> {
         session_id: Option<String>,
         provider_type: Option<String>,
         is_streaming: bool,
-    ) -> Result<(), AppError> {
+    ) -> Result<Option<Decimal>, AppError> {
         let pricing = self.get_model_pricing(&model)?;
 
         if pricing.is_none() {
@@ -218,6 +268,7 @@ impl<'a> UsageLogger<'a> {
         }
 
         let cost = CostCalculator::try_calculate(&usage, pricing.as_ref(), cost_multiplier);
+        let total_cost = cost.as_ref().map(|c| c.total_cost);
 
         let log = RequestLog {
             request_id,
@@ -234,9 +285,12 @@ impl<'a> UsageLogger<'a> {
             provider_type,
             is_streaming,
             cost_multiplier: cost_multiplier.to_string(),
+            member_id: self.member_id.clone(),
+            experiment: self.experiment.clone(),
         };
 
-        self.log_request(&log)
+        self.log_request(&log)?;
+        Ok(total_cost)
     }
 }
 
@@ -244,6 +298,14 @@ impl<'a> UsageLogger<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_output_tokens_per_second() {
+        assert_eq!(output_tokens_per_second(500, 2_500, Some(500)), Some(250.0));
+        assert_eq!(output_tokens_per_second(500, 2_500, None), None);
+        assert_eq!(output_tokens_per_second(0, 2_500, Some(500)), None);
+        assert_eq!(output_tokens_per_second(500, 500, Some(500)), None);
+    }
+
     #[test]
     fn test_log_request() -> Result<(), AppError> {
         let db = Database::memory()?;
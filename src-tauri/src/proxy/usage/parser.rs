@@ -109,6 +109,23 @@ impl TokenUsage {
                                     usage.input_tokens = input as u32;
                                 }
                             }
+                            // 部分上游（含转换后的流）只在 message_delta 中给出缓存用量
+                            if usage.cache_read_tokens == 0 {
+                                if let Some(v) = delta_usage
+                                    .get("cache_read_input_tokens")
+                                    .and_then(|v| v.as_u64())
+                                {
+                                    usage.cache_read_tokens = v as u32;
+                                }
+                            }
+                            if usage.cache_creation_tokens == 0 {
+                                if let Some(v) = delta_usage
+                                    .get("cache_creation_input_tokens")
+                                    .and_then(|v| v.as_u64())
+                                {
+                                    usage.cache_creation_tokens = v as u32;
+                                }
+                            }
                         }
                     }
                     _ => {}
@@ -495,6 +512,33 @@ mod tests {
         assert_eq!(usage.model, Some("claude-sonnet-4-20250514".to_string()));
     }
 
+    #[test]
+    fn test_claude_stream_parsing_cache_in_delta() {
+        let events = vec![
+            json!({
+                "type": "message_start",
+                "message": {
+                    "model": "claude-sonnet-4-20250514",
+                    "usage": {"input_tokens": 100}
+                }
+            }),
+            json!({
+                "type": "message_delta",
+                "usage": {
+                    "output_tokens": 50,
+                    "cache_read_input_tokens": 30,
+                    "cache_creation_input_tokens": 5
+                }
+            }),
+        ];
+
+        let usage = TokenUsage::from_claude_stream_events(&events).unwrap();
+        assert_eq!(usage.input_tokens, 100);
+        assert_eq!(usage.output_tokens, 50);
+        assert_eq!(usage.cache_read_tokens, 30);
+        assert_eq!(usage.cache_creation_tokens, 5);
+    }
+
     #[test]
     fn test_claude_stream_parsing_no_model() {
         let events = vec![
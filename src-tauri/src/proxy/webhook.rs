@@ -0,0 +1,229 @@
+//! Webhook 事件推送
+//!
+//! 在关键事件发生时，把事件推送到用户配置的外部地址（Slack、Discord、ntfy 或自建看板）：
+//! - `requestCompleted`：请求完成，附带 token 用量与消费
+//! - `providerFailover`：故障转移 / 自动回切切换了供应商
+//! - `budgetExceeded`：供应商超出预算限额（每个周期仅推送一次）
+//! - `providerDown`：供应商连续失败导致熔断器打开
+//!
+//! 推送在后台异步执行（超时 10 秒），失败只记录日志，不影响请求本身。
+//! 配置存储在 settings 表（`webhook_config`）中。
+
+use super::types::{WebhookEndpoint, WebhookEvent, WebhookFormat};
+use super::usage::parser::TokenUsage;
+use crate::database::Database;
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// 单次推送超时
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn event_name(event: WebhookEvent) -> &'static str {
+    match event {
+        WebhookEvent::RequestCompleted => "requestCompleted",
+        WebhookEvent::ProviderFailover => "providerFailover",
+        WebhookEvent::BudgetExceeded => "budgetExceeded",
+        WebhookEvent::ProviderDown => "providerDown",
+    }
+}
+
+/// 端点是否订阅了该事件
+fn subscribes(endpoint: &WebhookEndpoint, event: WebhookEvent) -> bool {
+    endpoint.enabled
+        && !endpoint.url.trim().is_empty()
+        && (endpoint.events.is_empty() || endpoint.events.contains(&event))
+}
+
+/// 生成一行可读摘要（用于 Slack / 纯文本格式）
+fn summarize(event: WebhookEvent, data: &Value) -> String {
+    let field = |key: &str| data.get(key).and_then(Value::as_str).unwrap_or("-");
+    match event {
+        WebhookEvent::RequestCompleted => format!(
+            "[cc-switch] {} 请求完成：供应商 {}，模型 {}，状态 {}，消费 ${}",
+            field("appType"),
+            field("providerId"),
+            field("model"),
+            data.get("statusCode").and_then(Value::as_u64).unwrap_or(0),
+            field("costUsd"),
+        ),
+        WebhookEvent::ProviderFailover => format!(
+            "[cc-switch] {} 已切换到供应商 {}（{}）",
+            field("appType"),
+            field("providerId"),
+            field("source"),
+        ),
+        WebhookEvent::BudgetExceeded => format!(
+            "[cc-switch] {} 供应商 {} 已超出{}限额",
+            field("appType"),
+            field("providerName"),
+            field("periodLabel"),
+        ),
+        WebhookEvent::ProviderDown => format!(
+            "[cc-switch] {} 供应商 {} 连续失败，已暂停使用：{}",
+            field("appType"),
+            field("providerId"),
+            field("error"),
+        ),
+    }
+}
+
+/// 按端点格式构造请求体，返回 (Content-Type, body)
+fn build_body(format: WebhookFormat, event: WebhookEvent, data: &Value) -> (&'static str, String) {
+    match format {
+        WebhookFormat::Json => (
+            "application/json",
+            json!({
+                "event": event_name(event),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "data": data,
+            })
+            .to_string(),
+        ),
+        WebhookFormat::Slack => (
+            "application/json",
+            json!({ "text": summarize(event, data) }).to_string(),
+        ),
+        WebhookFormat::Discord => (
+            "application/json",
+            json!({ "content": summarize(event, data) }).to_string(),
+        ),
+        WebhookFormat::Text => ("text/plain; charset=utf-8", summarize(event, data)),
+    }
+}
+
+/// 推送事件到全部订阅的端点（后台执行）
+pub fn dispatch(db: &Database, event: WebhookEvent, data: Value) {
+    let endpoints: Vec<WebhookEndpoint> = match db.get_webhook_config() {
+        Ok(config) => config
+            .endpoints
+            .into_iter()
+            .filter(|e| subscribes(e, event))
+            .collect(),
+        Err(e) => {
+            log::warn!("[Webhook] 读取配置失败: {e}");
+            return;
+        }
+    };
+    if endpoints.is_empty() {
+        return;
+    }
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+
+    runtime.spawn(async move {
+        for endpoint in endpoints {
+            if let Err(e) = deliver(&endpoint, event, &data).await {
+                log::warn!("[Webhook] 推送到 {} 失败: {e}", endpoint.name);
+            }
+        }
+    });
+}
+
+/// 推送请求完成事件
+#[allow(clippy::too_many_arguments)]
+pub fn notify_request_completed(
+    db: &Database,
+    app_type: &str,
+    provider_id: &str,
+    model: &str,
+    usage: &TokenUsage,
+    status_code: u16,
+    latency_ms: u64,
+    cost_usd: Option<Decimal>,
+) {
+    dispatch(
+        db,
+        WebhookEvent::RequestCompleted,
+        json!({
+            "appType": app_type,
+            "providerId": provider_id,
+            "model": model,
+            "statusCode": status_code,
+            "latencyMs": latency_ms,
+            "inputTokens": usage.input_tokens,
+            "outputTokens": usage.output_tokens,
+            "cacheReadTokens": usage.cache_read_tokens,
+            "cacheCreationTokens": usage.cache_creation_tokens,
+            "costUsd": cost_usd.unwrap_or_default().to_string(),
+        }),
+    );
+}
+
+/// 发送单次推送
+pub async fn deliver(
+    endpoint: &WebhookEndpoint,
+    event: WebhookEvent,
+    data: &Value,
+) -> Result<(), String> {
+    let (content_type, body) = build_body(endpoint.format, event, data);
+    let response = super::http_client::get()
+        .post(endpoint.url.trim())
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .timeout(DELIVERY_TIMEOUT)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(events: Vec<WebhookEvent>, format: WebhookFormat) -> WebhookEndpoint {
+        WebhookEndpoint {
+            id: "hook".into(),
+            name: "hook".into(),
+            url: "https://example.com/hook".into(),
+            enabled: true,
+            events,
+            format,
+        }
+    }
+
+    #[test]
+    fn filters_events_and_formats_bodies() {
+        let all = endpoint(Vec::new(), WebhookFormat::Json);
+        let failover_only = endpoint(vec![WebhookEvent::ProviderFailover], WebhookFormat::Slack);
+        assert!(subscribes(&all, WebhookEvent::RequestCompleted));
+        assert!(!subscribes(&failover_only, WebhookEvent::RequestCompleted));
+        assert!(subscribes(&failover_only, WebhookEvent::ProviderFailover));
+
+        let data = json!({ "appType": "claude", "providerId": "backup", "source": "failover" });
+        let (content_type, body) =
+            build_body(WebhookFormat::Json, WebhookEvent::ProviderFailover, &data);
+        assert_eq!(content_type, "application/json");
+        let parsed: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed["event"], "providerFailover");
+        assert_eq!(parsed["data"]["providerId"], "backup");
+
+        let (_, slack) = build_body(WebhookFormat::Slack, WebhookEvent::ProviderFailover, &data);
+        let text = serde_json::from_str::<Value>(&slack).unwrap()["text"].clone();
+        assert_eq!(text, "[cc-switch] claude 已切换到供应商 backup（failover）");
+
+        let (content_type, plain) =
+            build_body(WebhookFormat::Text, WebhookEvent::ProviderFailover, &data);
+        assert!(content_type.starts_with("text/plain"));
+        assert_eq!(plain, text.as_str().unwrap());
+    }
+
+    #[test]
+    fn formats_provider_down_for_discord() {
+        let data = json!({ "appType": "codex", "providerId": "p1", "error": "HTTP 503" });
+        let (content_type, body) =
+            build_body(WebhookFormat::Discord, WebhookEvent::ProviderDown, &data);
+        assert_eq!(content_type, "application/json");
+        let parsed: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            parsed["content"],
+            "[cc-switch] codex 供应商 p1 连续失败，已暂停使用：HTTP 503"
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! WebSocket 透传（Realtime API）
+//!
+//! 客户端的 WebSocket 升级请求先由代理与上游握手，握手成功后再接受客户端升级，
+//! 之后双向原样转发消息。上游握手请求与 HTTP 转发使用相同的凭据注入流程
+//! （适配器认证头、密钥池、自定义认证方式、请求头改写规则），客户端携带的占位凭据不会发送到上游。
+//!
+//! 会话结束时记录持续时间与双向消息数。当前上游连接直接建立，不经过供应商配置的上游代理与 mTLS 证书。
+
+use super::{
+    auth_scheme, header_filter::HeaderFilter, header_rules, key_pool, providers::get_adapter,
+    server::ProxyState, ProxyError,
+};
+use crate::app_config::AppType;
+use axum::{
+    extract::{
+        ws::{self, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::{HeaderMap, Uri},
+    response::Response,
+};
+use futures::{SinkExt, StreamExt};
+use std::time::Instant;
+use tokio_tungstenite::tungstenite::{
+    self, client::IntoClientRequest, protocol::frame::coding::CloseCode,
+};
+
+/// 由 WebSocket 握手自身管理的请求头，不从客户端透传
+const HANDSHAKE_HEADERS: &[&str] = &[
+    "connection",
+    "upgrade",
+    "sec-websocket-key",
+    "sec-websocket-version",
+    "sec-websocket-extensions",
+];
+
+/// 将 HTTP(S) 地址转换为 WS(S) 地址
+fn to_ws_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        url.to_string()
+    }
+}
+
+/// 由已添加认证信息的 HTTP 请求构造 WebSocket 握手请求
+///
+/// 地址取自 `request.url()`：查询参数认证会把密钥追加到该 URL 上，HMAC 签名的也是它的路径与查询参数，
+/// 这里只把协议换成 ws(s)，签名仍然有效
+fn handshake_request(
+    request: &reqwest::Request,
+) -> Result<tungstenite::handshake::client::Request, ProxyError> {
+    let url = to_ws_url(request.url().as_str());
+    let mut handshake = url.as_str().into_client_request().map_err(|e| {
+        ProxyError::ForwardFailed(format!(
+            "无效的 WebSocket 地址 {}: {e}",
+            request.url().path()
+        ))
+    })?;
+    handshake.headers_mut().extend(
+        request
+            .headers()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone())),
+    );
+    Ok(handshake)
+}
+
+/// 去掉 `/codex` 前缀，得到上游路径（含查询参数）
+fn upstream_endpoint(uri: &Uri) -> String {
+    let path = uri.path();
+    let path = path.strip_prefix("/codex").unwrap_or(path);
+    match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    }
+}
+
+fn to_upstream(message: ws::Message) -> tungstenite::Message {
+    match message {
+        ws::Message::Text(text) => tungstenite::Message::Text(text),
+        ws::Message::Binary(data) => tungstenite::Message::Binary(data),
+        ws::Message::Ping(data) => tungstenite::Message::Ping(data),
+        ws::Message::Pong(data) => tungstenite::Message::Pong(data),
+        ws::Message::Close(frame) => {
+            tungstenite::Message::Close(frame.map(|f| tungstenite::protocol::CloseFrame {
+                code: CloseCode::from(f.code),
+                reason: f.reason,
+            }))
+        }
+    }
+}
+
+fn to_client(message: tungstenite::Message) -> Option<ws::Message> {
+    Some(match message {
+        tungstenite::Message::Text(text) => ws::Message::Text(text),
+        tungstenite::Message::Binary(data) => ws::Message::Binary(data),
+        tungstenite::Message::Ping(data) => ws::Message::Ping(data),
+        tungstenite::Message::Pong(data) => ws::Message::Pong(data),
+        tungstenite::Message::Close(frame) => ws::Message::Close(frame.map(|f| ws::CloseFrame {
+            code: f.code.into(),
+            reason: f.reason,
+        })),
+        // 原始帧仅在写入时使用，读取时不会出现
+        tungstenite::Message::Frame(_) => return None,
+    })
+}
+
+/// 处理 Realtime API 的 WebSocket 升级请求（Codex 供应商）
+pub async fn handle_realtime(
+    State(state): State<ProxyState>,
+    upgrade: WebSocketUpgrade,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Result<Response, ProxyError> {
+    let provider = state
+        .provider_router
+        .select_providers("codex")
+        .await
+        .map_err(|e| ProxyError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .next()
+        .ok_or(ProxyError::NoAvailableProvider)?;
+
+    let adapter = get_adapter(&AppType::Codex);
+    let endpoint = upstream_endpoint(&uri);
+    let http_url = adapter.build_url(&adapter.extract_base_url(&provider)?, &endpoint);
+
+    // 借助 HTTP 请求构建器生成与普通转发一致的认证头与改写规则
+    let client = super::http_client::get();
+    let mut request = client.get(&http_url);
+    let header_filter = HeaderFilter::from_provider(&provider);
+    for (key, value) in &headers {
+        let name = key.as_str();
+        if header_filter.should_forward(name) && !HANDSHAKE_HEADERS.contains(&name) {
+            request = request.header(key, value);
+        }
+    }
+    let mut auth = adapter.extract_auth(&provider);
+    key_pool::override_auth(&provider, &mut auth);
+    let auth_scheme = auth_scheme::get_auth_scheme(&provider);
+    if let (Some(auth), None) = (&auth, auth_scheme) {
+        request = adapter.add_auth_headers(request, auth);
+    }
+    let mut request = request
+        .build()
+        .map_err(|e| ProxyError::ForwardFailed(format!("构建请求失败: {e}")))?;
+    if let (Some(auth), Some(scheme)) = (&auth, auth_scheme) {
+        auth_scheme::apply_auth_scheme(&mut request, scheme, &auth.api_key)?;
+    }
+    if let Some(rules) = header_rules::get_header_rules(&provider) {
+        header_rules::apply_header_rules(
+            request.headers_mut(),
+            rules,
+            auth.as_ref().map(|a| a.api_key.as_str()),
+        );
+    }
+
+    let handshake = handshake_request(&request)?;
+
+    log::info!("[WebSocket] {endpoint} -> {}", provider.name);
+    let (upstream, upstream_response) =
+        tokio_tungstenite::connect_async(handshake)
+            .await
+            .map_err(|e| match e {
+                tungstenite::Error::Http(response) => ProxyError::UpstreamError {
+                    status: response.status().as_u16(),
+                    body: response
+                        .body()
+                        .as_ref()
+                        .map(|body| String::from_utf8_lossy(body).to_string()),
+                },
+                e => ProxyError::ForwardFailed(format!("WebSocket 握手失败: {e}")),
+            })?;
+
+    // 子协议以上游的选择为准
+    let upgrade = match upstream_response
+        .headers()
+        .get("sec-websocket-protocol")
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(protocol) => upgrade.protocols([protocol.to_string()]),
+        None => upgrade,
+    };
+    let provider_name = provider.name.clone();
+    Ok(upgrade.on_upgrade(move |socket| tunnel(socket, upstream, provider_name)))
+}
+
+/// 双向转发消息，任一方关闭或出错时结束会话
+async fn tunnel<S>(
+    client: WebSocket,
+    upstream: tokio_tungstenite::WebSocketStream<S>,
+    provider_name: String,
+) where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let started = Instant::now();
+    let (mut client_tx, mut client_rx) = client.split();
+    let (mut upstream_tx, mut upstream_rx) = upstream.split();
+    let mut sent = 0u64;
+    let mut received = 0u64;
+
+    let client_to_upstream = async {
+        while let Some(Ok(message)) = client_rx.next().await {
+            sent += 1;
+            if upstream_tx.send(to_upstream(message)).await.is_err() {
+                break;
+            }
+        }
+        let _ = upstream_tx.close().await;
+    };
+    let upstream_to_client = async {
+        while let Some(Ok(message)) = upstream_rx.next().await {
+            let Some(message) = to_client(message) else {
+                continue;
+            };
+            received += 1;
+            if client_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+        let _ = client_tx.close().await;
+    };
+    {
+        tokio::pin!(client_to_upstream, upstream_to_client);
+        tokio::select! {
+            _ = &mut client_to_upstream => {}
+            _ = &mut upstream_to_client => {}
+        }
+    }
+
+    log::info!(
+        "[WebSocket] 会话结束 ({provider_name})：持续 {:.1}s，发送 {sent} 条，接收 {received} 条消息",
+        started.elapsed().as_secs_f64()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_upstream_url_and_endpoint() {
+        assert_eq!(
+            to_ws_url("https://api.openai.com/v1/realtime?model=x"),
+            "wss://api.openai.com/v1/realtime?model=x"
+        );
+        assert_eq!(
+            to_ws_url("http://127.0.0.1:8080/v1"),
+            "ws://127.0.0.1:8080/v1"
+        );
+        let uri: Uri = "/codex/v1/realtime?model=gpt-realtime".parse().unwrap();
+        assert_eq!(upstream_endpoint(&uri), "/v1/realtime?model=gpt-realtime");
+    }
+
+    #[test]
+    fn handshake_keeps_credentials_added_by_auth_scheme() {
+        use crate::provider::{AuthScheme, AuthSchemeType};
+
+        let scheme = |kind| AuthScheme {
+            kind,
+            name: None,
+            prefix: None,
+            access_key_id: Some("ak".to_string()),
+        };
+        let client = reqwest::Client::new();
+        let build = || {
+            client
+                .get("https://gw.example.com/v1/realtime?model=gpt-realtime")
+                .build()
+                .unwrap()
+        };
+
+        // 查询参数认证：密钥随 URL 进入握手请求
+        let mut request = build();
+        auth_scheme::apply_auth_scheme(&mut request, &scheme(AuthSchemeType::Query), "sk-1")
+            .unwrap();
+        let handshake = handshake_request(&request).unwrap();
+        assert_eq!(handshake.uri().scheme_str(), Some("wss"));
+        assert_eq!(handshake.uri().query(), Some("model=gpt-realtime&key=sk-1"));
+
+        // HMAC：签名头与被签名的路径、查询参数都原样用于握手
+        let mut request = build();
+        auth_scheme::apply_auth_scheme(&mut request, &scheme(AuthSchemeType::Hmac), "secret")
+            .unwrap();
+        let handshake = handshake_request(&request).unwrap();
+        assert_eq!(
+            handshake.uri().path_and_query().unwrap().as_str(),
+            format!(
+                "{}?{}",
+                request.url().path(),
+                request.url().query().unwrap()
+            )
+        );
+        for (name, value) in request.headers() {
+            assert_eq!(handshake.headers().get(name), Some(value));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! 诊断包导出
+//!
+//! 一键收集问题反馈所需的信息并打包为 zip：
+//! - `system.json`：应用版本、操作系统与架构、数据目录
+//! - `proxy-status.json`：代理运行状态
+//! - `providers.json`：供应商配置摘要（不含密钥，与导出供应商摘要相同）
+//! - `logs/`：最近的应用日志、调试日志与崩溃日志
+//!
+//! 日志每个文件只保留末尾 [`MAX_LOG_BYTES`] 字节，写入前统一经过密钥脱敏；
+//! 已压缩的历史调试日志不包含在内。
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::proxy::secret_redaction::redact_log_text;
+use crate::proxy::types::ProxyStatus;
+use crate::services::provider::SummaryFormat;
+use crate::services::ProviderService;
+use crate::store::AppState;
+use serde_json::json;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+
+/// 每个日志文件最多打包的字节数（取末尾）
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+
+/// 应用日志与调试日志各自最多打包的文件数（按修改时间取最新）
+const MAX_LOG_FILES: usize = 3;
+
+/// 按修改时间倒序列出目录中的 `.log` 文件
+fn recent_logs(dir: &Path, limit: usize) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(PathBuf, std::time::SystemTime)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|p| {
+            let modified = std::fs::metadata(&p).ok()?.modified().ok()?;
+            Some((p, modified))
+        })
+        .collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+    files.into_iter().take(limit).map(|(p, _)| p).collect()
+}
+
+/// 读取文件末尾（超出上限时从下一行开始，避免截断半行）
+fn read_tail(path: &Path, max_bytes: u64) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let truncated = len > max_bytes;
+    if truncated {
+        file.seek(SeekFrom::Start(len - max_bytes))?;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(match text.split_once('\n').filter(|_| truncated) {
+        Some((_, rest)) => rest.to_string(),
+        None => text.into_owned(),
+    })
+}
+
+fn system_info() -> serde_json::Value {
+    let paths = crate::data_dir::current_paths();
+    json!({
+        "appVersion": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "osFamily": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "paths": paths,
+        "generatedAt": chrono::Local::now().to_rfc3339(),
+    })
+}
+
+/// 收集诊断包中的各个文件（文件名, 内容）
+fn collect_entries(
+    state: &AppState,
+    proxy_status: &ProxyStatus,
+) -> Result<Vec<(String, String)>, AppError> {
+    let to_json = |value: serde_json::Value| {
+        serde_json::to_string_pretty(&value).map_err(|e| AppError::JsonSerialize { source: e })
+    };
+    let status =
+        serde_json::to_value(proxy_status).map_err(|e| AppError::JsonSerialize { source: e })?;
+    // 供应商摘要失败（如数据库异常）时记录原因，不影响其余内容
+    let providers = ProviderService::export_summary(state, None::<AppType>, SummaryFormat::Json)
+        .unwrap_or_else(|e| format!("导出供应商摘要失败: {e}"));
+
+    let mut entries = vec![
+        ("system.json".to_string(), to_json(system_info())?),
+        ("proxy-status.json".to_string(), to_json(status)?),
+        ("providers.json".to_string(), providers),
+    ];
+
+    let mut logs: Vec<(String, PathBuf)> = Vec::new();
+    for file in recent_logs(&crate::panic_hook::get_log_dir(), MAX_LOG_FILES) {
+        logs.push((format!("logs/app/{}", file_name(&file)), file));
+    }
+    for file in recent_logs(&crate::proxy::log_retention::debug_log_dir(), MAX_LOG_FILES) {
+        logs.push((format!("logs/debug/{}", file_name(&file)), file));
+    }
+    let crash_log = crate::config::get_app_config_dir().join("crash.log");
+    if crash_log.is_file() {
+        logs.push(("logs/crash.log".to_string(), crash_log));
+    }
+    for (name, file) in logs {
+        match read_tail(&file, MAX_LOG_BYTES) {
+            Ok(text) => entries.push((name, redact_log_text(&text).into_owned())),
+            Err(e) => log::warn!("读取日志 {} 失败: {e}", file.display()),
+        }
+    }
+    Ok(entries)
+}
+
+/// 导出诊断包，返回打包的文件数
+pub fn export_diagnostic_bundle(
+    state: &AppState,
+    proxy_status: &ProxyStatus,
+    path: &Path,
+) -> Result<usize, AppError> {
+    let entries = collect_entries(state, proxy_status)?;
+    let zip_error = |e: zip::result::ZipError| AppError::Message(format!("写入诊断包失败: {e}"));
+
+    let file = std::fs::File::create(path).map_err(|e| AppError::io(path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in &entries {
+        zip.start_file(name.as_str(), options).map_err(zip_error)?;
+        zip.write_all(content.as_bytes())
+            .map_err(|e| AppError::io(path, e))?;
+    }
+    zip.finish().map_err(zip_error)?;
+    Ok(entries.len())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_tail_starts_at_line_boundary() -> Result<(), AppError> {
+        let dir = tempfile::tempdir().map_err(|e| AppError::io("tempdir", e))?;
+        let path = dir.path().join("cc-switch.log");
+        std::fs::write(&path, "first line\nsecond line\nthird\n")
+            .map_err(|e| AppError::io(&path, e))?;
+
+        assert_eq!(
+            read_tail(&path, 1024).map_err(|e| AppError::io(&path, e))?,
+            "first line\nsecond line\nthird\n"
+        );
+        assert_eq!(
+            read_tail(&path, 15).map_err(|e| AppError::io(&path, e))?,
+            "third\n"
+        );
+        Ok(())
+    }
+}
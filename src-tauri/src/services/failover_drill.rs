@@ -0,0 +1,179 @@
+//! 故障转移演练
+//!
+//! 模拟当前供应商故障（不产生真实业务流量），按代理路由的实际顺序依次探测故障转移队列，
+//! 报告哪个供应商会接管以及预计的接管耗时，用于验证路由配置。
+
+use serde::Serialize;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::stream_check::{StreamCheckConfig, StreamCheckResult, StreamCheckService};
+use crate::services::StatusWatcherService;
+use crate::store::AppState;
+
+/// 演练中单个供应商的探测结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillStep {
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 未探测的原因（如被模拟故障的当前供应商）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub probe: Option<StreamCheckResult>,
+    /// 截至该步骤的累计耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
+/// 故障转移演练报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverDrillReport {
+    pub app_type: String,
+    pub auto_failover_enabled: bool,
+    /// 被模拟故障的供应商
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_provider_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_provider_name: Option<String>,
+    pub steps: Vec<DrillStep>,
+    /// 实际接管的供应商
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub takeover_provider_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub takeover_provider_name: Option<String>,
+    /// 当前供应商快速失败（连接拒绝等）时的预计接管耗时
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_takeover_ms: Option<u64>,
+    /// 当前供应商超时失败时的预计接管耗时（含首字节超时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub worst_case_takeover_ms: Option<u64>,
+    pub warnings: Vec<String>,
+}
+
+/// 按代理路由器的顺序排列故障转移队列（状态页故障的供应商排在最后）
+fn routing_order(app_type: &str, mut chain: Vec<Provider>) -> Vec<Provider> {
+    chain.sort_by_key(|p| StatusWatcherService::is_deprioritized(app_type, &p.id));
+    chain
+}
+
+/// 单次探测的耗时：成功取响应时间，失败按超时计
+fn probe_cost_ms(result: &StreamCheckResult, config: &StreamCheckConfig) -> u64 {
+    result
+        .response_time_ms
+        .unwrap_or(config.timeout_secs * 1000)
+}
+
+/// 故障转移演练服务
+pub struct FailoverDrillService;
+
+impl FailoverDrillService {
+    /// 执行演练
+    pub async fn run(state: &AppState, app_type: AppType) -> Result<FailoverDrillReport, AppError> {
+        let app_str = app_type.as_str();
+        let app_config = state.db.get_proxy_config_for_app(app_str).await?;
+        let check_config = state.db.get_stream_check_config()?;
+
+        let current_id = state.db.get_current_provider(app_str)?;
+        let current = match &current_id {
+            Some(id) => state.db.get_provider_by_id(id, app_str)?,
+            None => None,
+        };
+
+        let mut report = FailoverDrillReport {
+            app_type: app_str.to_string(),
+            auto_failover_enabled: app_config.auto_failover_enabled,
+            failed_provider_id: current.as_ref().map(|p| p.id.clone()),
+            failed_provider_name: current.as_ref().map(|p| p.name.clone()),
+            steps: Vec::new(),
+            takeover_provider_id: None,
+            takeover_provider_name: None,
+            estimated_takeover_ms: None,
+            worst_case_takeover_ms: None,
+            warnings: Vec::new(),
+        };
+
+        if !app_config.auto_failover_enabled {
+            report
+                .warnings
+                .push("自动故障转移未开启，当前供应商故障时请求将直接失败".to_string());
+            return Ok(report);
+        }
+
+        let chain = routing_order(app_str, state.db.get_failover_providers(app_str)?);
+        if chain.is_empty() {
+            report.warnings.push("故障转移队列为空".to_string());
+            return Ok(report);
+        }
+
+        let mut elapsed_ms = 0u64;
+        for provider in chain {
+            if current_id.as_deref() == Some(provider.id.as_str()) {
+                report.steps.push(DrillStep {
+                    provider_id: provider.id.clone(),
+                    provider_name: provider.name.clone(),
+                    skipped_reason: Some("模拟故障".to_string()),
+                    probe: None,
+                    elapsed_ms,
+                });
+                continue;
+            }
+
+            if StatusWatcherService::is_deprioritized(app_str, &provider.id) {
+                report
+                    .warnings
+                    .push(format!("{} 的状态页正在报告故障", provider.name));
+            }
+
+            let probe =
+                StreamCheckService::check_with_retry(&app_type, &provider, &check_config).await;
+            let probe = match probe {
+                Ok(result) => result,
+                Err(e) => {
+                    report.steps.push(DrillStep {
+                        provider_id: provider.id.clone(),
+                        provider_name: provider.name.clone(),
+                        skipped_reason: Some(format!("探测失败: {e}")),
+                        probe: None,
+                        elapsed_ms,
+                    });
+                    continue;
+                }
+            };
+
+            elapsed_ms += probe_cost_ms(&probe, &check_config);
+            let success = probe.success;
+            report.steps.push(DrillStep {
+                provider_id: provider.id.clone(),
+                provider_name: provider.name.clone(),
+                skipped_reason: None,
+                probe: Some(probe),
+                elapsed_ms,
+            });
+
+            if success {
+                report.takeover_provider_id = Some(provider.id.clone());
+                report.takeover_provider_name = Some(provider.name.clone());
+                report.estimated_takeover_ms = Some(elapsed_ms);
+                let first_byte_timeout_ms = app_config.streaming_first_byte_timeout as u64 * 1000;
+                report.worst_case_takeover_ms = Some(elapsed_ms + first_byte_timeout_ms);
+                break;
+            }
+        }
+
+        if report.takeover_provider_id.is_none() {
+            report
+                .warnings
+                .push("故障转移队列中没有可用的供应商".to_string());
+        }
+
+        log::info!(
+            "[FailoverDrill] {app_str}: 接管供应商 {:?}，预计耗时 {:?}ms",
+            report.takeover_provider_name,
+            report.estimated_takeover_ms
+        );
+        Ok(report)
+    }
+}
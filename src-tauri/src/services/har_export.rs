@@ -0,0 +1,231 @@
+//! HAR 导出
+//!
+//! 将请求重放采集（`proxy_request_captures`）中指定时间范围内的请求与上游响应转换为 HAR 1.2 文件，
+//! 便于向中转服务商提供复现材料，或在浏览器开发者工具、Charles 等标准工具中查看。
+//!
+//! 导出前统一脱敏：
+//! - 请求头：采集时不保存客户端请求头，导出中仅包含 `content-type`
+//! - JSON 字段：`api_key`、`authorization`、`token` 等字段的值替换为 `[REDACTED]`
+//! - 文本：形如 `sk-...`、`Bearer ...`、`AIza...` 的密钥替换为 `[REDACTED]`
+//! - URL：查询参数中的 `key` 等密钥参数
+//! - 按日志脱敏配置（`log_redaction_config`）处理 tool_result 内容
+//!
+//! 仅包含启用采集后的请求；只有透传模式的响应会被记录，未记录响应的条目状态码为 0。
+
+use crate::database::{Database, RequestCapture};
+use crate::error::AppError;
+use crate::proxy::log_redaction::redact_for_log;
+use crate::proxy::secret_redaction::{is_secret_key, redact_text, redact_value, REDACTED};
+use crate::proxy::types::LogRedactionConfig;
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// 脱敏响应体：JSON 按字段处理，其余（如 SSE 文本）按文本处理
+fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            value.to_string()
+        }
+        Err(_) => redact_text(body),
+    }
+}
+
+/// 脱敏 URL 查询参数中的密钥
+fn redact_url(url: &str) -> (String, Vec<Value>) {
+    let Ok(mut parsed) = url::Url::parse(url) else {
+        return (redact_text(url), Vec::new());
+    };
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if is_secret_key(&name) {
+                REDACTED.to_string()
+            } else {
+                redact_text(&value)
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    if pairs.is_empty() {
+        return (parsed.to_string(), Vec::new());
+    }
+    parsed.query_pairs_mut().clear().extend_pairs(&pairs);
+    let query = pairs
+        .into_iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect();
+    (parsed.to_string(), query)
+}
+
+fn mime_type(body: &str) -> &'static str {
+    if body.starts_with("data:") || body.starts_with("event:") {
+        "text/event-stream"
+    } else {
+        "application/json"
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    reqwest::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("")
+}
+
+/// 将单条采集转换为 HAR entry
+fn build_entry(capture: &RequestCapture, base_url: &str, redaction: &LogRedactionConfig) -> Value {
+    let (url, query_string) = redact_url(&format!(
+        "{}{}",
+        base_url.trim_end_matches('/'),
+        capture.endpoint
+    ));
+
+    let request_text = match serde_json::from_str::<Value>(&capture.body) {
+        Ok(body) => {
+            let mut body = redact_for_log(&body, redaction).into_owned();
+            redact_value(&mut body);
+            body.to_string()
+        }
+        Err(_) => redact_text(&capture.body),
+    };
+    let response_text = capture
+        .response_body
+        .as_deref()
+        .map(redact_body)
+        .unwrap_or_default();
+    let status = capture.status_code.unwrap_or(0);
+    let duration = capture.duration_ms.unwrap_or(0);
+    let started = Utc
+        .timestamp_opt(capture.created_at, 0)
+        .single()
+        .unwrap_or_default()
+        .to_rfc3339();
+
+    json!({
+        "startedDateTime": started,
+        "time": duration,
+        "request": {
+            "method": "POST",
+            "url": url,
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": [{ "name": "content-type", "value": "application/json" }],
+            "queryString": query_string,
+            "postData": { "mimeType": "application/json", "text": request_text },
+            "headersSize": -1,
+            "bodySize": request_text.len(),
+        },
+        "response": {
+            "status": status,
+            "statusText": status_text(status),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": [],
+            "content": {
+                "size": response_text.len(),
+                "mimeType": mime_type(&response_text),
+                "text": response_text,
+            },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": response_text.len(),
+        },
+        "cache": {},
+        "timings": { "send": 0, "wait": duration, "receive": 0 },
+        "_requestId": capture.request_id,
+        "_appType": capture.app_type,
+        "_providerId": capture.provider_id,
+        "_model": capture.model,
+    })
+}
+
+/// 生成 HAR 文档，`base_url` 为请求 URL 的前缀（通常为本地代理地址）
+pub fn build_har(
+    captures: &[RequestCapture],
+    base_url: &str,
+    redaction: &LogRedactionConfig,
+) -> Value {
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "CC Switch", "version": env!("CARGO_PKG_VERSION") },
+            "entries": captures
+                .iter()
+                .map(|capture| build_entry(capture, base_url, redaction))
+                .collect::<Vec<_>>(),
+        }
+    })
+}
+
+/// 导出时间范围内（Unix 秒）的采集请求为 HAR 文件，返回导出的条目数
+pub fn export_har(
+    db: &Database,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+    base_url: &str,
+    path: &Path,
+) -> Result<usize, AppError> {
+    let captures = db.get_request_captures_between(start_date, end_date)?;
+    let redaction = db.get_log_redaction_config()?;
+    let har = build_har(&captures, base_url, &redaction);
+    let content =
+        serde_json::to_string_pretty(&har).map_err(|e| AppError::JsonSerialize { source: e })?;
+    std::fs::write(path, content).map_err(|e| AppError::io(path, e))?;
+    Ok(captures.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_redacted_entries() {
+        let capture = RequestCapture {
+            request_id: "r1".into(),
+            app_type: "gemini".into(),
+            endpoint: "/v1beta/models/gemini-pro:generateContent?alt=sse&key=AIzaSecret".into(),
+            provider_id: "p1".into(),
+            model: "gemini-pro".into(),
+            created_at: 0,
+            body: json!({
+                "contents": [{ "parts": [{ "text": "use sk-abcdefghijklmnopqrstuvwxyz" }] }],
+                "api_key": "plain-secret",
+                "max_tokens": 10,
+            })
+            .to_string(),
+            status_code: Some(200),
+            duration_ms: Some(42),
+            response_body: Some(
+                "data: {\"token\":\"Bearer abcdefghijklmnopqrstuvwxyz\"}\n\n".into(),
+            ),
+        };
+
+        let har = build_har(
+            &[capture],
+            "http://127.0.0.1:15721/",
+            &LogRedactionConfig::default(),
+        );
+        let entry = &har["log"]["entries"][0];
+        let url = entry["request"]["url"].as_str().unwrap();
+        assert!(url.starts_with("http://127.0.0.1:15721/v1beta/models/gemini-pro:generateContent?"));
+        assert!(!url.contains("AIzaSecret"));
+        assert_eq!(entry["request"]["queryString"][0]["value"], "sse");
+
+        let request: Value =
+            serde_json::from_str(entry["request"]["postData"]["text"].as_str().unwrap()).unwrap();
+        assert_eq!(request["api_key"], REDACTED);
+        assert_eq!(request["max_tokens"], 10);
+        assert_eq!(request["contents"][0]["parts"][0]["text"], "use [REDACTED]");
+
+        let content = &entry["response"]["content"];
+        assert_eq!(content["mimeType"], "text/event-stream");
+        assert!(!content["text"]
+            .as_str()
+            .unwrap()
+            .contains("abcdefghijklmnop"));
+        assert_eq!(entry["response"]["statusText"], "OK");
+        assert_eq!(entry["time"], 42);
+    }
+}
@@ -0,0 +1,219 @@
+//! 后台上游健康探测
+//!
+//! 启用后定时对每个供应商发起一次轻量探测（复用流式检查的 1 token 请求，不重试），
+//! 记录可达性与延迟：结果写入流式检查日志，并在内存中保留每个供应商的最近一次结果，
+//! 每次探测完成后向前端发送 `health-probe-result` 事件。
+//!
+//! 默认只探测代理会用到的供应商（各应用当前供应商与故障转移队列），避免为闲置供应商产生费用。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::services::stream_check::{
+    HealthStatus, StreamCheckConfig, StreamCheckResult, StreamCheckService,
+};
+
+/// 调度器检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 健康探测配置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthProbeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 探测间隔（分钟）
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u64,
+    /// 仅探测当前供应商与故障转移队列中的供应商
+    #[serde(default = "default_proxy_targets_only")]
+    pub proxy_targets_only: bool,
+}
+
+fn default_interval_minutes() -> u64 {
+    10
+}
+
+fn default_proxy_targets_only() -> bool {
+    true
+}
+
+impl Default for HealthProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: default_interval_minutes(),
+            proxy_targets_only: default_proxy_targets_only(),
+        }
+    }
+}
+
+/// 单个供应商的探测结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthProbeResult {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub reachable: bool,
+    pub result: StreamCheckResult,
+}
+
+type ResultMap = HashMap<String, HealthProbeResult>;
+
+fn results() -> &'static RwLock<ResultMap> {
+    static RESULTS: OnceLock<RwLock<ResultMap>> = OnceLock::new();
+    RESULTS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn result_key(app_type: &str, provider_id: &str) -> String {
+    format!("{app_type}:{provider_id}")
+}
+
+/// 后台健康探测服务
+pub struct HealthProbeService;
+
+impl HealthProbeService {
+    /// 所有供应商的最近一次探测结果
+    pub fn list_results() -> Vec<HealthProbeResult> {
+        let guard = results().read().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<_> = guard.values().cloned().collect();
+        list.sort_by(|a, b| {
+            (a.app_type.as_str(), a.provider_name.as_str())
+                .cmp(&(b.app_type.as_str(), b.provider_name.as_str()))
+        });
+        list
+    }
+
+    /// 启动后台探测任务（配置在每次检查时重新读取，开关与间隔修改后无需重启）
+    pub fn start(db: Arc<Database>, app_handle: tauri::AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut last_round: Option<Instant> = None;
+            loop {
+                let config = db.get_health_probe_config().unwrap_or_default();
+                let interval = Duration::from_secs(config.interval_minutes.max(1) * 60);
+                if config.enabled && last_round.is_none_or(|t| t.elapsed() >= interval) {
+                    last_round = Some(Instant::now());
+                    Self::probe_all(&db, &app_handle, &config).await;
+                }
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+    }
+
+    /// 代理会用到的供应商 ID（当前供应商与故障转移队列）
+    fn probe_targets(db: &Database, app_type: &str) -> HashSet<String> {
+        let mut ids = HashSet::new();
+        if let Ok(Some(current_id)) = db.get_current_provider(app_type) {
+            ids.insert(current_id);
+        }
+        if let Ok(queue) = db.get_failover_queue(app_type) {
+            ids.extend(queue.into_iter().map(|item| item.provider_id));
+        }
+        ids
+    }
+
+    async fn probe_all(db: &Database, app_handle: &tauri::AppHandle, config: &HealthProbeConfig) {
+        // 探测只需确认可达性与延迟，不做重试
+        let check_config = StreamCheckConfig {
+            max_retries: 0,
+            ..db.get_stream_check_config().unwrap_or_default()
+        };
+        let mut probed_keys = Vec::new();
+
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app_str = app_type.as_str();
+            let providers = match db.get_all_providers(app_str) {
+                Ok(p) => p,
+                Err(e) => {
+                    log::warn!("[HealthProbe] 读取 {app_str} 供应商失败: {e}");
+                    continue;
+                }
+            };
+            let targets = config
+                .proxy_targets_only
+                .then(|| Self::probe_targets(db, app_str));
+
+            for (id, provider) in providers {
+                if targets.as_ref().is_some_and(|ids| !ids.contains(&id)) {
+                    continue;
+                }
+
+                let result =
+                    StreamCheckService::check_with_retry(&app_type, &provider, &check_config)
+                        .await
+                        .unwrap_or_else(|e| StreamCheckResult {
+                            status: HealthStatus::Failed,
+                            success: false,
+                            message: e.to_string(),
+                            response_time_ms: None,
+                            http_status: None,
+                            model_used: String::new(),
+                            tested_at: chrono::Utc::now().timestamp(),
+                            retry_count: 0,
+                        });
+                if let Err(e) = db.save_stream_check_log(&id, &provider.name, app_str, &result) {
+                    log::debug!("[HealthProbe] 保存探测结果失败: {e}");
+                }
+                if !result.success {
+                    log::warn!(
+                        "[HealthProbe] {} 探测失败: {}",
+                        provider.name,
+                        result.message
+                    );
+                }
+
+                let probe = HealthProbeResult {
+                    app_type: app_str.to_string(),
+                    provider_id: id.clone(),
+                    provider_name: provider.name.clone(),
+                    reachable: result.success,
+                    result,
+                };
+                let key = result_key(app_str, &id);
+                results()
+                    .write()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(key.clone(), probe.clone());
+                probed_keys.push(key);
+                let _ = app_handle.emit("health-probe-result", &probe);
+            }
+        }
+
+        // 清理已删除或不再探测的供应商
+        results()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|k, _| probed_keys.contains(k));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+
+    #[test]
+    fn test_config_defaults_and_roundtrip() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        assert_eq!(db.get_health_probe_config()?, HealthProbeConfig::default());
+
+        let config = HealthProbeConfig {
+            enabled: true,
+            interval_minutes: 3,
+            proxy_targets_only: false,
+        };
+        db.save_health_probe_config(&config)?;
+        assert_eq!(db.get_health_probe_config()?, config);
+
+        let partial: HealthProbeConfig = serde_json::from_str(r#"{"enabled":true}"#).unwrap();
+        assert_eq!(partial.interval_minutes, 10);
+        assert!(partial.proxy_targets_only);
+        Ok(())
+    }
+}
@@ -1,16 +1,31 @@
 pub mod config;
+pub mod diagnostics;
 pub mod env_checker;
 pub mod env_manager;
+pub mod failover_drill;
+pub mod har_export;
+pub mod health_probe;
 pub mod mcp;
+pub mod onboarding;
+pub mod profile;
 pub mod prompt;
 pub mod provider;
 pub mod proxy;
+pub mod quota_calendar;
+pub mod response_compare;
+pub mod settings_history;
 pub mod skill;
 pub mod speedtest;
+pub mod status_watcher;
 pub mod stream_check;
+pub mod telemetry;
+pub mod usage_report;
 pub mod usage_stats;
+pub mod wake_watcher;
 
 pub use config::ConfigService;
+pub use failover_drill::{FailoverDrillReport, FailoverDrillService};
+pub use health_probe::{HealthProbeConfig, HealthProbeResult, HealthProbeService};
 pub use mcp::McpService;
 pub use prompt::PromptService;
 pub use provider::{ProviderService, ProviderSortUpdate};
@@ -18,8 +33,11 @@ pub use proxy::ProxyService;
 #[allow(unused_imports)]
 pub use skill::{DiscoverableSkill, Skill, SkillRepo, SkillService};
 pub use speedtest::{EndpointLatency, SpeedtestService};
+pub use status_watcher::{StatusIncident, StatusWatcherService};
+pub use telemetry::TelemetryService;
 #[allow(unused_imports)]
 pub use usage_stats::{
     DailyStats, LogFilters, ModelStats, PaginatedLogs, ProviderLimitStatus, ProviderStats,
-    RequestLogDetail, UsageSummary,
+    RequestLogDetail, UsageSeriesPoint, UsageSeriesQuery, UsageSummary,
 };
+pub use wake_watcher::WakeWatcherService;
@@ -0,0 +1,820 @@
+//! 供应商接入向导
+//!
+//! 新增供应商时按步骤引导用户完成配置，每一步返回结构化结果供前端渲染：
+//! 1. 地址规范化：补全协议、去除误填的接口路径
+//! 2. 认证探测：通过模型列表接口验证密钥
+//! 3. 能力探测：模型列表、流式响应、count_tokens 是否可用
+//! 4. 模型发现：拉取供应商提供的模型列表
+//! 5. 模型映射建议：根据模型列表推荐各档位模型
+//! 6. 端到端测试：用最终配置对每个映射模型做一次流式检查
+//!
+//! 各步骤相互独立、无状态，前端可按需重试任一步骤。
+
+use reqwest::{RequestBuilder, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::services::stream_check::{StreamCheckConfig, StreamCheckResult, StreamCheckService};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 需要从地址末尾去除的接口路径（用户常把完整接口地址填入 base URL）
+const ENDPOINT_SUFFIXES: &[&str] = &["/chat/completions", "/responses", "/messages", "/models"];
+
+/// Claude 模型映射的环境变量与对应的档位关键字
+const CLAUDE_TIERS: &[(&str, &str)] = &[
+    ("ANTHROPIC_DEFAULT_HAIKU_MODEL", "haiku"),
+    ("ANTHROPIC_DEFAULT_SONNET_MODEL", "sonnet"),
+    ("ANTHROPIC_DEFAULT_OPUS_MODEL", "opus"),
+];
+
+/// 步骤结果状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Passed,
+    Warning,
+    Failed,
+}
+
+/// 步骤 1：地址规范化结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UrlCheck {
+    pub status: StepStatus,
+    pub message: String,
+    pub input: String,
+    /// 规范化后的地址（失败时为空）
+    pub normalized: String,
+    /// 做出的修改（供前端提示）
+    pub changes: Vec<String>,
+}
+
+/// 步骤 2：认证探测结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthProbe {
+    pub status: StepStatus,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    pub response_time_ms: u64,
+}
+
+/// 步骤 3：能力探测结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapabilityProbe {
+    pub status: StepStatus,
+    pub message: String,
+    /// 是否提供模型列表接口
+    pub models_endpoint: bool,
+    /// 流式响应是否可用
+    pub streaming: bool,
+    /// count_tokens 接口是否可用（仅 Claude）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count_tokens: Option<bool>,
+    pub model_used: String,
+    pub notes: Vec<String>,
+}
+
+/// 发现的模型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveredModel {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+}
+
+/// 步骤 4：模型发现结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDiscovery {
+    pub status: StepStatus,
+    pub message: String,
+    pub models: Vec<DiscoveredModel>,
+}
+
+/// 步骤 5：模型映射建议
+///
+/// `mapping` 的键为供应商配置中的字段名（Claude 为 `ANTHROPIC_*` 环境变量，
+/// Codex 为 `model`，Gemini 为 `GEMINI_MODEL`），前端可直接写入配置
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedModelMap {
+    pub status: StepStatus,
+    pub message: String,
+    pub mapping: BTreeMap<String, String>,
+    pub notes: Vec<String>,
+}
+
+/// 端到端测试中单个模型的检查结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCheck {
+    pub model: String,
+    /// 使用该模型的映射字段
+    pub used_by: Vec<String>,
+    pub result: StreamCheckResult,
+}
+
+/// 步骤 6：端到端测试结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EndToEndTest {
+    pub status: StepStatus,
+    pub message: String,
+    pub checks: Vec<ModelCheck>,
+    /// 测试所用的供应商配置（可直接作为新供应商的 settingsConfig 保存）
+    pub settings_config: Value,
+}
+
+/// 供应商接入向导服务
+pub struct ProviderOnboardingService;
+
+impl ProviderOnboardingService {
+    /// 步骤 1：规范化供应商地址
+    pub fn normalize_url(app_type: &AppType, raw: &str) -> UrlCheck {
+        let input = raw.to_string();
+        let mut changes = Vec::new();
+        let fail = |message: String| UrlCheck {
+            status: StepStatus::Failed,
+            message,
+            input: input.clone(),
+            normalized: String::new(),
+            changes: Vec::new(),
+        };
+
+        let mut value = raw.trim().to_string();
+        if value.is_empty() {
+            return fail("地址不能为空".to_string());
+        }
+        if value != raw {
+            changes.push("去除首尾空白".to_string());
+        }
+        if !value.contains("://") {
+            value = format!("https://{value}");
+            changes.push("补全协议 https://".to_string());
+        }
+
+        let mut url = match Url::parse(&value) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") && url.host().is_some() => url,
+            Ok(url) => return fail(format!("不支持的地址协议: {}", url.scheme())),
+            Err(e) => return fail(format!("无效的地址: {e}")),
+        };
+        if url.query().is_some() || url.fragment().is_some() {
+            url.set_query(None);
+            url.set_fragment(None);
+            changes.push("去除查询参数".to_string());
+        }
+
+        let mut path = url.path().trim_end_matches('/').to_string();
+        for suffix in ENDPOINT_SUFFIXES {
+            if let Some(stripped) = path.strip_suffix(suffix) {
+                changes.push(format!("去除接口路径 {suffix}"));
+                path = stripped.to_string();
+                break;
+            }
+        }
+        match app_type {
+            // 代理与 Claude CLI 都会追加 /v1/messages
+            AppType::Claude => {
+                if let Some(stripped) = path.strip_suffix("/v1") {
+                    changes.push("去除版本路径 /v1".to_string());
+                    path = stripped.to_string();
+                }
+            }
+            // Codex CLI 直接在 base_url 后追加 /responses，约定 base_url 以 /v1 结尾
+            AppType::Codex => {
+                if path.is_empty() {
+                    changes.push("补全版本路径 /v1".to_string());
+                    path = "/v1".to_string();
+                }
+            }
+            // Gemini CLI 会追加 /v1beta/models/...
+            AppType::Gemini => {
+                for version in ["/v1beta", "/v1"] {
+                    if let Some(stripped) = path.strip_suffix(version) {
+                        changes.push(format!("去除版本路径 {version}"));
+                        path = stripped.to_string();
+                        break;
+                    }
+                }
+            }
+        }
+
+        let normalized = format!(
+            "{}{}",
+            url.origin().ascii_serialization(),
+            path.trim_end_matches('/')
+        );
+        let insecure = url.scheme() == "http"
+            && !matches!(
+                url.host_str(),
+                Some("localhost" | "127.0.0.1" | "::1" | "[::1]")
+            );
+        let (status, message) = if insecure {
+            (
+                StepStatus::Warning,
+                "使用明文 HTTP，密钥可能在传输中泄露".to_string(),
+            )
+        } else if changes.is_empty() {
+            (StepStatus::Passed, "地址格式正确".to_string())
+        } else {
+            (StepStatus::Passed, "已规范化地址".to_string())
+        };
+
+        UrlCheck {
+            status,
+            message,
+            input,
+            normalized,
+            changes,
+        }
+    }
+
+    /// 步骤 2：通过模型列表接口探测密钥是否有效
+    pub async fn probe_auth(app_type: &AppType, base_url: &str, api_key: &str) -> AuthProbe {
+        let start = std::time::Instant::now();
+        let result = Self::get_models(app_type, base_url, api_key).await;
+        let response_time_ms = start.elapsed().as_millis() as u64;
+
+        let (status, message, http_status) = match result {
+            Ok((code, _)) if code.is_success() => {
+                (StepStatus::Passed, "密钥有效".to_string(), Some(code))
+            }
+            Ok((code, _)) if matches!(code.as_u16(), 401 | 403) => (
+                StepStatus::Failed,
+                "密钥无效或无访问权限".to_string(),
+                Some(code),
+            ),
+            Ok((code, _)) if matches!(code.as_u16(), 404 | 405) => (
+                StepStatus::Warning,
+                "供应商未提供模型列表接口，无法单独验证密钥，将在端到端测试中验证".to_string(),
+                Some(code),
+            ),
+            Ok((code, body)) => (
+                StepStatus::Warning,
+                format!("无法确认密钥状态: HTTP {code}: {}", truncate(&body, 200)),
+                Some(code),
+            ),
+            Err(e) => (StepStatus::Failed, e.to_string(), None),
+        };
+
+        AuthProbe {
+            status,
+            message,
+            http_status: http_status.map(|c| c.as_u16()),
+            response_time_ms,
+        }
+    }
+
+    /// 步骤 3：探测供应商能力
+    ///
+    /// `model` 为空时使用流式检查配置中的默认测试模型
+    pub async fn probe_capabilities(
+        app_type: &AppType,
+        base_url: &str,
+        api_key: &str,
+        model: Option<&str>,
+        check_config: &StreamCheckConfig,
+    ) -> CapabilityProbe {
+        let mut notes = Vec::new();
+        let model = model
+            .map(str::to_string)
+            .filter(|m| !m.trim().is_empty())
+            .unwrap_or_else(|| default_test_model(app_type, check_config));
+
+        let models_endpoint = matches!(
+            Self::get_models(app_type, base_url, api_key).await,
+            Ok((code, _)) if code.is_success()
+        );
+        if !models_endpoint {
+            notes.push("模型列表接口不可用，需要手动填写模型名".to_string());
+        }
+
+        // 只做一次检查，不重试，以便如实反映能力
+        let single_check = StreamCheckConfig {
+            max_retries: 0,
+            ..check_config.clone()
+        };
+        let mapping = BTreeMap::from([(model_field(app_type).to_string(), model.clone())]);
+        let provider = draft_provider(app_type, base_url, api_key, &mapping);
+        let stream_result =
+            StreamCheckService::check_with_retry(app_type, &provider, &single_check).await;
+        let streaming = match &stream_result {
+            Ok(r) if r.success => true,
+            Ok(r) => {
+                notes.push(format!("流式请求失败: {}", r.message));
+                false
+            }
+            Err(e) => {
+                notes.push(format!("流式请求失败: {e}"));
+                false
+            }
+        };
+
+        let count_tokens = match app_type {
+            AppType::Claude => {
+                let supported = Self::probe_count_tokens(base_url, api_key, &model).await;
+                if !supported {
+                    notes.push("count_tokens 不可用，代理将使用本地估算".to_string());
+                }
+                Some(supported)
+            }
+            _ => None,
+        };
+
+        let (status, message) = if streaming {
+            if notes.is_empty() {
+                (StepStatus::Passed, "全部能力可用".to_string())
+            } else {
+                (StepStatus::Warning, "部分能力不可用".to_string())
+            }
+        } else {
+            (StepStatus::Failed, "流式请求不可用".to_string())
+        };
+
+        CapabilityProbe {
+            status,
+            message,
+            models_endpoint,
+            streaming,
+            count_tokens,
+            model_used: model,
+            notes,
+        }
+    }
+
+    /// 步骤 4：拉取模型列表
+    pub async fn discover_models(
+        app_type: &AppType,
+        base_url: &str,
+        api_key: &str,
+    ) -> ModelDiscovery {
+        let (status, message, models) = match Self::get_models(app_type, base_url, api_key).await {
+            Ok((code, body)) if code.is_success() => match serde_json::from_str::<Value>(&body) {
+                Ok(json) => {
+                    let models = parse_models(&json);
+                    if models.is_empty() {
+                        (StepStatus::Warning, "模型列表为空".to_string(), models)
+                    } else {
+                        let message = format!("发现 {} 个模型", models.len());
+                        (StepStatus::Passed, message, models)
+                    }
+                }
+                Err(e) => (
+                    StepStatus::Failed,
+                    format!("无法解析模型列表: {e}"),
+                    Vec::new(),
+                ),
+            },
+            Ok((code, body)) => (
+                StepStatus::Failed,
+                format!("HTTP {code}: {}", truncate(&body, 200)),
+                Vec::new(),
+            ),
+            Err(e) => (StepStatus::Failed, e.to_string(), Vec::new()),
+        };
+
+        ModelDiscovery {
+            status,
+            message,
+            models,
+        }
+    }
+
+    /// 步骤 5：根据模型列表推荐模型映射
+    pub fn suggest_model_map(app_type: &AppType, models: &[String]) -> SuggestedModelMap {
+        let mut mapping = BTreeMap::new();
+        let mut notes = Vec::new();
+        let models: Vec<&str> = models
+            .iter()
+            .map(|m| m.trim())
+            .filter(|m| !m.is_empty() && !is_non_chat_model(m))
+            .collect();
+
+        let Some(first) = models.first().copied() else {
+            return SuggestedModelMap {
+                status: StepStatus::Failed,
+                message: "没有可用于推荐的模型".to_string(),
+                mapping,
+                notes,
+            };
+        };
+
+        match app_type {
+            AppType::Claude => {
+                for (field, tier) in CLAUDE_TIERS {
+                    match latest_matching(&models, tier) {
+                        Some(model) => {
+                            mapping.insert(field.to_string(), model.to_string());
+                        }
+                        None => notes.push(format!("未找到 {tier} 档位模型")),
+                    }
+                }
+                let default = mapping
+                    .get("ANTHROPIC_DEFAULT_SONNET_MODEL")
+                    .cloned()
+                    .unwrap_or_else(|| first.to_string());
+                // 非 Claude 模型的中转（如 GLM、Kimi）统一使用默认模型
+                for (field, _) in CLAUDE_TIERS {
+                    mapping
+                        .entry(field.to_string())
+                        .or_insert_with(|| default.clone());
+                }
+                mapping.insert("ANTHROPIC_MODEL".to_string(), default);
+            }
+            AppType::Codex => {
+                let model = latest_matching(&models, "codex").unwrap_or(first);
+                mapping.insert(model_field(app_type).to_string(), model.to_string());
+            }
+            AppType::Gemini => {
+                let model = latest_matching(&models, "pro").unwrap_or(first);
+                mapping.insert(model_field(app_type).to_string(), model.to_string());
+            }
+        }
+
+        let status = if notes.is_empty() {
+            StepStatus::Passed
+        } else {
+            StepStatus::Warning
+        };
+        SuggestedModelMap {
+            status,
+            message: format!("已根据 {} 个模型生成建议", models.len()),
+            mapping,
+            notes,
+        }
+    }
+
+    /// 步骤 6：使用最终配置对每个映射模型做流式检查
+    pub async fn end_to_end_test(
+        app_type: &AppType,
+        base_url: &str,
+        api_key: &str,
+        mapping: &BTreeMap<String, String>,
+        check_config: &StreamCheckConfig,
+    ) -> EndToEndTest {
+        let settings_config = draft_settings_config(app_type, base_url, api_key, mapping);
+
+        // 按模型去重，同一模型只检查一次
+        let mut models: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (field, model) in mapping.iter().filter(|(_, m)| !m.trim().is_empty()) {
+            models
+                .entry(model.trim().to_string())
+                .or_default()
+                .push(field.clone());
+        }
+        if models.is_empty() {
+            models.insert(default_test_model(app_type, check_config), Vec::new());
+        }
+
+        let mut checks = Vec::new();
+        for (model, used_by) in models {
+            let single = BTreeMap::from([(model_field(app_type).to_string(), model.clone())]);
+            let provider = draft_provider(app_type, base_url, api_key, &single);
+            let result = StreamCheckService::check_with_retry(app_type, &provider, check_config)
+                .await
+                .unwrap_or_else(|e| StreamCheckResult {
+                    status: crate::services::stream_check::HealthStatus::Failed,
+                    success: false,
+                    message: e.to_string(),
+                    response_time_ms: None,
+                    http_status: None,
+                    model_used: model.clone(),
+                    tested_at: chrono::Utc::now().timestamp(),
+                    retry_count: check_config.max_retries,
+                });
+            checks.push(ModelCheck {
+                model,
+                used_by,
+                result,
+            });
+        }
+
+        let failed = checks.iter().filter(|c| !c.result.success).count();
+        let (status, message) = match failed {
+            0 => (StepStatus::Passed, "所有模型检查通过".to_string()),
+            n if n == checks.len() => (StepStatus::Failed, "所有模型检查失败".to_string()),
+            n => (StepStatus::Warning, format!("{n} 个模型检查失败")),
+        };
+
+        EndToEndTest {
+            status,
+            message,
+            checks,
+            settings_config,
+        }
+    }
+
+    /// 请求模型列表接口，返回状态码与响应体
+    async fn get_models(
+        app_type: &AppType,
+        base_url: &str,
+        api_key: &str,
+    ) -> Result<(StatusCode, String), AppError> {
+        let client = crate::proxy::http_client::get();
+        let request = client
+            .get(models_url(app_type, base_url))
+            .timeout(PROBE_TIMEOUT);
+        let response = with_auth(app_type, request, api_key)
+            .send()
+            .await
+            .map_err(map_request_error)?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Ok((status, body))
+    }
+
+    /// 探测 Claude count_tokens 接口
+    async fn probe_count_tokens(base_url: &str, api_key: &str, model: &str) -> bool {
+        let client = crate::proxy::http_client::get();
+        let url = format!(
+            "{}/v1/messages/count_tokens",
+            base_url.trim_end_matches('/')
+        );
+        let request = client.post(url).timeout(PROBE_TIMEOUT).json(&json!({
+            "model": model,
+            "messages": [{ "role": "user", "content": "hi" }]
+        }));
+        match with_auth(&AppType::Claude, request, api_key).send().await {
+            Ok(resp) if resp.status().is_success() => resp
+                .json::<Value>()
+                .await
+                .is_ok_and(|v| v.get("input_tokens").is_some()),
+            _ => false,
+        }
+    }
+}
+
+/// 模型列表接口地址
+fn models_url(app_type: &AppType, base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    match app_type {
+        AppType::Gemini => format!("{base}/v1beta/models"),
+        _ if base.ends_with("/v1") => format!("{base}/models"),
+        _ => format!("{base}/v1/models"),
+    }
+}
+
+/// 按应用类型添加认证头
+pub(crate) fn with_auth(
+    app_type: &AppType,
+    request: RequestBuilder,
+    api_key: &str,
+) -> RequestBuilder {
+    match app_type {
+        // 与流式检查一致，同时发送两种认证头以兼容各类中转
+        AppType::Claude => request
+            .header("x-api-key", api_key)
+            .header("authorization", format!("Bearer {api_key}"))
+            .header("anthropic-version", "2023-06-01"),
+        AppType::Codex => request.header("authorization", format!("Bearer {api_key}")),
+        AppType::Gemini => request.header("x-goog-api-key", api_key),
+    }
+}
+
+pub(crate) fn map_request_error(e: reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::Message("请求超时".to_string())
+    } else if e.is_connect() {
+        AppError::Message(format!("无法连接: {e}"))
+    } else {
+        AppError::Message(e.to_string())
+    }
+}
+
+/// 解析模型列表（OpenAI / Anthropic 的 `data[].id`，Gemini 的 `models[].name`）
+fn parse_models(json: &Value) -> Vec<DiscoveredModel> {
+    let mut models: Vec<DiscoveredModel> = if let Some(data) = json.get("data") {
+        data.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| {
+                Some(DiscoveredModel {
+                    id: m.get("id")?.as_str()?.to_string(),
+                    display_name: m
+                        .get("display_name")
+                        .and_then(|n| n.as_str())
+                        .map(String::from),
+                })
+            })
+            .collect()
+    } else {
+        json.get("models")
+            .and_then(|m| m.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|m| {
+                let name = m.get("name")?.as_str()?;
+                Some(DiscoveredModel {
+                    id: name.strip_prefix("models/").unwrap_or(name).to_string(),
+                    display_name: m
+                        .get("displayName")
+                        .and_then(|n| n.as_str())
+                        .map(String::from),
+                })
+            })
+            .collect()
+    };
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup_by(|a, b| a.id == b.id);
+    models
+}
+
+/// 嵌入、语音、图像等非对话模型不参与推荐
+fn is_non_chat_model(model: &str) -> bool {
+    let lower = model.to_ascii_lowercase();
+    [
+        "embed",
+        "tts",
+        "whisper",
+        "dall-e",
+        "image",
+        "moderation",
+        "audio",
+    ]
+    .iter()
+    .any(|k| lower.contains(k))
+}
+
+/// 名称包含关键字的模型中按名称排序取最后一个（通常为最新版本）
+fn latest_matching<'a>(models: &[&'a str], keyword: &str) -> Option<&'a str> {
+    models
+        .iter()
+        .copied()
+        .filter(|m| m.to_ascii_lowercase().contains(keyword))
+        .max()
+}
+
+/// 各应用类型配置中的主模型字段
+fn model_field(app_type: &AppType) -> &'static str {
+    match app_type {
+        AppType::Claude => "ANTHROPIC_MODEL",
+        AppType::Codex => "model",
+        AppType::Gemini => "GEMINI_MODEL",
+    }
+}
+
+fn default_test_model(app_type: &AppType, config: &StreamCheckConfig) -> String {
+    match app_type {
+        AppType::Claude => config.claude_model.clone(),
+        AppType::Codex => config.codex_model.clone(),
+        AppType::Gemini => config.gemini_model.clone(),
+    }
+}
+
+/// 构造新供应商的 settingsConfig
+fn draft_settings_config(
+    app_type: &AppType,
+    base_url: &str,
+    api_key: &str,
+    mapping: &BTreeMap<String, String>,
+) -> Value {
+    match app_type {
+        AppType::Claude => {
+            let mut env = serde_json::Map::new();
+            env.insert("ANTHROPIC_BASE_URL".into(), json!(base_url));
+            env.insert("ANTHROPIC_AUTH_TOKEN".into(), json!(api_key));
+            for (field, model) in mapping {
+                env.insert(field.clone(), json!(model));
+            }
+            json!({ "env": env })
+        }
+        AppType::Codex => {
+            // model 必须位于首行，与流式检查读取模型的方式一致
+            let model = mapping.get("model").map(String::as_str).unwrap_or_default();
+            let config = format!(
+                "model = \"{model}\"\nmodel_provider = \"custom\"\n\n[model_providers.custom]\nname = \"custom\"\nbase_url = \"{base_url}\"\nwire_api = \"responses\"\n"
+            );
+            json!({
+                "auth": { "OPENAI_API_KEY": api_key },
+                "config": config
+            })
+        }
+        AppType::Gemini => {
+            let mut env = serde_json::Map::new();
+            env.insert("GOOGLE_GEMINI_BASE_URL".into(), json!(base_url));
+            env.insert("GEMINI_API_KEY".into(), json!(api_key));
+            for (field, model) in mapping {
+                env.insert(field.clone(), json!(model));
+            }
+            json!({ "env": env })
+        }
+    }
+}
+
+fn draft_provider(
+    app_type: &AppType,
+    base_url: &str,
+    api_key: &str,
+    mapping: &BTreeMap<String, String>,
+) -> Provider {
+    Provider::with_id(
+        "onboarding-draft".to_string(),
+        "Onboarding".to_string(),
+        draft_settings_config(app_type, base_url, api_key, mapping),
+        None,
+    )
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::providers::get_adapter;
+
+    #[test]
+    fn normalizes_pasted_endpoint_urls() {
+        let check = ProviderOnboardingService::normalize_url(
+            &AppType::Claude,
+            " api.example.com/v1/messages?beta=true ",
+        );
+        assert_eq!(check.status, StepStatus::Passed);
+        assert_eq!(check.normalized, "https://api.example.com");
+        assert_eq!(check.changes.len(), 5);
+
+        let codex =
+            ProviderOnboardingService::normalize_url(&AppType::Codex, "https://relay.example.com/");
+        assert_eq!(codex.normalized, "https://relay.example.com/v1");
+
+        let gemini = ProviderOnboardingService::normalize_url(
+            &AppType::Gemini,
+            "http://gw.example.com/proxy/v1beta/models",
+        );
+        assert_eq!(gemini.status, StepStatus::Warning);
+        assert_eq!(gemini.normalized, "http://gw.example.com/proxy");
+
+        let invalid = ProviderOnboardingService::normalize_url(&AppType::Claude, "ftp://x");
+        assert_eq!(invalid.status, StepStatus::Failed);
+    }
+
+    #[test]
+    fn parses_openai_and_gemini_model_lists() {
+        let openai = json!({"data": [{"id": "b"}, {"id": "a", "display_name": "A"}, {"id": "b"}]});
+        let ids: Vec<_> = parse_models(&openai).into_iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        let gemini = json!({"models": [{"name": "models/gemini-2.5-pro", "displayName": "Pro"}]});
+        assert_eq!(parse_models(&gemini)[0].id, "gemini-2.5-pro");
+    }
+
+    #[test]
+    fn suggests_claude_tiers_with_fallback() {
+        let models = [
+            "claude-3-5-haiku-20241022",
+            "claude-sonnet-4-20250514",
+            "claude-sonnet-4-5-20250929",
+            "text-embedding-3-small",
+        ]
+        .map(String::from);
+        let map = ProviderOnboardingService::suggest_model_map(&AppType::Claude, &models);
+        assert_eq!(map.status, StepStatus::Warning);
+        assert_eq!(
+            map.mapping["ANTHROPIC_DEFAULT_SONNET_MODEL"],
+            "claude-sonnet-4-5-20250929"
+        );
+        assert_eq!(
+            map.mapping["ANTHROPIC_DEFAULT_HAIKU_MODEL"],
+            "claude-3-5-haiku-20241022"
+        );
+        // 缺少 opus 时回退到默认模型
+        assert_eq!(
+            map.mapping["ANTHROPIC_DEFAULT_OPUS_MODEL"],
+            map.mapping["ANTHROPIC_MODEL"]
+        );
+
+        let empty = ProviderOnboardingService::suggest_model_map(&AppType::Codex, &[]);
+        assert_eq!(empty.status, StepStatus::Failed);
+    }
+
+    #[test]
+    fn draft_config_is_readable_by_adapters() {
+        let mapping = BTreeMap::from([("model".to_string(), "gpt-5-codex".to_string())]);
+        let provider = draft_provider(
+            &AppType::Codex,
+            "https://relay.example.com/v1",
+            "sk-1",
+            &mapping,
+        );
+        let adapter = get_adapter(&AppType::Codex);
+        assert_eq!(
+            adapter.extract_base_url(&provider).unwrap(),
+            "https://relay.example.com/v1"
+        );
+        assert_eq!(adapter.extract_auth(&provider).unwrap().api_key, "sk-1");
+    }
+}
@@ -0,0 +1,150 @@
+//! 配置档案（Profile）
+//!
+//! 一个档案为每个应用打包：当前供应商、故障转移队列、是否启用代理接管与自动故障转移，
+//! 例如「工作」「个人」「离线中转」。应用档案时一次性切换全部设置，而不必逐个调整供应商。
+//!
+//! 档案中未设置的项保持不变；应用前会先校验全部供应商是否存在，校验失败时不做任何修改。
+//! 档案列表与当前档案 ID 存储在 settings 表（`profiles` / `active_profile_id`）中。
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::services::ProviderService;
+use crate::store::AppState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// 档案中单个应用的设置
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileAppSettings {
+    /// 当前供应商
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    /// 故障转移队列中的供应商（整体替换原队列）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failover_queue: Option<Vec<String>>,
+    /// 是否启用代理接管
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_enabled: Option<bool>,
+    /// 是否启用自动故障转移
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_failover_enabled: Option<bool>,
+}
+
+/// 配置档案
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// app_type → 设置
+    #[serde(default)]
+    pub apps: BTreeMap<String, ProfileAppSettings>,
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+pub struct ProfileService;
+
+impl ProfileService {
+    /// 以当前各应用的供应商与代理设置创建档案（不保存）
+    pub async fn capture(state: &AppState, name: &str) -> Result<Profile, AppError> {
+        let mut apps = BTreeMap::new();
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app = app_type.as_str();
+            let proxy = state.db.get_proxy_config_for_app(app).await?;
+            let queue = state
+                .db
+                .get_failover_queue(app)?
+                .into_iter()
+                .map(|item| item.provider_id)
+                .collect();
+            apps.insert(
+                app.to_string(),
+                ProfileAppSettings {
+                    provider_id: state.db.get_current_provider(app)?,
+                    failover_queue: Some(queue),
+                    proxy_enabled: Some(proxy.enabled),
+                    auto_failover_enabled: Some(proxy.auto_failover_enabled),
+                },
+            );
+        }
+        Ok(Profile {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            apps,
+            created_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// 校验档案引用的应用与供应商均存在
+    pub fn validate(state: &AppState, profile: &Profile) -> Result<(), AppError> {
+        for (app, settings) in &profile.apps {
+            AppType::from_str(app)
+                .map_err(|_| AppError::Message(format!("无效的应用类型: {app}")))?;
+            let providers = state.db.get_all_providers(app)?;
+            let referenced = settings
+                .provider_id
+                .iter()
+                .chain(settings.failover_queue.iter().flatten());
+            for id in referenced {
+                if !providers.contains_key(id) {
+                    return Err(AppError::Message(format!(
+                        "档案 {} 引用的 {app} 供应商 {id} 不存在",
+                        profile.name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 应用档案：依次切换代理接管、自动故障转移、故障转移队列与当前供应商
+    pub async fn apply(state: &AppState, id: &str) -> Result<Profile, AppError> {
+        let profile = state
+            .db
+            .get_profiles()?
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| AppError::Message(format!("档案 {id} 不存在")))?;
+        Self::validate(state, &profile)?;
+
+        for (app, settings) in &profile.apps {
+            let app_type = AppType::from_str(app)
+                .map_err(|_| AppError::Message(format!("无效的应用类型: {app}")))?;
+
+            // 先切换接管状态，后续切换供应商时才会走正确的（热切换 / 写 Live）路径
+            if let Some(enabled) = settings.proxy_enabled {
+                state
+                    .proxy_service
+                    .set_takeover_for_app(app, enabled)
+                    .await
+                    .map_err(AppError::Message)?;
+            }
+            if let Some(enabled) = settings.auto_failover_enabled {
+                let mut config = state.db.get_proxy_config_for_app(app).await?;
+                if config.auto_failover_enabled != enabled {
+                    config.auto_failover_enabled = enabled;
+                    state.db.update_proxy_config_for_app(config).await?;
+                }
+            }
+            if let Some(queue) = &settings.failover_queue {
+                state.db.clear_failover_queue(app)?;
+                for provider_id in queue {
+                    state.db.add_to_failover_queue(app, provider_id)?;
+                }
+            }
+            if let Some(provider_id) = &settings.provider_id {
+                if state.db.get_current_provider(app)?.as_deref() != Some(provider_id.as_str()) {
+                    ProviderService::switch(state, app_type, provider_id)?;
+                }
+            }
+        }
+
+        state.db.set_active_profile_id(Some(&profile.id))?;
+        log::info!("已应用配置档案: {}", profile.name);
+        Ok(profile)
+    }
+}
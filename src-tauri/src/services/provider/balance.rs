@@ -0,0 +1,388 @@
+//! Relay balance query
+//!
+//! Fetches remaining credit from common relay dashboards without a usage script,
+//! and flags providers whose balance is below the configured warning threshold.
+
+use serde_json::Value;
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::{BalanceConfig, BalanceKind, BalanceResult, Provider};
+use crate::settings;
+use crate::store::AppState;
+
+use super::usage::{extract_api_key_from_provider, extract_base_url_from_provider};
+
+const BALANCE_TIMEOUT_SECS: u64 = 10;
+
+/// Parsed balance value with unit
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Balance {
+    pub amount: f64,
+    pub unit: Option<String>,
+}
+
+/// Parse a number that may be encoded as a JSON string (e.g. DeepSeek returns "12.34")
+fn as_number(value: &Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn missing_field(field: &str) -> AppError {
+    AppError::localized(
+        "provider.balance.field_missing",
+        format!("余额响应缺少字段: {field}"),
+        format!("Balance response is missing field: {field}"),
+    )
+}
+
+/// Extract the balance from a response body according to the endpoint kind
+///
+/// `openai_billing` requires both the subscription and usage responses; `usage` is
+/// ignored for all other kinds.
+pub(crate) fn parse_balance(
+    kind: BalanceKind,
+    primary: &Value,
+    usage: Option<&Value>,
+    balance_path: Option<&str>,
+) -> Result<Balance, AppError> {
+    match kind {
+        BalanceKind::OpenaiBilling => {
+            let limit = primary
+                .get("hard_limit_usd")
+                .and_then(as_number)
+                .ok_or_else(|| missing_field("hard_limit_usd"))?;
+            // total_usage 以美分计
+            let used = usage
+                .and_then(|u| u.get("total_usage"))
+                .and_then(as_number)
+                .ok_or_else(|| missing_field("total_usage"))?
+                / 100.0;
+            Ok(Balance {
+                amount: limit - used,
+                unit: Some("USD".to_string()),
+            })
+        }
+        BalanceKind::Openrouter => {
+            let data = primary.get("data").ok_or_else(|| missing_field("data"))?;
+            let total = data
+                .get("total_credits")
+                .and_then(as_number)
+                .ok_or_else(|| missing_field("data.total_credits"))?;
+            let used = data.get("total_usage").and_then(as_number).unwrap_or(0.0);
+            Ok(Balance {
+                amount: total - used,
+                unit: Some("USD".to_string()),
+            })
+        }
+        BalanceKind::Deepseek => {
+            let info = primary
+                .get("balance_infos")
+                .and_then(|v| v.as_array())
+                .and_then(|arr| arr.first())
+                .ok_or_else(|| missing_field("balance_infos"))?;
+            let amount = info
+                .get("total_balance")
+                .and_then(as_number)
+                .ok_or_else(|| missing_field("balance_infos[0].total_balance"))?;
+            Ok(Balance {
+                amount,
+                unit: info
+                    .get("currency")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+            })
+        }
+        BalanceKind::Siliconflow => {
+            let amount = primary
+                .pointer("/data/totalBalance")
+                .and_then(as_number)
+                .ok_or_else(|| missing_field("data.totalBalance"))?;
+            Ok(Balance {
+                amount,
+                unit: Some("CNY".to_string()),
+            })
+        }
+        BalanceKind::Custom => {
+            let path = balance_path.filter(|p| !p.is_empty()).ok_or_else(|| {
+                AppError::localized(
+                    "provider.balance.path_missing",
+                    "自定义余额接口需要配置字段路径",
+                    "Custom balance endpoint requires a field path",
+                )
+            })?;
+            let amount = primary
+                .pointer(path)
+                .and_then(as_number)
+                .ok_or_else(|| missing_field(path))?;
+            Ok(Balance { amount, unit: None })
+        }
+    }
+}
+
+/// Resolve the request URLs for a balance kind (second URL is the usage endpoint)
+fn resolve_urls(kind: BalanceKind, base: &str) -> (String, Option<String>) {
+    let base = base.trim_end_matches('/');
+    match kind {
+        BalanceKind::OpenaiBilling => {
+            let root = base.trim_end_matches("/v1");
+            let today = chrono::Local::now().date_naive();
+            let start = today - chrono::Duration::days(99);
+            (
+                format!("{root}/v1/dashboard/billing/subscription"),
+                Some(format!(
+                    "{root}/v1/dashboard/billing/usage?start_date={start}&end_date={}",
+                    today + chrono::Duration::days(1)
+                )),
+            )
+        }
+        BalanceKind::Openrouter => {
+            let root = if base.is_empty() {
+                "https://openrouter.ai/api"
+            } else {
+                base.trim_end_matches("/v1")
+            };
+            (format!("{root}/v1/credits"), None)
+        }
+        BalanceKind::Deepseek => {
+            let root = if base.is_empty() {
+                "https://api.deepseek.com"
+            } else {
+                base.trim_end_matches("/v1")
+            };
+            (format!("{root}/user/balance"), None)
+        }
+        BalanceKind::Siliconflow => {
+            let root = if base.is_empty() {
+                "https://api.siliconflow.cn"
+            } else {
+                base.trim_end_matches("/v1")
+            };
+            (format!("{root}/v1/user/info"), None)
+        }
+        BalanceKind::Custom => (base.to_string(), None),
+    }
+}
+
+async fn fetch_json(url: &str, token: &str) -> Result<Value, AppError> {
+    let client = crate::proxy::http_client::get();
+    let resp = client
+        .get(url)
+        .bearer_auth(token)
+        .timeout(std::time::Duration::from_secs(BALANCE_TIMEOUT_SECS))
+        .send()
+        .await
+        .map_err(|e| {
+            AppError::localized(
+                "provider.balance.request_failed",
+                format!("余额查询请求失败: {e}"),
+                format!("Balance request failed: {e}"),
+            )
+        })?;
+
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(AppError::localized(
+            "provider.balance.http_error",
+            format!("余额查询失败: HTTP {status}"),
+            format!("Balance query failed: HTTP {status}"),
+        ));
+    }
+
+    resp.json::<Value>().await.map_err(|e| {
+        AppError::localized(
+            "provider.balance.parse_failed",
+            format!("解析余额响应失败: {e}"),
+            format!("Failed to parse balance response: {e}"),
+        )
+    })
+}
+
+/// Fetch the balance for a provider with the given configuration
+async fn fetch_balance(provider: &Provider, config: &BalanceConfig) -> Result<Balance, AppError> {
+    let base = config
+        .url
+        .clone()
+        .filter(|u| !u.is_empty())
+        .or_else(|| extract_base_url_from_provider(provider))
+        .unwrap_or_default();
+    if base.is_empty()
+        && matches!(
+            config.kind,
+            BalanceKind::OpenaiBilling | BalanceKind::Custom
+        )
+    {
+        return Err(AppError::localized(
+            "provider.balance.url_missing",
+            "未配置余额查询地址",
+            "Balance endpoint URL is not configured",
+        ));
+    }
+    let token = config
+        .token
+        .clone()
+        .filter(|t| !t.is_empty())
+        .or_else(|| extract_api_key_from_provider(provider))
+        .unwrap_or_default();
+
+    let (primary_url, usage_url) = resolve_urls(config.kind, &base);
+    let primary = fetch_json(&primary_url, &token).await?;
+    let usage = match usage_url {
+        Some(url) => Some(fetch_json(&url, &token).await?),
+        None => None,
+    };
+
+    let mut balance = parse_balance(
+        config.kind,
+        &primary,
+        usage.as_ref(),
+        config.balance_path.as_deref(),
+    )?;
+    if let Some(unit) = config.unit.clone().filter(|u| !u.is_empty()) {
+        balance.unit = Some(unit);
+    }
+    Ok(balance)
+}
+
+/// Query provider balance (using saved balance configuration)
+pub async fn query_balance(
+    state: &AppState,
+    app_type: AppType,
+    provider_id: &str,
+) -> Result<BalanceResult, AppError> {
+    let (provider, config) = {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let provider = providers.get(provider_id).cloned().ok_or_else(|| {
+            AppError::localized(
+                "provider.not_found",
+                format!("供应商不存在: {provider_id}"),
+                format!("Provider not found: {provider_id}"),
+            )
+        })?;
+        let config = provider
+            .meta
+            .as_ref()
+            .and_then(|m| m.balance.clone())
+            .ok_or_else(|| {
+                AppError::localized(
+                    "provider.balance.missing",
+                    "未配置余额查询",
+                    "Balance query is not configured",
+                )
+            })?;
+        if !config.enabled {
+            return Err(AppError::localized(
+                "provider.balance.disabled",
+                "余额查询未启用",
+                "Balance query is disabled",
+            ));
+        }
+        (provider, config)
+    };
+
+    match fetch_balance(&provider, &config).await {
+        Ok(balance) => {
+            let low_balance = config
+                .low_threshold
+                .is_some_and(|threshold| balance.amount < threshold);
+            if low_balance {
+                log::warn!(
+                    "[Balance] 供应商 {} 余额不足: {:.2} {}",
+                    provider.name,
+                    balance.amount,
+                    balance.unit.as_deref().unwrap_or("")
+                );
+            }
+            Ok(BalanceResult {
+                success: true,
+                balance: Some(balance.amount),
+                unit: balance.unit,
+                low_balance,
+                error: None,
+            })
+        }
+        Err(err) => {
+            let lang = settings::get_settings()
+                .language
+                .unwrap_or_else(|| "zh".to_string());
+            let msg = match err {
+                AppError::Localized { zh, en, .. } => {
+                    if lang == "en" {
+                        en
+                    } else {
+                        zh
+                    }
+                }
+                other => other.to_string(),
+            };
+            Ok(BalanceResult {
+                success: false,
+                error: Some(msg),
+                ..Default::default()
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_openai_billing() {
+        let sub = json!({"hard_limit_usd": 50.0});
+        let usage = json!({"total_usage": 1250.0});
+        let balance = parse_balance(BalanceKind::OpenaiBilling, &sub, Some(&usage), None).unwrap();
+        assert_eq!(balance.amount, 37.5);
+        assert_eq!(balance.unit.as_deref(), Some("USD"));
+    }
+
+    #[test]
+    fn parse_deepseek_string_amount() {
+        let body = json!({
+            "is_available": true,
+            "balance_infos": [{"currency": "CNY", "total_balance": "110.00"}]
+        });
+        let balance = parse_balance(BalanceKind::Deepseek, &body, None, None).unwrap();
+        assert_eq!(balance.amount, 110.0);
+        assert_eq!(balance.unit.as_deref(), Some("CNY"));
+    }
+
+    #[test]
+    fn parse_openrouter_and_siliconflow() {
+        let body = json!({"data": {"total_credits": 20, "total_usage": 5.5}});
+        let balance = parse_balance(BalanceKind::Openrouter, &body, None, None).unwrap();
+        assert_eq!(balance.amount, 14.5);
+
+        let body = json!({"data": {"totalBalance": "3.2"}});
+        let balance = parse_balance(BalanceKind::Siliconflow, &body, None, None).unwrap();
+        assert_eq!(balance.amount, 3.2);
+    }
+
+    #[test]
+    fn parse_custom_pointer() {
+        let body = json!({"data": {"wallet": {"left": 8}}});
+        let balance =
+            parse_balance(BalanceKind::Custom, &body, None, Some("/data/wallet/left")).unwrap();
+        assert_eq!(balance.amount, 8.0);
+        assert!(parse_balance(BalanceKind::Custom, &body, None, None).is_err());
+        assert!(parse_balance(BalanceKind::Custom, &body, None, Some("/nope")).is_err());
+    }
+
+    #[test]
+    fn resolve_urls_strip_v1_suffix() {
+        let (url, usage) = resolve_urls(BalanceKind::OpenaiBilling, "https://relay.example/v1/");
+        assert_eq!(
+            url,
+            "https://relay.example/v1/dashboard/billing/subscription"
+        );
+        assert!(usage
+            .unwrap()
+            .starts_with("https://relay.example/v1/dashboard/billing/usage?"));
+
+        let (url, _) = resolve_urls(BalanceKind::Deepseek, "");
+        assert_eq!(url, "https://api.deepseek.com/user/balance");
+    }
+}
@@ -0,0 +1,467 @@
+//! Encrypted provider export/import
+//!
+//! Packs every app's providers (including API keys) into a single file encrypted
+//! with a user passphrase, so configs can be migrated or shared without leaving
+//! keys in plaintext JSON. The key is derived with PBKDF2-HMAC-SHA256 and the
+//! payload is sealed with AES-256-GCM; a wrong passphrase or a tampered file
+//! fails authentication instead of producing garbage.
+//!
+//! The same envelope is also available as a compact text "config seed" sized to fit
+//! a single QR code, so a config can be moved between machines by scanning instead of
+//! copying files. Only the seed text is handled here: rendering it as a QR image and
+//! reading a QR code back from an image file are not supported by the backend.
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use indexmap::IndexMap;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// File format marker
+const FORMAT: &str = "cc-switch-providers";
+const VERSION: u32 = 1;
+const KDF: &str = "pbkdf2-sha256";
+const DEFAULT_ITERATIONS: u32 = 600_000;
+/// Upper bound accepted on import, so a crafted file cannot stall the app in key derivation
+const MAX_ITERATIONS: u32 = 10_000_000;
+const SALT_LEN: usize = 16;
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+const APP_TYPES: [AppType; 3] = [AppType::Claude, AppType::Codex, AppType::Gemini];
+
+/// Config seed marker (the text carried by a QR code)
+const SEED_PREFIX: &str = "ccswitch-seed:";
+const SEED_VERSION: u8 = 1;
+/// Largest text a single QR code holds (version 40, error correction L, byte mode)
+const MAX_SEED_LEN: usize = 2953;
+/// Upper bound for the inflated seed payload
+const MAX_SEED_PAYLOAD: u64 = 4 * 1024 * 1024;
+
+/// On-disk envelope (all binary fields base64-encoded)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedFile {
+    format: String,
+    version: u32,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Decrypted payload: app type -> providers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProviderBundle {
+    exported_at: i64,
+    apps: IndexMap<String, Vec<Provider>>,
+}
+
+/// Result of an encrypted import
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImportResult {
+    pub added: usize,
+    pub updated: usize,
+    /// Existing providers left untouched (`overwrite` disabled)
+    pub skipped: usize,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<LessSafeKey, AppError> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| AppError::InvalidInput("Invalid iteration count".to_string()))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&AES_256_GCM, &key)
+        .map_err(|_| AppError::Message("Failed to create encryption key".to_string()))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Sealed payload shared by the file and seed formats
+struct Sealed {
+    iterations: u32,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+    ciphertext: Vec<u8>,
+}
+
+fn seal(mut data: Vec<u8>, passphrase: &str, iterations: u32) -> Result<Sealed, AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::localized(
+            "providerBundle.passphraseTooShort",
+            format!("密码至少需要 {MIN_PASSPHRASE_LEN} 个字符"),
+            format!("Passphrase must be at least {MIN_PASSPHRASE_LEN} characters"),
+        ));
+    }
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| AppError::Message("Failed to generate random bytes".to_string()))?;
+
+    let key = derive_key(passphrase, &salt, iterations)?;
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(FORMAT),
+        &mut data,
+    )
+    .map_err(|_| AppError::Message("Failed to encrypt providers".to_string()))?;
+    Ok(Sealed {
+        iterations,
+        salt,
+        nonce,
+        ciphertext: data,
+    })
+}
+
+fn open(
+    passphrase: &str,
+    salt: &[u8],
+    iterations: u32,
+    nonce: [u8; NONCE_LEN],
+    mut data: Vec<u8>,
+) -> Result<Vec<u8>, AppError> {
+    if iterations > MAX_ITERATIONS {
+        return Err(AppError::InvalidInput(format!(
+            "Iteration count too large: {iterations}"
+        )));
+    }
+    let key = derive_key(passphrase, salt, iterations)?;
+    let len = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(FORMAT),
+            &mut data,
+        )
+        .map_err(|_| {
+            AppError::localized(
+                "providerBundle.decryptFailed",
+                "密码错误或文件已损坏",
+                "Wrong passphrase or corrupted file",
+            )
+        })?
+        .len();
+    data.truncate(len);
+    Ok(data)
+}
+
+fn encrypt(bundle: &ProviderBundle, passphrase: &str, iterations: u32) -> Result<String, AppError> {
+    let data = serde_json::to_vec(bundle).map_err(|e| AppError::JsonSerialize { source: e })?;
+    let sealed = seal(data, passphrase, iterations)?;
+    let file = EncryptedFile {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        kdf: KDF.to_string(),
+        iterations: sealed.iterations,
+        salt: STANDARD.encode(sealed.salt),
+        nonce: STANDARD.encode(sealed.nonce),
+        ciphertext: STANDARD.encode(sealed.ciphertext),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| AppError::JsonSerialize { source: e })
+}
+
+fn decrypt(content: &str, passphrase: &str) -> Result<ProviderBundle, AppError> {
+    let invalid = || {
+        AppError::localized(
+            "providerBundle.invalidFile",
+            "不是有效的供应商加密导出文件",
+            "Not a valid encrypted provider export",
+        )
+    };
+    let file: EncryptedFile = serde_json::from_str(content).map_err(|_| invalid())?;
+    if file.format != FORMAT || file.kdf != KDF {
+        return Err(invalid());
+    }
+    if file.version > VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported export version: {}",
+            file.version
+        )));
+    }
+    let salt = STANDARD.decode(&file.salt).map_err(|_| invalid())?;
+    let nonce: [u8; NONCE_LEN] = STANDARD
+        .decode(&file.nonce)
+        .ok()
+        .and_then(|n| n.try_into().ok())
+        .ok_or_else(invalid)?;
+    let data = STANDARD.decode(&file.ciphertext).map_err(|_| invalid())?;
+
+    let plaintext = open(passphrase, &salt, file.iterations, nonce, data)?;
+    serde_json::from_slice(&plaintext).map_err(|_| invalid())
+}
+
+fn invalid_seed() -> AppError {
+    AppError::localized(
+        "providerBundle.invalidSeed",
+        "不是有效的配置种子",
+        "Not a valid config seed",
+    )
+}
+
+/// Seed layout (base64url): version (1) | iterations (4, BE) | salt | nonce | ciphertext,
+/// where the plaintext is the deflate-compressed bundle JSON
+fn encode_seed(
+    bundle: &ProviderBundle,
+    passphrase: &str,
+    iterations: u32,
+) -> Result<String, AppError> {
+    let json = serde_json::to_vec(bundle).map_err(|e| AppError::JsonSerialize { source: e })?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder
+        .write_all(&json)
+        .map_err(|e| AppError::Message(format!("Failed to compress providers: {e}")))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| AppError::Message(format!("Failed to compress providers: {e}")))?;
+    let sealed = seal(compressed, passphrase, iterations)?;
+
+    let mut raw = vec![SEED_VERSION];
+    raw.extend_from_slice(&sealed.iterations.to_be_bytes());
+    raw.extend_from_slice(&sealed.salt);
+    raw.extend_from_slice(&sealed.nonce);
+    raw.extend_from_slice(&sealed.ciphertext);
+    let seed = format!("{SEED_PREFIX}{}", URL_SAFE_NO_PAD.encode(raw));
+    if seed.len() > MAX_SEED_LEN {
+        return Err(AppError::localized(
+            "providerBundle.seedTooLarge",
+            "供应商过多，无法放入一个二维码，请只导出单个应用或改用加密文件导出",
+            "Too many providers for one QR code; export a single app or use the encrypted file export",
+        ));
+    }
+    Ok(seed)
+}
+
+fn decode_seed(seed: &str, passphrase: &str) -> Result<ProviderBundle, AppError> {
+    let seed = seed.trim();
+    if seed.len() > MAX_SEED_LEN {
+        return Err(invalid_seed());
+    }
+    let raw = seed
+        .strip_prefix(SEED_PREFIX)
+        .and_then(|body| URL_SAFE_NO_PAD.decode(body).ok())
+        .ok_or_else(invalid_seed)?;
+    let header_len = 1 + 4 + SALT_LEN + NONCE_LEN;
+    if raw.len() <= header_len {
+        return Err(invalid_seed());
+    }
+    if raw[0] > SEED_VERSION {
+        return Err(AppError::InvalidInput(format!(
+            "Unsupported seed version: {}",
+            raw[0]
+        )));
+    }
+    let iterations = u32::from_be_bytes(raw[1..5].try_into().map_err(|_| invalid_seed())?);
+    let salt = &raw[5..5 + SALT_LEN];
+    let nonce: [u8; NONCE_LEN] = raw[5 + SALT_LEN..header_len]
+        .try_into()
+        .map_err(|_| invalid_seed())?;
+    let compressed = open(
+        passphrase,
+        salt,
+        iterations,
+        nonce,
+        raw[header_len..].to_vec(),
+    )?;
+
+    let mut json = Vec::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .take(MAX_SEED_PAYLOAD)
+        .read_to_end(&mut json)
+        .map_err(|_| invalid_seed())?;
+    serde_json::from_slice(&json).map_err(|_| invalid_seed())
+}
+
+/// Collect the providers of the given apps
+fn collect(state: &AppState, apps: &[AppType]) -> Result<ProviderBundle, AppError> {
+    let mut bundle = ProviderBundle {
+        exported_at: chrono::Utc::now().timestamp(),
+        ..Default::default()
+    };
+    for app_type in apps {
+        let providers: Vec<Provider> = state
+            .db
+            .get_all_providers(app_type.as_str())?
+            .into_values()
+            .collect();
+        if !providers.is_empty() {
+            bundle.apps.insert(app_type.as_str().to_string(), providers);
+        }
+    }
+    Ok(bundle)
+}
+
+/// Add or replace the providers of a decrypted bundle
+fn apply(
+    state: &AppState,
+    bundle: ProviderBundle,
+    overwrite: bool,
+) -> Result<BundleImportResult, AppError> {
+    let mut result = BundleImportResult::default();
+    for (app, providers) in bundle.apps {
+        let Ok(app_type) = app.parse::<AppType>() else {
+            log::warn!("Skipping providers of unknown app type: {app}");
+            continue;
+        };
+        let existing = state.db.get_all_providers(app_type.as_str())?;
+        for provider in providers {
+            if !existing.contains_key(&provider.id) {
+                ProviderService::add(state, app_type.clone(), provider)?;
+                result.added += 1;
+            } else if overwrite {
+                ProviderService::update(state, app_type.clone(), provider)?;
+                result.updated += 1;
+            } else {
+                result.skipped += 1;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Export all providers into a passphrase-encrypted file, returning the provider count
+pub fn export_encrypted(
+    state: &AppState,
+    passphrase: &str,
+    path: &Path,
+) -> Result<usize, AppError> {
+    let bundle = collect(state, &APP_TYPES)?;
+    let count = bundle.apps.values().map(Vec::len).sum();
+    let content = encrypt(&bundle, passphrase, DEFAULT_ITERATIONS)?;
+    std::fs::write(path, content).map_err(|e| AppError::io(path, e))?;
+    Ok(count)
+}
+
+/// Import providers from an encrypted file
+///
+/// Providers whose id already exists are replaced when `overwrite` is set and skipped otherwise.
+pub fn import_encrypted(
+    state: &AppState,
+    passphrase: &str,
+    path: &Path,
+    overwrite: bool,
+) -> Result<BundleImportResult, AppError> {
+    let content = std::fs::read_to_string(path).map_err(|e| AppError::io(path, e))?;
+    let bundle = decrypt(&content, passphrase)?;
+    apply(state, bundle, overwrite)
+}
+
+/// Export providers as a passphrase-encrypted config seed for a QR code
+///
+/// `app_type` limits the seed to one app; all apps are included otherwise.
+pub fn export_seed(
+    state: &AppState,
+    passphrase: &str,
+    app_type: Option<AppType>,
+) -> Result<String, AppError> {
+    let apps = match app_type {
+        Some(app_type) => vec![app_type],
+        None => APP_TYPES.to_vec(),
+    };
+    let bundle = collect(state, &apps)?;
+    encode_seed(&bundle, passphrase, DEFAULT_ITERATIONS)
+}
+
+/// Import providers from a config seed (the text decoded from a QR code)
+pub fn import_seed(
+    state: &AppState,
+    passphrase: &str,
+    seed: &str,
+    overwrite: bool,
+) -> Result<BundleImportResult, AppError> {
+    let bundle = decode_seed(seed, passphrase)?;
+    apply(state, bundle, overwrite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bundle() -> ProviderBundle {
+        let provider = Provider::with_id(
+            "p1".to_string(),
+            "Relay".to_string(),
+            json!({ "env": { "ANTHROPIC_AUTH_TOKEN": "sk-secret-token" } }),
+            None,
+        );
+        ProviderBundle {
+            exported_at: 1,
+            apps: IndexMap::from([("claude".to_string(), vec![provider])]),
+        }
+    }
+
+    #[test]
+    fn round_trips_without_plaintext_secrets() {
+        let content = encrypt(&bundle(), "correct horse", 1_000).unwrap();
+        assert!(!content.contains("sk-secret-token"));
+        let decrypted = decrypt(&content, "correct horse").unwrap();
+        assert_eq!(
+            serde_json::to_value(decrypted).unwrap(),
+            serde_json::to_value(bundle()).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_wrong_passphrase_and_tampering() {
+        let content = encrypt(&bundle(), "correct horse", 1_000).unwrap();
+        assert!(decrypt(&content, "wrong horse!").is_err());
+
+        let mut file: EncryptedFile = serde_json::from_str(&content).unwrap();
+        let mut data = STANDARD.decode(&file.ciphertext).unwrap();
+        data[0] ^= 1;
+        file.ciphertext = STANDARD.encode(data);
+        let tampered = serde_json::to_string(&file).unwrap();
+        assert!(decrypt(&tampered, "correct horse").is_err());
+    }
+
+    #[test]
+    fn rejects_excessive_iterations() {
+        let content = encrypt(&bundle(), "correct horse", 1_000).unwrap();
+        let mut file: EncryptedFile = serde_json::from_str(&content).unwrap();
+        file.iterations = u32::MAX;
+        let crafted = serde_json::to_string(&file).unwrap();
+        assert!(decrypt(&crafted, "correct horse").is_err());
+    }
+
+    #[test]
+    fn seed_round_trips_within_qr_capacity() {
+        let seed = encode_seed(&bundle(), "correct horse", 1_000).unwrap();
+        assert!(seed.starts_with(SEED_PREFIX));
+        assert!(seed.len() <= MAX_SEED_LEN);
+        assert!(!seed.contains("sk-secret-token"));
+
+        let decoded = decode_seed(&format!("  {seed}\n"), "correct horse").unwrap();
+        assert_eq!(
+            serde_json::to_value(decoded).unwrap(),
+            serde_json::to_value(bundle()).unwrap()
+        );
+        assert!(decode_seed(&seed, "wrong horse!").is_err());
+        assert!(decode_seed("ccswitch-seed:AAAA", "correct horse").is_err());
+    }
+
+    #[test]
+    fn rejects_short_passphrase() {
+        assert!(encrypt(&bundle(), "short", 1_000).is_err());
+    }
+}
@@ -0,0 +1,373 @@
+//! Provider import from clipboard text
+//!
+//! Sniffs pasted text and turns it into a provider draft. Recognized formats:
+//! - `ccswitch://` deep links
+//! - full provider JSON (with `settingsConfig`) or a raw settings blob (`env` / `auth`+`config`)
+//! - env-style vendor snippets (`export ANTHROPIC_BASE_URL=...`, `.env` files, flat JSON maps)
+//! - a bare base URL or a bare API key
+//!
+//! Incomplete drafts (e.g. only an API key) are returned for the frontend to prefill;
+//! complete ones are validated and optionally saved.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::ProviderService;
+use crate::app_config::AppType;
+use crate::deeplink::{
+    build_provider_from_request, infer_homepage_from_endpoint, parse_and_merge_config,
+    parse_deeplink_url, validate_url, DeepLinkImportRequest,
+};
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::store::AppState;
+
+/// Detected clipboard content kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClipboardContentKind {
+    DeepLink,
+    ProviderJson,
+    VendorSnippet,
+    BaseUrl,
+    ApiKey,
+}
+
+/// Result of a clipboard import
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardImportResult {
+    pub kind: ClipboardContentKind,
+    /// Provider draft (saved when `created` is true)
+    pub provider: Provider,
+    /// Required fields still missing (`apiKey` / `baseUrl`)
+    pub missing: Vec<String>,
+    pub created: bool,
+}
+
+/// Fields extracted from loosely structured text
+#[derive(Debug, Default, PartialEq)]
+struct SniffedFields {
+    api_key: Option<String>,
+    endpoint: Option<String>,
+    model: Option<String>,
+    name: Option<String>,
+}
+
+/// Inspect clipboard text and build a provider draft; save it when `create` is set and complete
+pub fn import_from_clipboard(
+    state: &AppState,
+    app_type: AppType,
+    text: &str,
+    create: bool,
+) -> Result<ClipboardImportResult, AppError> {
+    let (kind, mut provider) = sniff(&app_type, text)?;
+
+    let missing = missing_fields(&app_type, &provider);
+    let created = create && missing.is_empty();
+    if created {
+        ProviderService::add(state, app_type, provider.clone())?;
+    } else {
+        provider.id.clear();
+    }
+
+    Ok(ClipboardImportResult {
+        kind,
+        provider,
+        missing,
+        created,
+    })
+}
+
+/// Detect the content kind and build a provider draft with a fresh id
+fn sniff(app_type: &AppType, text: &str) -> Result<(ClipboardContentKind, Provider), AppError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AppError::localized(
+            "clipboard.empty",
+            "剪贴板内容为空",
+            "Clipboard is empty",
+        ));
+    }
+
+    // Deep link
+    if text.starts_with("ccswitch://") {
+        let request = parse_deeplink_url(text)?;
+        if request.resource != "provider" {
+            return Err(AppError::InvalidInput(format!(
+                "Expected provider resource, got '{}'",
+                request.resource
+            )));
+        }
+        let request = parse_and_merge_config(&request)?;
+        let provider = build_draft(app_type, request)?;
+        return Ok((ClipboardContentKind::DeepLink, provider));
+    }
+
+    // JSON: full provider, settings blob, or flat env map
+    if let Ok(value) = serde_json::from_str::<Value>(text) {
+        if let Some(obj) = value.as_object() {
+            if obj.contains_key("settingsConfig") {
+                let mut provider: Provider = serde_json::from_value(value.clone())
+                    .map_err(|e| AppError::InvalidInput(format!("Invalid provider JSON: {e}")))?;
+                provider.id = generate_id(&provider.name);
+                return Ok((ClipboardContentKind::ProviderJson, provider));
+            }
+            if obj.contains_key("env") || obj.contains_key("auth") {
+                let mut provider = Provider::with_id(String::new(), String::new(), value, None);
+                if let Ok((_, base_url)) = ProviderService::extract_credentials(&provider, app_type)
+                {
+                    provider.name = name_from_endpoint(&base_url).unwrap_or_default();
+                    provider.website_url = infer_homepage_from_endpoint(&base_url);
+                }
+                provider.id = generate_id(&provider.name);
+                return Ok((ClipboardContentKind::ProviderJson, provider));
+            }
+            let pairs = obj
+                .iter()
+                .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                .collect::<Vec<_>>();
+            let fields = fields_from_pairs(&pairs);
+            if fields != SniffedFields::default() {
+                return Ok((
+                    ClipboardContentKind::VendorSnippet,
+                    build_from_fields(app_type, fields)?,
+                ));
+            }
+        }
+        return Err(unrecognized());
+    }
+
+    // Env-style snippet
+    let pairs = parse_env_pairs(text);
+    if !pairs.is_empty() {
+        let fields = fields_from_pairs(&pairs);
+        if fields != SniffedFields::default() {
+            return Ok((
+                ClipboardContentKind::VendorSnippet,
+                build_from_fields(app_type, fields)?,
+            ));
+        }
+    }
+
+    // Bare URL
+    if text.starts_with("http://") || text.starts_with("https://") {
+        let fields = SniffedFields {
+            endpoint: Some(text.to_string()),
+            ..Default::default()
+        };
+        return Ok((
+            ClipboardContentKind::BaseUrl,
+            build_from_fields(app_type, fields)?,
+        ));
+    }
+
+    // Bare API key
+    if looks_like_api_key(text) {
+        let fields = SniffedFields {
+            api_key: Some(text.to_string()),
+            ..Default::default()
+        };
+        return Ok((
+            ClipboardContentKind::ApiKey,
+            build_from_fields(app_type, fields)?,
+        ));
+    }
+
+    Err(unrecognized())
+}
+
+fn unrecognized() -> AppError {
+    AppError::localized(
+        "clipboard.unrecognized",
+        "无法识别剪贴板内容，请粘贴 API Key、Base URL 或供应商配置",
+        "Unrecognized clipboard content; paste an API key, base URL or provider config",
+    )
+}
+
+/// Parse `KEY=VALUE` lines (`export`, `set` and PowerShell `$env:` prefixes, quotes stripped)
+fn parse_env_pairs(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line
+                .strip_prefix("export ")
+                .or_else(|| line.strip_prefix("set "))
+                .or_else(|| line.strip_prefix("$env:"))
+                .unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return None;
+            }
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Map well-known env keys onto provider fields
+fn fields_from_pairs(pairs: &[(String, String)]) -> SniffedFields {
+    let mut fields = SniffedFields::default();
+    for (key, value) in pairs {
+        if value.is_empty() {
+            continue;
+        }
+        let upper = key.to_ascii_uppercase();
+        if upper.ends_with("BASE_URL") || upper.ends_with("ENDPOINT") || upper.ends_with("API_BASE")
+        {
+            fields.endpoint.get_or_insert_with(|| value.clone());
+        } else if upper.ends_with("API_KEY") || upper.ends_with("AUTH_TOKEN") {
+            fields.api_key.get_or_insert_with(|| value.clone());
+        } else if upper.ends_with("_MODEL") || upper == "MODEL" {
+            fields.model.get_or_insert_with(|| value.clone());
+        }
+    }
+    fields
+}
+
+fn looks_like_api_key(text: &str) -> bool {
+    text.len() >= 16
+        && !text.contains(char::is_whitespace)
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+fn build_from_fields(app_type: &AppType, fields: SniffedFields) -> Result<Provider, AppError> {
+    let request = DeepLinkImportRequest {
+        version: "v1".to_string(),
+        resource: "provider".to_string(),
+        app: Some(app_type.as_str().to_string()),
+        name: fields.name,
+        endpoint: fields.endpoint,
+        api_key: fields.api_key,
+        model: fields.model,
+        ..Default::default()
+    };
+    build_draft(app_type, request)
+}
+
+/// Validate the endpoint, fill name/homepage defaults and build the provider
+fn build_draft(
+    app_type: &AppType,
+    mut request: DeepLinkImportRequest,
+) -> Result<Provider, AppError> {
+    let endpoint = request
+        .endpoint
+        .as_deref()
+        .and_then(|ep| ep.split(',').next())
+        .map(|ep| ep.trim().to_string())
+        .filter(|ep| !ep.is_empty());
+
+    if let Some(endpoint) = &endpoint {
+        validate_url(endpoint, "endpoint")?;
+        if request.homepage.as_deref().is_none_or(str::is_empty) {
+            request.homepage = infer_homepage_from_endpoint(endpoint);
+        }
+        if request.name.as_deref().is_none_or(str::is_empty) {
+            request.name = name_from_endpoint(endpoint);
+        }
+    }
+
+    let mut provider = build_provider_from_request(app_type, &request)?;
+    provider.id = generate_id(&provider.name);
+    Ok(provider)
+}
+
+fn name_from_endpoint(endpoint: &str) -> Option<String> {
+    let url = url::Url::parse(endpoint).ok()?;
+    let host = url.host_str()?;
+    Some(
+        host.strip_prefix("api.")
+            .or_else(|| host.strip_prefix("api-"))
+            .unwrap_or(host)
+            .to_string(),
+    )
+}
+
+fn generate_id(name: &str) -> String {
+    let sanitized = name
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect::<String>()
+        .to_lowercase();
+    let prefix = if sanitized.is_empty() {
+        "clipboard".to_string()
+    } else {
+        sanitized
+    };
+    format!("{prefix}-{}", chrono::Utc::now().timestamp_millis())
+}
+
+/// Required fields still empty in the draft
+fn missing_fields(app_type: &AppType, provider: &Provider) -> Vec<String> {
+    let (api_key, base_url) =
+        ProviderService::extract_credentials(provider, app_type).unwrap_or_default();
+    let mut missing = Vec::new();
+    if api_key.trim().is_empty() {
+        missing.push("apiKey".to_string());
+    }
+    if base_url.trim().is_empty() {
+        missing.push("baseUrl".to_string());
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_export_snippet() {
+        let text = "export ANTHROPIC_BASE_URL=\"https://api.relay.example/v1\"\nexport ANTHROPIC_AUTH_TOKEN=sk-test-123";
+        let (kind, provider) = sniff(&AppType::Claude, text).unwrap();
+        assert_eq!(kind, ClipboardContentKind::VendorSnippet);
+        assert_eq!(provider.name, "relay.example");
+        assert_eq!(
+            provider.settings_config["env"]["ANTHROPIC_BASE_URL"],
+            "https://api.relay.example/v1"
+        );
+        assert_eq!(
+            provider.settings_config["env"]["ANTHROPIC_AUTH_TOKEN"],
+            "sk-test-123"
+        );
+        assert!(missing_fields(&AppType::Claude, &provider).is_empty());
+    }
+
+    #[test]
+    fn sniffs_bare_key_and_reports_missing_url() {
+        let (kind, provider) = sniff(&AppType::Claude, "sk-ant-abcdefghijklmnop").unwrap();
+        assert_eq!(kind, ClipboardContentKind::ApiKey);
+        assert_eq!(missing_fields(&AppType::Claude, &provider), vec!["baseUrl"]);
+    }
+
+    #[test]
+    fn sniffs_bare_url() {
+        let (kind, provider) = sniff(&AppType::Gemini, "https://gemini.example.com").unwrap();
+        assert_eq!(kind, ClipboardContentKind::BaseUrl);
+        assert_eq!(
+            provider.settings_config["env"]["GOOGLE_GEMINI_BASE_URL"],
+            "https://gemini.example.com"
+        );
+    }
+
+    #[test]
+    fn sniffs_settings_blob() {
+        let text =
+            r#"{"env":{"ANTHROPIC_BASE_URL":"https://api.foo.dev","ANTHROPIC_AUTH_TOKEN":"k"}}"#;
+        let (kind, provider) = sniff(&AppType::Claude, text).unwrap();
+        assert_eq!(kind, ClipboardContentKind::ProviderJson);
+        assert_eq!(provider.name, "foo.dev");
+    }
+
+    #[test]
+    fn rejects_invalid_url_and_garbage() {
+        assert!(sniff(&AppType::Claude, "ANTHROPIC_BASE_URL=ftp://x").is_err());
+        assert!(sniff(&AppType::Claude, "hello world").is_err());
+        assert!(sniff(&AppType::Claude, "   ").is_err());
+    }
+}
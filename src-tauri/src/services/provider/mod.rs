@@ -2,9 +2,13 @@
 //!
 //! Handles provider CRUD operations, switching, and configuration management.
 
+mod balance;
+mod bundle;
+mod clipboard;
 mod endpoints;
 mod gemini_auth;
 mod live;
+mod summary;
 mod usage;
 
 use indexmap::IndexMap;
@@ -14,13 +18,16 @@ use serde_json::Value;
 
 use crate::app_config::AppType;
 use crate::error::AppError;
-use crate::provider::{Provider, UsageResult};
+use crate::provider::{BalanceResult, Provider, UsageResult};
 use crate::services::mcp::McpService;
 use crate::settings::CustomEndpoint;
 use crate::store::AppState;
 
 // Re-export sub-module functions for external access
+pub use bundle::BundleImportResult;
+pub use clipboard::ClipboardImportResult;
 pub use live::{import_default_config, read_live_settings, sync_current_to_live};
+pub use summary::SummaryFormat;
 
 // Internal re-exports (pub(crate))
 pub(crate) use live::write_live_snapshot;
@@ -603,6 +610,72 @@ impl ProviderService {
         usage::query_usage(state, app_type, provider_id).await
     }
 
+    /// Query relay balance (re-export)
+    pub async fn query_balance(
+        state: &AppState,
+        app_type: AppType,
+        provider_id: &str,
+    ) -> Result<BalanceResult, AppError> {
+        balance::query_balance(state, app_type, provider_id).await
+    }
+
+    /// Import a provider from clipboard text (re-export)
+    pub fn import_from_clipboard(
+        state: &AppState,
+        app_type: AppType,
+        text: &str,
+        create: bool,
+    ) -> Result<ClipboardImportResult, AppError> {
+        clipboard::import_from_clipboard(state, app_type, text, create)
+    }
+
+    /// Export all providers into a passphrase-encrypted file (re-export)
+    pub fn export_encrypted(
+        state: &AppState,
+        passphrase: &str,
+        path: &std::path::Path,
+    ) -> Result<usize, AppError> {
+        bundle::export_encrypted(state, passphrase, path)
+    }
+
+    /// Import providers from a passphrase-encrypted file (re-export)
+    pub fn import_encrypted(
+        state: &AppState,
+        passphrase: &str,
+        path: &std::path::Path,
+        overwrite: bool,
+    ) -> Result<BundleImportResult, AppError> {
+        bundle::import_encrypted(state, passphrase, path, overwrite)
+    }
+
+    /// Export providers as an encrypted QR config seed (re-export)
+    pub fn export_seed(
+        state: &AppState,
+        passphrase: &str,
+        app_type: Option<AppType>,
+    ) -> Result<String, AppError> {
+        bundle::export_seed(state, passphrase, app_type)
+    }
+
+    /// Import providers from an encrypted QR config seed (re-export)
+    pub fn import_seed(
+        state: &AppState,
+        passphrase: &str,
+        seed: &str,
+        overwrite: bool,
+    ) -> Result<BundleImportResult, AppError> {
+        bundle::import_seed(state, passphrase, seed, overwrite)
+    }
+
+    /// Export a secret-free provider summary (re-export)
+    pub fn export_summary(
+        state: &AppState,
+        app_type: Option<AppType>,
+        format: SummaryFormat,
+    ) -> Result<String, AppError> {
+        summary::export_summary(state, app_type, format)
+    }
+
     /// Test usage script (re-export)
     #[allow(clippy::too_many_arguments)]
     pub async fn test_usage_script(
@@ -698,6 +771,37 @@ impl ProviderService {
             if let Some(usage_script) = &meta.usage_script {
                 validate_usage_script(usage_script)?;
             }
+            if let Some(proxy_url) = &meta.upstream_proxy {
+                crate::proxy::http_client::validate_proxy(Some(proxy_url.trim()))
+                    .map_err(AppError::InvalidInput)?;
+            }
+            if let Some(ca_path) = meta
+                .tls
+                .as_ref()
+                .and_then(|t| t.ca_bundle_path.as_deref())
+                .filter(|p| !p.trim().is_empty())
+            {
+                crate::proxy::http_client::validate_ca_bundle(ca_path)
+                    .map_err(AppError::InvalidInput)?;
+            }
+            if let Some(tls) = &meta.tls {
+                let non_empty = |p: &Option<String>| p.clone().filter(|p| !p.trim().is_empty());
+                match (
+                    non_empty(&tls.client_cert_path),
+                    non_empty(&tls.client_key_path),
+                ) {
+                    (Some(cert), Some(key)) => {
+                        crate::proxy::http_client::validate_client_identity(&cert, &key)
+                            .map_err(AppError::InvalidInput)?
+                    }
+                    (None, None) => {}
+                    _ => {
+                        return Err(AppError::InvalidInput(
+                            "mTLS 客户端证书与私钥必须同时配置".to_string(),
+                        ))
+                    }
+                }
+            }
         }
 
         Ok(())
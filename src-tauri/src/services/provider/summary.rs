@@ -0,0 +1,320 @@
+//! Provider summary export
+//!
+//! Renders the provider list into a shareable Markdown/JSON summary for team
+//! documentation or support requests. Secrets (API keys, tokens, header values)
+//! are never included.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::app_config::AppType;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::providers::get_adapter;
+use crate::store::AppState;
+
+/// Summary output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryFormat {
+    Markdown,
+    Json,
+}
+
+/// Secret-free summary of a single provider
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProviderSummary {
+    pub id: String,
+    pub name: String,
+    pub is_current: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Model slots (e.g. `ANTHROPIC_MODEL` -> model name)
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub models: IndexMap<String, String>,
+    pub in_failover_queue: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom_endpoints: Vec<String>,
+    /// Routing/rewrite rules in effect (names only, no values)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+/// Secret-free summary of one app's providers
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppSummary {
+    pub app_type: String,
+    pub providers: Vec<ProviderSummary>,
+}
+
+/// Env keys holding model names, per app
+fn model_env_keys(app_type: &AppType) -> &'static [&'static str] {
+    match app_type {
+        AppType::Claude => &[
+            "ANTHROPIC_MODEL",
+            "ANTHROPIC_DEFAULT_HAIKU_MODEL",
+            "ANTHROPIC_DEFAULT_SONNET_MODEL",
+            "ANTHROPIC_DEFAULT_OPUS_MODEL",
+            "ANTHROPIC_SMALL_FAST_MODEL",
+        ],
+        AppType::Codex => &[],
+        AppType::Gemini => &["GEMINI_MODEL"],
+    }
+}
+
+fn extract_models(app_type: &AppType, provider: &Provider) -> IndexMap<String, String> {
+    let mut models = IndexMap::new();
+
+    if let Some(env) = provider.settings_config.get("env") {
+        for key in model_env_keys(app_type) {
+            if let Some(model) = env.get(*key).and_then(|v| v.as_str()) {
+                if !model.trim().is_empty() {
+                    models.insert((*key).to_string(), model.to_string());
+                }
+            }
+        }
+    }
+
+    if matches!(app_type, AppType::Codex) {
+        let model = provider
+            .settings_config
+            .get("config")
+            .and_then(|c| c.as_str())
+            .and_then(|s| toml::from_str::<toml::Value>(s).ok())
+            .and_then(|v| v.get("model").and_then(|m| m.as_str()).map(String::from));
+        if let Some(model) = model {
+            models.insert("model".to_string(), model);
+        }
+    }
+
+    models
+}
+
+fn extract_rules(provider: &Provider) -> Vec<String> {
+    let Some(meta) = provider.meta.as_ref() else {
+        return Vec::new();
+    };
+
+    let mut rules = Vec::new();
+    if meta.endpoint_auto_select == Some(true) {
+        rules.push("endpointAutoSelect".to_string());
+    }
+    if let Some(header_rules) = meta.header_rules.as_ref().filter(|r| !r.is_empty()) {
+        let names: Vec<&str> = header_rules
+            .inject
+            .keys()
+            .chain(header_rules.set.keys())
+            .map(String::as_str)
+            .chain(header_rules.strip.iter().map(String::as_str))
+            .collect();
+        rules.push(format!("headerRules: {}", names.join(", ")));
+    }
+    if meta.header_passthrough.is_some() {
+        rules.push("headerPassthrough".to_string());
+    }
+    if meta.system_prompt.is_some() {
+        rules.push("systemPrompt".to_string());
+    }
+    if let Some(version) = &meta.anthropic_version {
+        rules.push(format!("anthropicVersion: {version}"));
+    }
+    if meta.prompt_caching.as_ref().is_some_and(|c| c.enabled) {
+        rules.push("promptCaching".to_string());
+    }
+    if let Some(limit) = &meta.max_tokens_limit {
+        rules.push(format!("maxTokensLimit: {}", limit.cap));
+    }
+    if meta.sampling.is_some() {
+        rules.push("sampling".to_string());
+    }
+    if !meta.field_denylist.is_empty() {
+        rules.push(format!("fieldDenylist: {}", meta.field_denylist.join(", ")));
+    }
+    rules
+}
+
+pub(crate) fn summarize_provider(
+    app_type: &AppType,
+    provider: &Provider,
+    current_id: &str,
+) -> ProviderSummary {
+    let base_url = get_adapter(app_type)
+        .extract_base_url(provider)
+        .ok()
+        .filter(|u| !u.is_empty());
+
+    let custom_endpoints = provider
+        .meta
+        .as_ref()
+        .map(|m| m.custom_endpoints.keys().cloned().collect())
+        .unwrap_or_default();
+
+    ProviderSummary {
+        id: provider.id.clone(),
+        name: provider.name.clone(),
+        is_current: provider.id == current_id,
+        category: provider.category.clone(),
+        website_url: provider.website_url.clone(),
+        base_url,
+        models: extract_models(app_type, provider),
+        in_failover_queue: provider.in_failover_queue,
+        custom_endpoints,
+        rules: extract_rules(provider),
+        notes: provider.notes.clone().filter(|n| !n.trim().is_empty()),
+    }
+}
+
+/// Escape characters that would break a Markdown table cell
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+pub(crate) fn render_markdown(apps: &[AppSummary]) -> String {
+    let mut out = String::from("# Providers\n");
+
+    for app in apps {
+        out.push_str(&format!("\n## {}\n\n", app.app_type));
+        if app.providers.is_empty() {
+            out.push_str("_No providers configured._\n");
+            continue;
+        }
+        out.push_str("| Name | Base URL | Models | Failover | Rules |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for p in &app.providers {
+            let name = if p.is_current {
+                format!("**{}** (current)", md_cell(&p.name))
+            } else {
+                md_cell(&p.name)
+            };
+            let models = p
+                .models
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join("<br>");
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                name,
+                md_cell(p.base_url.as_deref().unwrap_or("-")),
+                md_cell(&models),
+                if p.in_failover_queue { "yes" } else { "no" },
+                md_cell(&p.rules.join("; ")),
+            ));
+        }
+    }
+
+    out
+}
+
+/// Export a secret-free summary of providers (all apps when `app_type` is None)
+pub fn export_summary(
+    state: &AppState,
+    app_type: Option<AppType>,
+    format: SummaryFormat,
+) -> Result<String, AppError> {
+    let app_types = match app_type {
+        Some(app_type) => vec![app_type],
+        None => vec![AppType::Claude, AppType::Codex, AppType::Gemini],
+    };
+
+    let mut apps = Vec::with_capacity(app_types.len());
+    for app_type in app_types {
+        let providers = state.db.get_all_providers(app_type.as_str())?;
+        let current_id = crate::settings::get_effective_current_provider(&state.db, &app_type)?
+            .unwrap_or_default();
+
+        let mut sorted: Vec<&Provider> = providers.values().collect();
+        sorted.sort_by_key(|p| (p.sort_index.unwrap_or(usize::MAX), p.created_at));
+
+        apps.push(AppSummary {
+            app_type: app_type.as_str().to_string(),
+            providers: sorted
+                .into_iter()
+                .map(|p| summarize_provider(&app_type, p, &current_id))
+                .collect(),
+        });
+    }
+
+    match format {
+        SummaryFormat::Markdown => Ok(render_markdown(&apps)),
+        SummaryFormat::Json => {
+            serde_json::to_string_pretty(&apps).map_err(|source| AppError::JsonSerialize { source })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn claude_provider() -> Provider {
+        let mut p = Provider::with_id(
+            "p1".into(),
+            "Relay | One".into(),
+            json!({
+                "env": {
+                    "ANTHROPIC_BASE_URL": "https://relay.example.com",
+                    "ANTHROPIC_AUTH_TOKEN": "sk-secret-token",
+                    "ANTHROPIC_MODEL": "claude-sonnet-4"
+                }
+            }),
+            None,
+        );
+        p.in_failover_queue = true;
+        p
+    }
+
+    #[test]
+    fn summary_excludes_secrets() {
+        let summary = summarize_provider(&AppType::Claude, &claude_provider(), "p1");
+        assert!(summary.is_current);
+        assert_eq!(
+            summary.base_url.as_deref(),
+            Some("https://relay.example.com")
+        );
+        assert_eq!(summary.models["ANTHROPIC_MODEL"], "claude-sonnet-4");
+
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(!json.contains("sk-secret-token"));
+    }
+
+    #[test]
+    fn codex_model_from_toml() {
+        let p = Provider::with_id(
+            "c1".into(),
+            "Codex".into(),
+            json!({
+                "auth": {"OPENAI_API_KEY": "sk-x"},
+                "config": "model = \"gpt-5-codex\"\nbase_url = \"https://api.example.com/v1\"\n"
+            }),
+            None,
+        );
+        let summary = summarize_provider(&AppType::Codex, &p, "");
+        assert_eq!(summary.models["model"], "gpt-5-codex");
+    }
+
+    #[test]
+    fn markdown_escapes_cells() {
+        let apps = vec![AppSummary {
+            app_type: "claude".into(),
+            providers: vec![summarize_provider(
+                &AppType::Claude,
+                &claude_provider(),
+                "p1",
+            )],
+        }];
+        let md = render_markdown(&apps);
+        assert!(md.contains("**Relay \\| One** (current)"));
+        assert!(md.contains("| yes |"));
+        assert!(!md.contains("sk-secret-token"));
+    }
+}
@@ -82,7 +82,9 @@ pub(crate) async fn execute_and_format_usage_result(
 }
 
 /// Extract API key from provider configuration
-fn extract_api_key_from_provider(provider: &crate::provider::Provider) -> Option<String> {
+pub(super) fn extract_api_key_from_provider(
+    provider: &crate::provider::Provider,
+) -> Option<String> {
     if let Some(env) = provider.settings_config.get("env") {
         // Try multiple possible API key fields
         env.get("ANTHROPIC_AUTH_TOKEN")
@@ -97,7 +99,9 @@ fn extract_api_key_from_provider(provider: &crate::provider::Provider) -> Option
 }
 
 /// Extract base URL from provider configuration
-fn extract_base_url_from_provider(provider: &crate::provider::Provider) -> Option<String> {
+pub(super) fn extract_base_url_from_provider(
+    provider: &crate::provider::Provider,
+) -> Option<String> {
     if let Some(env) = provider.settings_config.get("env") {
         // Try multiple possible base URL fields
         env.get("ANTHROPIC_BASE_URL")
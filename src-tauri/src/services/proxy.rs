@@ -17,6 +17,9 @@ use tokio::sync::RwLock;
 /// 用于接管 Live 配置时的占位符（避免客户端提示缺少 key，同时不泄露真实 Token）
 const PROXY_TOKEN_PLACEHOLDER: &str = "PROXY_MANAGED";
 
+/// 监听地址变更时旧服务器等待剩余请求完成的最长时间（与上游请求默认超时一致）
+const SERVER_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(600);
+
 /// 代理接管模式下需要从 Claude Live 配置中移除的“模型覆盖”字段。
 ///
 /// 原因：接管模式切换供应商时不会写回 Live 配置，如果保留这些字段，
@@ -127,6 +130,11 @@ impl ProxyService {
         *self.server.write().await = Some(server);
 
         log::info!("代理服务器已启动: {}:{}", info.address, info.port);
+
+        // 配置的端口被占用、顺延到其他端口时，Live 配置中的代理地址需要同步更新
+        if info.port != config.listen_port {
+            self.refresh_takeover_proxy_urls().await?;
+        }
         Ok(info)
     }
 
@@ -772,27 +780,27 @@ impl ProxyService {
     }
 
     /// 构造写入 Live 的代理地址（处理 0.0.0.0 / IPv6 等特殊情况）
+    ///
+    /// 代理运行中时使用实际监听端口（配置的端口被占用时会顺延）
     async fn build_proxy_urls(&self) -> Result<(String, String), String> {
         let config = self
             .db
             .get_proxy_config()
             .await
             .map_err(|e| format!("获取代理配置失败: {e}"))?;
+        let port = match self.server.read().await.as_ref() {
+            Some(server) => server.get_status().await.port,
+            None => config.listen_port,
+        };
 
         // listen_address 可能是 0.0.0.0（用于监听所有网卡），但客户端无法用 0.0.0.0 连接；
         // 因此写回到各应用配置时，优先使用本机回环地址。
-        let connect_host = match config.listen_address.as_str() {
-            "0.0.0.0" => "127.0.0.1".to_string(),
-            "::" => "::1".to_string(),
-            _ => config.listen_address.clone(),
-        };
-        let connect_host_for_url = if connect_host.contains(':') && !connect_host.starts_with('[') {
-            format!("[{connect_host}]")
-        } else {
-            connect_host
-        };
-
-        let proxy_origin = format!("http://{}:{}", connect_host_for_url, config.listen_port);
+        let https = self
+            .db
+            .get_proxy_tls_config()
+            .map(|c| c.enabled)
+            .unwrap_or(false);
+        let proxy_origin = crate::proxy::discovery::base_url(&config.listen_address, port, https);
         let proxy_url = proxy_origin.clone();
         let proxy_codex_base_url = format!("{}/v1", proxy_origin.trim_end_matches('/'));
 
@@ -1222,10 +1230,12 @@ impl ProxyService {
 
     fn is_local_proxy_url(url: &str) -> bool {
         let url = url.trim();
-        if !url.starts_with("http://") {
+        let Some(rest) = url
+            .strip_prefix("http://")
+            .or_else(|| url.strip_prefix("https://"))
+        else {
             return false;
-        }
-        let rest = &url["http://".len()..];
+        };
         rest.starts_with("127.0.0.1")
             || rest.starts_with("localhost")
             || rest.starts_with("0.0.0.0")
@@ -1492,6 +1502,9 @@ impl ProxyService {
     }
 
     /// 代理模式下切换供应商（热切换，不写 Live）
+    ///
+    /// 无需重启代理服务器：每个请求在开始时读取一次故障转移链，
+    /// 切换后到达的请求使用新供应商，进行中的请求（包括流式响应）继续使用原供应商完成
     pub async fn switch_proxy_target(
         &self,
         app_type: &str,
@@ -1688,6 +1701,8 @@ impl ProxyService {
     }
 
     /// 更新代理配置
+    ///
+    /// 仅监听地址或端口变更时需要新的监听器，此时新旧服务器平滑交接，熔断器状态与客户端限流计数随之沿用；其余配置实时生效
     pub async fn update_config(&self, config: &ProxyConfig) -> Result<(), String> {
         // 记录旧配置用于判定是否需要重启
         let previous = self
@@ -1695,6 +1710,11 @@ impl ProxyService {
             .get_proxy_config()
             .await
             .map_err(|e| format!("获取代理配置失败: {e}"))?;
+        crate::proxy::lan_access::check_listen_address(
+            &self.db,
+            &config.listen_address,
+            Some(&previous.listen_address),
+        )?;
 
         // 保存到数据库（保持 live_takeover_active 状态不变）
         let mut new_config = config.clone();
@@ -1707,58 +1727,51 @@ impl ProxyService {
 
         // 检查服务器当前状态
         let mut server_guard = self.server.write().await;
-        if server_guard.is_none() {
+        let Some(current) = server_guard.as_ref() else {
             return Ok(());
-        }
+        };
 
         // 判断是否需要重启（地址或端口变更）
         let require_restart = new_config.listen_address != previous.listen_address
             || new_config.listen_port != previous.listen_port;
 
         if require_restart {
-            if let Some(server) = server_guard.take() {
-                server
-                    .stop()
-                    .await
-                    .map_err(|e| format!("重启前停止代理服务器失败: {e}"))?;
-            }
-
+            // 先在新地址启动，再让旧服务器平滑下线，进行中的流式响应不受影响；
+            // 新地址与旧地址冲突（如仅修改监听 IP、端口不变）时退回为先停后启。
+            // 新服务器沿用旧服务器的熔断器状态与客户端限流计数
             let app_handle = self.app_handle.read().await.clone();
-            let new_server = ProxyServer::new(new_config, self.db.clone(), app_handle);
-            new_server
-                .start()
-                .await
-                .map_err(|e| format!("重启代理服务器失败: {e}"))?;
-
-            *server_guard = Some(new_server);
-            log::info!("代理配置已更新，服务器已自动重启应用最新配置");
-
-            // 如果当前存在任意 app 的 Live 接管，需要同步更新 Live 中的代理地址（否则客户端仍指向旧端口）
-            drop(server_guard);
-            if let Ok(takeover) = self.get_takeover_status().await {
-                let mut updated_any = false;
-
-                if takeover.claude {
-                    self.takeover_live_config_best_effort(&AppType::Claude)
-                        .await?;
-                    updated_any = true;
-                }
-                if takeover.codex {
-                    self.takeover_live_config_best_effort(&AppType::Codex)
-                        .await?;
-                    updated_any = true;
-                }
-                if takeover.gemini {
-                    self.takeover_live_config_best_effort(&AppType::Gemini)
-                        .await?;
-                    updated_any = true;
+            let new_server = current.successor(new_config.clone(), app_handle.clone());
+            match new_server.start_exact().await {
+                Ok(_) => {
+                    if let Some(old) = server_guard.take() {
+                        old.drain(SERVER_DRAIN_TIMEOUT).await;
+                    }
+                    *server_guard = Some(new_server);
+                    log::info!("代理配置已更新，新服务器已接管，旧服务器处理完剩余请求后退出");
                 }
+                Err(handover_err) => {
+                    log::info!("无法与旧服务器并行启动（{handover_err}），改为先停止再启动");
+                    if let Some(server) = server_guard.take() {
+                        server
+                            .stop()
+                            .await
+                            .map_err(|e| format!("重启前停止代理服务器失败: {e}"))?;
+                    }
 
-                if updated_any {
-                    log::info!("已同步更新 Live 配置中的代理地址");
+                    new_server
+                        .start()
+                        .await
+                        .map_err(|e| format!("重启代理服务器失败: {e}"))?;
+
+                    *server_guard = Some(new_server);
+                    log::info!("代理配置已更新，服务器已自动重启应用最新配置");
                 }
             }
 
+            // 如果当前存在任意 app 的 Live 接管，需要同步更新 Live 中的代理地址（否则客户端仍指向旧端口）
+            drop(server_guard);
+            self.refresh_takeover_proxy_urls().await?;
+
             return Ok(());
         } else if let Some(server) = server_guard.as_ref() {
             server.apply_runtime_config(&new_config).await;
@@ -1768,6 +1781,43 @@ impl ProxyService {
         Ok(())
     }
 
+    /// 把已接管应用的 Live 配置中的代理地址更新为当前实际监听地址
+    async fn refresh_takeover_proxy_urls(&self) -> Result<(), String> {
+        let Ok(takeover) = self.get_takeover_status().await else {
+            return Ok(());
+        };
+        let mut updated_any = false;
+
+        if takeover.claude {
+            self.takeover_live_config_best_effort(&AppType::Claude)
+                .await?;
+            updated_any = true;
+        }
+        if takeover.codex {
+            self.takeover_live_config_best_effort(&AppType::Codex)
+                .await?;
+            updated_any = true;
+        }
+        if takeover.gemini {
+            self.takeover_live_config_best_effort(&AppType::Gemini)
+                .await?;
+            updated_any = true;
+        }
+
+        if updated_any {
+            log::info!("已同步更新 Live 配置中的代理地址");
+        }
+        Ok(())
+    }
+
+    /// 当前实际监听的代理地址（未运行时为 None）
+    pub async fn get_active_endpoint(
+        &self,
+    ) -> Option<crate::proxy::discovery::ActiveProxyEndpoint> {
+        self.server.read().await.as_ref()?;
+        crate::proxy::discovery::read()
+    }
+
     /// 检查服务器是否正在运行
     pub async fn is_running(&self) -> bool {
         self.server.read().await.is_some()
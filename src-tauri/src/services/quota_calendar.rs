@@ -0,0 +1,156 @@
+//! 供应商限额重置日历
+//!
+//! 根据 `quotaCalendar` 计算当前每日 / 长周期限额窗口的起止时间，
+//! 供限额统计、重置倒计时与预算路由共用。
+
+use crate::provider::{Provider, QuotaCalendar, QuotaPeriod};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, Offset, TimeZone, Utc};
+
+/// 限额窗口（Unix 秒，左闭右开）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+/// 当前的每日窗口与长周期窗口
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaWindows {
+    pub daily: QuotaWindow,
+    pub period: QuotaWindow,
+}
+
+/// 读取供应商的重置日历（未配置时使用默认日历）
+pub fn calendar_of(provider: &Provider) -> QuotaCalendar {
+    provider
+        .meta
+        .as_ref()
+        .and_then(|m| m.quota_calendar.clone())
+        .unwrap_or_default()
+}
+
+/// 计算 `now` 所在的限额窗口
+pub fn current_windows(calendar: &QuotaCalendar, now: DateTime<Utc>) -> QuotaWindows {
+    let offset = resolve_offset(calendar, now);
+    let local = now.with_timezone(&offset).naive_local();
+    let hour = calendar.reset_hour.min(23);
+
+    let reset_at = |date: NaiveDate| -> i64 {
+        let naive = date.and_hms_opt(hour, 0, 0).unwrap_or_default();
+        naive.and_utc().timestamp() - offset.local_minus_utc() as i64
+    };
+
+    // 以重置时刻为一天的起点
+    let today = (local - Duration::hours(hour as i64)).date();
+    let daily = QuotaWindow {
+        start: reset_at(today),
+        end: reset_at(today + Duration::days(1)),
+    };
+
+    let period = match calendar.period {
+        QuotaPeriod::Weekly => {
+            let weekday = calendar.reset_weekday.unwrap_or(0).min(6);
+            let back = (today.weekday().num_days_from_monday() + 7 - weekday) % 7;
+            let start = today - Duration::days(back as i64);
+            QuotaWindow {
+                start: reset_at(start),
+                end: reset_at(start + Duration::days(7)),
+            }
+        }
+        QuotaPeriod::Monthly => {
+            let day = calendar.reset_day.unwrap_or(1).clamp(1, 28);
+            let mut start = month_day(today.year(), today.month(), day);
+            if start > today {
+                start = shift_month(start, -1);
+            }
+            QuotaWindow {
+                start: reset_at(start),
+                end: reset_at(shift_month(start, 1)),
+            }
+        }
+    };
+
+    QuotaWindows { daily, period }
+}
+
+/// 重置日历使用的时区偏移
+fn resolve_offset(calendar: &QuotaCalendar, now: DateTime<Utc>) -> FixedOffset {
+    calendar
+        .utc_offset_minutes
+        .and_then(|minutes| FixedOffset::east_opt(minutes * 60))
+        .unwrap_or_else(|| Local.offset_from_utc_datetime(&now.naive_utc()).fix())
+}
+
+fn month_day(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap_or_default()
+}
+
+fn shift_month(date: NaiveDate, delta: i32) -> NaiveDate {
+    let index = date.year() * 12 + date.month0() as i32 + delta;
+    month_day(
+        index.div_euclid(12),
+        index.rem_euclid(12) as u32 + 1,
+        date.day(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn daily_window_follows_offset() {
+        // UTC-8 零点重置 = UTC 08:00
+        let calendar = QuotaCalendar {
+            utc_offset_minutes: Some(-480),
+            ..Default::default()
+        };
+        let windows = current_windows(&calendar, utc("2026-03-10T05:00:00Z"));
+        assert_eq!(windows.daily.start, utc("2026-03-09T08:00:00Z").timestamp());
+        assert_eq!(windows.daily.end, utc("2026-03-10T08:00:00Z").timestamp());
+    }
+
+    #[test]
+    fn weekly_window_starts_on_reset_weekday() {
+        // 2026-03-11 是周三；每周一 UTC 09:00 重置
+        let calendar = QuotaCalendar {
+            utc_offset_minutes: Some(0),
+            reset_hour: 9,
+            period: QuotaPeriod::Weekly,
+            reset_weekday: Some(0),
+            ..Default::default()
+        };
+        let windows = current_windows(&calendar, utc("2026-03-11T12:00:00Z"));
+        assert_eq!(
+            windows.period.start,
+            utc("2026-03-09T09:00:00Z").timestamp()
+        );
+        assert_eq!(windows.period.end, utc("2026-03-16T09:00:00Z").timestamp());
+
+        // 周一重置前仍属于上一周
+        let windows = current_windows(&calendar, utc("2026-03-09T08:59:00Z"));
+        assert_eq!(
+            windows.period.start,
+            utc("2026-03-02T09:00:00Z").timestamp()
+        );
+    }
+
+    #[test]
+    fn monthly_window_wraps_year() {
+        let calendar = QuotaCalendar {
+            utc_offset_minutes: Some(0),
+            reset_day: Some(15),
+            ..Default::default()
+        };
+        let windows = current_windows(&calendar, utc("2026-01-03T00:00:00Z"));
+        assert_eq!(
+            windows.period.start,
+            utc("2025-12-15T00:00:00Z").timestamp()
+        );
+        assert_eq!(windows.period.end, utc("2026-01-15T00:00:00Z").timestamp());
+    }
+}
@@ -0,0 +1,391 @@
+//! 供应商响应对比
+//!
+//! 把同一个提示同时发送给两个供应商（非流式、不经过代理），并排返回两边的回复文本、
+//! 延迟、Token 用量与成本，便于比较不同中转的质量差异。
+//!
+//! 提示来源（按优先级）：
+//! 1. `body`：应用原生格式的完整请求体（`model` 与 `stream` 字段会被覆盖）
+//! 2. `requestId`：已记录对话的用户输入（需开启对话记录）
+//! 3. `prompt`：纯文本提示
+
+use futures::future;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::provider::Provider;
+use crate::proxy::cost_annotation;
+use crate::proxy::providers::get_adapter;
+use crate::proxy::usage::parser::TokenUsage;
+use crate::services::onboarding::{map_request_error, with_auth};
+use crate::services::stream_check::StreamCheckService;
+
+const COMPARE_TIMEOUT: Duration = Duration::from_secs(120);
+
+fn default_max_tokens() -> u32 {
+    1024
+}
+
+/// 对比请求
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareRequest {
+    pub app_type: AppType,
+    pub provider_a: String,
+    pub provider_b: String,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub body: Option<Value>,
+    /// 指定模型（为空时使用请求体中的模型，再回退到各供应商配置的模型）
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 纯文本提示时的最大输出 token 数
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+}
+
+/// 单个供应商的响应
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparedResponse {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub model: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_status: Option<u16>,
+    pub latency_ms: u64,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// 成本（美元，未知定价时为 `unknown`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 对比结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonReport {
+    pub app_type: String,
+    /// 纯文本提示（使用原始请求体时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    pub a: ComparedResponse,
+    pub b: ComparedResponse,
+}
+
+/// 提示来源
+enum PromptSource {
+    Body(Value),
+    Text(String),
+}
+
+pub struct ResponseCompareService;
+
+impl ResponseCompareService {
+    /// 并发向两个供应商发送同一提示
+    pub async fn compare(
+        db: &Database,
+        request: CompareRequest,
+    ) -> Result<ComparisonReport, AppError> {
+        let app_type = request.app_type.clone();
+        let app = app_type.as_str();
+        let source = Self::resolve_source(db, &request)?;
+
+        let load = |id: &str| {
+            db.get_provider_by_id(id, app)?
+                .ok_or_else(|| AppError::Message(format!("供应商 {id} 不存在")))
+        };
+        let provider_a = load(&request.provider_a)?;
+        let provider_b = load(&request.provider_b)?;
+        let check_config = db.get_stream_check_config()?;
+
+        let model_for = |provider: &Provider| {
+            request
+                .model
+                .clone()
+                .filter(|m| !m.trim().is_empty())
+                .or_else(|| match &source {
+                    PromptSource::Body(body) => body
+                        .get("model")
+                        .and_then(Value::as_str)
+                        .map(str::to_string),
+                    PromptSource::Text(_) => None,
+                })
+                .unwrap_or_else(|| {
+                    StreamCheckService::resolve_test_model(&app_type, provider, &check_config)
+                })
+        };
+        let model_a = model_for(&provider_a);
+        let model_b = model_for(&provider_b);
+
+        let (a, b) = future::join(
+            Self::send(
+                db,
+                &app_type,
+                &provider_a,
+                &model_a,
+                &source,
+                request.max_tokens,
+            ),
+            Self::send(
+                db,
+                &app_type,
+                &provider_b,
+                &model_b,
+                &source,
+                request.max_tokens,
+            ),
+        )
+        .await;
+
+        Ok(ComparisonReport {
+            app_type: app.to_string(),
+            prompt: match source {
+                PromptSource::Text(text) => Some(text),
+                PromptSource::Body(_) => None,
+            },
+            a,
+            b,
+        })
+    }
+
+    fn resolve_source(db: &Database, request: &CompareRequest) -> Result<PromptSource, AppError> {
+        if let Some(body) = request.body.clone().filter(Value::is_object) {
+            return Ok(PromptSource::Body(body));
+        }
+        if let Some(request_id) = request.request_id.as_deref() {
+            let transcript = db
+                .get_transcript(request_id)?
+                .ok_or_else(|| AppError::Message(format!("未找到请求 {request_id} 的对话记录")))?;
+            return Ok(PromptSource::Text(transcript.request_text));
+        }
+        match request.prompt.as_deref().map(str::trim) {
+            Some(prompt) if !prompt.is_empty() => Ok(PromptSource::Text(prompt.to_string())),
+            _ => Err(AppError::Message("请提供提示内容".to_string())),
+        }
+    }
+
+    async fn send(
+        db: &Database,
+        app_type: &AppType,
+        provider: &Provider,
+        model: &str,
+        source: &PromptSource,
+        max_tokens: u32,
+    ) -> ComparedResponse {
+        let start = Instant::now();
+        let result = Self::send_once(app_type, provider, model, source, max_tokens).await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let mut response = ComparedResponse {
+            provider_id: provider.id.clone(),
+            provider_name: provider.name.clone(),
+            model: model.to_string(),
+            success: false,
+            http_status: None,
+            latency_ms,
+            text: String::new(),
+            usage: None,
+            cost_usd: None,
+            error: None,
+        };
+        match result {
+            Ok((status, body)) => {
+                response.http_status = Some(status);
+                response.success = (200..300).contains(&status);
+                if response.success {
+                    response.text = extract_text(app_type, &body);
+                    response.usage = parse_usage(app_type, &body);
+                    response.cost_usd = response.usage.as_ref().map(|usage| {
+                        cost_annotation::format_cost(cost_annotation::calculate_cost(
+                            db, provider, model, usage,
+                        ))
+                    });
+                } else {
+                    response.error = Some(format!("HTTP {status}: {body}"));
+                }
+            }
+            Err(e) => response.error = Some(e.to_string()),
+        }
+        response
+    }
+
+    async fn send_once(
+        app_type: &AppType,
+        provider: &Provider,
+        model: &str,
+        source: &PromptSource,
+        max_tokens: u32,
+    ) -> Result<(u16, Value), AppError> {
+        let adapter = get_adapter(app_type);
+        let base_url = adapter
+            .extract_base_url(provider)
+            .map_err(|e| AppError::Message(format!("Failed to extract base_url: {e}")))?;
+        let auth = adapter
+            .extract_auth(provider)
+            .ok_or_else(|| AppError::Message("API Key not found".to_string()))?;
+
+        let body = build_body(app_type, model, source, max_tokens);
+        let client = crate::proxy::http_client::get();
+        let request = client
+            .post(endpoint_url(app_type, &base_url, model))
+            .timeout(COMPARE_TIMEOUT)
+            .json(&body);
+        let response = with_auth(app_type, request, &auth.api_key)
+            .send()
+            .await
+            .map_err(map_request_error)?;
+
+        let status = response.status().as_u16();
+        let text = response.text().await.unwrap_or_default();
+        let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
+        Ok((status, body))
+    }
+}
+
+/// 各应用的非流式接口地址
+fn endpoint_url(app_type: &AppType, base_url: &str, model: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    match app_type {
+        AppType::Claude if base.ends_with("/v1") => format!("{base}/messages"),
+        AppType::Claude => format!("{base}/v1/messages"),
+        AppType::Codex if base.ends_with("/v1") => format!("{base}/responses"),
+        AppType::Codex => format!("{base}/v1/responses"),
+        AppType::Gemini => format!("{base}/v1beta/models/{model}:generateContent"),
+    }
+}
+
+/// 构造请求体（强制非流式）
+fn build_body(app_type: &AppType, model: &str, source: &PromptSource, max_tokens: u32) -> Value {
+    let mut body = match source {
+        PromptSource::Body(body) => body.clone(),
+        PromptSource::Text(prompt) => match app_type {
+            AppType::Claude => json!({
+                "max_tokens": max_tokens,
+                "messages": [{ "role": "user", "content": prompt }],
+            }),
+            AppType::Codex => json!({
+                "input": [{
+                    "type": "message",
+                    "role": "user",
+                    "content": [{ "type": "input_text", "text": prompt }],
+                }],
+                "max_output_tokens": max_tokens,
+            }),
+            AppType::Gemini => json!({
+                "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+                "generationConfig": { "maxOutputTokens": max_tokens },
+            }),
+        },
+    };
+
+    if let Some(obj) = body.as_object_mut() {
+        if matches!(app_type, AppType::Gemini) {
+            obj.remove("model");
+            obj.remove("stream");
+        } else {
+            obj.insert("model".to_string(), json!(model));
+            obj.insert("stream".to_string(), json!(false));
+        }
+    }
+    body
+}
+
+/// 提取回复文本
+fn extract_text(app_type: &AppType, body: &Value) -> String {
+    let texts: Vec<&str> = match app_type {
+        AppType::Claude => body
+            .get("content")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect(),
+        AppType::Codex => {
+            if let Some(text) = body.get("output_text").and_then(Value::as_str) {
+                vec![text]
+            } else {
+                body.get("output")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|item| item.get("content").and_then(Value::as_array))
+                    .flatten()
+                    .filter(|part| part.get("type").and_then(Value::as_str) == Some("output_text"))
+                    .filter_map(|part| part.get("text").and_then(Value::as_str))
+                    .collect()
+            }
+        }
+        AppType::Gemini => body
+            .pointer("/candidates/0/content/parts")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect(),
+    };
+    texts.join("")
+}
+
+fn parse_usage(app_type: &AppType, body: &Value) -> Option<TokenUsage> {
+    match app_type {
+        AppType::Claude => TokenUsage::from_claude_response(body),
+        AppType::Codex => TokenUsage::from_codex_response_auto(body),
+        AppType::Gemini => TokenUsage::from_gemini_response(body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_non_streaming_bodies() {
+        let text = PromptSource::Text("hi".into());
+        let claude = build_body(&AppType::Claude, "m1", &text, 64);
+        assert_eq!(claude["model"], "m1");
+        assert_eq!(claude["stream"], false);
+        assert_eq!(claude["messages"][0]["content"], "hi");
+
+        let raw = PromptSource::Body(json!({ "model": "old", "stream": true, "contents": [] }));
+        let gemini = build_body(&AppType::Gemini, "g", &raw, 64);
+        assert!(gemini.get("model").is_none() && gemini.get("stream").is_none());
+        assert_eq!(
+            endpoint_url(&AppType::Gemini, "https://x/", "g"),
+            "https://x/v1beta/models/g:generateContent"
+        );
+        assert_eq!(
+            endpoint_url(&AppType::Codex, "https://x/v1", "m"),
+            "https://x/v1/responses"
+        );
+    }
+
+    #[test]
+    fn extracts_reply_text_per_app() {
+        let claude = json!({ "content": [
+            { "type": "thinking", "thinking": "..." },
+            { "type": "text", "text": "Hello" }
+        ]});
+        assert_eq!(extract_text(&AppType::Claude, &claude), "Hello");
+
+        let codex = json!({ "output": [
+            { "type": "reasoning", "summary": [] },
+            { "type": "message", "content": [{ "type": "output_text", "text": "Hi" }] }
+        ]});
+        assert_eq!(extract_text(&AppType::Codex, &codex), "Hi");
+
+        let gemini = json!({ "candidates": [{ "content": { "parts": [{ "text": "A" }, { "text": "B" }] } }] });
+        assert_eq!(extract_text(&AppType::Gemini, &gemini), "AB");
+    }
+}
@@ -0,0 +1,234 @@
+//! Claude 配置变更历史
+//!
+//! cc-switch 每次写入 `~/.claude/settings.json` 时，把写入的原始内容另存一份快照到
+//! `<app_config_dir>/settings-history/claude/<毫秒时间戳>.json`（内容与上一版相同时跳过），
+//! 最多保留 [`MAX_VERSIONS`] 份。支持任意两个版本（或版本与当前文件）之间的逐行对比，
+//! 以及一键恢复到指定版本。
+
+use crate::config::{atomic_write, get_app_config_dir, get_claude_settings_path};
+use crate::error::AppError;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 最多保留的快照数量
+const MAX_VERSIONS: usize = 100;
+
+/// 单个快照的元信息
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsVersion {
+    /// 快照 ID（写入时的毫秒时间戳）
+    pub id: String,
+    pub created_at: i64,
+    pub size: u64,
+}
+
+/// 对比结果中的一行
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// `same` / `added` / `removed`
+    pub kind: &'static str,
+    pub text: String,
+}
+
+/// 快照目录
+pub struct SettingsHistory {
+    dir: PathBuf,
+}
+
+impl SettingsHistory {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Claude settings.json 的快照目录
+    pub fn claude() -> Self {
+        Self::new(get_app_config_dir().join("settings-history").join("claude"))
+    }
+
+    fn version_path(&self, id: &str) -> Result<PathBuf, AppError> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AppError::InvalidInput(format!("无效的版本 ID: {id}")));
+        }
+        Ok(self.dir.join(format!("{id}.json")))
+    }
+
+    /// 全部快照（从新到旧）
+    pub fn list(&self) -> Result<Vec<SettingsVersion>, AppError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut versions: Vec<SettingsVersion> = fs::read_dir(&self.dir)
+            .map_err(|e| AppError::io(&self.dir, e))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let id = path.file_stem()?.to_str()?.to_string();
+                let created_at = id.parse::<i64>().ok()?;
+                (path.extension()? == "json").then_some(SettingsVersion {
+                    id,
+                    created_at,
+                    size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                })
+            })
+            .collect();
+        versions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(versions)
+    }
+
+    /// 读取快照内容
+    pub fn read(&self, id: &str) -> Result<String, AppError> {
+        let path = self.version_path(id)?;
+        if !path.exists() {
+            return Err(AppError::InvalidInput(format!("版本 {id} 不存在")));
+        }
+        fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))
+    }
+
+    /// 记录一次写入（与最新快照相同时跳过），返回新快照 ID
+    pub fn record(&self, content: &[u8]) -> Result<Option<String>, AppError> {
+        let versions = self.list()?;
+        if let Some(latest) = versions.first() {
+            let path = self.version_path(&latest.id)?;
+            if fs::read(&path).is_ok_and(|existing| existing == content) {
+                return Ok(None);
+            }
+        }
+
+        // 同一毫秒内多次写入时顺延，保证 ID 唯一且递增
+        let mut created_at = chrono::Utc::now().timestamp_millis();
+        if let Some(latest) = versions.first() {
+            created_at = created_at.max(latest.created_at + 1);
+        }
+        let id = created_at.to_string();
+        atomic_write(&self.version_path(&id)?, content)?;
+
+        for stale in versions.iter().skip(MAX_VERSIONS - 1) {
+            let path = self.version_path(&stale.id)?;
+            let _ = fs::remove_file(path);
+        }
+        Ok(Some(id))
+    }
+
+    /// 对比两个版本；`to` 为 None 时与 `target` 当前内容对比
+    pub fn diff(
+        &self,
+        from: &str,
+        to: Option<&str>,
+        target: &Path,
+    ) -> Result<Vec<DiffLine>, AppError> {
+        let old = self.read(from)?;
+        let new = match to {
+            Some(id) => self.read(id)?,
+            None if target.exists() => {
+                fs::read_to_string(target).map_err(|e| AppError::io(target, e))?
+            }
+            None => String::new(),
+        };
+        Ok(diff_lines(&old, &new))
+    }
+
+    /// 把 `target` 恢复为指定版本，并把恢复结果记为新快照
+    pub fn restore(&self, id: &str, target: &Path) -> Result<(), AppError> {
+        let content = self.read(id)?;
+        atomic_write(target, content.as_bytes())?;
+        self.record(content.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// 逐行对比（最长公共子序列）
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j]：a[i..] 与 b[j..] 的最长公共子序列长度
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+    let (mut i, mut j) = (0, 0);
+    let mut result = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            result.push(line("same", a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(line("removed", a[i]));
+            i += 1;
+        } else {
+            result.push(line("added", b[j]));
+            j += 1;
+        }
+    }
+    result.extend(a[i..].iter().map(|text| line("removed", text)));
+    result.extend(b[j..].iter().map(|text| line("added", text)));
+    result
+}
+
+/// 写入 Claude settings.json 后记录快照（失败仅记录日志，不影响写入）
+pub fn record_claude_settings_write(path: &Path, content: &[u8]) {
+    if path != get_claude_settings_path() {
+        return;
+    }
+    if let Err(e) = SettingsHistory::claude().record(content) {
+        log::warn!("记录 Claude 配置历史失败: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_deduplicates_and_restores() -> Result<(), AppError> {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let history = SettingsHistory::new(tmp.path().join("history"));
+        let target = tmp.path().join("settings.json");
+
+        let first = history.record(b"{\n  \"model\": \"a\"\n}")?.expect("first");
+        assert_eq!(history.record(b"{\n  \"model\": \"a\"\n}")?, None);
+        let second = history
+            .record(b"{\n  \"model\": \"b\"\n}")?
+            .expect("second");
+        let ids: Vec<_> = history.list()?.into_iter().map(|v| v.id).collect();
+        assert_eq!(ids, vec![second.clone(), first.clone()]);
+
+        let diff = history.diff(&first, Some(&second), &target)?;
+        let changed: Vec<_> = diff
+            .iter()
+            .filter(|l| l.kind != "same")
+            .map(|l| (l.kind, l.text.trim()))
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                ("removed", "\"model\": \"a\""),
+                ("added", "\"model\": \"b\"")
+            ]
+        );
+
+        history.restore(&first, &target)?;
+        assert_eq!(
+            fs::read_to_string(&target).unwrap(),
+            "{\n  \"model\": \"a\"\n}"
+        );
+        assert_eq!(history.list()?.len(), 3);
+        assert!(history.read("../secret").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+//! 上游状态页监控
+//!
+//! 后台定时轮询供应商配置的状态页（Statuspage / RSS），发现故障时向前端发送
+//! `provider-status-incident` 事件，恢复时发送 `provider-status-resolved` 事件。
+//! 开启 `deprioritizeOnOutage` 的供应商在故障期间会被路由器移到故障转移队列末尾。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::error::AppError;
+use crate::provider::{Provider, StatusPageConfig, StatusPageKind};
+
+/// 默认轮询间隔（分钟）
+const DEFAULT_POLL_INTERVAL_MINUTES: u64 = 5;
+/// 调度器检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 状态页上报的故障
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusIncident {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    /// 严重程度（Statuspage: minor / major / critical；RSS 固定为 incident）
+    pub indicator: String,
+    pub title: String,
+    pub status_page_url: String,
+    pub deprioritized: bool,
+    /// 首次发现时间（Unix 秒）
+    pub detected_at: i64,
+}
+
+type IncidentMap = HashMap<String, StatusIncident>;
+
+fn incidents() -> &'static RwLock<IncidentMap> {
+    static INCIDENTS: OnceLock<RwLock<IncidentMap>> = OnceLock::new();
+    INCIDENTS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+fn incident_key(app_type: &str, provider_id: &str) -> String {
+    format!("{app_type}:{provider_id}")
+}
+
+/// 解析 Statuspage `status.json`，正常运行时返回 None
+pub(crate) fn parse_statuspage(body: &Value) -> Option<(String, String)> {
+    let status = body.get("status")?;
+    let indicator = status.get("indicator")?.as_str()?;
+    if indicator == "none" {
+        return None;
+    }
+    let description = status
+        .get("description")
+        .and_then(|d| d.as_str())
+        .unwrap_or(indicator);
+    Some((indicator.to_string(), description.to_string()))
+}
+
+/// 截取 `<tag>...</tag>` 之间的文本（忽略属性与 CDATA 包裹）
+fn extract_tag<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}");
+    let start = text.find(&open)?;
+    let content_start = start + text[start..].find('>')? + 1;
+    let end = content_start + text[content_start..].find(&format!("</{tag}>"))?;
+    let content = text[content_start..end].trim();
+    Some(
+        content
+            .strip_prefix("<![CDATA[")
+            .and_then(|c| c.strip_suffix("]]>"))
+            .unwrap_or(content)
+            .trim(),
+    )
+}
+
+/// 解析 RSS / Atom 订阅：最新条目未标记为已解决时视为进行中的故障
+pub(crate) fn parse_feed(text: &str) -> Option<String> {
+    let item_start = text.find("<item").or_else(|| text.find("<entry"))?;
+    let item = &text[item_start..];
+    let item_end = item
+        .find("</item>")
+        .or_else(|| item.find("</entry>"))
+        .unwrap_or(item.len());
+    let item = &item[..item_end];
+
+    let title = extract_tag(item, "title").unwrap_or_default();
+    let body = extract_tag(item, "description")
+        .or_else(|| extract_tag(item, "summary"))
+        .or_else(|| extract_tag(item, "content"))
+        .unwrap_or_default();
+
+    let lower = format!("{title} {body}").to_ascii_lowercase();
+    let resolved = ["resolved", "completed", "postmortem", "恢复", "已解决"]
+        .iter()
+        .any(|kw| lower.contains(kw));
+
+    if resolved || title.is_empty() {
+        None
+    } else {
+        Some(title.to_string())
+    }
+}
+
+async fn fetch_status(config: &StatusPageConfig) -> Result<Option<(String, String)>, AppError> {
+    let client = crate::proxy::http_client::get();
+    let url = match config.kind {
+        StatusPageKind::Statuspage => {
+            let base = config.url.trim_end_matches('/');
+            if base.ends_with(".json") {
+                base.to_string()
+            } else {
+                format!("{base}/api/v2/status.json")
+            }
+        }
+        StatusPageKind::Rss => config.url.clone(),
+    };
+
+    let resp = client
+        .get(&url)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| AppError::Message(format!("请求状态页失败: {e}")))?;
+    if !resp.status().is_success() {
+        return Err(AppError::Message(format!(
+            "状态页返回 HTTP {}",
+            resp.status()
+        )));
+    }
+    let text = resp
+        .text()
+        .await
+        .map_err(|e| AppError::Message(format!("读取状态页失败: {e}")))?;
+
+    Ok(match config.kind {
+        StatusPageKind::Statuspage => {
+            let body: Value = serde_json::from_str(&text)
+                .map_err(|e| AppError::Message(format!("解析状态页失败: {e}")))?;
+            parse_statuspage(&body)
+        }
+        StatusPageKind::Rss => parse_feed(&text).map(|title| ("incident".to_string(), title)),
+    })
+}
+
+/// 状态页监控服务
+pub struct StatusWatcherService;
+
+impl StatusWatcherService {
+    /// 当前所有进行中的故障
+    pub fn list_incidents() -> Vec<StatusIncident> {
+        let guard = incidents().read().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<_> = guard.values().cloned().collect();
+        list.sort_by_key(|i| i.detected_at);
+        list
+    }
+
+    /// 供应商是否因状态页故障需要降低路由优先级
+    pub fn is_deprioritized(app_type: &str, provider_id: &str) -> bool {
+        let guard = incidents().read().unwrap_or_else(|e| e.into_inner());
+        guard
+            .get(&incident_key(app_type, provider_id))
+            .is_some_and(|i| i.deprioritized)
+    }
+
+    /// 更新单个供应商的故障状态，返回 (新故障, 已恢复)
+    fn update(
+        app_type: &str,
+        provider: &Provider,
+        config: &StatusPageConfig,
+        status: Option<(String, String)>,
+    ) -> (Option<StatusIncident>, Option<StatusIncident>) {
+        let key = incident_key(app_type, &provider.id);
+        let mut guard = incidents().write().unwrap_or_else(|e| e.into_inner());
+        match status {
+            Some((indicator, title)) => {
+                if let Some(existing) = guard.get_mut(&key) {
+                    existing.indicator = indicator;
+                    existing.title = title;
+                    existing.deprioritized = config.deprioritize_on_outage;
+                    return (None, None);
+                }
+                let incident = StatusIncident {
+                    app_type: app_type.to_string(),
+                    provider_id: provider.id.clone(),
+                    provider_name: provider.name.clone(),
+                    indicator,
+                    title,
+                    status_page_url: config.url.clone(),
+                    deprioritized: config.deprioritize_on_outage,
+                    detected_at: chrono::Utc::now().timestamp(),
+                };
+                guard.insert(key, incident.clone());
+                (Some(incident), None)
+            }
+            None => (None, guard.remove(&key)),
+        }
+    }
+
+    /// 启动后台轮询任务
+    pub fn start(db: Arc<Database>, app_handle: tauri::AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut last_polled: HashMap<String, Instant> = HashMap::new();
+            loop {
+                Self::poll_due(&db, &app_handle, &mut last_polled).await;
+                tokio::time::sleep(TICK_INTERVAL).await;
+            }
+        });
+    }
+
+    async fn poll_due(
+        db: &Database,
+        app_handle: &tauri::AppHandle,
+        last_polled: &mut HashMap<String, Instant>,
+    ) {
+        let mut watched_keys = Vec::new();
+
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app_str = app_type.as_str();
+            let providers = match db.get_all_providers(app_str) {
+                Ok(p) => p,
+                Err(e) => {
+                    log::warn!("[StatusWatcher] 读取 {app_str} 供应商失败: {e}");
+                    continue;
+                }
+            };
+
+            for provider in providers.values() {
+                let Some(config) = provider
+                    .meta
+                    .as_ref()
+                    .and_then(|m| m.status_page.as_ref())
+                    .filter(|c| !c.url.trim().is_empty())
+                else {
+                    continue;
+                };
+
+                let key = incident_key(app_str, &provider.id);
+                watched_keys.push(key.clone());
+
+                let interval = Duration::from_secs(
+                    config
+                        .poll_interval_minutes
+                        .unwrap_or(DEFAULT_POLL_INTERVAL_MINUTES)
+                        .max(1)
+                        * 60,
+                );
+                if last_polled
+                    .get(&key)
+                    .is_some_and(|t| t.elapsed() < interval)
+                {
+                    continue;
+                }
+                last_polled.insert(key, Instant::now());
+
+                let status = match fetch_status(config).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        log::debug!("[StatusWatcher] {} 状态页检查失败: {e}", provider.name);
+                        continue;
+                    }
+                };
+
+                let (opened, resolved) = Self::update(app_str, provider, config, status);
+                if let Some(incident) = opened {
+                    log::warn!(
+                        "[StatusWatcher] {} 状态页报告故障 ({}): {}",
+                        provider.name,
+                        incident.indicator,
+                        incident.title
+                    );
+                    let _ = app_handle.emit("provider-status-incident", &incident);
+                }
+                if let Some(incident) = resolved {
+                    log::info!("[StatusWatcher] {} 状态页故障已恢复", provider.name);
+                    let _ = app_handle.emit("provider-status-resolved", &incident);
+                }
+            }
+        }
+
+        // 清理已删除或取消监控的供应商
+        last_polled.retain(|k, _| watched_keys.contains(k));
+        incidents()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|k, _| watched_keys.contains(k));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_statuspage() {
+        let ok = json!({"status": {"indicator": "none", "description": "All Systems Operational"}});
+        assert_eq!(parse_statuspage(&ok), None);
+
+        let down = json!({"status": {"indicator": "major", "description": "Partial Outage"}});
+        assert_eq!(
+            parse_statuspage(&down),
+            Some(("major".to_string(), "Partial Outage".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_feed_latest_item() {
+        let active = r#"<rss><channel><title>Status</title>
+            <item><title>Elevated errors on Claude Opus</title>
+            <description><![CDATA[<p><strong>Investigating</strong> - We are investigating</p>]]></description></item>
+            <item><title>Old incident</title><description>Resolved</description></item>
+            </channel></rss>"#;
+        assert_eq!(
+            parse_feed(active).as_deref(),
+            Some("Elevated errors on Claude Opus")
+        );
+
+        let resolved = r#"<feed><entry><title type="html">API latency</title>
+            <content>Resolved - This incident has been resolved.</content></entry></feed>"#;
+        assert_eq!(parse_feed(resolved), None);
+    }
+
+    #[test]
+    fn test_update_tracks_incident_lifecycle() {
+        let provider = Provider::with_id("sw-test".into(), "P".into(), json!({}), None);
+        let config = StatusPageConfig {
+            url: "https://status.example.com".into(),
+            deprioritize_on_outage: true,
+            ..Default::default()
+        };
+
+        let (opened, _) = StatusWatcherService::update(
+            "claude",
+            &provider,
+            &config,
+            Some(("minor".into(), "Degraded".into())),
+        );
+        assert!(opened.is_some());
+        assert!(StatusWatcherService::is_deprioritized("claude", "sw-test"));
+
+        // 重复上报不会再次触发
+        let (opened, _) = StatusWatcherService::update(
+            "claude",
+            &provider,
+            &config,
+            Some(("major".into(), "Outage".into())),
+        );
+        assert!(opened.is_none());
+
+        let (_, resolved) = StatusWatcherService::update("claude", &provider, &config, None);
+        assert_eq!(resolved.unwrap().indicator, "major");
+        assert!(!StatusWatcherService::is_deprioritized("claude", "sw-test"));
+    }
+}
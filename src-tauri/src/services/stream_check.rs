@@ -144,8 +144,9 @@ impl StreamCheckService {
             .extract_auth(provider)
             .ok_or_else(|| AppError::Message("API Key not found".to_string()))?;
 
-        // 使用全局 HTTP 客户端（已包含代理配置）
-        let client = crate::proxy::http_client::get();
+        // 使用供应商的 HTTP 客户端（已包含全局或供应商专用代理配置）
+        let client =
+            crate::proxy::http_client::get_for_provider(provider).map_err(AppError::Message)?;
         let request_timeout = std::time::Duration::from_secs(config.timeout_secs);
 
         let model_to_test = Self::resolve_test_model(app_type, provider, config);
@@ -464,7 +465,7 @@ impl StreamCheckService {
         }
     }
 
-    fn resolve_test_model(
+    pub(crate) fn resolve_test_model(
         app_type: &AppType,
         provider: &Provider,
         config: &StreamCheckConfig,
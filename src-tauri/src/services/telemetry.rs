@@ -0,0 +1,225 @@
+//! 匿名使用统计（需用户主动开启）
+//!
+//! 开启且配置了上报地址后，每天最多上报一次粗粒度的计数：
+//! - 各应用的供应商数量区间、是否启用代理与自动故障转移
+//! - 可选功能是否启用（Webhook、自动回切、告警规则、消费提醒、A/B 实验）
+//! - 最近 7 天请求数与错误分类（限流 / 其他错误）的数量区间
+//!
+//! 报告中只有随机生成的安装 ID，不包含提示词、响应内容、密钥、供应商名称或地址。
+//! 可通过 `preview_telemetry_report` 命令查看将要发送的完整内容。
+
+use crate::database::Database;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 报告格式版本
+const SCHEMA_VERSION: u32 = 1;
+/// 统计的请求时间范围（天）
+const PERIOD_DAYS: i64 = 7;
+/// 两次上报的最小间隔
+const REPORT_INTERVAL_SECS: i64 = 24 * 60 * 60;
+/// 调度器检查间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const APP_TYPES: [&str; 3] = ["claude", "codex", "gemini"];
+
+/// 使用统计配置（存储在 settings 表中）
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryConfig {
+    /// 是否开启（默认关闭）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 上报地址（为空时不发送）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// 随机安装 ID（首次开启时生成，与设备信息无关）
+    #[serde(default)]
+    pub install_id: String,
+    /// 上次成功上报时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_sent_at: Option<i64>,
+}
+
+/// 单个应用的功能使用情况
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppUsage {
+    /// 供应商数量区间
+    pub providers: &'static str,
+    pub proxy_enabled: bool,
+    pub auto_failover: bool,
+    /// 请求数区间
+    pub requests: &'static str,
+    /// 错误分类 → 数量区间
+    pub errors: BTreeMap<&'static str, &'static str>,
+}
+
+/// 上报内容
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryReport {
+    pub schema_version: u32,
+    pub install_id: String,
+    pub app_version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub period_days: i64,
+    pub apps: BTreeMap<&'static str, AppUsage>,
+    /// 可选功能 → 是否启用
+    pub features: BTreeMap<&'static str, bool>,
+}
+
+/// 把数量归入区间，避免上报精确值
+fn bucket(count: u64) -> &'static str {
+    match count {
+        0 => "0",
+        1..=10 => "1-10",
+        11..=100 => "11-100",
+        101..=1000 => "101-1000",
+        _ => "1000+",
+    }
+}
+
+/// 生成上报内容
+pub async fn build_report(db: &Database, install_id: &str) -> Result<TelemetryReport, AppError> {
+    let since = chrono::Utc::now().timestamp() - PERIOD_DAYS * 24 * 60 * 60;
+    let mut apps = BTreeMap::new();
+    for app in APP_TYPES {
+        let providers = db.get_all_providers(app)?.len() as u64;
+        let proxy = db.get_proxy_config_for_app(app).await?;
+        let stats = db.get_recent_provider_stats(app, since)?;
+        let requests: u64 = stats.iter().map(|s| s.requests).sum();
+        let errors: u64 = stats.iter().map(|s| s.errors).sum();
+        let rate_limited: u64 = stats.iter().map(|s| s.rate_limited).sum();
+        apps.insert(
+            app,
+            AppUsage {
+                providers: bucket(providers),
+                proxy_enabled: proxy.enabled,
+                auto_failover: proxy.auto_failover_enabled,
+                requests: bucket(requests),
+                errors: BTreeMap::from([
+                    ("rateLimited", bucket(rate_limited)),
+                    ("other", bucket(errors.saturating_sub(rate_limited))),
+                ]),
+            },
+        );
+    }
+
+    let features = BTreeMap::from([
+        (
+            "webhooks",
+            db.get_webhook_config()?.endpoints.iter().any(|e| e.enabled),
+        ),
+        ("failback", db.get_failback_config()?.enabled),
+        (
+            "alertRules",
+            db.get_alert_rules()?.iter().any(|r| r.enabled),
+        ),
+        ("costAlert", db.get_cost_alert_config()?.enabled),
+        ("experiments", !db.get_experiments()?.is_empty()),
+    ]);
+
+    Ok(TelemetryReport {
+        schema_version: SCHEMA_VERSION,
+        install_id: install_id.to_string(),
+        app_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        period_days: PERIOD_DAYS,
+        apps,
+        features,
+    })
+}
+
+async fn send(db: &Database, config: &TelemetryConfig, endpoint: &str) -> Result<(), String> {
+    let report = build_report(db, &config.install_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let response = crate::proxy::http_client::get()
+        .post(endpoint)
+        .timeout(REQUEST_TIMEOUT)
+        .json(&report)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+/// 匿名使用统计服务
+pub struct TelemetryService;
+
+impl TelemetryService {
+    /// 启动后台上报任务（配置在每次检查时重新读取，未开启时不发送任何内容）
+    pub fn start(db: Arc<Database>) {
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+
+                let Ok(mut config) = db.get_telemetry_config() else {
+                    continue;
+                };
+                let Some(endpoint) = config
+                    .endpoint
+                    .clone()
+                    .filter(|e| config.enabled && !e.trim().is_empty())
+                else {
+                    continue;
+                };
+                let now = chrono::Utc::now().timestamp();
+                if config
+                    .last_sent_at
+                    .is_some_and(|t| now - t < REPORT_INTERVAL_SECS)
+                {
+                    continue;
+                }
+
+                match send(&db, &config, endpoint.trim()).await {
+                    Ok(()) => {
+                        config.last_sent_at = Some(now);
+                        if let Err(e) = db.set_telemetry_config(&config) {
+                            log::debug!("[Telemetry] 保存上报时间失败: {e}");
+                        }
+                    }
+                    Err(e) => log::debug!("[Telemetry] 上报失败: {e}"),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_counts() {
+        assert_eq!(bucket(0), "0");
+        assert_eq!(bucket(10), "1-10");
+        assert_eq!(bucket(11), "11-100");
+        assert_eq!(bucket(5000), "1000+");
+    }
+
+    #[tokio::test]
+    async fn report_contains_only_coarse_counters() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let report = build_report(&db, "install-1").await?;
+        assert_eq!(report.apps.len(), 3);
+        assert_eq!(report.apps["claude"].requests, "0");
+        assert_eq!(report.features.get("webhooks"), Some(&false));
+
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["installId"], "install-1");
+        assert_eq!(value["apps"]["codex"]["errors"]["rateLimited"], "0");
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+//! 使用记录导出
+//!
+//! 将指定时间范围内的请求记录导出为 CSV 或 JSON，用于报销与团队用量汇报。
+
+use crate::database::Database;
+use crate::error::AppError;
+use crate::services::usage_stats::{LogFilters, RequestLogDetail};
+use chrono::{Local, TimeZone};
+use serde::Deserialize;
+use std::path::Path;
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageReportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "time",
+    "request_id",
+    "app_type",
+    "provider_id",
+    "provider_name",
+    "model",
+    "status_code",
+    "is_streaming",
+    "input_tokens",
+    "output_tokens",
+    "cache_read_tokens",
+    "cache_creation_tokens",
+    "input_cost_usd",
+    "output_cost_usd",
+    "cache_read_cost_usd",
+    "cache_creation_cost_usd",
+    "total_cost_usd",
+    "latency_ms",
+    "first_token_ms",
+    "output_tokens_per_second",
+    "error_message",
+];
+
+/// 导出使用记录到文件，返回导出的记录数
+pub fn export_usage_report(
+    db: &Database,
+    filters: &LogFilters,
+    format: UsageReportFormat,
+    path: &Path,
+) -> Result<usize, AppError> {
+    // 复用请求日志查询（含历史记录的成本回填），按时间正序导出
+    let mut logs = db.get_request_logs(filters, 0, u32::MAX)?.data;
+    logs.reverse();
+
+    let content = match format {
+        UsageReportFormat::Csv => render_csv(&logs),
+        UsageReportFormat::Json => serde_json::to_string_pretty(&logs)
+            .map_err(|e| AppError::JsonSerialize { source: e })?,
+    };
+    std::fs::write(path, content).map_err(|e| AppError::io(path, e))?;
+
+    log::info!("已导出 {} 条使用记录到 {}", logs.len(), path.display());
+    Ok(logs.len())
+}
+
+/// 渲染 CSV（RFC 4180 转义）
+fn render_csv(logs: &[RequestLogDetail]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+
+    for log in logs {
+        let time = Local
+            .timestamp_opt(log.created_at, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let fields = [
+            time,
+            log.request_id.clone(),
+            log.app_type.clone(),
+            log.provider_id.clone(),
+            log.provider_name.clone().unwrap_or_default(),
+            log.model.clone(),
+            log.status_code.to_string(),
+            log.is_streaming.to_string(),
+            log.input_tokens.to_string(),
+            log.output_tokens.to_string(),
+            log.cache_read_tokens.to_string(),
+            log.cache_creation_tokens.to_string(),
+            log.input_cost_usd.clone(),
+            log.output_cost_usd.clone(),
+            log.cache_read_cost_usd.clone(),
+            log.cache_creation_cost_usd.clone(),
+            log.total_cost_usd.clone(),
+            log.latency_ms.to_string(),
+            log.first_token_ms
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+            log.output_tokens_per_second
+                .map(|v| format!("{v:.1}"))
+                .unwrap_or_default(),
+            log.error_message.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| escape_csv(f)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::lock_conn;
+    use rusqlite::params;
+
+    #[test]
+    fn exports_csv_in_chronological_order() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, ts, error) in [
+                ("req2", 2000, Some("bad, \"quoted\"")),
+                ("req1", 1000, None),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, error_message, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![id, "p1", "claude", "claude-3", 100, 50, "0.01", 100, 200, error, ts],
+                )?;
+            }
+        }
+
+        let dir = tempfile::tempdir().map_err(|e| AppError::io("tempdir", e))?;
+        let path = dir.path().join("usage.csv");
+        let count =
+            export_usage_report(&db, &LogFilters::default(), UsageReportFormat::Csv, &path)?;
+        assert_eq!(count, 2);
+
+        let content = std::fs::read_to_string(&path).map_err(|e| AppError::io(&path, e))?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].starts_with("time,request_id,"));
+        assert!(lines[1].contains(",req1,"));
+        assert!(lines[2].ends_with(",\"bad, \"\"quoted\"\"\""));
+        Ok(())
+    }
+}
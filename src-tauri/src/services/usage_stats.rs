@@ -4,6 +4,8 @@
 
 use crate::database::{lock_conn, Database};
 use crate::error::AppError;
+use crate::provider::{QuotaCalendar, QuotaPeriod};
+use crate::services::quota_calendar::{self, QuotaWindow};
 use chrono::{Local, TimeZone};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
@@ -49,6 +51,8 @@ pub struct ProviderStats {
     pub total_cost: String,
     pub success_rate: f32,
     pub avg_latency_ms: u64,
+    /// 平均流式输出速度（tokens/s，无流式记录时为空）
+    pub avg_output_tokens_per_second: Option<f64>,
 }
 
 /// 模型统计
@@ -62,14 +66,78 @@ pub struct ModelStats {
     pub avg_cost_per_request: String,
 }
 
+/// 用量序列的时间粒度
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageInterval {
+    Hour,
+    #[default]
+    Day,
+}
+
+/// 用量序列的分组维度
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroupBy {
+    /// 不分组（每个时间桶一条）
+    #[default]
+    None,
+    Provider,
+    Model,
+}
+
+/// 用量序列查询参数
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSeriesQuery {
+    pub start_date: Option<i64>,
+    pub end_date: Option<i64>,
+    #[serde(default)]
+    pub interval: UsageInterval,
+    #[serde(default)]
+    pub group_by: UsageGroupBy,
+    pub app_type: Option<String>,
+}
+
+/// 用量序列中的一个数据点（时间桶 × 分组）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSeriesPoint {
+    /// 时间桶起点（本地时间，按日为 `YYYY-MM-DD`，按小时为 `YYYY-MM-DDTHH:00:00`）
+    pub bucket: String,
+    /// 分组键（provider_id 或模型名，不分组时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// 供应商名称（仅按供应商分组时）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_name: Option<String>,
+    pub request_count: u64,
+    pub error_count: u64,
+    /// 错误率（百分比）
+    pub error_rate: f32,
+    pub total_tokens: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub total_cost: String,
+}
+
 /// 请求日志过滤器
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LogFilters {
     pub app_type: Option<String>,
     pub provider_name: Option<String>,
+    /// 精确匹配供应商 ID
+    pub provider_id: Option<String>,
+    /// 请求 ID（前缀匹配）
+    pub request_id: Option<String>,
     pub model: Option<String>,
     pub status_code: Option<u16>,
+    /// 状态码范围（闭区间），如 400~599 只看失败请求
+    pub status_min: Option<u16>,
+    pub status_max: Option<u16>,
     pub start_date: Option<i64>,
     pub end_date: Option<i64>,
 }
@@ -107,6 +175,8 @@ pub struct RequestLogDetail {
     pub latency_ms: u64,
     pub first_token_ms: Option<u64>,
     pub duration_ms: Option<u64>,
+    /// 流式输出速度（tokens/s，首 token 之后的生成阶段）
+    pub output_tokens_per_second: Option<f64>,
     pub status_code: u16,
     pub error_message: Option<String>,
     pub created_at: i64,
@@ -291,6 +361,88 @@ impl Database {
         Ok(stats)
     }
 
+    /// 获取按时间桶（及供应商 / 模型）聚合的用量序列
+    ///
+    /// 没有请求的时间桶不会出现在结果中；结果按时间桶、分组键排序
+    pub fn get_usage_series(
+        &self,
+        query: &UsageSeriesQuery,
+    ) -> Result<Vec<UsageSeriesPoint>, AppError> {
+        let conn = lock_conn!(self.conn);
+
+        let (bucket_format, default_span) = match query.interval {
+            UsageInterval::Hour => ("%Y-%m-%dT%H:00:00", 24 * 60 * 60),
+            UsageInterval::Day => ("%Y-%m-%d", 30 * 24 * 60 * 60),
+        };
+        let end_ts = query.end_date.unwrap_or_else(|| Local::now().timestamp());
+        let start_ts = query.start_date.unwrap_or(end_ts - default_span);
+
+        let group_expr = match query.group_by {
+            UsageGroupBy::None => "NULL",
+            UsageGroupBy::Provider => "l.provider_id",
+            UsageGroupBy::Model => "l.model",
+        };
+        let group_name_expr = match query.group_by {
+            UsageGroupBy::Provider => "MAX(p.name)",
+            _ => "NULL",
+        };
+
+        let sql = format!(
+            "SELECT
+                strftime(?1, l.created_at, 'unixepoch', 'localtime') as bucket,
+                {group_expr} as group_key,
+                {group_name_expr} as group_name,
+                COUNT(*) as request_count,
+                COALESCE(SUM(CASE WHEN l.status_code >= 400 THEN 1 ELSE 0 END), 0) as error_count,
+                COALESCE(SUM(l.input_tokens), 0),
+                COALESCE(SUM(l.output_tokens), 0),
+                COALESCE(SUM(l.cache_read_tokens), 0),
+                COALESCE(SUM(l.cache_creation_tokens), 0),
+                COALESCE(SUM(CAST(l.total_cost_usd AS REAL)), 0)
+             FROM proxy_request_logs l
+             LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
+             WHERE l.created_at >= ?2 AND l.created_at <= ?3
+               AND (?4 IS NULL OR l.app_type = ?4)
+             GROUP BY bucket, group_key
+             ORDER BY bucket ASC, group_key ASC"
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(
+            params![bucket_format, start_ts, end_ts, query.app_type],
+            |row| {
+                let request_count = row.get::<_, i64>(3)? as u64;
+                let error_count = row.get::<_, i64>(4)? as u64;
+                let input_tokens = row.get::<_, i64>(5)? as u64;
+                let output_tokens = row.get::<_, i64>(6)? as u64;
+                Ok(UsageSeriesPoint {
+                    bucket: row.get(0)?,
+                    group: row.get(1)?,
+                    group_name: row.get(2)?,
+                    request_count,
+                    error_count,
+                    error_rate: if request_count > 0 {
+                        (error_count as f32 / request_count as f32) * 100.0
+                    } else {
+                        0.0
+                    },
+                    total_tokens: input_tokens + output_tokens,
+                    input_tokens,
+                    output_tokens,
+                    cache_read_tokens: row.get::<_, i64>(7)? as u64,
+                    cache_creation_tokens: row.get::<_, i64>(8)? as u64,
+                    total_cost: format!("{:.6}", row.get::<_, f64>(9)?),
+                })
+            },
+        )?;
+
+        let mut points = Vec::new();
+        for row in rows {
+            points.push(row?);
+        }
+        Ok(points)
+    }
+
     /// 获取 Provider 统计
     pub fn get_provider_stats(&self) -> Result<Vec<ProviderStats>, AppError> {
         let conn = lock_conn!(self.conn);
@@ -302,7 +454,8 @@ impl Database {
                 COALESCE(SUM(l.input_tokens + l.output_tokens), 0) as total_tokens,
                 COALESCE(SUM(CAST(l.total_cost_usd AS REAL)), 0) as total_cost,
                 COALESCE(SUM(CASE WHEN l.status_code >= 200 AND l.status_code < 300 THEN 1 ELSE 0 END), 0) as success_count,
-                COALESCE(AVG(l.latency_ms), 0) as avg_latency
+                COALESCE(AVG(l.latency_ms), 0) as avg_latency,
+                AVG(l.output_tokens_per_second) as avg_tps
              FROM proxy_request_logs l
              LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
              GROUP BY l.provider_id, l.app_type
@@ -328,6 +481,7 @@ impl Database {
                 total_cost: format!("{:.6}", row.get::<_, f64>(4)?),
                 success_rate,
                 avg_latency_ms: row.get::<_, f64>(6)? as u64,
+                avg_output_tokens_per_second: row.get(7)?,
             })
         })?;
 
@@ -399,6 +553,18 @@ impl Database {
             conditions.push("p.name LIKE ?");
             params.push(Box::new(format!("%{provider_name}%")));
         }
+        if let Some(ref provider_id) = filters.provider_id {
+            conditions.push("l.provider_id = ?");
+            params.push(Box::new(provider_id.clone()));
+        }
+        if let Some(ref request_id) = filters.request_id {
+            conditions.push("l.request_id LIKE ? ESCAPE '\\'");
+            let escaped = request_id
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            params.push(Box::new(format!("{escaped}%")));
+        }
         if let Some(ref model) = filters.model {
             conditions.push("l.model LIKE ?");
             params.push(Box::new(format!("%{model}%")));
@@ -407,6 +573,14 @@ impl Database {
             conditions.push("l.status_code = ?");
             params.push(Box::new(status as i64));
         }
+        if let Some(min) = filters.status_min {
+            conditions.push("l.status_code >= ?");
+            params.push(Box::new(min as i64));
+        }
+        if let Some(max) = filters.status_max {
+            conditions.push("l.status_code <= ?");
+            params.push(Box::new(max as i64));
+        }
         if let Some(start) = filters.start_date {
             conditions.push("l.created_at >= ?");
             params.push(Box::new(start));
@@ -443,7 +617,7 @@ impl Database {
                     l.input_tokens, l.output_tokens, l.cache_read_tokens, l.cache_creation_tokens,
                     l.input_cost_usd, l.output_cost_usd, l.cache_read_cost_usd, l.cache_creation_cost_usd, l.total_cost_usd,
                     l.is_streaming, l.latency_ms, l.first_token_ms, l.duration_ms,
-                    l.status_code, l.error_message, l.created_at
+                    l.status_code, l.error_message, l.created_at, l.output_tokens_per_second
              FROM proxy_request_logs l
              LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
              {where_clause}
@@ -473,6 +647,7 @@ impl Database {
                 latency_ms: row.get::<_, i64>(15)? as u64,
                 first_token_ms: row.get::<_, Option<i64>>(16)?.map(|v| v as u64),
                 duration_ms: row.get::<_, Option<i64>>(17)?.map(|v| v as u64),
+                output_tokens_per_second: row.get(21)?,
                 status_code: row.get::<_, i64>(18)? as u16,
                 error_message: row.get(19)?,
                 created_at: row.get(20)?,
@@ -502,6 +677,203 @@ impl Database {
         })
     }
 
+    /// 获取 Provider 最近一次请求的状态与延迟
+    pub fn get_last_request(
+        &self,
+        app_type: &str,
+        provider_id: &str,
+    ) -> Result<Option<LastRequestInfo>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let result = conn.query_row(
+            "SELECT latency_ms, first_token_ms, status_code, created_at
+             FROM proxy_request_logs
+             WHERE app_type = ?1 AND provider_id = ?2
+             ORDER BY created_at DESC
+             LIMIT 1",
+            rusqlite::params![app_type, provider_id],
+            |row| {
+                Ok(LastRequestInfo {
+                    latency_ms: row.get::<_, i64>(0)? as u64,
+                    first_token_ms: row.get::<_, Option<i64>>(1)?.map(|v| v as u64),
+                    status_code: row.get::<_, i64>(2)? as u16,
+                    created_at: row.get(3)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(info) => Ok(Some(info)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(AppError::Database(e.to_string())),
+        }
+    }
+
+    /// 按 Provider 汇总累计请求指标（供 `/metrics` 导出）
+    ///
+    /// `ttft_buckets_ms` 为首字延迟直方图的桶上界（升序），返回各桶的累计计数
+    pub fn get_provider_metrics(
+        &self,
+        ttft_buckets_ms: &[u64],
+    ) -> Result<Vec<ProviderMetrics>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let bucket_columns: String = ttft_buckets_ms
+            .iter()
+            .map(|b| {
+                format!(", COALESCE(SUM(CASE WHEN first_token_ms <= {b} THEN 1 ELSE 0 END), 0)")
+            })
+            .collect();
+        let sql = format!(
+            "SELECT app_type, provider_id, COUNT(*),
+                    COALESCE(SUM(CASE WHEN status_code >= 400 THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0),
+                    COUNT(first_token_ms), COALESCE(SUM(first_token_ms), 0){bucket_columns}
+             FROM proxy_request_logs
+             GROUP BY app_type, provider_id
+             ORDER BY app_type, provider_id"
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            let ttft_buckets = (0..ttft_buckets_ms.len())
+                .map(|i| row.get::<_, i64>(7 + i).map(|v| v as u64))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(ProviderMetrics {
+                app_type: row.get(0)?,
+                provider_id: row.get(1)?,
+                requests: row.get::<_, i64>(2)? as u64,
+                errors: row.get::<_, i64>(3)? as u64,
+                cost_usd: row.get(4)?,
+                ttft_count: row.get::<_, i64>(5)? as u64,
+                ttft_sum_ms: row.get::<_, i64>(6)? as u64,
+                ttft_buckets,
+            })
+        })?;
+
+        let mut metrics = Vec::new();
+        for row in rows {
+            metrics.push(row?);
+        }
+        Ok(metrics)
+    }
+
+    /// 统计指定时间之后各 Provider 的请求结果（供应商评分使用）
+    pub fn get_recent_provider_stats(
+        &self,
+        app_type: &str,
+        since: i64,
+    ) -> Result<Vec<RecentProviderStats>, AppError> {
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn.prepare(
+            "SELECT provider_id, COUNT(*),
+                    COALESCE(SUM(CASE WHEN status_code >= 400 THEN 1 ELSE 0 END), 0),
+                    COALESCE(SUM(CASE WHEN status_code = 429 THEN 1 ELSE 0 END), 0),
+                    COALESCE(AVG(CASE WHEN status_code < 400 THEN COALESCE(first_token_ms, latency_ms) END), 0),
+                    COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+             FROM proxy_request_logs
+             WHERE app_type = ?1 AND created_at >= ?2
+             GROUP BY provider_id",
+        )?;
+        let rows = stmt.query_map(params![app_type, since], |row| {
+            Ok(RecentProviderStats {
+                provider_id: row.get(0)?,
+                requests: row.get::<_, i64>(1)? as u64,
+                errors: row.get::<_, i64>(2)? as u64,
+                rate_limited: row.get::<_, i64>(3)? as u64,
+                avg_latency_ms: row.get(4)?,
+                cost_usd: row.get(5)?,
+            })
+        })?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            stats.push(row?);
+        }
+        Ok(stats)
+    }
+
+    /// 统计各 Provider 在最近若干分钟内成功请求的延迟与首字延迟分位数
+    ///
+    /// 每个窗口（分钟）分别计算，结果按窗口升序、p50 延迟升序排列
+    pub fn get_provider_latency_percentiles(
+        &self,
+        windows_minutes: &[u32],
+    ) -> Result<Vec<ProviderLatencyPercentiles>, AppError> {
+        let Some(max_window) = windows_minutes.iter().copied().max() else {
+            return Ok(Vec::new());
+        };
+        let now = chrono::Utc::now().timestamp();
+        let since = now - i64::from(max_window) * 60;
+
+        let conn = lock_conn!(self.conn);
+        let mut stmt = conn.prepare(
+            "SELECT l.app_type, l.provider_id, p.name, l.latency_ms, l.first_token_ms, l.created_at
+             FROM proxy_request_logs l
+             LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
+             WHERE l.created_at >= ?1 AND l.status_code < 400",
+        )?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, i64>(3)? as u64,
+                row.get::<_, Option<i64>>(4)?.map(|v| v as u64),
+                row.get::<_, i64>(5)?,
+            ))
+        })?;
+
+        // (app_type, provider_id) -> (provider_name, [(latency, ttft, created_at)])
+        type Samples = (Option<String>, Vec<(u64, Option<u64>, i64)>);
+        let mut providers: HashMap<(String, String), Samples> = HashMap::new();
+        for row in rows {
+            let (app_type, provider_id, name, latency, ttft, created_at) = row?;
+            let entry = providers
+                .entry((app_type, provider_id))
+                .or_insert_with(|| (name, Vec::new()));
+            entry.1.push((latency, ttft, created_at));
+        }
+
+        let mut windows = windows_minutes.to_vec();
+        windows.sort_unstable();
+        windows.dedup();
+
+        let mut result = Vec::new();
+        for window in windows {
+            let window_since = now - i64::from(window) * 60;
+            let mut window_stats: Vec<ProviderLatencyPercentiles> = providers
+                .iter()
+                .filter_map(|((app_type, provider_id), (name, samples))| {
+                    let mut latencies = Vec::new();
+                    let mut ttfts = Vec::new();
+                    for (latency, ttft, created_at) in samples {
+                        if *created_at >= window_since {
+                            latencies.push(*latency);
+                            ttfts.extend(*ttft);
+                        }
+                    }
+                    let latency = LatencyPercentiles::from_samples(&mut latencies)?;
+                    Some(ProviderLatencyPercentiles {
+                        app_type: app_type.clone(),
+                        provider_id: provider_id.clone(),
+                        provider_name: name.clone().unwrap_or_else(|| "Unknown".to_string()),
+                        window_minutes: window,
+                        sample_count: latencies.len() as u64,
+                        latency,
+                        first_token: LatencyPercentiles::from_samples(&mut ttfts),
+                    })
+                })
+                .collect();
+            window_stats.sort_by(|a, b| {
+                a.latency
+                    .p50
+                    .cmp(&b.latency.p50)
+                    .then_with(|| a.provider_id.cmp(&b.provider_id))
+            });
+            result.extend(window_stats);
+        }
+        Ok(result)
+    }
+
     /// 获取单个请求详情
     pub fn get_request_detail(
         &self,
@@ -514,7 +886,7 @@ impl Database {
                     input_tokens, output_tokens, cache_read_tokens, cache_creation_tokens,
                     input_cost_usd, output_cost_usd, cache_read_cost_usd, cache_creation_cost_usd, total_cost_usd,
                     is_streaming, latency_ms, first_token_ms, duration_ms,
-                    status_code, error_message, created_at
+                    status_code, error_message, created_at, output_tokens_per_second
              FROM proxy_request_logs l
              LEFT JOIN providers p ON l.provider_id = p.id AND l.app_type = p.app_type
              WHERE l.request_id = ?",
@@ -539,6 +911,7 @@ impl Database {
                     latency_ms: row.get::<_, i64>(15)? as u64,
                     first_token_ms: row.get::<_, Option<i64>>(16)?.map(|v| v as u64),
                     duration_ms: row.get::<_, Option<i64>>(17)?.map(|v| v as u64),
+                    output_tokens_per_second: row.get(21)?,
                     status_code: row.get::<_, i64>(18)? as u16,
                     error_message: row.get(19)?,
                     created_at: row.get(20)?,
@@ -572,7 +945,7 @@ impl Database {
         let conn = lock_conn!(self.conn);
 
         // 获取 provider 的限额设置
-        let (limit_daily, limit_monthly) = conn
+        let (limit_daily, limit_monthly, token_limit_daily, token_limit_monthly, calendar) = conn
             .query_row(
                 "SELECT meta FROM providers WHERE id = ? AND app_type = ?",
                 params![provider_id, app_type],
@@ -592,40 +965,50 @@ impl Database {
                     .get("limitMonthlyUsd")
                     .and_then(|v| v.as_str())
                     .and_then(|s| s.parse::<f64>().ok());
-                (daily, monthly)
+                let daily_tokens = meta.get("limitDailyTokens").and_then(|v| v.as_u64());
+                let monthly_tokens = meta.get("limitMonthlyTokens").and_then(|v| v.as_u64());
+                let calendar = meta
+                    .get("quotaCalendar")
+                    .and_then(|v| serde_json::from_value::<QuotaCalendar>(v.clone()).ok())
+                    .unwrap_or_default();
+                (daily, monthly, daily_tokens, monthly_tokens, calendar)
             })
-            .unwrap_or((None, None));
+            .unwrap_or_default();
 
-        // 计算今日使用量
-        let daily_usage: f64 = conn
-            .query_row(
-                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
-             FROM proxy_request_logs
-             WHERE provider_id = ? AND app_type = ?
-               AND date(datetime(created_at, 'unixepoch', 'localtime')) = date('now', 'localtime')",
-                params![provider_id, app_type],
-                |row| row.get(0),
-            )
-            .unwrap_or(0.0);
+        // 按重置日历计算当前窗口
+        let windows = quota_calendar::current_windows(&calendar, chrono::Utc::now());
 
-        // 计算本月使用量
-        let monthly_usage: f64 = conn
-            .query_row(
-                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0)
+        let window_usage = |window: QuotaWindow| -> (f64, i64) {
+            conn.query_row(
+                "SELECT COALESCE(SUM(CAST(total_cost_usd AS REAL)), 0),
+                    COALESCE(SUM(input_tokens + output_tokens), 0)
              FROM proxy_request_logs
              WHERE provider_id = ? AND app_type = ?
-               AND strftime('%Y-%m', datetime(created_at, 'unixepoch', 'localtime')) = strftime('%Y-%m', 'now', 'localtime')",
-                params![provider_id, app_type],
-                |row| row.get(0),
+               AND created_at >= ? AND created_at < ?",
+                params![provider_id, app_type, window.start, window.end],
+                |row| Ok((row.get(0)?, row.get(1)?)),
             )
-            .unwrap_or(0.0);
+            .unwrap_or((0.0, 0))
+        };
+
+        // 计算当日 / 当前周期使用量（费用与 token）
+        let (daily_usage, daily_tokens) = window_usage(windows.daily);
+        let (monthly_usage, monthly_tokens) = window_usage(windows.period);
+        let daily_tokens = daily_tokens.max(0) as u64;
+        let monthly_tokens = monthly_tokens.max(0) as u64;
 
         let daily_exceeded = limit_daily
             .map(|limit| daily_usage >= limit)
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || token_limit_daily
+                .map(|limit| daily_tokens >= limit)
+                .unwrap_or(false);
         let monthly_exceeded = limit_monthly
             .map(|limit| monthly_usage >= limit)
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || token_limit_monthly
+                .map(|limit| monthly_tokens >= limit)
+                .unwrap_or(false);
 
         Ok(ProviderLimitStatus {
             provider_id: provider_id.to_string(),
@@ -635,10 +1018,100 @@ impl Database {
             monthly_usage: format!("{monthly_usage:.6}"),
             monthly_limit: limit_monthly.map(|l| format!("{l:.2}")),
             monthly_exceeded,
+            daily_tokens,
+            daily_token_limit: token_limit_daily,
+            monthly_tokens,
+            monthly_token_limit: token_limit_monthly,
+            period: calendar.period,
+            daily_resets_at: windows.daily.end,
+            monthly_resets_at: windows.period.end,
+        })
+    }
+}
+
+/// Provider 最近一次请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastRequestInfo {
+    pub latency_ms: u64,
+    pub first_token_ms: Option<u64>,
+    pub status_code: u16,
+    pub created_at: i64,
+}
+
+/// Provider 近期请求结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentProviderStats {
+    pub provider_id: String,
+    pub requests: u64,
+    /// 状态码 >= 400 的请求数
+    pub errors: u64,
+    /// 状态码 429 的请求数
+    pub rate_limited: u64,
+    /// 成功请求的平均延迟（流式取首字延迟）
+    pub avg_latency_ms: f64,
+    pub cost_usd: f64,
+}
+
+/// 延迟分位数（毫秒）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LatencyPercentiles {
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+impl LatencyPercentiles {
+    /// 按最近秩法计算分位数，样本为空时返回 None
+    fn from_samples(samples: &mut [u64]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        let rank = |p: f64| {
+            let index = (p * samples.len() as f64).ceil() as usize;
+            samples[index.clamp(1, samples.len()) - 1]
+        };
+        Some(Self {
+            p50: rank(0.50),
+            p95: rank(0.95),
+            p99: rank(0.99),
         })
     }
 }
 
+/// Provider 在时间窗口内的延迟分位数
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderLatencyPercentiles {
+    pub app_type: String,
+    pub provider_id: String,
+    pub provider_name: String,
+    pub window_minutes: u32,
+    /// 窗口内成功请求数
+    pub sample_count: u64,
+    /// 总延迟
+    pub latency: LatencyPercentiles,
+    /// 首字延迟（窗口内没有流式请求时为空）
+    pub first_token: Option<LatencyPercentiles>,
+}
+
+/// Provider 累计请求指标
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderMetrics {
+    pub app_type: String,
+    pub provider_id: String,
+    pub requests: u64,
+    /// 状态码 >= 400 的请求数
+    pub errors: u64,
+    pub cost_usd: f64,
+    /// 有首字延迟记录的请求数
+    pub ttft_count: u64,
+    pub ttft_sum_ms: u64,
+    /// 与桶上界一一对应的累计计数
+    pub ttft_buckets: Vec<u64>,
+}
+
 /// Provider 限额状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -650,6 +1123,18 @@ pub struct ProviderLimitStatus {
     pub monthly_usage: String,
     pub monthly_limit: Option<String>,
     pub monthly_exceeded: bool,
+    /// 今日 token 用量（输入 + 输出）
+    pub daily_tokens: u64,
+    pub daily_token_limit: Option<u64>,
+    /// 本月 token 用量（输入 + 输出）
+    pub monthly_tokens: u64,
+    pub monthly_token_limit: Option<u64>,
+    /// 长周期类型（`monthly*` 字段按此周期统计）
+    pub period: QuotaPeriod,
+    /// 每日限额下次重置时间（Unix 秒）
+    pub daily_resets_at: i64,
+    /// 长周期限额下次重置时间（Unix 秒）
+    pub monthly_resets_at: i64,
 }
 
 #[derive(Clone)]
@@ -876,6 +1361,173 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_usage_series_grouped_by_provider() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, provider, status, ts) in [
+                ("req1", "p1", 200, 1000),
+                ("req2", "p1", 500, 1100),
+                ("req3", "p2", 200, 1200),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![id, provider, "claude", "claude-3", 100, 50, "0.01", 100, status, ts],
+                )?;
+            }
+        }
+
+        let query = UsageSeriesQuery {
+            start_date: Some(0),
+            end_date: Some(2000),
+            interval: UsageInterval::Hour,
+            group_by: UsageGroupBy::Provider,
+            app_type: Some("claude".to_string()),
+        };
+        let series = db.get_usage_series(&query)?;
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].group.as_deref(), Some("p1"));
+        assert_eq!(series[0].request_count, 2);
+        assert_eq!(series[0].error_count, 1);
+        assert_eq!(series[0].error_rate, 50.0);
+        assert_eq!(series[0].total_tokens, 300);
+        assert_eq!(series[1].group.as_deref(), Some("p2"));
+
+        let totals = db.get_usage_series(&UsageSeriesQuery {
+            group_by: UsageGroupBy::None,
+            ..query
+        })?;
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].request_count, 3);
+        assert!(totals[0].group.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_request_logs_filters() -> Result<(), AppError> {
+        let db = Database::memory()?;
+
+        {
+            let conn = lock_conn!(db.conn);
+            for (id, provider, status, ts) in [
+                ("req_a1", "p1", 200, 1000),
+                ("req_a2", "p1", 429, 1100),
+                ("req_b1", "p2", 500, 1200),
+                ("reqXb2", "p2", 200, 1300),
+            ] {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, status_code, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![id, provider, "claude", "claude-3", 100, 50, "0.01", 100, status, ts],
+                )?;
+            }
+        }
+
+        let failed = db.get_request_logs(
+            &LogFilters {
+                status_min: Some(400),
+                status_max: Some(599),
+                ..Default::default()
+            },
+            0,
+            10,
+        )?;
+        assert_eq!(failed.total, 2);
+        assert_eq!(failed.data[0].request_id, "req_b1");
+
+        let by_provider = db.get_request_logs(
+            &LogFilters {
+                provider_id: Some("p1".to_string()),
+                start_date: Some(1050),
+                ..Default::default()
+            },
+            0,
+            10,
+        )?;
+        assert_eq!(by_provider.total, 1);
+        assert_eq!(by_provider.data[0].request_id, "req_a2");
+
+        // `_` 按字面匹配，不作为通配符
+        let by_id = db.get_request_logs(
+            &LogFilters {
+                request_id: Some("req_".to_string()),
+                ..Default::default()
+            },
+            0,
+            1,
+        )?;
+        assert_eq!(by_id.total, 3);
+        assert_eq!(by_id.data.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_provider_latency_percentiles() -> Result<(), AppError> {
+        let db = Database::memory()?;
+        let now = chrono::Utc::now().timestamp();
+
+        {
+            let conn = lock_conn!(db.conn);
+            let mut rows: Vec<(&str, i64, Option<i64>, i64, i64)> = (1..=100)
+                .map(|i| ("slow", i * 10, Some(i), 200, now - 60))
+                .collect();
+            rows.push(("fast", 50, None, 200, now - 60));
+            // 窗口外与失败的请求不计入
+            rows.push(("fast", 9_000, None, 200, now - 7200));
+            rows.push(("fast", 9_000, None, 500, now - 60));
+            for (i, (provider, latency, ttft, status, ts)) in rows.into_iter().enumerate() {
+                conn.execute(
+                    "INSERT INTO proxy_request_logs (
+                        request_id, provider_id, app_type, model,
+                        input_tokens, output_tokens, total_cost_usd,
+                        latency_ms, first_token_ms, status_code, created_at
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        format!("req-{i}"),
+                        provider,
+                        "claude",
+                        "claude-3",
+                        100,
+                        50,
+                        "0.01",
+                        latency,
+                        ttft,
+                        status,
+                        ts
+                    ],
+                )?;
+            }
+        }
+
+        let stats = db.get_provider_latency_percentiles(&[60])?;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].provider_id, "fast");
+        assert_eq!(stats[0].sample_count, 1);
+        assert_eq!(stats[0].first_token, None);
+        assert_eq!(
+            stats[1].latency,
+            LatencyPercentiles {
+                p50: 500,
+                p95: 950,
+                p99: 990
+            }
+        );
+        assert_eq!(stats[1].first_token.map(|t| t.p95), Some(95));
+
+        Ok(())
+    }
+
     #[test]
     fn test_get_model_stats() -> Result<(), AppError> {
         let db = Database::memory()?;
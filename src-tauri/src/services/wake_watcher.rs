@@ -0,0 +1,183 @@
+//! 睡眠唤醒与网络切换处理
+//!
+//! 笔记本合盖唤醒或切换网络后，连接池中保留的上游连接往往已经失效，
+//! 第一个请求会卡到超时才失败。后台任务定时检测：
+//! - 墙钟时间跳变（两次检测的间隔远大于预期）→ 视为从睡眠中唤醒
+//! - 本机出口地址变化 → 视为网络切换
+//!
+//! 检测到后重建全局 HTTP 客户端（丢弃失效连接），向前端发送 `system-resumed` 事件，
+//! 并对已启用代理的应用的当前供应商重新执行一次健康检查（结果通过 `provider-wake-probe` 事件上报）。
+
+use serde::Serialize;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tauri::Emitter;
+
+use crate::app_config::AppType;
+use crate::database::Database;
+use crate::services::stream_check::{StreamCheckResult, StreamCheckService};
+
+/// 检测间隔
+const TICK_INTERVAL: Duration = Duration::from_secs(10);
+/// 墙钟时间超出预期多久视为发生过睡眠
+const SLEEP_THRESHOLD: Duration = Duration::from_secs(30);
+
+/// 恢复原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResumeReason {
+    Wake,
+    NetworkChange,
+}
+
+/// `system-resumed` 事件内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeEvent {
+    pub reason: ResumeReason,
+    /// 估算的睡眠时长（秒，仅唤醒时有值）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slept_secs: Option<u64>,
+}
+
+/// `provider-wake-probe` 事件内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WakeProbeEvent {
+    pub app_type: String,
+    pub provider_id: String,
+    pub result: StreamCheckResult,
+}
+
+/// 根据两次检测之间的墙钟间隔判断是否发生过睡眠，返回估算的睡眠时长
+pub(crate) fn detect_sleep(wall_elapsed: Duration, expected: Duration) -> Option<Duration> {
+    let slept = wall_elapsed.checked_sub(expected)?;
+    (slept >= SLEEP_THRESHOLD).then_some(slept)
+}
+
+/// 获取本机默认出口地址（UDP connect 不会实际发送数据）
+fn outbound_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("1.1.1.1:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip())
+}
+
+/// 睡眠唤醒 / 网络切换监控服务
+pub struct WakeWatcherService;
+
+impl WakeWatcherService {
+    /// 启动后台监控任务
+    pub fn start(db: Arc<Database>, app_handle: tauri::AppHandle) {
+        tauri::async_runtime::spawn(async move {
+            let mut last_tick = SystemTime::now();
+            let mut last_ip = outbound_ip();
+
+            loop {
+                tokio::time::sleep(TICK_INTERVAL).await;
+
+                let now = SystemTime::now();
+                let wall_elapsed = now.duration_since(last_tick).unwrap_or_default();
+                last_tick = now;
+
+                let ip = outbound_ip();
+                let event = if let Some(slept) = detect_sleep(wall_elapsed, TICK_INTERVAL) {
+                    Some(ResumeEvent {
+                        reason: ResumeReason::Wake,
+                        slept_secs: Some(slept.as_secs()),
+                    })
+                } else if ip.is_some() && ip != last_ip {
+                    Some(ResumeEvent {
+                        reason: ResumeReason::NetworkChange,
+                        slept_secs: None,
+                    })
+                } else {
+                    None
+                };
+                last_ip = ip;
+
+                if let Some(event) = event {
+                    Self::handle_resume(&db, &app_handle, event).await;
+                }
+            }
+        });
+    }
+
+    async fn handle_resume(db: &Database, app_handle: &tauri::AppHandle, event: ResumeEvent) {
+        log::info!(
+            "[WakeWatcher] 检测到 {:?}（睡眠 {:?} 秒），重建上游连接",
+            event.reason,
+            event.slept_secs
+        );
+
+        let proxy_url = crate::proxy::http_client::get_current_proxy_url();
+        if let Err(e) = crate::proxy::http_client::update_proxy(proxy_url.as_deref()) {
+            log::warn!("[WakeWatcher] 重建 HTTP 客户端失败: {e}");
+        }
+        crate::proxy::http_client::clear_provider_clients();
+
+        let _ = app_handle.emit("system-resumed", &event);
+
+        let check_config = db.get_stream_check_config().unwrap_or_default();
+        for app_type in [AppType::Claude, AppType::Codex, AppType::Gemini] {
+            let app_str = app_type.as_str();
+            let enabled = db
+                .get_proxy_config_for_app(app_str)
+                .await
+                .map(|c| c.enabled)
+                .unwrap_or(false);
+            if !enabled {
+                continue;
+            }
+
+            let Ok(Some(provider_id)) =
+                crate::settings::get_effective_current_provider(db, &app_type)
+            else {
+                continue;
+            };
+            let Ok(Some(provider)) = db.get_provider_by_id(&provider_id, app_str) else {
+                continue;
+            };
+
+            let result =
+                match StreamCheckService::check_with_retry(&app_type, &provider, &check_config)
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(e) => {
+                        log::warn!("[WakeWatcher] {app_str} 供应商 {provider_id} 探测失败: {e}");
+                        continue;
+                    }
+                };
+
+            log::info!(
+                "[WakeWatcher] {app_str} 供应商 {provider_id} 唤醒后探测: {}",
+                result.message
+            );
+            let _ = app_handle.emit(
+                "provider-wake-probe",
+                WakeProbeEvent {
+                    app_type: app_str.to_string(),
+                    provider_id,
+                    result,
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_sleep_only_past_threshold() {
+        let tick = Duration::from_secs(10);
+        assert_eq!(detect_sleep(Duration::from_secs(11), tick), None);
+        assert_eq!(detect_sleep(Duration::from_secs(5), tick), None);
+        assert_eq!(
+            detect_sleep(Duration::from_secs(130), tick),
+            Some(Duration::from_secs(120))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use crate::database::Database;
 use crate::services::ProxyService;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+
+/// 无窗口模式（`--daemon`）下的应用状态，供代理内部（如管理 API）访问
+static HEADLESS_STATE: OnceLock<Arc<AppState>> = OnceLock::new();
 
 /// 全局应用状态
 pub struct AppState {
@@ -16,3 +19,13 @@ impl AppState {
         Self { db, proxy_service }
     }
 }
+
+/// 注册无窗口模式的应用状态（GUI 模式下通过 AppHandle 获取，无需注册）
+pub fn set_headless_state(state: Arc<AppState>) {
+    let _ = HEADLESS_STATE.set(state);
+}
+
+/// 无窗口模式的应用状态
+pub fn headless_state() -> Option<Arc<AppState>> {
+    HEADLESS_STATE.get().cloned()
+}
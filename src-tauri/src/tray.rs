@@ -231,25 +231,37 @@ pub fn apply_tray_policy(app: &tauri::AppHandle, dock_visible: bool) {
     }
 }
 
+/// 显示并聚焦主窗口
+///
+/// 统一处理从托盘隐藏后的恢复：Windows 恢复任务栏图标，macOS 恢复 Dock 显示。
+/// 托盘菜单、单实例回调、深链接与 macOS Reopen 事件共用此逻辑。
+pub fn show_main_window(app: &tauri::AppHandle) -> bool {
+    let Some(window) = app.get_webview_window("main") else {
+        log::warn!("未找到主窗口，无法聚焦");
+        return false;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = window.set_skip_taskbar(false);
+    }
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+    #[cfg(target_os = "macos")]
+    {
+        apply_tray_policy(app, true);
+    }
+    true
+}
+
 /// 处理托盘菜单事件
 pub fn handle_tray_menu_event(app: &tauri::AppHandle, event_id: &str) {
     log::info!("处理托盘菜单事件: {event_id}");
 
     match event_id {
         "show_main" => {
-            if let Some(window) = app.get_webview_window("main") {
-                #[cfg(target_os = "windows")]
-                {
-                    let _ = window.set_skip_taskbar(false);
-                }
-                let _ = window.unminimize();
-                let _ = window.show();
-                let _ = window.set_focus();
-                #[cfg(target_os = "macos")]
-                {
-                    apply_tray_policy(app, true);
-                }
-            }
+            show_main_window(app);
         }
         "quit" => {
             log::info!("退出应用");
@@ -430,6 +430,122 @@ export const providerPresets: ProviderPreset[] = [
     icon: "openrouter",
     iconColor: "#6566F1",
   },
+  {
+    name: "Ollama (Local)",
+    websiteUrl: "https://ollama.com",
+    settingsConfig: {
+      env: {
+        ANTHROPIC_BASE_URL: "http://localhost:11434",
+        ANTHROPIC_AUTH_TOKEN: "ollama",
+        ANTHROPIC_MODEL: "${MODEL}",
+        ANTHROPIC_DEFAULT_HAIKU_MODEL: "${MODEL}",
+        ANTHROPIC_DEFAULT_SONNET_MODEL: "${MODEL}",
+        ANTHROPIC_DEFAULT_OPUS_MODEL: "${MODEL}",
+      },
+    },
+    category: "custom",
+    templateValues: {
+      MODEL: {
+        label: "Model",
+        placeholder: "qwen3-coder",
+        defaultValue: "qwen3-coder",
+        editorValue: "qwen3-coder",
+      },
+    },
+    icon: "ollama",
+    iconColor: "#000000",
+  },
+  {
+    name: "LM Studio (Local)",
+    websiteUrl: "https://lmstudio.ai",
+    settingsConfig: {
+      env: {
+        ANTHROPIC_BASE_URL: "http://localhost:1234",
+        ANTHROPIC_AUTH_TOKEN: "lmstudio",
+        ANTHROPIC_MODEL: "${MODEL}",
+        ANTHROPIC_DEFAULT_HAIKU_MODEL: "${MODEL}",
+        ANTHROPIC_DEFAULT_SONNET_MODEL: "${MODEL}",
+        ANTHROPIC_DEFAULT_OPUS_MODEL: "${MODEL}",
+      },
+    },
+    category: "custom",
+    templateValues: {
+      MODEL: {
+        label: "Model",
+        placeholder: "qwen/qwen3-coder-30b",
+        defaultValue: "",
+        editorValue: "",
+      },
+    },
+  },
+  {
+    name: "Amazon Bedrock",
+    websiteUrl: "https://aws.amazon.com/bedrock/",
+    apiKeyUrl: "https://console.aws.amazon.com/bedrock/home#/api-keys",
+    settingsConfig: {
+      env: {
+        CLAUDE_CODE_USE_BEDROCK: "1",
+        AWS_REGION: "${AWS_REGION}",
+        AWS_BEARER_TOKEN_BEDROCK: "${AWS_BEARER_TOKEN_BEDROCK}",
+        ANTHROPIC_MODEL: "us.anthropic.claude-sonnet-4-5-20250929-v1:0",
+        ANTHROPIC_DEFAULT_HAIKU_MODEL:
+          "us.anthropic.claude-haiku-4-5-20251001-v1:0",
+        ANTHROPIC_DEFAULT_SONNET_MODEL:
+          "us.anthropic.claude-sonnet-4-5-20250929-v1:0",
+        ANTHROPIC_DEFAULT_OPUS_MODEL:
+          "us.anthropic.claude-opus-4-1-20250805-v1:0",
+      },
+    },
+    category: "official",
+    templateValues: {
+      AWS_REGION: {
+        label: "AWS Region",
+        placeholder: "us-east-1",
+        defaultValue: "us-east-1",
+        editorValue: "us-east-1",
+      },
+      AWS_BEARER_TOKEN_BEDROCK: {
+        label: "Bedrock API Key",
+        placeholder: "ABSK...",
+        defaultValue: "",
+        editorValue: "",
+      },
+    },
+    icon: "aws",
+    iconColor: "#FF9900",
+  },
+  {
+    name: "Google Vertex AI",
+    websiteUrl: "https://cloud.google.com/vertex-ai",
+    settingsConfig: {
+      env: {
+        CLAUDE_CODE_USE_VERTEX: "1",
+        CLOUD_ML_REGION: "${CLOUD_ML_REGION}",
+        ANTHROPIC_VERTEX_PROJECT_ID: "${ANTHROPIC_VERTEX_PROJECT_ID}",
+        ANTHROPIC_MODEL: "claude-sonnet-4-5@20250929",
+        ANTHROPIC_DEFAULT_HAIKU_MODEL: "claude-haiku-4-5@20251001",
+        ANTHROPIC_DEFAULT_SONNET_MODEL: "claude-sonnet-4-5@20250929",
+        ANTHROPIC_DEFAULT_OPUS_MODEL: "claude-opus-4-1@20250805",
+      },
+    },
+    category: "official",
+    templateValues: {
+      CLOUD_ML_REGION: {
+        label: "Region",
+        placeholder: "global",
+        defaultValue: "global",
+        editorValue: "global",
+      },
+      ANTHROPIC_VERTEX_PROJECT_ID: {
+        label: "GCP Project ID",
+        placeholder: "my-gcp-project",
+        defaultValue: "",
+        editorValue: "",
+      },
+    },
+    icon: "googlecloud",
+    iconColor: "#4285F4",
+  },
   {
     name: "Xiaomi MiMo",
     websiteUrl: "https://platform.xiaomimimo.com",
@@ -26,6 +26,7 @@ export interface RequestLog {
   latencyMs: number;
   firstTokenMs?: number;
   durationMs?: number;
+  outputTokensPerSecond?: number;
   statusCode: number;
   errorMessage?: string;
   createdAt: number;
@@ -76,6 +77,23 @@ export interface ProviderStats {
   totalCost: string;
   successRate: number;
   avgLatencyMs: number;
+  avgOutputTokensPerSecond?: number;
+}
+
+export interface LatencyPercentiles {
+  p50: number;
+  p95: number;
+  p99: number;
+}
+
+export interface ProviderLatencyPercentiles {
+  appType: string;
+  providerId: string;
+  providerName: string;
+  windowMinutes: number;
+  sampleCount: number;
+  latency: LatencyPercentiles;
+  firstToken?: LatencyPercentiles;
 }
 
 export interface ModelStats {
@@ -89,8 +107,12 @@ export interface ModelStats {
 export interface LogFilters {
   appType?: string;
   providerName?: string;
+  providerId?: string;
+  requestId?: string;
   model?: string;
   statusCode?: number;
+  statusMin?: number;
+  statusMax?: number;
   startDate?: number;
   endDate?: number;
 }